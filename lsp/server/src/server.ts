@@ -24,6 +24,9 @@ import {
 
 const rust = import('../pkg');
 
+// The diagnostics of open documents are kept between edits in one session.
+const session = rust.then(mm => new mm.DiagnosticSession());
+
 // Create a connection for the server, using Node's IPC as a transport.
 // Also include all preview / proposed LSP features.
 const connection = createConnection(ProposedFeatures.all);
@@ -203,9 +206,9 @@ async function validateAllTextDocuments(): Promise<void> {
 	}
 
 	// get diagnostics
-	const mm = await rust;
+	const diagnosticSession = await session;
 	try {
-		const result = mm.riscv_get_diagnostics(rvDocuments) as RVDiagnostic[];
+		const result = diagnosticSession.get_diagnostics(rvDocuments) as RVDiagnostic[];
 		for (const diag of result) {
 			connection.sendDiagnostics(diag);
 		}
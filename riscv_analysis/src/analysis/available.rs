@@ -7,8 +7,8 @@ use std::rc::Rc;
 
 use serde::{Deserialize, Serialize};
 
-use crate::cfg::AvailableValueMap;
-use crate::parser::{LabelString, RegSets};
+use crate::cfg::{AvailableValueMap, MathOp};
+use crate::parser::{IArithType, LabelString, RegSets, Xlen};
 use crate::parser::{ParserNode, Register};
 use crate::passes::{CfgError, GenerationPass};
 
@@ -27,14 +27,25 @@ use super::memory_location::MemoryLocation;
 #[derive(Deserialize, Serialize)]
 pub enum AvailableValue {
     /// A known constant value.
+    ///
+    /// Values are sign extended to 64 bits, so a value on RV32 is always
+    /// in the range of an `i32`.
     #[serde(rename = "c")]
-    Constant(i32),
+    Constant(i64),
     /// The address of some memory location.
     ///
     /// This is used when loading the address from a label. For example, using
     /// the `la` instruction to load the address of a label into a register.
     #[serde(rename = "a")]
     Address(LabelString),
+    /// The upper bits of the address of a label.
+    ///
+    /// This is used when the upper part of an address is loaded with a
+    /// relocation, like `lui a0, %hi(label)`. Adding the lower part of the
+    /// same address, like with `addi a0, a0, %lo(label)`, gives the address
+    /// of the label.
+    #[serde(rename = "ua")]
+    UpperAddress(LabelString),
     /// The value of a memory location at some offset.
     ///
     /// This is a copy of the actual bit of memory that lives at plus some offset.
@@ -109,16 +120,29 @@ impl GenerationPass for AvailableValuePass {
         // the values with the correct previous nodes are calculated.
         #[allow(clippy::mutable_key_type)]
         let mut visited = HashSet::new();
+        // Other roots than the program entry are also entered from outside
+        // of the program, where only the stack pointer and the return
+        // address are known
+        let roots = cfg
+            .roots()
+            .iter()
+            .filter(|root| !root.node().is_program_entry())
+            .map(|root| root.node().id())
+            .collect::<HashSet<_>>();
         while changed {
             changed = false;
             for node in cfg.iter() {
-                // in[n] = AND out[p] for all p in prev[n]
+                let is_root = roots.contains(&node.node().id());
+
+                // in[n] = AND out[p] for all p in prev[n], and the values on
+                // entry if n is a root
                 let in_reg_n = node
                     .prevs()
                     .clone()
                     .into_iter()
                     .filter(|x| visited.contains(x))
                     .map(|x| x.reg_values_out())
+                    .chain(is_root.then(|| RegSets::sp_ra().into_available_values()))
                     .reduce(|mut acc, x| {
                         acc &= &x;
                         acc
@@ -126,13 +150,15 @@ impl GenerationPass for AvailableValuePass {
                     .unwrap_or_default();
                 node.set_reg_values_in(in_reg_n);
 
-                // in_memory[n] = AND out_memory[p] for all p in prev[n]
+                // in_memory[n] = AND out_memory[p] for all p in prev[n], and
+                // nothing if n is a root
                 let in_memory_n = node
                     .prevs()
                     .clone()
                     .into_iter()
                     .filter(|x| visited.contains(x))
                     .map(|x| x.memory_values_out())
+                    .chain(is_root.then(AvailableValueMap::new))
                     .reduce(|mut acc, x| {
                         acc &= &x;
                         acc
@@ -182,7 +208,13 @@ impl GenerationPass for AvailableValuePass {
                     &mut out_memory_n,
                     &node.memory_values_in(),
                 );
-                rule_perform_math_ops(&node.node(), &mut out_reg_n, &node.reg_values_in());
+                rule_perform_math_ops(
+                    &node.node(),
+                    &mut out_reg_n,
+                    &node.reg_values_in(),
+                    cfg.xlen(),
+                );
+                rule_pair_relocations(&node.node(), &mut out_reg_n, &node.reg_values_in());
                 rule_known_values_to_stack(&node.node(), &mut out_memory_n, &node.reg_values_in());
                 // TODO stack reset?
 
@@ -220,10 +252,10 @@ fn rule_zero_to_const(
     for val in available_in {
         match val.1 {
             AvailableValue::OriginalRegisterWithScalar(r, i)
-            | AvailableValue::RegisterWithScalar(r, i) => {
-                if r == &Register::X0 {
-                    available_out.insert(*val.0, AvailableValue::Constant(*i));
-                }
+            | AvailableValue::RegisterWithScalar(r, i)
+                if r == &Register::X0 =>
+            {
+                available_out.insert(*val.0, AvailableValue::Constant(i64::from(*i)));
             }
             _ => {}
         }
@@ -231,10 +263,10 @@ fn rule_zero_to_const(
     for val in memory_in {
         match val.1 {
             AvailableValue::OriginalRegisterWithScalar(r, i)
-            | AvailableValue::RegisterWithScalar(r, i) => {
-                if r == &Register::X0 {
-                    memory_out.insert(val.0.clone(), AvailableValue::Constant(*i));
-                }
+            | AvailableValue::RegisterWithScalar(r, i)
+                if r == &Register::X0 =>
+            {
+                memory_out.insert(val.0.clone(), AvailableValue::Constant(i64::from(*i)));
             }
             _ => {}
         }
@@ -265,6 +297,8 @@ fn rule_expand_address_for_load(
                     store_reg.data,
                     AvailableValue::Memory(label.clone(), load.imm.data.0),
                 );
+            } else if let Some(label) = lower_address_of(node, available_in.get(&load.rs1.data)) {
+                available_out.insert(store_reg.data, AvailableValue::Memory(label, 0));
             }
         }
     }
@@ -274,42 +308,66 @@ fn rule_expand_address_for_load(
 ///
 /// If a register is stored to and we can determine the new value based on the
 /// values before and known math operations, store the new value in the register.
+///
+/// A result that overflows wraps around like it does in a register, which
+/// is 32 bits for RV32 and for the arithmetic on words of RV64, and 64 bits
+/// otherwise.
 fn rule_perform_math_ops(
     node: &ParserNode,
     available_out: &mut AvailableValueMap<Register>,
     available_in: &AvailableValueMap<Register>,
+    xlen: Xlen,
 ) {
     if let Some(reg) = node.stores_to() {
+        // The zero register is always known, which allows copies like
+        // `mv t0, ra` (`add t0, ra, zero`) to keep the value.
+        let value_of = |src: Register| {
+            if src == Register::X0 {
+                Some(AvailableValue::Constant(0))
+            } else {
+                available_in.get(&src).cloned()
+            }
+        };
+
         let lhs = match node {
-            ParserNode::Arith(expr) => available_in.get(&expr.rs1.data).cloned(),
-            ParserNode::IArith(expr) => available_in.get(&expr.rs1.data).cloned(),
+            ParserNode::Arith(expr) => value_of(expr.rs1.data),
+            ParserNode::IArith(expr) => value_of(expr.rs1.data),
             _ => None,
         };
 
         let rhs = match node {
-            ParserNode::Arith(expr) => available_in.get(&expr.rs2.data).cloned(),
-            ParserNode::IArith(expr) => Some(AvailableValue::Constant(expr.imm.data.0)),
+            ParserNode::Arith(expr) => value_of(expr.rs2.data),
+            ParserNode::IArith(expr) if expr.reloc.is_none() => {
+                Some(AvailableValue::Constant(expr.imm.data.0.into()))
+            }
             _ => None,
         };
 
         let result = match (lhs, rhs) {
-            (Some(AvailableValue::Constant(x)), Some(AvailableValue::Constant(y))) => node
-                .inst()
-                .math_op()
-                .map(|op| op.operate(x, y))
-                .map(AvailableValue::Constant),
+            (Some(AvailableValue::Constant(x)), Some(AvailableValue::Constant(y))) => {
+                let bits = node.inst().arith_bits(xlen);
+                node.inst()
+                    .math_op()
+                    .map(|op| AvailableValue::Constant(op.operate(x, y, bits)))
+            }
             (
                 Some(AvailableValue::OriginalRegisterWithScalar(new_reg, x)),
                 Some(AvailableValue::Constant(y)),
-            )
-            | (
-                Some(AvailableValue::Constant(x)),
-                Some(AvailableValue::OriginalRegisterWithScalar(new_reg, y)),
             ) => node
                 .inst()
                 .scalar_op()
-                .map(|op| op.operate(x, y))
+                .and_then(|op| scalar_offset(&op, x, y))
                 .map(|z| AvailableValue::OriginalRegisterWithScalar(new_reg, z)),
+            // A register subtracted from a constant, like `sub a0, zero, a1`,
+            // is negated, which cannot be represented.
+            (
+                Some(AvailableValue::Constant(x)),
+                Some(AvailableValue::OriginalRegisterWithScalar(new_reg, y)),
+            ) => match node.inst().scalar_op() {
+                Some(MathOp::Add) => scalar_offset(&MathOp::Add, y, x)
+                    .map(|z| AvailableValue::OriginalRegisterWithScalar(new_reg, z)),
+                _ => None,
+            },
             (_, _) => None,
         };
         if let Some(val) = result {
@@ -318,6 +376,43 @@ fn rule_perform_math_ops(
     }
 }
 
+/// Apply a constant to the scalar offset of a register, if the result can
+/// still be an offset.
+fn scalar_offset(op: &MathOp, offset: i32, constant: i64) -> Option<i32> {
+    i32::try_from(op.operate(offset.into(), constant, 64)).ok()
+}
+
+/// Rule that pairs the lower part of an address with its upper part.
+///
+/// If the lower part of the address of a label, like `%lo(label)`, is added
+/// to a register that holds the upper part of the same address, like after
+/// `lui a0, %hi(label)`, then the result is the address of the label.
+fn rule_pair_relocations(
+    node: &ParserNode,
+    available_out: &mut AvailableValueMap<Register>,
+    available_in: &AvailableValueMap<Register>,
+) {
+    if let ParserNode::IArith(expr) = node {
+        if expr.inst != IArithType::Addi {
+            return;
+        }
+        if let Some(label) = lower_address_of(node, available_in.get(&expr.rs1.data)) {
+            available_out.insert(expr.rd.data, AvailableValue::Address(label));
+        }
+    }
+}
+
+/// Get the label that the relocation of a node is the lower part of the
+/// address of, if `base` holds the upper part of the same address.
+fn lower_address_of(node: &ParserNode, base: Option<&AvailableValue>) -> Option<LabelString> {
+    let reloc = node.relocation().filter(|reloc| !reloc.kind.is_upper())?;
+    let label = reloc.address_of()?;
+    match base {
+        Some(AvailableValue::UpperAddress(upper)) if upper == label => Some(label.clone()),
+        _ => None,
+    }
+}
+
 /// Rule that restores guaranteed register values from the stack.
 ///
 /// If a register is stored to from a memory location that is the stack, and
@@ -356,7 +451,8 @@ fn rule_known_values_to_stack(
             if let Some(item) = available_in.get(&reg) {
                 match item {
                     AvailableValue::Constant(x) => {
-                        memory_out.insert(pos, AvailableValue::Constant(*x + off));
+                        memory_out
+                            .insert(pos, AvailableValue::Constant(x.wrapping_add(off.into())));
                     }
                     AvailableValue::OriginalRegisterWithScalar(reg2, off3) => {
                         memory_out.insert(
@@ -370,3 +466,61 @@ fn rule_known_values_to_stack(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::cfg::{Cfg, CfgNode};
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    /// Find the instruction with the given text, not the function entry
+    /// that shares its token.
+    fn find(cfg: &Cfg, text: &str) -> Rc<CfgNode> {
+        cfg.nodes()
+            .iter()
+            .find(|node| node.node().is_instruction() && node.node().token().text == text)
+            .map(Rc::clone)
+            .unwrap()
+    }
+
+    #[test]
+    fn zero_register_operands_are_constant() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                add    a0, zero, s1    \n\
+                sub    a2, s1, zero    \n\
+                sub    a3, zero, s1    \n\
+                li     t0, 5           \n\
+                sub    a4, t0, s1      \n\
+                add    a5, t0, s1      \n\
+                ret                    \n",
+        );
+        let value =
+            |text: &str, reg: Register| find(&cfg, text).reg_values_out().get(&reg).cloned();
+
+        let s1 = AvailableValue::OriginalRegisterWithScalar(Register::X9, 0);
+        assert_eq!(value("add a0 zero s1", Register::X10), Some(s1.clone()));
+        assert_eq!(value("sub a2 s1 zero", Register::X12), Some(s1));
+        // `zero - s1` and `5 - s1` negate s1, so they are not known
+        assert_eq!(value("sub a3 zero s1", Register::X13), None);
+        assert_eq!(value("sub a4 t0 s1", Register::X14), None);
+        assert_eq!(
+            value("add a5 t0 s1", Register::X15),
+            Some(AvailableValue::OriginalRegisterWithScalar(Register::X9, 5))
+        );
+    }
+}
@@ -5,6 +5,7 @@ impl std::fmt::Display for AvailableValue {
         match self {
             AvailableValue::Constant(v) => write!(f, "{v}"),
             AvailableValue::Address(a) => write!(f, "{a}"),
+            AvailableValue::UpperAddress(a) => write!(f, "%hi({a})"),
             AvailableValue::Memory(a, off) => write!(f, "{off}({a})"),
             AvailableValue::OriginalRegisterWithScalar(reg, off) => {
                 if off == &0 {
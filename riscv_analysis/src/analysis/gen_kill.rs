@@ -1,6 +1,6 @@
 use crate::{
     cfg::RegisterSet,
-    parser::{IArithType, ParserNode, RegSets, Register},
+    parser::{IArithType, ParserNode, RegSets, Register, UpperArithType},
 };
 
 use super::{AvailableValue, MemoryLocation};
@@ -21,14 +21,11 @@ impl ParserNode {
             RegisterSet::new()
         } else if self.is_function_entry() {
             RegSets::caller_saved()
-        } else if let Some(stored_reg) = self.stores_to().map(|x| x.data) {
-            if stored_reg == Register::X0 {
-                RegisterSet::new()
-            } else {
-                [stored_reg].into_iter().collect()
-            }
         } else {
-            RegisterSet::new()
+            self.write_registers()
+                .map(|x| x.data)
+                .collect::<RegisterSet>()
+                - Register::X0
         }
     }
 
@@ -37,12 +34,51 @@ impl ParserNode {
         let regs = if self.is_return() {
             RegSets::callee_saved()
         } else {
-            self.reads_from().iter().map(|x| x.data).collect()
+            self.read_registers().map(|x| x.data).collect()
         };
 
         regs - Register::X0
     }
 
+    /// Get the registers that this node may write to.
+    ///
+    /// This includes the registers that are written implicitly. A function
+    /// call may write to any caller-saved register and to `ra`, and an
+    /// `ecall` may write to any caller-saved register. Writes to the zero
+    /// register are not included, as they have no effect.
+    ///
+    /// These are computed from the node alone, so they are conservative.
+    /// Use the liveness information of a `CfgNode` for results that
+    /// account for the rest of the program.
+    #[must_use]
+    pub fn defs(&self) -> RegisterSet {
+        if self.is_ecall() {
+            RegSets::caller_saved()
+        } else {
+            self.kill_reg_value()
+        }
+    }
+
+    /// Get the registers that this node may read from.
+    ///
+    /// This includes the registers that are read implicitly. A function call
+    /// may read from any argument register, an `ecall` may read from any
+    /// argument register (including `a7`, which selects the call), and a
+    /// return reads the callee-saved registers, as their values are passed
+    /// back to the caller. Reads from the zero register are not included.
+    ///
+    /// These are computed from the node alone, so they are conservative.
+    /// For example, the arguments of a known `ecall` are found using the
+    /// available values of a `CfgNode`.
+    #[must_use]
+    pub fn uses(&self) -> RegisterSet {
+        if self.calls_to().is_some() || self.is_ecall() {
+            RegSets::argument()
+        } else {
+            self.gen_reg()
+        }
+    }
+
     #[must_use]
     pub fn gen_memory_value(&self) -> Option<(MemoryLocation, AvailableValue)> {
         match self {
@@ -69,20 +105,22 @@ impl ParserNode {
                 expr.rd.data,
                 AvailableValue::Address(expr.name.data.clone()),
             )),
+            // The offset of a relocation is not known
+            ParserNode::Load(expr) if expr.reloc.is_some() => None,
             ParserNode::Load(expr) => Some((
                 expr.rd.data,
                 AvailableValue::MemoryAtRegister(expr.rs1.data, expr.imm.data.0),
             )),
             ParserNode::IArith(expr) => {
-                if expr.rs1 == Register::X0 {
+                if expr.rs1 == Register::X0 && expr.reloc.is_none() {
                     match expr.inst.data {
                         IArithType::Addi
-                        | IArithType::Lui
                         | IArithType::Addiw
                         | IArithType::Xori
-                        | IArithType::Ori => {
-                            Some((expr.rd.data, AvailableValue::Constant(expr.imm.data.0)))
-                        }
+                        | IArithType::Ori => Some((
+                            expr.rd.data,
+                            AvailableValue::Constant(expr.imm.data.0.into()),
+                        )),
                         IArithType::Andi
                         | IArithType::Slli
                         | IArithType::Slliw
@@ -96,6 +134,18 @@ impl ParserNode {
                     None
                 }
             }
+            // The value of `auipc` depends on the address of the instruction,
+            // which is not known, unless it is the upper part of an address.
+            ParserNode::UpperArith(expr) => match (&expr.reloc, expr.inst.data) {
+                (Some(reloc), _) if reloc.kind.is_upper() => Some((
+                    expr.rd.data,
+                    AvailableValue::UpperAddress(reloc.symbol.data.clone()),
+                )),
+                (Some(_), _) | (None, UpperArithType::Auipc) => None,
+                (None, UpperArithType::Lui) => {
+                    Some((expr.rd.data, AvailableValue::Constant(expr.value().into())))
+                }
+            },
             ParserNode::Arith(expr) => {
                 if expr.rs1 == Register::X0 && expr.rs2 == Register::X0 {
                     Some((expr.rd.data, AvailableValue::Constant(0)))
@@ -107,3 +157,58 @@ impl ParserNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::RegisterSet;
+    use crate::parser::{ParserNode, RVStringParser, RegSets, Register};
+
+    fn parse_single(text: &str) -> ParserNode {
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        nodes[1].clone()
+    }
+
+    fn set(regs: &[Register]) -> RegisterSet {
+        regs.iter().copied().collect()
+    }
+
+    #[test]
+    fn defs_and_uses_of_instruction() {
+        let node = parse_single("add a0, a1, a2\n");
+        assert_eq!(node.defs(), set(&[Register::X10]));
+        assert_eq!(node.uses(), set(&[Register::X11, Register::X12]));
+
+        let node = parse_single("sw a0, 4(sp)\n");
+        assert_eq!(node.defs(), RegisterSet::new());
+        assert_eq!(node.uses(), set(&[Register::X2, Register::X10]));
+    }
+
+    #[test]
+    fn zero_register_is_ignored() {
+        let node = parse_single("add zero, zero, a0\n");
+        assert_eq!(node.defs(), RegisterSet::new());
+        assert_eq!(node.uses(), set(&[Register::X10]));
+    }
+
+    #[test]
+    fn defs_and_uses_of_call() {
+        let node = parse_single("jal fn_a\n");
+        assert_eq!(node.defs(), RegSets::caller_saved() | Register::X1);
+        assert_eq!(node.uses(), RegSets::argument());
+    }
+
+    #[test]
+    fn defs_and_uses_of_ecall() {
+        let node = parse_single("ecall\n");
+        assert_eq!(node.defs(), RegSets::caller_saved());
+        assert_eq!(node.uses(), RegSets::argument());
+    }
+
+    #[test]
+    fn uses_of_return() {
+        let node = parse_single("ret\n");
+        assert_eq!(node.defs(), RegisterSet::new());
+        assert_eq!(node.uses(), RegSets::callee_saved());
+    }
+}
@@ -8,6 +8,7 @@ pub struct LivenessPass;
 impl GenerationPass for LivenessPass {
     #[allow(clippy::too_many_lines)]
     fn run(cfg: &mut crate::cfg::Cfg) -> Result<(), Box<CfgError>> {
+        let graph = cfg.function_call_graph();
         let mut changed = true;
         #[allow(clippy::mutable_key_type)]
         let mut visited = HashSet::new();
@@ -15,16 +16,26 @@ impl GenerationPass for LivenessPass {
             changed = false;
             for node in cfg.iter().rev() {
                 // live_out[n] = U live_in[s] for all s in next[n]
+                // A tail call also passes arguments to the function entry
+                // that it jumps to, like a call does.
                 let live_out = node
                     .nexts()
                     .clone()
                     .into_iter()
-                    .map(|x| x.live_in())
+                    .map(|x| {
+                        if node.node().is_tail_call() && x.node().is_function_entry() {
+                            x.live_in() | (x.live_out() & RegSets::argument())
+                        } else {
+                            x.live_in()
+                        }
+                    })
                     .reduce(|acc, x| acc | x)
                     .unwrap_or_default();
                 node.set_live_out(live_out);
 
                 if let Some((func, _)) = node.calls_to(cfg) {
+                    let kill = graph.changed_by_call(&func);
+
                     // live_in[F_exit] = live_in[F_exit] U gen[F_exit] (live_out[n] AND u_def[F_exit])
                     // We take the union of the existing live_in to match multiple call sites
                     let func_exit_live_in = (node.live_out() & func.exit().u_def())
@@ -37,7 +48,7 @@ impl GenerationPass for LivenessPass {
                     }
 
                     // u_def[n] = (AND u_def[s] for all s in prev[n]) - kill[n] | (u_def[F_exit] AND return-registers)
-                    // kill[n] = caller-saved
+                    // kill[n] = caller-saved registers that F may change
                     // NOTE: we use the UDEF_f because the udefs are all "candidates"
                     // for returns. If one happens to be the return, we can be sure
                     // that it is always defined. Otherwise, it is an error becuase
@@ -53,12 +64,11 @@ impl GenerationPass for LivenessPass {
                         .map(|x| x.u_def())
                         .reduce(|acc, x| acc & x)
                         .unwrap_or_default()
-                        - RegSets::caller_saved())
+                        - kill)
                         | (func.exit().u_def() & RegSets::ret());
 
                     // live_in[n] = (live_in[F] & argument-registers) U (live_out[n] - kill[n])
-                    // kill[n] = caller-saved
-                    let live_in_temp = node.live_out() - RegSets::caller_saved();
+                    let live_in_temp = node.live_out() - kill;
                     let live_in = (func.entry().live_out() & RegSets::argument()) | live_in_temp;
 
                     if live_in != node.live_in() {
@@ -109,6 +119,18 @@ impl GenerationPass for LivenessPass {
                         .reduce(|acc, x| acc & x)
                         .unwrap_or_default();
 
+                    // A function in a library can be called from outside of
+                    // it, where the return values and the callee-saved
+                    // registers are used.
+                    if cfg.is_library() {
+                        let live_in =
+                            node.live_in() | (u_def & RegSets::ret()) | RegSets::callee_saved();
+                        if live_in != node.live_in() {
+                            changed = true;
+                            node.set_live_in(live_in);
+                        }
+                    }
+
                     if u_def != node.u_def() {
                         changed = true;
                         node.set_u_def(u_def);
@@ -157,6 +179,7 @@ impl GenerationPass for LivenessPass {
                 visited.insert(node);
             }
         }
+        cfg.set_liveness_computed();
         Ok(())
     }
 }
@@ -0,0 +1,559 @@
+use crate::cfg::Cfg;
+use crate::parser::{EmptyFileReader, RVParser};
+use crate::passes::{
+    dedup_diagnostics, DiagnosticItem, LintLevel, Manager, ManagerConfiguration, SeverityLevel,
+};
+use crate::reader::FileReader;
+
+/// A source of RISC-V assembly that can be analyzed.
+///
+/// This is implemented for a single string of source text, which cannot
+/// use `.include` directives, and for a pair of a file reader and the path
+/// of the base file, which follows `.include` directives through the reader.
+pub trait AnalysisSource {
+    type Reader: FileReader + Clone;
+
+    /// Split the source into a file reader and the path of the base file.
+    fn into_reader(self) -> (Self::Reader, String);
+}
+
+impl AnalysisSource for &str {
+    type Reader = EmptyFileReader;
+
+    fn into_reader(self) -> (Self::Reader, String) {
+        (
+            EmptyFileReader::new(self),
+            EmptyFileReader::get_file_path().to_owned(),
+        )
+    }
+}
+
+impl<T: FileReader + Clone> AnalysisSource for (T, &str) {
+    type Reader = T;
+
+    fn into_reader(self) -> (Self::Reader, String) {
+        (self.0, self.1.to_owned())
+    }
+}
+
+/// Summary counts of a single analysis run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AnalysisStats {
+    /// Number of instructions that were parsed.
+    pub instructions: usize,
+    /// Number of functions that were found in the CFG.
+    pub functions: usize,
+    /// Number of error-level diagnostics.
+    pub errors: usize,
+    /// Number of warning-level diagnostics.
+    pub warnings: usize,
+}
+
+/// The result of analyzing a program with [`analyze`].
+pub struct AnalysisResult<T: FileReader + Clone> {
+    /// All parse errors, CFG errors and lints, sorted by location.
+    pub diagnostics: Vec<DiagnosticItem>,
+    /// The fully analyzed CFG, if it could be built.
+    pub cfg: Option<Cfg>,
+    /// Summary counts of the analysis.
+    pub stats: AnalysisStats,
+    /// The file reader used for the analysis.
+    ///
+    /// This can be used to look up the file names and text of the files
+    /// that the diagnostics refer to.
+    pub reader: T,
+}
+
+/// Parse and analyze a program in a single call.
+///
+/// This runs every step of the analysis: parsing (including any
+/// `.include`d files), building the CFG, running the generation passes
+/// and running the lints. Parse errors, CFG errors and lints are all
+/// reported as diagnostics.
+///
+/// ```
+/// use riscv_analysis::analyze;
+/// use riscv_analysis::passes::ManagerConfiguration;
+///
+/// let result = analyze("main:\n    addi a0, a0, 1\n    ret\n", &ManagerConfiguration::default());
+/// assert_eq!(result.diagnostics.len(), 1);
+/// assert_eq!(result.diagnostics[0].title, "Unused value");
+/// assert_eq!(result.stats.instructions, 2);
+/// assert!(result.cfg.is_some());
+/// ```
+pub fn analyze<S: AnalysisSource>(
+    source: S,
+    config: &ManagerConfiguration,
+) -> AnalysisResult<S::Reader> {
+    let (reader, base) = source.into_reader();
+    let mut parser = RVParser::new(reader);
+    let (diagnostics, cfg, stats) = run_analysis(&mut parser, &base, config);
+    AnalysisResult {
+        diagnostics,
+        cfg,
+        stats,
+        reader: parser.reader,
+    }
+}
+
+/// Analyze a program with a parser that is kept between analyses.
+///
+/// This is the same as [`analyze`], but the parser is not dropped. An
+/// [incremental](RVParser::set_incremental) parser only reads the files that
+/// changed since it last read them, which makes repeated analyses of the
+/// same files faster. The returned reader is a copy of the parser's reader.
+pub fn analyze_with_parser<T: FileReader + Clone>(
+    parser: &mut RVParser<T>,
+    base: &str,
+    config: &ManagerConfiguration,
+) -> AnalysisResult<T> {
+    let (diagnostics, cfg, stats) = run_analysis(parser, base, config);
+    AnalysisResult {
+        diagnostics,
+        cfg,
+        stats,
+        reader: parser.reader.clone(),
+    }
+}
+
+/// Run every step of the analysis with a parser.
+fn run_analysis<T: FileReader + Clone>(
+    parser: &mut RVParser<T>,
+    base: &str,
+    config: &ManagerConfiguration,
+) -> (Vec<DiagnosticItem>, Option<Cfg>, AnalysisStats) {
+    parser.set_align_mode(config.align_mode);
+
+    let (nodes, parse_errors) = parser.parse_from_file(base, false);
+    let mut diagnostics = parse_errors
+        .into_iter()
+        .map(DiagnosticItem::from)
+        .collect::<Vec<_>>();
+
+    let mut stats = AnalysisStats {
+        instructions: nodes.iter().filter(|node| node.is_instruction()).count(),
+        ..Default::default()
+    };
+
+    let cfg = match Manager::gen_full_cfg_with_config(nodes, config) {
+        Ok(cfg) => {
+            if config.run_lints {
+                let mut errors = Vec::new();
+                Manager::run_diagnostics(&cfg, config, &mut errors);
+                diagnostics.extend(errors.into_iter().map(|error| {
+                    let level = config
+                        .lint_level(error.name())
+                        .and_then(LintLevel::severity);
+                    let mut diagnostic = DiagnosticItem::from(error);
+                    if let Some(level) = level {
+                        diagnostic.level = level;
+                    }
+                    diagnostic
+                }));
+            }
+            stats.functions = cfg.functions().len();
+            Some(cfg)
+        }
+        Err(err) => {
+            diagnostics.push(DiagnosticItem::from(*err));
+            None
+        }
+    };
+
+    if config.dedup_diagnostics {
+        diagnostics = dedup_diagnostics(diagnostics);
+    } else {
+        diagnostics.sort();
+    }
+    for diagnostic in &diagnostics {
+        match diagnostic.level {
+            SeverityLevel::Error => stats.errors += 1,
+            SeverityLevel::Warning => stats.warnings += 1,
+            SeverityLevel::Information | SeverityLevel::Hint => {}
+        }
+    }
+
+    (diagnostics, cfg, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::analysis::AvailableValue;
+    use crate::parser::Register;
+    use crate::passes::{CustomDiagnostic, LintError, LintPass};
+
+    #[test]
+    fn can_analyze_text() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                jal    fn_a            \n\
+                ret                    \n\
+            fn_a:                      \n\
+                li     a0, 2           \n\
+                ret                    \n";
+
+        let result = analyze(input, &ManagerConfiguration::default());
+        assert_eq!(result.diagnostics.len(), 2);
+        assert!(result.diagnostics.iter().all(|d| d.title == "Unused value"));
+        assert_eq!(result.stats.instructions, 5);
+        assert_eq!(result.stats.functions, 1);
+        assert_eq!(result.stats.errors, 0);
+        assert!(result.cfg.is_some());
+    }
+
+    #[test]
+    fn can_analyze_with_file_reader() {
+        let reader = EmptyFileReader::new("main:\n    addi a0, a0, 1\n    ret\n");
+        let result = analyze(
+            (reader, EmptyFileReader::get_file_path()),
+            &ManagerConfiguration::default(),
+        );
+        assert_eq!(result.diagnostics.len(), 1);
+        let file = result.diagnostics[0].file;
+        assert_eq!(
+            result.reader.get_filename(file).as_deref(),
+            Some(EmptyFileReader::get_file_path())
+        );
+    }
+
+    #[test]
+    fn parse_errors_and_lints_are_reported_together() {
+        let input = "\
+            main:                      \n\
+                addi   a0, a0, 1       \n\
+                jall                   \n\
+                ret                    \n";
+
+        let result = analyze(input, &ManagerConfiguration::default());
+        assert_eq!(result.diagnostics.len(), 2);
+        assert_eq!(result.stats.errors, 1);
+        assert_eq!(result.stats.warnings, 1);
+        // Diagnostics are sorted by location
+        assert!(result.diagnostics[0].range.start.line < result.diagnostics[1].range.start.line);
+    }
+
+    #[test]
+    fn cfg_errors_are_reported() {
+        let result = analyze("a:\nnop\na:\nnop\n", &ManagerConfiguration::default());
+        assert!(result.cfg.is_none());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.stats.errors, 1);
+    }
+
+    #[test]
+    fn undefined_labels_do_not_stop_analysis() {
+        let input = "\
+            main:                      \n\
+                bnez   a0, missing     \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let result = analyze(input, &ManagerConfiguration::default());
+        assert!(result.cfg.is_some());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].code, "undefined-label");
+    }
+
+    #[test]
+    fn extern_call_target_is_not_undefined() {
+        let input = "\
+            .extern print_int          \n\
+            main:                      \n\
+                li     a0, 4           \n\
+                call   print_int       \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let result = analyze(input, &ManagerConfiguration::default());
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code != "undefined-label"));
+    }
+
+    #[test]
+    fn lints_can_be_disabled() {
+        let config = ManagerConfiguration {
+            run_lints: false,
+            ..Default::default()
+        };
+        let result = analyze("main:\n    addi a0, a0, 1\n    ret\n", &config);
+        assert!(result.diagnostics.is_empty());
+        assert!(result.cfg.is_some());
+    }
+
+    #[test]
+    fn function_size_can_be_limited() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                ret                    \n\
+            fn_a:                      \n\
+                li     a0, 1           \n\
+                addi   a0, a0, 1       \n\
+                ret                    \n";
+
+        let is_size_lint = |d: &&DiagnosticItem| d.title.starts_with("Function");
+        let result = analyze(input, &ManagerConfiguration::default());
+        assert_eq!(result.diagnostics.iter().filter(is_size_lint).count(), 0);
+
+        let config = ManagerConfiguration {
+            max_function_instructions: Some(2),
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        let lints = result.diagnostics.iter().filter(is_size_lint).collect::<Vec<_>>();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].title, "Function fn_a has 3 instructions");
+    }
+
+    /// A lint that reports every `nop`, with the value of `a0` before it.
+    struct NopCheck;
+    impl LintPass for NopCheck {
+        fn lints(&self) -> &'static [&'static str] {
+            &["no-nop"]
+        }
+
+        fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+            for node in cfg {
+                if node.node().token().text != "nop" {
+                    continue;
+                }
+                let value = match node.reg_values_in().get(&Register::X10) {
+                    Some(AvailableValue::Constant(value)) => value.to_string(),
+                    _ => "unknown".to_string(),
+                };
+                errors.push(LintError::Custom(CustomDiagnostic {
+                    name: "no-nop",
+                    node: node.node(),
+                    level: SeverityLevel::Warning,
+                    title: "Unnecessary nop".to_string(),
+                    description: format!("a0 is {value}"),
+                }));
+            }
+        }
+    }
+
+    #[test]
+    fn custom_lints_are_run() {
+        let input = "\
+            main:                      \n\
+                li     a0, 4           \n\
+                nop                    \n\
+                addi   a0, a0, 1       \n\
+                nop                    \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+        // A `nop` writes to the zero register, which is also reported at
+        // the same location
+        let mut config = ManagerConfiguration {
+            custom_lints: vec![Rc::new(NopCheck)],
+            dedup_diagnostics: false,
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        let nops = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == "no-nop")
+            .collect::<Vec<_>>();
+        assert_eq!(nops.len(), 2);
+        assert_eq!(nops[0].title, "Unnecessary nop");
+        assert_eq!(nops[0].level, SeverityLevel::Warning);
+        assert_eq!(nops[0].range.start.line, 2);
+        // The lint sees the values found by the analysis
+        assert_eq!(nops[0].long_description, "a0 is 4");
+        assert_eq!(nops[1].long_description, "a0 is 5");
+
+        config
+            .lint_levels
+            .insert("no-nop".to_string(), LintLevel::Off);
+        let result = analyze(input, &config);
+        assert!(result.diagnostics.iter().all(|d| d.code != "no-nop"));
+    }
+
+    #[test]
+    fn same_issue_is_reported_once() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                jal    fn_b            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+            fn_b:                      \n\
+                li     s0, 1           \n\
+                ret                    \n";
+
+        // Both functions overwrite s0 at the same line, which is one issue,
+        // and the lost value of s0 at the same line is another
+        let result = analyze(input, &ManagerConfiguration::default());
+        let codes = result
+            .diagnostics
+            .iter()
+            .map(|d| d.code.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec!["lost-register-value", "overwrite-callee-saved-register"]);
+        assert_eq!(result.stats.errors, 1);
+        assert_eq!(result.stats.warnings, 1);
+
+        let config = ManagerConfiguration {
+            dedup_diagnostics: false,
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        assert_eq!(result.diagnostics.len(), 3);
+    }
+
+    #[test]
+    fn library_functions_are_checked_on_their_own() {
+        let input = "\
+            square:                    \n\
+                mul    a0, a0, a0      \n\
+                ret                    \n\
+            sum_squares:               \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                sw     s0, 4(sp)       \n\
+                mv     s0, a1          \n\
+                jal    square          \n\
+                mv     t0, a0          \n\
+                mv     a0, s0          \n\
+                mv     s0, t0          \n\
+                jal    square          \n\
+                add    a0, a0, s0      \n\
+                lw     ra, 0(sp)       \n\
+                lw     s0, 4(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n";
+
+        let result = analyze(input, &ManagerConfiguration::default());
+        assert!(!result.diagnostics.is_empty());
+
+        let config = ManagerConfiguration {
+            library: true,
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.stats.functions, 2);
+    }
+
+    #[test]
+    fn library_function_errors_are_reported() {
+        let input = "\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                ret                    \n\
+            fn_b:                      \n\
+                li     s0, 1           \n\
+                mv     a0, s0          \n\
+                ret                    \n";
+
+        let config = ManagerConfiguration {
+            library: true,
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.title == "Overwriting callee-saved register"));
+        assert_eq!(result.stats.functions, 2);
+    }
+
+    #[test]
+    fn library_label_that_never_returns_is_not_a_function() {
+        let input = "\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                ret                    \n\
+            hang:                      \n\
+                li     a0, 1           \n\
+            loop:                      \n\
+                j      loop            \n";
+
+        let config = ManagerConfiguration {
+            library: true,
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        assert!(result.cfg.is_some());
+        assert_eq!(result.stats.functions, 1);
+    }
+
+    #[test]
+    fn lint_can_be_turned_off() {
+        let input = "main:\n    addi a0, a0, 1\n    ret\n";
+        let config = ManagerConfiguration {
+            lint_levels: [("dead-assignment".to_string(), LintLevel::Off)].into(),
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.stats.warnings, 0);
+    }
+
+    #[test]
+    fn lint_level_can_be_changed() {
+        let input = "main:\n    addi a0, a0, 1\n    ret\n";
+        let config = ManagerConfiguration {
+            lint_levels: [("dead-assignment".to_string(), LintLevel::Error)].into(),
+            ..Default::default()
+        };
+        let result = analyze(input, &config);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert!(matches!(result.diagnostics[0].level, SeverityLevel::Error));
+        assert_eq!(result.stats.errors, 1);
+        assert_eq!(result.stats.warnings, 0);
+    }
+
+    #[test]
+    fn emitted_diagnostics_are_in_rule_catalog() {
+        let inputs = [
+            "\
+            main:                      \n\
+                li     t1, 5           \n\
+                add    x0, a0, t1      \n\
+                mv     t2, t1          \n\
+                mv     t1, t2          \n\
+                sub    t3, t3, t3      \n\
+                beq    t1, t2, next    \n\
+            next:                      \n\
+                li     a7, 99          \n\
+                ecall                  \n\
+                li     a0, 1           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -4      \n\
+                sw     s0, 0(sp)       \n\
+                lw     s0, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                lw     t0, -4(sp)      \n\
+                li     s1, 1           \n\
+                li     ra, 0           \n\
+                ret                    \n",
+            "main:\n    addi a0, a0, 1\n    jall\n    ret\n",
+            "j missing\n",
+        ];
+        let config = ManagerConfiguration {
+            dedup_diagnostics: false,
+            ..Default::default()
+        };
+        let catalog = Manager::rule_catalog();
+        for input in inputs {
+            let result = analyze(input, &config);
+            for diagnostic in result.diagnostics {
+                let rule = catalog.iter().find(|rule| rule.id == diagnostic.code);
+                let rule = rule.unwrap_or_else(|| panic!("no rule for {}", diagnostic.code));
+                assert!(diagnostic.level <= rule.level);
+            }
+        }
+    }
+}
@@ -93,6 +93,41 @@ impl AvailableValueMap<Register> {
         None
     }
 
+    /// Get the value of a register if it is a known constant.
+    ///
+    /// Constants from `li` and `lui` with `addi` are already folded by the
+    /// available value analysis. A register that holds a copy of another
+    /// register plus some offset, like after `mv` or `addi rd, rs, 0`, is
+    /// resolved through the value of the other register.
+    ///
+    /// A register that holds different values on different paths has no
+    /// available value, so it is not a known constant.
+    #[must_use]
+    pub fn resolved_constant(&self, reg: Register) -> Option<i64> {
+        let mut offset = 0_i32;
+        let mut current = reg;
+        let mut seen = RegisterSet::new();
+        loop {
+            if current == Register::X0 {
+                return Some(i64::from(offset));
+            }
+            if seen.contains(&current) {
+                return None;
+            }
+            seen.set_register(&current);
+            match self.get(&current)? {
+                AvailableValue::Constant(value) => {
+                    return Some(value.wrapping_add(offset.into()));
+                }
+                AvailableValue::RegisterWithScalar(source, scalar) => {
+                    offset = offset.wrapping_add(*scalar);
+                    current = *source;
+                }
+                _ => return None,
+            }
+        }
+    }
+
     /// Determine if a register has the same value it had at the beginning of
     /// a function.
     #[must_use]
@@ -214,6 +249,8 @@ impl AvailableValueMap<Register> {
 mod test {
 
     use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
 
     #[test]
     fn can_create_an_empty_map() {
@@ -249,6 +286,94 @@ mod test {
         assert!(map_iter.next().is_none(), "Map should be empty");
     }
 
+    #[test]
+    fn resolved_constant_follows_copies() {
+        let mut map = AvailableValueMap::<Register>::new();
+        map.insert(Register::X5, AvailableValue::Constant(3));
+        map.insert(
+            Register::X6,
+            AvailableValue::RegisterWithScalar(Register::X5, 4),
+        );
+        map.insert(
+            Register::X7,
+            AvailableValue::RegisterWithScalar(Register::X6, 0),
+        );
+        map.insert(
+            Register::X28,
+            AvailableValue::RegisterWithScalar(Register::X0, 9),
+        );
+        map.insert(
+            Register::X29,
+            AvailableValue::OriginalRegisterWithScalar(Register::X29, 0),
+        );
+        map.insert(
+            Register::X30,
+            AvailableValue::RegisterWithScalar(Register::X31, 0),
+        );
+        map.insert(
+            Register::X31,
+            AvailableValue::RegisterWithScalar(Register::X30, 0),
+        );
+
+        assert_eq!(map.resolved_constant(Register::X5), Some(3));
+        assert_eq!(map.resolved_constant(Register::X7), Some(7));
+        assert_eq!(map.resolved_constant(Register::X28), Some(9));
+        assert_eq!(map.resolved_constant(Register::X0), Some(0));
+        assert_eq!(map.resolved_constant(Register::X29), None);
+        assert_eq!(map.resolved_constant(Register::X30), None);
+        assert_eq!(map.resolved_constant(Register::X10), None);
+    }
+
+    fn values_at_ecall(input: &str) -> AvailableValueMap<Register> {
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let ecall = cfg.iter().find(|node| node.node().is_ecall()).unwrap();
+        ecall.reg_values_in()
+    }
+
+    #[test]
+    fn resolved_constant_of_folded_immediates() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0x12345678  \n\
+                mv     a0, t0          \n\
+                lui    t1, 0x1         \n\
+                addi   t1, t1, -1      \n\
+                addi   a7, t1, 0       \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let values = values_at_ecall(input);
+        assert_eq!(values.resolved_constant(Register::X10), Some(0x1234_5678));
+        assert_eq!(values.resolved_constant(Register::X17), Some(0xfff));
+    }
+
+    #[test]
+    fn resolved_constant_after_merge() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                beqz   t0, other       \n\
+                li     a7, 1           \n\
+                j      end             \n\
+            other:                     \n\
+                li     a7, 11          \n\
+            end:                       \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let values = values_at_ecall(input);
+        assert_eq!(values.resolved_constant(Register::X10), Some(1));
+        assert_eq!(values.resolved_constant(Register::X17), None);
+    }
+
     #[test]
     fn can_serialize_a_basic_map() {
         let mut map = AvailableValueMap::<Register>::new();
@@ -0,0 +1,493 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::rc::Rc;
+
+use itertools::Itertools;
+use uuid::Uuid;
+
+use crate::parser::{LabelString, ParserNode, RegSets, Register, With};
+
+use super::{escape_dot, Cfg, Function, RegisterSet};
+
+/// The calls that a function makes, see [`Cfg::function_call_graph`].
+#[derive(Debug, Clone, Default)]
+pub struct FunctionCalls {
+    /// The functions that are called, in order of name.
+    pub callees: Vec<Rc<Function>>,
+    /// Calls to labels that do not start a function, like a label of data.
+    pub unresolved: Vec<With<LabelString>>,
+    /// Whether a call through a register goes to a target that is not known.
+    pub indirect: bool,
+}
+
+/// The graph of which functions call which other functions.
+///
+/// A call is a `jal` or `call` to a label, a `tail` to a function, or a call
+/// through a register whose target is known. Code outside of every function
+/// is not part of the graph.
+pub struct CallGraph {
+    functions: Vec<(Rc<Function>, FunctionCalls)>,
+    /// For each function, the functions that it can reach through one or
+    /// more calls, by index.
+    reachable: Vec<HashSet<usize>>,
+}
+
+impl CallGraph {
+    /// Create the call graph of a CFG.
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        let funcs = cfg.unique_functions();
+        let index = funcs
+            .iter()
+            .enumerate()
+            .map(|(i, func)| (func.entry().node().id(), i))
+            .collect::<HashMap<Uuid, usize>>();
+
+        let functions = funcs
+            .into_iter()
+            .map(|func| {
+                let calls = Self::calls_of(cfg, &func);
+                (func, calls)
+            })
+            .collect::<Vec<_>>();
+
+        let edges = functions
+            .iter()
+            .map(|(_, calls)| {
+                calls
+                    .callees
+                    .iter()
+                    .filter_map(|callee| index.get(&callee.entry().node().id()).copied())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let reachable = (0..functions.len())
+            .map(|start| {
+                let mut reached = HashSet::new();
+                let mut stack = edges.get(start).cloned().unwrap_or_default();
+                while let Some(i) = stack.pop() {
+                    if reached.insert(i) {
+                        stack.extend(edges.get(i).into_iter().flatten());
+                    }
+                }
+                reached
+            })
+            .collect();
+
+        CallGraph {
+            functions,
+            reachable,
+        }
+    }
+
+    /// Find the calls made by the nodes of a function.
+    fn calls_of(cfg: &Cfg, func: &Function) -> FunctionCalls {
+        let functions = cfg.functions();
+        let mut calls = FunctionCalls::default();
+        for node in func.nodes().iter() {
+            let parser_node = node.node();
+            if let Some(name) = parser_node.calls_to() {
+                match functions.get(&name) {
+                    Some(callee) => calls.callees.push(Rc::clone(callee)),
+                    None => calls.unresolved.push(name),
+                }
+            } else if parser_node.is_tail_call() {
+                if let Some(callee) = parser_node.jumps_to().and_then(|name| functions.get(&name)) {
+                    calls.callees.push(Rc::clone(callee));
+                }
+            } else if let ParserNode::JumpLinkR(jump) = &parser_node {
+                if jump.rd.data != Register::X1 || !parser_node.is_indirect_jump() {
+                    continue;
+                }
+                let Some(targets) = cfg.indirect_jump_targets(node) else {
+                    calls.indirect = true;
+                    continue;
+                };
+                for target in targets {
+                    let name = With::new(target, jump.rs1.info());
+                    match functions.get(&name) {
+                        Some(callee) => calls.callees.push(Rc::clone(callee)),
+                        None => calls.unresolved.push(name),
+                    }
+                }
+            }
+        }
+
+        calls.callees = calls
+            .callees
+            .into_iter()
+            .unique_by(|callee| callee.entry().node().id())
+            .sorted_by_key(|callee| callee.name().0)
+            .collect();
+        calls.unresolved = calls
+            .unresolved
+            .into_iter()
+            .unique_by(|name| name.data.clone())
+            .sorted_by_key(|name| name.data.0.clone())
+            .collect();
+        calls
+    }
+
+    /// Get the functions of the graph, in order of name.
+    pub fn functions(&self) -> impl Iterator<Item = &Rc<Function>> {
+        self.functions.iter().map(|(func, _)| func)
+    }
+
+    /// Get the calls that a function makes.
+    ///
+    /// Returns `None` if the function is not part of the graph.
+    #[must_use]
+    pub fn calls(&self, func: &Function) -> Option<&FunctionCalls> {
+        self.index_of(func)
+            .and_then(|i| self.functions.get(i))
+            .map(|(_, calls)| calls)
+    }
+
+    /// Check if a function can call itself, either directly or through
+    /// other functions.
+    #[must_use]
+    pub fn is_recursive(&self, func: &Function) -> bool {
+        self.index_of(func).is_some_and(|i| {
+            self.reachable
+                .get(i)
+                .is_some_and(|reached| reached.contains(&i))
+        })
+    }
+
+    /// Get the registers that a call to a function may write, including the
+    /// ones written by the functions that it calls.
+    ///
+    /// If the function, or a function that it calls, makes a call whose
+    /// target is not known, every caller-saved register may be written.
+    #[must_use]
+    pub fn clobbered_registers(&self, func: &Function) -> RegisterSet {
+        let Some(i) = self.index_of(func) else {
+            return RegSets::caller_saved();
+        };
+        let mut clobbered = RegisterSet::new();
+        let reached = self.reachable.get(i).into_iter().flatten();
+        for (callee, calls) in std::iter::once(&i)
+            .chain(reached)
+            .filter_map(|j| self.functions.get(*j))
+        {
+            if calls.indirect || !calls.unresolved.is_empty() {
+                return RegSets::caller_saved();
+            }
+            clobbered |= *callee.defs();
+            // An ecall can set the return registers
+            if callee.nodes().iter().any(|node| node.node().is_ecall()) {
+                clobbered |= RegSets::ret();
+            }
+        }
+        clobbered - Register::X0
+    }
+
+    /// Get the caller-saved registers that a call to a function may change.
+    ///
+    /// A temporary register keeps its value across the call if the function
+    /// is known not to write to it, see [`CallGraph::clobbered_registers`].
+    /// The argument registers always change, as they hold the return values.
+    #[must_use]
+    pub fn changed_by_call(&self, func: &Function) -> RegisterSet {
+        RegSets::argument() | (self.clobbered_registers(func) & RegSets::temporary())
+    }
+
+    /// Get the groups of functions that call each other in a cycle.
+    ///
+    /// A function that calls itself is a group on its own. Each group is in
+    /// order of name, and the groups are in order of their first function.
+    #[must_use]
+    pub fn cycles(&self) -> Vec<Vec<Rc<Function>>> {
+        let mut grouped = HashSet::new();
+        let mut cycles = Vec::new();
+        for (i, reached) in self.reachable.iter().enumerate() {
+            if !reached.contains(&i) || grouped.contains(&i) {
+                continue;
+            }
+            let group = reached
+                .iter()
+                .copied()
+                .filter(|j| self.reachable.get(*j).is_some_and(|back| back.contains(&i)))
+                .sorted()
+                .collect::<Vec<_>>();
+            grouped.extend(group.iter().copied());
+            cycles.push(
+                group
+                    .into_iter()
+                    .filter_map(|j| self.functions.get(j).map(|(func, _)| Rc::clone(func)))
+                    .collect(),
+            );
+        }
+        cycles
+    }
+
+    /// Export the call graph as a Graphviz DOT graph.
+    ///
+    /// Each function is drawn as one box, with an edge to each function that
+    /// it calls. Functions that can call themselves are filled in orange.
+    /// Calls to labels that are not functions are drawn as dashed edges to a
+    /// dashed box, and calls through a register with an unknown target are
+    /// drawn as dashed edges to a `?` node.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        self.to_string()
+    }
+
+    fn index_of(&self, func: &Function) -> Option<usize> {
+        let id = func.entry().node().id();
+        self.functions
+            .iter()
+            .position(|(other, _)| other.entry().node().id() == id)
+    }
+}
+
+impl Display for CallGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "digraph calls {{")?;
+        writeln!(f, "    node [shape=box, fontname=monospace];")?;
+        for (i, (func, _)) in self.functions.iter().enumerate() {
+            let name = escape_dot(&func.name().0);
+            let style = if self.is_recursive(func) {
+                ", style=filled, fillcolor=orange"
+            } else {
+                ""
+            };
+            writeln!(f, "    f{i} [label=\"{name}\"{style}];")?;
+        }
+
+        let unresolved = self
+            .functions
+            .iter()
+            .flat_map(|(_, calls)| calls.unresolved.iter().map(|name| name.data.0.clone()))
+            .unique()
+            .sorted()
+            .collect::<Vec<_>>();
+        for (i, name) in unresolved.iter().enumerate() {
+            let name = escape_dot(name);
+            writeln!(f, "    u{i} [label=\"{name}\", style=dashed];")?;
+        }
+        if self.functions.iter().any(|(_, calls)| calls.indirect) {
+            writeln!(f, "    unknown [label=\"?\", shape=circle, style=dashed];")?;
+        }
+
+        for (i, (_, calls)) in self.functions.iter().enumerate() {
+            for callee in &calls.callees {
+                if let Some(j) = self.index_of(callee) {
+                    writeln!(f, "    f{i} -> f{j};")?;
+                }
+            }
+            for name in &calls.unresolved {
+                if let Some(j) = unresolved.iter().position(|other| *other == name.data.0) {
+                    writeln!(f, "    f{i} -> u{j} [style=dashed];")?;
+                }
+            }
+            if calls.indirect {
+                writeln!(f, "    f{i} -> unknown [style=dashed];")?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl Cfg {
+    /// Get the graph of which functions call which other functions.
+    #[must_use]
+    pub fn function_call_graph(&self) -> CallGraph {
+        CallGraph::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    fn names(funcs: &[Rc<Function>]) -> Vec<String> {
+        funcs.iter().map(|func| func.name().0).collect()
+    }
+
+    fn find(graph: &CallGraph, name: &str) -> Rc<Function> {
+        graph
+            .functions()
+            .find(|func| func.name().0 == name)
+            .map(Rc::clone)
+            .unwrap()
+    }
+
+    #[test]
+    fn mutual_recursion_is_a_cycle() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a0, 5           \n\
+                jal    is_even         \n\
+                jal    helper          \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            is_even:                   \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                beqz   a0, even_done   \n\
+                addi   a0, a0, -1      \n\
+                jal    is_odd          \n\
+            even_done:                 \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n\
+            is_odd:                    \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                beqz   a0, odd_done    \n\
+                addi   a0, a0, -1      \n\
+                jal    is_even         \n\
+            odd_done:                  \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n\
+            helper:                    \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                jal    is_odd          \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n",
+        );
+        let graph = cfg.function_call_graph();
+        assert_eq!(
+            names(&graph.functions().cloned().collect::<Vec<_>>()),
+            vec!["helper", "is_even", "is_odd"]
+        );
+
+        let even = find(&graph, "is_even");
+        let odd = find(&graph, "is_odd");
+        let helper = find(&graph, "helper");
+        assert_eq!(names(&graph.calls(&even).unwrap().callees), vec!["is_odd"]);
+        assert_eq!(names(&graph.calls(&odd).unwrap().callees), vec!["is_even"]);
+        assert!(graph.is_recursive(&even));
+        assert!(graph.is_recursive(&odd));
+        assert!(!graph.is_recursive(&helper));
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(names(&cycles[0]), vec!["is_even", "is_odd"]);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph calls {"));
+        assert!(dot.contains("f1 [label=\"is_even\", style=filled, fillcolor=orange];"));
+        assert!(dot.contains("f0 -> f2;"));
+        assert!(dot.contains("f1 -> f2;"));
+        assert!(dot.contains("f2 -> f1;"));
+    }
+
+    #[test]
+    fn direct_recursion_is_a_cycle() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a0, 5           \n\
+                jal    count           \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            count:                     \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                beqz   a0, done        \n\
+                addi   a0, a0, -1      \n\
+                jal    count           \n\
+            done:                      \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n",
+        );
+        let graph = cfg.function_call_graph();
+        let count = find(&graph, "count");
+        assert!(graph.is_recursive(&count));
+        assert_eq!(names(&graph.calls(&count).unwrap().callees), vec!["count"]);
+        assert_eq!(graph.cycles().len(), 1);
+    }
+
+    #[test]
+    fn clobbered_registers_include_callees() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                mv     s0, ra          \n\
+                li     t0, 1           \n\
+                jal    fn_b            \n\
+                mv     ra, s0          \n\
+                ret                    \n\
+            fn_b:                      \n\
+                li     t1, 2           \n\
+                ret                    \n",
+        );
+        let graph = cfg.function_call_graph();
+        let clobbered = graph.clobbered_registers(&find(&graph, "fn_a"));
+        assert!(clobbered.contains(&Register::X5));
+        assert!(clobbered.contains(&Register::X6));
+        assert!(!clobbered.contains(&Register::X7));
+
+        let clobbered = graph.clobbered_registers(&find(&graph, "fn_b"));
+        assert_eq!(clobbered, [Register::X6].into_iter().collect());
+    }
+
+    #[test]
+    fn unknown_call_target_is_indirect() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                jalr   a0              \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n",
+        );
+        let graph = cfg.function_call_graph();
+        let calls = graph.calls(&find(&graph, "fn_a")).unwrap();
+        assert!(calls.indirect);
+        assert!(calls.callees.is_empty());
+        assert_eq!(
+            graph.clobbered_registers(&find(&graph, "fn_a")),
+            RegSets::caller_saved()
+        );
+        assert!(graph.cycles().is_empty());
+        assert!(graph.to_dot().contains("f0 -> unknown [style=dashed];"));
+    }
+
+    #[test]
+    fn call_to_data_label_is_unresolved() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                jal    msg             \n\
+                ret                    \n\
+            .data                      \n\
+            msg: .word 1               \n",
+        );
+        let graph = cfg.function_call_graph();
+        let calls = graph.calls(&find(&graph, "fn_a")).unwrap();
+        assert!(calls.callees.is_empty());
+        assert_eq!(calls.unresolved.len(), 1);
+        assert_eq!(calls.unresolved[0].data.0, "msg");
+        assert!(graph.to_dot().contains("u0 [label=\"msg\", style=dashed];"));
+    }
+}
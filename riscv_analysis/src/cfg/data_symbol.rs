@@ -0,0 +1,217 @@
+use crate::parser::{DataValue, DirectiveType, LabelString, With};
+
+/// A run of data that is declared by one directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DataPart {
+    /// The offset of the data from the label, in bytes.
+    offset: usize,
+    /// The number of bytes of data.
+    size: usize,
+    /// The size of each value of the data, in bytes.
+    ///
+    /// This is unknown for untyped data, like `.space`.
+    element_width: Option<usize>,
+}
+
+/// The data that is declared at a label.
+///
+/// A label can be followed by any number of data directives, like
+/// `.word 1, 2` followed by `.byte 3`. The data of a label ends at the
+/// next label, instruction, or directive that does not declare data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSymbol {
+    label: With<LabelString>,
+    parts: Vec<DataPart>,
+    /// The labels whose addresses are stored in the data, with their
+    /// offsets from the label, in bytes.
+    addresses: Vec<(usize, With<LabelString>)>,
+    /// The alignment of the next data, in bytes, from an `.align` since
+    /// the last data.
+    alignment: usize,
+}
+
+impl DataSymbol {
+    #[must_use]
+    pub fn new(label: With<LabelString>) -> Self {
+        DataSymbol {
+            label,
+            parts: Vec::new(),
+            addresses: Vec::new(),
+            alignment: 1,
+        }
+    }
+
+    /// Add the data of a directive to the end of the symbol.
+    ///
+    /// An `.align` pads the symbol before the data that follows it, so that
+    /// the data starts at a multiple of the alignment from the label.
+    ///
+    /// Returns `false` if the directive does not declare data of a known
+    /// size, or is an invalid alignment, which ends the symbol.
+    pub fn push(&mut self, dir: &DirectiveType) -> bool {
+        if let DirectiveType::Align { bytes, .. } = dir {
+            let Some(bytes) = bytes.and_then(|bytes| usize::try_from(bytes).ok()) else {
+                return false;
+            };
+            self.alignment = self.alignment.max(bytes);
+            return true;
+        }
+        let Some(image) = dir.data_image() else {
+            return false;
+        };
+        // The label is taken to be aligned at least as much as its data, so
+        // the padding is found from the start of the label
+        let offset = self.size().next_multiple_of(self.alignment);
+        self.alignment = 1;
+        let element_width = match dir {
+            DirectiveType::Data(data_type, values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if let DataValue::Label(label) = &value.data {
+                        self.addresses.push((
+                            offset + i * data_type.size(),
+                            With::new(label.clone(), value.info()),
+                        ));
+                    }
+                }
+                Some(data_type.size())
+            }
+            DirectiveType::Ascii { .. } => Some(1),
+            _ => None,
+        };
+        self.parts.push(DataPart {
+            offset,
+            size: image.len(),
+            element_width,
+        });
+        true
+    }
+
+    /// Get the label of the symbol.
+    #[must_use]
+    pub fn label(&self) -> &With<LabelString> {
+        &self.label
+    }
+
+    /// Get the number of bytes of data that the symbol declares.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.parts.last().map_or(0, |part| part.offset + part.size)
+    }
+
+    /// Get the size of the values at an offset from the label, in bytes.
+    ///
+    /// This is `None` if the offset is outside of the data, or if the data
+    /// at the offset is untyped.
+    #[must_use]
+    pub fn element_width_at(&self, offset: i32) -> Option<usize> {
+        let offset = usize::try_from(offset).ok()?;
+        self.parts
+            .iter()
+            .find(|part| (part.offset..part.offset + part.size).contains(&offset))
+            .and_then(|part| part.element_width)
+    }
+
+    /// Get the label whose address is stored at an offset from the label.
+    ///
+    /// This is `None` if the value at the offset is not the address of a
+    /// label, like a number or the middle of a value.
+    #[must_use]
+    pub fn address_at(&self, offset: i32) -> Option<&With<LabelString>> {
+        let offset = usize::try_from(offset).ok()?;
+        self.addresses
+            .iter()
+            .find(|(at, _)| *at == offset)
+            .map(|(_, label)| label)
+    }
+
+    /// Get the labels whose addresses are stored in the data, in order.
+    ///
+    /// For a jump table, like `.word case_a, case_b`, these are the
+    /// targets of the table.
+    #[must_use]
+    pub fn addresses(&self) -> Vec<&With<LabelString>> {
+        self.addresses.iter().map(|(_, label)| label).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::Cfg;
+    use crate::parser::{LabelString, RVStringParser};
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    #[test]
+    fn align_pads_following_data() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            .data                      \n\
+            table:                     \n\
+                .byte  1               \n\
+                .align 2               \n\
+                .word  2, 3            \n\
+                .balign 8              \n\
+                .half  4               \n",
+        );
+        let symbol = cfg.data_symbol(&LabelString("table".to_owned())).unwrap();
+        assert_eq!(symbol.element_width_at(0), Some(1));
+        // The padding after the byte is not typed
+        assert_eq!(symbol.element_width_at(1), None);
+        assert_eq!(symbol.element_width_at(3), None);
+        assert_eq!(symbol.element_width_at(4), Some(4));
+        assert_eq!(symbol.element_width_at(11), Some(4));
+        assert_eq!(symbol.element_width_at(12), None);
+        assert_eq!(symbol.element_width_at(16), Some(2));
+        assert_eq!(symbol.size(), 18);
+    }
+
+    #[test]
+    fn aligned_data_keeps_addresses() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            case_a:                    \n\
+                ret                    \n\
+            case_b:                    \n\
+                ret                    \n\
+            .data                      \n\
+            table:                     \n\
+                .byte  0               \n\
+                .align 2               \n\
+                .word  case_a, case_b  \n",
+        );
+        let symbol = cfg.data_symbol(&LabelString("table".to_owned())).unwrap();
+        assert_eq!(symbol.address_at(1), None);
+        assert_eq!(symbol.address_at(4).unwrap().data.0, "case_a");
+        assert_eq!(symbol.address_at(8).unwrap().data.0, "case_b");
+        assert_eq!(symbol.addresses().len(), 2);
+    }
+
+    #[test]
+    fn aligned_data_is_not_padded() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            .data                      \n\
+            table:                     \n\
+                .word  1               \n\
+                .align 2               \n\
+                .word  2               \n",
+        );
+        let symbol = cfg.data_symbol(&LabelString("table".to_owned())).unwrap();
+        assert_eq!(symbol.element_width_at(4), Some(4));
+        assert_eq!(symbol.size(), 8);
+    }
+}
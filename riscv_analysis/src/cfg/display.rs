@@ -1,11 +1,14 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::Display,
+    rc::Rc,
 };
 
 use itertools::Itertools;
+use uuid::Uuid;
 
-use super::{CfgNode, Cfg};
+use super::{CfgNode, Cfg, InterproceduralCfg};
+use crate::parser::RegisterNames;
 
 pub trait SetListString {
     fn str(&self) -> String;
@@ -71,3 +74,325 @@ impl Display for Cfg {
         Ok(())
     }
 }
+
+/// Escape text to be used inside a quoted Graphviz string.
+pub(super) fn escape_dot(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Cfg {
+    /// Get the ids of the entry nodes and the exit nodes of all functions.
+    fn function_boundaries(&self) -> (HashSet<Uuid>, HashSet<Uuid>) {
+        let functions = self.functions();
+        let entries = functions
+            .values()
+            .map(|func| func.entry().node().id())
+            .collect();
+        let exits = functions
+            .values()
+            .map(|func| func.exit().node().id())
+            .collect();
+        (entries, exits)
+    }
+
+    /// Split the nodes of the CFG into basic blocks.
+    ///
+    /// A block is a run of nodes in source order where each node is the only
+    /// next of the one before it, and the only way to reach it. Function
+    /// entries always start a block and function exits always end one, so
+    /// that they can be styled on their own.
+    #[must_use]
+    pub fn basic_blocks(&self) -> Vec<Vec<Rc<CfgNode>>> {
+        self.blocks_along(
+            |node| node.nexts().iter().map(Rc::clone).collect(),
+            |node| node.prevs().len(),
+        )
+    }
+
+    /// Split the nodes of the CFG into basic blocks, like
+    /// [`Cfg::basic_blocks`], with the nexts and the number of prevs of each
+    /// node given by `nexts` and `prevs`.
+    fn blocks_along(
+        &self,
+        nexts: impl Fn(&CfgNode) -> Vec<Rc<CfgNode>>,
+        prevs: impl Fn(&CfgNode) -> usize,
+    ) -> Vec<Vec<Rc<CfgNode>>> {
+        let (entries, exits) = self.function_boundaries();
+        let mut blocks: Vec<Vec<Rc<CfgNode>>> = Vec::new();
+        for node in self.nodes() {
+            let continues = blocks.last().and_then(|block| block.last()).is_some_and(|last| {
+                let last_nexts = nexts(last);
+                last_nexts.len() == 1
+                    && prevs(node) == 1
+                    && last_nexts.contains(node)
+                    && !entries.contains(&node.node().id())
+                    && !exits.contains(&last.node().id())
+            });
+            match blocks.last_mut() {
+                Some(block) if continues => block.push(Rc::clone(node)),
+                _ => blocks.push(vec![Rc::clone(node)]),
+            }
+        }
+        blocks
+    }
+
+    /// Export the CFG as a Graphviz DOT graph.
+    ///
+    /// Each basic block is drawn as one box listing its instructions, with
+    /// an edge to each block it can continue to. Blocks that start a
+    /// function are filled in green, and blocks that return from one are
+    /// filled in red. If `show_liveness` is set, each edge is labelled with
+    /// the registers that are live into the block it points to. Registers
+    /// are shown with `names`.
+    #[must_use]
+    pub fn to_dot(&self, show_liveness: bool, names: RegisterNames) -> String {
+        DotGraph {
+            cfg: self,
+            view: None,
+            show_liveness,
+            names,
+        }
+        .to_string()
+    }
+
+    /// Export the [inter-procedural view](Cfg::interprocedural_view) of the
+    /// CFG as a Graphviz DOT graph.
+    ///
+    /// This is drawn like [`Cfg::to_dot`], except that each call continues
+    /// at the entry of the function that it calls, and each return continues
+    /// after every call to its function. These edges are dashed.
+    #[must_use]
+    pub fn to_interprocedural_dot(&self, show_liveness: bool, names: RegisterNames) -> String {
+        DotGraph {
+            cfg: self,
+            view: Some(self.interprocedural_view()),
+            show_liveness,
+            names,
+        }
+        .to_string()
+    }
+}
+
+/// A CFG written as a Graphviz DOT graph, see [`Cfg::to_dot`].
+struct DotGraph<'a> {
+    cfg: &'a Cfg,
+    /// The edges to draw instead of the edges of the CFG, if any.
+    view: Option<InterproceduralCfg>,
+    show_liveness: bool,
+    names: RegisterNames,
+}
+
+impl DotGraph<'_> {
+    /// Get the nodes that a node continues at in the graph.
+    fn nexts(&self, node: &CfgNode) -> Vec<Rc<CfgNode>> {
+        match &self.view {
+            Some(view) => view.nexts(node).to_vec(),
+            None => node.nexts().iter().map(Rc::clone).collect(),
+        }
+    }
+
+    /// Get the number of nodes that continue at a node in the graph.
+    fn prevs(&self, node: &CfgNode) -> usize {
+        match &self.view {
+            Some(view) => view.prevs(node).len(),
+            None => node.prevs().len(),
+        }
+    }
+}
+
+impl Display for DotGraph<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (entries, exits) = self.cfg.function_boundaries();
+        let blocks = self
+            .cfg
+            .blocks_along(|node| self.nexts(node), |node| self.prevs(node));
+        let block_of = blocks
+            .iter()
+            .enumerate()
+            .flat_map(|(i, block)| block.iter().map(move |node| (node.node().id(), i)))
+            .collect::<HashMap<Uuid, usize>>();
+
+        writeln!(f, "digraph cfg {{")?;
+        writeln!(f, "    node [shape=box, fontname=monospace];")?;
+        for (i, block) in blocks.iter().enumerate() {
+            let Some(first) = block.first() else {
+                continue;
+            };
+            let mut label = String::new();
+            for name in first.labels().iter().map(|x| x.data.0.clone()).sorted() {
+                label.push_str(&escape_dot(&name));
+                label.push_str(":\\l");
+            }
+            for node in block {
+                label.push_str(&escape_dot(&node.node().display(self.names).to_string()));
+                label.push_str("\\l");
+            }
+
+            let style = if block.iter().any(|node| entries.contains(&node.node().id())) {
+                ", style=filled, fillcolor=palegreen"
+            } else if block.iter().any(|node| exits.contains(&node.node().id())) {
+                ", style=filled, fillcolor=lightpink"
+            } else {
+                ""
+            };
+            writeln!(f, "    b{i} [label=\"{label}\"{style}];")?;
+        }
+
+        for (i, block) in blocks.iter().enumerate() {
+            let Some(last) = block.last() else {
+                continue;
+            };
+            let nexts = self
+                .nexts(last)
+                .into_iter()
+                .filter_map(|next| Some((*block_of.get(&next.node().id())?, next)))
+                .sorted_by_key(|(j, _)| *j)
+                .collect::<Vec<_>>();
+            for (j, next) in nexts {
+                // Calls and returns are not edges of the CFG
+                let style = if last.nexts().contains(&next) {
+                    ""
+                } else {
+                    ", style=dashed"
+                };
+                if self.show_liveness {
+                    let live = next
+                        .live_in()
+                        .into_iter()
+                        .map(|reg| reg.display(self.names))
+                        .join(", ");
+                    let live = escape_dot(&format!("[{live}]"));
+                    writeln!(f, "    b{i} -> b{j} [label=\"{live}\"{style}];")?;
+                } else if style.is_empty() {
+                    writeln!(f, "    b{i} -> b{j};")?;
+                } else {
+                    writeln!(f, "    b{i} -> b{j} [style=dashed];")?;
+                }
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn dot(input: &str, show_liveness: bool, names: RegisterNames) -> String {
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        cfg.to_dot(show_liveness, names)
+    }
+
+    #[test]
+    fn blocks_and_edges() {
+        let input = "\
+            main:                      \n\
+                li     a0, 3           \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                beqz   a0, done        \n\
+                addi   a0, a0, -1      \n\
+            done:                      \n\
+                ret                    \n";
+
+        let dot = dot(input, false, RegisterNames::Abi);
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("fn_a:\\l"));
+        assert!(dot.contains("addi a0 <- a0, -1\\l"));
+        assert_eq!(dot.matches("fillcolor=palegreen").count(), 1);
+        assert_eq!(dot.matches("fillcolor=lightpink").count(), 1);
+        // The branch goes to both the next block and the return
+        let branch = dot
+            .lines()
+            .find(|line| line.contains("beqz") || line.contains("beq"))
+            .and_then(|line| line.split_whitespace().next())
+            .unwrap();
+        assert_eq!(
+            dot.lines()
+                .filter(|line| line.trim_start().starts_with(&format!("{branch} ->")))
+                .count(),
+            2
+        );
+        assert!(!dot.contains("label=\"["));
+    }
+
+    #[test]
+    fn edges_show_liveness() {
+        let input = "\
+            main:                      \n\
+                lw     a0, 0(sp)       \n\
+                addi   a0, a0, 1       \n\
+                beqz   a0, end         \n\
+                nop                    \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let dot = dot(input, true, RegisterNames::Abi);
+        assert!(dot.contains("-> b"));
+        assert!(dot.contains("label=\"["));
+    }
+
+    #[test]
+    fn numeric_register_names() {
+        let input = "\
+            main:                      \n\
+                lw     a0, 0(sp)       \n\
+                addi   a0, a0, 1       \n\
+                beqz   a0, end         \n\
+                nop                    \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let dot = dot(input, true, RegisterNames::Numeric);
+        assert!(dot.contains("addi x10 <- x10, 1\\l"));
+        assert!(!dot.contains("a0"));
+    }
+
+    #[test]
+    fn interprocedural_edges_are_dashed() {
+        let input = "\
+            main:                      \n\
+                li     a0, 3           \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   a0, a0, -1      \n\
+                ret                    \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let dot = cfg.to_dot(false, RegisterNames::Abi);
+        assert!(!dot.contains("style=dashed"));
+
+        // The call goes to the entry of fn_a, and its return after the call
+        let dot = cfg.to_interprocedural_dot(false, RegisterNames::Abi);
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert_eq!(dot.matches("[style=dashed]").count(), 2);
+    }
+
+    #[test]
+    fn escape_quotes_and_newlines() {
+        assert_eq!(escape_dot("a \"b\"\nc\\d"), "a \\\"b\\\"\\nc\\\\d");
+    }
+}
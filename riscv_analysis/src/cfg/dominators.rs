@@ -0,0 +1,275 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use super::{Cfg, CfgNode, CfgPostOrderIterator};
+
+/// The dominator tree of a CFG.
+///
+/// A node dominates another node if every path from an entry to the other
+/// node goes through it. The immediate dominator of a node is the closest
+/// node that dominates it. The tree is found with the iterative algorithm of
+/// Cooper, Harvey and Kennedy.
+///
+/// The [roots](Cfg::roots) of the CFG and the entry of every function are
+/// the roots of the tree, so the nodes of a function are dominated by its entry. Roots have
+/// no immediate dominator, and neither do nodes that can be reached from more
+/// than one root, or nodes that cannot be reached from any root.
+#[derive(Debug, Clone)]
+pub struct DominatorTree {
+    idoms: HashMap<Uuid, Rc<CfgNode>>,
+    reachable: HashSet<Uuid>,
+}
+
+impl DominatorTree {
+    /// Find the dominator tree of a CFG.
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        let position = cfg
+            .nodes()
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.node().id(), i))
+            .collect::<HashMap<_, _>>();
+        let mut roots = cfg.roots();
+        let mut entries = cfg
+            .functions()
+            .values()
+            .map(|func| func.entry())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| position.get(&entry.node().id()).copied());
+        roots.extend(entries);
+
+        // The roots share one extra root before them, which is numbered last
+        let order = CfgPostOrderIterator::from_roots(cfg, &roots).collect::<Vec<_>>();
+        let index = order
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.node().id(), i))
+            .collect::<HashMap<_, _>>();
+        let start = order.len();
+        let root_ids = roots
+            .iter()
+            .map(|root| root.node().id())
+            .collect::<HashSet<_>>();
+        let preds = order
+            .iter()
+            .map(|node| {
+                let mut preds = node
+                    .prevs()
+                    .iter()
+                    .filter_map(|prev| index.get(&prev.node().id()).copied())
+                    .collect::<Vec<_>>();
+                if root_ids.contains(&node.node().id()) {
+                    preds.push(start);
+                }
+                preds
+            })
+            .collect::<Vec<_>>();
+
+        let mut idoms = vec![None; start + 1];
+        if let Some(idom) = idoms.get_mut(start) {
+            *idom = Some(start);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, node_preds) in preds.iter().enumerate().rev() {
+                let mut processed = node_preds
+                    .iter()
+                    .copied()
+                    .filter(|pred| idoms.get(*pred).is_some_and(Option::is_some));
+                let Some(first) = processed.next() else {
+                    continue;
+                };
+                let new_idom = processed.fold(first, |a, b| Self::intersect(&idoms, a, b));
+                if let Some(idom) = idoms.get_mut(i) {
+                    if *idom != Some(new_idom) {
+                        *idom = Some(new_idom);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let idoms = order
+            .iter()
+            .zip(&idoms)
+            .filter_map(|(node, idom)| {
+                let idom = order.get((*idom)?)?;
+                Some((node.node().id(), Rc::clone(idom)))
+            })
+            .collect();
+        DominatorTree {
+            idoms,
+            reachable: index.into_keys().collect(),
+        }
+    }
+
+    /// Find the closest common dominator of two nodes, by their post-order
+    /// numbers.
+    fn intersect(idoms: &[Option<usize>], mut a: usize, mut b: usize) -> usize {
+        while a != b {
+            while a < b {
+                a = idoms.get(a).copied().flatten().unwrap_or(b);
+            }
+            while b < a {
+                b = idoms.get(b).copied().flatten().unwrap_or(a);
+            }
+        }
+        a
+    }
+
+    /// Get the immediate dominator of a node.
+    ///
+    /// Returns `None` for roots, for nodes that can be reached from more than
+    /// one root, and for nodes that cannot be reached.
+    #[must_use]
+    pub fn immediate_dominator(&self, node: &CfgNode) -> Option<Rc<CfgNode>> {
+        self.idoms.get(&node.node().id()).map(Rc::clone)
+    }
+
+    /// Check if a node can be reached from the program entry or from the
+    /// entry of a function.
+    #[must_use]
+    pub fn is_reachable(&self, node: &CfgNode) -> bool {
+        self.reachable.contains(&node.node().id())
+    }
+
+    /// Check if every path to node `b` goes through node `a`.
+    ///
+    /// A reachable node dominates itself. Nodes that cannot be reached do not
+    /// dominate, and are not dominated by, any node.
+    #[must_use]
+    pub fn dominates(&self, a: &CfgNode, b: &CfgNode) -> bool {
+        if !self.is_reachable(a) || !self.is_reachable(b) {
+            return false;
+        }
+        let target = a.node().id();
+        let mut current = b.node().id();
+        loop {
+            if current == target {
+                return true;
+            }
+            match self.idoms.get(&current) {
+                Some(idom) => current = idom.node().id(),
+                None => return false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    fn find(cfg: &Cfg, text: &str) -> Rc<CfgNode> {
+        cfg.nodes()
+            .iter()
+            .find(|node| node.node().token().text == text)
+            .map(Rc::clone)
+            .unwrap()
+    }
+
+    fn idom_text(tree: &DominatorTree, node: &CfgNode) -> Option<String> {
+        tree.immediate_dominator(node)
+            .map(|idom| idom.node().token().text)
+    }
+
+    #[test]
+    fn diamond() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                beqz   a0, else        \n\
+                li     a1, 1           \n\
+                j      end             \n\
+            else:                      \n\
+                li     a1, 2           \n\
+            end:                       \n\
+                mv     a0, a1          \n\
+                li     a7, 10          \n\
+                ecall                  \n",
+        );
+        let tree = cfg.dominator_tree();
+        let branch = find(&cfg, "beqz a0 else");
+        let then = find(&cfg, "li a1 1");
+        let other = find(&cfg, "li a1 2");
+        let join = find(&cfg, "mv a0 a1");
+
+        assert_eq!(idom_text(&tree, &then).as_deref(), Some("beqz a0 else"));
+        assert_eq!(idom_text(&tree, &other).as_deref(), Some("beqz a0 else"));
+        // Neither side of the branch is on every path to the join
+        assert_eq!(idom_text(&tree, &join).as_deref(), Some("beqz a0 else"));
+        assert!(tree.dominates(&branch, &join));
+        assert!(!tree.dominates(&then, &join));
+        assert!(tree.dominates(&join, &join));
+    }
+
+    #[test]
+    fn loop_header_dominates_body() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                addi   a0, a0, 1       \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, loop        \n\
+                li     a7, 10          \n\
+                ecall                  \n",
+        );
+        let tree = cfg.dominator_tree();
+        let init = find(&cfg, "li t0 10");
+        let header = find(&cfg, "addi a0 a0 1");
+        let latch = find(&cfg, "bnez t0 loop");
+        let exit = find(&cfg, "li a7 10");
+
+        // The back edge does not change the dominator of the header
+        assert_eq!(idom_text(&tree, &header).as_deref(), Some("li t0 10"));
+        assert_eq!(idom_text(&tree, &latch).as_deref(), Some("addi t0 t0 -1"));
+        assert_eq!(idom_text(&tree, &exit).as_deref(), Some("bnez t0 loop"));
+        assert!(tree.dominates(&header, &latch));
+        assert!(tree.dominates(&init, &exit));
+        assert!(!tree.dominates(&latch, &header));
+    }
+
+    #[test]
+    fn functions_and_unreachable_code() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+                li     a0, 3           \n\
+            fn_a:                      \n\
+                li     a0, 1           \n\
+                ret                    \n",
+        );
+        let tree = cfg.dominator_tree();
+        let call = find(&cfg, "jal fn_a");
+        let entry = find(&cfg, "li a0 1");
+        let ret = find(&cfg, "ret");
+        let dead = find(&cfg, "li a0 3");
+
+        // The entry of a function is a root
+        assert!(tree.is_reachable(&entry));
+        assert_eq!(tree.immediate_dominator(&entry), None);
+        assert_eq!(idom_text(&tree, &ret).as_deref(), Some("li a0 1"));
+        assert!(!tree.dominates(&call, &entry));
+
+        assert!(!tree.is_reachable(&dead));
+        assert_eq!(tree.immediate_dominator(&dead), None);
+        assert!(!tree.dominates(&dead, &dead));
+    }
+}
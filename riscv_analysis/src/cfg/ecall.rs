@@ -1,55 +1,111 @@
 use crate::parser::Register;
+use crate::parser::Register::{X10, X11, X12, X13};
 
 use super::RegisterSet;
 
-#[allow(clippy::match_same_arms)]
+/// A system call that is made with `ecall`.
+///
+/// The call is selected by the number in `a7`. Its arguments are read from
+/// the input registers, and its results are written to the output
+/// registers. Every other register keeps its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syscall {
+    pub number: i32,
+    pub name: &'static str,
+    pub inputs: &'static [Register],
+    pub outputs: &'static [Register],
+}
+
+impl Syscall {
+    const fn new(
+        number: i32,
+        name: &'static str,
+        inputs: &'static [Register],
+        outputs: &'static [Register],
+    ) -> Self {
+        Syscall {
+            number,
+            name,
+            inputs,
+            outputs,
+        }
+    }
+}
+
+/// The system calls that are known, using the numbers of RARS.
+///
+/// To support another call, add it to this table.
+pub const SYSCALLS: &[Syscall] = &[
+    Syscall::new(1, "PrintInt", &[X10], &[]),
+    // 2 PrintFloat and 3 PrintDouble: floating point is not supported yet
+    Syscall::new(4, "PrintString", &[X10], &[]),
+    Syscall::new(5, "ReadInt", &[], &[X10]),
+    // 6 ReadFloat and 7 ReadDouble: floating point is not supported yet
+    Syscall::new(8, "ReadString", &[X10, X11], &[]),
+    Syscall::new(9, "Sbrk", &[X10], &[X10]),
+    Syscall::new(10, "Exit", &[], &[]),
+    Syscall::new(11, "PrintChar", &[X10], &[]),
+    Syscall::new(12, "ReadChar", &[], &[X10]),
+    Syscall::new(17, "GetCWD", &[X10, X11], &[X10]),
+    Syscall::new(30, "Time", &[], &[X10, X11]),
+    Syscall::new(31, "MidiOut", &[X10, X11, X12, X13], &[]),
+    Syscall::new(32, "Sleep", &[X10], &[]),
+    Syscall::new(33, "MidiOutSync", &[X10, X11, X12, X13], &[]),
+    Syscall::new(34, "PrintIntHex", &[X10], &[]),
+    Syscall::new(35, "PrintIntBinary", &[X10], &[]),
+    Syscall::new(36, "PrintIntUnsigned", &[X10], &[]),
+    Syscall::new(40, "RandSeed", &[X10, X11], &[]),
+    Syscall::new(41, "RandInt", &[X10], &[X10]),
+    Syscall::new(42, "RandIntRange", &[X10, X11], &[X10]),
+    Syscall::new(43, "RandFloat", &[X10], &[X10]),
+    // 44 RandDouble: floating point is not supported yet
+    Syscall::new(50, "ConfirmDialog", &[X10], &[X10]),
+    Syscall::new(54, "InputDialogString", &[X10, X11, X12], &[X11]),
+    Syscall::new(55, "MessageDialog", &[X10], &[]),
+    Syscall::new(56, "MessageDialogInt", &[X10, X11], &[]),
+    Syscall::new(57, "Close", &[X10], &[]),
+    // 58 MessageDialogDouble: floating point is not supported yet
+    Syscall::new(59, "MessageDialogString", &[X10, X11], &[]),
+    // 60 MessageDialogFloat: floating point is not supported yet
+    Syscall::new(62, "LSeek", &[X10, X11, X12], &[X10]),
+    Syscall::new(63, "Read", &[X10, X11, X12], &[X10]),
+    Syscall::new(64, "Write", &[X10, X11, X12], &[X10]),
+    Syscall::new(93, "Exit2", &[X10], &[]),
+    Syscall::new(1024, "Open", &[X10, X11], &[X10]),
+];
+
+/// Get the system call with a number.
 #[must_use]
-pub fn environment_in_outs(call_num: i32) -> Option<(RegisterSet, RegisterSet)> {
-    use crate::parser::Register::{X10, X11, X12, X13};
-    let vecs: (&[Register], &[Register]) = match call_num {
-        1 => (&[X10], &[]),
-        // 2 => (&[], &[]), Not supporting floating point yet
-        // 3 => (&[], &[]),
-        4 => (&[X10], &[]),
-        5 => (&[], &[X10]),
-        // 6 => (&[], &[]),
-        // 7 => (&[], &[]),
-        8 => (&[X10, X11], &[]),
-        9 => (&[X10], &[X10]),
-        10 => (&[], &[]),
-        11 => (&[X10], &[]),
-        12 => (&[], &[X10]),
-        17 => (&[X10, X11], &[X10]),
-        30 => (&[], &[X10, X11]),
-        31 => (&[X10, X11, X12, X13], &[]),
-        32 => (&[X10], &[]),
-        33 => (&[X10, X11, X12, X13], &[]),
-        34 => (&[X10], &[]),
-        35 => (&[X10], &[]),
-        36 => (&[X10], &[]),
-        40 => (&[X10, X11], &[]),
-        41 => (&[X10], &[X10]),
-        42 => (&[X10, X11], &[X10]),
-        43 => (&[X10], &[X10]),
-        // 44 => (&[X10], &[]),
-        50 => (&[X10], &[X10]),
-        54 => (&[X10, X11, X12], &[X11]),
-        55 => (&[X10], &[]),
-        56 => (&[X10, X11], &[]),
-        57 => (&[X10], &[]),
-        // 58 => (&[X10], &[]),
-        59 => (&[X10, X11], &[]),
-        // 60 => (&[X10], &[]),
-        62 => (&[X10, X11, X12], &[X10]),
-        63 => (&[X10, X11, X12], &[X10]),
-        64 => (&[X10, X11, X12], &[X10]),
-        93 => (&[X10], &[]),
-        1024 => (&[X10, X11], &[X10]),
-        _ => return None,
-    };
+pub fn syscall(call_num: i32) -> Option<&'static Syscall> {
+    SYSCALLS.iter().find(|call| call.number == call_num)
+}
 
+/// Get the input and output registers of the system call with a number.
+#[must_use]
+pub fn environment_in_outs(call_num: i32) -> Option<(RegisterSet, RegisterSet)> {
+    let call = syscall(call_num)?;
     Some((
-        vecs.0.iter().copied().collect(),
-        vecs.1.iter().copied().collect(),
+        call.inputs.iter().copied().collect(),
+        call.outputs.iter().copied().collect(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syscall_numbers_are_unique() {
+        for (i, call) in SYSCALLS.iter().enumerate() {
+            assert!(SYSCALLS.iter().skip(i + 1).all(|c| c.number != call.number));
+        }
+    }
+
+    #[test]
+    fn can_find_syscall_by_number() {
+        let call = syscall(4).unwrap();
+        assert_eq!(call.name, "PrintString");
+        assert_eq!(call.inputs, &[X10]);
+        assert!(syscall(2).is_none());
+    }
+}
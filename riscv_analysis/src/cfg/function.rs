@@ -6,10 +6,44 @@ use std::{
 };
 use uuid::Uuid;
 
-use crate::parser::{LabelString, RegSets, Register, With};
+use crate::analysis::AvailableValue;
+use crate::parser::{Annotation, AnnotationKind, LabelString, ParserNode, RegSets, Register, With};
+use crate::passes::DiagnosticLocation;
 
 use super::{CfgNode, RegisterSet};
 
+/// The label of the jumps that the returns of a function are turned into,
+/// when the function has more than one return.
+pub(crate) const MERGED_RETURN_LABEL: &str = "__return__";
+
+/// A summary of how a function uses registers, in terms of the calling
+/// convention.
+///
+/// A function that tail calls another function continues in the code of that
+/// function, so the registers that the other function uses are part of the
+/// summary. Registers used by a function that is called with `jal` are not,
+/// other than the return values that it writes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FunctionUsage {
+    /// The callee-saved registers, other than `sp`, that the function writes.
+    /// These have to be saved and restored by the function.
+    pub callee_saved_modified: RegisterSet,
+    /// The callee-saved registers that do not hold their original value when
+    /// the function returns.
+    pub callee_saved_not_restored: RegisterSet,
+    /// The argument registers that the function reads before writing them.
+    pub arguments_read: RegisterSet,
+    /// The return registers that are written on every path to the return of
+    /// the function. A function that is tail called can also be entered
+    /// directly, so the registers written before a tail call are not on
+    /// every path.
+    pub returns_written: RegisterSet,
+    /// The registers that the function reads before writing them. The
+    /// return reads every callee-saved register, to check that it is
+    /// restored.
+    pub inputs: RegisterSet,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Function {
     uuid: Uuid,
@@ -29,6 +63,9 @@ pub struct Function {
 
     /// The registers that are set ever in the function
     defs: RefCell<RegisterSet>,
+
+    /// The annotations in the comments before the labels of the function
+    annotations: RefCell<Vec<Annotation>>,
 }
 
 impl Hash for Function {
@@ -63,6 +100,7 @@ impl Function {
             entry,
             exit: RefCell::new(exit),
             defs: RefCell::new(RegisterSet::new()),
+            annotations: RefCell::new(Vec::new()),
         }
     }
 
@@ -87,7 +125,7 @@ impl Function {
     }
 
     /// Return the set of written registers.
-    pub fn defs(&self) -> Ref<RegisterSet> {
+    pub fn defs(&self) -> Ref<'_, RegisterSet> {
         self.defs.borrow()
     }
 
@@ -97,13 +135,69 @@ impl Function {
         (*self.defs() & RegSets::callee_saved()) - Register::X2
     }
 
+    /// Summarize how this function uses registers.
+    ///
+    /// The summary is computed from the liveness and available values of the
+    /// nodes of the function, so those passes must have run.
+    #[must_use]
+    pub fn usage_summary(&self) -> FunctionUsage {
+        let exit = self.exit();
+
+        // A call or an ecall does not store to its return values, but the
+        // values that it is known to define are written by it.
+        let mut written = *self.defs();
+        for node in self.nodes().iter() {
+            if node.node().calls_to().is_some() || node.node().is_ecall() {
+                written |= node.u_def() & RegSets::ret();
+            }
+        }
+
+        let exit_values = exit.reg_values_in();
+        let callee_saved_not_restored = RegSets::callee_saved()
+            .into_iter()
+            .filter(|reg| {
+                exit_values.get(reg) != Some(&AvailableValue::OriginalRegisterWithScalar(*reg, 0))
+            })
+            .collect::<RegisterSet>();
+
+        FunctionUsage {
+            callee_saved_modified: self.to_save(),
+            callee_saved_not_restored,
+            arguments_read: self.arguments(),
+            returns_written: exit.u_def() & RegSets::ret() & written,
+            inputs: self.entry.live_out(),
+        }
+    }
+
+    /// Get the annotations of this function, like `# @arg a0: count`, in
+    /// the order they are written.
+    pub fn annotations(&self) -> Ref<'_, Vec<Annotation>> {
+        self.annotations.borrow()
+    }
+
+    /// Get the registers that the annotations of this function declare as
+    /// one kind, like the arguments.
+    #[must_use]
+    pub fn declared_registers(&self, kind: AnnotationKind) -> RegisterSet {
+        self.annotations()
+            .iter()
+            .filter(|annotation| annotation.kind == kind)
+            .map(|annotation| annotation.reg.data)
+            .collect()
+    }
+
+    /// Set the annotations of this function.
+    pub fn set_annotations(&self, annotations: Vec<Annotation>) {
+        *self.annotations.borrow_mut() = annotations;
+    }
+
     /// Set the instructions composing this function.
     pub fn set_nodes(&self, instructions: Vec<Rc<CfgNode>>) {
         *self.nodes.borrow_mut() = instructions;
     }
 
     /// Return the instructions in the function.
-    pub fn nodes(&self) -> Ref<Vec<Rc<CfgNode>>> {
+    pub fn nodes(&self) -> Ref<'_, Vec<Rc<CfgNode>>> {
         self.nodes.borrow()
     }
 
@@ -114,12 +208,153 @@ impl Function {
 
     /// Return the exit node of this function. In general, this corresponds to a
     /// `ret` instruction.
-    pub fn exit(&self) -> Ref<Rc<CfgNode>> {
+    pub fn exit(&self) -> Ref<'_, Rc<CfgNode>> {
         self.exit.borrow()
     }
 
+    /// Return the nodes that return from this function, in the order they
+    /// appear in the source.
+    ///
+    /// The first return that is found is the exit of the function. Any
+    /// other return is turned into a jump to the exit, so the returns are
+    /// the exit and those jumps.
+    pub fn return_sites(&self) -> Vec<Rc<CfgNode>> {
+        let exit = Rc::clone(&self.exit());
+        let mut ids = HashSet::new();
+        let mut sites = self
+            .nodes()
+            .iter()
+            .filter(|node| {
+                matches!(node.node(), ParserNode::JumpLink(jump)
+                    if jump.name.data == MERGED_RETURN_LABEL)
+            })
+            .chain(std::iter::once(&exit))
+            .filter(|node| ids.insert(node.node().id()))
+            .map(Rc::clone)
+            .collect::<Vec<_>>();
+        sites.sort_by_key(|node| (node.node().file(), node.node().range()));
+        sites
+    }
+
     /// Set the exit node of this function.
     pub fn set_exit(&self, node: Rc<CfgNode>) {
         *self.exit.borrow_mut() = node;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::RegisterSet;
+    use crate::parser::{RVStringParser, RegSets, Register};
+    use crate::passes::Manager;
+
+    use super::FunctionUsage;
+
+    fn usage(input: &str, name: &str) -> FunctionUsage {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let functions = cfg.functions();
+        let func = functions
+            .iter()
+            .find(|(label, _)| label.data.0 == name)
+            .unwrap();
+        func.1.usage_summary()
+    }
+
+    fn set(regs: &[Register]) -> RegisterSet {
+        regs.iter().copied().collect()
+    }
+
+    #[test]
+    fn leaf_function() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                jal    fn_a            \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     s0, 0(sp)       \n\
+                add    s0, a0, a1      \n\
+                mv     a0, s0          \n\
+                lw     s0, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n";
+
+        let usage = usage(input, "fn_a");
+        assert_eq!(usage.callee_saved_modified, set(&[Register::X8]));
+        assert_eq!(usage.callee_saved_not_restored, RegisterSet::new());
+        assert_eq!(usage.arguments_read, set(&[Register::X10, Register::X11]));
+        assert_eq!(usage.returns_written, set(&[Register::X10]));
+        assert_eq!(usage.inputs - RegSets::callee_saved(), usage.arguments_read);
+    }
+
+    #[test]
+    fn function_that_calls_another() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                jal    fn_a            \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                add    a0, a0, t0      \n\
+                jal    fn_b            \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n\
+            fn_b:                      \n\
+                addi   a1, a0, 1       \n\
+                mv     s1, a1          \n\
+                ret                    \n";
+
+        // The return value of `fn_b` is written by the call, but `a0` is not,
+        // as the call does not keep it. `fn_b` clobbers `s1` for itself only.
+        let fn_a = usage(input, "fn_a");
+        assert_eq!(fn_a.callee_saved_modified, set(&[Register::X1]));
+        assert_eq!(fn_a.callee_saved_not_restored, RegisterSet::new());
+        assert_eq!(fn_a.arguments_read, set(&[Register::X10]));
+        assert_eq!(fn_a.returns_written, set(&[Register::X11]));
+        assert!(fn_a.inputs.contains(&Register::X5));
+
+        let fn_b = usage(input, "fn_b");
+        assert_eq!(fn_b.callee_saved_modified, set(&[Register::X9]));
+        assert_eq!(fn_b.callee_saved_not_restored, set(&[Register::X9]));
+        assert_eq!(fn_b.returns_written, set(&[Register::X11]));
+    }
+
+    #[test]
+    fn tail_call_includes_callee() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                jal    fn_a            \n\
+                jal    fn_b            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                tail   fn_b            \n\
+            fn_b:                      \n\
+                mv     s2, a0          \n\
+                addi   a1, s2, 2       \n\
+                ret                    \n";
+
+        // `fn_b` can be entered without going through `fn_a`, so the write
+        // to `a0` is not on every path to the return
+        let usage = usage(input, "fn_a");
+        assert_eq!(usage.callee_saved_modified, set(&[Register::X18]));
+        assert_eq!(usage.callee_saved_not_restored, set(&[Register::X18]));
+        assert_eq!(usage.arguments_read, set(&[Register::X10]));
+        assert_eq!(usage.returns_written, set(&[Register::X11]));
+    }
+}
@@ -1,33 +1,55 @@
 use crate::parser;
+use crate::parser::Annotation;
 use crate::parser::DirectiveType;
 use crate::parser::LabelString;
 use crate::parser::ParserNode;
 use crate::parser::With;
+use crate::parser::Xlen;
 use crate::passes::CfgError;
 use crate::passes::DiagnosticLocation;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::iter::Rev;
 use std::rc::Rc;
 
 use super::CfgIterator;
 use super::CfgNextsIterator;
 use super::CfgNode;
+use super::CfgPostOrderIterator;
 use super::CfgPrevsIterator;
+use super::DataSymbol;
+use super::DominatorTree;
+use super::NaturalLoops;
 use super::CfgSourceIterator;
 use super::Function;
+use super::InterproceduralCfg;
+use super::RegisterSet;
 use super::Segment;
+use uuid::Uuid;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Cfg {
     nodes: Vec<Rc<CfgNode>>,
     pub label_node_map: HashMap<String, Rc<CfgNode>>,
     label_function_map: HashMap<With<LabelString>, Rc<Function>>,
+    entry_points: HashSet<String>,
+    globals: HashSet<String>,
+    /// The labels declared with `.extern` that are not defined.
+    externs: HashSet<String>,
+    /// The uses of labels that are not defined, in source order.
+    undefined_labels: Vec<With<LabelString>>,
+    xlen: Xlen,
+    library: bool,
+    data_symbols: HashMap<String, DataSymbol>,
+    /// The annotations in the comments before each label that has any.
+    annotations: HashMap<String, Vec<Annotation>>,
+    liveness_computed: bool,
 }
 
 impl Cfg {
     /// Get an iterator over the `Cfg` nodes.
     #[must_use]
-    pub fn iter(&self) -> CfgIterator {
+    pub fn iter(&self) -> CfgIterator<'_> {
         CfgIterator::new(self)
     }
 
@@ -37,6 +59,24 @@ impl Cfg {
         CfgSourceIterator::new(self)
     }
 
+    /// Get an iterator over the `Cfg` nodes in post-order.
+    ///
+    /// See [`CfgPostOrderIterator`] for how cycles and unreachable nodes are
+    /// handled. This is the order that backward dataflow analyses, like
+    /// liveness, converge fastest in.
+    #[must_use]
+    pub fn post_order(&self) -> CfgPostOrderIterator {
+        CfgPostOrderIterator::new(self)
+    }
+
+    /// Get an iterator over the `Cfg` nodes in reverse post-order.
+    ///
+    /// Apart from back edges, every node comes before its nexts. This is the
+    /// order that forward dataflow analyses converge fastest in.
+    pub fn reverse_post_order(&self) -> Rev<CfgPostOrderIterator> {
+        self.post_order().rev()
+    }
+
     /// Get an iterator over the `Cfg` nodes that are reachable using the
     /// nexts of `node`.
     #[must_use]
@@ -57,6 +97,23 @@ impl Cfg {
         self.label_function_map.clone()
     }
 
+    /// Get every function of the CFG once, sorted by name.
+    ///
+    /// A function with more than one label is in `functions` once for each
+    /// of its labels, so this is used to visit each function only once.
+    #[must_use]
+    pub fn unique_functions(&self) -> Vec<Rc<Function>> {
+        let mut seen = HashSet::new();
+        let mut functions = self
+            .label_function_map
+            .values()
+            .filter(|func| seen.insert(func.entry().node().id()))
+            .map(Rc::clone)
+            .collect::<Vec<_>>();
+        functions.sort_by_key(|func| func.name().0);
+        functions
+    }
+
     /// Insert a new function
     pub fn insert_function(&mut self, label: With<LabelString>, func: Rc<Function>) {
         self.label_function_map.insert(label, func);
@@ -67,6 +124,267 @@ impl Cfg {
     pub fn nodes(&self) -> &Vec<Rc<CfgNode>> {
         &self.nodes
     }
+
+    /// Get the labels that the program can also be entered through.
+    ///
+    /// These are the labels exported with `.globl`, along with any added
+    /// with [`Cfg::add_entry_point`]. The code at these labels is a
+    /// [root](Cfg::roots) of the CFG.
+    #[must_use]
+    pub fn entry_points(&self) -> &HashSet<String> {
+        &self.entry_points
+    }
+
+    /// Get the nodes that the program can be entered through, in source
+    /// order.
+    ///
+    /// These are the program entry and the code at each [entry
+    /// point](Cfg::entry_points) in the text segment. Entry points that are
+    /// functions are not included, as functions are entered through calls.
+    /// There are no edges between roots, so the values at one entry do not
+    /// flow into another.
+    #[must_use]
+    pub fn roots(&self) -> Vec<Rc<CfgNode>> {
+        let entries = self
+            .entry_points
+            .iter()
+            .filter_map(|label| self.label_node_map.get(label))
+            .filter(|node| !node.node().is_any_entry() && node.segment() == Segment::Text)
+            .map(|node| node.node().id())
+            .collect::<HashSet<_>>();
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(i, node)| {
+                (*i == 0 && node.node().is_program_entry()) || entries.contains(&node.node().id())
+            })
+            .map(|(_, node)| Rc::clone(node))
+            .collect()
+    }
+
+    /// Get the labels exported with `.globl` or `.global`.
+    ///
+    /// Exported labels are meant to be entered from outside of the
+    /// program, so they are also [entry points](Cfg::entry_points).
+    #[must_use]
+    pub fn globals(&self) -> &HashSet<String> {
+        &self.globals
+    }
+
+    /// Get the labels declared with `.extern` that the program does not
+    /// define.
+    ///
+    /// These labels are defined outside of the program, like in a library
+    /// that it is linked with. A label that is declared with `.extern` and
+    /// also defined in the program is a normal label, so it is not included.
+    #[must_use]
+    pub fn externs(&self) -> &HashSet<String> {
+        &self.externs
+    }
+
+    /// Get every use of a label that is not defined, in source order.
+    ///
+    /// Labels declared with `.globl` or `.extern` are defined outside of the
+    /// program, so their uses are not included. Calls, jumps and branches to
+    /// labels that are not defined have no edge in the CFG.
+    #[must_use]
+    pub fn undefined_labels(&self) -> &[With<LabelString>] {
+        &self.undefined_labels
+    }
+
+    /// Add a label that the program can also be entered through.
+    pub fn add_entry_point(&mut self, label: String) {
+        self.entry_points.insert(label);
+    }
+
+    /// Get the register width of the target of the program.
+    #[must_use]
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    /// Set the register width of the target of the program.
+    ///
+    /// This must be set before the available value analysis is run.
+    pub fn set_xlen(&mut self, xlen: Xlen) {
+        self.xlen = xlen;
+    }
+
+    /// Get the data that is declared at a label, if any.
+    #[must_use]
+    pub fn data_symbol(&self, label: &LabelString) -> Option<&DataSymbol> {
+        self.data_symbols.get(&label.0)
+    }
+
+    /// Get the data that is declared at every label, in no particular order.
+    pub fn data_symbols(&self) -> impl Iterator<Item = &DataSymbol> {
+        self.data_symbols.values()
+    }
+
+    /// Get the annotations in the comments before a label, like
+    /// `# @arg a0: count`.
+    #[must_use]
+    pub fn annotations(&self, label: &str) -> &[Annotation] {
+        self.annotations.get(label).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the natural loops of the CFG, found with its dominator tree.
+    ///
+    /// Cycles that can be entered at more than one node are not loops, and
+    /// are reported as irreducible instead, see [`NaturalLoops`].
+    #[must_use]
+    pub fn natural_loops(&self) -> NaturalLoops {
+        NaturalLoops::new(self)
+    }
+
+    /// Get the dominator tree of the CFG.
+    ///
+    /// See [`DominatorTree`] for how the entries of functions are handled.
+    #[must_use]
+    pub fn dominator_tree(&self) -> DominatorTree {
+        DominatorTree::new(self)
+    }
+
+    /// Get a view of the CFG where calls continue into the functions they
+    /// call.
+    ///
+    /// See [`InterproceduralCfg`] for how calls and returns are connected.
+    #[must_use]
+    pub fn interprocedural_view(&self) -> InterproceduralCfg {
+        InterproceduralCfg::new(self)
+    }
+
+    /// Check if the CFG was built from a library with [`Cfg::new_library`].
+    #[must_use]
+    pub fn is_library(&self) -> bool {
+        self.library
+    }
+
+    /// Mark that the liveness analysis has filled in the live registers of
+    /// every node.
+    pub(crate) fn set_liveness_computed(&mut self) {
+        self.liveness_computed = true;
+    }
+
+    /// Get the registers that are live before a node.
+    ///
+    /// This returns the sets found by the liveness analysis, so the CFG must
+    /// come from [`Manager::gen_full_cfg`](crate::passes::Manager::gen_full_cfg).
+    /// Returns `None` if the analysis has not run, or if the node is not
+    /// part of this CFG.
+    #[must_use]
+    pub fn live_in_at(&self, node: &Rc<CfgNode>) -> Option<RegisterSet> {
+        self.analyzed_node(node).map(|node| node.live_in())
+    }
+
+    /// Get the registers that are live after a node.
+    ///
+    /// See [`Cfg::live_in_at`] for when this returns `None`.
+    #[must_use]
+    pub fn live_out_at(&self, node: &Rc<CfgNode>) -> Option<RegisterSet> {
+        self.analyzed_node(node).map(|node| node.live_out())
+    }
+
+    /// Get the node of a CFG if the liveness analysis has run on it.
+    fn analyzed_node<'a>(&self, node: &'a Rc<CfgNode>) -> Option<&'a Rc<CfgNode>> {
+        let in_cfg = self.nodes.iter().any(|x| Rc::ptr_eq(x, node));
+        (self.liveness_computed && in_cfg).then_some(node)
+    }
+
+    /// Get the first instruction that starts on a line of a file.
+    ///
+    /// Lines start at zero. Instructions that expand to many nodes, like
+    /// `lw a0, label`, return the first of them.
+    #[must_use]
+    pub fn node_at_line(&self, file: Uuid, line: usize) -> Option<Rc<CfgNode>> {
+        self.nodes
+            .iter()
+            .find(|x| {
+                let node = x.node();
+                node.is_instruction() && node.file() == file && node.range().start.line == line
+            })
+            .map(Rc::clone)
+    }
+
+    /// Connect jumps through a register to the labels they are known to go
+    /// to, like the arms of a jump table.
+    ///
+    /// See [`Cfg::indirect_jump_targets`] for how the targets are found.
+    /// This reads the available values of the nodes, so it must run after
+    /// the available value analysis. Calls through a register, like
+    /// `jalr t0`, are not connected, as they return to the next instruction.
+    pub(crate) fn connect_indirect_jumps(&self) {
+        for node in self {
+            if !node.node().is_unconditional_jump() {
+                continue;
+            }
+            let Some(targets) = self.indirect_jump_targets(&node) else {
+                continue;
+            };
+            for label in targets {
+                if let Some(target) = self.label_node_map.get(&label.0) {
+                    node.insert_next(Rc::clone(target));
+                    target.insert_prev(Rc::clone(&node));
+                }
+            }
+        }
+    }
+
+    /// Get the branches whose direction is known from the values of their
+    /// registers, along with whether each is taken.
+    ///
+    /// See [`CfgNode::known_branch_direction`] for when the direction is
+    /// known. Branches inside of a loop are left out, as a condition that
+    /// never changes inside a loop is a bug in the loop, which is reported
+    /// on its own.
+    #[must_use]
+    pub fn known_branches(&self) -> Vec<(Rc<CfgNode>, bool)> {
+        let known = self
+            .nodes()
+            .iter()
+            .filter_map(|node| {
+                node.known_branch_direction()
+                    .map(|taken| (Rc::clone(node), taken))
+            })
+            .collect::<Vec<_>>();
+        if known.is_empty() {
+            return known;
+        }
+        let natural = self.natural_loops();
+        known
+            .into_iter()
+            .filter(|(node, _)| !natural.loops().iter().any(|lp| lp.contains(node)))
+            .collect()
+    }
+
+    /// Remove the paths that branches with a known direction never take.
+    ///
+    /// See [`Cfg::known_branches`] for which branches these are. This reads
+    /// the available values of the nodes, so it must run after the available
+    /// value analysis. A branch to the instruction right after it is kept, as
+    /// both of its paths go to the same place.
+    pub(crate) fn prune_known_branches(&self) {
+        for (node, taken) in self.known_branches() {
+            let target = node
+                .node()
+                .jumps_to()
+                .and_then(|label| self.label_node_map.get(&label.data.0).cloned());
+            let fall_through = self
+                .nodes()
+                .iter()
+                .position(|other| Rc::ptr_eq(other, &node))
+                .and_then(|i| self.nodes().get(i + 1).cloned());
+            let (Some(target), Some(fall_through)) = (target, fall_through) else {
+                continue;
+            };
+            if Rc::ptr_eq(&target, &fall_through) {
+                continue;
+            }
+            let never = if taken { fall_through } else { target };
+            node.remove_next(&never);
+            never.remove_prev(&node);
+        }
+    }
 }
 
 impl<'a> IntoIterator for &'a Cfg {
@@ -82,7 +400,9 @@ trait BaseCfgGen {
     fn call_names(&self) -> HashSet<With<LabelString>>;
     fn jump_names(&self) -> HashSet<With<LabelString>>;
     fn label_names(&self) -> HashSet<With<LabelString>>;
-    fn load_names(&self) -> HashSet<With<LabelString>>;
+    fn label_uses(&self) -> Vec<With<LabelString>>;
+    fn root_names(&self) -> HashSet<With<LabelString>>;
+    fn data_symbols(&self) -> HashMap<String, DataSymbol>;
 }
 
 impl BaseCfgGen for Vec<ParserNode> {
@@ -98,12 +418,80 @@ impl BaseCfgGen for Vec<ParserNode> {
             .collect()
     }
 
-    fn load_names(&self) -> HashSet<With<LabelString>> {
+    /// Get every label that is called, jumped to, or has its address read,
+    /// at each place it is used.
+    fn label_uses(&self) -> Vec<With<LabelString>> {
         self.iter()
-            .filter_map(parser::ParserNode::reads_address_of)
+            .filter_map(|node| {
+                node.calls_to()
+                    .or_else(|| node.jumps_to())
+                    .or_else(|| node.reads_address_of())
+            })
             .collect()
     }
 
+    /// Get the labels of code that can only be entered from outside of the
+    /// file.
+    ///
+    /// These are the exported labels, along with labels in the text segment
+    /// that are never jumped to and that code does not fall into.
+    fn root_names(&self) -> HashSet<With<LabelString>> {
+        let jump_names = self.jump_names();
+        let mut roots = HashSet::new();
+        let mut segment = Segment::Text;
+        let mut falls_through = false;
+        for node in self {
+            match node {
+                ParserNode::Label(s) => {
+                    if segment == Segment::Text && !falls_through && !jump_names.contains(&s.name)
+                    {
+                        roots.insert(s.name.clone());
+                    }
+                }
+                ParserNode::Directive(x) if x.dir.segment().is_some() => {
+                    segment = x.dir.segment().unwrap_or(segment);
+                }
+                ParserNode::Directive(x) => {
+                    if let DirectiveType::Global(names) = &x.dir {
+                        roots.extend(names.iter().cloned());
+                    }
+                }
+                ParserNode::ProgramEntry(_) => {}
+                _ => falls_through = !(node.is_return() || node.is_unconditional_jump()),
+            }
+        }
+        roots
+    }
+
+    fn data_symbols(&self) -> HashMap<String, DataSymbol> {
+        let mut symbols = HashMap::new();
+        let mut current: Vec<DataSymbol> = Vec::new();
+        for node in self {
+            match node {
+                ParserNode::Label(s) => {
+                    // Labels with no data between them share the same data
+                    if current.iter().any(|symbol| symbol.size() > 0) {
+                        for symbol in current.drain(..) {
+                            symbols.insert(symbol.label().data.0.clone(), symbol);
+                        }
+                    }
+                    current.push(DataSymbol::new(s.name.clone()));
+                }
+                ParserNode::Directive(x) if current.iter_mut().all(|s| s.push(&x.dir)) => {}
+                _ => {
+                    for symbol in current.drain(..) {
+                        symbols.insert(symbol.label().data.0.clone(), symbol);
+                    }
+                }
+            }
+        }
+        for symbol in current {
+            symbols.insert(symbol.label().data.0.clone(), symbol);
+        }
+        symbols.retain(|_, symbol| symbol.size() > 0);
+        symbols
+    }
+
     fn label_names(&self) -> HashSet<With<LabelString>> {
         self.iter()
             .filter_map(|x| match x {
@@ -115,28 +503,39 @@ impl BaseCfgGen for Vec<ParserNode> {
 }
 impl Cfg {
     pub fn new(old_nodes: Vec<ParserNode>) -> Result<Cfg, Box<CfgError>> {
+        Self::build(old_nodes, false)
+    }
+
+    /// Create a CFG for a library, which is a file of functions that are
+    /// called from outside of it.
+    ///
+    /// A library has no single program entry. Instead, every label that can
+    /// only be entered from outside of the file, like a label after a return
+    /// or one exported with `.globl`, is the entry of a function.
+    pub fn new_library(old_nodes: Vec<ParserNode>) -> Result<Cfg, Box<CfgError>> {
+        Self::build(old_nodes, true)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn build(old_nodes: Vec<ParserNode>, library: bool) -> Result<Cfg, Box<CfgError>> {
         let mut labels = HashMap::new();
         let mut nodes = Vec::new();
         let mut current_labels = HashSet::new();
         let mut all_labels = HashSet::new();
+        let mut globals = HashSet::new();
+        let mut externs = HashSet::new();
+        let mut annotations = HashMap::new();
 
         let label_names = old_nodes.label_names();
+        let label_uses = old_nodes.label_uses();
         let call_names = old_nodes.call_names();
-        let jump_names = old_nodes.jump_names();
-        let load_names = old_nodes.load_names();
-
-        // Check if any call or jump names are not defined
-        let undefined_labels = call_names
-            .union(&jump_names)
-            .cloned()
-            .collect::<HashSet<_>>()
-            .union(&load_names)
-            .filter(|x| !label_names.contains(x))
-            .cloned()
-            .collect::<HashSet<With<LabelString>>>();
-
-        if !undefined_labels.is_empty() {
-            return Err(Box::new(CfgError::LabelsNotDefined(undefined_labels)));
+        let data_symbols = old_nodes.data_symbols();
+
+        // Labels that are called start a function. In a library, so does
+        // any label that can be entered from outside of the file.
+        let mut function_names = call_names;
+        if library {
+            function_names.extend(old_nodes.root_names());
         }
 
         // Code always begins in the text segment if it is not defined.
@@ -148,6 +547,9 @@ impl Cfg {
         for node in old_nodes {
             match node {
                 ParserNode::Label(s) => {
+                    if !s.annotations.is_empty() {
+                        annotations.insert(s.name.data.0.clone(), s.annotations);
+                    }
                     current_labels.insert(s.name.clone());
 
                     // Check for duplicate labels
@@ -155,19 +557,28 @@ impl Cfg {
                         return Err(Box::new(CfgError::DuplicateLabel(s.name)));
                     }
                 }
-                ParserNode::Directive(x) if x.dir == DirectiveType::DataSection => {
-                    segment = Segment::Data;
+                ParserNode::Directive(x) if x.dir.segment().is_some() => {
+                    segment = x.dir.segment().unwrap_or(segment);
                 }
-                ParserNode::Directive(x) if x.dir == DirectiveType::TextSection => {
-                    segment = Segment::Text;
+                ParserNode::Directive(x) => {
+                    // Exported labels are entry points, and external labels
+                    // are not reported as undefined. Other directives are
+                    // ignored
+                    match x.dir {
+                        DirectiveType::Global(names) => {
+                            globals.extend(names.into_iter().map(|name| name.data.0));
+                        }
+                        DirectiveType::Extern(names) => {
+                            externs.extend(names.into_iter().map(|name| name.data.0));
+                        }
+                        _ => {}
+                    }
                 }
-                // Ignore other types of directives
-                ParserNode::Directive(_) => {},
                 _ => {
                     // If any of the labels are a function call, add a function entry node
                     if current_labels
                         .clone()
-                        .intersection(&call_names)
+                        .intersection(&function_names)
                         .next()
                         .is_some()
                     {
@@ -209,10 +620,283 @@ impl Cfg {
             }
         }
 
+        // A label that is defined in the program is used instead of one of
+        // the same name outside of it
+        let defined = label_names
+            .iter()
+            .map(|label| label.data.0.as_str())
+            .collect::<HashSet<_>>();
+        externs.retain(|name| !defined.contains(name.as_str()));
+
+        // Uses of labels that are not defined are left out of the graph, and
+        // are reported by a lint. References to numeric local labels that
+        // are not defined are already reported by the parser.
+        let undefined_labels = label_uses
+            .into_iter()
+            .filter(|label| {
+                !label_names.contains(label)
+                    && !globals.contains(&label.data.0)
+                    && !externs.contains(&label.data.0)
+                    && label.data.local_reference().is_none()
+            })
+            .collect();
+
         Ok(Cfg {
             nodes,
             label_function_map: HashMap::new(),
             label_node_map: labels,
+            entry_points: globals.clone(),
+            globals,
+            externs,
+            undefined_labels,
+            xlen: Xlen::default(),
+            library,
+            data_symbols,
+            annotations,
+            liveness_computed: false,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, RegSets, Register};
+    use crate::passes::Manager;
+
+    const LOOP: &str = "\
+        main:                      \n\
+            li     t0, 0           \n\
+            li     t1, 10          \n\
+        loop:                      \n\
+            addi   t0, t0, 1       \n\
+            blt    t0, t1, loop    \n\
+            mv     a0, t0          \n\
+            addi   a7, zero, 10    \n\
+            ecall                  \n";
+
+    fn registers(set: Option<RegisterSet>) -> Vec<Register> {
+        set.unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn live_registers_in_loop() {
+        let (nodes, errors) = RVStringParser::parse_from_text(LOOP);
+        assert_eq!(errors.len(), 0);
+        let file = nodes[0].file();
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+
+        let add = cfg.node_at_line(file, 4).unwrap();
+        assert_eq!(add.node().to_string(), "addi t0 <- t0, 1");
+        assert_eq!(
+            registers(cfg.live_in_at(&add)),
+            vec![Register::X5, Register::X6]
+        );
+        assert_eq!(
+            registers(cfg.live_out_at(&add)),
+            vec![Register::X5, Register::X6]
+        );
+
+        let mv = cfg.node_at_line(file, 6).unwrap();
+        assert_eq!(registers(cfg.live_in_at(&mv)), vec![Register::X5]);
+        assert!(registers(cfg.live_out_at(&mv)).is_empty());
+
+        assert!(cfg.node_at_line(file, 3).is_none());
+        assert!(cfg.node_at_line(file, 20).is_none());
+    }
+
+    #[test]
+    fn live_registers_need_analysis() {
+        let (nodes, _) = RVStringParser::parse_from_text(LOOP);
+        let file = nodes[0].file();
+        let cfg = Cfg::new(nodes).unwrap();
+        let add = cfg.node_at_line(file, 4).unwrap();
+        assert!(cfg.live_in_at(&add).is_none());
+        assert!(cfg.live_out_at(&add).is_none());
+    }
+
+    const DOT_PRODUCT: &str = "\
+        main:                           \n\
+            jal      dot                \n\
+            fcvt.w.s a0, fa0, rtz       \n\
+            addi     a7, zero, 10       \n\
+            ecall                       \n\
+        dot:                            \n\
+            fmv.w.x  fa0, zero          \n\
+        loop:                           \n\
+            beqz     a2, done           \n\
+            flw      ft0, 0(a0)         \n\
+            flw      ft1, 0(a1)         \n\
+            fmadd.s  fa0, ft0, ft1, fa0 \n\
+            addi     a0, a0, 4          \n\
+            addi     a1, a1, 4          \n\
+            addi     a2, a2, -1         \n\
+            j        loop               \n\
+        done:                           \n\
+            ret                         \n";
+
+    #[test]
+    fn live_float_registers_in_loop() {
+        let (nodes, errors) = RVStringParser::parse_from_text(DOT_PRODUCT);
+        assert_eq!(errors.len(), 0);
+        let file = nodes[0].file();
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+
+        let fmadd = cfg.node_at_line(file, 11).unwrap();
+        assert_eq!(fmadd.node().to_string(), "fmadd.s fa0 <- ft0, ft1, fa0");
+        // Callee-saved registers are live until the function returns
+        let live_in = cfg.live_in_at(&fmadd).unwrap() - RegSets::callee_saved();
+        assert_eq!(
+            live_in.into_iter().collect::<Vec<_>>(),
+            vec![
+                Register::X10,
+                Register::X11,
+                Register::X12,
+                Register::F0,
+                Register::F1,
+                Register::F10
+            ]
+        );
+        let live_out = cfg.live_out_at(&fmadd).unwrap();
+        assert!(live_out.contains(&Register::F10));
+        assert!(!live_out.contains(&Register::F0));
+        assert!(!live_out.contains(&Register::F1));
+
+        // The sum is written before it is read, and is returned
+        let functions = cfg.functions();
+        let dot = functions.values().next().unwrap();
+        assert_eq!(
+            dot.arguments().into_iter().collect::<Vec<_>>(),
+            vec![Register::X10, Register::X11, Register::X12]
+        );
+        assert!(dot.returns().contains(&Register::F10));
+        assert!(!dot.returns().contains(&Register::X10));
+    }
+
+    #[test]
+    fn jump_table_arms_are_reachable() {
+        let input = "\
+            .data                      \n\
+            table:                     \n\
+                .word  case_a, case_b, case_c \n\
+            .text                      \n\
+            main:                      \n\
+                slli   t1, a0, 2       \n\
+                la     t0, table       \n\
+                add    t0, t0, t1      \n\
+                lw     t0, 0(t0)       \n\
+                jr     t0              \n\
+            case_a:                    \n\
+                li     a0, 1           \n\
+                j      end             \n\
+            case_b:                    \n\
+                li     a0, 2           \n\
+                j      end             \n\
+            case_c:                    \n\
+                li     a0, 3           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let file = nodes[0].file();
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+
+        let jump = cfg.node_at_line(file, 9).unwrap();
+        let mut arms = jump
+            .nexts()
+            .iter()
+            .map(|next| next.node().range().start.line)
+            .collect::<Vec<_>>();
+        arms.sort_unstable();
+        assert_eq!(arms, vec![11, 14, 17]);
+
+        // None of the arms are dead
+        for line in [11, 12, 14, 15, 17, 19] {
+            let node = cfg.node_at_line(file, line).unwrap();
+            assert!(!node.prevs().is_empty(), "line {line} is unreachable");
+        }
+    }
+
+    #[test]
+    fn extern_labels_are_recorded() {
+        let input = "\
+            .extern printf, helper     \n\
+            main:                      \n\
+                call   printf          \n\
+                call   helper          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            helper:                    \n\
+                ret                    \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let file = nodes[0].file();
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+
+        // The local definition of helper is used instead of the external one
+        assert_eq!(
+            cfg.externs().iter().collect::<Vec<_>>(),
+            vec![&"printf".to_string()]
+        );
+        assert!(cfg.undefined_labels().is_empty());
+        assert!(cfg.functions().keys().any(|label| label.data == "helper"));
+
+        // The code after the external call is not dead
+        let exit = cfg.node_at_line(file, 4).unwrap();
+        assert!(!exit.prevs().is_empty());
+    }
+
+    #[test]
+    fn function_with_two_labels_is_unique() {
+        let input = "\
+            main:                      \n\
+                jal    fn_b            \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+            fn_b:                      \n\
+                li     a0, 1           \n\
+                ret                    \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+
+        assert_eq!(cfg.functions().len(), 2);
+        let functions = cfg.unique_functions();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].labels().len(), 2);
+    }
+
+    #[test]
+    fn entry_points_are_roots_without_edges() {
+        let input = "\
+            .globl handler, fn_a       \n\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            handler:                   \n\
+                li     a0, 1           \n\
+                ret                    \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+
+        // fn_a is a function, so it is entered through calls
+        let roots = cfg.roots();
+        assert_eq!(roots.len(), 2);
+        assert!(roots[0].node().is_program_entry());
+        assert_eq!(roots[0].nexts().len(), 1);
+        assert_eq!(roots[1].node().token().text, "li a0 1");
+        assert!(roots[1].prevs().is_empty());
+        assert_eq!(roots[1].nexts().len(), 1);
+    }
+}
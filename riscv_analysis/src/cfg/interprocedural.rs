@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use super::{Cfg, CfgNode};
+
+/// A view of a CFG where calls continue into the functions they call.
+///
+/// In the CFG, a call continues at the instruction after it, and the return
+/// of a function has no nexts. In this view, a call continues at the entry of
+/// the function it calls, and the return of the function continues at the
+/// instruction after every call to it. All other edges are the same.
+///
+/// The nodes of a function are shared by all of its calls, so the values of
+/// different calls are merged, and recursion does not need to be expanded.
+/// The view does not change the CFG.
+pub struct InterproceduralCfg {
+    nodes: Vec<Rc<CfgNode>>,
+    nexts: HashMap<Uuid, Vec<Rc<CfgNode>>>,
+    prevs: HashMap<Uuid, Vec<Rc<CfgNode>>>,
+}
+
+impl InterproceduralCfg {
+    /// Create a new inter-procedural view of a CFG.
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        let index = cfg
+            .nodes()
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.node().id(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut nexts: HashMap<Uuid, Vec<Rc<CfgNode>>> = HashMap::new();
+        for node in cfg {
+            let successors = nexts.entry(node.node().id()).or_default();
+            if let Some((func, _)) = node.calls_to(cfg) {
+                successors.push(func.entry());
+                let exit = func.exit().node().id();
+                for ret in node.nexts().iter() {
+                    nexts.entry(exit).or_default().push(Rc::clone(ret));
+                }
+            } else {
+                successors.extend(node.nexts().iter().map(Rc::clone));
+            }
+        }
+
+        let mut prevs: HashMap<Uuid, Vec<Rc<CfgNode>>> = HashMap::new();
+        for node in cfg {
+            let successors = nexts.entry(node.node().id()).or_default();
+            successors.sort_by_key(|next| index.get(&next.node().id()).copied());
+            successors.dedup();
+            for next in successors.iter() {
+                prevs
+                    .entry(next.node().id())
+                    .or_default()
+                    .push(Rc::clone(&node));
+            }
+        }
+
+        InterproceduralCfg {
+            nodes: cfg.nodes().clone(),
+            nexts,
+            prevs,
+        }
+    }
+
+    /// Get the nodes of the view, in the order of the CFG.
+    #[must_use]
+    pub fn nodes(&self) -> &Vec<Rc<CfgNode>> {
+        &self.nodes
+    }
+
+    /// Get the nodes that a node continues at, in the order of the CFG.
+    #[must_use]
+    pub fn nexts(&self, node: &CfgNode) -> &[Rc<CfgNode>] {
+        self.nexts.get(&node.node().id()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the nodes that continue at a node, in the order of the CFG.
+    #[must_use]
+    pub fn prevs(&self, node: &CfgNode) -> &[Rc<CfgNode>] {
+        self.prevs.get(&node.node().id()).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    fn find(cfg: &Cfg, text: &str) -> Rc<CfgNode> {
+        cfg.nodes()
+            .iter()
+            .find(|node| node.node().token().text == text)
+            .map(Rc::clone)
+            .unwrap()
+    }
+
+    fn texts(nodes: &[Rc<CfgNode>]) -> Vec<String> {
+        nodes.iter().map(|node| node.node().token().text).collect()
+    }
+
+    #[test]
+    fn calls_continue_into_function() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                mv     s0, a0          \n\
+                jal    fn_a            \n\
+                add    a0, a0, s0      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     a0, 1           \n\
+                ret                    \n",
+        );
+        let view = cfg.interprocedural_view();
+        let func = cfg.functions().values().next().map(Rc::clone).unwrap();
+
+        let call = find(&cfg, "jal fn_a");
+        assert_eq!(view.nexts(&call).len(), 1);
+        assert_eq!(view.nexts(&call)[0], func.entry());
+        assert_eq!(view.prevs(&func.entry()).len(), 2);
+
+        let ret = find(&cfg, "ret");
+        assert_eq!(texts(view.nexts(&ret)), vec!["mv s0 a0", "add a0 a0 s0"]);
+        assert_eq!(texts(view.prevs(&find(&cfg, "mv s0 a0"))), vec!["ret"]);
+
+        // The CFG is not changed
+        assert!(ret.nexts().is_empty());
+    }
+
+    #[test]
+    fn recursion_shares_nodes() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a0, 5           \n\
+                jal    count           \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            count:                     \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                beqz   a0, done        \n\
+                addi   a0, a0, -1      \n\
+                jal    count           \n\
+            done:                      \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n",
+        );
+        let view = cfg.interprocedural_view();
+        assert_eq!(view.nodes().len(), cfg.nodes().len());
+
+        let ret = find(&cfg, "ret");
+        assert_eq!(texts(view.nexts(&ret)), vec!["li a7 10", "lw ra 0 ( sp )"]);
+    }
+}
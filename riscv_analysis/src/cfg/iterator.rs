@@ -1,5 +1,8 @@
 use crate::cfg::{Cfg, CfgNode};
-use std::{collections::HashSet, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 /// Iterate over all nodes in a CFG.
 pub struct CfgIterator<'a> {
@@ -226,10 +229,105 @@ impl Iterator for CfgPrevsIterator {
     }
 }
 
+/// Iterate over all CFG nodes in depth-first post-order, using the nexts.
+///
+/// In post-order, a node is visited after all of the nodes after it, except
+/// for the nodes it reaches through a back edge, like the jump at the end of a
+/// loop. Back edges are ignored during the search, so every cycle is
+/// traversed once. Reversing the iterator gives a reverse post-order, where a
+/// node comes before all of its nexts other than back edges. This is the
+/// order that forward dataflow analyses converge fastest in.
+///
+/// The search starts at the program entry. Nodes that are not reachable from
+/// it, like function entries, are searched afterwards in the order of the
+/// CFG, so every node is visited exactly once. Nexts are searched in the
+/// order of the CFG, so the result does not depend on hashing.
+pub struct CfgPostOrderIterator {
+    nodes: Vec<Rc<CfgNode>>,
+    start: usize,
+    end: usize,
+}
+
+impl CfgPostOrderIterator {
+    /// Create a new post-order iterator.
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        Self::from_roots(cfg, cfg.nodes())
+    }
+
+    /// Create a post-order iterator over the nodes that can be reached from
+    /// some roots, which are searched in the order they are given.
+    pub(crate) fn from_roots(cfg: &Cfg, roots: &[Rc<CfgNode>]) -> Self {
+        let index = cfg
+            .nodes()
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.node().id(), i))
+            .collect::<HashMap<_, _>>();
+        let sorted_nexts = |node: &Rc<CfgNode>| {
+            let mut nexts = node.nexts().iter().map(Rc::clone).collect::<Vec<_>>();
+            nexts.sort_by_key(|next| index.get(&next.node().id()).copied());
+            nexts
+        };
+
+        let mut visited = HashSet::new();
+        let mut order = Vec::with_capacity(cfg.nodes().len());
+        for root in roots {
+            if !visited.insert(root.node().id()) {
+                continue;
+            }
+
+            // Each entry is a node and the nexts that are left to search
+            let mut stack = vec![(Rc::clone(root), sorted_nexts(root).into_iter())];
+            while let Some((node, nexts)) = stack.last_mut() {
+                if let Some(next) = nexts.next() {
+                    if visited.insert(next.node().id()) {
+                        let next_nexts = sorted_nexts(&next).into_iter();
+                        stack.push((next, next_nexts));
+                    }
+                } else {
+                    order.push(Rc::clone(node));
+                    stack.pop();
+                }
+            }
+        }
+
+        Self {
+            end: order.len(),
+            nodes: order,
+            start: 0,
+        }
+    }
+}
+
+impl Iterator for CfgPostOrderIterator {
+    type Item = Rc<CfgNode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        let node = self.nodes.get(self.start).map(Rc::clone);
+        self.start += 1;
+        node
+    }
+}
+
+impl DoubleEndedIterator for CfgPostOrderIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        self.nodes.get(self.end).map(Rc::clone)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::cfg::Cfg;
+    use super::*;
     use crate::parser::RVStringParser;
+    use crate::passes::Manager;
 
     /// Generate the complete CFG from an input string.
     fn gen_cfg(input: &str) -> Cfg {
@@ -247,4 +345,73 @@ mod tests {
         iterator.next(); // There is a program entry node by default
         assert_eq!(iterator.next(), None);
     }
+
+    /// Get the position of every node in an order, by the text of the node.
+    fn positions(order: impl Iterator<Item = Rc<CfgNode>>) -> HashMap<String, usize> {
+        order
+            .enumerate()
+            .map(|(i, node)| (node.node().token().text, i))
+            .collect()
+    }
+
+    #[test]
+    fn reverse_post_order_of_diamond() {
+        let input = "\
+            main:                      \n\
+                beqz   a0, other       \n\
+                li     a1, 1           \n\
+                j      done            \n\
+            other:                     \n\
+                li     a1, 2           \n\
+            done:                      \n\
+                sw     a1, 0(sp)       \n\
+                ret                    \n";
+        let cfg = Manager::gen_full_cfg(RVStringParser::parse_from_text(input).0).unwrap();
+
+        let order = positions(cfg.reverse_post_order());
+        assert_eq!(order.len(), cfg.nodes().len());
+        assert_eq!(order["beqz a0 other"], 1);
+        assert!(order["beqz a0 other"] < order["li a1 1"]);
+        assert!(order["beqz a0 other"] < order["li a1 2"]);
+        assert!(order["j done"] < order["sw a1 0 ( sp )"]);
+        assert!(order["li a1 2"] < order["sw a1 0 ( sp )"]);
+        assert_eq!(order["ret"], order.len() - 1);
+    }
+
+    #[test]
+    fn post_order_of_loop() {
+        let input = "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, loop        \n\
+                ret                    \n";
+        let cfg = Manager::gen_full_cfg(RVStringParser::parse_from_text(input).0).unwrap();
+
+        // The back edge of the loop is ignored, so the loop body is visited
+        // before the branch at its end
+        let order = positions(cfg.reverse_post_order());
+        assert!(order["li t0 10"] < order["addi t0 t0 -1"]);
+        assert!(order["addi t0 t0 -1"] < order["bnez t0 loop"]);
+        assert!(order["bnez t0 loop"] < order["ret"]);
+
+        let post = cfg.post_order().collect::<Vec<_>>();
+        let mut reversed = cfg.reverse_post_order().collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(post, reversed);
+    }
+
+    #[test]
+    fn post_order_visits_unreachable_nodes() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                ret                    \n\
+            fn_a:                      \n\
+                li     a0, 1           \n\
+                ret                    \n";
+        let cfg = Manager::gen_full_cfg(RVStringParser::parse_from_text(input).0).unwrap();
+        assert_eq!(cfg.post_order().count(), cfg.nodes().len());
+    }
 }
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::analysis::AvailableValue;
+use crate::parser::{ArithType, LabelString, ParserNode, Register};
+
+use super::{Cfg, CfgNode};
+
+impl Cfg {
+    /// Get the labels that a jump through a register, like `jr t0`, can go
+    /// to.
+    ///
+    /// The target is known if the register holds the address of a label,
+    /// like after `la t0, label`, or if it is loaded from data that stores
+    /// the addresses of labels. For a jump table, like `.word case_a,
+    /// case_b`, indexed by a register that is added to the address of the
+    /// table, every label of the table is a target.
+    ///
+    /// Returns `None` if the node is not a jump through a register other than
+    /// `ra`, or if its target is not known.
+    #[must_use]
+    pub fn indirect_jump_targets(&self, node: &Rc<CfgNode>) -> Option<Vec<LabelString>> {
+        let ParserNode::JumpLinkR(jump) = node.node() else {
+            return None;
+        };
+        if !node.node().is_indirect_jump() {
+            return None;
+        }
+        match node.reg_values_in().get(&jump.rs1.data) {
+            Some(AvailableValue::Address(label)) if jump.imm.data.0 == 0 => {
+                return Some(vec![label.clone()]);
+            }
+            Some(AvailableValue::Memory(table, offset)) => {
+                let target = self.data_symbol(table)?.address_at(*offset)?;
+                return Some(vec![target.data.clone()]);
+            }
+            _ => {}
+        }
+
+        // The register is loaded from an unknown offset of a table, like
+        // `add t0, t0, t1` followed by `lw t0, 0(t0)`.
+        let load = defined_by(node, jump.rs1.data)?;
+        let ParserNode::Load(load_expr) = load.node() else {
+            return None;
+        };
+        let add = defined_by(&load, load_expr.rs1.data)?;
+        let ParserNode::Arith(add_expr) = add.node() else {
+            return None;
+        };
+        if add_expr.inst != ArithType::Add {
+            return None;
+        }
+        let values = add.reg_values_in();
+        let table = [add_expr.rs1.data, add_expr.rs2.data]
+            .iter()
+            .find_map(|reg| match values.get(reg) {
+                Some(AvailableValue::Address(label)) => Some(label.clone()),
+                _ => None,
+            })?;
+        let targets = self
+            .data_symbol(&table)?
+            .addresses()
+            .into_iter()
+            .map(|label| label.data.clone())
+            .collect::<Vec<_>>();
+        (!targets.is_empty()).then_some(targets)
+    }
+}
+
+/// Find the node that last stores to a register before a node.
+///
+/// Only straight-line code is searched, so this stops at any node with more
+/// or less than one previous node.
+fn defined_by(node: &Rc<CfgNode>, reg: Register) -> Option<Rc<CfgNode>> {
+    let mut current = Rc::clone(node);
+    let mut seen = HashSet::new();
+    loop {
+        let prev = {
+            let prevs = current.prevs();
+            if prevs.len() != 1 {
+                return None;
+            }
+            Rc::clone(prevs.iter().next()?)
+        };
+        if !seen.insert(prev.node().id()) {
+            return None;
+        }
+        if prev.node().stores_to().is_some_and(|rd| rd == reg) {
+            return Some(prev);
+        }
+        current = prev;
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use super::{Cfg, CfgNode, RegisterSet};
+
+/// A natural loop in the CFG, see [`NaturalLoops`].
+///
+/// The header of the loop dominates every node of the loop, and the latches
+/// are the nodes that jump back to it. The body of the loop is every node
+/// that can reach a latch without going through the header.
+#[derive(Debug, Clone)]
+pub struct Loop {
+    header: Rc<CfgNode>,
+    latches: Vec<Rc<CfgNode>>,
+    nodes: Vec<Rc<CfgNode>>,
+    ids: HashSet<Uuid>,
+}
+
+impl Loop {
+    fn new(cfg: &Cfg, header: Rc<CfgNode>, mut latches: Vec<Rc<CfgNode>>) -> Self {
+        let mut ids = HashSet::from([header.node().id()]);
+        let mut queue = latches.clone();
+        while let Some(node) = queue.pop() {
+            if ids.insert(node.node().id()) {
+                queue.extend(node.prevs().iter().map(Rc::clone));
+            }
+        }
+
+        let nodes = cfg
+            .nodes()
+            .iter()
+            .filter(|node| ids.contains(&node.node().id()))
+            .map(Rc::clone)
+            .collect::<Vec<_>>();
+        latches.sort_by_key(|latch| nodes.iter().position(|node| node == latch));
+        Loop {
+            header,
+            latches,
+            nodes,
+            ids,
+        }
+    }
+
+    /// Get the first node of the loop, which every iteration starts at.
+    #[must_use]
+    pub fn header(&self) -> Rc<CfgNode> {
+        Rc::clone(&self.header)
+    }
+
+    /// Get the nodes that jump back to the header.
+    #[must_use]
+    pub fn latches(&self) -> &Vec<Rc<CfgNode>> {
+        &self.latches
+    }
+
+    /// Get the nodes of the loop, including the header, in the order of the
+    /// CFG.
+    #[must_use]
+    pub fn nodes(&self) -> &Vec<Rc<CfgNode>> {
+        &self.nodes
+    }
+
+    /// Check if a node is part of the loop.
+    #[must_use]
+    pub fn contains(&self, node: &CfgNode) -> bool {
+        self.ids.contains(&node.node().id())
+    }
+
+    /// Get the registers that any node of the loop may write to.
+    ///
+    /// Calls and `ecall`s inside the loop may write to any caller-saved
+    /// register.
+    #[must_use]
+    pub fn writes(&self) -> RegisterSet {
+        self.nodes
+            .iter()
+            .fold(RegisterSet::new(), |acc, node| acc | node.node().defs())
+    }
+
+    /// Get the nodes of the loop that continue outside of it.
+    ///
+    /// These are the nodes with a next outside of the loop, along with
+    /// returns and program exits, which leave the loop without a next.
+    #[must_use]
+    pub fn exits(&self) -> Vec<Rc<CfgNode>> {
+        self.nodes
+            .iter()
+            .filter(|node| {
+                node.node().is_return()
+                    || node.is_program_exit()
+                    || node.nexts().iter().any(|next| !self.contains(next))
+            })
+            .map(Rc::clone)
+            .collect()
+    }
+}
+
+/// The natural loops of a CFG, see [`Cfg::natural_loops`].
+///
+/// A back edge is an edge to a node that dominates its source, and the
+/// loop of a back edge is the header and every node that can reach the back
+/// edge without going through the header. Back edges with the same header
+/// form one loop.
+///
+/// An edge that goes back to a node still being searched in a depth-first
+/// search, but whose target does not dominate its source, is part of a
+/// cycle that can be entered at more than one node. These cycles are not
+/// natural loops, so they are kept as irreducible edges instead.
+#[derive(Debug, Clone)]
+pub struct NaturalLoops {
+    loops: Vec<Loop>,
+    /// The innermost loop that contains each loop, by index.
+    parents: Vec<Option<usize>>,
+    irreducible: Vec<(Rc<CfgNode>, Rc<CfgNode>)>,
+}
+
+impl NaturalLoops {
+    /// Find the natural loops of a CFG.
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        let tree = cfg.dominator_tree();
+        let post_order = cfg
+            .post_order()
+            .enumerate()
+            .map(|(i, node)| (node.node().id(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut latches: HashMap<Uuid, Vec<Rc<CfgNode>>> = HashMap::new();
+        let mut irreducible = Vec::new();
+        for node in cfg {
+            // Cycles in code that cannot be reached are not checked
+            if !tree.is_reachable(&node) {
+                continue;
+            }
+            for next in node.nexts().iter() {
+                if tree.dominates(next, &node) {
+                    latches
+                        .entry(next.node().id())
+                        .or_default()
+                        .push(Rc::clone(&node));
+                } else if post_order.get(&next.node().id()) >= post_order.get(&node.node().id()) {
+                    irreducible.push((Rc::clone(&node), Rc::clone(next)));
+                }
+            }
+        }
+
+        let loops = cfg
+            .nodes()
+            .iter()
+            .filter_map(|header| {
+                let latches = latches.remove(&header.node().id())?;
+                Some(Loop::new(cfg, Rc::clone(header), latches))
+            })
+            .collect::<Vec<_>>();
+        let parents = loops
+            .iter()
+            .map(|inner| {
+                loops
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, outer)| {
+                        outer.header != inner.header && outer.contains(&inner.header)
+                    })
+                    .min_by_key(|(_, outer)| outer.nodes.len())
+                    .map(|(i, _)| i)
+            })
+            .collect();
+
+        NaturalLoops {
+            loops,
+            parents,
+            irreducible,
+        }
+    }
+
+    /// Get the loops, in the order of their headers.
+    #[must_use]
+    pub fn loops(&self) -> &[Loop] {
+        &self.loops
+    }
+
+    /// Get the innermost loop that contains a loop.
+    ///
+    /// Returns `None` if the loop is not nested in another loop.
+    #[must_use]
+    pub fn parent(&self, lp: &Loop) -> Option<&Loop> {
+        let i = self.index_of(lp)?;
+        let parent = self.parents.get(i).copied().flatten()?;
+        self.loops.get(parent)
+    }
+
+    /// Get the loops that are directly nested in a loop.
+    #[must_use]
+    pub fn children(&self, lp: &Loop) -> Vec<&Loop> {
+        let i = self.index_of(lp);
+        self.loops
+            .iter()
+            .zip(&self.parents)
+            .filter(|(_, parent)| i.is_some() && **parent == i)
+            .map(|(child, _)| child)
+            .collect()
+    }
+
+    /// Get the number of loops that contain a node.
+    #[must_use]
+    pub fn depth(&self, node: &CfgNode) -> usize {
+        self.loops.iter().filter(|lp| lp.contains(node)).count()
+    }
+
+    /// Get the edges of cycles that can be entered at more than one node, as
+    /// the source and the target of each edge.
+    #[must_use]
+    pub fn irreducible_edges(&self) -> &[(Rc<CfgNode>, Rc<CfgNode>)] {
+        &self.irreducible
+    }
+
+    /// Check if every cycle of the CFG is a natural loop.
+    #[must_use]
+    pub fn is_reducible(&self) -> bool {
+        self.irreducible.is_empty()
+    }
+
+    fn index_of(&self, lp: &Loop) -> Option<usize> {
+        self.loops
+            .iter()
+            .position(|other| other.header == lp.header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, Register};
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    fn texts(nodes: &[Rc<CfgNode>]) -> Vec<String> {
+        nodes.iter().map(|node| node.node().token().text).collect()
+    }
+
+    #[test]
+    fn simple_loop() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                addi   a0, a0, 1       \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, loop        \n\
+                ret                    \n",
+        );
+
+        let natural = cfg.natural_loops();
+        let loops = natural.loops();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].header().node().token().text, "addi a0 a0 1");
+        assert_eq!(texts(loops[0].latches()), vec!["bnez t0 loop"]);
+        assert_eq!(
+            texts(loops[0].nodes()),
+            vec!["addi a0 a0 1", "addi t0 t0 -1", "bnez t0 loop"]
+        );
+        assert!(loops[0].writes().contains(&Register::X5));
+        assert_eq!(texts(&loops[0].exits()), vec!["bnez t0 loop"]);
+    }
+
+    #[test]
+    fn nested_loops() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     t0, 10          \n\
+            outer:                     \n\
+                li     t1, 10          \n\
+            inner:                     \n\
+                addi   t1, t1, -1      \n\
+                bnez   t1, inner       \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, outer       \n\
+                ret                    \n",
+        );
+
+        let natural = cfg.natural_loops();
+        let loops = natural.loops();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].nodes().len(), 5);
+        assert_eq!(
+            texts(loops[1].nodes()),
+            vec!["addi t1 t1 -1", "bnez t1 inner"]
+        );
+        assert!(loops[1].nodes().iter().all(|node| loops[0].contains(node)));
+    }
+
+    #[test]
+    fn code_without_loops() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                beqz   a0, done        \n\
+                addi   a0, a0, 1       \n\
+            done:                      \n\
+                ret                    \n",
+        );
+
+        assert!(cfg.natural_loops().loops().is_empty());
+    }
+
+    #[test]
+    fn natural_loop() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                addi   a0, a0, 1       \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, loop        \n\
+                ret                    \n",
+        );
+
+        let natural = cfg.natural_loops();
+        assert!(natural.is_reducible());
+        assert_eq!(natural.loops().len(), 1);
+        let lp = &natural.loops()[0];
+        assert_eq!(lp.header().node().token().text, "addi a0 a0 1");
+        assert_eq!(texts(lp.latches()), vec!["bnez t0 loop"]);
+        assert_eq!(lp.nodes().len(), 3);
+        assert!(natural.parent(lp).is_none());
+        assert_eq!(natural.depth(&lp.header()), 1);
+    }
+
+    #[test]
+    fn nested_natural_loops() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     t0, 10          \n\
+            outer:                     \n\
+                li     t1, 10          \n\
+            inner:                     \n\
+                addi   t1, t1, -1      \n\
+                bnez   t1, inner       \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, outer       \n\
+                ret                    \n",
+        );
+
+        let natural = cfg.natural_loops();
+        let loops = natural.loops();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].header().node().token().text, "li t1 10");
+        assert_eq!(loops[1].header().node().token().text, "addi t1 t1 -1");
+        assert_eq!(
+            natural
+                .parent(&loops[1])
+                .map(|lp| lp.header().node().token().text),
+            Some("li t1 10".to_owned())
+        );
+        assert!(natural.parent(&loops[0]).is_none());
+        assert_eq!(natural.children(&loops[0]).len(), 1);
+        assert_eq!(natural.depth(&loops[1].header()), 2);
+    }
+
+    #[test]
+    fn irreducible_cycle() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     t0, 10          \n\
+                beqz   a0, second      \n\
+            first:                     \n\
+                addi   a0, a0, 1       \n\
+            second:                    \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, first       \n\
+                ret                    \n",
+        );
+
+        // The cycle can be entered at either label, so it is not a loop
+        let natural = cfg.natural_loops();
+        assert!(natural.loops().is_empty());
+        assert!(!natural.is_reducible());
+        let edges = natural.irreducible_edges();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].0.node().token().text, "bnez t0 first");
+        assert_eq!(edges[0].1.node().token().text, "addi a0 a0 1");
+    }
+}
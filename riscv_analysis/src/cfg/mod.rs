@@ -30,3 +30,20 @@ pub use register_set::*;
 
 mod available_value_map;
 pub use available_value_map::*;
+
+mod data_symbol;
+pub use data_symbol::*;
+
+mod loops;
+pub use loops::*;
+
+mod interprocedural;
+pub use interprocedural::*;
+
+mod call_graph;
+pub use call_graph::*;
+
+mod dominators;
+pub use dominators::*;
+
+mod jump_target;
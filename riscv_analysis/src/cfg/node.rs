@@ -108,16 +108,16 @@ impl CfgNode {
         self.node.borrow().clone()
     }
 
-    pub fn nexts(&self) -> Ref<HashSet<Rc<CfgNode>>> {
+    pub fn nexts(&self) -> Ref<'_, HashSet<Rc<CfgNode>>> {
         self.nexts.borrow()
     }
 
-    pub fn prevs(&self) -> Ref<HashSet<Rc<CfgNode>>> {
+    pub fn prevs(&self) -> Ref<'_, HashSet<Rc<CfgNode>>> {
         self.prevs.borrow()
     }
 
     /// Return the functions that this node belongs to.
-    pub fn functions(&self) -> Ref<HashSet<Rc<Function>>> {
+    pub fn functions(&self) -> Ref<'_, HashSet<Rc<Function>>> {
         self.function.borrow()
     }
 
@@ -192,14 +192,34 @@ impl CfgNode {
     }
 
     pub fn known_ecall(&self) -> Option<i32> {
-        if self.node().is_ecall() {
-            if let Some(AvailableValue::Constant(call_num)) =
-                self.reg_values_in().get(&Register::ecall_type())
-            {
-                return Some(*call_num);
-            }
+        if !self.node().is_ecall() {
+            return None;
         }
-        None
+        self.reg_values_in()
+            .resolved_constant(Register::ecall_type())
+            .and_then(|call_num| i32::try_from(call_num).ok())
+    }
+
+    /// Get whether a conditional branch is taken, if the values of both of
+    /// its registers are known.
+    ///
+    /// A value is only known if it is the same on every path to the branch.
+    /// Returns `None` if the node is not a branch, or if it compares a
+    /// register with itself, as the direction of that branch does not depend
+    /// on its values.
+    #[must_use]
+    pub fn known_branch_direction(&self) -> Option<bool> {
+        let node = self.node();
+        let ParserNode::Branch(branch) = &node else {
+            return None;
+        };
+        if node.is_unconditional_jump() || node.is_never_taken_branch() {
+            return None;
+        }
+        let values = self.reg_values_in();
+        let x = values.resolved_constant(branch.rs1.data)?;
+        let y = values.resolved_constant(branch.rs2.data)?;
+        Some(branch.inst.data.is_taken(x, y))
     }
 
     pub fn known_ecall_signature(&self) -> Option<(RegisterSet, RegisterSet)> {
@@ -215,6 +235,30 @@ impl CfgNode {
         self.known_ecall() == Some(10) || self.known_ecall() == Some(93)
     }
 
+    /// Check if this node stores the original value of a register to the
+    /// stack, like the saves at the start of a function.
+    pub fn is_stack_save(&self, reg: Register) -> bool {
+        let ParserNode::Store(store) = self.node() else {
+            return false;
+        };
+        store.rs2 == reg
+            && store.rs1 == Register::X2
+            && self.reg_values_in().get(&reg) == Some(&Self::original_value(reg))
+    }
+
+    /// Check if this node loads the original value of a register back into
+    /// it, like the restores at the end of a function.
+    pub fn is_stack_restore(&self, reg: Register) -> bool {
+        let ParserNode::Load(load) = self.node() else {
+            return false;
+        };
+        load.rd == reg && self.reg_values_out().get(&reg) == Some(&Self::original_value(reg))
+    }
+
+    fn original_value(reg: Register) -> AvailableValue {
+        AvailableValue::OriginalRegisterWithScalar(reg, 0)
+    }
+
     pub fn insert_next(&self, next: Rc<CfgNode>) {
         self.nexts.borrow_mut().insert(next);
     }
@@ -252,7 +296,7 @@ impl CfgNode {
 
     /// Return true if this node is part of a function.
     pub fn is_part_of_some_function(&self) -> bool {
-        return self.functions().len() > 0;
+        return !self.functions().is_empty();
     }
 
     pub fn labels(&self) -> HashSet<With<LabelString>> {
@@ -1,4 +1,4 @@
-use crate::parser::Inst;
+use crate::parser::{BranchType, Inst, Xlen};
 
 pub enum MathOp {
     Add,
@@ -26,15 +26,15 @@ impl Inst {
     #[must_use]
     pub fn math_op(self) -> Option<MathOp> {
         match self {
-            Inst::Add | Inst::Addi => Some(MathOp::Add),
+            Inst::Add | Inst::Addi | Inst::Addw | Inst::Addiw => Some(MathOp::Add),
             Inst::And | Inst::Andi => Some(MathOp::And),
             Inst::Or | Inst::Ori => Some(MathOp::Or),
-            Inst::Sll | Inst::Slli => Some(MathOp::Sll),
+            Inst::Sll | Inst::Slli | Inst::Sllw | Inst::Slliw => Some(MathOp::Sll),
             Inst::Slt | Inst::Slti => Some(MathOp::Slt),
             Inst::Sltu | Inst::Sltiu => Some(MathOp::Sltu),
-            Inst::Sra | Inst::Srai => Some(MathOp::Sra),
-            Inst::Srl | Inst::Srli => Some(MathOp::Srl),
-            Inst::Sub => Some(MathOp::Sub),
+            Inst::Sra | Inst::Srai | Inst::Sraw | Inst::Sraiw => Some(MathOp::Sra),
+            Inst::Srl | Inst::Srli | Inst::Srlw | Inst::Srliw => Some(MathOp::Srl),
+            Inst::Sub | Inst::Subw => Some(MathOp::Sub),
             Inst::Xor | Inst::Xori => Some(MathOp::Xor),
             Inst::Mul => Some(MathOp::Mul),
             Inst::Mulh => Some(MathOp::Mulh),
@@ -48,6 +48,30 @@ impl Inst {
         }
     }
 
+    /// Get the number of bits that the arithmetic of the instruction is
+    /// done in.
+    ///
+    /// The arithmetic on words that ends with `w` is done in 32 bits, and
+    /// the rest is done in the width of a register.
+    #[must_use]
+    pub fn arith_bits(self, xlen: Xlen) -> u32 {
+        match self {
+            Inst::Addw
+            | Inst::Sllw
+            | Inst::Sraw
+            | Inst::Srlw
+            | Inst::Subw
+            | Inst::Divw
+            | Inst::Remw
+            | Inst::Remuw
+            | Inst::Addiw
+            | Inst::Slliw
+            | Inst::Sraiw
+            | Inst::Srliw => 32,
+            _ => xlen.bits(),
+        }
+    }
+
     // To allow for scalar operations only, like stack manipulation
     #[must_use]
     pub fn scalar_op(self) -> Option<MathOp> {
@@ -60,61 +84,99 @@ impl Inst {
 }
 
 impl MathOp {
+    /// Perform the operation on the values of registers with `bits` bits.
+    ///
+    /// Values are sign extended from `bits` bits to 64 bits, like the
+    /// values of 32-bit operations on RV64. Registers wrap around on
+    /// overflow, and only the lower bits of a shift amount are used.
+    #[allow(clippy::cast_possible_truncation)]
     #[allow(clippy::cast_possible_wrap)]
     #[allow(clippy::cast_sign_loss)]
     #[must_use]
-    pub fn operate(&self, x: i32, y: i32) -> i32 {
-        match self {
+    pub fn operate(&self, x: i64, y: i64, bits: u32) -> i64 {
+        let (x, y) = (wrap(x.into(), bits), wrap(y.into(), bits));
+        let (x, y) = (i128::from(x), i128::from(y));
+        let mask = (1_i128 << bits) - 1;
+        let (ux, uy) = (x & mask, y & mask);
+        let shift = (y & i128::from(bits - 1)) as u32;
+        let result = match self {
             MathOp::Add => x + y,
             MathOp::And => x & y,
             MathOp::Or => x | y,
-            MathOp::Sll => x << y,
-            MathOp::Slt => i32::from(x < y),
-            MathOp::Sltu => i32::from((x as u32) < (y as u32)),
-            MathOp::Sra => x >> y,
-            MathOp::Srl => (x as u32 >> y) as i32,
+            MathOp::Sll => x << shift,
+            MathOp::Slt => i128::from(x < y),
+            MathOp::Sltu => i128::from(ux < uy),
+            MathOp::Sra => x >> shift,
+            MathOp::Srl => ux >> shift,
             MathOp::Sub => x - y,
             MathOp::Xor => x ^ y,
             MathOp::Mul => x * y,
-            MathOp::Mulh | MathOp::Mulhsu => {
-                let (x, y) = (i64::from(x), i64::from(y));
-                ((x * y) >> 32) as i32
-            }
-            MathOp::Mulhu => {
-                let (x, y) = (x as u64, y as u64);
-                ((x * y) >> 32) as i32
-            }
+            MathOp::Mulh | MathOp::Mulhsu => (x * y) >> bits,
+            MathOp::Mulhu => ((ux as u128 * uy as u128) >> bits) as i128,
             // NOTE: The RISC-V spec doesn't trap for integer division by zero,
             // instead, RISC-V returns the following results for x / 0 (or x % 0):
             // - div   -1
-            // - divu: 2^32 - 1
+            // - divu: 2^XLEN - 1
             // - rem:  x
             // - remu: x
-            MathOp::Div => {
-                match y {
-                    0 => -1,    // 2^32 - 1 as i32
-                    _ => x / y,
-                }
+            MathOp::Div => match y {
+                0 => -1,
+                _ => x / y,
             },
-            MathOp::Divu => {
-                match y {
-                    0 => -1,
-                    _ => (x as u32 / y as u32) as i32,
-                }
-
-            }
-            MathOp::Rem => {
-                match y {
-                    0 => x,
-                    _ => x % y,
-                }
+            MathOp::Divu => match uy {
+                0 => -1, // 2^XLEN - 1 as a signed value
+                _ => ux / uy,
             },
-            MathOp::Remu => {
-                match y {
-                    0 => x,
-                    _ => (x as u32 % y as u32) as i32,
-                }
-            }
+            MathOp::Rem => match y {
+                0 => x,
+                _ => x % y,
+            },
+            MathOp::Remu => match uy {
+                0 => x,
+                _ => ux % uy,
+            },
+        };
+        wrap(result, bits)
+    }
+
+    /// Get the exact result of an operation if it does not fit in `bits`
+    /// bits.
+    ///
+    /// This is the value that [`MathOp::operate`] wraps around. Only
+    /// addition, subtraction and multiplication are checked.
+    #[must_use]
+    pub fn overflow(&self, x: i64, y: i64, bits: u32) -> Option<i128> {
+        let (x, y) = (wrap(x.into(), bits), wrap(y.into(), bits));
+        let (x, y) = (i128::from(x), i128::from(y));
+        let exact = match self {
+            MathOp::Add => x + y,
+            MathOp::Sub => x - y,
+            MathOp::Mul => x * y,
+            _ => return None,
+        };
+        (i128::from(wrap(exact, bits)) != exact).then_some(exact)
+    }
+}
+
+/// Keep the lower `bits` bits of a value, sign extended to 64 bits.
+#[allow(clippy::cast_possible_truncation)]
+fn wrap(value: i128, bits: u32) -> i64 {
+    let unused = 128 - bits;
+    ((value << unused) >> unused) as i64
+}
+
+impl BranchType {
+    /// Check if a branch is taken for the values of its two registers.
+    #[allow(clippy::cast_sign_loss)]
+    #[must_use]
+    pub fn is_taken(self, x: i64, y: i64) -> bool {
+        match self {
+            BranchType::Beq => x == y,
+            BranchType::Bne => x != y,
+            BranchType::Blt => x < y,
+            BranchType::Bge => x >= y,
+            BranchType::Bltu => (x as u64) < (y as u64),
+            BranchType::Bgeu => (x as u64) >= (y as u64),
         }
     }
 }
@@ -123,19 +185,67 @@ impl MathOp {
 mod test {
     use super::MathOp;
 
-    #[allow(overflowing_literals)]
     #[test]
     fn bitwise() {
-        assert_eq!(MathOp::And.operate(0xABCD_EF01, 0x1234_5678), 0x0204_4600);
-        assert_eq!(MathOp::Or.operate(0xABCD_EF01, 0x1234_5678), 0xBBFD_FF79);
-        assert_eq!(MathOp::Xor.operate(0xABCD_EF01, 0x1234_5678), 0xb9f9_b979);
+        assert_eq!(
+            MathOp::And.operate(0xABCD_EF01, 0x1234_5678, 32),
+            0x0204_4600
+        );
+        assert_eq!(
+            MathOp::Or.operate(0xABCD_EF01, 0x1234_5678, 32),
+            -0x4402_0087
+        );
+        assert_eq!(
+            MathOp::Xor.operate(0xABCD_EF01, 0x1234_5678, 32),
+            -0x4606_4687
+        );
     }
 
     #[test]
     fn div_zero() {
-        assert_eq!(MathOp::Div.operate(12345678, 0), -1);
-        assert_eq!(MathOp::Divu.operate(12345678, 0), -1);
-        assert_eq!(MathOp::Rem.operate(12345678, 0), 12345678);
-        assert_eq!(MathOp::Remu.operate(12345678, 0), 12345678);
+        assert_eq!(MathOp::Div.operate(12_345_678, 0, 32), -1);
+        assert_eq!(MathOp::Divu.operate(12_345_678, 0, 32), -1);
+        assert_eq!(MathOp::Rem.operate(12_345_678, 0, 32), 12_345_678);
+        assert_eq!(MathOp::Remu.operate(12_345_678, 0, 32), 12_345_678);
+    }
+
+    #[test]
+    fn wraps_on_overflow() {
+        let (max, min) = (i32::MAX.into(), i32::MIN.into());
+        assert_eq!(MathOp::Add.operate(max, 1, 32), min);
+        assert_eq!(MathOp::Sub.operate(min, 1, 32), max);
+        assert_eq!(MathOp::Mul.operate(0x10000, 0x10000, 32), 0);
+        assert_eq!(MathOp::Div.operate(min, -1, 32), min);
+        assert_eq!(MathOp::Sll.operate(1, 33, 32), 2);
+        assert_eq!(MathOp::Mulhu.operate(-1, -1, 32), -2);
+    }
+
+    #[test]
+    fn wraps_at_64_bits() {
+        assert_eq!(MathOp::Add.operate(i32::MAX.into(), 1, 64), 0x8000_0000);
+        assert_eq!(MathOp::Add.operate(i64::MAX, 1, 64), i64::MIN);
+        assert_eq!(MathOp::Div.operate(i64::MIN, -1, 64), i64::MIN);
+        assert_eq!(MathOp::Sll.operate(1, 33, 64), 0x2_0000_0000);
+        assert_eq!(MathOp::Srl.operate(-1, 32, 64), 0xFFFF_FFFF);
+        assert_eq!(MathOp::Mulhu.operate(-1, -1, 64), -2);
+        // Word arithmetic only uses the lower 32 bits of its operands
+        assert_eq!(MathOp::Add.operate(0x1_0000_0001, 1, 32), 2);
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(MathOp::Add.overflow(i32::MAX.into(), 1, 32), Some(0x8000_0000));
+        assert_eq!(MathOp::Sub.overflow(i32::MIN.into(), 1, 32), Some(-0x8000_0001));
+        assert_eq!(
+            MathOp::Mul.overflow(0x10000, 0x10000, 32),
+            Some(0x1_0000_0000)
+        );
+        assert_eq!(MathOp::Add.overflow(i32::MAX.into(), 0, 32), None);
+        assert_eq!(MathOp::Sll.overflow(1, 31, 32), None);
+        assert_eq!(MathOp::Add.overflow(i32::MAX.into(), 1, 64), None);
+        assert_eq!(
+            MathOp::Add.overflow(i64::MAX, 1, 64),
+            Some(0x8000_0000_0000_0000)
+        );
     }
 }
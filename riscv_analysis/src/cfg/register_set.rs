@@ -9,16 +9,31 @@ use super::AvailableValueMap;
 
 /// A set of registers that are used in a basic block.
 ///
-/// This is currently limited to 32 registers as it is
-/// tied heavily to the RISC-V architecture. For future
-/// use cases, growing to u64 or u128 is a good option.
+/// The 32 integer registers and the 32 floating-point registers are kept
+/// in separate halves of the set, so `a0` and `fa0` are never confused.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RegisterSet {
     /// The registers that are used in the basic block.
-    /// The bit at index `i` is set if register `i` is used.
-    /// For example, the number 0x00000003 would indicate
-    /// that registers X0 and X1 are used.
-    registers: u32,
+    /// The bit at index `i` is set if integer register `i` is used, and
+    /// the bit at index `32 + i` is set if floating-point register `i` is
+    /// used. For example, the number 0x00000003 would indicate that
+    /// registers X0 and X1 are used.
+    registers: u64,
+}
+
+/// Get the bit of a register in a [`RegisterSet`].
+fn bit(register: Register) -> u64 {
+    let offset = if register.is_float() { 32 } else { 0 };
+    1 << (register.to_num() + offset)
+}
+
+/// Get the register at a bit index of a [`RegisterSet`].
+fn register_at(index: u8) -> Option<Register> {
+    if index < 32 {
+        Register::from_num(index).ok()
+    } else {
+        Register::from_float_num(index - 32).ok()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -52,21 +67,24 @@ impl RegisterSet {
 
     /// Set the given register in the set.
     pub fn set_register(&mut self, register: &Register) {
-        self.registers |= 1 << register.to_num();
+        self.registers |= bit(*register);
     }
 
     /// Unset the given register in the set.
     pub fn unset_register(&mut self, register: &Register) {
-        self.registers &= !(1 << register.to_num());
+        self.registers &= !bit(*register);
     }
 
     /// Check if the given register is set in the set.
     #[must_use]
     pub fn contains(&self, register: &Register) -> bool {
-        self.registers & (1 << register.to_num()) != 0
+        self.registers & bit(*register) != 0
     }
 
     /// Return a borrowed iterator.
+    ///
+    /// The integer registers are visited first, from `x0` to `x31`, then
+    /// the floating-point registers, from `f0` to `f31`.
     #[must_use]
     pub fn iter(&self) -> RegisterSetIter<'_> {
         self.into_iter()
@@ -78,6 +96,42 @@ impl RegisterSet {
         self.registers == 0
     }
 
+    /// Get the number of registers in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.registers.count_ones() as usize
+    }
+
+    /// Get the registers that are in either set. This is the same as
+    /// `self | other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    /// Get the registers that are in both sets. This is the same as
+    /// `self & other`.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    /// Get the registers that are in this set but not in `other`. This is
+    /// the same as `self - other`.
+    ///
+    /// For example, `live_out.difference(&defined)` is the set of registers
+    /// that are live out of a node but not defined by it.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    /// Check if every register in this set is also in `other`.
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
     /// Represent the set of registers as a map to available values, with
     /// all the registers set to their original value.
     #[must_use]
@@ -98,8 +152,7 @@ impl Iterator for RegisterSetIter<'_> {
     type Item = Register;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.current < 32 {
-            let register = Register::from_num(self.current).unwrap();
+        while let Some(register) = register_at(self.current) {
             self.current += 1;
             if self.registers.contains(&register) {
                 return Some(register);
@@ -142,14 +195,14 @@ impl BitAnd<Register> for RegisterSet {
 
     fn bitand(self, rhs: Register) -> Self::Output {
         Self {
-            registers: self.registers & (1 << rhs.to_num()),
+            registers: self.registers & bit(rhs),
         }
     }
 }
 
 impl BitAndAssign<Register> for RegisterSet {
     fn bitand_assign(&mut self, rhs: Register) {
-        self.registers &= 1 << rhs.to_num();
+        self.registers &= bit(rhs);
     }
 }
 
@@ -174,14 +227,14 @@ impl BitOr<Register> for RegisterSet {
 
     fn bitor(self, rhs: Register) -> Self::Output {
         RegisterSet {
-            registers: self.registers | (1 << rhs.to_num()),
+            registers: self.registers | bit(rhs),
         }
     }
 }
 
 impl BitOrAssign<Register> for RegisterSet {
     fn bitor_assign(&mut self, rhs: Register) {
-        self.registers |= 1 << rhs.to_num();
+        self.registers |= bit(rhs);
     }
 }
 
@@ -206,14 +259,14 @@ impl Sub<Register> for RegisterSet {
 
     fn sub(self, rhs: Register) -> Self::Output {
         Self {
-            registers: self.registers & !(1 << rhs.to_num()),
+            registers: self.registers & !bit(rhs),
         }
     }
 }
 
 impl SubAssign<Register> for RegisterSet {
     fn sub_assign(&mut self, rhs: Register) {
-        self.registers &= !(1 << rhs.to_num());
+        self.registers &= !bit(rhs);
     }
 }
 
@@ -290,7 +343,7 @@ mod tests {
     fn can_loop_in_order_of_registers() {
         let mut set = RegisterSet::new();
         set.set_register(&Register::X3);
-        set = set | Register::X1;
+        set |= Register::X1;
         set |= Register::X2;
         let mut set_iter = set.iter();
         assert_eq!(set_iter.next(), Some(Register::X1));
@@ -301,4 +354,77 @@ mod tests {
             "Set should only contain X1, X2, X3"
         );
     }
+
+    #[test]
+    fn integer_and_float_registers_are_separate() {
+        let mut set = RegisterSet::from_register(Register::X10);
+        assert!(!set.contains(&Register::F10));
+        set |= Register::F10;
+        set |= Register::F31;
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Register::X10, Register::F10, Register::F31]
+        );
+        set -= Register::X10;
+        assert!(set.contains(&Register::F10));
+        assert!(!set.contains(&Register::X10));
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: RegisterSet = [Register::X1, Register::X2, Register::F1]
+            .into_iter()
+            .collect();
+        let b: RegisterSet = [Register::X2, Register::X3].into_iter().collect();
+
+        assert_eq!(
+            a.union(&b).iter().collect::<Vec<_>>(),
+            vec![Register::X1, Register::X2, Register::X3, Register::F1]
+        );
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<_>>(),
+            vec![Register::X2]
+        );
+        assert_eq!(
+            a.difference(&b).iter().collect::<Vec<_>>(),
+            vec![Register::X1, Register::F1]
+        );
+        assert_eq!(
+            b.difference(&a).iter().collect::<Vec<_>>(),
+            vec![Register::X3]
+        );
+        assert_eq!(a.len(), 3);
+        assert!(a.intersection(&b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+    }
+
+    #[test]
+    fn set_algebra_with_empty_set() {
+        let empty = RegisterSet::new();
+        let a: RegisterSet = [Register::X5, Register::F5].into_iter().collect();
+
+        assert_eq!(a.union(&empty), a);
+        assert_eq!(a.intersection(&empty), empty);
+        assert_eq!(a.difference(&empty), a);
+        assert_eq!(empty.difference(&a), empty);
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_subset(&a));
+        assert!(empty.is_subset(&empty));
+    }
+
+    #[test]
+    fn set_algebra_with_full_set() {
+        let full = Register::all();
+        let a: RegisterSet = [Register::X31, Register::F31].into_iter().collect();
+
+        assert_eq!(full.len(), 64);
+        assert_eq!(full.iter().count(), 64);
+        assert_eq!(a.union(&full), full);
+        assert_eq!(a.intersection(&full), a);
+        assert_eq!(a.difference(&full), RegisterSet::new());
+        assert_eq!(full.difference(&a).len(), 62);
+        assert!(!full.difference(&a).contains(&Register::F31));
+        assert!(a.is_subset(&full));
+        assert!(!full.is_subset(&a));
+    }
 }
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /// Enum representing the different segments of a RISC-V binary.
 ///
 /// The segments are:
@@ -7,10 +9,68 @@
 /// All instructions must be in the `.text` segment, and all data
 /// must be in the `.data` segment. Jumping to instructions in
 /// the `.data` segment is highly unlikely.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Segment {
     /// The `.text` segment containing the instructions
     Text,
     /// The `.data` segment containing binary data
     Data,
 }
+
+impl Segment {
+    /// Get the segment that a section named with `.section` belongs to.
+    ///
+    /// Sections named `.text` or starting with `.text.`, like
+    /// `.text.startup`, hold instructions. All other sections, like
+    /// `.rodata`, `.bss` or unknown names, are treated as data.
+    #[must_use]
+    pub fn from_section_name(name: &str) -> Self {
+        let name = name.strip_prefix('.').unwrap_or(name);
+        if name == "text" || name.starts_with("text.") {
+            Segment::Text
+        } else {
+            Segment::Data
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    #[test]
+    fn section_names() {
+        assert_eq!(Segment::from_section_name(".text"), Segment::Text);
+        assert_eq!(Segment::from_section_name(".text.startup"), Segment::Text);
+        assert_eq!(Segment::from_section_name(".data"), Segment::Data);
+        assert_eq!(Segment::from_section_name(".rodata"), Segment::Data);
+        assert_eq!(Segment::from_section_name(".bss"), Segment::Data);
+        assert_eq!(Segment::from_section_name(".textual"), Segment::Data);
+        assert_eq!(Segment::from_section_name(".custom"), Segment::Data);
+    }
+
+    #[test]
+    fn nodes_placed_by_section() {
+        let input = "\
+            .section .rodata           \n\
+            msg: .word 1               \n\
+            .section .text.startup     \n\
+            main:                      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            .section .bss              \n\
+                addi   a0, zero, 1     \n";
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let segments = cfg
+            .nodes()
+            .iter()
+            .filter(|node| node.node().is_instruction())
+            .map(|node| node.segment())
+            .collect::<Vec<_>>();
+        assert_eq!(segments, vec![Segment::Text, Segment::Text, Segment::Data]);
+    }
+}
@@ -67,12 +67,12 @@ impl NodeWrapper {
                 cfg.iter()
                    .position(|other| func.entry().node().id() == other.node().id())
                    .unwrap()
-            }).collect::<Vec<_>>(),
+            }).sorted().collect::<Vec<_>>(),
             func_exit: node.functions().iter().map(|func| {
                 cfg.iter()
                    .position(|other| func.exit().node().id() == other.node().id())
                    .unwrap()
-            }).collect::<Vec<_>>(),
+            }).sorted().collect::<Vec<_>>(),
             nexts: node
                 .nexts()
                 .iter()
@@ -102,16 +102,30 @@ impl NodeWrapper {
     }
 }
 
+/// A serializable view of a CFG.
+///
+/// Nodes are listed in source order, and refer to each other by their
+/// index in the list, so the output is the same on every run.
 #[derive(Debug, Deserialize, Serialize, Eq, PartialEq)]
-pub struct CfgWrapper(Vec<NodeWrapper>);
+pub struct CfgWrapper {
+    /// The version of this format. It changes whenever the format does.
+    pub schema_version: u32,
+    pub nodes: Vec<NodeWrapper>,
+}
+
+impl CfgWrapper {
+    /// The current version of the format.
+    pub const SCHEMA_VERSION: u32 = 2;
+}
 
 impl From<&Cfg> for CfgWrapper {
     fn from(cfg: &Cfg) -> Self {
-        CfgWrapper(
-            cfg.iter()
-               .map(|x| NodeWrapper::from(&x, cfg))
-               .collect(),
-        )
+        CfgWrapper {
+            schema_version: Self::SCHEMA_VERSION,
+            nodes: cfg.iter()
+                      .map(|x| NodeWrapper::from(&x, cfg))
+                      .collect(),
+        }
     }
 }
 
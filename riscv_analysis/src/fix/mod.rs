@@ -1,9 +1,10 @@
+use std::collections::HashSet;
 use std::rc::Rc;
 
 use crate::{
-    cfg::{Cfg, Function},
+    cfg::{Cfg, CfgNode, Function},
     parser::{Position, Range},
-    passes::DiagnosticLocation,
+    passes::{DiagnosticLocation, TextEdit},
 };
 use itertools::Itertools;
 use std::fmt::Write;
@@ -12,43 +13,63 @@ use std::fmt::Write;
 ///
 /// On stack fix with an input function, we will:
 /// - insert stack updates to entry
-/// - find exit points of code, if there is one, insert stack updates
-///
-/// TODO if there are multiple exit points, convert to a single exit point by adding
-///   a label in between
+/// - insert stack updates before every return of the function
 pub enum Manipulation {
     /// Insert text at a given position
     ///
     /// (file, position, text, lines)
     Insert(uuid::Uuid, Position, String, usize),
+    /// Remove the line that starts at a given position
+    ///
+    /// (file, position)
+    RemoveLine(uuid::Uuid, Position),
 }
 
 impl Manipulation {
     #[must_use]
     pub fn line(&self) -> usize {
         match self {
-            Manipulation::Insert(_, pos, _, _) => pos.line,
+            Manipulation::Insert(_, pos, _, _) | Manipulation::RemoveLine(_, pos) => pos.line,
         }
     }
 
     #[must_use]
     pub fn column(&self) -> usize {
         match self {
-            Manipulation::Insert(_, pos, _, _) => pos.column,
+            Manipulation::Insert(_, pos, _, _) | Manipulation::RemoveLine(_, pos) => pos.column,
         }
     }
 
     #[must_use]
     pub fn raw_pos(&self) -> usize {
         match self {
-            Manipulation::Insert(_, pos, _, _) => pos.raw_index,
+            Manipulation::Insert(_, pos, _, _) | Manipulation::RemoveLine(_, pos) => pos.raw_index,
         }
     }
 
     #[must_use]
     pub fn file(&self) -> uuid::Uuid {
         match self {
-            Manipulation::Insert(file, _, _, _) => *file,
+            Manipulation::Insert(file, _, _, _) | Manipulation::RemoveLine(file, _) => *file,
+        }
+    }
+
+    /// Get the edit to the source that makes this change.
+    ///
+    /// Only insertions can be made into an edit. The end of a removed line
+    /// is not known without the source, so a removal returns `None`.
+    #[must_use]
+    pub fn text_edit(&self) -> Option<TextEdit> {
+        match self {
+            Manipulation::Insert(file, pos, text, _) => Some(TextEdit {
+                file: *file,
+                range: Range {
+                    start: *pos,
+                    end: *pos,
+                },
+                replacement: text.clone(),
+            }),
+            Manipulation::RemoveLine(..) => None,
         }
     }
 }
@@ -68,7 +89,6 @@ pub fn get_function_label_ranges(cfg: &Cfg) -> Vec<Range> {
 pub fn fix_stack(func: &Rc<Function>) -> Vec<Manipulation> {
     // go to the beginning of the function
     let entry = &func.entry();
-    let exit = &func.exit();
     // sorted to make the output nicer
     let regs = func.to_save().into_iter().sorted().collect_vec();
     let count = regs.len();
@@ -95,17 +115,203 @@ pub fn fix_stack(func: &Rc<Function>) -> Vec<Manipulation> {
 
     let offset = count + 4;
 
-    // Move range to beginning of line
-    let mut entry_range = entry.node().range().start;
-    entry_range.raw_index -= entry_range.column;
-    entry_range.column = 0;
+    let mut fixes = vec![Manipulation::Insert(
+        entry.node().file(),
+        insert_position(entry),
+        entry_text,
+        offset,
+    )];
 
-    let mut exit_range = exit.node().range().start;
-    exit_range.raw_index -= exit_range.column;
-    exit_range.column = 0;
+    // Restore before each return, so every path out of the function
+    // restores the registers
+    for exit in func.return_sites() {
+        fixes.push(Manipulation::Insert(
+            exit.node().file(),
+            insert_position(&exit),
+            exit_text.clone(),
+            offset,
+        ));
+    }
+    fixes
+}
+
+/// Get the position to insert code that runs right before a node.
+///
+/// This is the beginning of the line of the node. If a label is on the same
+/// line, it is the node itself, so that jumps to the label run the code.
+fn insert_position(node: &CfgNode) -> Position {
+    let mut start = node.node().range().start;
+    let after_label = node
+        .labels()
+        .iter()
+        .any(|label| label.file == node.node().file() && label.pos.start.line == start.line);
+    if !after_label {
+        start.raw_index -= start.column;
+        start.column = 0;
+    }
+    start
+}
+
+/// Remove the saves and restores of registers that a function already has.
+///
+/// This is used along with `fix_stack`, which saves every register that
+/// must be saved. Only the loads and stores are removed, so any space that
+/// the function makes on the stack is left as it is. Lines that also hold a
+/// label are kept.
+#[must_use]
+pub fn remove_stack_saves(func: &Rc<Function>) -> Vec<Manipulation> {
+    let regs = func.to_save();
+    let label_lines = func
+        .nodes()
+        .iter()
+        .flat_map(|node| node.labels())
+        .chain(func.labels())
+        .map(|label| (label.file, label.pos.start.line))
+        .collect::<HashSet<_>>();
+    func.nodes()
+        .iter()
+        .filter(|node| {
+            regs.iter()
+                .any(|reg| node.is_stack_save(reg) || node.is_stack_restore(reg))
+        })
+        .filter(|node| !label_lines.contains(&(node.node().file(), node.node().range().start.line)))
+        .map(|node| {
+            let mut start = node.node().range().start;
+            start.raw_index -= start.column;
+            start.column = 0;
+            Manipulation::RemoveLine(node.node().file(), start)
+        })
+        .unique_by(|fix| (fix.file(), fix.line()))
+        .collect()
+}
 
-    vec![
-        Manipulation::Insert(entry.node().file(), entry_range, entry_text, offset),
-        Manipulation::Insert(exit.node().file(), exit_range, exit_text, offset),
-    ]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lints::CalleeSavedRegisterCheck;
+    use crate::parser::RVStringParser;
+    use crate::passes::{LintPass, Manager};
+
+    fn function(input: &str, name: &str) -> Rc<Function> {
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let functions = cfg.functions();
+        let (_, func) = functions
+            .iter()
+            .find(|(label, _)| label.data == name)
+            .unwrap();
+        Rc::clone(func)
+    }
+
+    /// Apply the insertions of a fix to the source they were made for.
+    fn apply(input: &str, fixes: &[Manipulation]) -> String {
+        let mut output = input.to_string();
+        for fix in fixes.iter().sorted_by_key(|fix| fix.raw_pos()).rev() {
+            if let Manipulation::Insert(_, pos, text, _) = fix {
+                output.insert_str(pos.raw_index, text);
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn every_return_is_restored() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     s0, 1           \n\
+                beqz   a0, early       \n\
+                bltz   a0, done        \n\
+                add    a0, a0, s0      \n\
+                ret                    \n\
+            early:                     \n\
+                li     a0, 0           \n\
+                ret                    \n\
+            done: ret                  \n";
+
+        let func = function(input, "fn_a");
+        assert_eq!(func.return_sites().len(), 3);
+        let fixes = fix_stack(&func);
+        assert_eq!(fixes.len(), 4);
+        let restores = fixes
+            .iter()
+            .filter(|fix| {
+                matches!(fix, Manipulation::Insert(_, _, text, _) if text.contains("restore"))
+            })
+            .map(|fix| (fix.line(), fix.column()))
+            .collect_vec();
+        assert_eq!(restores, vec![(9, 0), (12, 0), (13, 6)]);
+
+        // Every path out of the function restores the register
+        let (nodes, _) = RVStringParser::parse_from_text(input);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        assert!(!CalleeSavedRegisterCheck
+            .run_single_pass_along_cfg(&cfg)
+            .is_empty());
+
+        // The edits insert the same text at the same positions
+        let edits = fixes
+            .iter()
+            .filter_map(Manipulation::text_edit)
+            .collect_vec();
+        assert_eq!(edits.len(), 4);
+        assert!(edits.iter().all(|edit| edit.range.start == edit.range.end));
+        assert_eq!(edits[0].range.start.line, 5);
+        assert!(edits[0].replacement.contains("sw s0, 0(sp)"));
+
+        let output = apply(input, &fixes);
+        let (nodes, errors) = RVStringParser::parse_from_text(&output);
+        assert_eq!(errors.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        assert!(CalleeSavedRegisterCheck
+            .run_single_pass_along_cfg(&cfg)
+            .is_empty());
+    }
+
+    #[test]
+    fn old_saves_and_restores_are_removed() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -8      \n\
+                sw     s0, 0(sp)       \n\
+                sw     a0, 4(sp)       \n\
+                li     s0, 1           \n\
+                add    a0, a0, s0      \n\
+                lw     s0, 0(sp)       \n\
+                addi   sp, sp, 8       \n\
+                ret                    \n";
+
+        let fixes = remove_stack_saves(&function(input, "fn_a"));
+        let mut lines = fixes.iter().map(Manipulation::line).collect_vec();
+        lines.sort_unstable();
+        assert_eq!(lines, vec![6, 10]);
+        assert!(fixes
+            .iter()
+            .all(|fix| matches!(fix, Manipulation::RemoveLine(..)) && fix.column() == 0));
+    }
+
+    #[test]
+    fn save_on_label_line_is_kept() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a: sw   s0, -4(sp)      \n\
+                li     s0, 1           \n\
+                add    a0, a0, s0      \n\
+                lw     s0, -4(sp)      \n\
+                ret                    \n";
+
+        let fixes = remove_stack_saves(&function(input, "fn_a"));
+        assert_eq!(fixes.iter().map(Manipulation::line).collect_vec(), vec![7]);
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::{
     cfg::Cfg,
     passes::{CfgError, GenerationPass},
@@ -11,12 +13,26 @@ impl GenerationPass for EliminateDeadCodeDirectionsPass {
         // Eliminate nexts and prevs for dead code
 
         let nodes = cfg.nodes();
+        let roots = cfg
+            .roots()
+            .iter()
+            .map(|root| root.node().id())
+            .collect::<HashSet<_>>();
         let mut changed = true;
         while changed {
             changed = false;
             let old = nodes.clone();
             for node in nodes {
-                if node.node().is_return() || node.node().is_any_entry() {
+                // Indirect jumps go somewhere, even if it is not known
+                // where, exits end the program on purpose, and roots are
+                // entered from outside of the program
+                if node.node().is_return()
+                    || node.node().is_trap_return()
+                    || node.node().is_any_entry()
+                    || roots.contains(&node.node().id())
+                    || node.node().is_indirect_jump()
+                    || node.is_program_exit()
+                {
                     continue;
                 }
                 // If the node has no nexts, remove it from the prevs of all its prevs
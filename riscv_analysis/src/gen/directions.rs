@@ -9,20 +9,26 @@ use crate::{
 ///
 /// This allows for easier (and required) traversal of the CFG.
 /// This must be run before most passes.
+///
+/// In a library, the program entry does not continue to the first line of
+/// code, as code is only entered through functions.
 pub struct NodeDirectionPass;
 impl GenerationPass for NodeDirectionPass {
     fn run(cfg: &mut Cfg) -> Result<(), Box<CfgError>> {
         let mut prev = None;
         for node in cfg.iter() {
             // If node jumps to another node, add it to the nexts of the current node and the prevs of the node it jumps to.
-            if let Some(label) = node.node().jumps_to() {
-                let jump_to_node = cfg
-                    .iter()
-                    .find(|n| n.labels.contains(&label))
-                    .ok_or_else(|| CfgError::UnexpectedError)?;
-
-                node.insert_next(Rc::clone(&jump_to_node));
-                jump_to_node.insert_prev(Rc::clone(&node));
+            // A branch that is never taken does not jump, and a jump to a
+            // label that is not defined leaves the program.
+            if let Some(label) = node
+                .node()
+                .jumps_to()
+                .filter(|_| !node.node().is_never_taken_branch())
+            {
+                if let Some(jump_to_node) = cfg.iter().find(|n| n.labels.contains(&label)) {
+                    node.insert_next(Rc::clone(&jump_to_node));
+                    jump_to_node.insert_prev(Rc::clone(&node));
+                }
             }
 
             // Linearly scan for nexts and prevs
@@ -32,7 +38,11 @@ impl GenerationPass for NodeDirectionPass {
             }
 
             // Set previous node to current node, if it is not a return
-            prev = if node.node().is_return() || node.node().is_unconditional_jump() {
+            prev = if node.node().is_return()
+                || node.node().is_trap_return()
+                || node.node().is_unconditional_jump()
+                || (cfg.is_library() && node.node().is_program_entry())
+            {
                 None
             } else {
                 Some(Rc::clone(&node))
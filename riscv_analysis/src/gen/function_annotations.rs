@@ -1,7 +1,7 @@
 use std::{rc::Rc, vec};
 
 use crate::{
-    cfg::{Cfg, CfgNode, Function, RegisterSet},
+    cfg::{Cfg, CfgNode, Function, RegisterSet, MERGED_RETURN_LABEL},
     parser::{Info, JumpLinkType, LabelString, ParserNode, Register, With},
     passes::{CfgError, DiagnosticLocation, GenerationPass},
 };
@@ -53,9 +53,10 @@ impl FunctionMarkupPass {
 
                     let inst = With::new(JumpLinkType::Jal, info.clone());
                     let rd = With::new(Register::X0, info.clone());
-                    let name = With::new(LabelString("__return__".to_string()), info.clone());
+                    let name =
+                        With::new(LabelString(MERGED_RETURN_LABEL.to_string()), info.clone());
                     let new_node =
-                        ParserNode::new_jump_link(inst, rd, name, prev_ret.node().token());
+                        ParserNode::new_jump_link(inst, rd, name, found_ret.node().token());
                     found_ret.set_node(new_node);
                 }
                 // If this is the first return node, save it
@@ -87,6 +88,16 @@ impl GenerationPass for FunctionMarkupPass {
                 continue;
             }
 
+            // Labels in a library are only guessed to be functions, so the
+            // ones that never return are not treated as functions.
+            if cfg.is_library()
+                && !cfg
+                    .iter_nexts(Rc::clone(&entry))
+                    .any(|node| node.node().is_return())
+            {
+                continue;
+            }
+
             // Get the labels for the entry block
             let labels = entry.labels().iter().cloned().collect::<Vec<_>>();
 
@@ -98,6 +109,13 @@ impl GenerationPass for FunctionMarkupPass {
                 Rc::clone(&entry),
             ));
 
+            let mut annotations = labels
+                .iter()
+                .flat_map(|label| cfg.annotations(&label.data.0).iter().cloned())
+                .collect::<Vec<_>>();
+            annotations.sort_by_key(|annotation| annotation.reg.range());
+            func.set_annotations(annotations);
+
             for label in &labels {
                 cfg.insert_function(label.clone(), Rc::clone(&func));
             }
@@ -141,7 +159,7 @@ mod tests {
         let funcs = cfg.functions();
         funcs
             .iter()
-            .map(|both| (both.0.data.0.clone(), both.1.clone()))
+            .map(|both| (both.0.data.0.clone(), Rc::clone(both.1)))
             .collect()
     }
 
@@ -309,6 +327,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tail_call() {
+        // `fn_a` returns through `fn_b`, which it tail calls
+        let input = "\
+            main:                       \n\
+                jal     fn_a            \n\
+                jal     fn_b            \n\
+                addi    a7, zero, 10    \n\
+                ecall                   \n\
+            fn_a:                       \n\
+                addi    a0, a0, 1       \n\
+                tail    fn_b            \n\
+            fn_b:                       \n\
+                addi    a0, a0, 2       \n\
+                ret                     \n";
+
+        let cfg = gen_cfg(input);
+        let funcs = function_map(&cfg);
+
+        assert_eq!(funcs.len(), 2);
+        assert_eq!(
+            function_tokens(&funcs["fn_a"]),
+            HashSet::from([
+                "addi a0 a0 1".to_string(),
+                "tail fn_b".to_string(),
+                "addi a0 a0 2".to_string(),
+                "ret".to_string(),
+            ])
+        );
+        assert_eq!(funcs["fn_a"].exit().node().token().text, "ret");
+
+        // The tail call is a jump, so the argument stays live across it
+        let tail = funcs["fn_a"]
+            .nodes()
+            .iter()
+            .find(|node| node.node().is_tail_call())
+            .map(Rc::clone)
+            .unwrap();
+        assert!(tail.live_out().contains(&crate::parser::Register::X10));
+    }
+
     #[test]
     fn interleaved_source() {
         // Two functions have interleaved sources, but share no code
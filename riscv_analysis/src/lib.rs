@@ -1,7 +1,7 @@
 #![deny(clippy::all, clippy::pedantic, clippy::cargo)]
 #![deny(
     clippy::try_err,
-    clippy::string_to_string,
+    clippy::implicit_clone,
     clippy::string_slice,
     clippy::shadow_unrelated,
     clippy::unseparated_literal_suffix,
@@ -25,6 +25,18 @@
 #![allow(clippy::multiple_crate_versions)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::missing_errors_doc)]
+#![cfg_attr(
+    test,
+    allow(
+        clippy::indexing_slicing,
+        clippy::expect_used,
+        clippy::shadow_unrelated,
+        clippy::use_debug
+    )
+)]
+
+mod analyze;
+pub use analyze::*;
 
 pub mod analysis;
 pub mod cfg;
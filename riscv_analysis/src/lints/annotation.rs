@@ -0,0 +1,178 @@
+use std::rc::Rc;
+
+use crate::{
+    cfg::Cfg,
+    parser::AnnotationKind,
+    passes::{DiagnosticLocation, LintError, LintPass},
+};
+
+/// A lint to find functions whose annotations do not match how they use
+/// registers.
+///
+/// Annotations are comments like `# @arg a0: count` or `# @returns a0: sum`
+/// before the label of a function. If a function has any annotations, they
+/// are taken to list every argument that it takes, so reading any other
+/// argument register before setting it is reported. A register declared
+/// with `@returns` that is not set on every path to the return is also
+/// reported.
+///
+/// Functions without annotations are not checked.
+pub struct AnnotationCheck;
+impl LintPass for AnnotationCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::ANNOTATION_MISMATCH.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for func in &cfg.unique_functions() {
+            if func.annotations().is_empty() {
+                continue;
+            }
+
+            let usage = func.usage_summary();
+            let undeclared = usage.arguments_read - func.declared_registers(AnnotationKind::Arg);
+            let mut reads = undeclared
+                .iter()
+                .flat_map(|reg| Cfg::error_ranges_for_first_usage(&func.entry(), reg))
+                .collect::<Vec<_>>();
+            reads.sort_by_key(DiagnosticLocation::range);
+            for read in reads {
+                errors.push(LintError::AnnotationMismatch(
+                    read,
+                    Rc::clone(func),
+                    AnnotationKind::Arg,
+                ));
+            }
+
+            for annotation in func.annotations().iter() {
+                if annotation.kind == AnnotationKind::Returns
+                    && !usage.returns_written.contains(&annotation.reg.data)
+                {
+                    errors.push(LintError::AnnotationMismatch(
+                        annotation.reg.clone(),
+                        Rc::clone(func),
+                        AnnotationKind::Returns,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, Register};
+    use crate::passes::{DiagnosticMessage, Manager};
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    #[test]
+    fn annotations_are_kept_on_function() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                jal    add             \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            # Add two numbers          \n\
+            # @arg a0: first           \n\
+            # @arg a1: second          \n\
+            # @arg count               \n\
+            # @returns a0: sum         \n\
+            add:                       \n\
+                add    a0, a0, a1      \n\
+                ret                    \n",
+        );
+        let func = cfg.functions().values().next().map(Rc::clone).unwrap();
+        let annotations = func.annotations();
+        // The malformed annotation is ignored
+        assert_eq!(annotations.len(), 3);
+        assert_eq!(annotations[0].kind, AnnotationKind::Arg);
+        assert_eq!(annotations[0].reg.data, Register::X10);
+        assert_eq!(annotations[0].description, "first");
+        assert_eq!(annotations[1].reg.data, Register::X11);
+        assert_eq!(annotations[2].kind, AnnotationKind::Returns);
+        assert_eq!(annotations[2].description, "sum");
+
+        assert_eq!(AnnotationCheck.run_single_pass_along_cfg(&cfg).len(), 0);
+    }
+
+    #[test]
+    fn undeclared_argument_is_reported() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                li     a2, 3           \n\
+                jal    add             \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            # @arg a0: first           \n\
+            # @arg a1: second          \n\
+            add:                       \n\
+                add    a0, a0, a1      \n\
+                add    a0, a0, a2      \n\
+                ret                    \n",
+        );
+        let lints = AnnotationCheck.run_single_pass_along_cfg(&cfg);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::AnnotationMismatch(reg, func, AnnotationKind::Arg)
+                if reg.data == Register::X12 && func.name().0 == "add"
+        ));
+        assert_eq!(lints[0].range().start.line, 11);
+        assert!(lints[0].long_description().contains("a0, a1"));
+    }
+
+    #[test]
+    fn unset_return_is_reported() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    get             \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            # @returns a0: value       \n\
+            # @returns a1: other       \n\
+            get:                       \n\
+                li     a0, 1           \n\
+                ret                    \n",
+        );
+        let lints = AnnotationCheck.run_single_pass_along_cfg(&cfg);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::AnnotationMismatch(reg, _, AnnotationKind::Returns)
+                if reg.data == Register::X11
+        ));
+        // The diagnostic points at the register in the comment
+        assert_eq!(lints[0].range().start.line, 5);
+        assert_eq!(lints[0].range().start.column, 11);
+    }
+
+    #[test]
+    fn comment_not_before_label_is_not_kept() {
+        let cfg = gen_cfg(
+            "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            # @arg a0: unused          \n\
+                nop                    \n\
+            fn_a:                      \n\
+                add    a0, a0, a1      \n\
+                ret                    \n",
+        );
+        let func = cfg.functions().values().next().map(Rc::clone).unwrap();
+        assert!(func.annotations().is_empty());
+        assert_eq!(AnnotationCheck.run_single_pass_along_cfg(&cfg).len(), 0);
+    }
+}
@@ -0,0 +1,173 @@
+use std::rc::Rc;
+
+use crate::{
+    cfg::Cfg,
+    parser::Register,
+    passes::{DiagnosticLocation, LintError, LintPass},
+};
+
+/// The integer argument registers, `a0` to `a7`, in the order that
+/// arguments are passed in.
+const INT_ARGUMENTS: [Register; 8] = [
+    Register::X10,
+    Register::X11,
+    Register::X12,
+    Register::X13,
+    Register::X14,
+    Register::X15,
+    Register::X16,
+    Register::X17,
+];
+
+/// The floating-point argument registers, `fa0` to `fa7`, in the order that
+/// arguments are passed in.
+const FLOAT_ARGUMENTS: [Register; 8] = [
+    Register::F10,
+    Register::F11,
+    Register::F12,
+    Register::F13,
+    Register::F14,
+    Register::F15,
+    Register::F16,
+    Register::F17,
+];
+
+/// A lint to find functions that read an argument register past the
+/// arguments they take.
+///
+/// Arguments are passed in order, so the arguments of a function are the
+/// first argument registers that it reads before setting. If a function
+/// reads `a0`, `a1` and `a3`, it takes two arguments, and `a3` is probably
+/// an argument that its callers do not pass, or a register that it should
+/// have set first.
+///
+/// A function that takes a variable number of arguments cannot be told
+/// apart from one with a missing argument, so this is only a warning.
+pub struct ArgumentArityCheck;
+impl LintPass for ArgumentArityCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::ARGUMENT_BEYOND_ARITY.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for func in &cfg.unique_functions() {
+            let read = func.usage_summary().arguments_read;
+            for order in [INT_ARGUMENTS, FLOAT_ARGUMENTS] {
+                let arguments = order
+                    .into_iter()
+                    .take_while(|reg| read.contains(reg))
+                    .collect::<Vec<_>>();
+                let mut reads = order
+                    .iter()
+                    .skip(arguments.len())
+                    .filter(|reg| read.contains(reg))
+                    .flat_map(|reg| Cfg::error_ranges_for_first_usage(&func.entry(), *reg))
+                    .collect::<Vec<_>>();
+                reads.sort_by_key(DiagnosticLocation::range);
+                for reg in reads {
+                    errors.push(LintError::ArgumentBeyondArity(
+                        reg,
+                        Rc::clone(func),
+                        arguments.clone(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticMessage, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        ArgumentArityCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn argument_after_gap() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                add    a0, a0, a1      \n\
+                add    a0, a0, a3      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::ArgumentBeyondArity(reg, func, arguments)
+                if reg.data == Register::X13
+                    && func.name().0 == "fn_a"
+                    && arguments == &vec![Register::X10, Register::X11]
+        ));
+        assert_eq!(lints[0].range().start.line, 8);
+        assert!(lints[0].long_description().contains("only takes a0, a1"));
+    }
+
+    #[test]
+    fn arguments_in_order() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                li     a2, 3           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                add    a0, a0, a1      \n\
+                add    a0, a0, a2      \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn argument_set_before_read() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     a3, 4           \n\
+                add    a0, a0, a3      \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn float_arguments_are_counted_apart() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                fadd.s fa0, fa0, fa2   \n\
+                add    a0, a0, a0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::ArgumentBeyondArity(reg, _, arguments)
+                if reg.data == Register::F12 && arguments == &vec![Register::F10]
+        ));
+    }
+}
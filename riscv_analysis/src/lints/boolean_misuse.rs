@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    cfg::{Cfg, CfgNode, RegisterSet},
+    parser::{ArithType, IArithType, ParserNode, RegSets, Register, With},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find comparison results that are used like numbers.
+///
+/// The set instructions (`slt`, `sltu`, `slti`, `sltiu` and the pseudo
+/// instructions that use them, like `seqz` and `snez`) set a register to
+/// either 0 or 1. Using this result as the base of a memory access, as the
+/// target of a jump, or as a shift amount is almost always a mistake, such
+/// as using the comparison result instead of the values that were compared.
+///
+/// This is a heuristic, so the lint is only a hint. It can be turned off
+/// like any other lint, with
+/// [`crate::passes::ManagerConfiguration::lint_levels`].
+pub struct BooleanMisuseCheck;
+impl LintPass for BooleanMisuseCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::BOOLEAN_MISUSE.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let booleans_in = Self::booleans_in(cfg);
+        for node in cfg {
+            let Some(booleans) = booleans_in.get(&node.node().id()) else {
+                continue;
+            };
+            if let Some(reg) = Self::suspicious_use(&node.node()) {
+                if booleans.contains(&reg.data) {
+                    errors.push(LintError::BooleanMisuse(reg));
+                }
+            }
+        }
+    }
+}
+
+impl BooleanMisuseCheck {
+    /// Get the register of a node that should not hold a comparison result.
+    fn suspicious_use(node: &ParserNode) -> Option<With<Register>> {
+        match node {
+            ParserNode::Load(expr) => Some(expr.rs1.clone()),
+            ParserNode::Store(expr) => Some(expr.rs1.clone()),
+            ParserNode::JumpLinkR(expr) => Some(expr.rs1.clone()),
+            ParserNode::Arith(expr) => match expr.inst.data {
+                ArithType::Sll
+                | ArithType::Sllw
+                | ArithType::Srl
+                | ArithType::Srlw
+                | ArithType::Sra
+                | ArithType::Sraw => Some(expr.rs2.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Check if a node always sets its destination to either 0 or 1.
+    fn makes_boolean(node: &ParserNode, booleans: RegisterSet) -> bool {
+        match node {
+            ParserNode::Arith(expr) => match expr.inst.data {
+                ArithType::Slt | ArithType::Sltu => true,
+                ArithType::And | ArithType::Or | ArithType::Xor => {
+                    booleans.contains(&expr.rs1.data) && booleans.contains(&expr.rs2.data)
+                }
+                _ => false,
+            },
+            ParserNode::IArith(expr) => match expr.inst.data {
+                IArithType::Slti | IArithType::Sltiu => true,
+                // Negating a comparison result, like `xori t0, t0, 1`
+                IArithType::Xori => booleans.contains(&expr.rs1.data) && expr.imm.data.0 == 1,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Compute the registers that hold a comparison result going into every
+    /// node.
+    ///
+    /// A register only holds a comparison result at a node if it does along
+    /// every previous path that has been visited.
+    fn booleans_in(cfg: &Cfg) -> HashMap<Uuid, RegisterSet> {
+        let mut ins: HashMap<Uuid, RegisterSet> = HashMap::new();
+        let mut outs: HashMap<Uuid, RegisterSet> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in cfg {
+                // in[n] = AND out[p] for all p in prev[n]
+                let in_n = node
+                    .prevs()
+                    .iter()
+                    .filter_map(|prev| outs.get(&prev.node().id()).copied())
+                    .reduce(|acc, out| acc & out)
+                    .unwrap_or_default();
+
+                let out_n = Self::transfer(&node, in_n);
+                if outs.get(&node.node().id()) != Some(&out_n) {
+                    changed = true;
+                    outs.insert(node.node().id(), out_n);
+                }
+                ins.insert(node.node().id(), in_n);
+            }
+        }
+        ins
+    }
+
+    /// Compute the registers that hold a comparison result after a node.
+    fn transfer(node: &CfgNode, booleans_in: RegisterSet) -> RegisterSet {
+        let mut killed = node.node().kill_reg_value();
+        if node.node().is_ecall() {
+            killed |= RegSets::caller_saved();
+        }
+
+        let mut out = booleans_in - killed;
+        if let Some(rd) = node.node().stores_to() {
+            if rd != Register::X0 && Self::makes_boolean(&node.node(), booleans_in) {
+                out |= rd.data;
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        BooleanMisuseCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn comparison_used_as_address() {
+        let input = "\
+            main:                      \n\
+                slt    t0, a0, a1      \n\
+                lw     t1, 0(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(&lints[0], LintError::BooleanMisuse(reg) if reg.data == Register::X5));
+    }
+
+    #[test]
+    fn comparison_used_as_shift_amount() {
+        let input = "\
+            main:                      \n\
+                seqz   t0, a0          \n\
+                sll    a0, a1, t0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn negated_comparison_used_as_address() {
+        let input = "\
+            main:                      \n\
+                slt    t0, a0, a1      \n\
+                xori   t0, t0, 1       \n\
+                sw     a0, 4(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn comparison_used_as_value() {
+        let input = "\
+            main:                      \n\
+                sltu   t0, a0, a1      \n\
+                add    a0, a0, t0      \n\
+                sw     t0, 0(sp)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn comparison_overwritten_on_one_path() {
+        let input = "\
+            main:                      \n\
+                slt    t0, a0, a1      \n\
+                beqz   a2, skip        \n\
+                la     t0, main        \n\
+            skip:                      \n\
+                lw     t1, 0(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
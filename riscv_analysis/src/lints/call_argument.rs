@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::{
+    cfg::{Cfg, CfgNode, Function},
+    parser::{LabelString, RegSets, Register, With},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find argument registers that are set up for a call, but that
+/// the called function never reads.
+///
+/// The arguments of a function are the argument registers that are live at
+/// its entry. If a value written to an argument register is only ever
+/// passed to calls of functions that do not read it, the value is unused.
+/// This is often a sign that the function is called with the wrong number
+/// of arguments.
+pub struct UnusedCallArgumentCheck;
+impl LintPass for UnusedCallArgumentCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNUSED_CALL_ARGUMENT.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            let Some(rd) = node.node().stores_to() else {
+                continue;
+            };
+            if !RegSets::argument().contains(&rd.data)
+                || node.live_out().contains(&rd.data)
+                || node.node().can_skip_save_checks()
+            {
+                continue;
+            }
+            let Some(mut calls) = Self::reached_calls(cfg, &node, rd.data) else {
+                continue;
+            };
+            if calls
+                .iter()
+                .any(|(func, _)| func.arguments().contains(&rd.data))
+            {
+                continue;
+            }
+            calls.sort_by_key(|(_, call_site)| call_site.range());
+            if let Some((func, call_site)) = calls.into_iter().next() {
+                errors.push(LintError::UnusedCallArgument(rd, func, call_site));
+            }
+        }
+    }
+}
+
+impl UnusedCallArgumentCheck {
+    /// Get the calls that the value of a register written by a node is
+    /// passed to.
+    ///
+    /// Every path from the node must reach a call before the register is
+    /// read or written again, otherwise the value is not only used as an
+    /// argument.
+    fn reached_calls(
+        cfg: &Cfg,
+        node: &Rc<CfgNode>,
+        reg: Register,
+    ) -> Option<Vec<(Rc<Function>, With<LabelString>)>> {
+        let mut calls = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = node.nexts().iter().map(Rc::clone).collect::<Vec<_>>();
+        while let Some(next) = queue.pop() {
+            if !visited.insert(next.node().id()) {
+                continue;
+            }
+            if let Some(call) = next.calls_to(cfg) {
+                calls.push(call);
+                continue;
+            }
+            let nexts = next.nexts();
+            if nexts.is_empty()
+                || next.node().is_ecall()
+                || next.node().uses().contains(&reg)
+                || next.node().defs().contains(&reg)
+            {
+                return None;
+            }
+            queue.extend(nexts.iter().map(Rc::clone));
+        }
+        (!calls.is_empty()).then_some(calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        UnusedCallArgumentCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn extra_argument() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                li     a2, 3           \n\
+                jal    add_two         \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            add_two:                   \n\
+                add    a0, a0, a1      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnusedCallArgument(reg, func, _)
+                if reg.data == Register::X12 && func.name().0 == "add_two"
+        ));
+    }
+
+    #[test]
+    fn all_arguments_read() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                jal    add_two         \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            add_two:                   \n\
+                add    a0, a0, a1      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn argument_read_by_one_callee() {
+        let input = "\
+            main:                      \n\
+                li     a1, 2           \n\
+                beqz   a0, other       \n\
+                jal    add_two         \n\
+                j      done            \n\
+            other:                     \n\
+                jal    ignore          \n\
+            done:                      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            add_two:                   \n\
+                add    a0, a0, a1      \n\
+                ret                    \n\
+            ignore:                    \n\
+                li     a0, 0           \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn value_overwritten_before_call() {
+        let input = "\
+            main:                      \n\
+                li     a2, 3           \n\
+                li     a2, 4           \n\
+                jal    ignore          \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            ignore:                    \n\
+                li     a0, 0           \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnusedCallArgument(reg, _, _)
+                if reg.pos.start.line == 2
+        ));
+    }
+}
@@ -45,14 +45,14 @@ impl Cfg {
                     continue;
                 }
             }
-            queue.extend(prev.prevs().clone().into_iter());
+            queue.extend(prev.prevs().clone());
         }
         ranges
     }
 
     // TODO move to a more appropriate place
     // TODO make better, what even is this?
-    fn error_ranges_for_first_usage(node: &Rc<CfgNode>, item: Register) -> Vec<With<Register>> {
+    pub(crate) fn error_ranges_for_first_usage(node: &Rc<CfgNode>, item: Register) -> Vec<With<Register>> {
         let mut queue = VecDeque::new();
         let mut ranges = Vec::new();
         // push the next nodes onto the queue
@@ -89,7 +89,7 @@ impl Cfg {
                 break;
             }
 
-            queue.extend(next.nexts().clone().into_iter());
+            queue.extend(next.nexts().clone());
         }
         ranges
     }
@@ -98,30 +98,27 @@ impl Cfg {
 // Checks are passes that occur after the CFG is built. As much data as possible is collected
 // during the CFG build. Then, the data is applied via a check.
 
-pub struct SaveToZeroCheck;
-impl LintPass for SaveToZeroCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
-        for node in cfg {
-            if let Some(register) = node.node().stores_to() {
-                if register == Register::X0 && !node.node().can_skip_save_checks() {
-                    errors.push(LintError::SaveToZero(register.clone()));
-                }
-            }
-        }
-    }
-}
-
 pub struct DeadValueCheck;
 impl LintPass for DeadValueCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[
+            LintError::INVALID_USE_AFTER_CALL.id,
+            LintError::DEAD_ASSIGNMENT.id,
+        ]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let graph = cfg.function_call_graph();
+        let loops = cfg.natural_loops();
         for node in cfg {
             // check the out of the node for any uses that
             // should not be there (temporaries)
             // TODO merge with Callee saved register check
             if let Some((function, call_site)) = node.calls_to(cfg) {
                 // check the expected return values of the function:
+                // temporaries that the function never writes keep their value
 
-                let out = (RegSets::caller_saved() - function.returns()) & node.live_out();
+                let out = (graph.changed_by_call(&function) - function.returns()) & node.live_out();
 
                 // if there is anything left, then there is an error
                 // for each item, keep going to the next node until a use of
@@ -130,19 +127,27 @@ impl LintPass for DeadValueCheck {
                 for item in &out {
                     ranges.append(&mut Cfg::error_ranges_for_first_usage(&node, item));
                 }
+                // A value kept across a call in a loop is lost on every
+                // iteration
+                let in_loop = loops.loops().iter().any(|lp| lp.contains(&node));
                 for item in ranges {
                     errors.push(LintError::InvalidUseAfterCall(
                         item,
                         Rc::clone(&function),
                         call_site.clone(),
+                        in_loop,
                     ));
                 }
             }
             // Check for any assignments that don't make it
             // to the end of the node. These assignments are not
-            // used.
+            // used. Writes to x0 are not kept, like in `nop`, and
+            // mistaken ones are reported as saves to zero.
             else if let Some(def) = node.node().stores_to() {
-                if !node.live_out().contains(&def.data) && !node.node().can_skip_save_checks() {
+                if def.data != Register::X0
+                    && !node.live_out().contains(&def.data)
+                    && !node.node().can_skip_save_checks()
+                {
                     errors.push(LintError::DeadAssignment(def));
                 }
             }
@@ -154,7 +159,11 @@ impl LintPass for DeadValueCheck {
 // Check if there are any instructions after an ecall to terminate the program
 pub struct EcallCheck;
 impl LintPass for EcallCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNKNOWN_ECALL.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         for node in cfg {
             if node.node().is_ecall() && node.known_ecall().is_none() {
                 errors.push(LintError::UnknownEcall(node.node().clone()));
@@ -168,15 +177,34 @@ impl LintPass for EcallCheck {
 // Check if there are any in values at the start of a program
 pub struct GarbageInputValueCheck;
 impl LintPass for GarbageInputValueCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::INVALID_USE_BEFORE_ASSIGNMENT.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let roots = cfg
+            .roots()
+            .iter()
+            .map(|root| root.node().id())
+            .collect::<HashSet<_>>();
         for node in cfg {
-            if node.node().is_program_entry() {
+            if roots.contains(&node.node().id()) {
                 // get registers
                 let garbage = node.live_in() - RegSets::program_args();
                 if !garbage.is_empty() {
                     let mut ranges = Vec::new();
                     for reg in &garbage {
-                        let mut ranges_tmp = Cfg::error_ranges_for_first_usage(&node, reg);
+                        // A root other than the program entry is an
+                        // instruction, which may be the first use itself
+                        let read = node
+                            .node()
+                            .reads_from()
+                            .into_iter()
+                            .find(|read| *read == reg);
+                        let mut ranges_tmp = match read {
+                            Some(read) => vec![read],
+                            None => Cfg::error_ranges_for_first_usage(&node, reg),
+                        };
                         ranges.append(&mut ranges_tmp);
                     }
                     for range in ranges {
@@ -184,8 +212,10 @@ impl LintPass for GarbageInputValueCheck {
                     }
                 }
             } else if let Some(func) = node.is_function_entry() {
-                let args = func.arguments();
-                let garbage = node.live_in() - args - RegSets::callee_saved();
+                // Caller-saved registers read on entry are arguments or
+                // temporaries, which have checks of their own
+                let garbage =
+                    func.usage_summary().inputs - RegSets::caller_saved() - RegSets::callee_saved();
                 if !garbage.is_empty() {
                     let mut ranges = Vec::new();
                     for reg in &garbage {
@@ -204,7 +234,16 @@ impl LintPass for GarbageInputValueCheck {
 // Check that we know the stack position at every point in the program (aka. within scopes)
 pub struct StackCheckPass;
 impl LintPass for StackCheckPass {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[
+            LintError::UNKNOWN_STACK.id,
+            LintError::INVALID_STACK_POINTER.id,
+            LintError::INVALID_STACK_POSITION.id,
+            LintError::INVALID_STACK_OFFSET_USAGE.id,
+        ]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         // PASS 1
         // check that we know the stack position at every point in the program
         // check that the stack is never in an invalid position
@@ -254,7 +293,11 @@ impl LintPass for StackCheckPass {
 // check if the value of a calle-saved register is read as its original value
 pub struct CalleeSavedGarbageReadCheck;
 impl LintPass for CalleeSavedGarbageReadCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::INVALID_USE_BEFORE_ASSIGNMENT.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         for node in cfg {
             for read in node.node().reads_from() {
                 // if the node uses a calle saved register but not a memory access and the value going in is the original value, then we are reading a garbage value
@@ -277,27 +320,23 @@ impl LintPass for CalleeSavedGarbageReadCheck {
 // Check if the values of callee-saved registers are restored to the original value at the end of the function
 pub struct CalleeSavedRegisterCheck;
 impl LintPass for CalleeSavedRegisterCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::OVERWRITE_CALLEE_SAVED_REGISTER.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         for func in cfg.functions().values() {
-            let exit_vals = func.exit().reg_values_in();
-            for reg in &RegSets::callee_saved() {
-                match exit_vals.get(&reg) {
-                    Some(AvailableValue::OriginalRegisterWithScalar(reg2, offset))
-                        if reg2 == &reg && offset == &0 =>
-                    {
-                        // GOOD!
-                    }
-                    _ => {
-                        // TODO combine with lost register check
-
-                        // This means that we are overwriting a callee-saved register
-                        // We will traverse the function to find the first time
-                        // from the return point that that register was overwritten.
-                        let ranges = Cfg::error_ranges_for_first_store(&func.exit(), reg);
-                        for range in ranges {
-                            errors.push(LintError::OverwriteCalleeSavedRegister(range));
-                        }
-                    }
+            // TODO combine with lost register check
+            for reg in &func.usage_summary().callee_saved_not_restored {
+                // This means that we are overwriting a callee-saved register
+                // We will traverse the function to find the first time
+                // from the return point that that register was overwritten.
+                let ranges = Cfg::error_ranges_for_first_store(&func.exit(), reg);
+                for range in ranges {
+                    errors.push(LintError::OverwriteCalleeSavedRegister(
+                        range,
+                        Rc::clone(func),
+                    ));
                 }
             }
         }
@@ -309,7 +348,11 @@ impl LintPass for CalleeSavedRegisterCheck {
 // diagnostic information in the future.
 pub struct LostCalleeSavedRegisterCheck;
 impl LintPass for LostCalleeSavedRegisterCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::LOST_REGISTER_VALUE.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         for node in cfg {
             let callee = RegSets::saved();
 
@@ -358,3 +401,188 @@ impl LintPass for LostCalleeSavedRegisterCheck {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticLocation, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        DeadValueCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn nop_is_not_unused_value() {
+        let input = "\
+            main:                      \n\
+                nop                    \n\
+                addi   zero, zero, 0   \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn temporary_changed_by_callee() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                jal    fn_a            \n\
+                mv     a0, t0          \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     t0, 1           \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 2);
+        assert!(matches!(&lints[0], LintError::DeadAssignment(reg) if reg.data == Register::X5));
+        assert!(matches!(
+            &lints[1], LintError::InvalidUseAfterCall(reg, func, _, false)
+                if reg.data == Register::X5 && func.name().0 == "fn_a"
+        ));
+        assert_eq!(lints[1].range().start.line, 3);
+    }
+
+    #[test]
+    fn temporary_changed_by_nested_call() {
+        let input = "\
+            main:                      \n\
+                li     t1, 5           \n\
+                jal    fn_a            \n\
+                mv     a0, t1          \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                jal    fn_b            \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n\
+            fn_b:                      \n\
+                li     t1, 1           \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert!(lints.iter().any(|lint| matches!(
+            lint, LintError::InvalidUseAfterCall(reg, func, ..)
+                if reg.data == Register::X6 && func.name().0 == "fn_a"
+        )));
+    }
+
+    #[test]
+    fn temporary_kept_by_callee() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                jal    fn_a            \n\
+                add    a0, a0, t0      \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     a0, 2           \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn counter_in_temporary_across_loop_call() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0           \n\
+                li     s1, 10          \n\
+            loop:                      \n\
+                mv     a0, t0          \n\
+                jal    print           \n\
+                addi   t0, t0, 1       \n\
+                blt    t0, s1, loop    \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            print:                     \n\
+                li     a7, 1           \n\
+                li     t0, 2           \n\
+                ecall                  \n\
+                ret                    \n";
+
+        let lints = run_pass(input)
+            .into_iter()
+            .filter(|lint| matches!(lint, LintError::InvalidUseAfterCall(..)))
+            .collect::<Vec<_>>();
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::InvalidUseAfterCall(reg, func, _, true)
+                if reg.data == Register::X5 && func.name().0 == "print"
+        ));
+        assert_eq!(lints[0].range().start.line, 6);
+    }
+
+    #[test]
+    fn counter_in_temporary_kept_by_loop_call() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0           \n\
+                li     s1, 10          \n\
+            loop:                      \n\
+                mv     a0, t0          \n\
+                jal    print           \n\
+                addi   t0, t0, 1       \n\
+                blt    t0, s1, loop    \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            print:                     \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                ret                    \n";
+
+        assert!(!run_pass(input)
+            .iter()
+            .any(|lint| matches!(lint, LintError::InvalidUseAfterCall(..))));
+    }
+
+    #[test]
+    fn entry_point_reads_its_own_garbage() {
+        let input = "\
+            .globl handler             \n\
+            main:                      \n\
+                li     t0, 1           \n\
+                mv     a0, t0          \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            handler:                   \n\
+                mv     a0, t0          \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        assert!(!cfg.nodes()[0].live_in().contains(&Register::X5));
+
+        let lints = GarbageInputValueCheck.run_single_pass_along_cfg(&cfg);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::InvalidUseBeforeAssignment(reg) if reg.data == Register::X5
+        ));
+        assert_eq!(lints[0].range().start.line, 9);
+    }
+}
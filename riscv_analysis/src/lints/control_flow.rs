@@ -1,6 +1,6 @@
 use crate::{
     cfg::Cfg,
-    parser::ParserNode,
+    parser::{ParserNode, Register},
     passes::{LintError, LintPass},
 };
 use std::rc::Rc;
@@ -9,44 +9,68 @@ use std::rc::Rc;
 
 /// This pass checks for the following control flow issues:
 /// - A function is entered through the first line of code (Why?).
-/// - A function is entered through an jump that is not a function call.
-/// - Any code that has no previous nodes, i.e. is unreachable.
+/// - A function is entered through an jump that is not a function call or a
+///   tail call.
+/// - A jump through a register other than `ra` whose target is not known,
+///   as the CFG is missing the edges of the jump.
+/// - A branch that compares a register with itself, so it is always or
+///   never taken. `beq x0, x0` is allowed, as it is a common way to write
+///   an unconditional jump.
+/// - A branch whose registers hold known values on every path to it, so it
+///   always goes the same way. The path that it never takes is removed from
+///   the CFG.
 pub struct ControlFlowCheck;
 impl LintPass for ControlFlowCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[
+            LintError::FIRST_INSTRUCTION_IS_FUNCTION.id,
+            LintError::INVALID_JUMP_TO_FUNCTION.id,
+            LintError::UNKNOWN_JUMP_TARGET.id,
+            LintError::CONSTANT_BRANCH.id,
+            LintError::KNOWN_BRANCH_DIRECTION.id,
+        ]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for (node, taken) in cfg.known_branches() {
+            errors.push(LintError::KnownBranchDirection(node.node(), taken));
+        }
         for node in &cfg.clone() {
-            match node.node() {
-                ParserNode::FuncEntry(_) => {
-                    // If the previous nodes set is not empty
-                    // Note: this also accounts for functions being at the beginning
-                    // of a program, as the ProgEntry node will be the previous node
-                    for prev_node in node.prevs().iter() {
-                        for function in node.functions().iter() {
-                            if prev_node.node().is_program_entry() {
-                                errors.push(LintError::FirstInstructionIsFunction(
-                                    node.node().clone(),
-                                    Rc::clone(function),
-                                ));
-                            }
-                            // Jumps (J not JAL) to the start of recognized
-                            // functions are errors
-                            else if prev_node.node().is_unconditional_jump() {
-                                errors.push(LintError::InvalidJumpToFunction(
-                                    node.node().clone(),
-                                    prev_node.node().clone(),
-                                    Rc::clone(function),
-                                ));
-                                // Create at most one error per node
-                                break;
-                            }
-                        }
-                    }
+            if node.node().is_indirect_jump() && cfg.indirect_jump_targets(&node).is_none() {
+                errors.push(LintError::UnknownJumpTarget(node.node()));
+            }
+            if let ParserNode::Branch(branch) = node.node() {
+                let taken = node.node().is_unconditional_jump();
+                let never_taken = node.node().is_never_taken_branch();
+                if (taken && branch.rs1 != Register::X0) || never_taken {
+                    errors.push(LintError::ConstantBranch(node.node(), taken));
                 }
-                // The program entry should have no prevs
-                ParserNode::ProgramEntry(_) => {}
-                _ => {
-                    if node.prevs().is_empty() {
-                        errors.push(LintError::UnreachableCode(node.node().clone()));
+            }
+            if let ParserNode::FuncEntry(_) = node.node() {
+                // If the previous nodes set is not empty
+                // Note: this also accounts for functions being at the beginning
+                // of a program, as the ProgEntry node will be the previous node
+                for prev_node in node.prevs().iter() {
+                    for function in node.functions().iter() {
+                        if prev_node.node().is_program_entry() {
+                            errors.push(LintError::FirstInstructionIsFunction(
+                                node.node().clone(),
+                                Rc::clone(function),
+                            ));
+                        }
+                        // Jumps (J not JAL) to the start of recognized
+                        // functions are errors, unless they are tail calls
+                        else if prev_node.node().is_unconditional_jump()
+                            && !prev_node.node().is_tail_call()
+                        {
+                            errors.push(LintError::InvalidJumpToFunction(
+                                node.node().clone(),
+                                prev_node.node().clone(),
+                                Rc::clone(function),
+                            ));
+                            // Create at most one error per node
+                            break;
+                        }
                     }
                 }
             }
@@ -57,15 +81,16 @@ impl LintPass for ControlFlowCheck {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser::RVStringParser;
+    use crate::parser::{LabelString, RVStringParser};
     use crate::passes::Manager;
+    use std::str::FromStr;
 
     fn run_pass(input: &str) -> Vec<LintError> {
         let (nodes, error) = RVStringParser::parse_from_text(input);
         assert_eq!(error.len(), 0);
 
         let cfg = Manager::gen_full_cfg(nodes).unwrap();
-        ControlFlowCheck::run_single_pass_along_cfg(&cfg)
+        ControlFlowCheck.run_single_pass_along_cfg(&cfg)
     }
 
     #[test]
@@ -82,33 +107,14 @@ mod tests {
 
         let lints = run_pass(input);
 
-        // Error for function on at the program entry & 4 errors for all the
-        // unreachable instructions in `main`
-        assert_eq!(lints.len(), 5);
+        // Error for function on at the program entry
+        assert_eq!(lints.len(), 1);
 
-        // The first error should warn about the first instruction of `fn_a`
+        // The error should warn about the first instruction of `fn_a`
         assert!(matches!(
         &lints[0], LintError::FirstInstructionIsFunction(node, _)
             if node.token().text == "addi a0 a0 1"
         ));
-
-        // Next four errors should be about unreachable code
-        assert!(matches!(
-        &lints[1], LintError::UnreachableCode(node, ..)
-            if node.token().text == "li a0 0"
-        ));
-        assert!(matches!(
-        &lints[2], LintError::UnreachableCode(node, ..)
-            if node.token().text == "jal fn_a"
-        ));
-        assert!(matches!(
-        &lints[3], LintError::UnreachableCode(node, ..)
-            if node.token().text == "addi a7 zero 10"
-        ));
-        assert!(matches!(
-        &lints[4], LintError::UnreachableCode(node, ..)
-            if node.token().text == "ecall"
-        ));
     }
 
     #[test]
@@ -127,24 +133,38 @@ mod tests {
 
         let lints = run_pass(input);
 
-        // Error for function on at the program entry & 2 errors for all the
-        // unreachable instructions in `main` after the `j` instruction
-        assert_eq!(lints.len(), 3);
+        // Error for the jump into `fn_a`
+        assert_eq!(lints.len(), 1);
 
         assert!(matches!(
-        &lints[0], LintError::UnreachableCode(node, ..)
-            if node.token().text == "addi a7 zero 10"
-        ));
-        assert!(matches!(
-        &lints[1], LintError::UnreachableCode(node, ..)
-            if node.token().text == "ecall"
-        ));
-        assert!(matches!(
-        &lints[2], LintError::InvalidJumpToFunction(node, ..)
+        &lints[0], LintError::InvalidJumpToFunction(node, ..)
             if node.token().text == "addi a0 a0 1"
         ));
     }
 
+    #[test]
+    fn tail_call_to_function() {
+        let input = "\
+            main:                      \n\
+                li     a0, 0           \n\
+                jal    fn_a            \n\
+                jal    fn_b            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                tail   fn_b            \n\
+            fn_b:                      \n\
+                addi   a0, a0, 2       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+
+        // A function that ends in a tail call returns through the function
+        // that it calls
+        assert_eq!(lints.len(), 0);
+    }
+
     #[test]
     fn overlapping_functions() {
         let input = "\
@@ -188,4 +208,313 @@ mod tests {
         // An "unreachable" directive shouldn't cause an error
         assert_eq!(lints.len(), 0);
     }
+
+    #[test]
+    fn globl_function_is_not_entered_from_start() {
+        let input = "\
+            .globl fn_a                \n\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    const JUMP_TABLE: &str = "\
+        .data                          \n\
+        table:                         \n\
+            .word  case_a, case_b      \n\
+        .text                          \n\
+        main:                          \n\
+            slli   t1, a0, 2           \n\
+            la     t0, table           \n\
+            add    t0, t0, t1          \n\
+            lw     t0, 0(t0)           \n\
+            jr     t0                  \n\
+        case_a:                        \n\
+            li     a0, 1               \n\
+            j      end                 \n\
+        case_b:                        \n\
+            li     a0, 2               \n\
+        end:                           \n\
+            addi   a7, zero, 10        \n\
+            ecall                      \n";
+
+    fn unknown_jump_targets(input: &str) -> Vec<LintError> {
+        run_pass(input)
+            .into_iter()
+            .filter(|lint| matches!(lint, LintError::UnknownJumpTarget(_)))
+            .collect()
+    }
+
+    #[test]
+    fn jump_to_unknown_register() {
+        let input = "\
+            main:                      \n\
+                mv     t0, a0          \n\
+                jr     t0              \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = unknown_jump_targets(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnknownJumpTarget(node) if node.token().text == "jr t0"
+        ));
+    }
+
+    #[test]
+    fn jump_to_loaded_address() {
+        let input = "\
+            main:                      \n\
+                la     t0, end         \n\
+                jr     t0              \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let jump = cfg
+            .iter()
+            .find(|node| node.node().token().text == "jr t0")
+            .unwrap();
+        assert_eq!(
+            cfg.indirect_jump_targets(&jump),
+            Some(vec![LabelString::from_str("end").unwrap()])
+        );
+        assert_eq!(unknown_jump_targets(input).len(), 0);
+    }
+
+    #[test]
+    fn jump_through_table() {
+        let (nodes, error) = RVStringParser::parse_from_text(JUMP_TABLE);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let jump = cfg
+            .iter()
+            .find(|node| node.node().token().text == "jr t0")
+            .unwrap();
+        assert_eq!(
+            cfg.indirect_jump_targets(&jump),
+            Some(vec![
+                LabelString::from_str("case_a").unwrap(),
+                LabelString::from_str("case_b").unwrap(),
+            ])
+        );
+        assert_eq!(unknown_jump_targets(JUMP_TABLE).len(), 0);
+    }
+
+    #[test]
+    fn jump_through_table_entry() {
+        let input = "\
+            .data                      \n\
+            table:                     \n\
+                .word  case_a, case_b  \n\
+            .text                      \n\
+            main:                      \n\
+                lw     t0, table       \n\
+                la     t1, table       \n\
+                lw     t1, 4(t1)       \n\
+                jr     t1              \n\
+            case_a:                    \n\
+                jr     t0              \n\
+            case_b:                    \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let jump = cfg
+            .iter()
+            .find(|node| node.node().token().text == "jr t1")
+            .unwrap();
+        assert_eq!(
+            cfg.indirect_jump_targets(&jump),
+            Some(vec![LabelString::from_str("case_b").unwrap()])
+        );
+    }
+
+    #[test]
+    fn jump_through_table_of_numbers() {
+        let input = JUMP_TABLE.replace("case_a, case_b", "1, 2");
+
+        let lints = unknown_jump_targets(&input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    fn constant_branches(input: &str) -> Vec<LintError> {
+        run_pass(input)
+            .into_iter()
+            .filter(|lint| matches!(lint, LintError::ConstantBranch(..)))
+            .collect()
+    }
+
+    #[test]
+    fn branch_on_same_register_is_always_taken() {
+        let input = "\
+            main:                      \n\
+                beq    t0, t0, end     \n\
+                li     a0, 1           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = constant_branches(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::ConstantBranch(node, true) if node.token().text == "beq t0 t0 end"
+        ));
+
+        // The branch does not fall through
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let branch = cfg
+            .iter()
+            .find(|node| node.node().token().text == "beq t0 t0 end")
+            .unwrap();
+        assert_eq!(branch.nexts().len(), 1);
+    }
+
+    #[test]
+    fn branch_on_same_register_is_never_taken() {
+        let input = "\
+            main:                      \n\
+                bne    t0, t0, other   \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            other:                     \n\
+                li     a0, 1           \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = constant_branches(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(&lints[0], LintError::ConstantBranch(_, false)));
+
+        // The target is not reached from the branch
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let target = cfg.label_node_map.get("other").unwrap();
+        assert!(target.prevs().is_empty());
+    }
+
+    #[test]
+    fn branch_on_zero_register_is_a_jump() {
+        let input = "\
+            main:                      \n\
+                beq    x0, x0, end     \n\
+                li     a0, 1           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        assert_eq!(constant_branches(input).len(), 0);
+        assert_eq!(
+            constant_branches(&input.replace("beq    x0, x0", "bne    x0, x0")).len(),
+            1
+        );
+    }
+
+    fn known_branches(input: &str) -> Vec<LintError> {
+        run_pass(input)
+            .into_iter()
+            .filter(|lint| matches!(lint, LintError::KnownBranchDirection(..)))
+            .collect()
+    }
+
+    #[test]
+    fn branch_on_known_values_prunes_fall_through() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                li     t1, 3           \n\
+                blt    t1, t0, end     \n\
+                li     a0, 1           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = known_branches(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::KnownBranchDirection(node, true)
+                if node.token().text == "blt t1 t0 end"
+        ));
+
+        // The code after the branch is dead
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let skipped = cfg
+            .iter()
+            .find(|node| node.node().token().text == "li a0 1")
+            .unwrap();
+        assert!(skipped.prevs().is_empty());
+    }
+
+    #[test]
+    fn branch_on_known_values_prunes_target() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                mv     t1, t0          \n\
+                bne    t0, t1, other   \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            other:                     \n\
+                li     a0, 1           \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = known_branches(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0],
+            LintError::KnownBranchDirection(_, false)
+        ));
+
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        assert!(cfg.label_node_map.get("other").unwrap().prevs().is_empty());
+    }
+
+    #[test]
+    fn branch_on_values_that_differ_by_path_is_kept() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                beqz   a0, high        \n\
+                li     t1, 3           \n\
+                j      check           \n\
+            high:                      \n\
+                li     t1, 7           \n\
+            check:                     \n\
+                blt    t1, t0, end     \n\
+                li     a0, 1           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        assert_eq!(known_branches(input).len(), 0);
+
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let branch = cfg
+            .iter()
+            .find(|node| node.node().token().text == "blt t1 t0 end")
+            .unwrap();
+        assert_eq!(branch.nexts().len(), 2);
+    }
 }
@@ -0,0 +1,94 @@
+use crate::{
+    cfg::Cfg,
+    parser::{CSRIType, CSRType, ParserNode, Register},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find CSR instructions with an address that does not fit in
+/// 12 bits, or that write to a read-only CSR.
+///
+/// `csrrs` and `csrrc` only write to the CSR if the bits to set or clear
+/// are not zero, so `csrr t0, cycle` is not reported.
+pub struct CsrCheck;
+impl LintPass for CsrCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[
+            LintError::CSR_OUT_OF_RANGE.id,
+            LintError::READ_ONLY_CSR_WRITE.id,
+        ]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            let (csr, writes) = match node.node() {
+                ParserNode::Csr(x) => (x.csr, x.inst == CSRType::Csrrw || x.rs1 != Register::X0),
+                ParserNode::CsrI(x) => (x.csr, x.inst == CSRIType::Csrrwi || x.imm.data.0 != 0),
+                _ => continue,
+            };
+            if !csr.data.is_valid() {
+                errors.push(LintError::CsrOutOfRange(csr));
+            } else if writes && csr.data.is_read_only() {
+                errors.push(LintError::ReadOnlyCsrWrite(csr));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticLocation, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        CsrCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn read_only_csr_write() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                csrw   cycle, t0       \n\
+                csrs   instret, t0     \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 2);
+        assert_eq!(lints[0].to_string(), "CSR cycle is read-only");
+        assert_eq!(lints[0].range().start.line, 2);
+        assert_eq!(lints[0].range().start.column, 7);
+        assert!(matches!(&lints[1], LintError::ReadOnlyCsrWrite(csr) if csr.data.0 == 0xC02));
+    }
+
+    #[test]
+    fn read_only_csr_read() {
+        let input = "\
+            main:                      \n\
+                csrr   t0, cycle       \n\
+                csrrsi t1, time, 0     \n\
+                csrw   mstatus, t0     \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn csr_out_of_range() {
+        let input = "\
+            main:                      \n\
+                csrr   t0, 0x1000      \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].to_string(), "CSR address 0x1000 is out of range");
+    }
+}
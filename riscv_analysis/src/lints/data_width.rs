@@ -0,0 +1,137 @@
+use crate::{
+    analysis::AvailableValue,
+    cfg::Cfg,
+    parser::ParserNode,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find memory accesses that do not match the width of the data
+/// they access.
+///
+/// If a register holds the address of a label, like after `la t0, array`,
+/// the data declared at that label is known. Reading a `.word` with `lb`,
+/// or a `.byte` with `lw`, reads only part of a value or more than one value,
+/// which is usually a mistake.
+pub struct DataWidthCheck;
+impl LintPass for DataWidthCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::MISMATCHED_DATA_WIDTH.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            let (base, offset, width) = match node.node() {
+                ParserNode::Load(expr) => (expr.rs1.data, expr.imm.data.0, expr.inst.data.width()),
+                ParserNode::Store(expr) => (expr.rs1.data, expr.imm.data.0, expr.inst.data.width()),
+                _ => continue,
+            };
+            let Some(AvailableValue::Address(label)) = node.reg_values_in().get(&base).cloned()
+            else {
+                continue;
+            };
+            let Some(symbol) = cfg.data_symbol(&label) else {
+                continue;
+            };
+            if let Some(declared) = symbol.element_width_at(offset) {
+                if declared != width {
+                    errors.push(LintError::MismatchedDataWidth(
+                        node.node(),
+                        symbol.label().clone(),
+                        declared,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        DataWidthCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn byte_access_to_words() {
+        let input = "\
+            .data                      \n\
+            values:                    \n\
+                .word  1, 2, 3         \n\
+            .text                      \n\
+            main:                      \n\
+                la     t0, values      \n\
+                lb     t1, 4(t0)       \n\
+                sb     t1, 8(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 2);
+        assert!(matches!(
+            &lints[0], LintError::MismatchedDataWidth(node, label, 4)
+                if node.token().text == "lb t1 4 ( t0 )" && label.data.0 == "values"
+        ));
+    }
+
+    #[test]
+    fn word_access_to_bytes() {
+        let input = "\
+            .data                      \n\
+            flags:                     \n\
+                .byte  1, 0, 1, 1      \n\
+            .text                      \n\
+            main:                      \n\
+                lw     t1, flags       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(&lints[0], LintError::MismatchedDataWidth(_, _, 1)));
+    }
+
+    #[test]
+    fn access_matches_data_at_offset() {
+        let input = "\
+            .data                      \n\
+            record:                    \n\
+                .word  7               \n\
+                .byte  1, 2            \n\
+            message:                   \n\
+                .asciz \"hi\"          \n\
+            .text                      \n\
+            main:                      \n\
+                la     t0, record      \n\
+                lw     t1, 0(t0)       \n\
+                lbu    t2, 5(t0)       \n\
+                la     t0, message     \n\
+                lb     t3, 1(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn untyped_data_is_not_checked() {
+        let input = "\
+            .data                      \n\
+            buffer:                    \n\
+                .space 16              \n\
+            .text                      \n\
+            main:                      \n\
+                la     t0, buffer      \n\
+                lb     t1, 0(t0)       \n\
+                lw     t2, 4(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use crate::{
+    cfg::{syscall, Cfg, CfgNode},
+    parser::Register,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find arguments of an `ecall` that are not set before the call.
+///
+/// The call is found from the constant in `a7`, and its arguments from the
+/// table of system calls. An argument must be set on every path to the
+/// `ecall`, otherwise the call uses whatever value was left in the register.
+/// Calls whose number is not known are skipped.
+pub struct EcallArgumentCheck;
+impl LintPass for EcallArgumentCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNINITIALIZED_ECALL_ARGUMENT.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let roots = cfg
+            .roots()
+            .iter()
+            .map(|root| root.node().id())
+            .collect::<HashSet<_>>();
+        for node in cfg {
+            let Some(call) = node.known_ecall().and_then(syscall) else {
+                continue;
+            };
+            for reg in call.inputs {
+                if Self::may_be_unset(&node, *reg, &roots) {
+                    errors.push(LintError::UninitializedEcallArgument(
+                        node.node(),
+                        *reg,
+                        call.name,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl EcallArgumentCheck {
+    /// Check if a register may not be set on some path to a node.
+    ///
+    /// The paths are searched back to a [root](Cfg::roots), which is the ID
+    /// of a node in `roots`. A function entry ends a path, as the argument
+    /// registers of a function are set by its callers.
+    fn may_be_unset(node: &Rc<CfgNode>, reg: Register, roots: &HashSet<Uuid>) -> bool {
+        let mut visited = HashSet::new();
+        let mut queue = node.prevs().iter().map(Rc::clone).collect::<Vec<_>>();
+        while let Some(prev) = queue.pop() {
+            if !visited.insert(prev.node().id()) {
+                continue;
+            }
+            if prev.node().is_function_entry() || Self::sets(&prev, reg) {
+                continue;
+            }
+            if roots.contains(&prev.node().id()) {
+                return true;
+            }
+            queue.extend(prev.prevs().iter().map(Rc::clone));
+        }
+        false
+    }
+
+    /// Check if a node sets a register.
+    ///
+    /// A known system call only sets its outputs, and keeps the value of
+    /// every other register.
+    fn sets(node: &CfgNode, reg: Register) -> bool {
+        if node.node().is_ecall() {
+            if let Some((_, outputs)) = node.known_ecall_signature() {
+                return outputs.contains(&reg);
+            }
+        }
+        node.node().defs().contains(&reg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, Register};
+    use crate::passes::{DiagnosticLocation, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        EcallArgumentCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn print_int_with_argument() {
+        let input = "\
+            main:                      \n\
+                li     a0, 42          \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn print_int_without_argument() {
+        let input = "\
+            main:                      \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UninitializedEcallArgument(node, Register::X10, "PrintInt")
+                if node.range().start.line == 2
+        ));
+    }
+
+    #[test]
+    fn argument_set_on_one_path() {
+        let input = "\
+            main:                      \n\
+                beqz   t0, skip        \n\
+                la     a0, msg         \n\
+            skip:                      \n\
+                li     a7, 4           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n\
+            .data                      \n\
+            msg: .string \"hi\"        \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0],
+            LintError::UninitializedEcallArgument(_, Register::X10, "PrintString")
+        ));
+    }
+
+    #[test]
+    fn argument_kept_across_ecall() {
+        let input = "\
+            main:                      \n\
+                li     a7, 5           \n\
+                ecall                  \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn argument_of_function_is_set() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                jal    print           \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            print:                     \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn unknown_call_is_skipped() {
+        let input = "\
+            main:                      \n\
+                mv     a7, a0          \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{IArithType, LoadType, ParserNode, RegSets, Register, Xlen},
+    passes::{LintError, LintPass},
+};
+
+/// The known width of the value in a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Extension {
+    /// The value is the zero extension of this many of its lowest bits.
+    Zero(u32),
+    /// The value is the sign extension of this many of its lowest bits.
+    Sign(u32),
+}
+
+impl Extension {
+    /// Check if every value allowed by `other` is also allowed by this.
+    fn contains(self, other: Extension) -> bool {
+        match (self, other) {
+            (Extension::Zero(n), Extension::Zero(m)) | (Extension::Sign(n), Extension::Sign(m)) => {
+                m <= n
+            }
+            (Extension::Sign(n), Extension::Zero(m)) => m < n,
+            (Extension::Zero(_), Extension::Sign(_)) => false,
+        }
+    }
+
+    /// Join two extensions, to get one that allows the values of both.
+    ///
+    /// This is the larger of the two if it allows every value of the other.
+    /// Otherwise, nothing is known about the width of the value.
+    fn join(self, other: Extension) -> Option<Extension> {
+        if self.contains(other) {
+            Some(self)
+        } else if other.contains(self) {
+            Some(other)
+        } else {
+            None
+        }
+    }
+
+    /// Get the smallest extension that allows a constant.
+    fn of_constant(value: i64) -> Extension {
+        if value >= 0 {
+            Extension::Zero(64 - value.leading_zeros())
+        } else {
+            Extension::Sign(65 - value.leading_ones())
+        }
+    }
+}
+
+/// The known widths of the values in registers at some point in the program.
+type ExtensionMap = HashMap<Register, Extension>;
+
+/// A lint to find sign and zero extensions that do not change a value.
+///
+/// Values are zero extended with `andi` and a mask of low bits, or with a
+/// `slli` followed by a `srli` of the same amount. They are sign extended
+/// with a `slli` followed by a `srai`, or with `addiw rd, rs, 0` on RV64.
+/// If the value was already extended, such as by `lbu` or `lh`, or it is a
+/// constant that fits, the extension has no effect.
+///
+/// The pass tracks the known width of every register along every path, like
+/// the available value analysis does for values.
+pub struct RedundantExtensionCheck;
+impl LintPass for RedundantExtensionCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::REDUNDANT_EXTENSION.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let xlen = cfg.xlen();
+        let extensions_in = Self::extensions_in(cfg, xlen);
+        for node in cfg {
+            let ParserNode::IArith(expr) = node.node() else {
+                continue;
+            };
+            let Some(exts) = extensions_in.get(&node.node().id()) else {
+                continue;
+            };
+            let imm = expr.imm.data.0;
+            match expr.inst.data {
+                IArithType::Andi if imm > 0 && imm & (imm + 1) == 0 => {
+                    let mask = Extension::Zero(imm.trailing_ones());
+                    if Self::known(&node, expr.rs1.data, exts).is_some_and(|ext| mask.contains(ext))
+                    {
+                        errors.push(LintError::RedundantExtension(node.node(), None));
+                    }
+                }
+                IArithType::Addiw
+                    if xlen == Xlen::Rv64
+                        && imm == 0
+                        && Self::known(&node, expr.rs1.data, exts)
+                            .is_some_and(|ext| Extension::Sign(32).contains(ext)) =>
+                {
+                    errors.push(LintError::RedundantExtension(node.node(), None));
+                }
+                IArithType::Srli | IArithType::Srai => {
+                    let Some(shift) = Self::shift_before(&node) else {
+                        continue;
+                    };
+                    let ParserNode::IArith(shift_expr) = shift.node() else {
+                        continue;
+                    };
+                    let Some(shift_exts) = extensions_in.get(&shift.node().id()) else {
+                        continue;
+                    };
+                    let Some(width) = Self::shift_amount(imm, xlen).map(|k| xlen.bits() - k) else {
+                        continue;
+                    };
+                    let ext = if expr.inst.data == IArithType::Srai {
+                        Extension::Sign(width)
+                    } else {
+                        Extension::Zero(width)
+                    };
+                    if Self::known(&shift, shift_expr.rs1.data, shift_exts)
+                        .is_some_and(|known| ext.contains(known))
+                    {
+                        errors.push(LintError::RedundantExtension(
+                            node.node(),
+                            Some(shift.node()),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl RedundantExtensionCheck {
+    /// Get the known width of a register going into a node.
+    fn known(node: &CfgNode, reg: Register, exts: &ExtensionMap) -> Option<Extension> {
+        if reg == Register::X0 {
+            return Some(Extension::Zero(0));
+        }
+        match node.reg_values_in().get(&reg) {
+            Some(AvailableValue::Constant(value)) => Some(Extension::of_constant(*value)),
+            _ => exts.get(&reg).copied(),
+        }
+    }
+
+    /// Get a shift amount that moves some bits out of a register.
+    fn shift_amount(imm: i32, xlen: Xlen) -> Option<u32> {
+        u32::try_from(imm)
+            .ok()
+            .filter(|k| (1..xlen.bits()).contains(k))
+    }
+
+    /// Get the `slli` that starts an extension ending with a right shift.
+    ///
+    /// The left shift must be the only node before the right shift, and
+    /// shift the register that the right shift reads by the same amount.
+    fn shift_before(node: &CfgNode) -> Option<Rc<CfgNode>> {
+        let ParserNode::IArith(expr) = node.node() else {
+            return None;
+        };
+        let prevs = node.prevs();
+        let mut prevs = prevs.iter();
+        let (Some(prev), None) = (prevs.next(), prevs.next()) else {
+            return None;
+        };
+        let ParserNode::IArith(prev_expr) = prev.node() else {
+            return None;
+        };
+        (prev_expr.inst.data == IArithType::Slli
+            && prev_expr.rd == expr.rs1
+            && prev_expr.imm.data == expr.imm.data)
+            .then(|| Rc::clone(prev))
+    }
+
+    /// Compute the known widths of registers going into every node.
+    ///
+    /// A width is only known at a node if it is known along every previous
+    /// path that has been visited.
+    fn extensions_in(cfg: &Cfg, xlen: Xlen) -> HashMap<Uuid, ExtensionMap> {
+        let mut ins: HashMap<Uuid, ExtensionMap> = HashMap::new();
+        let mut outs: HashMap<Uuid, ExtensionMap> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in cfg {
+                // in[n] = AND out[p] for all p in prev[n]
+                let in_n = node
+                    .prevs()
+                    .iter()
+                    .filter_map(|prev| outs.get(&prev.node().id()))
+                    .fold(None, |acc: Option<ExtensionMap>, out| match acc {
+                        None => Some(out.clone()),
+                        Some(acc) => Some(
+                            acc.into_iter()
+                                .filter_map(|(reg, ext)| {
+                                    let other = out.get(&reg)?;
+                                    Some((reg, ext.join(*other)?))
+                                })
+                                .collect(),
+                        ),
+                    })
+                    .unwrap_or_default();
+
+                let out_n = Self::transfer(&node, &in_n, xlen);
+                if outs.get(&node.node().id()) != Some(&out_n) {
+                    changed = true;
+                    outs.insert(node.node().id(), out_n);
+                }
+                ins.insert(node.node().id(), in_n);
+            }
+        }
+        ins
+    }
+
+    /// Compute the known widths of registers after a node.
+    fn transfer(node: &CfgNode, exts_in: &ExtensionMap, xlen: Xlen) -> ExtensionMap {
+        let mut killed = node.node().kill_reg_value();
+        if node.node().is_ecall() {
+            killed |= RegSets::caller_saved();
+        }
+
+        let mut out = exts_in.clone();
+        out.retain(|reg, _| !killed.contains(reg));
+
+        if let Some(rd) = node.node().stores_to() {
+            if rd != Register::X0 {
+                if let Some(ext) = Self::extension_of(node, exts_in, xlen) {
+                    out.insert(rd.data, ext);
+                }
+            }
+        }
+        out
+    }
+
+    /// Get the known width of the value that a node writes.
+    fn extension_of(node: &CfgNode, exts_in: &ExtensionMap, xlen: Xlen) -> Option<Extension> {
+        if let Some(rs) = node.node().copies_from() {
+            return Self::known(node, rs.data, exts_in);
+        }
+        match node.node() {
+            ParserNode::Load(expr) => match expr.inst.data {
+                LoadType::Lb => Some(Extension::Sign(8)),
+                LoadType::Lbu => Some(Extension::Zero(8)),
+                LoadType::Lh => Some(Extension::Sign(16)),
+                LoadType::Lhu => Some(Extension::Zero(16)),
+                LoadType::Lw => Some(Extension::Sign(32)),
+                LoadType::Lwu => Some(Extension::Zero(32)),
+                LoadType::Ld | LoadType::Flw | LoadType::Fld => None,
+            },
+            ParserNode::IArith(expr) => {
+                let imm = expr.imm.data.0;
+                match expr.inst.data {
+                    IArithType::Andi if imm >= 0 => {
+                        let mask = Extension::Zero(32 - imm.leading_zeros());
+                        match Self::known(node, expr.rs1.data, exts_in) {
+                            Some(ext) if mask.contains(ext) => Some(ext),
+                            _ => Some(mask),
+                        }
+                    }
+                    IArithType::Srli => {
+                        Self::shift_amount(imm, xlen).map(|k| Extension::Zero(xlen.bits() - k))
+                    }
+                    IArithType::Srai => {
+                        Self::shift_amount(imm, xlen).map(|k| Extension::Sign(xlen.bits() - k))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        RedundantExtensionCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn mask_after_unsigned_byte_load() {
+        let input = "\
+            main:                      \n\
+                lbu    t0, 0(a0)       \n\
+                andi   t0, t0, 0xff    \n\
+                sw     t0, 0(a1)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::RedundantExtension(node, None)
+                if node.token().text == "andi t0 t0 0xff"
+        ));
+    }
+
+    #[test]
+    fn mask_after_signed_byte_load() {
+        let input = "\
+            main:                      \n\
+                lb     t0, 0(a0)       \n\
+                andi   t0, t0, 0xff    \n\
+                sw     t0, 0(a1)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn sign_extension_after_signed_half_load() {
+        let input = "\
+            main:                      \n\
+                lh     t0, 0(a0)       \n\
+                slli   t0, t0, 16      \n\
+                srai   t0, t0, 16      \n\
+                sw     t0, 0(a1)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::RedundantExtension(node, Some(shift))
+                if node.token().text == "srai t0 t0 16"
+                    && shift.token().text == "slli t0 t0 16"
+        ));
+    }
+
+    #[test]
+    fn sign_extension_of_wider_value() {
+        let input = "\
+            main:                      \n\
+                lh     t0, 0(a0)       \n\
+                slli   t0, t0, 24      \n\
+                srai   t0, t0, 24      \n\
+                sw     t0, 0(a1)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn extension_known_on_every_path() {
+        let input = "\
+            main:                      \n\
+                beqz   a2, other       \n\
+                lbu    t0, 0(a0)       \n\
+                j      done            \n\
+            other:                     \n\
+                lhu    t0, 0(a0)       \n\
+            done:                      \n\
+                andi   t0, t0, 0xff    \n\
+                sw     t0, 0(a1)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn mask_of_small_constant() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                andi   t0, t0, 0xf     \n\
+                sw     t0, 0(a1)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use std::rc::Rc;
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{ParserNode, Register},
+    passes::{DiagnosticLocation, LintError, LintPass},
+};
+
+/// A lint to find loads from stack slots that were freed.
+///
+/// Moving the stack pointer up frees the stack slots below it. A freed slot
+/// can be overwritten at any time, for example by the next call, so reading
+/// it after the stack pointer was moved reads a value that may be gone. The
+/// addresses of the load and the stack pointer are both known relative to
+/// the stack pointer at the start of the function.
+pub struct FreedStackReadCheck;
+impl LintPass for FreedStackReadCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::FREED_STACK_READ.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            let ParserNode::Load(load) = node.node() else {
+                continue;
+            };
+            let Some(slot) = Self::stack_address(&node, load.rs1.data, load.imm.data.0) else {
+                continue;
+            };
+            let Some(sp) = Self::stack_address(&node, Register::X2, 0) else {
+                continue;
+            };
+            if slot >= sp {
+                continue;
+            }
+
+            let mut stores = cfg
+                .iter_prevs(Rc::clone(&node))
+                .filter(|prev| match prev.node() {
+                    ParserNode::Store(store) => {
+                        Self::stack_address(prev, store.rs1.data, store.imm.data.0) == Some(slot)
+                    }
+                    _ => false,
+                })
+                .map(|prev| prev.node())
+                .collect::<Vec<_>>();
+            stores.sort_by_key(DiagnosticLocation::range);
+            errors.push(LintError::FreedStackRead(node.node(), sp - slot, stores));
+        }
+    }
+}
+
+impl FreedStackReadCheck {
+    /// Get the address of a register plus an offset, relative to the stack
+    /// pointer at the start of the function, before a node.
+    fn stack_address(node: &CfgNode, reg: Register, offset: i32) -> Option<i32> {
+        match node.reg_values_in().get(&reg) {
+            Some(AvailableValue::OriginalRegisterWithScalar(Register::X2, base)) => {
+                base.checked_add(offset)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        FreedStackReadCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn read_after_freeing_slot() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -8      \n\
+                sw     s0, 0(sp)       \n\
+                sw     ra, 4(sp)       \n\
+                li     s0, 1           \n\
+                addi   sp, sp, 8       \n\
+                lw     s0, -8(sp)      \n\
+                lw     ra, -4(sp)      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 2);
+        assert!(matches!(
+            &lints[0], LintError::FreedStackRead(node, 8, stores)
+                if node.token().text == "lw s0 -8 ( sp )"
+                    && stores.len() == 1
+                    && stores[0].token().text == "sw s0 0 ( sp )"
+        ));
+        assert!(matches!(&lints[1], LintError::FreedStackRead(_, 4, _)));
+    }
+
+    #[test]
+    fn read_through_copy_of_stack_pointer() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -4      \n\
+                mv     t0, sp          \n\
+                sw     a0, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                lw     a0, 0(t0)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn read_before_freeing_slot() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -8      \n\
+                sw     s0, 0(sp)       \n\
+                sw     ra, 4(sp)       \n\
+                li     s0, 1           \n\
+                lw     s0, 0(sp)       \n\
+                lw     ra, 4(sp)       \n\
+                addi   sp, sp, 8       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
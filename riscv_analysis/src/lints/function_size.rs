@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use crate::{
+    cfg::Cfg,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find functions with more instructions than a given maximum.
+///
+/// Large functions are harder to read and test, and can often be split into
+/// smaller functions. Instructions are counted as they are written, so a
+/// pseudo-instruction counts as one instruction, even if it expands to more,
+/// like a `li` with a large immediate. Labels, directives and the nodes added
+/// while building the CFG are not counted.
+///
+/// Unlike most lints, this lint depends on a configured maximum, so it is
+/// only run if one is set.
+pub struct FunctionSizeCheck {
+    /// The largest number of instructions a function may have.
+    pub max_instructions: usize,
+}
+
+impl LintPass for FunctionSizeCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::FUNCTION_TOO_LARGE.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for func in cfg.unique_functions() {
+            let count = func
+                .nodes()
+                .iter()
+                .filter(|node| {
+                    node.node().is_instruction() && !node.node().completes_load_immediate()
+                })
+                .map(|node| node.node().id())
+                .collect::<HashSet<_>>()
+                .len();
+            if count > self.max_instructions {
+                errors.push(LintError::FunctionTooLarge(
+                    func.entry().node(),
+                    func,
+                    count,
+                    self.max_instructions,
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str, max_instructions: usize) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let mut errors = Vec::new();
+        FunctionSizeCheck { max_instructions }.run(&cfg, &mut errors);
+        errors
+    }
+
+    const INPUT: &str = "\
+        main:                      \n\
+            jal    fn_a            \n\
+            jal    fn_b            \n\
+            ret                    \n\
+        fn_a:                      \n\
+            li     a0, 1           \n\
+            addi   a0, a0, 1       \n\
+            mv     a1, a0          \n\
+            ret                    \n\
+        fn_b:                      \n\
+            li     a0, 2           \n\
+            ret                    \n";
+
+    #[test]
+    fn large_function() {
+        let lints = run_pass(INPUT, 3);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::FunctionTooLarge(_, func, 4, 3)
+                if func.name().0 == "fn_a"
+        ));
+    }
+
+    #[test]
+    fn function_at_maximum() {
+        let lints = run_pass(INPUT, 4);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn large_immediate_counts_once() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                ret                    \n\
+            fn_a:                      \n\
+                li     a0, 0x12345     \n\
+                li     a1, 0x23456     \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input, 3).len(), 0);
+        let lints = run_pass(input, 2);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(&lints[0], LintError::FunctionTooLarge(_, _, 3, 2)));
+    }
+
+    #[test]
+    fn every_large_function_is_reported() {
+        let lints = run_pass(INPUT, 1);
+        assert_eq!(lints.len(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use crate::{
+    cfg::Cfg,
+    parser::{Inst, ParserNode, Register, Type},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find immediates that do not fit in their instruction.
+///
+/// An immediate is encoded in a fixed number of bits, which depends on the
+/// instruction, so a value that does not fit cannot be assembled. For
+/// example, `addi` takes a 12-bit signed value, and `slli` can only shift
+/// by less than the width of a register.
+///
+/// Loads and stores of an address with no base register, like
+/// `lw t0, 0x10010000`, are left out, as the assembler builds the address
+/// in a register first. So are the other instructions that a written
+/// instruction expands to.
+pub struct ImmediateRangeCheck;
+impl LintPass for ImmediateRangeCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::IMMEDIATE_OUT_OF_RANGE.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            let node = node.node();
+            if Self::is_expanded(&node) {
+                continue;
+            }
+            let Some(imm) = node.immediate() else {
+                continue;
+            };
+            let inst = node.inst();
+            let Some(range) = Type::from(&inst).immediate_range(cfg.xlen()) else {
+                continue;
+            };
+            if !range.contains(&imm.data.0) {
+                errors.push(LintError::ImmediateOutOfRange(imm, inst, range));
+            }
+        }
+    }
+}
+
+impl ImmediateRangeCheck {
+    /// Check if a node is made by the assembler from an instruction with a
+    /// different immediate, so its immediate is not checked.
+    fn is_expanded(node: &ParserNode) -> bool {
+        let written = node
+            .token()
+            .text
+            .split_whitespace()
+            .next()
+            .and_then(|mnemonic| Inst::from_str(&mnemonic.to_lowercase()).ok());
+        let absolute = match node {
+            ParserNode::Load(x) => x.rs1.data == Register::X0,
+            ParserNode::Store(x) => x.rs1.data == Register::X0,
+            _ => false,
+        };
+        let expanded = written.is_some_and(|written| {
+            !matches!(Type::from(&written), Type::Pseudo(_)) && written != node.inst()
+        });
+        absolute || expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, Xlen};
+    use crate::passes::{DiagnosticLocation, DiagnosticMessage, Manager, ManagerConfiguration};
+
+    fn run_pass(input: &str, xlen: Xlen) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let config = ManagerConfiguration {
+            xlen,
+            ..Default::default()
+        };
+        let cfg = Manager::gen_full_cfg_with_config(nodes, &config).unwrap();
+        ImmediateRangeCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    /// Run a program that writes `inst` and then exits.
+    fn run_inst(inst: &str, xlen: Xlen) -> Vec<LintError> {
+        run_pass(
+            &format!("main:\n    {inst}\n    li a7, 10\n    ecall\n"),
+            xlen,
+        )
+    }
+
+    #[test]
+    fn addi_out_of_range() {
+        let lints = run_inst("addi t0, t0, 2048", Xlen::Rv32);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::ImmediateOutOfRange(imm, Inst::Addi, range)
+                if imm.data.0 == 2048 && range == &(-2048..=2047)
+        ));
+        // The diagnostic points at the immediate
+        assert_eq!(lints[0].range().start.line, 1);
+        assert_eq!(lints[0].range().start.column, 17);
+        assert!(lints[0].long_description().contains("-2048 to 2047"));
+    }
+
+    #[test]
+    fn negative_immediates() {
+        assert_eq!(run_inst("addi t0, t0, -2048", Xlen::Rv32).len(), 0);
+        assert_eq!(run_inst("addi t0, t0, -2049", Xlen::Rv32).len(), 1);
+        assert_eq!(run_inst("lw t0, -2049(sp)", Xlen::Rv32).len(), 1);
+        assert_eq!(run_inst("slli t0, t0, -1", Xlen::Rv32).len(), 1);
+    }
+
+    #[test]
+    fn shift_wider_than_register() {
+        let lints = run_inst("slli t0, t0, 40", Xlen::Rv32);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::ImmediateOutOfRange(_, Inst::Slli, range) if range == &(0..=31)
+        ));
+        assert!(lints[0].long_description().contains("RV64"));
+
+        assert_eq!(run_inst("slli t0, t0, 40", Xlen::Rv64).len(), 0);
+        assert_eq!(run_inst("slli t0, t0, 64", Xlen::Rv64).len(), 1);
+        assert_eq!(run_inst("slliw t0, t0, 40", Xlen::Rv64).len(), 1);
+    }
+
+    #[test]
+    fn upper_and_csr_immediates() {
+        assert_eq!(run_inst("lui t0, 0xfffff", Xlen::Rv32).len(), 0);
+        assert_eq!(run_inst("lui t0, 0x100000", Xlen::Rv32).len(), 1);
+        assert_eq!(run_inst("csrrwi t0, ustatus, 32", Xlen::Rv32).len(), 1);
+        assert_eq!(run_inst("csrwi ustatus, 32", Xlen::Rv32).len(), 1);
+    }
+
+    #[test]
+    fn expanded_instructions_are_not_checked() {
+        assert_eq!(run_inst("li t0, 0x12345678", Xlen::Rv32).len(), 0);
+        assert_eq!(run_inst("lw t0, 0x10010000", Xlen::Rv32).len(), 0);
+        assert_eq!(run_inst("sw t0, 5000, t1", Xlen::Rv32).len(), 0);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    cfg::{Cfg, CfgNode},
+    parser::{ParserNode, RegSets, Register},
+    passes::{LintError, LintPass},
+};
+
+/// Register copies that are still valid at some point in the program.
+///
+/// A key of `(dest, source)` means that `dest` currently holds the same
+/// value as `source`. The value is the copy instruction that made them equal.
+type CopyMap = HashMap<(Register, Register), ParserNode>;
+
+/// A lint to warn about attempts to swap two registers without a temporary.
+///
+/// The sequence `mv t0, t1` followed by `mv t1, t0` does not swap the two
+/// registers: after the first copy, both registers hold the value of `t1`,
+/// so the second copy does nothing. Instead of matching this exact pattern,
+/// the pass tracks which registers are known to hold the same value. Any copy
+/// of a register into a register that already holds the same value, through
+/// any copy instruction and along every path, is reported.
+pub struct IneffectiveSwapCheck;
+impl LintPass for IneffectiveSwapCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::INEFFECTIVE_SWAP.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let copies_in = Self::copies_in(cfg);
+        for node in cfg {
+            let (Some(rd), Some(rs)) = (node.node().stores_to(), node.node().copies_from()) else {
+                continue;
+            };
+            if let Some(first) = copies_in
+                .get(&node.node().id())
+                .and_then(|copies| copies.get(&(rs.data, rd.data)))
+            {
+                errors.push(LintError::IneffectiveSwap(node.node(), first.clone()));
+            }
+        }
+    }
+}
+
+impl IneffectiveSwapCheck {
+    /// Compute the valid register copies going into every node.
+    ///
+    /// This is a forward analysis similar to the available value analysis.
+    /// A copy is only valid at a node if it is valid along every previous
+    /// path that has been visited.
+    fn copies_in(cfg: &Cfg) -> HashMap<Uuid, CopyMap> {
+        let mut ins: HashMap<Uuid, CopyMap> = HashMap::new();
+        let mut outs: HashMap<Uuid, CopyMap> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in cfg {
+                // in[n] = AND out[p] for all p in prev[n]
+                let in_n = node
+                    .prevs()
+                    .iter()
+                    .filter_map(|prev| outs.get(&prev.node().id()))
+                    .fold(None, |acc: Option<CopyMap>, out| match acc {
+                        None => Some(out.clone()),
+                        Some(mut acc) => {
+                            acc.retain(|key, _| out.contains_key(key));
+                            Some(acc)
+                        }
+                    })
+                    .unwrap_or_default();
+
+                let out_n = Self::transfer(&node, &in_n);
+                if outs.get(&node.node().id()) != Some(&out_n) {
+                    changed = true;
+                    outs.insert(node.node().id(), out_n);
+                }
+                ins.insert(node.node().id(), in_n);
+            }
+        }
+        ins
+    }
+
+    /// Compute the valid register copies after a node.
+    fn transfer(node: &CfgNode, copies_in: &CopyMap) -> CopyMap {
+        let mut killed = node.node().kill_reg_value();
+        if node.node().is_ecall() {
+            killed |= RegSets::caller_saved();
+        }
+
+        let mut out = copies_in.clone();
+        out.retain(|(dest, source), _| !killed.contains(dest) && !killed.contains(source));
+
+        if let (Some(rd), Some(rs)) = (node.node().stores_to(), node.node().copies_from()) {
+            if rd != Register::X0 && rd.data != rs.data {
+                out.insert((rd.data, rs.data), node.node());
+                // The destination also holds the value of anything that the
+                // source was a copy of.
+                for (dest, source) in copies_in.keys() {
+                    if *dest == rs.data && *source != rd.data {
+                        out.insert((rd.data, *source), node.node());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        IneffectiveSwapCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn broken_swap() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                li     t1, 2           \n\
+                mv     t0, t1          \n\
+                mv     t1, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::IneffectiveSwap(node, first)
+                if node.token().text == "mv t1 t0" && first.token().text == "mv t0 t1"
+        ));
+    }
+
+    #[test]
+    fn broken_swap_with_other_copies() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                li     t1, 2           \n\
+                addi   t0, t1, 0       \n\
+                or     t1, zero, t0    \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(&lints[0], LintError::IneffectiveSwap(..)));
+    }
+
+    #[test]
+    fn broken_swap_through_third_register() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                li     t1, 2           \n\
+                mv     t0, t1          \n\
+                mv     t2, t0          \n\
+                mv     t1, t2          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::IneffectiveSwap(node, _)
+                if node.token().text == "mv t1 t2"
+        ));
+    }
+
+    #[test]
+    fn swap_with_temporary() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                li     t1, 2           \n\
+                mv     t2, t0          \n\
+                mv     t0, t1          \n\
+                mv     t1, t2          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn copy_is_invalidated_by_write() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                li     t1, 2           \n\
+                mv     t0, t1          \n\
+                addi   t0, t0, 1       \n\
+                mv     t1, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn copy_on_only_one_path() {
+        let input = "\
+            main:                      \n\
+                li     t0, 1           \n\
+                li     t1, 2           \n\
+                beqz   a0, skip        \n\
+                mv     t0, t1          \n\
+            skip:                      \n\
+                mv     t1, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
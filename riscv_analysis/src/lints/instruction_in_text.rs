@@ -11,7 +11,11 @@ use crate::{
 /// behaviour that we do not handle.
 pub struct InstructionInTextCheck;
 impl LintPass for InstructionInTextCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::INVALID_SEGMENT.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         for node in cfg {
             if node.node().is_instruction() && node.segment() != Segment::Text {
                 errors.push(LintError::InvalidSegment(node.node().clone()));
@@ -28,14 +32,14 @@ mod tests {
     #[test]
     fn default_segment_is_text() {
         let nodes = &[iarith!(Addi X1 X0 0)];
-        let errors = InstructionInTextCheck::run_single_pass_along_nodes(nodes);
+        let errors = InstructionInTextCheck.run_single_pass_along_nodes(nodes);
         assert_eq!(errors.len(), 0);
     }
 
     #[test]
     fn explicit_text_segment_is_allowed() {
         let nodes = &[directive!(Text, TextSection), iarith!(Addi X1 X0 0)];
-        let errors = InstructionInTextCheck::run_single_pass_along_nodes(nodes);
+        let errors = InstructionInTextCheck.run_single_pass_along_nodes(nodes);
         assert_eq!(errors.len(), 0);
     }
 
@@ -50,7 +54,7 @@ mod tests {
             directive!(Text, TextSection),
             iarith!(Andi X1 X0 0),
         ];
-        let errors = InstructionInTextCheck::run_single_pass_along_nodes(nodes);
+        let errors = InstructionInTextCheck.run_single_pass_along_nodes(nodes);
         assert_eq!(errors.len(), 2);
         assert!(matches!(errors[0], LintError::InvalidSegment(_)));
         assert!(matches!(errors[1], LintError::InvalidSegment(_)));
@@ -71,7 +75,7 @@ mod tests {
             directive!(Text, TextSection),
             iarith!(Andi X1 X0 0),
         ];
-        let errors = InstructionInTextCheck::run_single_pass_along_nodes(nodes);
+        let errors = InstructionInTextCheck.run_single_pass_along_nodes(nodes);
         assert_eq!(errors.len(), 3);
         assert!(matches!(errors[0], LintError::InvalidSegment(_)));
         assert!(matches!(errors[1], LintError::InvalidSegment(_)));
@@ -0,0 +1,205 @@
+use crate::{
+    cfg::{Cfg, RegisterSet},
+    parser::{ParserNode, Register},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find loops whose exit condition never changes.
+///
+/// A loop usually ends when a branch compares a counter to a bound. If none
+/// of the registers that the branch compares are written inside the loop,
+/// the branch decides the same way on every iteration. When every way out
+/// of the loop is such a branch, the loop either never runs again or never
+/// ends, which usually means that the counter is never incremented or
+/// decremented.
+pub struct LoopCounterCheck;
+impl LintPass for LoopCounterCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNCHANGED_LOOP_CONDITION.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for lp in cfg.natural_loops().loops() {
+            let writes = lp.writes();
+            let exits = lp.exits();
+            let unchanged = exits
+                .iter()
+                .filter_map(|exit| {
+                    let ParserNode::Branch(branch) = exit.node() else {
+                        return None;
+                    };
+                    // Keep the registers in the order they were written, so
+                    // that `bgt a0, a1` is reported as comparing a0 and a1
+                    let mut regs = branch.source_operands();
+                    regs.retain(|reg| *reg != Register::X0);
+                    regs.dedup();
+                    let reads = regs.iter().copied().collect::<RegisterSet>();
+                    (!reads.is_empty() && (reads & writes).is_empty()).then(|| (exit.node(), regs))
+                })
+                .collect::<Vec<_>>();
+
+            // The loop can still end through another exit
+            if unchanged.len() != exits.len() {
+                continue;
+            }
+            for (branch, regs) in unchanged {
+                errors.push(LintError::UnchangedLoopCondition(
+                    branch,
+                    lp.header().node(),
+                    regs,
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticMessage, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        LoopCounterCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn counter_never_incremented() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0           \n\
+                li     t1, 10          \n\
+            loop:                      \n\
+                bge    t0, t1, done    \n\
+                add    a0, a0, t0      \n\
+                j      loop            \n\
+            done:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnchangedLoopCondition(branch, header, regs)
+                if branch.token().text == "bge t0 t1 done"
+                    && header.token().text == "bge t0 t1 done"
+                    && regs == &vec![Register::X5, Register::X6]
+        ));
+    }
+
+    #[test]
+    fn counter_never_decremented_at_end() {
+        let input = "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                addi   a0, a0, 1       \n\
+                bnez   t0, loop        \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnchangedLoopCondition(branch, header, _)
+                if branch.token().text == "bnez t0 loop"
+                    && header.token().text == "addi a0 a0 1"
+        ));
+    }
+
+    #[test]
+    fn irreducible_cycle_is_not_a_loop() {
+        let input = "\
+            main:                      \n\
+                li     t0, 10          \n\
+                beqz   a0, second      \n\
+            first:                     \n\
+                addi   a0, a0, 1       \n\
+            second:                    \n\
+                bnez   t0, first       \n\
+                ret                    \n";
+
+        // The cycle can be entered at either label, so it has no header
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn counter_changes() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0           \n\
+                li     t1, 10          \n\
+            loop:                      \n\
+                bge    t0, t1, done    \n\
+                add    a0, a0, t0      \n\
+                addi   t0, t0, 1       \n\
+                j      loop            \n\
+            done:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn loop_with_another_exit() {
+        let input = "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                lw     t1, 0(a0)       \n\
+                beqz   t1, done        \n\
+                addi   a0, a0, 4       \n\
+                bnez   t0, loop        \n\
+            done:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn condition_written_by_call() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+            loop:                      \n\
+                jal    check           \n\
+                bnez   a0, loop        \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n\
+            check:                     \n\
+                li     a0, 0           \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn pseudo_branch_keeps_written_order() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0           \n\
+                li     t1, 10          \n\
+            loop:                      \n\
+                bgt    t0, t1, done    \n\
+                add    a0, a0, t0      \n\
+                j      loop            \n\
+            done:                      \n\
+                ret                    \n";
+
+        // `bgt t0, t1` is parsed as `blt t1, t0`, but is reported as written
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnchangedLoopCondition(_, _, regs)
+                if regs == &vec![Register::X5, Register::X6]
+        ));
+        assert!(lints[0].long_description().contains("This `bgt` compares"));
+    }
+}
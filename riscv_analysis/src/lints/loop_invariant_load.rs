@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use crate::{
+    cfg::{Cfg, CfgNode, Loop},
+    parser::{Load, ParserNode},
+    passes::{LintError, LintPass},
+};
+
+use super::{MemoryAccess, RedundantLoadCheck};
+
+/// A lint to find loads inside a loop that read the same memory on every
+/// iteration.
+///
+/// A load is loop-invariant if the base register of its address is not
+/// written anywhere in the loop, and nothing in the loop could write to the
+/// memory it reads. A store through a different base register is assumed to
+/// write to it, unless the available values of both registers show that
+/// they point to bytes that do not overlap. A call or system call in the loop
+/// could write to any memory, so no load in that loop is reported.
+///
+/// Loads in nested loops are reported once, for the outermost loop that
+/// they can be moved out of.
+pub struct LoopInvariantLoadCheck;
+impl LintPass for LoopInvariantLoadCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::LOOP_INVARIANT_LOAD.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let natural = cfg.natural_loops();
+        let mut seen = HashSet::new();
+        for lp in natural.loops() {
+            if lp
+                .nodes()
+                .iter()
+                .any(|node| RedundantLoadCheck::clobbers_memory(&node.node()))
+            {
+                continue;
+            }
+            let writes = lp.writes();
+            for node in lp.nodes() {
+                let ParserNode::Load(load) = node.node() else {
+                    continue;
+                };
+                if writes.contains(&load.rs1.data)
+                    || Self::may_be_written(lp, node, &load)
+                    || !seen.insert(node.node().id())
+                {
+                    continue;
+                }
+                errors.push(LintError::LoopInvariantLoad(
+                    node.node(),
+                    lp.header().node(),
+                ));
+            }
+        }
+    }
+}
+
+impl LoopInvariantLoadCheck {
+    /// Check if a store in a loop could write to the memory that a load in
+    /// the loop reads.
+    fn may_be_written(lp: &Loop, load_node: &CfgNode, load: &Load) -> bool {
+        let read = MemoryAccess {
+            base: load.rs1.data,
+            offset: load.imm.data.0,
+            width: load.inst.data.width(),
+        };
+        lp.nodes().iter().any(|node| {
+            let ParserNode::Store(store) = node.node() else {
+                return false;
+            };
+            let write = MemoryAccess {
+                base: store.rs1.data,
+                offset: store.imm.data.0,
+                width: store.inst.data.width(),
+            };
+            // The base of the load is not written in the loop, so its value
+            // is the same before the store as before the load
+            RedundantLoadCheck::may_alias(node, &write, &read)
+                || RedundantLoadCheck::may_alias(load_node, &write, &read)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticLocation, DiagnosticMessage, Manager, SeverityLevel};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        LoopInvariantLoadCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    /// Sum a word ten times, with `body` inside of the loop.
+    fn sum_loop(body: &str) -> String {
+        format!(
+            "main:\n\
+             la a0, total\n\
+             la a1, other\n\
+             li t0, 10\n\
+             loop:\n\
+             lw t1, 0(a0)\n\
+             add t2, t2, t1\n\
+             {body}\n\
+             addi t0, t0, -1\n\
+             bnez t0, loop\n\
+             mv a0, t2\n\
+             li a7, 1\n\
+             ecall\n\
+             li a7, 10\n\
+             ecall\n\
+             .data\n\
+             total: .word 1\n\
+             other: .word 2\n"
+        )
+    }
+
+    #[test]
+    fn hoistable_load() {
+        let lints = run_pass(&sum_loop("sw t2, 0(a1)"));
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::LoopInvariantLoad(load, header)
+                if load.token().text == "lw t1 0 ( a0 )" && load == header
+        ));
+        assert_eq!(lints[0].range().start.line, 5);
+        assert_eq!(lints[0].level(), SeverityLevel::Hint);
+        assert!(lints[0].long_description().contains("before the loop"));
+    }
+
+    #[test]
+    fn store_to_loaded_memory() {
+        assert_eq!(run_pass(&sum_loop("sw t2, 0(a0)")).len(), 0);
+        // The address in a2 is not known, so it may be the loaded word
+        assert_eq!(run_pass(&sum_loop("sw t2, 0(a2)")).len(), 0);
+    }
+
+    #[test]
+    fn base_register_changes() {
+        assert_eq!(run_pass(&sum_loop("addi a0, a0, 4")).len(), 0);
+    }
+
+    #[test]
+    fn call_in_loop() {
+        let input = sum_loop("jal fn_a") + ".text\nfn_a:\nret\n";
+        assert_eq!(run_pass(&input).len(), 0);
+    }
+}
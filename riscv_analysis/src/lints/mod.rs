@@ -1,6 +1,9 @@
 mod checks;
 pub use checks::*;
 
+mod save_to_zero;
+pub use save_to_zero::*;
+
 mod instruction_in_text;
 pub use instruction_in_text::*;
 
@@ -9,3 +12,84 @@ pub use overlapping_function::*;
 
 mod control_flow;
 pub use control_flow::*;
+
+mod unreachable;
+pub use unreachable::*;
+
+mod ineffective_swap;
+pub use ineffective_swap::*;
+
+mod redundant_branch;
+pub use redundant_branch::*;
+
+mod function_size;
+pub use function_size::*;
+
+mod boolean_misuse;
+pub use boolean_misuse::*;
+
+mod unnecessary_save;
+pub use unnecessary_save::*;
+
+mod overflow;
+pub use overflow::*;
+
+mod return_address;
+pub use return_address::*;
+
+mod extension;
+pub use extension::*;
+
+mod data_width;
+pub use data_width::*;
+
+mod loop_counter;
+pub use loop_counter::*;
+
+mod self_cancel;
+pub use self_cancel::*;
+
+mod call_argument;
+pub use call_argument::*;
+
+mod freed_stack;
+pub use freed_stack::*;
+
+mod temporary_input;
+pub use temporary_input::*;
+
+mod rv64_instruction;
+pub use rv64_instruction::*;
+
+mod stack;
+pub use stack::*;
+
+mod stack_balance;
+pub use stack_balance::*;
+
+mod uninitialized_stack;
+pub use uninitialized_stack::*;
+
+mod ecall_argument;
+pub use ecall_argument::*;
+
+mod redundant_load;
+pub use redundant_load::*;
+
+mod immediate_range;
+pub use immediate_range::*;
+
+mod argument_arity;
+pub use argument_arity::*;
+
+mod annotation;
+pub use annotation::*;
+
+mod undefined_label;
+pub use undefined_label::*;
+
+mod csr;
+pub use csr::*;
+
+mod loop_invariant_load;
+pub use loop_invariant_load::*;
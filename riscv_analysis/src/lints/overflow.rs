@@ -0,0 +1,188 @@
+use crate::{
+    analysis::AvailableValue,
+    cfg::Cfg,
+    parser::ParserNode,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find arithmetic on known constants that overflows a register.
+///
+/// When both operands of an addition, subtraction or multiplication are
+/// known, the result can be checked. If it does not fit in a register, it
+/// wraps around, which is rarely what was intended.
+///
+/// Registers are 32 bits on RV32 and 64 bits on RV64, and the arithmetic on
+/// words of RV64, like `addw`, wraps at 32 bits.
+pub struct ConstantOverflowCheck;
+impl LintPass for ConstantOverflowCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::CONSTANT_OVERFLOW.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            // The upper bits of a large `li` may wrap around when its lower
+            // bits are negative, which gives the value that was written.
+            if node.node().completes_load_immediate() {
+                continue;
+            }
+            let Some(rd) = node.node().stores_to() else {
+                continue;
+            };
+            let values = node.reg_values_in();
+            let (lhs, rhs) = match node.node() {
+                ParserNode::Arith(expr) => (values.get(&expr.rs1.data), values.get(&expr.rs2.data)),
+                ParserNode::IArith(expr) => (
+                    values.get(&expr.rs1.data),
+                    Some(&AvailableValue::Constant(expr.imm.data.0.into())),
+                ),
+                _ => continue,
+            };
+            let (Some(AvailableValue::Constant(x)), Some(AvailableValue::Constant(y))) = (lhs, rhs)
+            else {
+                continue;
+            };
+            let Some(op) = node.node().inst().math_op() else {
+                continue;
+            };
+            let bits = node.node().inst().arith_bits(cfg.xlen());
+            if let Some(exact) = op.overflow(*x, *y, bits) {
+                let wrapped = op.operate(*x, *y, bits);
+                errors.push(LintError::ConstantOverflow(rd, exact, wrapped, bits));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, Register, Xlen};
+    use crate::passes::{Manager, ManagerConfiguration};
+
+    fn run_pass(input: &str, xlen: Xlen) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let config = ManagerConfiguration {
+            xlen,
+            ..Default::default()
+        };
+        let cfg = Manager::gen_full_cfg_with_config(nodes, &config).unwrap();
+        ConstantOverflowCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    const REPEATED_ADD: &str = "\
+        main:                      \n\
+            li     t0, 0x40000000  \n\
+            add    t0, t0, t0      \n\
+            add    t0, t0, t0      \n\
+            sw     t0, 0(sp)       \n\
+            ret                    \n";
+
+    #[test]
+    fn repeated_add_overflows() {
+        let lints = run_pass(REPEATED_ADD, Xlen::Rv32);
+        assert_eq!(lints.len(), 2);
+        assert!(matches!(
+            &lints[0],
+            LintError::ConstantOverflow(_, 0x8000_0000, -0x8000_0000, 32)
+        ));
+        // The wrapped value is used for the next addition
+        assert!(matches!(
+            &lints[1],
+            LintError::ConstantOverflow(_, -0x1_0000_0000, 0, 32)
+        ));
+    }
+
+    #[test]
+    fn add_fits_in_rv64_register() {
+        let lints = run_pass(REPEATED_ADD, Xlen::Rv64);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn word_arithmetic_overflows_on_rv64() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0x7fffffff  \n\
+                addiw  t1, t0, 1       \n\
+                addw   t2, t0, t0      \n\
+                slli   t3, t0, 32      \n\
+                add    t3, t3, t3      \n\
+                add    t4, t0, t0      \n\
+                sw     t1, 0(sp)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input, Xlen::Rv64);
+        assert_eq!(lints.len(), 3);
+        assert!(matches!(
+            &lints[0],
+            LintError::ConstantOverflow(reg, 0x8000_0000, -0x8000_0000, 32)
+                if reg.data == Register::X6
+        ));
+        assert!(matches!(
+            &lints[1],
+            LintError::ConstantOverflow(reg, 0xffff_fffe, -2, 32) if reg.data == Register::X7
+        ));
+        // `add` only wraps at 64 bits
+        assert!(matches!(
+            &lints[2],
+            LintError::ConstantOverflow(reg, 0xffff_fffe_0000_0000, -0x2_0000_0000, 64)
+                if reg.data == Register::X28
+        ));
+    }
+
+    #[test]
+    fn overflowed_value_wraps_at_register_width() {
+        let value_at_store = |xlen| {
+            let (nodes, _) = RVStringParser::parse_from_text(REPEATED_ADD);
+            let config = ManagerConfiguration {
+                xlen,
+                ..Default::default()
+            };
+            let cfg = Manager::gen_full_cfg_with_config(nodes, &config).unwrap();
+            let store = cfg
+                .iter()
+                .find(|node| matches!(node.node(), ParserNode::Store(_)))
+                .unwrap();
+            store.reg_values_in().get(&Register::X5).cloned()
+        };
+
+        assert_eq!(
+            value_at_store(Xlen::Rv32),
+            Some(AvailableValue::Constant(0))
+        );
+        assert_eq!(
+            value_at_store(Xlen::Rv64),
+            Some(AvailableValue::Constant(0x1_0000_0000))
+        );
+    }
+
+    #[test]
+    fn addi_overflows() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0x7fffffff  \n\
+                addi   t0, t0, 1       \n\
+                sw     t0, 0(sp)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input, Xlen::Rv32);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn arithmetic_in_range() {
+        let input = "\
+            main:                      \n\
+                li     t0, 0x7ffffffe  \n\
+                addi   t0, t0, 1       \n\
+                sub    t1, zero, t0    \n\
+                sw     t1, 0(sp)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input, Xlen::Rv32);
+        assert_eq!(lints.len(), 0);
+    }
+}
@@ -12,13 +12,28 @@ use uuid::Uuid;
 /// function.
 pub struct OverlappingFunctionCheck;
 impl LintPass for OverlappingFunctionCheck {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::NODE_IN_MANY_FUNCTIONS.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
         for node in cfg {
             // Capture entry points that are part of more than one function
             // NOTE: We only give an error for the first line of a function,
             //       even though there may be many overlapping instructions.
             //       This is done to not overwhelm the user with errors.
-            if node.functions().len() > 1 && node.is_function_entry().is_some() {
+            // NOTE: A function that is tail called is also part of the
+            //       function that calls it, which is expected.
+            // NOTE: An exported label is an intentional entry point, so
+            //       code that continues into it is expected.
+            if node.functions().len() > 1
+                && node.is_function_entry().is_some()
+                && !node.prevs().iter().any(|prev| prev.node().is_tail_call())
+                && !node
+                    .labels()
+                    .iter()
+                    .any(|label| cfg.globals().contains(&label.data.0))
+            {
                 // HACK: Create a dummy label with the same name
                 let labels = node.labels();
                 let labels = labels
@@ -32,6 +47,7 @@ impl LintPass for OverlappingFunctionCheck {
                                 pos: l.pos.clone(),
                                 file: l.file,
                             },
+                            annotations: Vec::new(),
                         }
                     })
                     .collect::<Vec<_>>();
@@ -63,7 +79,7 @@ mod tests {
         assert_eq!(error.len(), 0);
 
         let cfg = Manager::gen_full_cfg(nodes).unwrap(); // Need fn annotations
-        OverlappingFunctionCheck::run_single_pass_along_cfg(&cfg)
+        OverlappingFunctionCheck.run_single_pass_along_cfg(&cfg)
     }
 
     #[test]
@@ -95,6 +111,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tail_called_function() {
+        // `fn_a` continues into `fn_b` with a tail call
+        let input = "\
+            main:                      \n\
+                li     a0, 0           \n\
+                jal    fn_a            \n\
+                jal    fn_b            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                tail   fn_b            \n\
+            fn_b:                      \n\
+                addi   a0, a0, 2       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn global_function_overlap() {
+        // `fn_b` is exported, so continuing into it is intentional
+        let input = "\
+            .globl fn_b                \n\
+            main:                      \n\
+                li     a0, 0           \n\
+                jal    fn_a            \n\
+                jal    fn_b            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+            fn_b:                      \n\
+                addi   a0, a0, 2       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
     #[test]
     fn three_overlapping_functions() {
         // The functions `fn_a`, `fn_b`, and `fn_c` overlap
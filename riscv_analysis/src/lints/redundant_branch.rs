@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::{
+    cfg::{Cfg, CfgNode},
+    parser::ParserNode,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find conditional branches whose condition has no effect.
+///
+/// If the taken and the fall-through paths of a branch both continue at the
+/// same instruction, the branch condition does not change what the program
+/// does. For example, `beq t0, t1, L` followed by `j L`. The paths are
+/// followed through any unconditional jumps, as these have no other effect.
+pub struct RedundantBranchCheck;
+impl LintPass for RedundantBranchCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::REDUNDANT_BRANCH.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for (i, node) in cfg.nodes().iter().enumerate() {
+            let ParserNode::Branch(branch) = node.node() else {
+                continue;
+            };
+            if node.node().is_unconditional_jump() || node.node().is_never_taken_branch() {
+                continue;
+            }
+
+            let taken = cfg.label_node_map.get(&branch.name.data.0).cloned();
+            let fall_through = cfg.nodes().get(i + 1).cloned();
+            if let (Some(taken), Some(fall_through)) = (taken, fall_through) {
+                if Self::destination(cfg, taken) == Self::destination(cfg, fall_through) {
+                    errors.push(LintError::RedundantBranch(node.node()));
+                }
+            }
+        }
+    }
+}
+
+impl RedundantBranchCheck {
+    /// Follow a node through unconditional jumps to the first node that has
+    /// some effect.
+    fn destination(cfg: &Cfg, node: Rc<CfgNode>) -> Rc<CfgNode> {
+        #[allow(clippy::mutable_key_type)]
+        let mut visited = HashSet::new();
+        let mut current = node;
+        while let ParserNode::JumpLink(jump) = current.node() {
+            if !current.node().is_unconditional_jump() || !visited.insert(Rc::clone(&current)) {
+                break;
+            }
+            match cfg.label_node_map.get(&jump.name.data.0) {
+                Some(next) => current = Rc::clone(next),
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticMessage, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        RedundantBranchCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn branch_followed_by_jump_to_target() {
+        let input = "\
+            main:                      \n\
+                beq    a0, a1, end     \n\
+                j      end             \n\
+                li     a0, 1           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::RedundantBranch(node)
+                if node.token().text == "beq a0 a1 end"
+        ));
+    }
+
+    #[test]
+    fn branch_to_next_instruction() {
+        let input = "\
+            main:                      \n\
+                bne    a0, a1, next    \n\
+            next:                      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(&lints[0], LintError::RedundantBranch(_)));
+    }
+
+    #[test]
+    fn pseudo_branch_is_named_as_written() {
+        let input = "\
+            main:                      \n\
+                ble    a0, a1, next    \n\
+            next:                      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(lints[0]
+            .long_description()
+            .contains("of this `ble` continue"));
+    }
+
+    #[test]
+    fn branch_through_chain_of_jumps() {
+        let input = "\
+            main:                      \n\
+                blt    a0, a1, first   \n\
+                j      end             \n\
+            first:                     \n\
+                j      end             \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn branch_with_different_paths() {
+        let input = "\
+            main:                      \n\
+                beq    a0, a1, end     \n\
+                li     a0, 1           \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn jump_loop_does_not_hang() {
+        let input = "\
+            main:                      \n\
+                beq    a0, a1, spin    \n\
+                j      end             \n\
+            spin:                      \n\
+                j      spin            \n\
+            end:                       \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
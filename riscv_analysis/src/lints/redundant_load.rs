@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{LabelString, Load, LoadType, ParserNode, Register},
+    passes::{LintError, LintPass},
+};
+
+/// A value loaded from memory that is still held in a register.
+#[derive(Clone, PartialEq)]
+struct LoadedValue {
+    /// The base register of the address.
+    base: Register,
+    /// The offset from the base register.
+    offset: i32,
+    inst: LoadType,
+    /// The register that holds the loaded value.
+    rd: Register,
+    load: ParserNode,
+}
+
+impl LoadedValue {
+    /// Get the memory that this value was loaded from.
+    fn access(&self) -> MemoryAccess {
+        MemoryAccess {
+            base: self.base,
+            offset: self.offset,
+            width: self.inst.width(),
+        }
+    }
+
+    /// Check if a load reads this value.
+    fn is_read_by(&self, load: &Load) -> bool {
+        self.base == load.rs1.data && self.offset == load.imm.data.0 && self.inst == load.inst.data
+    }
+
+    /// Check if two values are the same, even if different loads read them.
+    fn is_same(&self, other: &Self) -> bool {
+        self.base == other.base
+            && self.offset == other.offset
+            && self.inst == other.inst
+            && self.rd == other.rd
+    }
+}
+
+/// The memory that an address points into, when it is known.
+#[derive(PartialEq)]
+enum Region {
+    Stack,
+    Data(LabelString),
+}
+
+/// The bytes that a load or a store reads or writes.
+pub(crate) struct MemoryAccess {
+    /// The base register of the address.
+    pub base: Register,
+    /// The offset from the base register.
+    pub offset: i32,
+    /// The number of bytes.
+    pub width: usize,
+}
+
+/// A lint to find loads of a value that is already in a register.
+///
+/// A load is redundant if, on every path to it, the same address was
+/// already loaded with the same instruction, and since then:
+/// - the base register of the address was not written,
+/// - the register holding the first result was not written,
+/// - no store could have written to the address, and
+/// - there was no call or system call, which could write to any memory.
+///
+/// A store through a different base register is assumed to write to the
+/// address, unless the available values of both registers show that they
+/// point into different regions of memory or to bytes that do not overlap.
+pub struct RedundantLoadCheck;
+impl LintPass for RedundantLoadCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::REDUNDANT_LOAD.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let states = Self::loaded_values(cfg);
+        for node in cfg {
+            let ParserNode::Load(load) = node.node() else {
+                continue;
+            };
+            let Some(state) = Self::state_in(&node, &states) else {
+                continue;
+            };
+            if let Some(first) = state.iter().find(|value| value.is_read_by(&load)) {
+                errors.push(LintError::RedundantLoad(
+                    node.node(),
+                    first.load.clone(),
+                    first.rd,
+                ));
+            }
+        }
+    }
+}
+
+impl RedundantLoadCheck {
+    /// Check if a node could write to any memory, like a call.
+    pub(crate) fn clobbers_memory(node: &ParserNode) -> bool {
+        node.calls_to().is_some()
+            || node.is_ecall()
+            || matches!(node, ParserNode::JumpLinkR(x) if x.rd.data != Register::X0)
+    }
+
+    /// Get the region and offset that a register plus an offset points to
+    /// before a node.
+    fn address(node: &CfgNode, reg: Register, offset: i32) -> Option<(Region, i32)> {
+        match node.reg_values_in().get(&reg) {
+            Some(AvailableValue::OriginalRegisterWithScalar(Register::X2, base)) => {
+                Some((Region::Stack, base.checked_add(offset)?))
+            }
+            Some(AvailableValue::Address(label)) => Some((Region::Data(label.clone()), offset)),
+            _ => None,
+        }
+    }
+
+    /// Check if two accesses could touch the same bytes, using the values
+    /// of their base registers before a node.
+    pub(crate) fn may_alias(node: &CfgNode, first: &MemoryAccess, second: &MemoryAccess) -> bool {
+        let overlaps = |a: i32, a_width: usize, b: i32, b_width: usize| {
+            let a_end = i64::from(a) + i64::try_from(a_width).unwrap_or(i64::MAX);
+            let b_end = i64::from(b) + i64::try_from(b_width).unwrap_or(i64::MAX);
+            i64::from(a) < b_end && i64::from(b) < a_end
+        };
+        if first.base == second.base {
+            return overlaps(first.offset, first.width, second.offset, second.width);
+        }
+        match (
+            Self::address(node, first.base, first.offset),
+            Self::address(node, second.base, second.offset),
+        ) {
+            (Some((first_region, first_at)), Some((second_region, second_at))) => {
+                first_region == second_region
+                    && overlaps(first_at, first.width, second_at, second.width)
+            }
+            _ => true,
+        }
+    }
+
+    /// Combine the values loaded after the previous nodes of a node.
+    ///
+    /// Previous nodes that were not reached yet are skipped. Returns `None`
+    /// if none of them were reached.
+    fn state_in(
+        node: &CfgNode,
+        states: &HashMap<Uuid, Vec<LoadedValue>>,
+    ) -> Option<Vec<LoadedValue>> {
+        node.prevs()
+            .iter()
+            .filter_map(|prev| states.get(&prev.node().id()))
+            .cloned()
+            .reduce(|acc, state| {
+                acc.into_iter()
+                    .filter(|value| state.iter().any(|other| value.is_same(other)))
+                    .collect()
+            })
+    }
+
+    /// Find the loaded values that are held in registers after each node.
+    fn loaded_values(cfg: &Cfg) -> HashMap<Uuid, Vec<LoadedValue>> {
+        let mut states: HashMap<Uuid, Vec<LoadedValue>> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in cfg.reverse_post_order() {
+                let node_value = node.node();
+                let mut state = if node_value.is_any_entry() || Self::clobbers_memory(&node_value) {
+                    Vec::new()
+                } else {
+                    Self::state_in(&node, &states).unwrap_or_default()
+                };
+
+                if let ParserNode::Store(store) = &node_value {
+                    let access = MemoryAccess {
+                        base: store.rs1.data,
+                        offset: store.imm.data.0,
+                        width: store.inst.data.width(),
+                    };
+                    state.retain(|value| !Self::may_alias(&node, &value.access(), &access));
+                }
+                if let Some(rd) = node_value.stores_to() {
+                    state.retain(|value| value.base != rd.data && value.rd != rd.data);
+                }
+                if let ParserNode::Load(load) = &node_value {
+                    let known = state.iter().any(|value| value.is_read_by(load));
+                    if !known && load.rd.data != load.rs1.data && load.rd.data != Register::X0 {
+                        state.push(LoadedValue {
+                            base: load.rs1.data,
+                            offset: load.imm.data.0,
+                            inst: load.inst.data,
+                            rd: load.rd.data,
+                            load: node_value.clone(),
+                        });
+                    }
+                }
+
+                if states.get(&node_value.id()) != Some(&state) {
+                    states.insert(node_value.id(), state);
+                    changed = true;
+                }
+            }
+        }
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticMessage, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        RedundantLoadCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    /// Load the same word twice with `between` in the middle.
+    fn load_twice(between: &str) -> String {
+        format!(
+            "main:\n\
+             lw t0, 0(a0)\n\
+             {between}\n\
+             lw t1, 0(a0)\n\
+             add a0, t0, t1\n\
+             li a7, 1\n\
+             ecall\n\
+             li a7, 10\n\
+             ecall\n"
+        )
+    }
+
+    #[test]
+    fn second_load_is_redundant() {
+        let lints = run_pass(&load_twice("addi t2, t2, 1"));
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::RedundantLoad(node, first, Register::X5)
+                if node.token().text == "lw t1 0 ( a0 )"
+                    && first.token().text == "lw t0 0 ( a0 )"
+        ));
+        assert!(lints[0].long_description().contains("`mv t1, t0`"));
+        let related = lints[0].related().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].range.start.line, 1);
+    }
+
+    #[test]
+    fn load_on_every_path_is_redundant() {
+        let input = "\
+            main:                      \n\
+                beqz   a1, other       \n\
+                lw     t0, 4(a0)       \n\
+                j      read            \n\
+            other:                     \n\
+                lw     t0, 4(a0)       \n\
+            read:                      \n\
+                lw     t0, 4(a0)       \n\
+                mv     a0, t0          \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0],
+            LintError::RedundantLoad(_, _, Register::X5)
+        ));
+    }
+
+    #[test]
+    fn store_to_same_address_is_not_redundant() {
+        assert_eq!(run_pass(&load_twice("sw t2, 0(a0)")).len(), 0);
+    }
+
+    #[test]
+    fn store_through_other_register_is_not_redundant() {
+        // `a1` might point to the same address as `a0`
+        assert_eq!(run_pass(&load_twice("sw t2, 0(a1)")).len(), 0);
+    }
+
+    #[test]
+    fn store_to_other_bytes_is_redundant() {
+        assert_eq!(run_pass(&load_twice("sw t2, 4(a0)")).len(), 1);
+    }
+
+    #[test]
+    fn store_to_other_region_is_redundant() {
+        let input = "\
+            .data                      \n\
+            value:  .word 1            \n\
+            .text                      \n\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                la     t3, value       \n\
+                lw     t0, 0(t3)       \n\
+                sw     t0, 0(sp)       \n\
+                lw     t1, 0(t3)       \n\
+                add    a0, t0, t1      \n\
+                addi   sp, sp, 16      \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        assert_eq!(run_pass(input).len(), 1);
+    }
+
+    #[test]
+    fn call_is_not_redundant() {
+        let input = "\
+            main:                      \n\
+                lw     s0, 0(s1)       \n\
+                jal    fn_a            \n\
+                lw     s2, 0(s1)       \n\
+                add    a0, s0, s2      \n\
+                li     a7, 1           \n\
+                ecall                  \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn write_to_base_is_not_redundant() {
+        assert_eq!(run_pass(&load_twice("addi a0, a0, 4")).len(), 0);
+    }
+
+    #[test]
+    fn write_to_first_result_is_not_redundant() {
+        assert_eq!(run_pass(&load_twice("addi t0, t0, 4")).len(), 0);
+    }
+
+    #[test]
+    fn different_width_is_not_redundant() {
+        let input = load_twice("addi t2, t2, 1").replace("lw t1", "lb t1");
+        assert_eq!(run_pass(&input).len(), 0);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{ParserNode, Register},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find the return address register used as a scratch register.
+///
+/// A `ret` jumps to the address in `ra`. If `ra` is written with some other
+/// value, like in `mv ra, t0` or `li ra, 1`, and the original value is not
+/// restored before the `ret`, the program returns to the wrong place.
+///
+/// Calls also write to `ra`, but those are checked by the callee-saved
+/// register lints. A write that puts the original return address back, like
+/// a restore from the stack, is not a misuse.
+pub struct ReturnAddressMisuseCheck;
+impl LintPass for ReturnAddressMisuseCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::RETURN_ADDRESS_OVERWRITTEN.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let writes_in = Self::scratch_writes_in(cfg);
+
+        // Collect the returns that every write reaches
+        let mut returns: HashMap<Uuid, Vec<ParserNode>> = HashMap::new();
+        for node in cfg {
+            if !node.node().is_return() {
+                continue;
+            }
+            for write in writes_in.get(&node.node().id()).into_iter().flatten() {
+                returns.entry(*write).or_default().push(node.node());
+            }
+        }
+
+        for node in cfg {
+            let Some(rets) = returns.remove(&node.node().id()) else {
+                continue;
+            };
+            if let Some(rd) = node.node().stores_to() {
+                errors.push(LintError::ReturnAddressOverwritten(rd, rets));
+            }
+        }
+    }
+}
+
+impl ReturnAddressMisuseCheck {
+    /// Check if a node writes something other than a return address to `ra`.
+    fn is_scratch_write(node: &CfgNode) -> bool {
+        let parser_node = node.node();
+        if parser_node.stores_to().is_none_or(|rd| rd != Register::X1) {
+            return false;
+        }
+        // Outside of a function, `ra` does not hold a return address
+        if !node.is_part_of_some_function() {
+            return false;
+        }
+        // Calls set `ra` to a return address
+        if matches!(
+            parser_node,
+            ParserNode::JumpLink(_) | ParserNode::JumpLinkR(_)
+        ) {
+            return false;
+        }
+        node.reg_values_out().get(&Register::X1)
+            != Some(&AvailableValue::OriginalRegisterWithScalar(Register::X1, 0))
+    }
+
+    /// Compute the scratch writes of `ra` that can reach every node without
+    /// `ra` being written again.
+    fn scratch_writes_in(cfg: &Cfg) -> HashMap<Uuid, HashSet<Uuid>> {
+        let mut ins: HashMap<Uuid, HashSet<Uuid>> = HashMap::new();
+        let mut outs: HashMap<Uuid, HashSet<Uuid>> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in cfg.reverse_post_order() {
+                // in[n] = OR out[p] for all p in prev[n]
+                let in_n = node
+                    .prevs()
+                    .iter()
+                    .filter_map(|prev| outs.get(&prev.node().id()))
+                    .flatten()
+                    .copied()
+                    .collect::<HashSet<_>>();
+
+                let out_n = if Self::is_scratch_write(&node) {
+                    HashSet::from([node.node().id()])
+                } else if node.node().stores_to().is_some_and(|rd| rd == Register::X1) {
+                    HashSet::new()
+                } else {
+                    in_n.clone()
+                };
+
+                if outs.get(&node.node().id()) != Some(&out_n) {
+                    changed = true;
+                    outs.insert(node.node().id(), out_n);
+                }
+                ins.insert(node.node().id(), in_n);
+            }
+        }
+        ins
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        ReturnAddressMisuseCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn return_address_used_as_scratch() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     ra, 5           \n\
+                add    a0, a0, ra      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::ReturnAddressOverwritten(rd, rets)
+                if rd.data == Register::X1 && rets.len() == 1
+        ));
+    }
+
+    #[test]
+    fn return_address_restored_from_register() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                mv     t0, ra          \n\
+                li     ra, 5           \n\
+                add    a0, a0, ra      \n\
+                mv     ra, t0          \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn return_address_restored_from_stack() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                jal    fn_b            \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n\
+            fn_b:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn return_address_overwritten_on_one_path() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                beqz   a0, done        \n\
+                mv     ra, a0          \n\
+            done:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn return_address_written_outside_function() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     ra, 0           \n\
+            fn_a:                      \n\
+                addi   a0, a0, 1       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
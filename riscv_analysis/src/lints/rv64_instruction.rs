@@ -0,0 +1,91 @@
+use crate::{
+    cfg::Cfg,
+    parser::Xlen,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find RV64 instructions in a program for an RV32 target.
+///
+/// Instructions like `ld`, `sd` and `addiw` are always parsed, so that
+/// their registers are tracked, but they do not exist on RV32 targets.
+pub struct Rv64InstructionCheck;
+impl LintPass for Rv64InstructionCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::RV64_ONLY_INSTRUCTION.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        if cfg.xlen() != Xlen::Rv32 {
+            return;
+        }
+
+        for node in cfg {
+            if node.node().is_instruction() && node.node().inst().is_rv64_only() {
+                errors.push(LintError::Rv64OnlyInstruction(node.node()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{Manager, ManagerConfiguration};
+
+    fn run_pass(input: &str, xlen: Xlen) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let config = ManagerConfiguration {
+            xlen,
+            ..Default::default()
+        };
+        let cfg = Manager::gen_full_cfg_with_config(nodes, &config).unwrap();
+        Rv64InstructionCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    const DOUBLEWORD_COPY: &str = "\
+        main:                      \n\
+            ld     t0, 0(a0)       \n\
+            addiw  t0, t0, 1       \n\
+            sd     t0, 0(a1)       \n\
+            ret                    \n";
+
+    #[test]
+    fn rv64_instructions_on_rv32() {
+        let lints = run_pass(DOUBLEWORD_COPY, Xlen::Rv32);
+        assert_eq!(lints.len(), 3);
+        assert!(matches!(
+            &lints[0], LintError::Rv64OnlyInstruction(node)
+                if node.token().text == "ld t0 0 ( a0 )"
+        ));
+    }
+
+    #[test]
+    fn pseudo_instructions_are_reported_as_written() {
+        let input = "\
+            main:                      \n\
+                negw   t0, a0          \n\
+                sext.w t1, a1          \n\
+                ret                    \n";
+
+        let messages = run_pass(input, Xlen::Rv32)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "Instruction negw is only available on RV64",
+                "Instruction sext.w is only available on RV64",
+            ]
+        );
+    }
+
+    #[test]
+    fn rv64_instructions_on_rv64() {
+        let lints = run_pass(DOUBLEWORD_COPY, Xlen::Rv64);
+        assert_eq!(lints.len(), 0);
+    }
+}
@@ -0,0 +1,157 @@
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::{
+    cfg::{Cfg, CfgNode, RegisterSet},
+    parser::{RegSets, Register, Token, With},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find instructions whose result is written to `x0`.
+///
+/// The zero register always holds 0, so an instruction whose only effect is
+/// to write to it does nothing. Instructions that write to `x0` on purpose
+/// are not reported: jumps that do not link, reads of a CSR for their side
+/// effect, `addi x0, x0, 0`, and instructions expanded from a
+/// pseudo-instruction that chose `x0` itself, like `nop`.
+///
+/// If a register is read later without being set, the result was likely
+/// meant for it, so it is suggested.
+pub struct SaveToZeroCheck;
+impl LintPass for SaveToZeroCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::SAVE_TO_ZERO.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let program_garbage = cfg
+            .iter()
+            .find(|node| node.node().is_program_entry())
+            .map(|entry| entry.live_out() - RegSets::program_args());
+        for node in cfg {
+            let Some(rd) = node.node().stores_to() else {
+                continue;
+            };
+            if rd.data != Register::X0
+                || node.node().can_skip_save_checks()
+                || node.node().is_nop()
+                || !Self::is_written(&rd)
+            {
+                continue;
+            }
+            let intended = Self::unset_registers(&node, program_garbage)
+                .and_then(|unset| (&(node.live_out() & unset)).into_iter().next());
+            errors.push(LintError::SaveToZero(rd, intended));
+        }
+    }
+}
+
+impl SaveToZeroCheck {
+    /// Check if a register was written in the source, rather than chosen by
+    /// the expansion of a pseudo-instruction.
+    ///
+    /// Registers that were written keep the token of their name. Registers
+    /// chosen by an expansion have the token of the instruction instead.
+    fn is_written(reg: &With<Register>) -> bool {
+        matches!(&reg.token, Token::Symbol(name) if Register::from_str(name).is_ok())
+    }
+
+    /// Get the registers that are read before they are set in the function
+    /// of a node, or in the program if the node is not in a function.
+    fn unset_registers(
+        node: &Rc<CfgNode>,
+        program_garbage: Option<RegisterSet>,
+    ) -> Option<RegisterSet> {
+        let functions = node.functions();
+        match functions.iter().next() {
+            Some(func) => {
+                Some(func.entry().live_out() - func.arguments() - RegSets::callee_saved())
+            }
+            None => program_garbage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        SaveToZeroCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn accidental_write_to_zero() {
+        let input = "\
+            main:                      \n\
+                li     a0, 1           \n\
+                li     a1, 2           \n\
+                add    x0, a0, a1      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::SaveToZero(rd, None)
+                if rd.data == Register::X0 && rd.pos.start.line == 3
+        ));
+    }
+
+    #[test]
+    fn nop_is_not_reported() {
+        let input = "\
+            main:                      \n\
+                nop                    \n\
+                addi   x0, x0, 0       \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn csr_read_is_not_reported() {
+        let input = "\
+            main:                      \n\
+                csrrs  x0, cycle, x0   \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn unset_register_is_suggested() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                add    zero, a0, a0    \n\
+                addi   a0, t0, 1       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0],
+            LintError::SaveToZero(_, Some(Register::X5))
+        ));
+    }
+}
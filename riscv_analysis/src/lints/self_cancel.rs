@@ -0,0 +1,87 @@
+use crate::{
+    cfg::Cfg,
+    parser::{ArithType, ParserNode},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find subtractions and exclusive ors of a register with itself.
+///
+/// `sub t0, s0, s0` and `xor t0, s0, s0` always produce zero. This is
+/// usually a typo for two different registers. If zeroing the register is
+/// intended, `li t0, 0` says so more clearly.
+pub struct SelfCancellingArithCheck;
+impl LintPass for SelfCancellingArithCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::SELF_CANCELLING_ARITH.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for node in cfg {
+            let ParserNode::Arith(expr) = node.node() else {
+                continue;
+            };
+            if matches!(
+                expr.inst.data,
+                ArithType::Sub | ArithType::Subw | ArithType::Xor
+            ) && expr.rs1 == expr.rs2
+            {
+                errors.push(LintError::SelfCancellingArith(node.node()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticMessage, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        SelfCancellingArithCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn subtract_register_from_itself() {
+        let input = "\
+            main:                      \n\
+                sub    a0, s0, s0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::SelfCancellingArith(node)
+                if node.token().text == "sub a0 s0 s0"
+        ));
+        assert!(lints[0].long_description().starts_with("`sub a0 s0 s0`"));
+    }
+
+    #[test]
+    fn xor_register_with_itself() {
+        let input = "\
+            main:                      \n\
+                xor    a0, a0, a0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+    }
+
+    #[test]
+    fn different_registers() {
+        let input = "\
+            main:                      \n\
+                sub    a0, a0, a1      \n\
+                xor    a0, a0, a1      \n\
+                add    a0, a0, a0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
@@ -0,0 +1,204 @@
+use std::{collections::HashSet, rc::Rc};
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{IArithType, ParserNode, Register},
+    passes::{DiagnosticLocation, LintError, LintPass},
+};
+
+/// The alignment of the stack pointer that the calling convention requires.
+const STACK_ALIGNMENT: i32 = 16;
+
+/// A lint to find stack pointer adjustments that leave the stack pointer
+/// unaligned when a function is called.
+///
+/// The RISC-V calling convention requires the stack pointer to be a multiple
+/// of 16 bytes at every call. The stack pointer is tracked relative to its
+/// value at the start of the function, so many adjustments in a row are
+/// summed. Adjustments by an unknown amount are skipped.
+pub struct StackAlignmentCheck;
+impl LintPass for StackAlignmentCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNALIGNED_STACK_POINTER.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let mut adjustments: Vec<(Rc<CfgNode>, i32, Vec<ParserNode>)> = Vec::new();
+        for node in cfg {
+            if node.node().calls_to().is_none() {
+                continue;
+            }
+            let Some(offset) = Self::stack_offset(node.reg_values_in().get(&Register::X2)) else {
+                continue;
+            };
+            if offset % STACK_ALIGNMENT == 0 {
+                continue;
+            }
+
+            for adjustment in Self::last_adjustments(&node) {
+                let id = adjustment.node().id();
+                if let Some((.., calls)) =
+                    adjustments.iter_mut().find(|(x, ..)| x.node().id() == id)
+                {
+                    calls.push(node.node());
+                    continue;
+                }
+                let offset = Self::stack_offset(adjustment.reg_values_out().get(&Register::X2))
+                    .unwrap_or(offset);
+                adjustments.push((adjustment, offset, vec![node.node()]));
+            }
+        }
+
+        for (adjustment, offset, mut calls) in adjustments {
+            calls.sort_by_key(DiagnosticLocation::range);
+            errors.push(LintError::UnalignedStackPointer(
+                adjustment.node(),
+                offset,
+                calls,
+            ));
+        }
+    }
+}
+
+impl StackAlignmentCheck {
+    /// Get the offset of a stack pointer value from the stack pointer at the
+    /// start of the function.
+    fn stack_offset(value: Option<&AvailableValue>) -> Option<i32> {
+        match value {
+            Some(AvailableValue::OriginalRegisterWithScalar(Register::X2, offset)) => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Check if a node adds an immediate to the stack pointer.
+    fn is_adjustment(node: &ParserNode) -> bool {
+        match node {
+            ParserNode::IArith(x) => {
+                matches!(x.inst.data, IArithType::Addi | IArithType::Addiw)
+                    && x.rd == Register::X2
+                    && x.rs1 == Register::X2
+            }
+            _ => false,
+        }
+    }
+
+    /// Find the stack pointer adjustments that set the stack pointer used by
+    /// a node.
+    ///
+    /// Writes to the stack pointer that are not adjustments end the search
+    /// without being reported.
+    fn last_adjustments(node: &Rc<CfgNode>) -> Vec<Rc<CfgNode>> {
+        let mut found = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = node.prevs().iter().map(Rc::clone).collect::<Vec<_>>();
+        while let Some(prev) = stack.pop() {
+            if !visited.insert(prev.node().id()) {
+                continue;
+            }
+            if prev.node().stores_to().is_some_and(|rd| rd == Register::X2) {
+                if Self::is_adjustment(&prev.node()) {
+                    found.push(prev);
+                }
+                continue;
+            }
+            stack.extend(prev.prevs().iter().map(Rc::clone));
+        }
+        found.sort_by_key(|x| x.node().range());
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        StackAlignmentCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn aligned_stack_at_call() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                jal    fn_a            \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn unaligned_stack_at_call() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -12     \n\
+                sw     ra, 0(sp)       \n\
+                jal    fn_a            \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 12      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnalignedStackPointer(node, -12, calls)
+                if node.token().text == "addi sp sp -12" && calls.len() == 1
+        ));
+        assert_eq!(lints[0].range().start.line, 1);
+    }
+
+    #[test]
+    fn chained_adjustments_are_summed() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -8      \n\
+                addi   sp, sp, -8      \n\
+                jal    fn_a            \n\
+                addi   sp, sp, -4      \n\
+                jal    fn_a            \n\
+                addi   sp, sp, 20      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnalignedStackPointer(node, -20, _)
+                if node.token().text == "addi sp sp -4"
+        ));
+    }
+
+    #[test]
+    fn unknown_adjustment_is_skipped() {
+        let input = "\
+            main:                      \n\
+                sub    sp, sp, a0      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
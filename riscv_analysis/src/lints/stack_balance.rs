@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use crate::{
+    cfg::{Cfg, CfgNode, Function},
+    parser::{IArithType, ParserNode, Register},
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find paths through a function that do not undo its stack
+/// pointer adjustments before returning.
+///
+/// Every path from the entry of a function is followed, and the amounts
+/// added to the stack pointer by `addi sp, sp, imm` are summed along the
+/// path. If the sum is not zero at a return, the stack pointer of the caller
+/// is wrong after the call. A path that sets the stack pointer in any other
+/// way is not followed, as the amount is not known.
+///
+/// Paths that never return, like ones that end in an infinite loop or a tail
+/// call, are not checked.
+pub struct StackBalanceCheck;
+impl LintPass for StackBalanceCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNBALANCED_STACK.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for func in &cfg.unique_functions() {
+            Self::check_function(func, errors);
+        }
+    }
+}
+
+/// A path through a function, as the nodes left to visit.
+struct PathState {
+    node: Rc<CfgNode>,
+    /// The stack pointer relative to its value at the entry.
+    offset: i32,
+    /// The stack pointer adjustments on the path, in order.
+    adjustments: Vec<ParserNode>,
+    /// The nodes on the path, to stop at loops.
+    visited: Vec<Uuid>,
+}
+
+impl StackBalanceCheck {
+    fn check_function(func: &Function, errors: &mut Vec<LintError>) {
+        let returns = func
+            .return_sites()
+            .iter()
+            .map(|node| node.node().id())
+            .collect::<HashSet<_>>();
+        // Each node is only followed once for each stack pointer offset
+        let mut seen = HashSet::new();
+        let mut stack = vec![PathState {
+            node: func.entry(),
+            offset: 0,
+            adjustments: Vec::new(),
+            visited: Vec::new(),
+        }];
+        while let Some(mut state) = stack.pop() {
+            let node = state.node.node();
+            let id = node.id();
+            // A loop that moves the stack pointer would give a new offset on
+            // every iteration, so a path stops when it goes around a loop
+            if state.visited.contains(&id) {
+                continue;
+            }
+
+            if let Some(imm) = Self::adjustment(&node) {
+                let Some(offset) = state.offset.checked_add(imm) else {
+                    continue;
+                };
+                state.offset = offset;
+                state.adjustments.push(node.clone());
+            } else if node.stores_to().is_some_and(|rd| rd == Register::X2) {
+                continue;
+            }
+            if !seen.insert((id, state.offset)) {
+                continue;
+            }
+
+            // Other returns are jumps to the first one, so the diagnostic
+            // is at the return of the path, not at the first return
+            if returns.contains(&id) {
+                if state.offset != 0 {
+                    errors.push(LintError::UnbalancedStack(
+                        node,
+                        state.offset,
+                        state.adjustments,
+                    ));
+                }
+                continue;
+            }
+            if node.is_tail_call() {
+                continue;
+            }
+
+            state.visited.push(id);
+            for next in state.node.nexts().iter() {
+                stack.push(PathState {
+                    node: Rc::clone(next),
+                    offset: state.offset,
+                    adjustments: state.adjustments.clone(),
+                    visited: state.visited.clone(),
+                });
+            }
+        }
+    }
+
+    /// Get the amount that a node adds to the stack pointer, if it adds an
+    /// immediate to it.
+    fn adjustment(node: &ParserNode) -> Option<i32> {
+        match node {
+            ParserNode::IArith(x)
+                if matches!(x.inst.data, IArithType::Addi | IArithType::Addiw)
+                    && x.rd == Register::X2
+                    && x.rs1 == Register::X2 =>
+            {
+                Some(x.imm.data.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticLocation, DiagnosticMessage, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        StackBalanceCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn balanced_function() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                beqz   a0, skip        \n\
+                addi   sp, sp, -16     \n\
+                sw     a0, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+            skip:                      \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn restore_missing_on_one_branch() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                beqz   a0, early       \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n\
+            early:                     \n\
+                lw     ra, 0(sp)       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnbalancedStack(_, -16, adjustments)
+                if adjustments.len() == 1
+        ));
+        // The diagnostic points at the return that is not balanced
+        assert_eq!(lints[0].range().start.line, 13);
+        assert!(lints[0].long_description().contains("16 bytes"));
+    }
+
+    #[test]
+    fn over_restore() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -8      \n\
+                sw     ra, 0(sp)       \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnbalancedStack(_, 8, adjustments)
+                if adjustments.len() == 2
+        ));
+    }
+
+    #[test]
+    fn paths_that_never_return() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                jal    fn_b            \n\
+                jal    fn_c            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                beqz   a0, spin        \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n\
+            spin:                      \n\
+                j      spin            \n\
+            fn_b:                      \n\
+                addi   sp, sp, -16     \n\
+                tail   fn_c            \n\
+            fn_c:                      \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn unknown_adjustment_is_skipped() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -16     \n\
+                sub    sp, sp, a0      \n\
+                ret                    \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+}
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use crate::{
+    cfg::Cfg,
+    parser::RegSets,
+    passes::{DiagnosticLocation, LintError, LintPass},
+};
+
+/// A lint to find functions that read a temporary register before setting
+/// it.
+///
+/// Temporary registers are never arguments, and callers do not keep their
+/// values across calls. A function that reads one on entry depends on a
+/// value that its caller happened to leave there.
+pub struct TemporaryInputCheck;
+impl LintPass for TemporaryInputCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::TEMPORARY_READ_ON_ENTRY.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for func in &cfg.unique_functions() {
+            let entry = func.entry();
+            let temporaries = entry.live_out() & RegSets::temporary();
+            if temporaries.is_empty() {
+                continue;
+            }
+            let mut reads = temporaries
+                .into_iter()
+                .flat_map(|reg| Cfg::error_ranges_for_first_usage(&entry, reg))
+                .collect::<Vec<_>>();
+            reads.sort_by_key(DiagnosticLocation::range);
+            errors.push(LintError::TemporaryReadOnEntry(
+                entry.node(),
+                Rc::clone(func),
+                reads,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{RVStringParser, Register};
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        TemporaryInputCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn temporary_read_on_entry() {
+        let input = "\
+            main:                      \n\
+                li     t0, 5           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                add    a0, a0, t0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::TemporaryReadOnEntry(_, func, reads)
+                if func.name().0 == "fn_a"
+                    && reads.len() == 1
+                    && reads[0].data == Register::X5
+                    && reads[0].pos.start.line == 6
+        ));
+    }
+
+    #[test]
+    fn temporary_set_before_read() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                li     t0, 5           \n\
+                add    a0, a0, t0      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn argument_read_on_entry() {
+        let input = "\
+            main:                      \n\
+                li     a1, 5           \n\
+                jal    fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                add    a0, a0, a1      \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
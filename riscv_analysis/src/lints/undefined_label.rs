@@ -0,0 +1,105 @@
+use crate::{
+    cfg::Cfg,
+    passes::{LintError, LintPass},
+};
+
+/// A lint to find calls, jumps, branches and address loads of labels that
+/// are not defined.
+///
+/// Labels are looked up once every included file is read, so a label
+/// defined in another file of the program is not reported. Labels declared
+/// with `.globl` or `.extern` are taken to be defined outside of the
+/// program, and are not reported either.
+pub struct UndefinedLabelCheck;
+impl LintPass for UndefinedLabelCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNDEFINED_LABEL.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for label in cfg.undefined_labels() {
+            errors.push(LintError::UndefinedLabel(label.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticLocation, Manager};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        UndefinedLabelCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn undefined_branch_target() {
+        let input = "\
+            main:                      \n\
+                li     t0, 10          \n\
+            loop:                      \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, lop         \n\
+                beqz   t0, lop         \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        // Each use of the label is reported
+        assert_eq!(lints.len(), 2);
+        assert!(matches!(
+            &lints[0], LintError::UndefinedLabel(label) if label.data == "lop"
+        ));
+        assert_eq!(lints[0].range().start.line, 4);
+        assert_eq!(lints[0].range().start.column, 11);
+        assert_eq!(lints[1].range().start.line, 5);
+    }
+
+    #[test]
+    fn undefined_call() {
+        let input = "\
+            main:                      \n\
+                call   fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].to_string(), "Label fn_a is not defined");
+    }
+
+    #[test]
+    fn extern_label_is_not_reported() {
+        let input = "\
+            .extern printf, exit       \n\
+            .globl  helper             \n\
+            main:                      \n\
+                la     a0, printf      \n\
+                call   printf          \n\
+                call   helper          \n\
+                j      exit            \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+
+    #[test]
+    fn defined_labels_are_not_reported() {
+        let input = "\
+            main:                      \n\
+                la     a0, msg         \n\
+                call   fn_a            \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                ret                    \n\
+            .data                      \n\
+            msg: .word 1               \n";
+
+        assert_eq!(run_pass(input).len(), 0);
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{ParserNode, Register},
+    passes::{LintError, LintPass},
+};
+
+/// The bytes of the stack frame that were written to.
+///
+/// Bytes are offsets from the stack pointer at the start of the function.
+/// If the address of the frame escaped, like by being passed to a call, or
+/// a store went to an unknown address, any byte could have been written.
+#[derive(Clone, PartialEq, Eq, Default)]
+struct WrittenBytes {
+    bytes: HashSet<i32>,
+    escaped: bool,
+}
+
+impl WrittenBytes {
+    fn contains(&self, byte: i32) -> bool {
+        self.escaped || self.bytes.contains(&byte)
+    }
+
+    /// Keep the bytes that are written on both paths.
+    fn intersect(&self, other: &Self) -> Self {
+        match (self.escaped, other.escaped) {
+            (true, _) => other.clone(),
+            (_, true) => self.clone(),
+            _ => WrittenBytes {
+                bytes: &self.bytes & &other.bytes,
+                escaped: false,
+            },
+        }
+    }
+
+    /// Keep the bytes that are written on either path.
+    fn union(&self, other: &Self) -> Self {
+        WrittenBytes {
+            bytes: &self.bytes | &other.bytes,
+            escaped: self.escaped || other.escaped,
+        }
+    }
+}
+
+/// The stack bytes written on every path to a node, and on some path to it.
+#[derive(Clone, PartialEq, Eq, Default)]
+struct StackState {
+    must: WrittenBytes,
+    may: WrittenBytes,
+}
+
+/// A lint to find loads from stack slots that were never stored to.
+///
+/// Stack slots are found with the available values of the base registers,
+/// as offsets from the stack pointer at the start of the function. A load
+/// from a slot with no store to it on any path reads garbage. If only some
+/// paths store to the slot, the load might read garbage.
+///
+/// Loads and stores through a base register with an unknown value are not
+/// checked, and once the address of the stack frame escapes to a call, any
+/// slot could have been written.
+pub struct UninitializedStackReadCheck;
+impl LintPass for UninitializedStackReadCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNINITIALIZED_STACK_READ.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let states = Self::written_bytes(cfg);
+        for node in cfg {
+            let ParserNode::Load(load) = node.node() else {
+                continue;
+            };
+            let Some(slot) = Self::stack_slot(&node, load.rs1.data, load.imm.data.0) else {
+                continue;
+            };
+            // Slots at or above the start of the frame belong to the caller
+            if slot >= 0 {
+                continue;
+            }
+            let Some(state) = Self::state_in(&node, &states) else {
+                continue;
+            };
+
+            let width = i32::try_from(load.inst.data.width()).unwrap_or(i32::MAX);
+            let bytes = (slot..slot.saturating_add(width)).collect::<Vec<_>>();
+            if bytes.iter().all(|byte| state.must.contains(*byte)) {
+                continue;
+            }
+            let never = !bytes.iter().any(|byte| state.may.contains(*byte));
+            errors.push(LintError::UninitializedStackRead(node.node(), slot, never));
+        }
+    }
+}
+
+impl UninitializedStackReadCheck {
+    /// Get the stack slot at a register plus an offset before a node.
+    ///
+    /// Returns `None` if the register does not point into the stack.
+    fn stack_slot(node: &CfgNode, reg: Register, offset: i32) -> Option<i32> {
+        match node.reg_values_in().get(&reg) {
+            Some(AvailableValue::OriginalRegisterWithScalar(Register::X2, base)) => {
+                base.checked_add(offset)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if a store through a register could write to the stack frame
+    /// at an unknown slot.
+    fn may_alias_stack(node: &CfgNode, reg: Register) -> bool {
+        !matches!(
+            node.reg_values_in().get(&reg),
+            Some(
+                AvailableValue::OriginalRegisterWithScalar(..)
+                    | AvailableValue::Address(_)
+                    | AvailableValue::Constant(_)
+            )
+        )
+    }
+
+    /// Check if a call or system call at a node could write to the stack
+    /// frame, because a register other than the stack pointer points to it.
+    fn frame_escapes(node: &CfgNode) -> bool {
+        let node_value = node.node();
+        if node_value.calls_to().is_none() && !node_value.is_ecall() {
+            return false;
+        }
+        node.reg_values_in().iter().any(|(reg, value)| {
+            *reg != Register::X2
+                && matches!(
+                    value,
+                    AvailableValue::OriginalRegisterWithScalar(Register::X2, _)
+                )
+        })
+    }
+
+    /// Combine the states after the previous nodes of a node.
+    ///
+    /// Previous nodes that were not reached yet are skipped. Returns `None`
+    /// if none of them were reached.
+    fn state_in(node: &CfgNode, states: &HashMap<Uuid, StackState>) -> Option<StackState> {
+        node.prevs()
+            .iter()
+            .filter_map(|prev| states.get(&prev.node().id()))
+            .cloned()
+            .reduce(|acc, state| StackState {
+                must: acc.must.intersect(&state.must),
+                may: acc.may.union(&state.may),
+            })
+    }
+
+    /// Find the stack bytes written before and after each node.
+    fn written_bytes(cfg: &Cfg) -> HashMap<Uuid, StackState> {
+        let mut states: HashMap<Uuid, StackState> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in cfg.reverse_post_order() {
+                let mut state = if node.node().is_any_entry() {
+                    StackState::default()
+                } else {
+                    Self::state_in(&node, &states).unwrap_or_default()
+                };
+
+                if let ParserNode::Store(store) = node.node() {
+                    if let Some(slot) = Self::stack_slot(&node, store.rs1.data, store.imm.data.0) {
+                        let width = i32::try_from(store.inst.data.width()).unwrap_or(i32::MAX);
+                        for byte in slot..slot.saturating_add(width) {
+                            state.must.bytes.insert(byte);
+                            state.may.bytes.insert(byte);
+                        }
+                    } else if Self::may_alias_stack(&node, store.rs1.data) {
+                        state.must.escaped = true;
+                        state.may.escaped = true;
+                    }
+                }
+                if Self::frame_escapes(&node) {
+                    state.must.escaped = true;
+                    state.may.escaped = true;
+                }
+
+                if states.get(&node.node().id()) != Some(&state) {
+                    states.insert(node.node().id(), state);
+                    changed = true;
+                }
+            }
+        }
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        UninitializedStackReadCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn read_slot_never_stored() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     a0, 0(sp)       \n\
+                lw     t0, 8(sp)       \n\
+                addi   sp, sp, 16      \n\
+                mv     a0, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UninitializedStackRead(node, -8, true)
+                if node.token().text == "lw t0 8 ( sp )"
+        ));
+    }
+
+    #[test]
+    fn read_slot_stored_on_every_path() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                beqz   a0, other       \n\
+                sw     a0, 8(sp)       \n\
+                j      read            \n\
+            other:                     \n\
+                sw     a1, 8(sp)       \n\
+            read:                      \n\
+                lw     t0, 8(sp)       \n\
+                addi   sp, sp, 16      \n\
+                mv     a0, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn read_slot_stored_on_some_paths() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                beqz   a0, read        \n\
+                sw     a0, 8(sp)       \n\
+            read:                      \n\
+                lw     t0, 8(sp)       \n\
+                addi   sp, sp, 16      \n\
+                mv     a0, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0],
+            LintError::UninitializedStackRead(_, -8, false)
+        ));
+    }
+
+    #[test]
+    fn stored_in_loop_before_read() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                sw     zero, 0(sp)     \n\
+            loop:                      \n\
+                lw     t0, 0(sp)       \n\
+                addi   t0, t0, 1       \n\
+                sw     t0, 0(sp)       \n\
+                blt    t0, a0, loop    \n\
+                addi   sp, sp, 16      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn escaped_frame_is_not_reported() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                mv     a0, sp          \n\
+                jal    fill            \n\
+                lw     t0, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                mv     a0, t0          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fill:                      \n\
+                sw     zero, 0(a0)     \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn unknown_base_register_is_not_reported() {
+        let input = "\
+            main:                      \n\
+                addi   sp, sp, -16     \n\
+                lw     t1, 0(a0)       \n\
+                sw     zero, 0(t1)     \n\
+                lw     t0, 4(sp)       \n\
+                lw     t2, 0(a1)       \n\
+                addi   sp, sp, 16      \n\
+                add    a0, t0, t2      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
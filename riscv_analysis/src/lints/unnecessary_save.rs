@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use crate::{
+    cfg::Cfg,
+    parser::Register,
+    passes::{DiagnosticLocation, LintError, LintPass},
+};
+
+/// A lint to find callee-saved registers that are saved and restored by a
+/// function that never changes them.
+///
+/// The registers a function writes to include the restores of its saved
+/// registers. If the restores are the only writes to a register, the
+/// function would leave the register unchanged anyway, so the save and
+/// restore can be removed.
+///
+/// A save is a store of the original value of a register to the stack, and
+/// a restore is a load that puts the original value back.
+pub struct UnnecessaryCalleeSaveCheck;
+impl LintPass for UnnecessaryCalleeSaveCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNNECESSARY_CALLEE_SAVE.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        for func in &cfg.unique_functions() {
+            // The return address is only saved so that the function can
+            // make calls, so it is not checked here.
+            let nodes = func.nodes();
+            for reg in &(func.to_save() - Register::X1) {
+                let mut saves = Vec::new();
+                let mut restores = Vec::new();
+                let mut clobbered = false;
+                let mut visited = HashSet::new();
+                for node in nodes.iter() {
+                    if !visited.insert(node.node().id()) {
+                        continue;
+                    }
+                    if node.is_stack_save(reg) {
+                        saves.push(node.node());
+                    } else if node.is_stack_restore(reg) {
+                        restores.push(node.node());
+                    } else if node.node().stores_to().is_some_and(|rd| rd == reg) {
+                        clobbered = true;
+                    }
+                }
+
+                if clobbered || restores.is_empty() {
+                    continue;
+                }
+                restores.sort_by_key(DiagnosticLocation::range);
+                for save in saves {
+                    errors.push(LintError::UnnecessaryCalleeSave(
+                        save,
+                        restores.clone(),
+                        reg,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::Manager;
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        UnnecessaryCalleeSaveCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn saved_register_never_changed() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -8      \n\
+                sw     s3, 0(sp)       \n\
+                sw     s4, 4(sp)       \n\
+                li     s4, 1           \n\
+                add    a0, a0, s4      \n\
+                lw     s3, 0(sp)       \n\
+                lw     s4, 4(sp)       \n\
+                addi   sp, sp, 8       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnnecessaryCalleeSave(save, restores, Register::X19)
+                if save.token().text == "sw s3 0 ( sp )"
+                    && restores.len() == 1
+                    && restores[0].token().text == "lw s3 0 ( sp )"
+        ));
+    }
+
+    #[test]
+    fn saved_register_changed() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -4      \n\
+                sw     s3, 0(sp)       \n\
+                mv     s3, a0          \n\
+                add    a0, a0, s3      \n\
+                lw     s3, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn return_address_is_not_reported() {
+        let input = "\
+            main:                      \n\
+                jal    fn_a            \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            fn_a:                      \n\
+                addi   sp, sp, -4      \n\
+                sw     ra, 0(sp)       \n\
+                addi   a0, a0, 1       \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 4       \n\
+                ret                    \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+}
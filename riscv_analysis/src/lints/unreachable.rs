@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use uuid::Uuid;
+
+use crate::{
+    analysis::AvailableValue,
+    cfg::{Cfg, CfgNode},
+    parser::{CSRType, LabelString, ParserNode},
+    passes::{LintError, LintPass},
+};
+
+/// The CSRs that hold the address of a trap handler: `utvec`, `stvec` and
+/// `mtvec`.
+const TRAP_VECTORS: [u32; 3] = [0x005, 0x105, 0x305];
+
+/// A lint to find code that no path reaches.
+///
+/// Every node reachable from the program entry, the entry of a function or
+/// a trap handler is found. The instructions that are left are dead, and
+/// each run of dead instructions in the source is reported once, named by
+/// the label at its start if it has one.
+///
+/// A trap handler is a label whose address is written to a trap vector CSR,
+/// like `csrw t0, utvec`. It is entered by the hardware, so it has no
+/// previous nodes in the CFG.
+pub struct UnreachableCodeCheck;
+impl LintPass for UnreachableCodeCheck {
+    fn lints(&self) -> &'static [&'static str] {
+        &[LintError::UNREACHABLE_CODE.id]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
+        let reachable = Self::reachable(cfg);
+
+        let mut region: Vec<Rc<CfgNode>> = Vec::new();
+        for node in cfg.iter_source() {
+            if !node.node().is_instruction() {
+                continue;
+            }
+            let continues = region.last().is_some_and(|last| {
+                last.node().token().file == node.node().token().file
+                    && last.segment() == node.segment()
+            });
+            if reachable.contains(&node.node().id()) || !continues {
+                Self::report(&mut region, errors);
+            }
+            if !reachable.contains(&node.node().id()) {
+                region.push(node);
+            }
+        }
+        Self::report(&mut region, errors);
+    }
+}
+
+impl UnreachableCodeCheck {
+    /// Get the IDs of the nodes that can be reached from an entry.
+    fn reachable(cfg: &Cfg) -> HashSet<Uuid> {
+        let mut roots = cfg.roots();
+        roots.extend(cfg.functions().values().map(|func| func.entry()));
+        roots.extend(
+            Self::trap_handlers(cfg)
+                .iter()
+                .filter_map(|label| cfg.label_node_map.get(&label.0))
+                .map(Rc::clone),
+        );
+
+        roots
+            .into_iter()
+            .flat_map(|root| cfg.iter_nexts(root))
+            .map(|node| node.node().id())
+            .collect()
+    }
+
+    /// Get the labels whose address is written to a trap vector.
+    fn trap_handlers(cfg: &Cfg) -> HashSet<LabelString> {
+        cfg.iter()
+            .filter_map(|node| match node.node() {
+                ParserNode::Csr(csr)
+                    if csr.inst.data == CSRType::Csrrw
+                        && TRAP_VECTORS.contains(&csr.csr.data.0) =>
+                {
+                    match node.reg_values_in().get(&csr.rs1.data) {
+                        Some(AvailableValue::Address(label)) => Some(label.clone()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Report a run of dead instructions, if there is one, and start a new
+    /// run.
+    fn report(region: &mut Vec<Rc<CfgNode>>, errors: &mut Vec<LintError>) {
+        let (Some(first), Some(last)) = (region.first(), region.last()) else {
+            return;
+        };
+        let name = first.labels().into_iter().map(|label| label.data).min();
+        errors.push(LintError::UnreachableCode(
+            first.node(),
+            last.node(),
+            region.len(),
+            name,
+        ));
+        region.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+    use crate::passes::{DiagnosticLocation, Manager, ManagerConfiguration};
+
+    fn run_pass(input: &str) -> Vec<LintError> {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        UnreachableCodeCheck.run_single_pass_along_cfg(&cfg)
+    }
+
+    #[test]
+    fn code_after_jump_is_one_region() {
+        let input = "\
+            main:                      \n\
+                j      end             \n\
+                li     a0, 1           \n\
+                addi   a0, a0, 1       \n\
+            end:                       \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnreachableCode(first, last, 2, None)
+                if first.token().text == "li a0 1" && last.token().text == "addi a0 a0 1"
+        ));
+        assert_eq!(lints[0].range().start.line, 2);
+        assert_eq!(lints[0].range().end.line, 3);
+    }
+
+    #[test]
+    fn region_is_named_by_its_label() {
+        let input = "\
+            main:                      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            unused:                    \n\
+                li     a0, 1           \n\
+                j      main            \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnreachableCode(_, _, 2, Some(name)) if name.0 == "unused"
+        ));
+    }
+
+    #[test]
+    fn dead_loop_is_unreachable() {
+        let input = "\
+            main:                      \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            spin:                      \n\
+                addi   t0, t0, 1       \n\
+                j      spin            \n";
+
+        // Each instruction of the loop has a previous node, but no path from
+        // the start of the program reaches the loop
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnreachableCode(_, _, 2, Some(name)) if name.0 == "spin"
+        ));
+    }
+
+    #[test]
+    fn regions_are_split_by_reachable_code() {
+        let input = "\
+            main:                      \n\
+                j      next            \n\
+                li     a0, 1           \n\
+            next:                      \n\
+                j      end             \n\
+                li     a0, 2           \n\
+            end:                       \n\
+                li     a7, 10          \n\
+                ecall                  \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 2);
+    }
+
+    #[test]
+    fn trap_handler_is_reachable() {
+        let input = "\
+            main:                      \n\
+                la     t0, handler     \n\
+                csrw   t0, utvec       \n\
+                li     a7, 10          \n\
+                ecall                  \n\
+            handler:                   \n\
+                addi   t1, t1, 1       \n\
+                uret                   \n";
+
+        let lints = run_pass(input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    const ALTERNATE_ENTRY: &str = "\
+            main:                      \n\
+                li     a0, 0           \n\
+                ret                    \n\
+            handler:                   \n\
+                li     a0, 1           \n\
+                ret                    \n";
+
+    #[test]
+    fn code_only_reachable_from_other_entry() {
+        let lints = run_pass(ALTERNATE_ENTRY);
+        assert_eq!(lints.len(), 1);
+        assert!(matches!(
+            &lints[0], LintError::UnreachableCode(_, _, 2, Some(name)) if name.0 == "handler"
+        ));
+    }
+
+    #[test]
+    fn globl_label_is_entry_point() {
+        let input = format!(".globl main, handler\n{ALTERNATE_ENTRY}");
+
+        let lints = run_pass(&input);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn uncalled_globl_function_is_reachable() {
+        let input = "\
+            .globl main, helper        \n\
+            main:                      \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n\
+            helper:                    \n\
+                addi   sp, sp, -16     \n\
+                sw     ra, 0(sp)       \n\
+                addi   a0, a0, 1       \n\
+                lw     ra, 0(sp)       \n\
+                addi   sp, sp, 16      \n\
+                ret                    \n";
+
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        assert!(cfg.globals().contains("main"));
+        assert!(cfg.globals().contains("helper"));
+
+        let lints = UnreachableCodeCheck.run_single_pass_along_cfg(&cfg);
+        assert_eq!(lints.len(), 0);
+    }
+
+    #[test]
+    fn configured_label_is_entry_point() {
+        let (nodes, error) = RVStringParser::parse_from_text(ALTERNATE_ENTRY);
+        assert_eq!(error.len(), 0);
+
+        let config = ManagerConfiguration {
+            entry_points: vec!["handler".to_string()],
+            ..Default::default()
+        };
+        let cfg = Manager::gen_full_cfg_with_config(nodes, &config).unwrap();
+        let lints = UnreachableCodeCheck.run_single_pass_along_cfg(&cfg);
+        assert_eq!(lints.len(), 0);
+    }
+}
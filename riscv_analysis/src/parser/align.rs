@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// How the argument of an `.align` directive is read.
+///
+/// GNU assemblers and RARS align to a power of two, so `.align 3` aligns to
+/// 8 bytes. Some other assemblers read the argument as a number of bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlignMode {
+    /// Align to 2^N bytes, as in GNU syntax.
+    #[default]
+    #[serde(alias = "power")]
+    Power,
+    /// Align to N bytes.
+    #[serde(alias = "bytes")]
+    Bytes,
+}
+
+impl AlignMode {
+    /// The largest alignment that is accepted, in bytes.
+    pub const MAX_BYTES: u32 = 4096;
+
+    /// Get the number of bytes that an `.align` argument aligns to.
+    ///
+    /// This is `None` if the argument is negative, is not a power of two
+    /// bytes, or is larger than [`AlignMode::MAX_BYTES`].
+    #[must_use]
+    pub fn bytes(self, arg: i32) -> Option<u32> {
+        let arg = u32::try_from(arg).ok()?;
+        let bytes = match self {
+            AlignMode::Power => 1_u32.checked_shl(arg)?,
+            AlignMode::Bytes => arg,
+        };
+        (bytes.is_power_of_two() && bytes <= Self::MAX_BYTES).then_some(bytes)
+    }
+}
+
+impl std::fmt::Display for AlignMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlignMode::Power => write!(f, "a power of two"),
+            AlignMode::Bytes => write!(f, "a number of bytes"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_of_two_alignment() {
+        assert_eq!(AlignMode::Power.bytes(0), Some(1));
+        assert_eq!(AlignMode::Power.bytes(2), Some(4));
+        assert_eq!(AlignMode::Power.bytes(12), Some(4096));
+        assert_eq!(AlignMode::Power.bytes(13), None);
+        assert_eq!(AlignMode::Power.bytes(40), None);
+        assert_eq!(AlignMode::Power.bytes(-1), None);
+    }
+
+    #[test]
+    fn byte_alignment() {
+        assert_eq!(AlignMode::Bytes.bytes(1), Some(1));
+        assert_eq!(AlignMode::Bytes.bytes(4), Some(4));
+        assert_eq!(AlignMode::Bytes.bytes(4096), Some(4096));
+        assert_eq!(AlignMode::Bytes.bytes(0), None);
+        assert_eq!(AlignMode::Bytes.bytes(6), None);
+        assert_eq!(AlignMode::Bytes.bytes(8192), None);
+    }
+}
@@ -0,0 +1,297 @@
+use itertools::Itertools;
+
+use super::{DirectiveType, Imm, Inst, LabelString, ParserNode, Relocation, Token, With};
+
+/// Get a label as it was written in the source.
+///
+/// Numeric local labels, like `1b`, are renamed when they are resolved, so
+/// the name in the source is kept to write them back out.
+fn written(label: &With<LabelString>) -> String {
+    match &label.token {
+        Token::Symbol(name) | Token::Label(name) => name.clone(),
+        _ => label.data.0.clone(),
+    }
+}
+
+/// Get an immediate, or the relocation that it was written as, like
+/// `%lo(symbol)`.
+fn immediate(imm: &With<Imm>, reloc: Option<&Relocation>) -> String {
+    match (reloc, &imm.token) {
+        (Some(_), Token::Symbol(text)) => text.clone(),
+        (Some(reloc), _) => reloc.to_string(),
+        (None, _) => imm.data.0.to_string(),
+    }
+}
+
+/// Quote a string so that an assembler reads it back as the same text.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl ParserNode {
+    /// Write the node as a line of assembly for an assembler.
+    ///
+    /// Pseudo-instructions are written as the instructions they expand to,
+    /// other than branches, `call`, `tail` and `j`. Labels, relocations like
+    /// `%hi(symbol)`, the labels of `.globl` and `.extern`, and the names and
+    /// flags of sections are written as they are in the source, so the output
+    /// can still be linked.
+    ///
+    /// Returns `None` for nodes that are not in the source, like the program
+    /// entry.
+    #[must_use]
+    pub fn to_assembly(&self) -> Option<String> {
+        let line = match self {
+            ParserNode::ProgramEntry(_) | ParserNode::FuncEntry(_) => return None,
+            ParserNode::Label(x) => format!("{}:", written(&x.name)),
+            ParserNode::Directive(x) => {
+                let args = match &x.dir {
+                    DirectiveType::TextSection => return Some(".text".to_owned()),
+                    DirectiveType::DataSection => return Some(".data".to_owned()),
+                    DirectiveType::Section { name, flags, .. } => {
+                        std::iter::once(&name.data).chain(flags).join(", ")
+                    }
+                    DirectiveType::Include(path) => quote(&path.data),
+                    DirectiveType::Align { value, .. } => value.data.0.to_string(),
+                    DirectiveType::Ascii { text, .. } => quote(&text.data),
+                    DirectiveType::Data(_, values) => values.iter().map(|v| &v.data).join(", "),
+                    DirectiveType::Space(size) => size.data.0.to_string(),
+                    DirectiveType::Global(labels) | DirectiveType::Extern(labels) => {
+                        labels.iter().map(written).join(", ")
+                    }
+                    DirectiveType::Constant { name, value } => {
+                        format!("{}, {}", name.data, value.data.0)
+                    }
+                };
+                format!("{} {args}", x.dir_token.data)
+            }
+            _ => format!("    {}", self.instruction_assembly()?),
+        };
+        Some(line)
+    }
+
+    /// Write an instruction as assembly, see [`ParserNode::to_assembly`].
+    fn instruction_assembly(&self) -> Option<String> {
+        let line = match self {
+            ParserNode::Arith(x) => format!(
+                "{} {}, {}, {}",
+                Inst::from(&x.inst.data),
+                x.rd.data,
+                x.rs1.data,
+                x.rs2.data
+            ),
+            ParserNode::IArith(x) => format!(
+                "{} {}, {}, {}",
+                Inst::from(&x.inst.data),
+                x.rd.data,
+                x.rs1.data,
+                immediate(&x.imm, x.reloc.as_ref())
+            ),
+            ParserNode::UpperArith(x) => format!(
+                "{} {}, {}",
+                Inst::from(&x.inst.data),
+                x.rd.data,
+                immediate(&x.imm, x.reloc.as_ref())
+            ),
+            ParserNode::JumpLink(x) => match &x.inst.token {
+                Token::Symbol(name)
+                    if ["call", "tail", "j"].contains(&name.to_lowercase().as_str()) =>
+                {
+                    format!("{} {}", name.to_lowercase(), written(&x.name))
+                }
+                _ => format!("jal {}, {}", x.rd.data, written(&x.name)),
+            },
+            ParserNode::JumpLinkR(x) => {
+                format!("jalr {}, {}({})", x.rd.data, x.imm.data.0, x.rs1.data)
+            }
+            ParserNode::Basic(x) => Inst::from(&x.inst.data).to_string(),
+            ParserNode::Branch(x) => {
+                let regs = x.source_operands().iter().join(", ");
+                format!("{} {regs}, {}", x.mnemonic(), written(&x.name))
+            }
+            ParserNode::Store(x) => format!(
+                "{} {}, {}({})",
+                Inst::from(&x.inst.data),
+                x.rs2.data,
+                immediate(&x.imm, x.reloc.as_ref()),
+                x.rs1.data
+            ),
+            ParserNode::Load(x) => format!(
+                "{} {}, {}({})",
+                Inst::from(&x.inst.data),
+                x.rd.data,
+                immediate(&x.imm, x.reloc.as_ref()),
+                x.rs1.data
+            ),
+            ParserNode::LoadAddr(x) => format!("la {}, {}", x.rd.data, written(&x.name)),
+            ParserNode::Csr(x) => format!(
+                "{} {}, {}, {}",
+                Inst::from(&x.inst.data),
+                x.rd.data,
+                x.csr.data,
+                x.rs1.data
+            ),
+            ParserNode::CsrI(x) => format!(
+                "{} {}, {}, {}",
+                Inst::from(&x.inst.data),
+                x.rd.data,
+                x.csr.data,
+                x.imm.data.0
+            ),
+            ParserNode::FloatArith(x) => {
+                let regs = [Some(&x.rd), Some(&x.rs1), x.rs2.as_ref(), x.rs3.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .map(|reg| reg.data)
+                    .join(", ");
+                format!("{} {regs}", Inst::from(&x.inst.data))
+            }
+            ParserNode::ProgramEntry(_)
+            | ParserNode::FuncEntry(_)
+            | ParserNode::Label(_)
+            | ParserNode::Directive(_) => return None,
+        };
+        Some(line)
+    }
+}
+
+/// Write parsed nodes back out as assembly for an assembler, one node per
+/// line.
+///
+/// See [`ParserNode::to_assembly`] for how each node is written.
+#[must_use]
+pub fn emit_assembly(nodes: &[ParserNode]) -> String {
+    nodes
+        .iter()
+        .filter_map(ParserNode::to_assembly)
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RVStringParser;
+
+    /// Parse the text and write it back out, checking that it parses.
+    fn emit(input: &str) -> String {
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        emit_assembly(&nodes)
+    }
+
+    #[test]
+    fn relocations_round_trip() {
+        // Output of a compiler, with the `.L` labels renamed, as labels
+        // cannot start with a `.`
+        let input = "\
+            \t.text\n\
+            \t.globl\tmain\n\
+            \t.extern\tputs\n\
+            main:\n\
+            \taddi\tsp,sp,-16\n\
+            \tsw\tra,12(sp)\n\
+            \tlui\ta0,%hi(msg)\n\
+            \taddi\ta0,a0,%lo(msg)\n\
+            \tcall\tputs\n\
+            pcrel_hi0:\n\
+            \tauipc\ta1,%pcrel_hi(counter)\n\
+            \tlw\ta2,%pcrel_lo(pcrel_hi0)(a1)\n\
+            \tlui\ta3,%hi(counter)\n\
+            \tsw\ta2,%lo(counter)(a3)\n\
+            \tlw\tra,12(sp)\n\
+            \taddi\tsp,sp,16\n\
+            \tjr\tra\n\
+            \t.data\n\
+            counter:\n\
+            \t.word\t3\n\
+            msg:\n\
+            \t.string\t\"Hello\\tworld\\n\"\n";
+
+        let output = emit(input);
+        for line in [
+            ".globl main",
+            ".extern puts",
+            "    lui a0, %hi(msg)",
+            "    addi a0, a0, %lo(msg)",
+            "    call puts",
+            "    auipc a1, %pcrel_hi(counter)",
+            "    lw a2, %pcrel_lo(pcrel_hi0)(a1)",
+            "    sw a2, %lo(counter)(a3)",
+            ".string \"Hello\\tworld\\n\"",
+        ] {
+            assert!(output.lines().any(|l| l == line), "missing `{line}`");
+        }
+
+        // Writing the output back out does not change it
+        assert_eq!(emit(&output), output);
+    }
+
+    #[test]
+    fn sections_are_written_as_in_source() {
+        let input = "\
+            .section .text.startup,\"ax\",@progbits\n\
+            main:\n\
+                li a7, 10\n\
+                ecall\n\
+            .section .rodata\n\
+            msg:\n\
+                .string \"hi\"\n\
+            .section .bss\n\
+            buf:\n\
+                .space 4\n\
+            .data\n";
+
+        let output = emit(input);
+        assert_eq!(
+            output
+                .lines()
+                .filter(|l| !l.starts_with(' '))
+                .collect::<Vec<_>>(),
+            vec![
+                ".section .text.startup, \"ax\", @progbits",
+                "main:",
+                ".section .rodata",
+                "msg:",
+                ".string \"hi\"",
+                ".section .bss",
+                "buf:",
+                ".space 4",
+                ".data",
+            ]
+        );
+        assert_eq!(emit(&output), output);
+    }
+
+    #[test]
+    fn local_labels_are_written_as_in_source() {
+        let output =
+            emit("1:  auipc a0, %pcrel_hi(msg)\n    addi a0, a0, %pcrel_lo(1b)\n    j 1b\n");
+        assert_eq!(
+            output,
+            "1:\n    auipc a0, %pcrel_hi(msg)\n    addi a0, a0, %pcrel_lo(1b)\n    j 1b\n"
+        );
+    }
+
+    #[test]
+    fn pseudo_instructions_are_expanded() {
+        let output = emit("li a0, 5\nmv a1, a0\nbeqz a1, end\nret\nend:\n");
+        assert_eq!(
+            output,
+            "    addi a0, zero, 5\n    add a1, a0, zero\n    beqz a1, end\n    jalr zero, 0(ra)\nend:\n"
+        );
+    }
+}
@@ -1,6 +1,97 @@
+use std::str::FromStr;
+
+use super::{Info, Position, Range, Register, Token, With};
+
+/// The kind of an annotation, like the `@arg` of `# @arg a0: count`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnnotationKind {
+    /// A register that the function takes an argument in, written `@arg`.
+    Arg,
+    /// A register that the function returns a value in, written `@returns`.
+    Returns,
+}
+
+/// A comment that documents a register of a function, like
+/// `# @arg a0: count` or `# @returns a0: sum`.
+///
+/// Annotations are written in the comments right before the label of a
+/// function. The description after the `:` is optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    /// The register, with the position of its name in the comment.
+    pub reg: With<Register>,
+    pub description: String,
+}
+
+impl Annotation {
+    /// Parse an annotation from a comment token.
+    ///
+    /// Returns `None` if the comment is not an annotation, or if it is not
+    /// well-formed, like an unknown tag or register.
+    #[must_use]
+    pub fn parse(comment: &Info) -> Option<Self> {
+        let Token::Comment(text) = &comment.token else {
+            return None;
+        };
+        let rest = text.trim_start().strip_prefix('@')?;
+        let (tag, rest) = rest.split_once(char::is_whitespace)?;
+        let kind = match tag {
+            "arg" | "param" => AnnotationKind::Arg,
+            "returns" | "return" => AnnotationKind::Returns,
+            _ => return None,
+        };
+        let rest = rest.trim_start();
+        let name_len = rest
+            .find(|c: char| c == ':' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let (name, description) = rest.split_at(name_len);
+        let reg = Register::from_str(&name.to_lowercase()).ok()?;
+        let description = description.trim_start();
+        let description = description
+            .strip_prefix(':')
+            .unwrap_or(description)
+            .trim()
+            .to_owned();
+
+        // The register starts after the `#` and everything before it
+        let offset = text.len() - rest.len() + 1;
+        let start = Position {
+            line: comment.pos.start.line,
+            column: comment.pos.start.column + offset,
+            raw_index: comment.pos.start.raw_index + offset,
+        };
+        let end = Position {
+            line: start.line,
+            column: start.column + name.len(),
+            raw_index: start.raw_index + name.len(),
+        };
+        let info = Info {
+            token: Token::Symbol(name.to_owned()),
+            pos: Range { start, end },
+            file: comment.file,
+        };
+        Some(Annotation {
+            kind,
+            reg: With::new(reg, info),
+            description,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::parser::{ParserNode, RVStringParser};
+    use uuid::Uuid;
+
+    fn comment(text: &str) -> Info {
+        Info {
+            token: Token::Comment(text.to_owned()),
+            pos: Range::default(),
+            file: Uuid::nil(),
+        }
+    }
 
     #[test]
     fn can_parse_comments_without_errors() {
@@ -12,4 +103,33 @@ mod tests {
         matches!(&nodes[1], ParserNode::Arith(_));
         assert_eq!(nodes[1].to_string(), "add ra <- a0, a1");
     }
+
+    #[test]
+    fn parses_annotations() {
+        let arg = Annotation::parse(&comment(" @arg a0: the count")).unwrap();
+        assert_eq!(arg.kind, AnnotationKind::Arg);
+        assert_eq!(arg.reg.data, Register::X10);
+        assert_eq!(arg.description, "the count");
+        // The register is after `# @arg `
+        assert_eq!(arg.reg.pos.start.column, 7);
+        assert_eq!(arg.reg.pos.end.column, 9);
+
+        let ret = Annotation::parse(&comment("@returns x10")).unwrap();
+        assert_eq!(ret.kind, AnnotationKind::Returns);
+        assert_eq!(ret.reg.data, Register::X10);
+        assert_eq!(ret.description, "");
+
+        let float = Annotation::parse(&comment(" @arg fa1 : scale")).unwrap();
+        assert_eq!(float.reg.data, Register::F11);
+        assert_eq!(float.description, "scale");
+    }
+
+    #[test]
+    fn malformed_annotations_are_ignored() {
+        assert_eq!(Annotation::parse(&comment(" a normal comment")), None);
+        assert_eq!(Annotation::parse(&comment(" @arg")), None);
+        assert_eq!(Annotation::parse(&comment(" @arg count: a0")), None);
+        assert_eq!(Annotation::parse(&comment(" @unknown a0: n")), None);
+        assert_eq!(Annotation::parse(&comment(" email me @ home")), None);
+    }
 }
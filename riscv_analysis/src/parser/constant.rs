@@ -0,0 +1,86 @@
+use std::{collections::HashMap, str::FromStr};
+
+use super::{DirectiveToken, Imm, Info, Lexer, ParseError, Token};
+
+/// Constants defined with `.equ`, `.set` or `.eqv`.
+///
+/// A constant can be used anywhere an immediate is expected, like
+/// `addi sp, sp, -SIZE`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Constants(HashMap<String, Imm>);
+
+impl Constants {
+    /// Get the value of a constant written as an operand.
+    ///
+    /// The name can be negated, like `-SIZE`.
+    pub(crate) fn get(&self, symbol: &str) -> Option<Imm> {
+        match symbol.strip_prefix('-') {
+            Some(name) => self.0.get(name).map(|imm| Imm(imm.0.wrapping_neg())),
+            None => self.0.get(symbol).cloned(),
+        }
+    }
+
+    /// Get the value of a token that is written as a number or a constant.
+    fn value_of(&self, info: &Info) -> Option<Imm> {
+        match &info.token {
+            Token::Symbol(s) => Imm::from_str(s).ok().or_else(|| self.get(s)),
+            _ => Imm::try_from(info.clone()).ok(),
+        }
+    }
+
+    /// Collect the constants defined in a file.
+    ///
+    /// This is done before the file is parsed, so that constants can be used
+    /// before they are defined. The value of a constant can refer to another
+    /// constant, in any order. Values that cannot be resolved are left out,
+    /// and are reported when the definition is parsed.
+    ///
+    /// Returns an error for each constant that is defined more than once.
+    pub(crate) fn collect(&mut self, lexer: Lexer) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        let mut pending = Vec::new();
+        let mut tokens = lexer.flatten();
+        while let Some(token) = tokens.next() {
+            let Token::Directive(dir) = &token.token else {
+                continue;
+            };
+            if !matches!(
+                DirectiveToken::from_str(dir),
+                Ok(DirectiveToken::Equ | DirectiveToken::Set | DirectiveToken::Eqv)
+            ) {
+                continue;
+            }
+            let (Some(name), Some(value)) = (tokens.next(), tokens.next()) else {
+                break;
+            };
+            let Token::Symbol(s) = &name.token else {
+                continue;
+            };
+            if self.0.contains_key(s)
+                || pending
+                    .iter()
+                    .any(|(x, _): &(Info, _)| x.token == name.token)
+            {
+                errors.push(ParseError::RedefinedConstant(name));
+                continue;
+            }
+            pending.push((name, value));
+        }
+
+        // Resolve constants until no more values are known
+        loop {
+            let before = pending.len();
+            pending.retain(|(name, value)| match (&name.token, self.value_of(value)) {
+                (Token::Symbol(s), Some(imm)) => {
+                    self.0.insert(s.clone(), imm);
+                    false
+                }
+                _ => true,
+            });
+            if pending.len() == before {
+                break;
+            }
+        }
+        errors
+    }
+}
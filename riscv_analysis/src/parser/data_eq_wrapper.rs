@@ -22,6 +22,9 @@ impl PartialEq for ParserNodeDataWrapper {
             (ParserNode::IArith(a), ParserNode::IArith(b)) => {
                 a.inst == b.inst && a.rd == b.rd && a.rs1 == b.rs1 && a.imm == b.imm
             }
+            (ParserNode::UpperArith(a), ParserNode::UpperArith(b)) => {
+                a.inst == b.inst && a.rd == b.rd && a.imm == b.imm
+            }
             (ParserNode::Label(a), ParserNode::Label(b)) => a.name == b.name,
             (ParserNode::JumpLink(a), ParserNode::JumpLink(b)) => {
                 a.inst == b.inst && a.name == b.name
@@ -43,6 +46,13 @@ impl PartialEq for ParserNodeDataWrapper {
             (ParserNode::Csr(a), ParserNode::Csr(b)) => {
                 a.inst == b.inst && a.rd == b.rd && a.csr == b.csr && a.rs1 == b.rs1
             }
+            (ParserNode::FloatArith(a), ParserNode::FloatArith(b)) => {
+                a.inst == b.inst
+                    && a.rd == b.rd
+                    && a.rs1 == b.rs1
+                    && a.rs2 == b.rs2
+                    && a.rs3 == b.rs3
+            }
             (ParserNode::LoadAddr(a), ParserNode::LoadAddr(b)) => {
                 a.inst == b.inst && a.rd == b.rd && a.name == b.name
             }
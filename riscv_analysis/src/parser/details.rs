@@ -1,12 +1,15 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::cfg::Segment;
+
 use super::{
-    ArithType, BasicType, BranchType, CSRIType, CSRImm, CSRType, DirectiveToken, IArithType,
-    IgnoreType, Imm, JumpLinkRType, JumpLinkType, LabelString, LoadType, PseudoType, RawToken,
-    Register, StoreType, With,
+    Annotation, ArithType, BasicType, BranchType, CSRIType, CSRImm, CSRType, DirectiveToken, FloatArithType,
+    IArithType, FloatImm, IgnoreType, Imm, Inst, JumpLinkRType, JumpLinkType, LabelString, LoadType, PseudoType, RawToken,
+    Register, Relocation, StoreType, Token, UpperArithType, With,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,18 +24,69 @@ pub struct Arith {
     pub token: RawToken,
 }
 
+/// An instruction of the F or D extensions that is not a load or store.
+///
+/// The second and third source registers are only set for instructions
+/// that read them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FloatArith {
+    pub inst: With<FloatArithType>,
+    pub rd: With<Register>,
+    pub rs1: With<Register>,
+    pub rs2: Option<With<Register>>,
+    pub rs3: Option<With<Register>>,
+    #[serde(skip)]
+    pub key: Uuid,
+    #[serde(skip)]
+    pub token: RawToken,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IArith {
     pub inst: With<IArithType>,
     pub rd: With<Register>,
     pub rs1: With<Register>,
     pub imm: With<Imm>,
+    /// The relocation that the immediate was written as, like `%lo(symbol)`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reloc: Option<Relocation>,
     #[serde(skip)]
     pub key: Uuid,
     #[serde(skip)]
     pub token: RawToken,
 }
 
+/// An instruction that loads an immediate into the upper 20 bits of a
+/// register, like `lui` and `auipc`.
+///
+/// The immediate is kept as it was written. The value it stands for is
+/// found with [`UpperArith::value`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpperArith {
+    pub inst: With<UpperArithType>,
+    pub rd: With<Register>,
+    pub imm: With<Imm>,
+    /// The relocation that the immediate was written as, like `%lo(symbol)`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reloc: Option<Relocation>,
+    #[serde(skip)]
+    pub key: Uuid,
+    #[serde(skip)]
+    pub token: RawToken,
+}
+
+impl UpperArith {
+    /// Get the immediate shifted into the upper bits of a register.
+    ///
+    /// `lui` writes this value to the register, and `auipc` adds it to the
+    /// address of the instruction. Only the lower 20 bits of the immediate
+    /// are used, so this cannot overflow.
+    #[must_use]
+    pub fn value(&self) -> i32 {
+        self.imm.data.0.wrapping_shl(12)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Label {
     pub name: With<LabelString>,
@@ -40,6 +94,9 @@ pub struct Label {
     pub key: Uuid,
     #[serde(skip)]
     pub token: RawToken,
+    /// The annotations in the comments right before the label.
+    #[serde(skip)]
+    pub annotations: Vec<Annotation>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JumpLink {
@@ -85,12 +142,47 @@ pub struct Branch {
     pub token: RawToken,
 }
 
+impl Branch {
+    /// Get the mnemonic that the branch was written with.
+    ///
+    /// A pseudo-instruction like `bgt` is stored as the branch it expands
+    /// to, like `blt`, but keeps the mnemonic that the user wrote.
+    #[must_use]
+    pub fn mnemonic(&self) -> String {
+        match &self.inst.token {
+            Token::Symbol(name) => name.to_lowercase(),
+            _ => Inst::from(&self.inst.data).to_string(),
+        }
+    }
+
+    /// Get the registers that the branch compares, in the order that they
+    /// were written.
+    ///
+    /// Some pseudo-instructions swap their registers when they expand, like
+    /// `bgt a0, a1` to `blt a1, a0`, and some compare with `zero` without
+    /// naming it, like `beqz a0`.
+    #[must_use]
+    pub fn source_operands(&self) -> Vec<Register> {
+        match Inst::from_str(&self.mnemonic()) {
+            Ok(Inst::Bgt | Inst::Ble | Inst::Bgtu | Inst::Bleu) => {
+                vec![self.rs2.data, self.rs1.data]
+            }
+            Ok(Inst::Beqz | Inst::Bnez | Inst::Bltz | Inst::Bgez) => vec![self.rs1.data],
+            Ok(Inst::Bgtz | Inst::Blez) => vec![self.rs2.data],
+            _ => vec![self.rs1.data, self.rs2.data],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Load {
     pub inst: With<LoadType>,
     pub rd: With<Register>,
     pub rs1: With<Register>,
     pub imm: With<Imm>,
+    /// The relocation that the immediate was written as, like `%lo(symbol)`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reloc: Option<Relocation>,
     #[serde(skip)]
     pub key: Uuid,
     #[serde(skip)]
@@ -103,6 +195,9 @@ pub struct Store {
     pub rs1: With<Register>,
     pub rs2: With<Register>,
     pub imm: With<Imm>,
+    /// The relocation that the immediate was written as, like `%lo(symbol)`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reloc: Option<Relocation>,
     #[serde(skip)]
     pub key: Uuid,
     #[serde(skip)]
@@ -132,35 +227,184 @@ impl Display for DataType {
     }
 }
 
+/// A value of a data directive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataValue {
+    Int(Imm),
+    Float(FloatImm),
+    /// The address of a label, like the entries of a jump table.
+    Label(LabelString),
+}
+
+impl Display for DataValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataValue::Int(i) => write!(f, "{}", i.0),
+            DataValue::Float(x) => write!(f, "{}", x.0),
+            DataValue::Label(label) => write!(f, "{label}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DirectiveType {
     Include(With<String>),
-    Align(With<Imm>),
+    /// Align the next data or code.
+    ///
+    /// The argument is kept as written, and `bytes` is the alignment in
+    /// bytes that it resolves to. This is `None` if the argument is not a
+    /// valid alignment.
+    Align {
+        value: With<Imm>,
+        bytes: Option<u32>,
+    },
     Ascii { text: With<String>, null_term: bool },
     DataSection,
     TextSection,
-    Data(DataType, Vec<With<Imm>>),
+    /// Switch to a section named with `.section`, like `.section .rodata`.
+    ///
+    /// The name and flags, like `"ax"`, are kept as written, and `segment`
+    /// is the segment that the section belongs to.
+    Section {
+        name: With<String>,
+        flags: Vec<String>,
+        segment: Segment,
+    },
+    Data(DataType, Vec<With<DataValue>>),
     Space(With<Imm>),
+    Global(Vec<With<LabelString>>),
+    /// Declare labels that are defined outside of the program, with
+    /// `.extern`.
+    Extern(Vec<With<LabelString>>),
+    /// Define a constant with `.equ`, `.set` or `.eqv`.
+    Constant { name: With<String>, value: With<Imm> },
 }
 
 impl Display for DirectiveType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DirectiveType::Include(s) => write!(f, "include {s}"),
-            DirectiveType::Align(i) => write!(f, "align {}", i.data.0),
+            DirectiveType::Align { value, .. } => write!(f, "align {}", value.data.0),
             DirectiveType::Ascii { text, .. } => {
                 write!(f, "ascii \"{}\"", text.data)
             }
             DirectiveType::DataSection => write!(f, ".data"),
             DirectiveType::TextSection => write!(f, ".text"),
+            DirectiveType::Section { name, .. } => write!(f, "section {}", name.data),
             DirectiveType::Data(dt, data) => {
                 write!(f, "{dt} ")?;
                 for d in data {
-                    write!(f, "{}, ", d.data.0)?;
+                    write!(f, "{}, ", d.data)?;
                 }
                 Ok(())
             }
             DirectiveType::Space(i) => write!(f, "space {}", i.data.0),
+            DirectiveType::Constant { name, value } => {
+                write!(f, "constant {} = {}", name.data, value.data.0)
+            }
+            DirectiveType::Global(labels) => {
+                write!(f, "globl ")?;
+                for label in labels {
+                    write!(f, "{}, ", label.data.0)?;
+                }
+                Ok(())
+            }
+            DirectiveType::Extern(labels) => {
+                write!(f, "extern ")?;
+                for label in labels {
+                    write!(f, "{}, ", label.data.0)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl DataType {
+    /// Get the size of one value of this type in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        match self {
+            DataType::Byte => 1,
+            DataType::Half => 2,
+            DataType::Word | DataType::Float => 4,
+            DataType::Double | DataType::Dword => 8,
+        }
+    }
+}
+
+impl DirectiveType {
+    /// Get the segment that this directive switches to, if it is a
+    /// section directive.
+    #[must_use]
+    pub fn segment(&self) -> Option<Segment> {
+        match self {
+            DirectiveType::TextSection => Some(Segment::Text),
+            DirectiveType::DataSection => Some(Segment::Data),
+            DirectiveType::Section { segment, .. } => Some(*segment),
+            _ => None,
+        }
+    }
+
+    /// Get the bytes that this directive places in memory.
+    ///
+    /// Values are encoded in little-endian order, and floating-point values
+    /// are encoded in the IEEE-754 format. Directives that do not place data
+    /// in memory, or whose size depends on where they are placed, like
+    /// `.align`, return `None`.
+    #[must_use]
+    pub fn data_image(&self) -> Option<Vec<u8>> {
+        match self {
+            DirectiveType::Data(data_type, values) => {
+                let mut bytes = Vec::with_capacity(data_type.size() * values.len());
+                for value in values {
+                    let encoded = match (data_type, &value.data) {
+                        (DataType::Float, DataValue::Float(x)) => {
+                            #[allow(clippy::cast_possible_truncation)]
+                            let x = x.0 as f32;
+                            x.to_le_bytes().to_vec()
+                        }
+                        (DataType::Float, DataValue::Int(i)) => {
+                            #[allow(clippy::cast_precision_loss)]
+                            let x = i.0 as f32;
+                            x.to_le_bytes().to_vec()
+                        }
+                        (DataType::Double, DataValue::Float(x)) => x.0.to_le_bytes().to_vec(),
+                        (DataType::Double, DataValue::Int(i)) => {
+                            f64::from(i.0).to_le_bytes().to_vec()
+                        }
+                        (_, DataValue::Int(i)) => {
+                            let mut b = i64::from(i.0).to_le_bytes().to_vec();
+                            b.truncate(data_type.size());
+                            b
+                        }
+                        // Addresses are not known until the program is
+                        // linked
+                        (DataType::Word | DataType::Dword, DataValue::Label(_)) => {
+                            vec![0; data_type.size()]
+                        }
+                        (_, DataValue::Float(_) | DataValue::Label(_)) => return None,
+                    };
+                    bytes.extend(encoded);
+                }
+                Some(bytes)
+            }
+            DirectiveType::Ascii { text, null_term } => {
+                let mut bytes = text.data.as_bytes().to_vec();
+                if *null_term {
+                    bytes.push(0);
+                }
+                Some(bytes)
+            }
+            DirectiveType::Space(size) => Some(vec![0; usize::try_from(size.data.0).ok()?]),
+            DirectiveType::Include(_)
+            | DirectiveType::Align { .. }
+            | DirectiveType::DataSection
+            | DirectiveType::TextSection
+            | DirectiveType::Section { .. }
+            | DirectiveType::Global(_)
+            | DirectiveType::Extern(_)
+            | DirectiveType::Constant { .. } => None,
         }
     }
 }
@@ -10,11 +10,14 @@ pub enum DirectiveToken {
     Align,
     Ascii,
     Asciz,
+    Balign,
     Byte,
     Data,
     Double,
     Dword,
     EndMacro,
+    Endr,
+    Equ,
     Eqv,
     Extern,
     Float,
@@ -23,7 +26,9 @@ pub enum DirectiveToken {
     Half,
     Include,
     Macro,
+    Rept,
     Section,
+    Set,
     Space,
     String,
     Text,
@@ -36,11 +41,14 @@ impl Display for DirectiveToken {
             DirectiveToken::Align => write!(f, ".align"),
             DirectiveToken::Ascii => write!(f, ".ascii"),
             DirectiveToken::Asciz => write!(f, ".asciz"),
+            DirectiveToken::Balign => write!(f, ".balign"),
             DirectiveToken::Byte => write!(f, ".byte"),
             DirectiveToken::Data => write!(f, ".data"),
             DirectiveToken::Double => write!(f, ".double"),
             DirectiveToken::Dword => write!(f, ".dword"),
             DirectiveToken::EndMacro => write!(f, ".endmacro"),
+            DirectiveToken::Endr => write!(f, ".endr"),
+            DirectiveToken::Equ => write!(f, ".equ"),
             DirectiveToken::Eqv => write!(f, ".eqv"),
             DirectiveToken::Extern => write!(f, ".extern"),
             DirectiveToken::Float => write!(f, ".float"),
@@ -49,7 +57,9 @@ impl Display for DirectiveToken {
             DirectiveToken::Half => write!(f, ".half"),
             DirectiveToken::Include => write!(f, ".include"),
             DirectiveToken::Macro => write!(f, ".macro"),
+            DirectiveToken::Rept => write!(f, ".rept"),
             DirectiveToken::Section => write!(f, ".section"),
+            DirectiveToken::Set => write!(f, ".set"),
             DirectiveToken::Space => write!(f, ".space"),
             DirectiveToken::String => write!(f, ".string"),
             DirectiveToken::Text => write!(f, ".text"),
@@ -67,11 +77,14 @@ impl FromStr for DirectiveToken {
             ".align" => Ok(DirectiveToken::Align),
             ".ascii" => Ok(DirectiveToken::Ascii),
             ".asciz" => Ok(DirectiveToken::Asciz),
+            ".balign" => Ok(DirectiveToken::Balign),
             ".byte" => Ok(DirectiveToken::Byte),
             ".data" => Ok(DirectiveToken::Data),
             ".double" => Ok(DirectiveToken::Double),
             ".dword" => Ok(DirectiveToken::Dword),
             ".endmacro" => Ok(DirectiveToken::EndMacro),
+            ".endr" => Ok(DirectiveToken::Endr),
+            ".equ" => Ok(DirectiveToken::Equ),
             ".eqv" => Ok(DirectiveToken::Eqv),
             ".extern" => Ok(DirectiveToken::Extern),
             ".float" => Ok(DirectiveToken::Float),
@@ -80,7 +93,9 @@ impl FromStr for DirectiveToken {
             ".half" => Ok(DirectiveToken::Half),
             ".include" => Ok(DirectiveToken::Include),
             ".macro" => Ok(DirectiveToken::Macro),
+            ".rept" => Ok(DirectiveToken::Rept),
             ".section" => Ok(DirectiveToken::Section),
+            ".set" => Ok(DirectiveToken::Set),
             ".space" => Ok(DirectiveToken::Space),
             ".string" => Ok(DirectiveToken::String),
             ".text" => Ok(DirectiveToken::Text),
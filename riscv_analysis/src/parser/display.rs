@@ -1,40 +1,75 @@
 use std::fmt::Display;
 
+use itertools::Itertools;
 use uuid::Uuid;
 
-use crate::{parser::Inst, passes::DiagnosticLocation};
+use crate::{
+    parser::{Inst, RegisterNames},
+    passes::DiagnosticLocation,
+};
 
 use super::ParserNode;
 
+impl ParserNode {
+    /// Returns an adapter that displays the node with registers shown with
+    /// the given names.
+    ///
+    /// The [`Display`] of a node always uses ABI names.
+    #[must_use]
+    pub fn display(&self, names: RegisterNames) -> NodeDisplay<'_> {
+        NodeDisplay { node: self, names }
+    }
+}
+
+/// A node displayed with a chosen set of register names, see
+/// [`ParserNode::display`].
+pub struct NodeDisplay<'a> {
+    node: &'a ParserNode,
+    names: RegisterNames,
+}
+
 impl Display for ParserNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let res = match &self {
+        self.display(RegisterNames::Abi).fmt(f)
+    }
+}
+
+impl Display for NodeDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = self.names;
+        let res = match self.node {
             ParserNode::ProgramEntry(_) => "--- [PROGRAM ENTRY] ---".to_string(),
             ParserNode::FuncEntry(_) => "--- FUNCTION ENTRY ---".to_string(),
             ParserNode::Arith(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rd = x.rd.data.to_string();
-                let rs1 = x.rs1.data.to_string();
-                let rs2 = x.rs2.data.to_string();
+                let rd = x.rd.data.display(names).to_string();
+                let rs1 = x.rs1.data.display(names).to_string();
+                let rs2 = x.rs2.data.display(names).to_string();
                 format!("{inst} {rd} <- {rs1}, {rs2}")
             }
             ParserNode::IArith(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rd = x.rd.data.to_string();
-                let rs1 = x.rs1.data.to_string();
+                let rd = x.rd.data.display(names).to_string();
+                let rs1 = x.rs1.data.display(names).to_string();
                 let imm = x.imm.data.0.to_string();
                 format!("{inst} {rd} <- {rs1}, {imm}")
             }
+            ParserNode::UpperArith(x) => {
+                let inst: Inst = Inst::from(&x.inst.data);
+                let rd = x.rd.data.display(names).to_string();
+                let imm = x.imm.data.0.to_string();
+                format!("{inst} {rd} <- {imm}")
+            }
             ParserNode::Label(x) => format!("---[{}]---", x.name.data.0),
             ParserNode::JumpLink(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
                 let name = x.name.data.0.clone();
-                let rd = x.rd.data.to_string();
+                let rd = x.rd.data.display(names).to_string();
                 format!("{inst} [{name}] | {rd} <- PC")
             }
             ParserNode::JumpLinkR(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rs1 = x.rs1.data.to_string();
+                let rs1 = x.rs1.data.display(names).to_string();
                 format!("{inst} [{rs1}]")
             }
             ParserNode::Basic(x) => {
@@ -46,43 +81,54 @@ impl Display for ParserNode {
                 format!("-<{dir}>-")
             }
             ParserNode::Branch(x) => {
-                let inst: Inst = Inst::from(&x.inst.data);
-                let rs1 = x.rs1.data.to_string();
-                let rs2 = x.rs2.data.to_string();
+                let inst = x.mnemonic();
+                let regs = x.source_operands();
+                let regs = regs.iter().map(|reg| reg.display(names)).join("--");
                 let name = x.name.data.0.clone();
-                format!("{inst} {rs1}--{rs2}, [{name}]")
+                format!("{inst} {regs}, [{name}]")
             }
             ParserNode::Store(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rs1 = x.rs1.data.to_string();
-                let rs2 = x.rs2.data.to_string();
+                let rs1 = x.rs1.data.display(names).to_string();
+                let rs2 = x.rs2.data.display(names).to_string();
                 let imm = x.imm.data.0.to_string();
                 format!("{inst} {rs2} -> {imm}({rs1})")
             }
             ParserNode::Load(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rd = x.rd.data.to_string();
-                let rs1 = x.rs1.data.to_string();
+                let rd = x.rd.data.display(names).to_string();
+                let rs1 = x.rs1.data.display(names).to_string();
                 let imm = x.imm.data.0.to_string();
                 format!("{inst} {rd} <- {imm}({rs1})")
             }
             ParserNode::LoadAddr(x) => {
                 let inst = "la";
-                let rd = x.rd.data.to_string();
+                let rd = x.rd.data.display(names).to_string();
                 let name = x.name.data.0.clone();
                 format!("{inst} {rd} <- [{name}]")
             }
             ParserNode::Csr(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rd = x.rd.data.to_string();
-                let csr = x.csr.data.0.to_string();
-                let rs1 = x.rs1.data.to_string();
+                let rd = x.rd.data.display(names).to_string();
+                let csr = x.csr.data.to_string();
+                let rs1 = x.rs1.data.display(names).to_string();
                 format!("{inst} {rd} <- {csr} <- {rs1}")
             }
+            ParserNode::FloatArith(x) => {
+                let inst: Inst = Inst::from(&x.inst.data);
+                let rd = x.rd.data.display(names).to_string();
+                let sources = [Some(&x.rs1), x.rs2.as_ref(), x.rs3.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .map(|reg| reg.data.display(names).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{inst} {rd} <- {sources}")
+            }
             ParserNode::CsrI(x) => {
                 let inst: Inst = Inst::from(&x.inst.data);
-                let rd = x.rd.data.to_string();
-                let csr = x.csr.data.0.to_string();
+                let rd = x.rd.data.display(names).to_string();
+                let csr = x.csr.data.to_string();
                 let imm = x.imm.data.0.to_string();
                 format!("{inst} {rd} <- {csr} <- {imm}")
             }
@@ -3,11 +3,14 @@ use std::fmt::Display;
 use uuid::Uuid;
 
 use crate::{
-    passes::{DiagnosticLocation, DiagnosticMessage, SeverityLevel},
+    passes::{DiagnosticLocation, DiagnosticMessage, RuleInfo, SeverityLevel},
     reader::FileReaderError,
 };
 
-use super::{Info, ParserNode, StringLexError, StringLexErrorType, With};
+use super::{
+    parsing::{MAX_REPEATED_TOKENS, MAX_REPEAT_COUNT},
+    AlignMode, Imm, Info, LocalLabelRef, ParserNode, StringLexError, StringLexErrorType, With,
+};
 
 #[derive(Debug, Clone)]
 /// Lexer error
@@ -20,6 +23,8 @@ pub enum LexError {
     IsNewline(Info),
     IgnoredWithWarning(Info),
     IgnoredWithoutWarning,
+    /// The token is a comment, which may hold an annotation.
+    IsComment(Info),
     UnexpectedToken(Info),
     UnexpectedEOF,
     NeedTwoNodes(Box<ParserNode>, Box<ParserNode>),
@@ -27,6 +32,29 @@ pub enum LexError {
     UnknownDirective(Info),
     UnsupportedDirective(Info),
     InvalidString(Info, Box<StringLexError>),
+    /// The line ended before an operand was found.
+    MissingOperand(Vec<ExpectedType>, Info),
+    /// An instruction has too few operands.
+    ///
+    /// (Where the operand was expected, expected count, found count)
+    WrongOperandCount(Box<Info>, usize, usize),
+    /// An instruction has too many operands. The instruction is still parsed.
+    ///
+    /// (Parsed nodes, extra operands, expected count, found count)
+    TooManyOperands(Vec<ParserNode>, Box<Info>, usize, usize),
+    /// A number is not a valid floating-point literal.
+    InvalidFloat(Info),
+    /// A CSR is written with a name that is not known.
+    UnknownCsr(Info),
+    /// An instruction has more than one error, in the order they were found.
+    ///
+    /// Operands of the wrong kind do not stop an instruction from being
+    /// parsed, so every bad operand can be reported.
+    Multiple(Vec<LexError>),
+    /// A `.rept` block starts, whose body the parser reads and repeats.
+    ///
+    /// (Directive, number of times to repeat the body)
+    Repeat(Box<Info>, Box<With<Imm>>),
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +73,38 @@ pub enum ParseError {
     FileNotFound(With<String>),
     IOError(With<String>, String),
     InvalidString(Info, Box<StringLexError>),
+    /// An instruction has the wrong number of operands.
+    ///
+    /// (Location, expected count, found count)
+    WrongOperandCount(Info, usize, usize),
+    /// A number is not a valid floating-point literal.
+    InvalidFloat(Info),
+    /// The argument of `.align` is not a valid alignment.
+    ///
+    /// (Location of the argument, how the argument is read)
+    InvalidAlignment(Info, AlignMode),
+    /// A numeric local label reference, like `1b`, has no matching
+    /// definition.
+    UndefinedLocalLabel(Info, LocalLabelRef),
+    /// A constant is defined more than once.
+    RedefinedConstant(Info),
+    /// A `.rept` block has no matching `.endr`.
+    UnterminatedRepeat(Info),
+    /// The count of a `.rept` block is negative, or repeats too much code.
+    InvalidRepeatCount(Info),
+    /// A CSR is written with a name that is not known, so the instruction
+    /// is skipped.
+    UnknownCsr(Info),
+}
+
+impl ParseError {
+    /// The rule of every parse error.
+    pub const RULE: RuleInfo = RuleInfo::new(
+        "parse-error",
+        "Parse error",
+        SeverityLevel::Error,
+        "The code could not be read, so it is not analyzed.",
+    );
 }
 
 impl FileReaderError {
@@ -86,11 +146,43 @@ impl Display for ParseError {
             ParseError::InvalidString(_info, _kind) => {
                 write!(f, "Invalid string")
             }
+            ParseError::WrongOperandCount(_, expected, found) => {
+                write!(
+                    f,
+                    "Expected {expected} operand{}, found {found}",
+                    if *expected == 1 { "" } else { "s" }
+                )
+            }
+            ParseError::InvalidFloat(info) => {
+                write!(f, "Invalid floating-point literal {}", info.token.as_original_string())
+            }
+            ParseError::InvalidAlignment(info, _) => {
+                write!(f, "Invalid alignment {}", info.token.as_original_string())
+            }
+            ParseError::UndefinedLocalLabel(_, LocalLabelRef::Backward(n)) => {
+                write!(f, "Local label {n} is not defined before here")
+            }
+            ParseError::UndefinedLocalLabel(_, LocalLabelRef::Forward(n)) => {
+                write!(f, "Local label {n} is not defined after here")
+            }
+            ParseError::RedefinedConstant(info) => {
+                write!(f, "Constant {} is already defined", info.token.as_original_string())
+            }
+            ParseError::UnterminatedRepeat(_) => write!(f, ".rept has no matching .endr"),
+            ParseError::InvalidRepeatCount(info) => {
+                write!(f, "Invalid repeat count {}", info.token.as_original_string())
+            }
+            ParseError::UnknownCsr(info) => {
+                write!(f, "Unknown CSR {}", info.token.as_original_string())
+            }
         }
     }
 }
 
 impl DiagnosticMessage for ParseError {
+    fn code(&self) -> String {
+        Self::RULE.id.to_string()
+    }
     fn related(&self) -> Option<Vec<crate::passes::RelatedDiagnosticItem>> {
         None
     }
@@ -134,6 +226,56 @@ impl DiagnosticMessage for ParseError {
             ".to_string(),
             ParseError::FileNotFound(file) => format!("File not found: {}", file.data),
             ParseError::IOError(file, err) => format!("IO Error: {} ({})", file.data, err),
+            ParseError::WrongOperandCount(..) => format!(
+                "{self}.\n\n\
+                This instruction has the wrong number of operands. Check that no operand is \
+                missing and that the operands are separated correctly."
+            ),
+            ParseError::InvalidFloat(_) => format!(
+                "{self}.\n\n\
+                Floating-point values are written with an optional sign, digits, an optional \
+                decimal point and an optional exponent, like 3.14, -0.5 or 1e10."
+            ),
+            ParseError::InvalidAlignment(_, AlignMode::Power) => format!(
+                "{self}.\n\n\
+                The argument of .align is the power of two to align to, so .align 2 aligns \
+                to 4 bytes. It must be between 0 and {}.",
+                AlignMode::MAX_BYTES.trailing_zeros()
+            ),
+            ParseError::InvalidAlignment(_, AlignMode::Bytes) => format!(
+                "{self}.\n\n\
+                The argument of .align is the number of bytes to align to, so .align 4 \
+                aligns to 4 bytes. It must be a power of two no larger than {}.",
+                AlignMode::MAX_BYTES
+            ),
+            ParseError::UndefinedLocalLabel(..) => format!(
+                "{self}.\n\n\
+                A reference like 1b jumps to the closest 1: before it, and 1f jumps to the \
+                closest 1: after it. Check that the label is defined on the right side of the \
+                reference."
+            ),
+            ParseError::RedefinedConstant(_) => format!(
+                "{self}.\n\n\
+                A constant can only be defined once. The first definition is used, so this \
+                one has no effect. Use a different name for the new value."
+            ),
+            ParseError::UnterminatedRepeat(_) => format!(
+                "{self}.\n\n\
+                The lines after .rept are repeated up to the matching .endr. Without one, it \
+                is not known which lines to repeat, so none of them are read. Add .endr after \
+                the last line to repeat."
+            ),
+            ParseError::InvalidRepeatCount(_) => format!(
+                "{self}.\n\n\
+                A .rept block can be repeated from 0 to {MAX_REPEAT_COUNT} times, and at most \
+                {MAX_REPEATED_TOKENS} tokens can be repeated in a program, so that it can be \
+                checked in a reasonable time. The block is not read."
+            ),
+            ParseError::UnknownCsr(_) => format!(
+                "{self}.\n\n\
+                This is not the name of a known CSR, so the instruction is not analyzed. \
+                Check the name for a typo, or write the address of the CSR, like 0x300."
+            ),
             ParseError::InvalidString(_, e) => {
                 match e.kind {
                     StringLexErrorType::InvalidEscapeSequence => {
@@ -176,6 +318,14 @@ impl DiagnosticLocation for ParseError {
             | ParseError::UnexpectedError(info)
             | ParseError::UnknownDirective(info)
             | ParseError::InvalidString(info, _)
+            | ParseError::WrongOperandCount(info, ..)
+            | ParseError::InvalidFloat(info)
+            | ParseError::InvalidAlignment(info, _)
+            | ParseError::UndefinedLocalLabel(info, _)
+            | ParseError::RedefinedConstant(info)
+            | ParseError::UnterminatedRepeat(info)
+            | ParseError::InvalidRepeatCount(info)
+            | ParseError::UnknownCsr(info)
             | ParseError::CyclicDependency(info) => info.file,
             ParseError::FileNotFound(file) | ParseError::IOError(file, _) => file.file,
         }
@@ -189,6 +339,14 @@ impl DiagnosticLocation for ParseError {
             | ParseError::UnexpectedError(info)
             | ParseError::UnknownDirective(info)
             | ParseError::InvalidString(info, _)
+            | ParseError::WrongOperandCount(info, ..)
+            | ParseError::InvalidFloat(info)
+            | ParseError::InvalidAlignment(info, _)
+            | ParseError::UndefinedLocalLabel(info, _)
+            | ParseError::RedefinedConstant(info)
+            | ParseError::UnterminatedRepeat(info)
+            | ParseError::InvalidRepeatCount(info)
+            | ParseError::UnknownCsr(info)
             | ParseError::CyclicDependency(info) => info.pos.clone(),
             ParseError::FileNotFound(file) | ParseError::IOError(file, _) => file.pos.clone(),
         }
@@ -206,7 +364,14 @@ impl From<&ParseError> for SeverityLevel {
             | ParseError::CyclicDependency(_)
             | ParseError::FileNotFound(_)
             | ParseError::InvalidString(..)
+            | ParseError::WrongOperandCount(..)
+            | ParseError::InvalidFloat(_)
+            | ParseError::InvalidAlignment(..)
+            | ParseError::UndefinedLocalLabel(..)
+            | ParseError::UnterminatedRepeat(_)
+            | ParseError::InvalidRepeatCount(_)
             | ParseError::IOError(_, _) => SeverityLevel::Error,
+            ParseError::RedefinedConstant(_) | ParseError::UnknownCsr(_) => SeverityLevel::Warning,
         }
     }
 }
@@ -214,6 +379,7 @@ impl From<&ParseError> for SeverityLevel {
 #[derive(Debug, Clone)]
 pub enum ExpectedType {
     Register,
+    FloatRegister,
     Imm,
     Label,
     LParen,
@@ -227,6 +393,7 @@ impl std::fmt::Display for ExpectedType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ExpectedType::Register => write!(f, "REGISTER"),
+            ExpectedType::FloatRegister => write!(f, "FLOAT-REGISTER"),
             ExpectedType::Imm => write!(f, "IMMEDIATE"),
             ExpectedType::Label => write!(f, "LABEL"),
             ExpectedType::LParen => write!(f, "LPAREN"),
@@ -1,3 +1,4 @@
+use std::fmt::Display;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
@@ -19,9 +20,103 @@ impl TryFrom<Info> for Imm {
     }
 }
 
+/// The address of a control and status register (CSR).
+///
+/// A CSR can be written by name, like `mstatus`, or by its address. The
+/// address is kept as written, so an address that does not fit in the 12
+/// bits of a CSR can still be reported.
 #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct CSRImm(pub u32);
 
+/// The name and address of every known CSR.
+const CSR_NAMES: &[(&str, u32)] = &[
+    ("ustatus", 0x000),
+    ("fflags", 0x001),
+    ("frm", 0x002),
+    ("fcsr", 0x003),
+    ("uie", 0x004),
+    ("utvec", 0x005),
+    ("uscratch", 0x040),
+    ("uepc", 0x041),
+    ("ucause", 0x042),
+    ("utval", 0x043),
+    ("uip", 0x044),
+    ("sstatus", 0x100),
+    ("sie", 0x104),
+    ("stvec", 0x105),
+    ("scounteren", 0x106),
+    ("sscratch", 0x140),
+    ("sepc", 0x141),
+    ("scause", 0x142),
+    ("stval", 0x143),
+    ("sip", 0x144),
+    ("satp", 0x180),
+    ("mstatus", 0x300),
+    ("misa", 0x301),
+    ("medeleg", 0x302),
+    ("mideleg", 0x303),
+    ("mie", 0x304),
+    ("mtvec", 0x305),
+    ("mcounteren", 0x306),
+    ("mscratch", 0x340),
+    ("mepc", 0x341),
+    ("mcause", 0x342),
+    ("mtval", 0x343),
+    ("mip", 0x344),
+    ("mcycle", 0xB00),
+    ("minstret", 0xB02),
+    ("mcycleh", 0xB80),
+    ("minstreth", 0xB82),
+    ("cycle", 0xC00),
+    ("time", 0xC01),
+    ("instret", 0xC02),
+    ("cycleh", 0xC80),
+    ("timeh", 0xC81),
+    ("instreth", 0xC82),
+    ("mvendorid", 0xF11),
+    ("marchid", 0xF12),
+    ("mimpid", 0xF13),
+    ("mhartid", 0xF14),
+];
+
+impl CSRImm {
+    /// The largest address of a CSR, which is 12 bits.
+    pub const MAX_ADDRESS: u32 = 0xFFF;
+
+    /// Get the name of this CSR, if it is known.
+    #[must_use]
+    pub fn name(&self) -> Option<&'static str> {
+        CSR_NAMES
+            .iter()
+            .find(|(_, address)| *address == self.0)
+            .map(|(name, _)| *name)
+    }
+
+    /// Check if this address fits in the 12 bits of a CSR.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.0 <= Self::MAX_ADDRESS
+    }
+
+    /// Check if this CSR can only be read.
+    ///
+    /// The top two bits of the address of a read-only CSR are set, like
+    /// those of `cycle` and `mhartid`.
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.is_valid() && self.0 >> 10 == 0b11
+    }
+}
+
+impl Display for CSRImm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
 impl TryFrom<Info> for CSRImm {
     type Error = ();
 
@@ -38,31 +133,28 @@ impl FromStr for CSRImm {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let string = s.to_lowercase();
-        let num = match string.as_str() {
-            "ustatus" => 0x000,
-            "fflags" => 0x001,
-            "frm" => 0x002,
-            "fcsr" => 0x003,
-            "uie" => 0x004,
-            "utvec" => 0x005,
-            "uscratch" => 0x040,
-            "uepc" => 0x041,
-            "ucause" => 0x042,
-            "utval" => 0x043,
-            "uip" => 0x044,
-            "cycle" => 0xC00,
-            "time" => 0xC01,
-            "instret" => 0xC02,
-            "cycleh" => 0xC80,
-            "timeh" => 0xC81,
-            "instreth" => 0xC82,
+        let num = match CSR_NAMES.iter().find(|(name, _)| *name == string) {
+            Some((_, address)) => *address,
             #[allow(clippy::cast_sign_loss)]
-            _ => Imm::from_str(s)?.0 as u32,
+            None => Imm::from_str(s)?.0 as u32,
         };
         Ok(CSRImm(num))
     }
 }
 
+impl Imm {
+    /// Remove the `_` separators from the digits of a number, like the ones
+    /// in `0x1234_5678`.
+    ///
+    /// Returns `None` if a separator is not between two digits.
+    fn strip_separators(digits: &str) -> Option<String> {
+        if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+            return None;
+        }
+        Some(digits.replace('_', ""))
+    }
+}
+
 impl FromStr for Imm {
     type Err = ();
 
@@ -79,26 +171,29 @@ impl FromStr for Imm {
         if s == "zero" {
             Ok(Imm(0))
         } else if let Some(stripped) = s.strip_prefix("0x") {
+            let stripped = Imm::strip_separators(stripped).ok_or(())?;
             if stripped.starts_with('-') {
                 Err(())
             } else {
-                match u32::from_str_radix(stripped, 16) {
+                match u32::from_str_radix(&stripped, 16) {
                     #[allow(clippy::cast_possible_wrap)]
                     Ok(i) => Ok(Imm(mul * i as i32)),
                     Err(_) => Err(()),
                 }
             }
         } else if let Some(stripped) = s.strip_prefix("0b") {
+            let stripped = Imm::strip_separators(stripped).ok_or(())?;
             if stripped.starts_with('-') {
                 Err(())
             } else {
-                match u32::from_str_radix(stripped, 2) {
+                match u32::from_str_radix(&stripped, 2) {
                     #[allow(clippy::cast_possible_wrap)]
                     Ok(i) => Ok(Imm(mul * i as i32)),
                     Err(_) => Err(()),
                 }
             }
         } else {
+            let s = Imm::strip_separators(s).ok_or(())?;
             if s.starts_with('-') {
                 return Err(());
             }
@@ -124,9 +219,60 @@ impl From<CSRImm> for Imm {
     }
 }
 
+/// A floating-point immediate, used by the `.float` and `.double` directives.
+///
+/// Integers are also accepted, so `.float 1` is the same as `.float 1.0`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FloatImm(pub f64);
+
+impl PartialEq for FloatImm {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for FloatImm {}
+
+impl TryFrom<Info> for FloatImm {
+    type Error = ();
+
+    fn try_from(value: Info) -> Result<Self, Self::Error> {
+        match value.token {
+            Token::Symbol(s) => FloatImm::from_str(&s),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FloatImm {
+    /// Check if a symbol is written like a number.
+    ///
+    /// Numbers start with a digit or a decimal point, after an optional sign.
+    #[must_use]
+    pub fn looks_numeric(s: &str) -> bool {
+        let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+        s.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+    }
+}
+
+impl FromStr for FloatImm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Words like `inf` and `nan` are not accepted
+        if !FloatImm::looks_numeric(s) {
+            return Err(());
+        }
+        if let Ok(imm) = Imm::from_str(s) {
+            return Ok(FloatImm(f64::from(imm.0)));
+        }
+        s.parse::<f64>().map(FloatImm).map_err(|_| ())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::parser::imm::Imm;
+    use crate::parser::imm::{CSRImm, FloatImm, Imm};
     use std::str::FromStr;
 
     #[test]
@@ -215,4 +361,57 @@ mod test {
         assert_eq!(Imm::from_str("0x-00000000"), Err(()));
         assert_eq!(Imm::from_str("0b-00000000"), Err(()));
     }
+
+    #[test]
+    fn underscore_grouping() {
+        assert_eq!(Imm::from_str("1_000"), Ok(Imm(1000)));
+        assert_eq!(Imm::from_str("-1_000_000"), Ok(Imm(-1_000_000)));
+        assert_eq!(Imm::from_str("0x1234_5678"), Ok(Imm(0x1234_5678)));
+        assert_eq!(Imm::from_str("0b1010_0101"), Ok(Imm(0b1010_0101)));
+    }
+
+    #[test]
+    fn misplaced_underscores() {
+        assert_eq!(Imm::from_str("0x_1"), Err(()));
+        assert_eq!(Imm::from_str("0b_1"), Err(()));
+        assert_eq!(Imm::from_str("1_"), Err(()));
+        assert_eq!(Imm::from_str("_1"), Err(()));
+        assert_eq!(Imm::from_str("1__0"), Err(()));
+        assert_eq!(Imm::from_str("0x_"), Err(()));
+    }
+
+    #[test]
+    fn csr_names() {
+        assert_eq!(CSRImm::from_str("mstatus"), Ok(CSRImm(0x300)));
+        assert_eq!(CSRImm::from_str("MEPC"), Ok(CSRImm(0x341)));
+        assert_eq!(CSRImm::from_str("0x305"), Ok(CSRImm(0x305)));
+        assert_eq!(CSRImm::from_str("mstatu"), Err(()));
+
+        assert_eq!(CSRImm(0x305).to_string(), "mtvec");
+        assert_eq!(CSRImm(0x7ff).to_string(), "0x7ff");
+        assert!(CSRImm(0xC00).is_read_only());
+        assert!(!CSRImm(0x300).is_read_only());
+        assert!(!CSRImm(0x1000).is_valid());
+    }
+
+    #[test]
+    fn float_imm() {
+        assert_eq!(FloatImm::from_str("2.75"), Ok(FloatImm(2.75)));
+        assert_eq!(FloatImm::from_str("-0.5"), Ok(FloatImm(-0.5)));
+        assert_eq!(FloatImm::from_str(".5"), Ok(FloatImm(0.5)));
+        assert_eq!(FloatImm::from_str("1e10"), Ok(FloatImm(1e10)));
+        assert_eq!(FloatImm::from_str("1.5E-3"), Ok(FloatImm(1.5e-3)));
+        assert_eq!(FloatImm::from_str("2.5e+2"), Ok(FloatImm(250.0)));
+        assert_eq!(FloatImm::from_str("7"), Ok(FloatImm(7.0)));
+        assert_eq!(FloatImm::from_str("0x10"), Ok(FloatImm(16.0)));
+    }
+
+    #[test]
+    fn invalid_float_imm() {
+        assert_eq!(FloatImm::from_str("3.1.4"), Err(()));
+        assert_eq!(FloatImm::from_str("1e"), Err(()));
+        assert_eq!(FloatImm::from_str("nan"), Err(()));
+        assert_eq!(FloatImm::from_str("inf"), Err(()));
+        assert_eq!(FloatImm::from_str("zero"), Err(()));
+    }
 }
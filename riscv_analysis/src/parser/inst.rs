@@ -1,7 +1,9 @@
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
+use super::Xlen;
+
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub enum BasicType {
     Ebreak,
@@ -24,6 +26,7 @@ pub enum ArithType {
     Srl,
     Srlw,
     Sub,
+    Subw,
     Xor,
     Mul,
     Mulh,
@@ -63,8 +66,14 @@ pub enum IArithType {
     Srli,
     Srliw,
     Xori,
-    Auipc, // Same as this
+}
+
+/// Instructions that load a 20-bit immediate into the upper bits of a
+/// register.
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum UpperArithType {
     Lui,
+    Auipc,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
@@ -75,6 +84,9 @@ pub enum LoadType {
     Lhu,
     Lw,
     Lwu,
+    Ld,
+    Flw,
+    Fld,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
@@ -82,6 +94,47 @@ pub enum StoreType {
     Sb,
     Sh,
     Sw,
+    Sd,
+    Fsw,
+    Fsd,
+}
+
+impl LoadType {
+    /// Get the number of bytes that the load reads from memory.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        match self {
+            LoadType::Lb | LoadType::Lbu => 1,
+            LoadType::Lh | LoadType::Lhu => 2,
+            LoadType::Lw | LoadType::Lwu | LoadType::Flw => 4,
+            LoadType::Ld | LoadType::Fld => 8,
+        }
+    }
+
+    /// Check if the load writes to a floating-point register.
+    #[must_use]
+    pub fn is_float(&self) -> bool {
+        matches!(self, LoadType::Flw | LoadType::Fld)
+    }
+}
+
+impl StoreType {
+    /// Get the number of bytes that the store writes to memory.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        match self {
+            StoreType::Sb => 1,
+            StoreType::Sh => 2,
+            StoreType::Sw | StoreType::Fsw => 4,
+            StoreType::Sd | StoreType::Fsd => 8,
+        }
+    }
+
+    /// Check if the store reads from a floating-point register.
+    #[must_use]
+    pub fn is_float(&self) -> bool {
+        matches!(self, StoreType::Fsw | StoreType::Fsd)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
@@ -114,6 +167,210 @@ pub enum JumpLinkRType {
     Jalr,
 }
 
+/// Arithmetic, comparisons and conversions of the F and D extensions.
+///
+/// Each instruction writes to one register and reads from one to three
+/// registers. Whether those are integer or floating-point registers depends
+/// on the instruction.
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum FloatArithType {
+    FaddS,
+    FsubS,
+    FmulS,
+    FdivS,
+    FsqrtS,
+    FminS,
+    FmaxS,
+    FsgnjS,
+    FsgnjnS,
+    FsgnjxS,
+    FmaddS,
+    FmsubS,
+    FnmaddS,
+    FnmsubS,
+    FeqS,
+    FltS,
+    FleS,
+    FclassS,
+    FcvtWS,
+    FcvtSW,
+    FcvtWuS,
+    FcvtSWu,
+    FcvtLS,
+    FcvtSL,
+    FcvtLuS,
+    FcvtSLu,
+    FaddD,
+    FsubD,
+    FmulD,
+    FdivD,
+    FsqrtD,
+    FminD,
+    FmaxD,
+    FsgnjD,
+    FsgnjnD,
+    FsgnjxD,
+    FmaddD,
+    FmsubD,
+    FnmaddD,
+    FnmsubD,
+    FeqD,
+    FltD,
+    FleD,
+    FclassD,
+    FcvtWD,
+    FcvtDW,
+    FcvtWuD,
+    FcvtDWu,
+    FcvtLD,
+    FcvtDL,
+    FcvtLuD,
+    FcvtDLu,
+    FcvtSD,
+    FcvtDS,
+    FmvXW,
+    FmvWX,
+    FmvXD,
+    FmvDX,
+}
+
+impl FloatArithType {
+    /// Check if the destination register is a floating-point register.
+    ///
+    /// Comparisons, classifications and conversions to integers write to
+    /// an integer register instead.
+    #[must_use]
+    pub fn rd_is_float(&self) -> bool {
+        !matches!(
+            self,
+            FloatArithType::FeqS
+                | FloatArithType::FltS
+                | FloatArithType::FleS
+                | FloatArithType::FclassS
+                | FloatArithType::FcvtWS
+                | FloatArithType::FcvtWuS
+                | FloatArithType::FcvtLS
+                | FloatArithType::FcvtLuS
+                | FloatArithType::FeqD
+                | FloatArithType::FltD
+                | FloatArithType::FleD
+                | FloatArithType::FclassD
+                | FloatArithType::FcvtWD
+                | FloatArithType::FcvtWuD
+                | FloatArithType::FcvtLD
+                | FloatArithType::FcvtLuD
+                | FloatArithType::FmvXW
+                | FloatArithType::FmvXD
+        )
+    }
+
+    /// Check if the source registers are floating-point registers.
+    ///
+    /// Conversions and moves from integers read from an integer register
+    /// instead.
+    #[must_use]
+    pub fn sources_are_float(&self) -> bool {
+        !matches!(
+            self,
+            FloatArithType::FcvtSW
+                | FloatArithType::FcvtSWu
+                | FloatArithType::FcvtSL
+                | FloatArithType::FcvtSLu
+                | FloatArithType::FcvtDW
+                | FloatArithType::FcvtDWu
+                | FloatArithType::FcvtDL
+                | FloatArithType::FcvtDLu
+                | FloatArithType::FmvWX
+                | FloatArithType::FmvDX
+        )
+    }
+
+    /// Get the number of source registers that the instruction reads.
+    #[must_use]
+    pub fn source_count(&self) -> usize {
+        match self {
+            FloatArithType::FmaddS
+            | FloatArithType::FmsubS
+            | FloatArithType::FnmaddS
+            | FloatArithType::FnmsubS
+            | FloatArithType::FmaddD
+            | FloatArithType::FmsubD
+            | FloatArithType::FnmaddD
+            | FloatArithType::FnmsubD => 3,
+            FloatArithType::FaddS
+            | FloatArithType::FsubS
+            | FloatArithType::FmulS
+            | FloatArithType::FdivS
+            | FloatArithType::FminS
+            | FloatArithType::FmaxS
+            | FloatArithType::FsgnjS
+            | FloatArithType::FsgnjnS
+            | FloatArithType::FsgnjxS
+            | FloatArithType::FeqS
+            | FloatArithType::FltS
+            | FloatArithType::FleS
+            | FloatArithType::FaddD
+            | FloatArithType::FsubD
+            | FloatArithType::FmulD
+            | FloatArithType::FdivD
+            | FloatArithType::FminD
+            | FloatArithType::FmaxD
+            | FloatArithType::FsgnjD
+            | FloatArithType::FsgnjnD
+            | FloatArithType::FsgnjxD
+            | FloatArithType::FeqD
+            | FloatArithType::FltD
+            | FloatArithType::FleD => 2,
+            _ => 1,
+        }
+    }
+
+    /// Check if the instruction can be written with a rounding mode, like
+    /// `rtz`, as its last operand.
+    #[must_use]
+    pub fn takes_rounding_mode(&self) -> bool {
+        matches!(
+            self,
+            FloatArithType::FaddS
+                | FloatArithType::FsubS
+                | FloatArithType::FmulS
+                | FloatArithType::FdivS
+                | FloatArithType::FsqrtS
+                | FloatArithType::FmaddS
+                | FloatArithType::FmsubS
+                | FloatArithType::FnmaddS
+                | FloatArithType::FnmsubS
+                | FloatArithType::FcvtWS
+                | FloatArithType::FcvtSW
+                | FloatArithType::FcvtWuS
+                | FloatArithType::FcvtSWu
+                | FloatArithType::FcvtLS
+                | FloatArithType::FcvtSL
+                | FloatArithType::FcvtLuS
+                | FloatArithType::FcvtSLu
+                | FloatArithType::FaddD
+                | FloatArithType::FsubD
+                | FloatArithType::FmulD
+                | FloatArithType::FdivD
+                | FloatArithType::FsqrtD
+                | FloatArithType::FmaddD
+                | FloatArithType::FmsubD
+                | FloatArithType::FnmaddD
+                | FloatArithType::FnmsubD
+                | FloatArithType::FcvtWD
+                | FloatArithType::FcvtDW
+                | FloatArithType::FcvtWuD
+                | FloatArithType::FcvtDWu
+                | FloatArithType::FcvtLD
+                | FloatArithType::FcvtDL
+                | FloatArithType::FcvtLuD
+                | FloatArithType::FcvtDLu
+                | FloatArithType::FcvtSD
+                | FloatArithType::FcvtDS
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Copy, Eq)]
 pub enum Inst {
     Ret,
@@ -133,6 +390,7 @@ pub enum Inst {
     Srl,
     Srlw,
     Sub,
+    Subw,
     Xor,
     Mul,
     Mulh,
@@ -171,9 +429,11 @@ pub enum Inst {
     Lhu,
     Lw,
     Lwu,
+    Ld,
     Sb,
     Sh,
     Sw,
+    Sd,
     Csrrw,
     Csrrs,
     Csrrc,
@@ -193,6 +453,9 @@ pub enum Inst {
     Li,
     Mv,
     Neg,
+    Negw,
+    SextW,
+    ZextB,
     Not,
     Seqz,
     Snez,
@@ -203,6 +466,7 @@ pub enum Inst {
     Bltz,
     Bgez,
     Call,
+    Tail,
     Bgt,
     Ble,
     Bgtu,
@@ -217,9 +481,115 @@ pub enum Inst {
     Csrsi,
     Csrwi,
     Uret,
+    Flw,
+    Fld,
+    Fsw,
+    Fsd,
+    FaddS,
+    FsubS,
+    FmulS,
+    FdivS,
+    FsqrtS,
+    FminS,
+    FmaxS,
+    FsgnjS,
+    FsgnjnS,
+    FsgnjxS,
+    FmaddS,
+    FmsubS,
+    FnmaddS,
+    FnmsubS,
+    FeqS,
+    FltS,
+    FleS,
+    FclassS,
+    FcvtWS,
+    FcvtSW,
+    FcvtWuS,
+    FcvtSWu,
+    FcvtLS,
+    FcvtSL,
+    FcvtLuS,
+    FcvtSLu,
+    FaddD,
+    FsubD,
+    FmulD,
+    FdivD,
+    FsqrtD,
+    FminD,
+    FmaxD,
+    FsgnjD,
+    FsgnjnD,
+    FsgnjxD,
+    FmaddD,
+    FmsubD,
+    FnmaddD,
+    FnmsubD,
+    FeqD,
+    FltD,
+    FleD,
+    FclassD,
+    FcvtWD,
+    FcvtDW,
+    FcvtWuD,
+    FcvtDWu,
+    FcvtLD,
+    FcvtDL,
+    FcvtLuD,
+    FcvtDLu,
+    FcvtSD,
+    FcvtDS,
+    FmvXW,
+    FmvWX,
+    FmvXD,
+    FmvDX,
+    FmvS,
+    FnegS,
+    FabsS,
+    FmvD,
+    FnegD,
+    FabsD,
 }
 
 impl Inst {
+    /// Check if the instruction only exists on RV64 targets.
+    ///
+    /// These are the loads and stores of 64-bit values, the unsigned word
+    /// load, and the arithmetic on words that ends with `w`.
+    #[must_use]
+    pub fn is_rv64_only(self) -> bool {
+        matches!(
+            self,
+            Inst::Addw
+                | Inst::Sllw
+                | Inst::Sraw
+                | Inst::Srlw
+                | Inst::Subw
+                | Inst::Divw
+                | Inst::Remw
+                | Inst::Remuw
+                | Inst::Addiw
+                | Inst::Slliw
+                | Inst::Sraiw
+                | Inst::Srliw
+                | Inst::Lwu
+                | Inst::Ld
+                | Inst::Sd
+                | Inst::Negw
+                | Inst::SextW
+                | Inst::FcvtLS
+                | Inst::FcvtSL
+                | Inst::FcvtLuS
+                | Inst::FcvtSLu
+                | Inst::FcvtLD
+                | Inst::FcvtDL
+                | Inst::FcvtLuD
+                | Inst::FcvtDLu
+                | Inst::FmvXD
+                | Inst::FmvDX
+        )
+    }
+
     #[must_use]
     #[allow(clippy::too_many_lines)]
     pub fn all() -> Vec<Inst> {
@@ -241,6 +611,7 @@ impl Inst {
             Inst::Srl,
             Inst::Srlw,
             Inst::Sub,
+            Inst::Subw,
             Inst::Xor,
             Inst::Mul,
             Inst::Mulh,
@@ -279,9 +650,11 @@ impl Inst {
             Inst::Lhu,
             Inst::Lw,
             Inst::Lwu,
+            Inst::Ld,
             Inst::Sb,
             Inst::Sh,
             Inst::Sw,
+            Inst::Sd,
             Inst::Csrrw,
             Inst::Csrrs,
             Inst::Csrrc,
@@ -301,6 +674,9 @@ impl Inst {
             Inst::Li,
             Inst::Mv,
             Inst::Neg,
+            Inst::Negw,
+            Inst::SextW,
+            Inst::ZextB,
             Inst::Not,
             Inst::Seqz,
             Inst::Snez,
@@ -311,6 +687,7 @@ impl Inst {
             Inst::Bltz,
             Inst::Bgez,
             Inst::Call,
+            Inst::Tail,
             Inst::Bgt,
             Inst::Ble,
             Inst::Bgtu,
@@ -325,6 +702,74 @@ impl Inst {
             Inst::Csrsi,
             Inst::Csrwi,
             Inst::Uret,
+            Inst::Flw,
+            Inst::Fld,
+            Inst::Fsw,
+            Inst::Fsd,
+            Inst::FaddS,
+            Inst::FsubS,
+            Inst::FmulS,
+            Inst::FdivS,
+            Inst::FsqrtS,
+            Inst::FminS,
+            Inst::FmaxS,
+            Inst::FsgnjS,
+            Inst::FsgnjnS,
+            Inst::FsgnjxS,
+            Inst::FmaddS,
+            Inst::FmsubS,
+            Inst::FnmaddS,
+            Inst::FnmsubS,
+            Inst::FeqS,
+            Inst::FltS,
+            Inst::FleS,
+            Inst::FclassS,
+            Inst::FcvtWS,
+            Inst::FcvtSW,
+            Inst::FcvtWuS,
+            Inst::FcvtSWu,
+            Inst::FcvtLS,
+            Inst::FcvtSL,
+            Inst::FcvtLuS,
+            Inst::FcvtSLu,
+            Inst::FaddD,
+            Inst::FsubD,
+            Inst::FmulD,
+            Inst::FdivD,
+            Inst::FsqrtD,
+            Inst::FminD,
+            Inst::FmaxD,
+            Inst::FsgnjD,
+            Inst::FsgnjnD,
+            Inst::FsgnjxD,
+            Inst::FmaddD,
+            Inst::FmsubD,
+            Inst::FnmaddD,
+            Inst::FnmsubD,
+            Inst::FeqD,
+            Inst::FltD,
+            Inst::FleD,
+            Inst::FclassD,
+            Inst::FcvtWD,
+            Inst::FcvtDW,
+            Inst::FcvtWuD,
+            Inst::FcvtDWu,
+            Inst::FcvtLD,
+            Inst::FcvtDL,
+            Inst::FcvtLuD,
+            Inst::FcvtDLu,
+            Inst::FcvtSD,
+            Inst::FcvtDS,
+            Inst::FmvXW,
+            Inst::FmvWX,
+            Inst::FmvXD,
+            Inst::FmvDX,
+            Inst::FmvS,
+            Inst::FnegS,
+            Inst::FabsS,
+            Inst::FmvD,
+            Inst::FnegD,
+            Inst::FabsD,
         ]
         .into_iter()
         .collect()
@@ -352,6 +797,7 @@ impl Display for Inst {
             Inst::Srl => write!(f, "srl"),
             Inst::Srlw => write!(f, "srlw"),
             Inst::Sub => write!(f, "sub"),
+            Inst::Subw => write!(f, "subw"),
             Inst::Xor => write!(f, "xor"),
             Inst::Mul => write!(f, "mul"),
             Inst::Mulh => write!(f, "mulh"),
@@ -390,9 +836,11 @@ impl Display for Inst {
             Inst::Lhu => write!(f, "lhu"),
             Inst::Lw => write!(f, "lw"),
             Inst::Lwu => write!(f, "lwu"),
+            Inst::Ld => write!(f, "ld"),
             Inst::Sb => write!(f, "sb"),
             Inst::Sh => write!(f, "sh"),
             Inst::Sw => write!(f, "sw"),
+            Inst::Sd => write!(f, "sd"),
             Inst::Csrrw => write!(f, "csrrw"),
             Inst::Csrrs => write!(f, "csrrs"),
             Inst::Csrrc => write!(f, "csrrc"),
@@ -412,6 +860,9 @@ impl Display for Inst {
             Inst::Li => write!(f, "li"),
             Inst::Mv => write!(f, "mv"),
             Inst::Neg => write!(f, "neg"),
+            Inst::Negw => write!(f, "negw"),
+            Inst::SextW => write!(f, "sext.w"),
+            Inst::ZextB => write!(f, "zext.b"),
             Inst::Not => write!(f, "not"),
             Inst::Seqz => write!(f, "seqz"),
             Inst::Snez => write!(f, "snez"),
@@ -422,6 +873,7 @@ impl Display for Inst {
             Inst::Bltz => write!(f, "bltz"),
             Inst::B => write!(f, "b"),
             Inst::Call => write!(f, "call"),
+            Inst::Tail => write!(f, "tail"),
             Inst::Bgt => write!(f, "bgt"),
             Inst::Ble => write!(f, "ble"),
             Inst::Bgtu => write!(f, "bgtu"),
@@ -436,6 +888,74 @@ impl Display for Inst {
             Inst::Csrsi => write!(f, "csrsi"),
             Inst::Csrwi => write!(f, "csrwi"),
             Inst::Uret => write!(f, "uret"),
+            Inst::Flw => write!(f, "flw"),
+            Inst::Fld => write!(f, "fld"),
+            Inst::Fsw => write!(f, "fsw"),
+            Inst::Fsd => write!(f, "fsd"),
+            Inst::FaddS => write!(f, "fadd.s"),
+            Inst::FsubS => write!(f, "fsub.s"),
+            Inst::FmulS => write!(f, "fmul.s"),
+            Inst::FdivS => write!(f, "fdiv.s"),
+            Inst::FsqrtS => write!(f, "fsqrt.s"),
+            Inst::FminS => write!(f, "fmin.s"),
+            Inst::FmaxS => write!(f, "fmax.s"),
+            Inst::FsgnjS => write!(f, "fsgnj.s"),
+            Inst::FsgnjnS => write!(f, "fsgnjn.s"),
+            Inst::FsgnjxS => write!(f, "fsgnjx.s"),
+            Inst::FmaddS => write!(f, "fmadd.s"),
+            Inst::FmsubS => write!(f, "fmsub.s"),
+            Inst::FnmaddS => write!(f, "fnmadd.s"),
+            Inst::FnmsubS => write!(f, "fnmsub.s"),
+            Inst::FeqS => write!(f, "feq.s"),
+            Inst::FltS => write!(f, "flt.s"),
+            Inst::FleS => write!(f, "fle.s"),
+            Inst::FclassS => write!(f, "fclass.s"),
+            Inst::FcvtWS => write!(f, "fcvt.w.s"),
+            Inst::FcvtSW => write!(f, "fcvt.s.w"),
+            Inst::FcvtWuS => write!(f, "fcvt.wu.s"),
+            Inst::FcvtSWu => write!(f, "fcvt.s.wu"),
+            Inst::FcvtLS => write!(f, "fcvt.l.s"),
+            Inst::FcvtSL => write!(f, "fcvt.s.l"),
+            Inst::FcvtLuS => write!(f, "fcvt.lu.s"),
+            Inst::FcvtSLu => write!(f, "fcvt.s.lu"),
+            Inst::FaddD => write!(f, "fadd.d"),
+            Inst::FsubD => write!(f, "fsub.d"),
+            Inst::FmulD => write!(f, "fmul.d"),
+            Inst::FdivD => write!(f, "fdiv.d"),
+            Inst::FsqrtD => write!(f, "fsqrt.d"),
+            Inst::FminD => write!(f, "fmin.d"),
+            Inst::FmaxD => write!(f, "fmax.d"),
+            Inst::FsgnjD => write!(f, "fsgnj.d"),
+            Inst::FsgnjnD => write!(f, "fsgnjn.d"),
+            Inst::FsgnjxD => write!(f, "fsgnjx.d"),
+            Inst::FmaddD => write!(f, "fmadd.d"),
+            Inst::FmsubD => write!(f, "fmsub.d"),
+            Inst::FnmaddD => write!(f, "fnmadd.d"),
+            Inst::FnmsubD => write!(f, "fnmsub.d"),
+            Inst::FeqD => write!(f, "feq.d"),
+            Inst::FltD => write!(f, "flt.d"),
+            Inst::FleD => write!(f, "fle.d"),
+            Inst::FclassD => write!(f, "fclass.d"),
+            Inst::FcvtWD => write!(f, "fcvt.w.d"),
+            Inst::FcvtDW => write!(f, "fcvt.d.w"),
+            Inst::FcvtWuD => write!(f, "fcvt.wu.d"),
+            Inst::FcvtDWu => write!(f, "fcvt.d.wu"),
+            Inst::FcvtLD => write!(f, "fcvt.l.d"),
+            Inst::FcvtDL => write!(f, "fcvt.d.l"),
+            Inst::FcvtLuD => write!(f, "fcvt.lu.d"),
+            Inst::FcvtDLu => write!(f, "fcvt.d.lu"),
+            Inst::FcvtSD => write!(f, "fcvt.s.d"),
+            Inst::FcvtDS => write!(f, "fcvt.d.s"),
+            Inst::FmvXW => write!(f, "fmv.x.w"),
+            Inst::FmvWX => write!(f, "fmv.w.x"),
+            Inst::FmvXD => write!(f, "fmv.x.d"),
+            Inst::FmvDX => write!(f, "fmv.d.x"),
+            Inst::FmvS => write!(f, "fmv.s"),
+            Inst::FnegS => write!(f, "fneg.s"),
+            Inst::FabsS => write!(f, "fabs.s"),
+            Inst::FmvD => write!(f, "fmv.d"),
+            Inst::FnegD => write!(f, "fneg.d"),
+            Inst::FabsD => write!(f, "fabs.d"),
         }
     }
 }
@@ -453,7 +973,108 @@ pub enum Type {
     Ignore(IgnoreType),
     Branch(BranchType),
     Pseudo(PseudoType),
-    UpperArith(IArithType),
+    UpperArith(UpperArithType),
+    FloatArith(FloatArithType),
+}
+
+impl Type {
+    /// The number of operands that an instruction of this type takes, as
+    /// written.
+    ///
+    /// An offset with a base register, like `0(sp)`, is a single operand.
+    /// Some instructions can be written in more than one form, so this is
+    /// a range. Ignored instructions have no operand count.
+    #[must_use]
+    pub fn operand_count(&self) -> Option<RangeInclusive<usize>> {
+        let count = match self {
+            Type::Basic(_) => 0..=0,
+            Type::Arith(_)
+            | Type::IArith(_)
+            | Type::Branch(_)
+            | Type::Csr(_)
+            | Type::CsrI(_) => 3..=3,
+            Type::UpperArith(_) | Type::Load(_) => 2..=2,
+            Type::Store(_) => 2..=3,
+            Type::JumpLink(_) => 1..=2,
+            Type::JumpLinkR(_) => 1..=3,
+            Type::FloatArith(inst) => {
+                let count = inst.source_count() + 1;
+                if inst.takes_rounding_mode() {
+                    count..=count + 1
+                } else {
+                    count..=count
+                }
+            }
+            Type::Ignore(_) => return None,
+            Type::Pseudo(inst) => match inst {
+                PseudoType::Nop | PseudoType::Ret => 0..=0,
+                PseudoType::J
+                | PseudoType::Jr
+                | PseudoType::B
+                | PseudoType::Call
+                | PseudoType::Tail => 1..=1,
+                PseudoType::Bgt | PseudoType::Ble | PseudoType::Bgtu | PseudoType::Bleu => 3..=3,
+                PseudoType::Beqz
+                | PseudoType::Bnez
+                | PseudoType::Bltz
+                | PseudoType::Bgez
+                | PseudoType::Bgtz
+                | PseudoType::Blez
+                | PseudoType::La
+                | PseudoType::Li
+                | PseudoType::Mv
+                | PseudoType::Neg
+                | PseudoType::Negw
+                | PseudoType::SextW
+                | PseudoType::ZextB
+                | PseudoType::Not
+                | PseudoType::Seqz
+                | PseudoType::Snez
+                | PseudoType::Sgtz
+                | PseudoType::Sltz
+                | PseudoType::Sgez
+                | PseudoType::Csrc
+                | PseudoType::Csrr
+                | PseudoType::Csrs
+                | PseudoType::Csrw
+                | PseudoType::Csrci
+                | PseudoType::Csrsi
+                | PseudoType::Csrwi
+                | PseudoType::FmvS
+                | PseudoType::FnegS
+                | PseudoType::FabsS
+                | PseudoType::FmvD
+                | PseudoType::FnegD
+                | PseudoType::FabsD => 2..=2,
+            },
+        };
+        Some(count)
+    }
+
+    /// The values that the immediate of an instruction of this type can
+    /// hold, as it is written.
+    ///
+    /// Most immediates are 12-bit signed values. A shift amount must be
+    /// less than the width of a register, so it depends on `xlen`, except
+    /// for shifts of words, which are always less than 32. The immediates
+    /// of `lui` and `auipc` are the 20 upper bits of a value, and the
+    /// immediates of CSR instructions are 5-bit unsigned values. Types
+    /// without an immediate have no range.
+    #[must_use]
+    pub fn immediate_range(&self, xlen: Xlen) -> Option<RangeInclusive<i32>> {
+        let range = match self {
+            Type::IArith(IArithType::Slli | IArithType::Srli | IArithType::Srai) => match xlen {
+                Xlen::Rv32 => 0..=31,
+                Xlen::Rv64 => 0..=63,
+            },
+            Type::IArith(IArithType::Slliw | IArithType::Srliw | IArithType::Sraiw)
+            | Type::CsrI(_) => 0..=31,
+            Type::IArith(_) | Type::Load(_) | Type::Store(_) => -2048..=2047,
+            Type::UpperArith(_) => 0..=0xf_ffff,
+            _ => return None,
+        };
+        Some(range)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -468,6 +1089,9 @@ pub enum PseudoType {
     Li,
     Mv,
     Neg,
+    Negw,
+    SextW,
+    ZextB,
     Nop,
     Not,
     Ret,
@@ -478,6 +1102,7 @@ pub enum PseudoType {
     Sgez,
     B,
     Call,
+    Tail,
     Bgt,
     Ble,
     Bgtu,
@@ -491,6 +1116,12 @@ pub enum PseudoType {
     Csrci,
     Csrsi,
     Csrwi,
+    FmvS,
+    FnegS,
+    FabsS,
+    FmvD,
+    FnegD,
+    FabsD,
 }
 
 impl FromStr for Inst {
@@ -516,6 +1147,7 @@ impl FromStr for Inst {
             "srl" => Ok(Inst::Srl),
             "srlw" => Ok(Inst::Srlw),
             "sub" => Ok(Inst::Sub),
+            "subw" => Ok(Inst::Subw),
             "xor" => Ok(Inst::Xor),
             "mul" => Ok(Inst::Mul),
             "mulh" => Ok(Inst::Mulh),
@@ -554,9 +1186,11 @@ impl FromStr for Inst {
             "lhu" => Ok(Inst::Lhu),
             "lw" => Ok(Inst::Lw),
             "lwu" => Ok(Inst::Lwu),
+            "ld" => Ok(Inst::Ld),
             "sb" => Ok(Inst::Sb),
             "sh" => Ok(Inst::Sh),
             "sw" => Ok(Inst::Sw),
+            "sd" => Ok(Inst::Sd),
             "csrrw" => Ok(Inst::Csrrw),
             "csrrs" => Ok(Inst::Csrrs),
             "csrrc" => Ok(Inst::Csrrc),
@@ -576,6 +1210,9 @@ impl FromStr for Inst {
             "li" => Ok(Inst::Li),
             "mv" => Ok(Inst::Mv),
             "neg" => Ok(Inst::Neg),
+            "negw" => Ok(Inst::Negw),
+            "sext.w" => Ok(Inst::SextW),
+            "zext.b" => Ok(Inst::ZextB),
             "not" => Ok(Inst::Not),
             "seqz" => Ok(Inst::Seqz),
             "snez" => Ok(Inst::Snez),
@@ -585,6 +1222,7 @@ impl FromStr for Inst {
             "bltz" => Ok(Inst::Bltz),
             "bgez" => Ok(Inst::Bgez),
             "call" => Ok(Inst::Call),
+            "tail" => Ok(Inst::Tail),
             "bgt" => Ok(Inst::Bgt),
             "ble" => Ok(Inst::Ble),
             "bgtu" => Ok(Inst::Bgtu),
@@ -600,6 +1238,74 @@ impl FromStr for Inst {
             "csrsi" => Ok(Inst::Csrsi),
             "csrwi" => Ok(Inst::Csrwi),
             "uret" => Ok(Inst::Uret),
+            "flw" => Ok(Inst::Flw),
+            "fld" => Ok(Inst::Fld),
+            "fsw" => Ok(Inst::Fsw),
+            "fsd" => Ok(Inst::Fsd),
+            "fadd.s" => Ok(Inst::FaddS),
+            "fsub.s" => Ok(Inst::FsubS),
+            "fmul.s" => Ok(Inst::FmulS),
+            "fdiv.s" => Ok(Inst::FdivS),
+            "fsqrt.s" => Ok(Inst::FsqrtS),
+            "fmin.s" => Ok(Inst::FminS),
+            "fmax.s" => Ok(Inst::FmaxS),
+            "fsgnj.s" => Ok(Inst::FsgnjS),
+            "fsgnjn.s" => Ok(Inst::FsgnjnS),
+            "fsgnjx.s" => Ok(Inst::FsgnjxS),
+            "fmadd.s" => Ok(Inst::FmaddS),
+            "fmsub.s" => Ok(Inst::FmsubS),
+            "fnmadd.s" => Ok(Inst::FnmaddS),
+            "fnmsub.s" => Ok(Inst::FnmsubS),
+            "feq.s" => Ok(Inst::FeqS),
+            "flt.s" => Ok(Inst::FltS),
+            "fle.s" => Ok(Inst::FleS),
+            "fclass.s" => Ok(Inst::FclassS),
+            "fcvt.w.s" => Ok(Inst::FcvtWS),
+            "fcvt.s.w" => Ok(Inst::FcvtSW),
+            "fcvt.wu.s" => Ok(Inst::FcvtWuS),
+            "fcvt.s.wu" => Ok(Inst::FcvtSWu),
+            "fcvt.l.s" => Ok(Inst::FcvtLS),
+            "fcvt.s.l" => Ok(Inst::FcvtSL),
+            "fcvt.lu.s" => Ok(Inst::FcvtLuS),
+            "fcvt.s.lu" => Ok(Inst::FcvtSLu),
+            "fadd.d" => Ok(Inst::FaddD),
+            "fsub.d" => Ok(Inst::FsubD),
+            "fmul.d" => Ok(Inst::FmulD),
+            "fdiv.d" => Ok(Inst::FdivD),
+            "fsqrt.d" => Ok(Inst::FsqrtD),
+            "fmin.d" => Ok(Inst::FminD),
+            "fmax.d" => Ok(Inst::FmaxD),
+            "fsgnj.d" => Ok(Inst::FsgnjD),
+            "fsgnjn.d" => Ok(Inst::FsgnjnD),
+            "fsgnjx.d" => Ok(Inst::FsgnjxD),
+            "fmadd.d" => Ok(Inst::FmaddD),
+            "fmsub.d" => Ok(Inst::FmsubD),
+            "fnmadd.d" => Ok(Inst::FnmaddD),
+            "fnmsub.d" => Ok(Inst::FnmsubD),
+            "feq.d" => Ok(Inst::FeqD),
+            "flt.d" => Ok(Inst::FltD),
+            "fle.d" => Ok(Inst::FleD),
+            "fclass.d" => Ok(Inst::FclassD),
+            "fcvt.w.d" => Ok(Inst::FcvtWD),
+            "fcvt.d.w" => Ok(Inst::FcvtDW),
+            "fcvt.wu.d" => Ok(Inst::FcvtWuD),
+            "fcvt.d.wu" => Ok(Inst::FcvtDWu),
+            "fcvt.l.d" => Ok(Inst::FcvtLD),
+            "fcvt.d.l" => Ok(Inst::FcvtDL),
+            "fcvt.lu.d" => Ok(Inst::FcvtLuD),
+            "fcvt.d.lu" => Ok(Inst::FcvtDLu),
+            "fcvt.s.d" => Ok(Inst::FcvtSD),
+            "fcvt.d.s" => Ok(Inst::FcvtDS),
+            "fmv.x.w" | "fmv.x.s" => Ok(Inst::FmvXW),
+            "fmv.w.x" | "fmv.s.x" => Ok(Inst::FmvWX),
+            "fmv.x.d" => Ok(Inst::FmvXD),
+            "fmv.d.x" => Ok(Inst::FmvDX),
+            "fmv.s" => Ok(Inst::FmvS),
+            "fneg.s" => Ok(Inst::FnegS),
+            "fabs.s" => Ok(Inst::FabsS),
+            "fmv.d" => Ok(Inst::FmvD),
+            "fneg.d" => Ok(Inst::FnegD),
+            "fabs.d" => Ok(Inst::FabsD),
             _ => Err(()),
         }
     }
@@ -622,6 +1328,7 @@ impl From<&Inst> for Type {
             Inst::Srl => Type::Arith(ArithType::Srl),
             Inst::Srlw => Type::Arith(ArithType::Srlw),
             Inst::Sub => Type::Arith(ArithType::Sub),
+            Inst::Subw => Type::Arith(ArithType::Subw),
             Inst::Xor => Type::Arith(ArithType::Xor),
             Inst::Mul => Type::Arith(ArithType::Mul),
             Inst::Mulh => Type::Arith(ArithType::Mulh),
@@ -653,16 +1360,18 @@ impl From<&Inst> for Type {
             Inst::Srli => Type::IArith(IArithType::Srli),
             Inst::Srliw => Type::IArith(IArithType::Srliw),
             Inst::Xori => Type::IArith(IArithType::Xori),
-            Inst::Lui => Type::UpperArith(IArithType::Lui),
+            Inst::Lui => Type::UpperArith(UpperArithType::Lui),
             Inst::Lb => Type::Load(LoadType::Lb),
             Inst::Lbu => Type::Load(LoadType::Lbu),
             Inst::Lh => Type::Load(LoadType::Lh),
             Inst::Lhu => Type::Load(LoadType::Lhu),
             Inst::Lw => Type::Load(LoadType::Lw),
             Inst::Lwu => Type::Load(LoadType::Lwu),
+            Inst::Ld => Type::Load(LoadType::Ld),
             Inst::Sb => Type::Store(StoreType::Sb),
             Inst::Sh => Type::Store(StoreType::Sh),
             Inst::Sw => Type::Store(StoreType::Sw),
+            Inst::Sd => Type::Store(StoreType::Sd),
             Inst::Fence => Type::Ignore(IgnoreType::Fence),
             Inst::Fencei => Type::Ignore(IgnoreType::Fencei),
             Inst::Jal => Type::JumpLink(JumpLinkType::Jal),
@@ -677,7 +1386,7 @@ impl From<&Inst> for Type {
             Inst::Csrrsi => Type::CsrI(CSRIType::Csrrsi),
             Inst::Csrrci => Type::CsrI(CSRIType::Csrrci),
             Inst::Nop => Type::Pseudo(PseudoType::Nop),
-            Inst::Auipc => Type::IArith(IArithType::Auipc),
+            Inst::Auipc => Type::UpperArith(UpperArithType::Auipc),
             Inst::Beqz => Type::Pseudo(PseudoType::Beqz),
             Inst::Bnez => Type::Pseudo(PseudoType::Bnez),
             Inst::J => Type::Pseudo(PseudoType::J),
@@ -686,6 +1395,9 @@ impl From<&Inst> for Type {
             Inst::La => Type::Pseudo(PseudoType::La),
             Inst::Mv => Type::Pseudo(PseudoType::Mv),
             Inst::Neg => Type::Pseudo(PseudoType::Neg),
+            Inst::Negw => Type::Pseudo(PseudoType::Negw),
+            Inst::SextW => Type::Pseudo(PseudoType::SextW),
+            Inst::ZextB => Type::Pseudo(PseudoType::ZextB),
             Inst::Not => Type::Pseudo(PseudoType::Not),
             Inst::Seqz => Type::Pseudo(PseudoType::Seqz),
             Inst::Snez => Type::Pseudo(PseudoType::Snez),
@@ -698,6 +1410,7 @@ impl From<&Inst> for Type {
             Inst::Bgtz => Type::Pseudo(PseudoType::Bgtz),
             Inst::Blez => Type::Pseudo(PseudoType::Blez),
             Inst::Call => Type::Pseudo(PseudoType::Call),
+            Inst::Tail => Type::Pseudo(PseudoType::Tail),
             Inst::Bgt => Type::Pseudo(PseudoType::Bgt),
             Inst::Ble => Type::Pseudo(PseudoType::Ble),
             Inst::Bgtu => Type::Pseudo(PseudoType::Bgtu),
@@ -710,6 +1423,74 @@ impl From<&Inst> for Type {
             Inst::Csrsi => Type::Pseudo(PseudoType::Csrsi),
             Inst::Csrwi => Type::Pseudo(PseudoType::Csrwi),
             Inst::Uret => Type::Basic(BasicType::Uret),
+            Inst::Flw => Type::Load(LoadType::Flw),
+            Inst::Fld => Type::Load(LoadType::Fld),
+            Inst::Fsw => Type::Store(StoreType::Fsw),
+            Inst::Fsd => Type::Store(StoreType::Fsd),
+            Inst::FaddS => Type::FloatArith(FloatArithType::FaddS),
+            Inst::FsubS => Type::FloatArith(FloatArithType::FsubS),
+            Inst::FmulS => Type::FloatArith(FloatArithType::FmulS),
+            Inst::FdivS => Type::FloatArith(FloatArithType::FdivS),
+            Inst::FsqrtS => Type::FloatArith(FloatArithType::FsqrtS),
+            Inst::FminS => Type::FloatArith(FloatArithType::FminS),
+            Inst::FmaxS => Type::FloatArith(FloatArithType::FmaxS),
+            Inst::FsgnjS => Type::FloatArith(FloatArithType::FsgnjS),
+            Inst::FsgnjnS => Type::FloatArith(FloatArithType::FsgnjnS),
+            Inst::FsgnjxS => Type::FloatArith(FloatArithType::FsgnjxS),
+            Inst::FmaddS => Type::FloatArith(FloatArithType::FmaddS),
+            Inst::FmsubS => Type::FloatArith(FloatArithType::FmsubS),
+            Inst::FnmaddS => Type::FloatArith(FloatArithType::FnmaddS),
+            Inst::FnmsubS => Type::FloatArith(FloatArithType::FnmsubS),
+            Inst::FeqS => Type::FloatArith(FloatArithType::FeqS),
+            Inst::FltS => Type::FloatArith(FloatArithType::FltS),
+            Inst::FleS => Type::FloatArith(FloatArithType::FleS),
+            Inst::FclassS => Type::FloatArith(FloatArithType::FclassS),
+            Inst::FcvtWS => Type::FloatArith(FloatArithType::FcvtWS),
+            Inst::FcvtSW => Type::FloatArith(FloatArithType::FcvtSW),
+            Inst::FcvtWuS => Type::FloatArith(FloatArithType::FcvtWuS),
+            Inst::FcvtSWu => Type::FloatArith(FloatArithType::FcvtSWu),
+            Inst::FcvtLS => Type::FloatArith(FloatArithType::FcvtLS),
+            Inst::FcvtSL => Type::FloatArith(FloatArithType::FcvtSL),
+            Inst::FcvtLuS => Type::FloatArith(FloatArithType::FcvtLuS),
+            Inst::FcvtSLu => Type::FloatArith(FloatArithType::FcvtSLu),
+            Inst::FaddD => Type::FloatArith(FloatArithType::FaddD),
+            Inst::FsubD => Type::FloatArith(FloatArithType::FsubD),
+            Inst::FmulD => Type::FloatArith(FloatArithType::FmulD),
+            Inst::FdivD => Type::FloatArith(FloatArithType::FdivD),
+            Inst::FsqrtD => Type::FloatArith(FloatArithType::FsqrtD),
+            Inst::FminD => Type::FloatArith(FloatArithType::FminD),
+            Inst::FmaxD => Type::FloatArith(FloatArithType::FmaxD),
+            Inst::FsgnjD => Type::FloatArith(FloatArithType::FsgnjD),
+            Inst::FsgnjnD => Type::FloatArith(FloatArithType::FsgnjnD),
+            Inst::FsgnjxD => Type::FloatArith(FloatArithType::FsgnjxD),
+            Inst::FmaddD => Type::FloatArith(FloatArithType::FmaddD),
+            Inst::FmsubD => Type::FloatArith(FloatArithType::FmsubD),
+            Inst::FnmaddD => Type::FloatArith(FloatArithType::FnmaddD),
+            Inst::FnmsubD => Type::FloatArith(FloatArithType::FnmsubD),
+            Inst::FeqD => Type::FloatArith(FloatArithType::FeqD),
+            Inst::FltD => Type::FloatArith(FloatArithType::FltD),
+            Inst::FleD => Type::FloatArith(FloatArithType::FleD),
+            Inst::FclassD => Type::FloatArith(FloatArithType::FclassD),
+            Inst::FcvtWD => Type::FloatArith(FloatArithType::FcvtWD),
+            Inst::FcvtDW => Type::FloatArith(FloatArithType::FcvtDW),
+            Inst::FcvtWuD => Type::FloatArith(FloatArithType::FcvtWuD),
+            Inst::FcvtDWu => Type::FloatArith(FloatArithType::FcvtDWu),
+            Inst::FcvtLD => Type::FloatArith(FloatArithType::FcvtLD),
+            Inst::FcvtDL => Type::FloatArith(FloatArithType::FcvtDL),
+            Inst::FcvtLuD => Type::FloatArith(FloatArithType::FcvtLuD),
+            Inst::FcvtDLu => Type::FloatArith(FloatArithType::FcvtDLu),
+            Inst::FcvtSD => Type::FloatArith(FloatArithType::FcvtSD),
+            Inst::FcvtDS => Type::FloatArith(FloatArithType::FcvtDS),
+            Inst::FmvXW => Type::FloatArith(FloatArithType::FmvXW),
+            Inst::FmvWX => Type::FloatArith(FloatArithType::FmvWX),
+            Inst::FmvXD => Type::FloatArith(FloatArithType::FmvXD),
+            Inst::FmvDX => Type::FloatArith(FloatArithType::FmvDX),
+            Inst::FmvS => Type::Pseudo(PseudoType::FmvS),
+            Inst::FnegS => Type::Pseudo(PseudoType::FnegS),
+            Inst::FabsS => Type::Pseudo(PseudoType::FabsS),
+            Inst::FmvD => Type::Pseudo(PseudoType::FmvD),
+            Inst::FnegD => Type::Pseudo(PseudoType::FnegD),
+            Inst::FabsD => Type::Pseudo(PseudoType::FabsD),
         }
     }
 }
@@ -720,6 +1501,7 @@ impl From<&ArithType> for Inst {
             ArithType::Add => Inst::Add,
             ArithType::Addw => Inst::Addw,
             ArithType::Sub => Inst::Sub,
+            ArithType::Subw => Inst::Subw,
             ArithType::Mul => Inst::Mul,
             ArithType::Div => Inst::Div,
             ArithType::Divu => Inst::Divu,
@@ -762,8 +1544,15 @@ impl From<&IArithType> for Inst {
             IArithType::Slli => Inst::Slli,
             IArithType::Srli => Inst::Srli,
             IArithType::Srai => Inst::Srai,
-            IArithType::Auipc => Inst::Auipc,
-            IArithType::Lui => Inst::Lui,
+        }
+    }
+}
+
+impl From<&UpperArithType> for Inst {
+    fn from(value: &UpperArithType) -> Self {
+        match value {
+            UpperArithType::Lui => Inst::Lui,
+            UpperArithType::Auipc => Inst::Auipc,
         }
     }
 }
@@ -777,6 +1566,9 @@ impl From<&LoadType> for Inst {
             LoadType::Lhu => Inst::Lhu,
             LoadType::Lw => Inst::Lw,
             LoadType::Lwu => Inst::Lwu,
+            LoadType::Ld => Inst::Ld,
+            LoadType::Flw => Inst::Flw,
+            LoadType::Fld => Inst::Fld,
         }
     }
 }
@@ -787,10 +1579,85 @@ impl From<&StoreType> for Inst {
             StoreType::Sb => Inst::Sb,
             StoreType::Sh => Inst::Sh,
             StoreType::Sw => Inst::Sw,
+            StoreType::Sd => Inst::Sd,
+            StoreType::Fsw => Inst::Fsw,
+            StoreType::Fsd => Inst::Fsd,
+        }
+    }
+}
+
+impl From<&FloatArithType> for Inst {
+    #[allow(clippy::too_many_lines)]
+    fn from(value: &FloatArithType) -> Self {
+        match value {
+            FloatArithType::FaddS => Inst::FaddS,
+            FloatArithType::FsubS => Inst::FsubS,
+            FloatArithType::FmulS => Inst::FmulS,
+            FloatArithType::FdivS => Inst::FdivS,
+            FloatArithType::FsqrtS => Inst::FsqrtS,
+            FloatArithType::FminS => Inst::FminS,
+            FloatArithType::FmaxS => Inst::FmaxS,
+            FloatArithType::FsgnjS => Inst::FsgnjS,
+            FloatArithType::FsgnjnS => Inst::FsgnjnS,
+            FloatArithType::FsgnjxS => Inst::FsgnjxS,
+            FloatArithType::FmaddS => Inst::FmaddS,
+            FloatArithType::FmsubS => Inst::FmsubS,
+            FloatArithType::FnmaddS => Inst::FnmaddS,
+            FloatArithType::FnmsubS => Inst::FnmsubS,
+            FloatArithType::FeqS => Inst::FeqS,
+            FloatArithType::FltS => Inst::FltS,
+            FloatArithType::FleS => Inst::FleS,
+            FloatArithType::FclassS => Inst::FclassS,
+            FloatArithType::FcvtWS => Inst::FcvtWS,
+            FloatArithType::FcvtSW => Inst::FcvtSW,
+            FloatArithType::FcvtWuS => Inst::FcvtWuS,
+            FloatArithType::FcvtSWu => Inst::FcvtSWu,
+            FloatArithType::FcvtLS => Inst::FcvtLS,
+            FloatArithType::FcvtSL => Inst::FcvtSL,
+            FloatArithType::FcvtLuS => Inst::FcvtLuS,
+            FloatArithType::FcvtSLu => Inst::FcvtSLu,
+            FloatArithType::FaddD => Inst::FaddD,
+            FloatArithType::FsubD => Inst::FsubD,
+            FloatArithType::FmulD => Inst::FmulD,
+            FloatArithType::FdivD => Inst::FdivD,
+            FloatArithType::FsqrtD => Inst::FsqrtD,
+            FloatArithType::FminD => Inst::FminD,
+            FloatArithType::FmaxD => Inst::FmaxD,
+            FloatArithType::FsgnjD => Inst::FsgnjD,
+            FloatArithType::FsgnjnD => Inst::FsgnjnD,
+            FloatArithType::FsgnjxD => Inst::FsgnjxD,
+            FloatArithType::FmaddD => Inst::FmaddD,
+            FloatArithType::FmsubD => Inst::FmsubD,
+            FloatArithType::FnmaddD => Inst::FnmaddD,
+            FloatArithType::FnmsubD => Inst::FnmsubD,
+            FloatArithType::FeqD => Inst::FeqD,
+            FloatArithType::FltD => Inst::FltD,
+            FloatArithType::FleD => Inst::FleD,
+            FloatArithType::FclassD => Inst::FclassD,
+            FloatArithType::FcvtWD => Inst::FcvtWD,
+            FloatArithType::FcvtDW => Inst::FcvtDW,
+            FloatArithType::FcvtWuD => Inst::FcvtWuD,
+            FloatArithType::FcvtDWu => Inst::FcvtDWu,
+            FloatArithType::FcvtLD => Inst::FcvtLD,
+            FloatArithType::FcvtDL => Inst::FcvtDL,
+            FloatArithType::FcvtLuD => Inst::FcvtLuD,
+            FloatArithType::FcvtDLu => Inst::FcvtDLu,
+            FloatArithType::FcvtSD => Inst::FcvtSD,
+            FloatArithType::FcvtDS => Inst::FcvtDS,
+            FloatArithType::FmvXW => Inst::FmvXW,
+            FloatArithType::FmvWX => Inst::FmvWX,
+            FloatArithType::FmvXD => Inst::FmvXD,
+            FloatArithType::FmvDX => Inst::FmvDX,
         }
     }
 }
 
+impl Display for FloatArithType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Inst::from(self))
+    }
+}
+
 impl From<&JumpLinkType> for Inst {
     fn from(value: &JumpLinkType) -> Self {
         match value {
@@ -45,6 +45,73 @@ impl FromStr for LabelString {
     }
 }
 
+/// A reference to a numeric local label, like `1b` or `1f`.
+///
+/// Numeric local labels, like `1:`, can be defined many times. A reference
+/// points to the closest definition before it (`b`) or after it (`f`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalLabelRef {
+    Backward(u32),
+    Forward(u32),
+}
+
+impl LocalLabelRef {
+    /// The number of the local label that is referred to.
+    #[must_use]
+    pub fn number(self) -> u32 {
+        match self {
+            LocalLabelRef::Backward(n) | LocalLabelRef::Forward(n) => n,
+        }
+    }
+}
+
+impl Display for LocalLabelRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalLabelRef::Backward(n) => write!(f, "{n}b"),
+            LocalLabelRef::Forward(n) => write!(f, "{n}f"),
+        }
+    }
+}
+
+impl LabelString {
+    /// Create the label of a numeric local label definition, like `1:`.
+    ///
+    /// The label is renamed to a unique name once all of the definitions
+    /// are known.
+    #[must_use]
+    pub fn local_definition(s: &str) -> Option<Self> {
+        (!s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+            .then(|| LabelString(s.to_string()))
+    }
+
+    /// Get the number of a numeric local label definition.
+    #[must_use]
+    pub fn local_number(&self) -> Option<u32> {
+        if self.0.chars().all(|c| c.is_ascii_digit()) {
+            self.0.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Get the numeric local label that this label refers to, if it is a
+    /// reference like `1b` or `1f`.
+    #[must_use]
+    pub fn local_reference(&self) -> Option<LocalLabelRef> {
+        let (number, direction) = self.0.split_at(self.0.len().checked_sub(1)?);
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let number = number.parse().ok()?;
+        match direction {
+            "b" | "B" => Some(LocalLabelRef::Backward(number)),
+            "f" | "F" => Some(LocalLabelRef::Forward(number)),
+            _ => None,
+        }
+    }
+}
+
 impl Display for LabelString {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
@@ -56,7 +123,11 @@ impl TryFrom<Info> for LabelString {
 
     fn try_from(value: Info) -> Result<Self, Self::Error> {
         match value.token {
-            Token::Symbol(s) => LabelString::try_from(s),
+            Token::Symbol(s) => LabelString::from_str(&s).or_else(|()| {
+                // Local label references, like 1b, start with a digit
+                let label = LabelString(s);
+                label.local_reference().map(|_| label).ok_or(())
+            }),
             _ => Err(()),
         }
     }
@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use uuid::Uuid;
 
 use crate::parser::token::Token;
@@ -32,6 +34,7 @@ impl StringLexError {
 ///
 /// The lexer implements the Iterator trait, so it can be used in a for loop for
 /// getting the next token.
+#[derive(Clone)]
 pub struct Lexer {
     pub source_id: Uuid,
     /// Raw source, don't read from this directly
@@ -42,17 +45,43 @@ pub struct Lexer {
     row: usize,
     /// The column that will be read next
     col: usize,
+    /// The number of columns a tab advances to, as set by the editor
+    tab_width: usize,
+    /// Tokens that are read before the source, like a repeated block
+    replay: VecDeque<Result<Info, LexError>>,
 }
 
 impl Lexer {
     /// Create a new lexer from a string.
     pub fn new<S: Into<String>>(source: S, id: Uuid) -> Lexer {
+        Self::new_with_tab_width(source, id, 1)
+    }
+
+    /// Create a new lexer from a string, where a tab advances to the next
+    /// multiple of `tab_width` columns.
+    ///
+    /// Editors show tabs as several columns wide, so positions only line
+    /// up with what the user sees when the same width is used here.
+    pub fn new_with_tab_width<S: Into<String>>(source: S, id: Uuid, tab_width: usize) -> Lexer {
         Lexer {
             source: source.into().chars().collect(),
             source_id: id,
             pos: 0,
             row: 0,
-            col: 0,
+            col: 1,
+            tab_width: tab_width.max(1),
+            replay: VecDeque::new(),
+        }
+    }
+
+    /// Create a lexer that reads tokens that were already lexed.
+    ///
+    /// The tokens keep their positions, so nodes parsed from them point to
+    /// where the tokens were first read.
+    pub(crate) fn from_tokens(tokens: Vec<Result<Info, LexError>>, id: Uuid) -> Lexer {
+        Lexer {
+            replay: tokens.into(),
+            ..Self::new("", id)
         }
     }
 
@@ -77,6 +106,10 @@ impl Lexer {
             if ch == '\n' {
                 self.row += 1;
                 self.col = 0;
+            } else if self.current() == Some('\t') {
+                // Move to the next tab stop
+                let column = self.col.saturating_sub(1);
+                self.col = (column / self.tab_width + 1) * self.tab_width + 1;
             } else {
                 self.col += 1;
             }
@@ -119,6 +152,23 @@ impl Lexer {
         Self::is_symbol_char(ch) || ch.is_ascii_digit()
     }
 
+    /// Check if the given character can continue a symbol.
+    ///
+    /// A dot cannot start a symbol, as it starts a directive, but it can be
+    /// used inside one. This allows for mnemonics like `sext.w`.
+    fn is_symbol_continue(ch: char) -> bool {
+        Self::is_symbol_item(ch) || ch == '.'
+    }
+
+    /// Check if the given character is the sign of an exponent in a
+    /// floating-point number, like the `+` in `1.5e+3`.
+    fn is_exponent_sign(symbol: &str, ch: char) -> bool {
+        ch == '+'
+            && (symbol.ends_with('e') || symbol.ends_with('E'))
+            && symbol.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+            && !symbol.to_lowercase().starts_with("0x")
+    }
+
     /// Skip whitespace.
     ///
     /// This function will skip all whitespace characters, excluding newlines.
@@ -275,6 +325,9 @@ impl Iterator for Lexer {
 
     #[allow(clippy::too_many_lines)]
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.replay.pop_front() {
+            return Some(token);
+        }
         self.skip_ws();
 
         // TODO(rajan): ensure that we are consistent with whether the tokens are included or not in the Token representation
@@ -313,7 +366,8 @@ impl Iterator for Lexer {
                     pos,
                 })
             }
-            Some('.') => {
+            // A dot followed by a digit is a number, like `.5`
+            Some('.') if !self.peek(1).is_some_and(|c| c.is_ascii_digit()) => {
                 // directive
                 let start = self.get_pos();
                 let mut dir_str: String = String::new();
@@ -341,6 +395,35 @@ impl Iterator for Lexer {
                     file: self.source_id,
                 })
             }
+            Some('%') => {
+                // relocation, like `%hi(symbol)`, which is kept as one symbol
+                let start = self.get_pos();
+                let mut reloc_str: String = String::new();
+
+                while let Some(current) = self.current() {
+                    reloc_str.push(current);
+                    match self.peek(1) {
+                        Some(next)
+                            if current != ')'
+                                && (Self::is_symbol_continue(next)
+                                    || next == '('
+                                    || next == ')') =>
+                        {
+                            self.consume_char();
+                        }
+                        _ => break,
+                    }
+                }
+
+                let end = self.get_pos();
+                self.consume_char();
+
+                Some(Info {
+                    token: Token::Symbol(reloc_str),
+                    pos: Range { start, end },
+                    file: self.source_id,
+                })
+            }
             Some('#') => {
                 // Convert comments to token
                 let start = self.get_pos();
@@ -390,7 +473,6 @@ impl Iterator for Lexer {
 
                 let end = self.get_pos();
                 self.consume_char();   // Skip final '"'
-                self.consume_char();
 
                 Some(Info {
                     token: Token::String(string_str.clone()),
@@ -463,9 +545,10 @@ impl Iterator for Lexer {
                 let start = self.get_pos();
                 let mut symbol_str: String = String::new();
 
-                // If the first character is not a symbol char -> error
+                // If the first character is not a symbol char -> error.
+                // Section types, like `@progbits`, start with an `@`.
                 if let Some(current) = self.current() {
-                    if !Self::is_symbol_item(current) {
+                    if !Self::is_symbol_item(current) && current != '.' && current != '@' {
                         return None;
                     }
                 }
@@ -473,7 +556,9 @@ impl Iterator for Lexer {
                 while let Some(current) = self.current() {
                     symbol_str.push(current);
                     if let Some(next) = self.peek(1) {
-                        if !Self::is_symbol_item(next) {
+                        if !Self::is_symbol_continue(next)
+                            && !Self::is_exponent_sign(&symbol_str, next)
+                        {
                             break;
                         }
                     }
@@ -534,6 +619,24 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn lex_relocation() {
+        let tokens = tokenize("lui a0, %hi(msg)\naddi a0, a0, %pcrel_lo(1b)");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Symbol("lui".to_owned()),
+                Token::Symbol("a0".to_owned()),
+                Token::Symbol("%hi(msg)".to_owned()),
+                Token::Newline,
+                Token::Symbol("addi".to_owned()),
+                Token::Symbol("a0".to_owned()),
+                Token::Symbol("a0".to_owned()),
+                Token::Symbol("%pcrel_lo(1b)".to_owned()),
+            ]
+        );
+    }
+
     #[test]
     fn lex_label() {
         let tokens = tokenize("My_Label:");
@@ -556,9 +659,9 @@ mod tests {
         assert_eq!(
             tokens,
             vec![
-                Token::Comment("".to_owned()),
+                Token::Comment(String::new()),
                 Token::Newline,
-                Token::Comment("".to_owned()),
+                Token::Comment(String::new()),
                 Token::Newline,
                 Token::Comment(" new line comments  with lots of \t whitespace and other special .text characters is allowed  jal ra, x0   ".to_owned()),
                 Token::Newline,
@@ -578,9 +681,9 @@ mod tests {
             vec![
                 Token::Comment("this is a comment".to_owned()),
                 Token::Newline,
-                Token::Comment("".to_owned()),
+                Token::Comment(String::new()),
             ]
-        )
+        );
     }
 
     #[test]
@@ -603,6 +706,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lex_instruction_with_dot() {
+        let tokens = tokenize("sext.w a0, a1");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Symbol("sext.w".to_owned()),
+                Token::Symbol("a0".to_owned()),
+                Token::Symbol("a1".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_float_literals() {
+        let tokens = tokenize(".float 3.14, .5, -1.5e+3, 2E-2");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Directive(".float".to_owned()),
+                Token::Symbol("3.14".to_owned()),
+                Token::Symbol(".5".to_owned()),
+                Token::Symbol("-1.5e+3".to_owned()),
+                Token::Symbol("2E-2".to_owned()),
+            ]
+        );
+    }
+
     #[test]
     fn lex_ints() {
         let tokens = tokenize("0x1234,    0b1010, 1234  -222");
@@ -745,13 +876,94 @@ mod tests {
         assert_eq!(
             tokens,
             vec![
-                Token::String("".into()),
+                Token::String(String::new()),
                 Token::String("abcde".into()),
                 Token::String("\\'\"\n\t\r\u{8}\u{c}\0\u{03bb}".into()),
             ]
         );
     }
 
+    #[test]
+    fn section_type_is_symbol() {
+        let tokens = tokenize("\"ax\", @progbits\nret");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::String("ax".into()),
+                Token::Symbol("@progbits".into()),
+                Token::Newline,
+                Token::Symbol("ret".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn string_before_newline() {
+        let tokens = tokenize("\"ax\"\nret");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::String("ax".into()),
+                Token::Newline,
+                Token::Symbol("ret".into()),
+            ]
+        );
+    }
+
+    /// Get the line, start column and last column of each token.
+    fn columns(input: &str, tab_width: usize) -> Vec<(usize, usize, usize)> {
+        Lexer::new_with_tab_width(input, uuid::Uuid::nil(), tab_width)
+            .map(|x| x.unwrap())
+            .filter(|x| x.token != Token::Newline)
+            .map(|x| (x.pos.start.line, x.pos.start.column, x.pos.end.column))
+            .collect()
+    }
+
+    #[test]
+    fn tabs_advance_to_tab_stop() {
+        // `\t` moves to column 4 from anywhere before it, and a tab right at
+        // a tab stop moves a full tab width.
+        let input = "\tadd t0,\tt1\nab\tcd  ef\n    x\t\ty";
+        assert_eq!(
+            columns(input, 4),
+            vec![
+                (0, 4, 6),
+                (0, 8, 9),
+                (0, 12, 13),
+                (1, 0, 1),
+                (1, 4, 5),
+                (1, 8, 9),
+                (2, 4, 4),
+                (2, 12, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn tabs_default_to_one_column() {
+        let input = "\tadd t0,\tt1\nab\tcd";
+        assert_eq!(
+            columns(input, 1),
+            columns(input.replace('\t', " ").as_str(), 1)
+        );
+        assert_eq!(
+            Lexer::new(input, uuid::Uuid::nil())
+                .map(|x| x.unwrap().pos)
+                .collect::<Vec<_>>(),
+            Lexer::new_with_tab_width(input, uuid::Uuid::nil(), 1)
+                .map(|x| x.unwrap().pos)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn tab_inside_token() {
+        // A tab inside a string still moves the rest of the line to the
+        // next tab stop.
+        let input = "\"a\tb\" x\n";
+        assert_eq!(columns(input, 8), vec![(0, 0, 9), (0, 11, 11)]);
+    }
+
     #[test]
     fn unbounded_string() {
         let input = "\"Good string\" \"Bad string";
@@ -913,7 +1125,7 @@ mod tests {
         let input = "'\\a'";
         let tokens = tokenize_err(input);
 
-        println!("{:?}", tokens);
+        println!("{tokens:?}");
         assert_eq!(tokens.len(), 1);
 
         assert!(matches!(
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use super::{LabelString, LocalLabelRef, ParseError, ParserNode, With};
+
+/// Get the label that a node defines or refers to.
+fn label_mut(node: &mut ParserNode) -> Option<&mut With<LabelString>> {
+    match node {
+        ParserNode::Label(x) => Some(&mut x.name),
+        ParserNode::JumpLink(x) => Some(&mut x.name),
+        ParserNode::Branch(x) => Some(&mut x.name),
+        ParserNode::LoadAddr(x) => Some(&mut x.name),
+        _ => node.relocation_mut().map(|reloc| &mut reloc.symbol),
+    }
+}
+
+/// The unique name given to a definition of a numeric local label.
+///
+/// Other labels cannot start with a digit, so these names never clash with
+/// a label in the program.
+fn unique_name(number: u32, index: usize) -> LabelString {
+    LabelString(format!("{number}${index}"))
+}
+
+/// Resolve numeric local labels, like `1:`, `1b` and `1f`.
+///
+/// Each definition of a numeric local label is renamed to a unique label,
+/// and each reference is renamed to the definition it points to. `1b` points
+/// to the closest `1:` before it, and `1f` to the closest `1:` after it.
+///
+/// Returns an error for each reference that has no matching definition.
+pub(crate) fn resolve_local_labels(nodes: &mut [ParserNode]) -> Vec<ParseError> {
+    // The position of every definition of each local label, in order
+    let mut definitions: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (pos, node) in nodes.iter_mut().enumerate() {
+        if let ParserNode::Label(label) = node {
+            if let Some(number) = label.name.data.local_number() {
+                let defs = definitions.entry(number).or_default();
+                label.name.data = unique_name(number, defs.len());
+                defs.push(pos);
+            }
+        }
+    }
+
+    let mut errors = Vec::new();
+    for (pos, node) in nodes.iter_mut().enumerate() {
+        if matches!(node, ParserNode::Label(_)) {
+            continue;
+        }
+        let Some(name) = label_mut(node) else {
+            continue;
+        };
+        let Some(reference) = name.data.local_reference() else {
+            continue;
+        };
+        let defs = definitions
+            .get(&reference.number())
+            .map_or(&[][..], Vec::as_slice);
+        // The number of definitions before this reference
+        let before = defs.partition_point(|&def| def < pos);
+        let index = match reference {
+            LocalLabelRef::Backward(_) => before.checked_sub(1),
+            LocalLabelRef::Forward(_) => (before < defs.len()).then_some(before),
+        };
+        match index {
+            Some(index) => name.data = unique_name(reference.number(), index),
+            None => errors.push(ParseError::UndefinedLocalLabel(name.info(), reference)),
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{ParseError, ParserNode, RVStringParser};
+    use crate::passes::Manager;
+
+    fn target(node: &ParserNode) -> String {
+        match node {
+            ParserNode::Label(x) => x.name.data.0.clone(),
+            ParserNode::Branch(x) => x.name.data.0.clone(),
+            ParserNode::JumpLink(x) => x.name.data.0.clone(),
+            _ => panic!("node has no label"),
+        }
+    }
+
+    #[test]
+    fn loop_with_backward_reference() {
+        let input = "\
+            main:                      \n\
+                li     t0, 10          \n\
+            1:                         \n\
+                addi   t0, t0, -1      \n\
+                bnez   t0, 1b          \n\
+                addi   a7, zero, 10    \n\
+                ecall                  \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(target(&nodes[3]), target(&nodes[5]));
+
+        let cfg = Manager::gen_full_cfg(nodes).unwrap();
+        let branch = cfg
+            .iter()
+            .find(|x| matches!(x.node(), ParserNode::Branch(_)));
+        assert_eq!(branch.unwrap().nexts().len(), 2);
+    }
+
+    #[test]
+    fn redefined_label_uses_closest_definition() {
+        let input = "\
+            main:                      \n\
+            1:                         \n\
+                beqz   a0, 1f          \n\
+                j      1b              \n\
+            1:                         \n\
+                j      1b              \n";
+
+        let (nodes, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 0);
+        assert_ne!(target(&nodes[2]), target(&nodes[5]));
+        assert_eq!(target(&nodes[3]), target(&nodes[5]));
+        assert_eq!(target(&nodes[4]), target(&nodes[2]));
+        assert_eq!(target(&nodes[6]), target(&nodes[5]));
+    }
+
+    #[test]
+    fn undefined_local_label() {
+        let input = "\
+            main:                      \n\
+                j      1b              \n\
+            1:                         \n\
+                j      2f              \n";
+
+        let (_, errors) = RVStringParser::parse_from_text(input);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "Local label 1 is not defined before here"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "Local label 2 is not defined after here"
+        );
+        assert!(matches!(errors[0], ParseError::UndefinedLocalLabel(..)));
+    }
+}
@@ -30,6 +30,9 @@ pub use error::*;
 
 mod display;
 
+mod assembly;
+pub use assembly::*;
+
 mod data_eq_wrapper;
 pub use data_eq_wrapper::*;
 
@@ -46,3 +49,19 @@ mod rv_string_parser;
 pub use rv_string_parser::*;
 
 mod comments;
+pub use comments::*;
+
+mod xlen;
+pub use xlen::*;
+
+mod align;
+pub use align::*;
+
+mod local_label;
+pub(crate) use local_label::*;
+
+mod relocation;
+pub use relocation::*;
+
+mod constant;
+pub(crate) use constant::*;
@@ -1,8 +1,8 @@
 use crate::parser::imm::{CSRImm, Imm};
 use crate::parser::inst::Inst;
 use crate::parser::inst::{
-    ArithType, BasicType, BranchType, CSRIType, CSRType, IArithType, JumpLinkRType, JumpLinkType,
-    LoadType, PseudoType, StoreType,
+    ArithType, BasicType, BranchType, CSRIType, CSRType, FloatArithType, IArithType,
+    JumpLinkRType, JumpLinkType, LoadType, PseudoType, StoreType, UpperArithType,
 };
 
 use crate::parser::register::Register;
@@ -11,13 +11,14 @@ use crate::parser::token::With;
 use std::collections::HashSet;
 
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::{
-    Arith, Basic, Branch, Csr, CsrI, Directive, DirectiveToken, DirectiveType, FuncEntry, IArith,
-    JumpLink, JumpLinkR, Label, LabelString, Load, LoadAddr, ProgramEntry, RawToken, Store,
+    Arith, Basic, Branch, Csr, CsrI, Directive, DirectiveToken, DirectiveType, FloatArith,
+    FuncEntry, IArith, JumpLink, JumpLinkR, Label, LabelString, Load, LoadAddr, ProgramEntry, RawToken, Relocation, Store, Token, UpperArith,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +27,7 @@ pub enum ParserNode {
     FuncEntry(FuncEntry),
     Arith(Arith),
     IArith(IArith),
+    UpperArith(UpperArith),
     Label(Label),
     JumpLink(JumpLink),
     JumpLinkR(JumpLinkR),
@@ -37,6 +39,7 @@ pub enum ParserNode {
     LoadAddr(LoadAddr), // Load address
     Csr(Csr),
     CsrI(CsrI),
+    FloatArith(FloatArith),
 }
 
 impl ParserNode {
@@ -45,6 +48,7 @@ impl ParserNode {
         match self {
             ParserNode::Arith(x) => x.token.clone(),
             ParserNode::IArith(x) => x.token.clone(),
+            ParserNode::UpperArith(x) => x.token.clone(),
             ParserNode::Label(x) => x.token.clone(),
             ParserNode::JumpLink(x) => x.token.clone(),
             ParserNode::JumpLinkR(x) => x.token.clone(),
@@ -54,6 +58,7 @@ impl ParserNode {
             ParserNode::Store(x) => x.token.clone(),
             ParserNode::Load(x) => x.token.clone(),
             ParserNode::Csr(x) => x.token.clone(),
+            ParserNode::FloatArith(x) => x.token.clone(),
             ParserNode::CsrI(x) => x.token.clone(),
             ParserNode::LoadAddr(x) => x.token.clone(),
             ParserNode::ProgramEntry(x) => x.token.clone(),
@@ -66,6 +71,7 @@ impl ParserNode {
         match self {
             ParserNode::Arith(a) => a.key,
             ParserNode::IArith(a) => a.key,
+            ParserNode::UpperArith(a) => a.key,
             ParserNode::Label(a) => a.key,
             ParserNode::JumpLink(a) => a.key,
             ParserNode::JumpLinkR(a) => a.key,
@@ -75,6 +81,7 @@ impl ParserNode {
             ParserNode::Store(a) => a.key,
             ParserNode::Load(a) => a.key,
             ParserNode::Csr(a) => a.key,
+            ParserNode::FloatArith(a) => a.key,
             ParserNode::CsrI(a) => a.key,
             ParserNode::LoadAddr(a) => a.key,
             ParserNode::FuncEntry(a) => a.key,
@@ -101,6 +108,7 @@ impl ParserNode {
         match self {
             ParserNode::Arith(x) => (&x.inst.data).into(),
             ParserNode::IArith(x) => (&x.inst.data).into(),
+            ParserNode::UpperArith(x) => (&x.inst.data).into(),
             ParserNode::JumpLink(x) => (&x.inst.data).into(),
             ParserNode::JumpLinkR(x) => (&x.inst.data).into(),
             ParserNode::Basic(x) => (&x.inst.data).into(),
@@ -108,6 +116,7 @@ impl ParserNode {
             ParserNode::Store(x) => (&x.inst.data).into(),
             ParserNode::Load(x) => (&x.inst.data).into(),
             ParserNode::Csr(x) => (&x.inst.data).into(),
+            ParserNode::FloatArith(x) => (&x.inst.data).into(),
             ParserNode::CsrI(x) => (&x.inst.data).into(),
             ParserNode::LoadAddr(_) => Inst::La,
             ParserNode::Label(_)
@@ -117,6 +126,37 @@ impl ParserNode {
         }
     }
 
+    /// Get the mnemonic that the instruction was written with.
+    ///
+    /// A pseudo-instruction like `negw` is stored as the instruction it
+    /// expands to, like `subw`, but keeps the mnemonic that the user wrote.
+    #[must_use]
+    pub fn mnemonic(&self) -> String {
+        let token = match self {
+            ParserNode::Arith(x) => &x.inst.token,
+            ParserNode::IArith(x) => &x.inst.token,
+            ParserNode::UpperArith(x) => &x.inst.token,
+            ParserNode::JumpLink(x) => &x.inst.token,
+            ParserNode::JumpLinkR(x) => &x.inst.token,
+            ParserNode::Basic(x) => &x.inst.token,
+            ParserNode::Branch(x) => return x.mnemonic(),
+            ParserNode::Store(x) => &x.inst.token,
+            ParserNode::Load(x) => &x.inst.token,
+            ParserNode::Csr(x) => &x.inst.token,
+            ParserNode::FloatArith(x) => &x.inst.token,
+            ParserNode::CsrI(x) => &x.inst.token,
+            ParserNode::LoadAddr(_)
+            | ParserNode::Label(_)
+            | ParserNode::Directive(_)
+            | ParserNode::FuncEntry(_)
+            | ParserNode::ProgramEntry(_) => return self.inst().to_string(),
+        };
+        match token {
+            Token::Symbol(name) => name.to_lowercase(),
+            _ => self.inst().to_string(),
+        }
+    }
+
     #[must_use]
     pub fn new_arith(
         inst: With<ArithType>,
@@ -135,6 +175,26 @@ impl ParserNode {
         })
     }
 
+    #[must_use]
+    pub fn new_float_arith(
+        inst: With<FloatArithType>,
+        rd: With<Register>,
+        rs1: With<Register>,
+        rs2: Option<With<Register>>,
+        rs3: Option<With<Register>>,
+        token: RawToken,
+    ) -> ParserNode {
+        ParserNode::FloatArith(FloatArith {
+            inst,
+            rd,
+            rs1,
+            rs2,
+            rs3,
+            key: Uuid::new_v4(),
+            token,
+        })
+    }
+
     #[must_use]
     pub fn new_iarith(
         inst: With<IArithType>,
@@ -148,6 +208,24 @@ impl ParserNode {
             rd,
             rs1,
             imm,
+            reloc: None,
+            key: Uuid::new_v4(),
+            token,
+        })
+    }
+
+    #[must_use]
+    pub fn new_upper_arith(
+        inst: With<UpperArithType>,
+        rd: With<Register>,
+        imm: With<Imm>,
+        token: RawToken,
+    ) -> ParserNode {
+        ParserNode::UpperArith(UpperArith {
+            inst,
+            rd,
+            imm,
+            reloc: None,
             key: Uuid::new_v4(),
             token,
         })
@@ -241,6 +319,7 @@ impl ParserNode {
             rs1,
             rs2,
             imm,
+            reloc: None,
             key: Uuid::new_v4(),
             token,
         })
@@ -259,6 +338,7 @@ impl ParserNode {
             rd,
             rs1,
             imm,
+            reloc: None,
             key: Uuid::new_v4(),
             token,
         })
@@ -324,6 +404,7 @@ impl ParserNode {
             name,
             key: Uuid::new_v4(),
             token,
+            annotations: Vec::new(),
         })
     }
 
@@ -356,6 +437,26 @@ impl ParserNode {
         }
     }
 
+    /// Checks if an instruction returns from a trap handler, like `uret`.
+    ///
+    /// Like a return, it does not fall through to the next instruction.
+    #[must_use]
+    pub fn is_trap_return(&self) -> bool {
+        matches!(self, ParserNode::Basic(x) if x.inst == BasicType::Uret)
+    }
+
+    /// Checks if a instruction jumps to an address in a register, other
+    /// than a return.
+    ///
+    /// This includes calls through a register, like `jalr t0`.
+    #[must_use]
+    pub fn is_indirect_jump(&self) -> bool {
+        match self {
+            ParserNode::JumpLinkR(x) => x.rs1 != Register::X1,
+            _ => false,
+        }
+    }
+
     /// Checks if a instruction is meant to be saved to zero
     ///
     /// Some instructions save to zero as part of their design. For example,
@@ -376,6 +477,32 @@ impl ParserNode {
         )
     }
 
+    /// Checks if a instruction is a no-op, written as `nop` or as
+    /// `addi x0, x0, 0`.
+    #[must_use]
+    pub fn is_nop(&self) -> bool {
+        matches!(
+            self,
+            ParserNode::IArith(x) if x.inst.data == IArithType::Addi
+                && x.rd.data == Register::X0
+                && x.rs1.data == Register::X0
+                && x.imm.data.0 == 0
+        )
+    }
+
+    /// Checks if a instruction adds the lower bits of a `li` whose immediate
+    /// does not fit in 12 bits. These are expanded to a `lui` of the upper
+    /// bits, followed by an `addi` of the lower bits into the same register.
+    #[must_use]
+    pub fn completes_load_immediate(&self) -> bool {
+        matches!(
+            self,
+            ParserNode::IArith(x) if x.inst.data == IArithType::Addi
+                && x.rs1.data != Register::X0
+                && matches!(&x.inst.token, Token::Symbol(name) if name.eq_ignore_ascii_case("li"))
+        )
+    }
+
     /// Checks if a instruction is a function call
     #[must_use]
     pub fn calls_to(&self) -> Option<With<LabelString>> {
@@ -385,6 +512,20 @@ impl ParserNode {
         }
     }
 
+    /// Checks if a instruction is a tail call, written with `tail`.
+    ///
+    /// A tail call is a jump to the start of another function, which then
+    /// returns to the caller of the current function.
+    #[must_use]
+    pub fn is_tail_call(&self) -> bool {
+        match self {
+            ParserNode::JumpLink(x) if x.rd == Register::X0 => {
+                matches!(&x.inst.token, Token::Symbol(s) if Inst::from_str(s) == Ok(Inst::Tail))
+            }
+            _ => false,
+        }
+    }
+
     /// Checks if a instruction is an environment call
     #[must_use]
     pub fn is_ecall(&self) -> bool {
@@ -408,10 +549,50 @@ impl ParserNode {
     pub fn reads_address_of(&self) -> Option<With<LabelString>> {
         match self {
             ParserNode::LoadAddr(x) => Some(x.name.clone()),
+            _ => self.relocation().map(|reloc| reloc.symbol.clone()),
+        }
+    }
+
+    /// Get the relocation that the immediate of an instruction was written
+    /// as, like `%lo(symbol)`.
+    #[must_use]
+    pub fn relocation(&self) -> Option<&Relocation> {
+        match self {
+            ParserNode::IArith(x) => x.reloc.as_ref(),
+            ParserNode::UpperArith(x) => x.reloc.as_ref(),
+            ParserNode::Load(x) => x.reloc.as_ref(),
+            ParserNode::Store(x) => x.reloc.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn relocation_mut(&mut self) -> Option<&mut Relocation> {
+        match self {
+            ParserNode::IArith(x) => x.reloc.as_mut(),
+            ParserNode::UpperArith(x) => x.reloc.as_mut(),
+            ParserNode::Load(x) => x.reloc.as_mut(),
+            ParserNode::Store(x) => x.reloc.as_mut(),
             _ => None,
         }
     }
 
+    /// Set the relocation that the immediate of an instruction was written
+    /// as.
+    ///
+    /// Only instructions with an immediate can have a relocation, so this
+    /// does nothing for other nodes.
+    #[must_use]
+    pub fn with_relocation(mut self, reloc: Option<Relocation>) -> ParserNode {
+        match &mut self {
+            ParserNode::IArith(x) => x.reloc = reloc,
+            ParserNode::UpperArith(x) => x.reloc = reloc,
+            ParserNode::Load(x) => x.reloc = reloc,
+            ParserNode::Store(x) => x.reloc = reloc,
+            _ => {}
+        }
+        self
+    }
+
     #[must_use]
     pub fn is_any_entry(&self) -> bool {
         matches!(self, ParserNode::ProgramEntry(_) | ParserNode::FuncEntry(_))
@@ -427,6 +608,19 @@ impl ParserNode {
         matches!(self, ParserNode::ProgramEntry(_))
     }
 
+    /// Get the immediate that is encoded in an instruction, if it has one.
+    #[must_use]
+    pub fn immediate(&self) -> Option<With<Imm>> {
+        match self {
+            ParserNode::IArith(x) => Some(x.imm.clone()),
+            ParserNode::UpperArith(x) => Some(x.imm.clone()),
+            ParserNode::Load(x) => Some(x.imm.clone()),
+            ParserNode::Store(x) => Some(x.imm.clone()),
+            ParserNode::CsrI(x) => Some(x.imm.clone()),
+            _ => None,
+        }
+    }
+
     /// Check if a node is an instruction.
     #[must_use]
     pub fn is_instruction(&self) -> bool {
@@ -434,6 +628,7 @@ impl ParserNode {
             self,
             ParserNode::Arith(_)
                 | ParserNode::IArith(_)
+                | ParserNode::UpperArith(_)
                 | ParserNode::JumpLink(_)
                 | ParserNode::JumpLinkR(_)
                 | ParserNode::Basic(_)
@@ -443,6 +638,7 @@ impl ParserNode {
                 | ParserNode::LoadAddr(_)
                 | ParserNode::Csr(_)
                 | ParserNode::CsrI(_)
+                | ParserNode::FloatArith(_)
         )
     }
 
@@ -462,13 +658,15 @@ impl ParserNode {
     /// Some jumps have side effects, like jumping to a function which sets
     /// the return address. This function checks if a jump is unconditional
     /// and has no side effects.
+    ///
+    /// A branch that compares a register with itself, like `beq t0, t0`, is
+    /// always taken if the comparison allows equal values.
     pub fn is_unconditional_jump(&self) -> bool {
         match self {
             ParserNode::JumpLink(x) if x.rd == Register::X0 => true,
             ParserNode::JumpLinkR(x) if x.rd == Register::X0 => true,
             ParserNode::Branch(x) => {
-                x.rs1 == Register::X0
-                    && x.rs2 == Register::X0
+                x.rs1 == x.rs2
                     && (x.inst == BranchType::Beq
                         || x.inst == BranchType::Bge
                         || x.inst == BranchType::Bgeu)
@@ -477,18 +675,43 @@ impl ParserNode {
         }
     }
 
-    // NOTE: This is in context to a register store, not a memory store
+    /// Checks whether a branch is never taken.
+    ///
+    /// A branch that compares a register with itself, like `bne t0, t0`, is
+    /// never taken if the comparison does not allow equal values.
     #[must_use]
-    pub fn stores_to(&self) -> Option<With<Register>> {
+    pub fn is_never_taken_branch(&self) -> bool {
         match self {
+            ParserNode::Branch(x) => {
+                x.rs1 == x.rs2
+                    && (x.inst == BranchType::Bne
+                        || x.inst == BranchType::Blt
+                        || x.inst == BranchType::Bltu)
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the registers that this instruction writes to, as operands.
+    ///
+    /// These are the registers of the instruction as it is encoded, so a
+    /// pseudo-instruction has the registers of the instruction that it
+    /// expands to, including the zero register. For example, `j label`
+    /// writes to `zero`. Registers that are written implicitly, like the
+    /// ones that a call may change, are not included. See
+    /// [`ParserNode::defs`] for those.
+    pub fn write_registers(&self) -> impl Iterator<Item = With<Register>> {
+        let reg = match self {
             ParserNode::Load(load) => Some(load.rd.clone()),
             ParserNode::LoadAddr(load) => Some(load.rd.clone()),
             ParserNode::Arith(arith) => Some(arith.rd.clone()),
             ParserNode::IArith(iarith) => Some(iarith.rd.clone()),
+            ParserNode::UpperArith(upper) => Some(upper.rd.clone()),
             ParserNode::JumpLink(jump_link) => Some(jump_link.rd.clone()),
             ParserNode::JumpLinkR(jump_link_r) => Some(jump_link_r.rd.clone()),
             ParserNode::Csr(csr) => Some(csr.rd.clone()),
             ParserNode::CsrI(csri) => Some(csri.rd.clone()),
+            ParserNode::FloatArith(x) => Some(x.rd.clone()),
             ParserNode::ProgramEntry(_)
             | ParserNode::FuncEntry(_)
             | ParserNode::Label(_)
@@ -496,12 +719,20 @@ impl ParserNode {
             | ParserNode::Directive(_)
             | ParserNode::Branch(_)
             | ParserNode::Store(_) => None,
-        }
+        };
+        reg.into_iter()
     }
 
-    #[must_use]
-    pub fn reads_from(&self) -> HashSet<With<Register>> {
-        let vector = match self {
+    /// Get the registers that this instruction reads from, as operands, in
+    /// the order they are written.
+    ///
+    /// Like [`ParserNode::write_registers`], these are the registers of the
+    /// instruction as it is encoded. For example, `beqz a0, label` reads
+    /// `a0` and `zero`, and `li a0, 1` reads `zero`. Registers that are read
+    /// implicitly, like the arguments of a call, are not included. See
+    /// [`ParserNode::uses`] for those.
+    pub fn read_registers(&self) -> impl Iterator<Item = With<Register>> {
+        let regs = match self {
             ParserNode::Arith(x) => vec![x.rs1.clone(), x.rs2.clone()],
             ParserNode::IArith(x) => vec![x.rs1.clone()],
             ParserNode::JumpLinkR(x) => vec![x.rs1.clone()],
@@ -509,6 +740,10 @@ impl ParserNode {
             ParserNode::Store(x) => vec![x.rs1.clone(), x.rs2.clone()],
             ParserNode::Load(x) => vec![x.rs1.clone()],
             ParserNode::Csr(x) => vec![x.rs1.clone()],
+            ParserNode::FloatArith(x) => [Some(x.rs1.clone()), x.rs2.clone(), x.rs3.clone()]
+                .into_iter()
+                .flatten()
+                .collect(),
             ParserNode::ProgramEntry(_)
             | ParserNode::FuncEntry(_)
             | ParserNode::Label(_)
@@ -516,15 +751,99 @@ impl ParserNode {
             | ParserNode::Basic(_)
             | ParserNode::Directive(_)
             | ParserNode::LoadAddr(_)
+            | ParserNode::UpperArith(_)
             | ParserNode::CsrI(_) => vec![],
         };
-        vector.into_iter().collect()
+        regs.into_iter()
+    }
+
+    /// Get the immediate operand of this instruction, if it has one.
+    ///
+    /// A relocation, like `%lo(symbol)`, is an immediate of zero, as the
+    /// address is not known. The CSR of a CSR instruction is not included.
+    pub fn immediates(&self) -> impl Iterator<Item = With<Imm>> {
+        let imm = match self {
+            ParserNode::IArith(x) => Some(x.imm.clone()),
+            ParserNode::UpperArith(x) => Some(x.imm.clone()),
+            ParserNode::JumpLinkR(x) => Some(x.imm.clone()),
+            ParserNode::Load(x) => Some(x.imm.clone()),
+            ParserNode::Store(x) => Some(x.imm.clone()),
+            ParserNode::CsrI(x) => Some(x.imm.clone()),
+            ParserNode::ProgramEntry(_)
+            | ParserNode::FuncEntry(_)
+            | ParserNode::Arith(_)
+            | ParserNode::Label(_)
+            | ParserNode::JumpLink(_)
+            | ParserNode::Basic(_)
+            | ParserNode::Directive(_)
+            | ParserNode::Branch(_)
+            | ParserNode::LoadAddr(_)
+            | ParserNode::Csr(_)
+            | ParserNode::FloatArith(_) => None,
+        };
+        imm.into_iter()
+    }
+
+    // NOTE: This is in context to a register store, not a memory store
+    #[must_use]
+    pub fn stores_to(&self) -> Option<With<Register>> {
+        self.write_registers().next()
+    }
+
+    #[must_use]
+    pub fn reads_from(&self) -> HashSet<With<Register>> {
+        self.read_registers().collect()
+    }
+
+    /// Returns the source register if this instruction only copies a register.
+    ///
+    /// Besides the `mv` pseudo-instruction, there are many ways to copy one
+    /// register into another, like `addi rd, rs, 0` or `or rd, x0, rs`. All
+    /// of these forms are recognized. Copies from the zero register are not
+    /// considered copies, as they are constant loads.
+    #[must_use]
+    pub fn copies_from(&self) -> Option<With<Register>> {
+        let source = match self {
+            ParserNode::Arith(x) => match x.inst.data {
+                ArithType::Add | ArithType::Or | ArithType::Xor => {
+                    if x.rs2 == Register::X0 {
+                        Some(x.rs1.clone())
+                    } else if x.rs1 == Register::X0 {
+                        Some(x.rs2.clone())
+                    } else {
+                        None
+                    }
+                }
+                ArithType::Sub | ArithType::Sll | ArithType::Srl | ArithType::Sra
+                    if x.rs2 == Register::X0 =>
+                {
+                    Some(x.rs1.clone())
+                }
+                _ => None,
+            },
+            ParserNode::IArith(x) => match x.inst.data {
+                IArithType::Addi
+                | IArithType::Ori
+                | IArithType::Xori
+                | IArithType::Slli
+                | IArithType::Srli
+                | IArithType::Srai
+                    if x.imm == Imm(0) =>
+                {
+                    Some(x.rs1.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        source.filter(|reg| reg != &Register::X0)
     }
 
     pub fn set_uuid(&mut self, uuid: Uuid) {
         match self {
             ParserNode::Arith(x) => x.key = uuid,
             ParserNode::IArith(x) => x.key = uuid,
+            ParserNode::UpperArith(x) => x.key = uuid,
             ParserNode::Label(x) => x.key = uuid,
             ParserNode::JumpLink(x) => x.key = uuid,
             ParserNode::JumpLinkR(x) => x.key = uuid,
@@ -535,6 +854,7 @@ impl ParserNode {
             ParserNode::Load(x) => x.key = uuid,
             ParserNode::Csr(x) => x.key = uuid,
             ParserNode::CsrI(x) => x.key = uuid,
+            ParserNode::FloatArith(x) => x.key = uuid,
             ParserNode::LoadAddr(x) => x.key = uuid,
             ParserNode::ProgramEntry(_) | ParserNode::FuncEntry(_) => (),
         }
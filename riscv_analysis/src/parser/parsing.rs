@@ -1,28 +1,40 @@
 use uuid::Uuid;
 
 use crate::parser::inst::{
-    ArithType, BranchType, CSRIType, CSRType, IArithType, Inst, JumpLinkRType, JumpLinkType,
-    PseudoType, Type,
+    ArithType, BranchType, CSRIType, CSRType, FloatArithType, IArithType, Inst, JumpLinkRType,
+    JumpLinkType, PseudoType, Type, UpperArithType,
 };
 use crate::parser::token::With;
-use crate::parser::{DataType, RawToken, Register};
+use crate::parser::{AlignMode, DataType, DataValue, RawToken, Register};
 use crate::parser::{DirectiveToken, LexError};
 use crate::parser::{DirectiveType, ParserNode};
 use crate::parser::{Lexer, Token};
+use crate::cfg::Segment;
 use crate::passes::{DiagnosticItem, Manager};
 use crate::reader::FileReader;
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::iter::Peekable;
 use std::str::FromStr;
 
-use super::imm::{CSRImm, Imm};
+use super::imm::{CSRImm, FloatImm, Imm};
 use super::token::Info;
-use super::{ExpectedType, LabelString, ParseError, Range};
+use super::{
+    link_pcrel_relocations, resolve_local_labels, Annotation, Constants, ExpectedType, LabelString,
+    ParseError, Range, Relocation,
+};
 
 #[derive(Deserialize, Clone)]
 pub struct RVDocument {
     pub uri: String,
     pub text: String,
+    /// The tab size of the editor, used to line up positions with tabs
+    #[serde(default = "default_tab_width")]
+    pub tab_width: usize,
+}
+
+fn default_tab_width() -> usize {
+    1
 }
 
 impl ParserNode {
@@ -46,6 +58,49 @@ impl ParserNode {
 pub trait CanGetURIString: FileReader {
     fn get_uri_string(&self, uuid: Uuid) -> RVDocument;
 }
+
+/// A file that was read by an incremental parser.
+///
+/// The nodes and errors include those of every file that it includes, so
+/// the whole file can be reused as long as none of these files changed.
+struct CachedFile {
+    /// The text of the file when it was read.
+    text: String,
+    /// The constants that were defined before the file was read.
+    constants_before: Constants,
+    /// The constants that were defined after the file was read.
+    constants_after: Constants,
+    nodes: Vec<ParserNode>,
+    errors: Vec<ParseError>,
+    /// Every file that is included, directly or through other files, with
+    /// its text when it was read.
+    includes: Vec<(Uuid, String)>,
+}
+
+/// A file that is being read, kept alongside its lexer.
+struct OpenFile {
+    uuid: Uuid,
+    text: String,
+    constants_before: Constants,
+    /// The index of the first node of the file.
+    first_node: usize,
+    /// The index of the first error of the file.
+    first_error: usize,
+    includes: Vec<(Uuid, String)>,
+    /// If the file can be cached when it is done.
+    ///
+    /// A file that includes a file that could not be read is not cached,
+    /// as the include would not be checked if that file is added later.
+    cacheable: bool,
+}
+
+/// The most times that a `.rept` block can be repeated.
+pub(crate) const MAX_REPEAT_COUNT: usize = 10_000;
+
+/// The most tokens that `.rept` blocks can repeat in a program, so that
+/// nested blocks cannot make a program too large to check.
+pub(crate) const MAX_REPEATED_TOKENS: usize = 1_000_000;
+
 /// Parser for RISC-V assembly
 pub struct RVParser<T>
 where
@@ -53,6 +108,20 @@ where
 {
     lexer_stack: Vec<Peekable<Lexer>>,
     pub reader: T,
+    align_mode: AlignMode,
+    constants: Constants,
+    /// The files of the lexer stack, in the same order.
+    open_files: Vec<OpenFile>,
+    /// If files are cached to be reused by the next parse.
+    incremental: bool,
+    cache: HashMap<Uuid, CachedFile>,
+    /// The files that were read by the current parse.
+    read_files: HashSet<Uuid>,
+    /// The positions in the lexer stack of the lexers of repeated blocks,
+    /// which have no file.
+    repeats: Vec<usize>,
+    /// The number of tokens repeated by `.rept` blocks in the current parse.
+    repeated_tokens: usize,
 }
 
 impl<T: FileReader + Clone> RVParser<T> {
@@ -67,9 +136,7 @@ impl<T: FileReader + Clone> RVParser<T> {
         let res = Manager::run(parsed.0);
         match res {
             Ok(lints) => {
-                lints
-                    .iter()
-                    .for_each(|x| diags.push(DiagnosticItem::from(x.clone())));
+                diags.extend(lints.iter().map(|x| DiagnosticItem::from(x.clone())));
             }
             Err(err) => diags.push(DiagnosticItem::from(*err)),
         }
@@ -81,7 +148,47 @@ impl<T: FileReader + Clone> RVParser<T> {
         RVParser {
             lexer_stack: Vec::new(),
             reader,
+            align_mode: AlignMode::default(),
+            constants: Constants::default(),
+            open_files: Vec::new(),
+            incremental: false,
+            cache: HashMap::new(),
+            read_files: HashSet::new(),
+            repeats: Vec::new(),
+            repeated_tokens: 0,
+        }
+    }
+
+    /// Set how the arguments of `.align` directives are read.
+    pub fn set_align_mode(&mut self, align_mode: AlignMode) {
+        if self.align_mode != align_mode {
+            self.cache.clear();
+        }
+        self.align_mode = align_mode;
+    }
+
+    /// Set if the parser keeps the files it reads to reuse them later.
+    ///
+    /// An incremental parser only lexes and parses a file again if its
+    /// text changed since it was last read, or if any file it includes
+    /// changed. This is used by the language server, which parses the same
+    /// files after every edit.
+    pub fn set_incremental(&mut self, incremental: bool) {
+        if !incremental {
+            self.cache.clear();
         }
+        self.incremental = incremental;
+    }
+
+    /// Get every file that a file includes, directly or through other
+    /// files, from the last time an incremental parser read it.
+    ///
+    /// Returns `None` if the file is not cached.
+    #[must_use]
+    pub fn cached_includes(&self, uuid: Uuid) -> Option<Vec<Uuid>> {
+        self.cache
+            .get(&uuid)
+            .map(|file| file.includes.iter().map(|(file, _)| *file).collect())
     }
 
     /// Skip the rest of the line
@@ -99,6 +206,82 @@ impl<T: FileReader + Clone> RVParser<T> {
         }
     }
 
+    /// Record an error from parsing a node.
+    ///
+    /// Returns true if the rest of the line should be skipped to recover
+    /// from the error.
+    fn report_error(
+        &mut self,
+        error: LexError,
+        nodes: &mut Vec<ParserNode>,
+        parse_errors: &mut Vec<ParseError>,
+    ) -> bool {
+        match error {
+            LexError::Expected(ex, got) | LexError::MissingOperand(ex, got) => {
+                parse_errors.push(ParseError::Expected(ex, got));
+            }
+            LexError::IsNewline(_) | LexError::IsComment(_) | LexError::IgnoredWithoutWarning => {
+                return false
+            }
+            LexError::UnexpectedToken(got) => {
+                parse_errors.push(ParseError::UnexpectedToken(got));
+            }
+            LexError::UnexpectedEOF => {
+                self.lexer_stack.pop();
+                if self.repeats.last() == Some(&self.lexer_stack.len()) {
+                    self.repeats.pop();
+                } else {
+                    self.close_file(nodes, parse_errors);
+                }
+                return false;
+            }
+            LexError::NeedTwoNodes(n1, n2) => {
+                nodes.push(*n1);
+                nodes.push(*n2);
+                return false;
+            }
+            LexError::UnexpectedError(x) => {
+                parse_errors.push(ParseError::UnexpectedError(x));
+            }
+            LexError::UnknownDirective(y) => {
+                parse_errors.push(ParseError::UnknownDirective(y));
+            }
+            LexError::IgnoredWithWarning(y) | LexError::UnsupportedDirective(y) => {
+                parse_errors.push(ParseError::Unsupported(y));
+            }
+            LexError::InvalidString(info, err) => {
+                parse_errors.push(ParseError::InvalidString(info, err));
+            }
+            LexError::InvalidFloat(info) => {
+                parse_errors.push(ParseError::InvalidFloat(info));
+            }
+            LexError::UnknownCsr(info) => {
+                parse_errors.push(ParseError::UnknownCsr(info));
+            }
+            LexError::WrongOperandCount(info, expected, found) => {
+                parse_errors.push(ParseError::WrongOperandCount(*info, expected, found));
+            }
+            LexError::TooManyOperands(parsed, info, expected, found) => {
+                nodes.extend(parsed);
+                parse_errors.push(ParseError::WrongOperandCount(*info, expected, found));
+                return false;
+            }
+            LexError::Repeat(directive, count) => {
+                self.repeat(*directive, &count, parse_errors);
+                return false;
+            }
+            LexError::Multiple(errors) => {
+                // The line is only skipped once, after all errors are recorded
+                let mut recover = true;
+                for inner in errors {
+                    recover &= self.report_error(inner, nodes, parse_errors);
+                }
+                return recover;
+            }
+        }
+        true
+    }
+
     /// Parse files
     ///
     /// This function is responsible for parsing the file. It will continue until no imports are left.
@@ -109,17 +292,19 @@ impl<T: FileReader + Clone> RVParser<T> {
     ) -> (Vec<ParserNode>, Vec<ParseError>) {
         let mut nodes = Vec::new();
         let mut parse_errors = Vec::new();
+        self.constants = Constants::default();
+        self.read_files.clear();
+        self.repeats.clear();
+        self.repeated_tokens = 0;
 
-        // import base lexer
-        let lexer = match self.reader.import_file(base, None) {
-            Ok(x) => Lexer::new(x.1, x.0),
+        // import base file
+        let (first_uuid, text) = match self.reader.import_file(base, None) {
+            Ok(file) => file,
             Err(e) => {
                 parse_errors.push(e.to_parse_error(With::new(base.to_owned(), Info::default())));
                 return (nodes, parse_errors);
             }
         };
-        let first_uuid = lexer.source_id;
-        self.lexer_stack.push(lexer.peekable());
 
         // Add program entry node
         nodes.push(ParserNode::new_program_entry(
@@ -130,21 +315,67 @@ impl<T: FileReader + Clone> RVParser<T> {
                 file: first_uuid,
             },
         ));
+        self.open_file(
+            first_uuid,
+            text,
+            !ignore_imports,
+            &mut nodes,
+            &mut parse_errors,
+        );
 
-        while let Some(l) = self.lexer() {
-            let node = ParserNode::try_from(l);
+        // Annotations in the comments since the last node, which are kept
+        // with the next label
+        let mut annotations = Vec::new();
+        while let Some(l) = self.lexer_stack.last_mut() {
+            let node = ParserNode::parse(l, &self.constants);
 
             match node {
-                Ok(x) => {
+                Err(LexError::IsComment(comment)) => {
+                    annotations.extend(Annotation::parse(&comment));
+                }
+                Err(error) => {
+                    if !matches!(error, LexError::IsNewline(_)) {
+                        annotations.clear();
+                    }
+                    if self.report_error(error, &mut nodes, &mut parse_errors) {
+                        self.recover_from_parse_error();
+                    }
+                }
+                Ok(mut x) => {
+                    match &mut x {
+                        ParserNode::Label(label) => {
+                            label.annotations = std::mem::take(&mut annotations);
+                        }
+                        _ => annotations.clear(),
+                    }
+                    if let Some(err) = self.resolve_alignment(&mut x) {
+                        parse_errors.push(err);
+                    }
                     if !ignore_imports {
                         if let Some(path) = x.get_include_path() {
                             match self.reader.import_file(&path.data, Some(path.file)) {
+                                Ok((new_uuid, _)) if self.is_open(new_uuid) => {
+                                    parse_errors.push(ParseError::CyclicDependency(path.info()));
+                                    self.set_uncacheable();
+                                }
+                                Ok((new_uuid, _)) if self.read_files.contains(&new_uuid) => {
+                                    // Files are only included once, so that
+                                    // a shared file does not define its
+                                    // labels twice.
+                                    self.set_uncacheable();
+                                }
                                 Ok((new_uuid, new_text)) => {
-                                    self.lexer_stack
-                                        .push(Lexer::new(new_text, new_uuid).peekable());
+                                    self.open_file(
+                                        new_uuid,
+                                        new_text,
+                                        true,
+                                        &mut nodes,
+                                        &mut parse_errors,
+                                    );
                                 }
                                 Err(error) => {
                                     parse_errors.push(error.to_parse_error(path.clone()));
+                                    self.set_uncacheable();
                                 }
                             }
                             continue;
@@ -152,49 +383,222 @@ impl<T: FileReader + Clone> RVParser<T> {
                     }
                     nodes.push(x);
                 }
-                Err(x) => match x {
-                    LexError::Expected(ex, got) => {
-                        parse_errors.push(ParseError::Expected(ex, got));
-                        self.recover_from_parse_error();
-                    }
-                    LexError::IsNewline(_) => {}
-                    LexError::UnexpectedToken(got) => {
-                        parse_errors.push(ParseError::UnexpectedToken(got));
-                        self.recover_from_parse_error();
-                    }
-                    LexError::UnexpectedEOF => {
-                        self.lexer_stack.pop();
-                    }
-                    LexError::NeedTwoNodes(n1, n2) => {
-                        nodes.push(*n1);
-                        nodes.push(*n2);
-                    }
-                    LexError::UnexpectedError(x) => {
-                        parse_errors.push(ParseError::UnexpectedError(x));
-                        self.recover_from_parse_error();
-                    }
-                    LexError::UnknownDirective(y) => {
-                        parse_errors.push(ParseError::UnknownDirective(y));
-                        self.recover_from_parse_error();
-                    }
-                    LexError::IgnoredWithWarning(y) | LexError::UnsupportedDirective(y) => {
-                        parse_errors.push(ParseError::Unsupported(y));
-                        self.recover_from_parse_error();
-                    }
-                    LexError::IgnoredWithoutWarning => (),
-                    LexError::InvalidString(info, err) => {
-                        parse_errors.push(ParseError::InvalidString(info, err));
-                        self.recover_from_parse_error();
-                    }
-                },
             }
         }
+        parse_errors.extend(resolve_local_labels(&mut nodes));
+        link_pcrel_relocations(&mut nodes);
         (nodes, parse_errors)
     }
 
+    /// Read the body of a `.rept` block, up to its matching `.endr`, and
+    /// read it again `count` times.
+    ///
+    /// Nested blocks are kept in the body, so they are repeated each time
+    /// the body is read.
+    fn repeat(&mut self, directive: Info, count: &With<Imm>, parse_errors: &mut Vec<ParseError>) {
+        let Some(lexer) = self.lexer() else {
+            return;
+        };
+        let mut body = Vec::new();
+        let mut depth = 0_usize;
+        let end = loop {
+            let Some(token) = lexer.next() else {
+                parse_errors.push(ParseError::UnterminatedRepeat(directive));
+                return;
+            };
+            if let Ok(Info {
+                token: Token::Directive(dir),
+                ..
+            }) = &token
+            {
+                match DirectiveToken::from_str(dir) {
+                    Ok(DirectiveToken::Rept) => depth += 1,
+                    Ok(DirectiveToken::Endr) if depth == 0 => break token,
+                    Ok(DirectiveToken::Endr) => depth -= 1,
+                    _ => {}
+                }
+            }
+            body.push(token);
+        };
+
+        // Each copy ends a line, in case the body does not
+        let newline = end.map(|end| Info {
+            token: Token::Newline,
+            ..end
+        });
+        body.push(newline);
+        let times = usize::try_from(count.data.0)
+            .ok()
+            .filter(|times| *times <= MAX_REPEAT_COUNT);
+        let tokens = times
+            .and_then(|times| times.checked_mul(body.len()))
+            .filter(|tokens| self.repeated_tokens + tokens <= MAX_REPEATED_TOKENS);
+        let (Some(times), Some(tokens)) = (times, tokens) else {
+            parse_errors.push(ParseError::InvalidRepeatCount(count.info()));
+            return;
+        };
+        self.repeated_tokens += tokens;
+        if times == 0 {
+            return;
+        }
+
+        let repeated = std::iter::repeat_n(body, times).flatten().collect();
+        self.repeats.push(self.lexer_stack.len());
+        self.lexer_stack
+            .push(Lexer::from_tokens(repeated, directive.file).peekable());
+    }
+
+    /// Check if a file is being read, either directly or by a file that it
+    /// includes.
+    fn is_open(&self, uuid: Uuid) -> bool {
+        self.open_files.iter().any(|file| file.uuid == uuid)
+    }
+
+    /// Stop the file being read from being cached.
+    ///
+    /// This is used when the nodes of a file depend on more than its text,
+    /// such as when an include failed or was skipped.
+    fn set_uncacheable(&mut self) {
+        if let Some(file) = self.open_files.last_mut() {
+            file.cacheable = false;
+        }
+    }
+
+    /// Start reading a file.
+    ///
+    /// If the parser is incremental and the file did not change since it was
+    /// last read, its cached nodes and errors are added instead.
+    fn open_file(
+        &mut self,
+        uuid: Uuid,
+        text: String,
+        cacheable: bool,
+        nodes: &mut Vec<ParserNode>,
+        parse_errors: &mut Vec<ParseError>,
+    ) {
+        let cacheable = cacheable && self.incremental;
+        if cacheable && self.reuse_file(uuid, &text, nodes, parse_errors) {
+            return;
+        }
+        self.read_files.insert(uuid);
+        self.open_files.push(OpenFile {
+            uuid,
+            text: text.clone(),
+            constants_before: self.constants.clone(),
+            first_node: nodes.len(),
+            first_error: parse_errors.len(),
+            includes: Vec::new(),
+            cacheable,
+        });
+        let lexer = self.new_lexer(text, uuid);
+        parse_errors.extend(self.push_lexer(lexer));
+    }
+
+    /// Add the cached nodes and errors of a file, if the file can be reused.
+    ///
+    /// A file can be reused if neither it nor any file that it includes
+    /// changed, and the constants defined before it are the same. Files are
+    /// only used once in a parse, so that no two nodes share an ID.
+    fn reuse_file(
+        &mut self,
+        uuid: Uuid,
+        text: &str,
+        nodes: &mut Vec<ParserNode>,
+        parse_errors: &mut Vec<ParseError>,
+    ) -> bool {
+        let Some(file) = self.cache.get(&uuid) else {
+            return false;
+        };
+        let unchanged = |(include, include_text): &(Uuid, String)| {
+            !self.read_files.contains(include)
+                && self.reader.get_text(*include).as_ref() == Some(include_text)
+        };
+        if file.text != text
+            || file.constants_before != self.constants
+            || self.read_files.contains(&uuid)
+            || !file.includes.iter().all(unchanged)
+        {
+            return false;
+        }
+
+        nodes.extend(file.nodes.iter().cloned());
+        parse_errors.extend(file.errors.iter().cloned());
+        self.constants = file.constants_after.clone();
+        self.read_files.insert(uuid);
+        self.read_files
+            .extend(file.includes.iter().map(|(include, _)| *include));
+        if let Some(parent) = self.open_files.last_mut() {
+            parent.includes.push((uuid, file.text.clone()));
+            parent.includes.extend(file.includes.iter().cloned());
+        }
+        true
+    }
+
+    /// Finish reading the file whose lexer was just removed, caching it if
+    /// the parser is incremental.
+    fn close_file(&mut self, nodes: &[ParserNode], parse_errors: &[ParseError]) {
+        let Some(file) = self.open_files.pop() else {
+            return;
+        };
+        if let Some(parent) = self.open_files.last_mut() {
+            parent.includes.push((file.uuid, file.text.clone()));
+            parent.includes.extend(file.includes.iter().cloned());
+            parent.cacheable &= file.cacheable;
+        }
+        if file.cacheable {
+            self.cache.insert(
+                file.uuid,
+                CachedFile {
+                    text: file.text,
+                    constants_before: file.constants_before,
+                    constants_after: self.constants.clone(),
+                    nodes: nodes.get(file.first_node..).unwrap_or_default().to_vec(),
+                    errors: parse_errors
+                        .get(file.first_error..)
+                        .unwrap_or_default()
+                        .to_vec(),
+                    includes: file.includes,
+                },
+            );
+        }
+    }
+
+    /// Create a lexer for a file, using the tab width the reader gives it.
+    fn new_lexer(&self, text: String, uuid: Uuid) -> Lexer {
+        Lexer::new_with_tab_width(text, uuid, self.reader.tab_width(uuid))
+    }
+
+    /// Start reading from a file, after collecting the constants it defines.
+    fn push_lexer(&mut self, lexer: Lexer) -> Vec<ParseError> {
+        let errors = self.constants.collect(lexer.clone());
+        self.lexer_stack.push(lexer.peekable());
+        errors
+    }
+
     fn lexer(&mut self) -> Option<&mut Peekable<Lexer>> {
         self.lexer_stack.last_mut()
     }
+
+    /// Resolve the alignment of an `.align` directive with the align mode of
+    /// the parser. The argument of `.balign` is always a number of bytes.
+    ///
+    /// Returns an error if the argument is not a valid alignment.
+    fn resolve_alignment(&self, node: &mut ParserNode) -> Option<ParseError> {
+        let ParserNode::Directive(dir) = node else {
+            return None;
+        };
+        let DirectiveType::Align { value, bytes } = &mut dir.dir else {
+            return None;
+        };
+        let mode = match dir.dir_token.data {
+            DirectiveToken::Balign => AlignMode::Bytes,
+            _ => self.align_mode,
+        };
+        *bytes = mode.bytes(value.data.0);
+        bytes
+            .is_none()
+            .then(|| ParseError::InvalidAlignment(value.info(), mode))
+    }
 }
 
 impl Info {
@@ -212,9 +616,20 @@ impl Info {
         }
     }
 
+    /// Read a token as an integer register.
     fn as_reg(&self) -> Result<With<Register>, LexError> {
         With::<Register>::try_from(self.clone())
-            .map_err(|()| LexError::Expected(vec![ExpectedType::Register], self.clone()))
+            .ok()
+            .filter(|reg| !reg.data.is_float())
+            .ok_or_else(|| LexError::Expected(vec![ExpectedType::Register], self.clone()))
+    }
+
+    /// Read a token as a floating-point register.
+    fn as_freg(&self) -> Result<With<Register>, LexError> {
+        With::<Register>::try_from(self.clone())
+            .ok()
+            .filter(|reg| reg.data.is_float())
+            .ok_or_else(|| LexError::Expected(vec![ExpectedType::FloatRegister], self.clone()))
     }
 
     fn as_imm(&self) -> Result<With<Imm>, LexError> {
@@ -227,9 +642,24 @@ impl Info {
             .map_err(|()| LexError::Expected(vec![ExpectedType::Label], self.clone()))
     }
 
+    /// Read a token as a CSR, written by name or by address.
     fn as_csrimm(&self) -> Result<With<CSRImm>, LexError> {
-        With::<CSRImm>::try_from(self.clone())
-            .map_err(|()| LexError::Expected(vec![ExpectedType::CSRImm], self.clone()))
+        With::<CSRImm>::try_from(self.clone()).map_err(|()| match &self.token {
+            Token::Symbol(s) if !FloatImm::looks_numeric(s) => LexError::UnknownCsr(self.clone()),
+            _ => LexError::Expected(vec![ExpectedType::CSRImm], self.clone()),
+        })
+    }
+
+    fn as_float_imm(&self) -> Result<With<FloatImm>, LexError> {
+        With::<FloatImm>::try_from(self.clone()).map_err(|()| LexError::InvalidFloat(self.clone()))
+    }
+
+    /// Check if this token is written like a number.
+    fn is_numeric(&self) -> bool {
+        match &self.token {
+            Token::Symbol(s) => FloatImm::looks_numeric(s),
+            _ => false,
+        }
     }
 
     fn as_string(&self) -> Result<With<String>, LexError> {
@@ -244,27 +674,173 @@ impl AnnotatedLexer<'_> {
     }
 
     fn expect_rparen(&mut self) -> Result<(), LexError> {
-        self.get_any()?.as_rparen()
+        self.get_operand(&[ExpectedType::RParen])?.as_rparen()
     }
 
     fn get_reg(&mut self) -> Result<With<Register>, LexError> {
-        self.get_any()?.as_reg()
+        let info = self.get_operand(&[ExpectedType::Register])?;
+        self.recover(&info, info.as_reg(), Register::X0)
+    }
+
+    fn get_freg(&mut self) -> Result<With<Register>, LexError> {
+        let info = self.get_operand(&[ExpectedType::FloatRegister])?;
+        self.recover(&info, info.as_freg(), Register::F0)
+    }
+
+    /// Get a floating-point register if `float` is set, or an integer
+    /// register otherwise.
+    fn get_reg_of(&mut self, float: bool) -> Result<With<Register>, LexError> {
+        if float {
+            self.get_freg()
+        } else {
+            self.get_reg()
+        }
+    }
+
+    /// Skip a rounding mode, like `rtz`, at the end of a floating-point
+    /// instruction.
+    ///
+    /// The rounding mode does not change which registers are used, so it is
+    /// not kept.
+    fn skip_rounding_mode(&mut self) -> Result<(), LexError> {
+        let is_rounding_mode = matches!(
+            self.lexer.peek(),
+            Some(Ok(Info { token: Token::Symbol(s), .. }))
+                if matches!(s.as_str(), "rne" | "rtz" | "rdn" | "rup" | "rmm" | "dyn")
+        );
+        if is_rounding_mode {
+            self.get_any()?;
+        }
+        Ok(())
     }
 
     fn get_imm(&mut self) -> Result<With<Imm>, LexError> {
-        self.get_any()?.as_imm()
+        let info = self.get_operand(&[ExpectedType::Imm])?;
+        let imm = self.as_imm(&info);
+        self.recover(&info, imm, Imm(0))
+    }
+
+    /// Get an immediate, or a relocation like `%lo(symbol)` in its place.
+    fn get_imm_or_relocation(&mut self) -> Result<(With<Imm>, Option<Relocation>), LexError> {
+        let info = self.get_operand(&[ExpectedType::Imm])?;
+        if let Ok(reloc) = Relocation::try_from(info.clone()) {
+            return Ok((With::new(Imm(0), info), Some(reloc)));
+        }
+        let imm = self.as_imm(&info);
+        Ok((self.recover(&info, imm, Imm(0))?, None))
+    }
+
+    /// Continue past an operand of the wrong kind.
+    ///
+    /// A single word in the place of an operand does not change where the
+    /// next operand starts, so the error is saved and `fallback` is used
+    /// instead. This lets the rest of the operands be checked. Other tokens,
+    /// like parentheses, still stop the instruction.
+    fn recover<D: PartialEq>(
+        &mut self,
+        info: &Info,
+        value: Result<With<D>, LexError>,
+        fallback: D,
+    ) -> Result<With<D>, LexError> {
+        match value {
+            Err(error @ LexError::Expected(..)) if matches!(info.token, Token::Symbol(_)) => {
+                self.errors.push(error);
+                Ok(With::new(fallback, info.clone()))
+            }
+            value => value,
+        }
+    }
+
+    /// Add the errors that parsing continued past to the result of parsing
+    /// an instruction.
+    ///
+    /// A node parsed with any of these errors uses fallback operands, so it
+    /// is dropped.
+    fn with_recovered_errors(
+        &mut self,
+        node: Result<ParserNode, LexError>,
+    ) -> Result<ParserNode, LexError> {
+        if self.errors.is_empty() {
+            return node;
+        }
+        let mut errors = std::mem::take(&mut self.errors);
+        match node {
+            Ok(_) | Err(LexError::NeedTwoNodes(..)) => {}
+            Err(LexError::TooManyOperands(_, info, expected, found)) => {
+                errors.push(LexError::WrongOperandCount(info, expected, found));
+            }
+            Err(error) => errors.push(error),
+        }
+        Err(match <[LexError; 1]>::try_from(errors) {
+            Ok([error]) => error,
+            Err(errors) => LexError::Multiple(errors),
+        })
+    }
+
+    /// Read a token as an immediate, replacing the name of a constant with
+    /// its value.
+    fn as_imm(&self, info: &Info) -> Result<With<Imm>, LexError> {
+        info.as_imm().or_else(|err| match &info.token {
+            Token::Symbol(s) => self
+                .constants
+                .get(s)
+                .map(|imm| With::new(imm, info.clone()))
+                .ok_or(err),
+            _ => Err(err),
+        })
+    }
+
+    /// Read a token as an immediate, or as a relocation like `%lo(symbol)`.
+    ///
+    /// The value of a relocation is not known until the program is
+    /// assembled, so its immediate is zero.
+    fn as_imm_or_relocation(
+        &self,
+        info: &Info,
+    ) -> Result<(With<Imm>, Option<Relocation>), LexError> {
+        match Relocation::try_from(info.clone()) {
+            Ok(reloc) => Ok((With::new(Imm(0), info.clone()), Some(reloc))),
+            Err(()) => self.as_imm(info).map(|imm| (imm, None)),
+        }
     }
 
     fn get_label(&mut self) -> Result<With<LabelString>, LexError> {
-        self.get_any()?.as_label()
+        self.get_operand(&[ExpectedType::Label])?.as_label()
     }
 
     fn get_csrimm(&mut self) -> Result<With<CSRImm>, LexError> {
-        self.get_any()?.as_csrimm()
+        self.get_operand(&[ExpectedType::CSRImm])?.as_csrimm()
     }
 
     fn get_string(&mut self) -> Result<With<String>, LexError> {
-        self.get_any()?.as_string()
+        self.get_operand(&[ExpectedType::String])?.as_string()
+    }
+
+    /// Get the name of a section, like `.text` or `.text.startup`.
+    ///
+    /// The lexer splits dotted names into one token per part, so the parts
+    /// that touch each other are joined back together.
+    fn get_section_name(&mut self) -> Result<With<String>, LexError> {
+        let first = self.get_operand(&[ExpectedType::Label])?;
+        let mut name = match &first.token {
+            Token::Directive(s) | Token::Symbol(s) => s.clone(),
+            _ => return Err(LexError::Expected(vec![ExpectedType::Label], first)),
+        };
+        let mut end = first.pos.end;
+        while let Some(Ok(Info {
+            token: Token::Directive(part) | Token::Symbol(part),
+            pos,
+            ..
+        })) = self.lexer.peek()
+        {
+            if pos.start.line != end.line || pos.start.column > end.column + 1 {
+                break;
+            }
+            name.push_str(part);
+            end = pos.end;
+            self.get_any()?;
+        }
+        Ok(With::new(name, first))
     }
 
     fn get_any(&mut self) -> Result<Info, LexError> {
@@ -277,6 +853,15 @@ impl AnnotatedLexer<'_> {
                     file: item.file,
                 };
             } else {
+                // Tokens that touch the previous token, like the parts of
+                // `0(sp)`, are part of the same operand.
+                let last = &self.raw_token.pos.end;
+                let touches = item.pos.start.line == last.line
+                    && item.pos.start.column <= last.column + 1;
+                if item.token != Token::RParen && !touches {
+                    self.operands += 1;
+                }
+
                 self.raw_token.text.push(' ');
                 self.raw_token
                     .text
@@ -293,627 +878,921 @@ impl AnnotatedLexer<'_> {
             None => Err(LexError::UnexpectedEOF)
         }
     }
+
+    /// Check if there are no more operands on the current line.
+    /// Check if the next token is an integer register.
+    fn next_is_reg(&mut self) -> bool {
+        matches!(self.lexer.peek(), Some(Ok(info)) if info.as_reg().is_ok())
+    }
+
+    fn at_end_of_line(&mut self) -> bool {
+        matches!(
+            self.lexer.peek(),
+            None | Some(Ok(Info {
+                token: Token::Newline | Token::Comment(_),
+                ..
+            }))
+        )
+    }
+
+    /// Get the next operand of the current line.
+    ///
+    /// If the line has ended, the newline is not consumed, so that parsing
+    /// can continue on the next line after the error.
+    fn get_operand(&mut self, expected: &[ExpectedType]) -> Result<Info, LexError> {
+        if !self.at_end_of_line() {
+            return self.get_any();
+        }
+        let token = match self.lexer.peek() {
+            Some(Ok(info)) => info.token.clone(),
+            _ => Token::Newline,
+        };
+
+        // Point right after the last token of the line
+        let mut pos = self.raw_token.pos.end;
+        pos.column += 1;
+        let info = Info {
+            token,
+            pos: Range {
+                start: pos,
+                end: pos,
+            },
+            file: self.raw_token.file,
+        };
+        Err(LexError::MissingOperand(expected.to_vec(), info))
+    }
+
+    /// Check that an instruction has the right number of operands.
+    ///
+    /// A missing operand is reported with the number of operands that
+    /// were found. Extra operands are consumed up to the end of the line and
+    /// reported along with the parsed instruction.
+    fn check_operand_count(
+        &mut self,
+        inst: Inst,
+        node: Result<ParserNode, LexError>,
+    ) -> Result<ParserNode, LexError> {
+        let Some(count) = Type::from(&inst).operand_count() else {
+            return node;
+        };
+
+        if let Err(LexError::MissingOperand(_, info)) = &node {
+            let expected = (*count.start()).max(self.operands + 1);
+            return Err(LexError::WrongOperandCount(
+                Box::new(info.clone()),
+                expected,
+                self.operands,
+            ));
+        }
+        if !matches!(node, Ok(_) | Err(LexError::NeedTwoNodes(..)))
+            || self.at_end_of_line()
+            || matches!(self.lexer.peek(), Some(Err(_)))
+        {
+            return node;
+        }
+        let nodes = match node {
+            Ok(node) => vec![node],
+            Err(LexError::NeedTwoNodes(n1, n2)) => vec![*n1, *n2],
+            Err(e) => return Err(e),
+        };
+
+        // Consume the rest of the line
+        let found_before = self.operands;
+        let mut extra: Option<Range> = None;
+        while !self.at_end_of_line() {
+            let Ok(item) = self.get_any() else {
+                break;
+            };
+            match extra {
+                Some(ref mut range) => range.end = item.pos.end,
+                None => extra = Some(item.pos),
+            }
+        }
+        let info = Info {
+            token: Token::Symbol(String::new()),
+            pos: extra.unwrap_or_default(),
+            file: self.raw_token.file,
+        };
+        let found = self.operands.max(found_before + 1);
+        Err(LexError::TooManyOperands(nodes, Box::new(info), *count.end(), found))
+    }
 }
 
 struct AnnotatedLexer<'a> {
     lexer: &'a mut Peekable<Lexer>,
+    /// The constants that can be used as immediates.
+    constants: &'a Constants,
     raw_token: RawToken,
+    /// The number of operands read after the first token.
+    operands: usize,
+    /// Errors in operands that parsing continued past.
+    errors: Vec<LexError>,
+}
+/// Parse the operands of an instruction and lower it into a node.
+///
+/// The mnemonic of the instruction has already been read as `next_node`.
+#[allow(clippy::too_many_lines)]
+fn parse_instruction(
+    lex: &mut AnnotatedLexer,
+    inst: Inst,
+    next_node: Info,
+) -> Result<ParserNode, LexError> {
+    use LexError::{Expected, IgnoredWithWarning, NeedTwoNodes};
+
+    match Type::from(&inst) {
+        Type::CsrI(inst) => {
+            let rd = lex.get_reg()?;
+            let csr = lex.get_csrimm()?;
+            let imm = lex.get_imm()?;
+            Ok(ParserNode::new_csri(
+                With::new(inst, next_node),
+                rd,
+                csr,
+                imm,
+                lex.raw_token.clone(),
+            ))
+        }
+        Type::Csr(inst) => {
+            let rd = lex.get_reg()?;
+            let csr = lex.get_csrimm()?;
+            let rs1 = lex.get_reg()?;
+            Ok(ParserNode::new_csr(
+                With::new(inst, next_node),
+                rd,
+                csr,
+                rs1,
+                lex.raw_token.clone(),
+            ))
+        }
+        Type::UpperArith(inst) => {
+            let rd = lex.get_reg()?;
+            let (imm, reloc) = lex.get_imm_or_relocation()?;
+            Ok(ParserNode::new_upper_arith(
+                With::new(inst, next_node),
+                rd,
+                imm,
+                lex.raw_token.clone(),
+            )
+            .with_relocation(reloc))
+        }
+        Type::Arith(inst) => {
+            let rd = lex.get_reg()?;
+            let rs1 = lex.get_reg()?;
+            let rs2 = lex.get_reg()?;
+            Ok(ParserNode::new_arith(
+                With::new(inst, next_node),
+                rd,
+                rs1,
+                rs2,
+                lex.raw_token.clone(),
+            ))
+        }
+        Type::IArith(inst) => {
+            let rd = lex.get_reg()?;
+            let rs1 = lex.get_reg()?;
+            let (imm, reloc) = lex.get_imm_or_relocation()?;
+            Ok(ParserNode::new_iarith(
+                With::new(inst, next_node),
+                rd,
+                rs1,
+                imm,
+                lex.raw_token.clone(),
+            )
+            .with_relocation(reloc))
+        }
+
+        Type::JumpLink(inst) => {
+            let next = lex.get_operand(&[ExpectedType::Register, ExpectedType::Label])?;
+
+            if let Ok(reg) = next.as_reg() {
+                let name = lex.get_label()?;
+                Ok(ParserNode::new_jump_link(
+                    With::new(inst, next_node),
+                    reg,
+                    name,
+                    lex.raw_token.clone(),
+                ))
+            } else if let Ok(name) = next.as_label() {
+                Ok(ParserNode::new_jump_link(
+                    With::new(inst, next_node.clone()),
+                    With::new(Register::X1, next_node),
+                    name,
+                    lex.raw_token.clone(),
+                ))
+            } else {
+                Err(Expected(
+                    vec![ExpectedType::Register, ExpectedType::Label],
+                    next,
+                ))
+            }
+        }
+        Type::JumpLinkR(inst) => {
+            let reg1 = lex.get_reg()?;
+            if lex.at_end_of_line() {
+                return Ok(ParserNode::new_jump_link_r(
+                    With::new(inst, next_node.clone()),
+                    With::new(Register::X1, next_node.clone()),
+                    reg1,
+                    With::new(Imm(0), next_node),
+                    lex.raw_token.clone(),
+                ));
+            }
+            let next = lex.get_operand(&[
+                ExpectedType::Register,
+                ExpectedType::Imm,
+                ExpectedType::LParen,
+            ])?;
+            if let Ok(rs1) = next.as_reg() {
+                let imm = lex.get_imm()?;
+                Ok(ParserNode::new_jump_link_r(
+                    With::new(inst, next_node),
+                    reg1,
+                    rs1,
+                    imm,
+                    lex.raw_token.clone(),
+                ))
+            } else if let Ok(imm) = lex.as_imm(&next) {
+                if let Ok(()) = lex.peek_any().and_then(|x| x.as_lparen()) {
+                    lex.get_any()?;
+                    let rs1 = lex.get_reg()?;
+                    lex.expect_rparen()?;
+                    Ok(ParserNode::new_jump_link_r(
+                        With::new(inst, next_node),
+                        reg1,
+                        rs1,
+                        imm,
+                        lex.raw_token.clone(),
+                    ))
+                } else {
+                    Ok(ParserNode::new_jump_link_r(
+                        With::new(inst, next_node.clone()),
+                        With::new(Register::X1, next_node),
+                        reg1,
+                        imm,
+                        lex.raw_token.clone(),
+                    ))
+                }
+            } else if let Ok(()) = next.as_lparen() {
+                let rs1 = lex.get_reg()?;
+                lex.expect_rparen()?;
+                Ok(ParserNode::new_jump_link_r(
+                    With::new(inst, next_node.clone()),
+                    reg1,
+                    rs1,
+                    With::new(Imm(0), next_node),
+                    lex.raw_token.clone(),
+                ))
+            } else {
+                Err(Expected(
+                    vec![ExpectedType::Register, ExpectedType::Imm, ExpectedType::LParen],
+                    next,
+                ))
+            }
+        }
+        Type::Load(inst) => {
+            let rd = lex.get_reg_of(inst.is_float())?;
+            let next = lex.get_operand(&[
+                ExpectedType::Label,
+                ExpectedType::Imm,
+                ExpectedType::LParen,
+            ])?;
+            if let Ok((imm, reloc)) = lex.as_imm_or_relocation(&next) {
+                if let Ok(()) = lex.peek_any().and_then(|x| x.as_lparen()) {
+                    lex.get_any()?;
+                    let rs1 = lex.get_reg()?;
+                    lex.expect_rparen()?;
+                    Ok(ParserNode::new_load(
+                        With::new(inst, next_node),
+                        rd,
+                        rs1,
+                        imm,
+                        lex.raw_token.clone(),
+                    )
+                    .with_relocation(reloc))
+                } else {
+                    Ok(ParserNode::new_load(
+                        With::new(inst, next_node.clone()),
+                        rd,
+                        With::new(Register::X0, next_node),
+                        imm,
+                        lex.raw_token.clone(),
+                    )
+                    .with_relocation(reloc))
+                }
+            } else if let Ok(label) = next.as_label() {
+                // A floating-point register cannot hold the address, so
+                // an integer register is given after the label
+                let temp_reg = if inst.is_float() {
+                    lex.get_reg()?
+                } else {
+                    rd.clone()
+                };
+                Err(NeedTwoNodes(
+                    Box::new(ParserNode::new_load_addr(
+                        With::new(PseudoType::La, next_node.clone()),
+                        temp_reg.clone(),
+                        label,
+                        lex.raw_token.clone(),
+                    )),
+                    Box::new(ParserNode::new_load(
+                        With::new(inst, next_node.clone()),
+                        rd,
+                        temp_reg,
+                        With::new(Imm(0), next_node),
+                        lex.raw_token.clone(),
+                    )),
+                ))
+            } else if let Ok(()) = next.as_lparen() {
+                let rs1 = lex.get_reg()?;
+                lex.expect_rparen()?;
+                Ok(ParserNode::new_load(
+                    With::new(inst, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Imm(0), next_node),
+                    lex.raw_token.clone(),
+                ))
+            } else {
+                Err(Expected(
+                    vec![
+                        ExpectedType::Label,
+                        ExpectedType::Imm,
+                        ExpectedType::LParen,
+                    ],
+                    next,
+                ))
+            }
+        }
+        Type::Store(inst) => {
+            let rs2 = lex.get_reg_of(inst.is_float())?;
+            let next = lex.get_operand(&[
+                ExpectedType::Label,
+                ExpectedType::Imm,
+                ExpectedType::LParen,
+            ])?;
+
+            if let Ok((imm, reloc)) = lex.as_imm_or_relocation(&next) {
+                if let Ok(()) = lex.peek_any().and_then(|x| x.as_lparen()) {
+                    lex.get_any()?;
+                    let rs1 = lex.get_reg()?;
+                    lex.expect_rparen()?;
+                    Ok(ParserNode::new_store(
+                        With::new(inst, next_node),
+                        rs1,
+                        rs2,
+                        imm,
+                        lex.raw_token.clone(),
+                    )
+                    .with_relocation(reloc))
+                } else if let Ok(tmp) = lex.peek_any().and_then(|x| x.as_reg()) {
+                    lex.get_any()?;
+                    Err(LexError::NeedTwoNodes(
+                        Box::new(
+                            ParserNode::new_iarith(
+                                With::new(IArithType::Addi, next_node.clone()),
+                                tmp.clone(),
+                                With::new(Register::X0, next_node.clone()),
+                                imm,
+                                lex.raw_token.clone(),
+                            )
+                            .with_relocation(reloc),
+                        ),
+                        Box::new(ParserNode::new_store(
+                            With::new(inst, next_node.clone()),
+                            tmp,
+                            rs2,
+                            With::new(Imm(0), next_node),
+                            lex.raw_token.clone(),
+                        )),
+                    ))
+                } else {
+                    Ok(ParserNode::new_store(
+                        With::new(inst, next_node.clone()),
+                        With::new(Register::X0, next_node),
+                        rs2,
+                        imm,
+                        lex.raw_token.clone(),
+                    )
+                    .with_relocation(reloc))
+                }
+            } else if let Ok(label) = next.as_label() {
+                let temp_reg = lex.get_reg()?;
+                Err(NeedTwoNodes(
+                    Box::new(ParserNode::new_load_addr(
+                        With::new(PseudoType::La, next_node.clone()),
+                        temp_reg.clone(),
+                        label,
+                        lex.raw_token.clone(),
+                    )),
+                    Box::new(ParserNode::new_store(
+                        With::new(inst, next_node.clone()),
+                        temp_reg,
+                        rs2,
+                        With::new(Imm(0), next_node),
+                        lex.raw_token.clone(),
+                    )),
+                ))
+            } else if let Ok(()) = next.as_lparen() {
+                let rs1 = lex.get_reg()?;
+                lex.expect_rparen()?;
+                Ok(ParserNode::new_store(
+                    With::new(inst, next_node.clone()),
+                    rs1,
+                    rs2,
+                    With::new(Imm(0), next_node),
+                    lex.raw_token.clone(),
+                ))
+            } else {
+                Err(Expected(
+                    vec![
+                        ExpectedType::Label,
+                        ExpectedType::Imm,
+                        ExpectedType::LParen,
+                    ],
+                    next,
+                ))
+            }
+        }
+        Type::FloatArith(inst) => {
+            let rd = lex.get_reg_of(inst.rd_is_float())?;
+            let float = inst.sources_are_float();
+            let rs1 = lex.get_reg_of(float)?;
+            let rs2 = if inst.source_count() >= 2 {
+                Some(lex.get_reg_of(float)?)
+            } else {
+                None
+            };
+            let rs3 = if inst.source_count() >= 3 {
+                Some(lex.get_reg_of(float)?)
+            } else {
+                None
+            };
+            if inst.takes_rounding_mode() {
+                lex.skip_rounding_mode()?;
+            }
+            Ok(ParserNode::new_float_arith(
+                With::new(inst, next_node),
+                rd,
+                rs1,
+                rs2,
+                rs3,
+                lex.raw_token.clone(),
+            ))
+        }
+        Type::Branch(inst) => {
+            let rs1 = lex.get_reg()?;
+            let rs2 = lex.get_reg()?;
+            let label = lex.get_label()?;
+            Ok(ParserNode::new_branch(
+                With::new(inst, next_node),
+                rs1,
+                rs2,
+                label,
+                lex.raw_token.clone(),
+            ))
+        }
+        Type::Ignore(_) => Err(IgnoredWithWarning(next_node)),
+        Type::Basic(inst) => Ok(ParserNode::new_basic(
+            With::new(inst, next_node),
+            lex.raw_token.clone(),
+        )),
+        Type::Pseudo(inst) => match inst {
+            PseudoType::Ret => {
+                Ok(ParserNode::new_jump_link_r(
+                    With::new(JumpLinkRType::Jalr, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    With::new(Register::X1, next_node.clone()),
+                    With::new(Imm(0), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::FmvS
+            | PseudoType::FnegS
+            | PseudoType::FabsS
+            | PseudoType::FmvD
+            | PseudoType::FnegD
+            | PseudoType::FabsD => {
+                // These are sign injections of a register with itself
+                let sign_inst = match inst {
+                    PseudoType::FmvS => FloatArithType::FsgnjS,
+                    PseudoType::FnegS => FloatArithType::FsgnjnS,
+                    PseudoType::FabsS => FloatArithType::FsgnjxS,
+                    PseudoType::FmvD => FloatArithType::FsgnjD,
+                    PseudoType::FnegD => FloatArithType::FsgnjnD,
+                    _ => FloatArithType::FsgnjxD,
+                };
+                let rd = lex.get_freg()?;
+                let rs = lex.get_freg()?;
+                Ok(ParserNode::new_float_arith(
+                    With::new(sign_inst, next_node),
+                    rd,
+                    rs.clone(),
+                    Some(rs),
+                    None,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Mv => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_arith(
+                    With::new(ArithType::Add, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Register::X0, next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Li => {
+                let rd = lex.get_reg()?;
+                let imm = lex.get_imm()?;
+                if (-2048..2048).contains(&imm.data.0) {
+                    return Ok(ParserNode::new_iarith(
+                        With::new(IArithType::Addi, next_node.clone()),
+                        rd,
+                        With::new(Register::X0, imm.info()),
+                        imm,
+                        lex.raw_token.clone(),
+                    ));
+                }
+
+                // The lower 12 bits are sign-extended by addi, so the upper
+                // bits are rounded up when they are negative. Immediates are
+                // 32-bit, so two instructions are always enough.
+                let lower = (imm.data.0 << 20) >> 20;
+                let upper = (imm.data.0.wrapping_sub(lower) >> 12) & 0xf_ffff;
+                let upper = ParserNode::new_upper_arith(
+                    With::new(UpperArithType::Lui, next_node.clone()),
+                    rd.clone(),
+                    With::new(Imm(upper), imm.info()),
+                    lex.raw_token.clone(),
+                );
+                if lower == 0 {
+                    return Ok(upper);
+                }
+                Err(NeedTwoNodes(
+                    Box::new(upper),
+                    Box::new(ParserNode::new_iarith(
+                        With::new(IArithType::Addi, next_node.clone()),
+                        rd.clone(),
+                        rd,
+                        With::new(Imm(lower), imm.info()),
+                        lex.raw_token.clone(),
+                    )),
+                ))
+            }
+            PseudoType::La => {
+                let rd = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_load_addr(
+                    With::new(PseudoType::La, next_node.clone()),
+                    rd,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::J | PseudoType::B | PseudoType::Tail => {
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_jump_link(
+                    With::new(JumpLinkType::Jal, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Jr => {
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_jump_link_r(
+                    With::new(JumpLinkRType::Jalr, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    rs1,
+                    With::new(Imm(0), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Beqz => {
+                let rs1 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Beq, next_node.clone()),
+                    rs1,
+                    With::new(Register::X0, next_node.clone()),
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bnez => {
+                let rs1 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Bne, next_node.clone()),
+                    rs1,
+                    With::new(Register::X0, next_node.clone()),
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bltz => {
+                let rs1 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Blt, next_node.clone()),
+                    rs1,
+                    With::new(Register::X0, next_node.clone()),
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bgtz => {
+                let rs2 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Blt, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    rs2,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Neg => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_arith(
+                    With::new(ArithType::Sub, next_node.clone()),
+                    rd,
+                    With::new(Register::X0, next_node.clone()),
+                    rs1,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Negw => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_arith(
+                    With::new(ArithType::Subw, next_node.clone()),
+                    rd,
+                    With::new(Register::X0, next_node.clone()),
+                    rs1,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::SextW => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_iarith(
+                    With::new(IArithType::Addiw, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Imm(0), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::ZextB => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_iarith(
+                    With::new(IArithType::Andi, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Imm(255), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Not => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_iarith(
+                    With::new(IArithType::Xori, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Imm(-1), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Seqz => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_iarith(
+                    With::new(IArithType::Sltiu, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Imm(1), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Snez => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_iarith(
+                    With::new(IArithType::Sltiu, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Imm(0), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Nop => {
+                Ok(ParserNode::new_iarith(
+                    With::new(IArithType::Addi, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    With::new(Imm(0), next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bgez => {
+                let rs1 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Bge, next_node.clone()),
+                    rs1,
+                    With::new(Register::X0, next_node.clone()),
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Sgtz => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_arith(
+                    With::new(ArithType::Slt, next_node.clone()),
+                    rd,
+                    With::new(Register::X0, next_node.clone()),
+                    rs1,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Sltz => {
+                let rd = lex.get_reg()?;
+                let rs1 = lex.get_reg()?;
+                Ok(ParserNode::new_arith(
+                    With::new(ArithType::Slt, next_node.clone()),
+                    rd,
+                    rs1,
+                    With::new(Register::X0, next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Blez | PseudoType::Sgez => {
+                let rs1 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Bge, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    rs1,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Call => {
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_jump_link(
+                    With::new(JumpLinkType::Jal, next_node.clone()),
+                    With::new(Register::X1, next_node.clone()),
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bgt => {
+                let rs1 = lex.get_reg()?;
+                let rs2 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Blt, next_node.clone()),
+                    rs2,
+                    rs1,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Ble => {
+                let rs1 = lex.get_reg()?;
+                let rs2 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Bge, next_node.clone()),
+                    rs2,
+                    rs1,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bgtu => {
+                let rs1 = lex.get_reg()?;
+                let rs2 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Bltu, next_node.clone()),
+                    rs2,
+                    rs1,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Bleu => {
+                let rs1 = lex.get_reg()?;
+                let rs2 = lex.get_reg()?;
+                let label = lex.get_label()?;
+                Ok(ParserNode::new_branch(
+                    With::new(BranchType::Bgeu, next_node.clone()),
+                    rs2,
+                    rs1,
+                    label,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Csrci | PseudoType::Csrsi | PseudoType::Csrwi => {
+                let csr = lex.get_csrimm()?;
+                let imm = lex.get_imm()?;
+                let inst = match inst {
+                    PseudoType::Csrci => CSRIType::Csrrci,
+                    PseudoType::Csrsi => CSRIType::Csrrsi,
+                    PseudoType::Csrwi => CSRIType::Csrrwi,
+                    _ => return Err(LexError::UnexpectedError(next_node.clone())),
+                };
+                Ok(ParserNode::new_csri(
+                    With::new(inst, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    csr,
+                    imm,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Csrc | PseudoType::Csrs | PseudoType::Csrw => {
+                // RARS writes the register first, like `csrw t0, mstatus`,
+                // and GNU writes the CSR first, like `csrw mstatus, t0`
+                let (rs1, csr) = if lex.next_is_reg() {
+                    let rs1 = lex.get_reg()?;
+                    (rs1, lex.get_csrimm()?)
+                } else {
+                    let csr = lex.get_csrimm()?;
+                    (lex.get_reg()?, csr)
+                };
+                let inst = match inst {
+                    PseudoType::Csrc => CSRType::Csrrc,
+                    PseudoType::Csrs => CSRType::Csrrs,
+                    PseudoType::Csrw => CSRType::Csrrw,
+                    _ => return Err(LexError::UnexpectedError(next_node.clone())),
+                };
+                Ok(ParserNode::new_csr(
+                    With::new(inst, next_node.clone()),
+                    With::new(Register::X0, next_node.clone()),
+                    csr,
+                    rs1,
+                    lex.raw_token.clone(),
+                ))
+            }
+            PseudoType::Csrr => {
+                let rd = lex.get_reg()?;
+                let csr = lex.get_csrimm()?;
+                Ok(ParserNode::new_csr(
+                    With::new(CSRType::Csrrs, next_node.clone()),
+                    rd,
+                    csr,
+                    With::new(Register::X0, next_node.clone()),
+                    lex.raw_token.clone(),
+                ))
+            }
+        },
+    }
 }
+
 impl TryFrom<&mut Peekable<Lexer>> for ParserNode {
     type Error = LexError;
 
+    fn try_from(val: &mut Peekable<Lexer>) -> Result<Self, Self::Error> {
+        ParserNode::parse(val, &Constants::default())
+    }
+}
+
+impl ParserNode {
     // TODO enforce that all "missing" values for With<> resolve to the token
     // of the instruction
 
+    /// Parse the next node, replacing the names of constants with their
+    /// values.
     #[allow(clippy::too_many_lines)]
-    fn try_from(val: &mut Peekable<Lexer>) -> Result<Self, Self::Error> {
-        use LexError::{Expected, IgnoredWithWarning, IsNewline, NeedTwoNodes};
+    pub(crate) fn parse(
+        val: &mut Peekable<Lexer>,
+        constants: &Constants,
+    ) -> Result<Self, LexError> {
+        use LexError::IsNewline;
 
         let mut lex = AnnotatedLexer {
             lexer: val,
+            constants,
             raw_token: RawToken::default(),
+            operands: 0,
+            errors: Vec::new(),
         };
 
         let next_node = lex.get_any()?;
         match &next_node.token {
             Token::Symbol(s) => {
                 if let Ok(inst) = Inst::from_str(s) {
-                    let node = match Type::from(&inst) {
-                        Type::CsrI(inst) => {
-                            let rd = lex.get_reg()?;
-                            let csr = lex.get_csrimm()?;
-                            let imm = lex.get_imm()?;
-                            Ok(ParserNode::new_csri(
-                                With::new(inst, next_node),
-                                rd,
-                                csr,
-                                imm,
-                                lex.raw_token,
-                            ))
-                        }
-                        Type::Csr(inst) => {
-                            let rd = lex.get_reg()?;
-                            let csr = lex.get_csrimm()?;
-                            let rs1 = lex.get_reg()?;
-                            Ok(ParserNode::new_csr(
-                                With::new(inst, next_node),
-                                rd,
-                                csr,
-                                rs1,
-                                lex.raw_token,
-                            ))
-                        }
-                        Type::UpperArith(inst) => {
-                            let rd = lex.get_reg()?;
-                            let mut imm = lex.get_imm()?;
-                            // shift left by 12
-                            imm.data.0 <<= 12;
-                            Ok(ParserNode::new_iarith(
-                                With::new(inst, next_node.clone()),
-                                rd,
-                                With::new(Register::X0, next_node),
-                                imm,
-                                lex.raw_token,
-                            ))
-                        }
-                        Type::Arith(inst) => {
-                            let rd = lex.get_reg()?;
-                            let rs1 = lex.get_reg()?;
-                            let rs2 = lex.get_reg()?;
-                            Ok(ParserNode::new_arith(
-                                With::new(inst, next_node),
-                                rd,
-                                rs1,
-                                rs2,
-                                lex.raw_token,
-                            ))
-                        }
-                        Type::IArith(inst) => {
-                            let rd = lex.get_reg()?;
-                            let rs1 = lex.get_reg()?;
-                            let imm = lex.get_imm()?;
-                            Ok(ParserNode::new_iarith(
-                                With::new(inst, next_node),
-                                rd,
-                                rs1,
-                                imm,
-                                lex.raw_token,
-                            ))
-                        }
-
-                        Type::JumpLink(inst) => {
-                            let next = lex.get_any()?;
-
-                            return if let Ok(reg) = next.as_reg() {
-                                let name = lex.get_label()?;
-                                Ok(ParserNode::new_jump_link(
-                                    With::new(inst, next_node),
-                                    reg,
-                                    name,
-                                    lex.raw_token,
-                                ))
-                            } else if let Ok(name) = next.as_label() {
-                                Ok(ParserNode::new_jump_link(
-                                    With::new(inst, next_node.clone()),
-                                    With::new(Register::X1, next_node),
-                                    name,
-                                    lex.raw_token,
-                                ))
-                            } else {
-                                Err(Expected(
-                                    vec![ExpectedType::Register, ExpectedType::Label],
-                                    next,
-                                ))
-                            };
-                        }
-                        Type::JumpLinkR(inst) => {
-                            let reg1 = lex.get_reg()?;
-                            let next = lex.get_any()?;
-                            return if let Ok(rs1) = next.as_reg() {
-                                let imm = lex.get_imm()?;
-                                Ok(ParserNode::new_jump_link_r(
-                                    With::new(inst, next_node),
-                                    reg1,
-                                    rs1,
-                                    imm,
-                                    lex.raw_token,
-                                ))
-                            } else if let Ok(imm) = next.as_imm() {
-                                if let Ok(()) = lex.peek_any()?.as_lparen() {
-                                    lex.get_any()?;
-                                    let rs1 = lex.get_reg()?;
-                                    lex.expect_rparen()?;
-                                    Ok(ParserNode::new_jump_link_r(
-                                        With::new(inst, next_node),
-                                        reg1,
-                                        rs1,
-                                        imm,
-                                        lex.raw_token,
-                                    ))
-                                } else {
-                                    Ok(ParserNode::new_jump_link_r(
-                                        With::new(inst, next_node.clone()),
-                                        With::new(Register::X1, next_node),
-                                        reg1,
-                                        imm,
-                                        lex.raw_token,
-                                    ))
-                                }
-                            } else if let Ok(()) = next.as_lparen() {
-                                let rs1 = lex.get_reg()?;
-                                lex.expect_rparen()?;
-                                Ok(ParserNode::new_jump_link_r(
-                                    With::new(inst, next_node.clone()),
-                                    reg1,
-                                    rs1,
-                                    With::new(Imm(0), next_node),
-                                    lex.raw_token,
-                                ))
-                            } else {
-                                Ok(ParserNode::new_jump_link_r(
-                                    With::new(inst, next_node.clone()),
-                                    With::new(Register::X1, next_node.clone()),
-                                    reg1,
-                                    With::new(Imm(0), next_node),
-                                    lex.raw_token,
-                                ))
-                            };
-                        }
-                        Type::Load(inst) => {
-                            let rd = lex.get_reg()?;
-                            let next = lex.get_any()?;
-                            return if let Ok(imm) = next.as_imm() {
-                                if let Ok(()) = lex.peek_any()?.as_lparen() {
-                                    lex.get_any()?;
-                                    let rs1 = lex.get_reg()?;
-                                    lex.expect_rparen()?;
-                                    Ok(ParserNode::new_load(
-                                        With::new(inst, next_node),
-                                        rd,
-                                        rs1,
-                                        imm,
-                                        lex.raw_token,
-                                    ))
-                                } else {
-                                    Ok(ParserNode::new_load(
-                                        With::new(inst, next_node.clone()),
-                                        rd,
-                                        With::new(Register::X0, next_node),
-                                        imm,
-                                        lex.raw_token,
-                                    ))
-                                }
-                            } else if let Ok(label) = next.as_label() {
-                                Err(NeedTwoNodes(
-                                    Box::new(ParserNode::new_load_addr(
-                                        With::new(PseudoType::La, next_node.clone()),
-                                        rd.clone(),
-                                        label,
-                                        lex.raw_token.clone(),
-                                    )),
-                                    Box::new(ParserNode::new_load(
-                                        With::new(inst, next_node.clone()),
-                                        rd.clone(),
-                                        rd,
-                                        With::new(Imm(0), next_node),
-                                        lex.raw_token,
-                                    )),
-                                ))
-                            } else if let Ok(()) = next.as_lparen() {
-                                let rs1 = lex.get_reg()?;
-                                lex.expect_rparen()?;
-                                Ok(ParserNode::new_load(
-                                    With::new(inst, next_node.clone()),
-                                    rd,
-                                    rs1,
-                                    With::new(Imm(0), next_node),
-                                    lex.raw_token,
-                                ))
-                            } else {
-                                Err(Expected(
-                                    vec![
-                                        ExpectedType::Label,
-                                        ExpectedType::Imm,
-                                        ExpectedType::LParen,
-                                    ],
-                                    next,
-                                ))
-                            };
-                        }
-                        Type::Store(inst) => {
-                            let rs2 = lex.get_reg()?;
-                            let next = lex.get_any()?;
-
-                            return if let Ok(imm) = next.as_imm() {
-                                if let Ok(()) = lex.peek_any()?.as_lparen() {
-                                    lex.get_any()?;
-                                    let rs1 = lex.get_reg()?;
-                                    lex.expect_rparen()?;
-                                    Ok(ParserNode::new_store(
-                                        With::new(inst, next_node),
-                                        rs1,
-                                        rs2,
-                                        imm,
-                                        lex.raw_token,
-                                    ))
-                                } else if let Ok(tmp) = lex.peek_any()?.as_reg() {
-                                    lex.get_any()?;
-                                    Err(LexError::NeedTwoNodes(
-                                        Box::new(ParserNode::new_iarith(
-                                            With::new(IArithType::Addi, next_node.clone()),
-                                            tmp.clone(),
-                                            With::new(Register::X0, next_node.clone()),
-                                            imm,
-                                            lex.raw_token.clone(),
-                                        )),
-                                        Box::new(ParserNode::new_store(
-                                            With::new(inst, next_node.clone()),
-                                            tmp,
-                                            rs2,
-                                            With::new(Imm(0), next_node),
-                                            lex.raw_token,
-                                        )),
-                                    ))
-                                } else {
-                                    Ok(ParserNode::new_store(
-                                        With::new(inst, next_node.clone()),
-                                        With::new(Register::X0, next_node),
-                                        rs2,
-                                        imm,
-                                        lex.raw_token,
-                                    ))
-                                }
-                            } else if let Ok(label) = next.as_label() {
-                                let temp_reg = lex.get_reg()?;
-                                Err(NeedTwoNodes(
-                                    Box::new(ParserNode::new_load_addr(
-                                        With::new(PseudoType::La, next_node.clone()),
-                                        temp_reg.clone(),
-                                        label,
-                                        lex.raw_token.clone(),
-                                    )),
-                                    Box::new(ParserNode::new_store(
-                                        With::new(inst, next_node.clone()),
-                                        temp_reg,
-                                        rs2,
-                                        With::new(Imm(0), next_node),
-                                        lex.raw_token,
-                                    )),
-                                ))
-                            } else if let Ok(()) = next.as_lparen() {
-                                let rs1 = lex.get_reg()?;
-                                lex.expect_rparen()?;
-                                Ok(ParserNode::new_store(
-                                    With::new(inst, next_node.clone()),
-                                    rs1,
-                                    rs2,
-                                    With::new(Imm(0), next_node),
-                                    lex.raw_token,
-                                ))
-                            } else {
-                                Err(Expected(
-                                    vec![
-                                        ExpectedType::Label,
-                                        ExpectedType::Imm,
-                                        ExpectedType::LParen,
-                                    ],
-                                    next,
-                                ))
-                            };
-                        }
-                        Type::Branch(inst) => {
-                            let rs1 = lex.get_reg()?;
-                            let rs2 = lex.get_reg()?;
-                            let label = lex.get_label()?;
-                            Ok(ParserNode::new_branch(
-                                With::new(inst, next_node),
-                                rs1,
-                                rs2,
-                                label,
-                                lex.raw_token,
-                            ))
-                        }
-                        Type::Ignore(_) => Err(IgnoredWithWarning(next_node)),
-                        Type::Basic(inst) => Ok(ParserNode::new_basic(
-                            With::new(inst, next_node),
-                            lex.raw_token,
-                        )),
-                        Type::Pseudo(inst) => match inst {
-                            PseudoType::Ret => {
-                                return Ok(ParserNode::new_jump_link_r(
-                                    With::new(JumpLinkRType::Jalr, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    With::new(Register::X1, next_node.clone()),
-                                    With::new(Imm(0), next_node.clone()),
-                                    lex.raw_token,
-                                ))
-                            }
-                            PseudoType::Mv => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_arith(
-                                    With::new(ArithType::Add, next_node.clone()),
-                                    rd,
-                                    rs1,
-                                    With::new(Register::X0, next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Li => {
-                                let rd = lex.get_reg()?;
-                                let imm = lex.get_imm()?;
-                                return Ok(ParserNode::new_iarith(
-                                    With::new(IArithType::Addi, next_node.clone()),
-                                    rd,
-                                    With::new(Register::X0, imm.info()),
-                                    imm,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::La => {
-                                let rd = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_load_addr(
-                                    With::new(PseudoType::La, next_node.clone()),
-                                    rd,
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::J | PseudoType::B => {
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_jump_link(
-                                    With::new(JumpLinkType::Jal, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Jr => {
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_jump_link_r(
-                                    With::new(JumpLinkRType::Jalr, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    rs1,
-                                    With::new(Imm(0), next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Beqz => {
-                                let rs1 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Beq, next_node.clone()),
-                                    rs1,
-                                    With::new(Register::X0, next_node.clone()),
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Bnez => {
-                                let rs1 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Bne, next_node.clone()),
-                                    rs1,
-                                    With::new(Register::X0, next_node.clone()),
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Bltz | PseudoType::Bgtz => {
-                                let rs1 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Blt, next_node.clone()),
-                                    rs1,
-                                    With::new(Register::X0, next_node.clone()),
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Neg => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_arith(
-                                    With::new(ArithType::Sub, next_node.clone()),
-                                    rd,
-                                    With::new(Register::X0, next_node.clone()),
-                                    rs1,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Not => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_iarith(
-                                    With::new(IArithType::Xori, next_node.clone()),
-                                    rd,
-                                    rs1,
-                                    With::new(Imm(-1), next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Seqz => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_iarith(
-                                    With::new(IArithType::Sltiu, next_node.clone()),
-                                    rd,
-                                    rs1,
-                                    With::new(Imm(1), next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Snez => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_iarith(
-                                    With::new(IArithType::Sltiu, next_node.clone()),
-                                    rd,
-                                    rs1,
-                                    With::new(Imm(0), next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Nop => {
-                                return Ok(ParserNode::new_iarith(
-                                    With::new(IArithType::Addi, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    With::new(Imm(0), next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Bgez | PseudoType::Blez => {
-                                let rs1 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Bge, next_node.clone()),
-                                    rs1,
-                                    With::new(Register::X0, next_node.clone()),
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Sgtz => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_arith(
-                                    With::new(ArithType::Slt, next_node.clone()),
-                                    rd,
-                                    With::new(Register::X0, next_node.clone()),
-                                    rs1,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Sltz => {
-                                let rd = lex.get_reg()?;
-                                let rs1 = lex.get_reg()?;
-                                return Ok(ParserNode::new_arith(
-                                    With::new(ArithType::Slt, next_node.clone()),
-                                    rd,
-                                    rs1,
-                                    With::new(Register::X0, next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Sgez => {
-                                let rs1 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Bge, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    rs1,
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Call => {
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_jump_link(
-                                    With::new(JumpLinkType::Jal, next_node.clone()),
-                                    With::new(Register::X1, next_node.clone()),
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Bgt => {
-                                let rs1 = lex.get_reg()?;
-                                let rs2 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Blt, next_node.clone()),
-                                    rs2,
-                                    rs1,
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Ble => {
-                                let rs1 = lex.get_reg()?;
-                                let rs2 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Bge, next_node.clone()),
-                                    rs2,
-                                    rs1,
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Bgtu => {
-                                let rs1 = lex.get_reg()?;
-                                let rs2 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Bltu, next_node.clone()),
-                                    rs2,
-                                    rs1,
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Bleu => {
-                                let rs1 = lex.get_reg()?;
-                                let rs2 = lex.get_reg()?;
-                                let label = lex.get_label()?;
-                                return Ok(ParserNode::new_branch(
-                                    With::new(BranchType::Bgeu, next_node.clone()),
-                                    rs2,
-                                    rs1,
-                                    label,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Csrci | PseudoType::Csrsi | PseudoType::Csrwi => {
-                                let csr = lex.get_csrimm()?;
-                                let imm = lex.get_imm()?;
-                                let inst = match inst {
-                                    PseudoType::Csrci => CSRIType::Csrrci,
-                                    PseudoType::Csrsi => CSRIType::Csrrsi,
-                                    PseudoType::Csrwi => CSRIType::Csrrwi,
-                                    _ => return Err(LexError::UnexpectedError(next_node.clone())),
-                                };
-                                return Ok(ParserNode::new_csri(
-                                    With::new(inst, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    csr,
-                                    imm,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Csrc | PseudoType::Csrs | PseudoType::Csrw => {
-                                let rs1 = lex.get_reg()?;
-                                let csr = lex.get_csrimm()?;
-                                let inst = match inst {
-                                    PseudoType::Csrc => CSRType::Csrrc,
-                                    PseudoType::Csrs => CSRType::Csrrs,
-                                    PseudoType::Csrw => CSRType::Csrrw,
-                                    _ => return Err(LexError::UnexpectedError(next_node.clone())),
-                                };
-                                return Ok(ParserNode::new_csr(
-                                    With::new(inst, next_node.clone()),
-                                    With::new(Register::X0, next_node.clone()),
-                                    csr,
-                                    rs1,
-                                    lex.raw_token,
-                                ));
-                            }
-                            PseudoType::Csrr => {
-                                let rd = lex.get_reg()?;
-                                let csr = lex.get_csrimm()?;
-                                return Ok(ParserNode::new_csr(
-                                    With::new(CSRType::Csrrs, next_node.clone()),
-                                    rd,
-                                    csr,
-                                    With::new(Register::X0, next_node.clone()),
-                                    lex.raw_token,
-                                ));
-                            }
-                        },
-                    };
-                    return node;
+                    let node = parse_instruction(&mut lex, inst, next_node);
+                    let node = lex.check_operand_count(inst, node);
+                    return lex.with_recovered_errors(node);
                 }
                 Err(LexError::Expected(
                     vec![ExpectedType::Inst],
@@ -922,9 +1801,12 @@ impl TryFrom<&mut Peekable<Lexer>> for ParserNode {
             }
             Token::Label(s) => Ok(ParserNode::new_label(
                 With::new(
-                    LabelString::from_str(s).map_err(|()| {
-                        LexError::Expected(vec![ExpectedType::Label], next_node.clone())
-                    })?,
+                    LabelString::from_str(s)
+                        .ok()
+                        .or_else(|| LabelString::local_definition(s))
+                        .ok_or_else(|| {
+                            LexError::Expected(vec![ExpectedType::Label], next_node.clone())
+                        })?,
                     next_node,
                 ),
                 lex.raw_token,
@@ -932,11 +1814,16 @@ impl TryFrom<&mut Peekable<Lexer>> for ParserNode {
             Token::Directive(dir) => {
                 if let Ok(directive) = DirectiveToken::from_str(dir) {
                     match directive {
-                        DirectiveToken::Align => {
-                            let imm = lex.get_imm()?;
+                        DirectiveToken::Align | DirectiveToken::Balign => {
+                            let value = lex.get_imm()?;
+                            let mode = match directive {
+                                DirectiveToken::Balign => AlignMode::Bytes,
+                                _ => AlignMode::default(),
+                            };
+                            let bytes = mode.bytes(value.data.0);
                             Ok(ParserNode::new_directive(
                                 With::new(directive, next_node.clone()),
-                                DirectiveType::Align(imm),
+                                DirectiveType::Align { value, bytes },
                                 lex.raw_token,
                             ))
                         }
@@ -980,17 +1867,38 @@ impl TryFrom<&mut Peekable<Lexer>> for ParserNode {
 
                             // keep looping through values until immediate or nl is
                             // not found
+                            let is_float =
+                                matches!(data_type, DataType::Float | DataType::Double);
+                            let is_address =
+                                matches!(data_type, DataType::Word | DataType::Dword);
                             let mut values = Vec::new();
-                            loop {
-                                let next = lex.peek_any()?;
+                            let mut line_ended = false;
+                            // the values can end at the end of the file
+                            while let Ok(next) = lex.peek_any() {
                                 if let Token::Newline = next.token {
                                     // consume newline
                                     lex.get_any()?;
-                                    continue;
-                                } else if let Ok(imm) = next.as_imm() {
+                                    line_ended = true;
+                                } else if is_float && next.is_numeric() {
+                                    // any number must be a valid float
+                                    lex.get_any()?;
+                                    let value = next.as_float_imm()?;
+                                    values.push(With::new(DataValue::Float(value.data), next));
+                                } else if let (false, Ok(imm)) = (is_float, lex.as_imm(&next)) {
                                     // try to get immediate
                                     lex.get_any()?;
-                                    values.push(imm);
+                                    values.push(With::new(DataValue::Int(imm.data), next));
+                                } else if let (true, false, Ok(label)) =
+                                    (is_address, line_ended, next.as_label())
+                                {
+                                    // labels are only taken from the line of the
+                                    // directive, as the next line can start with an
+                                    // instruction
+                                    if label.data.local_reference().is_some() {
+                                        break;
+                                    }
+                                    lex.get_any()?;
+                                    values.push(With::new(DataValue::Label(label.data), next));
                                 } else {
                                     break;
                                 }
@@ -1023,11 +1931,69 @@ impl TryFrom<&mut Peekable<Lexer>> for ParserNode {
                             Err(LexError::IgnoredWithWarning(next_node))
                         }
                         DirectiveToken::EndMacro => Err(LexError::IgnoredWithWarning(next_node)),
-                        DirectiveToken::Section
-                        | DirectiveToken::Extern
-                        | DirectiveToken::Eqv
-                        | DirectiveToken::Global
-                        | DirectiveToken::Globl => Err(LexError::UnsupportedDirective(next_node)),
+                        DirectiveToken::Rept => {
+                            // the body is read and repeated by the parser
+                            let count = lex.get_imm()?;
+                            Err(LexError::Repeat(Box::new(next_node), Box::new(count)))
+                        }
+                        DirectiveToken::Endr => Err(LexError::UnexpectedToken(next_node)),
+                        DirectiveToken::Section => {
+                            let name = lex.get_section_name()?;
+                            // Flags like `"ax", @progbits` do not change the
+                            // segment, but are kept to write the section out
+                            let mut flags = Vec::new();
+                            while !lex.at_end_of_line() {
+                                flags.push(lex.get_any()?.token.as_original_string());
+                            }
+                            let segment = Segment::from_section_name(&name.data);
+                            Ok(ParserNode::new_directive(
+                                With::new(directive, next_node.clone()),
+                                DirectiveType::Section {
+                                    name,
+                                    flags,
+                                    segment,
+                                },
+                                lex.raw_token,
+                            ))
+                        }
+                        DirectiveToken::Equ | DirectiveToken::Set | DirectiveToken::Eqv => {
+                            // the value was already collected before parsing
+                            let name = lex.get_operand(&[ExpectedType::Label])?;
+                            let name = match &name.token {
+                                Token::Symbol(s) if LabelString::from_str(s).is_ok() => {
+                                    With::new(s.clone(), name)
+                                }
+                                _ => {
+                                    return Err(LexError::Expected(
+                                        vec![ExpectedType::Label],
+                                        name,
+                                    ))
+                                }
+                            };
+                            let value = lex.get_imm()?;
+                            Ok(ParserNode::new_directive(
+                                With::new(directive, next_node.clone()),
+                                DirectiveType::Constant { name, value },
+                                lex.raw_token,
+                            ))
+                        }
+                        DirectiveToken::Global | DirectiveToken::Globl | DirectiveToken::Extern => {
+                            // keep reading labels until the end of the line
+                            let mut labels = vec![lex.get_label()?];
+                            while !lex.at_end_of_line() {
+                                labels.push(lex.get_label()?);
+                            }
+                            let dir_type = if directive == DirectiveToken::Extern {
+                                DirectiveType::Extern(labels)
+                            } else {
+                                DirectiveType::Global(labels)
+                            };
+                            Ok(ParserNode::new_directive(
+                                With::new(directive, next_node.clone()),
+                                dir_type,
+                                lex.raw_token,
+                            ))
+                        }
                         DirectiveToken::Include => {
                             let filename = lex.get_string()?;
                             Ok(ParserNode::new_directive(
@@ -1058,8 +2024,202 @@ impl TryFrom<&mut Peekable<Lexer>> for ParserNode {
             Token::LParen | Token::RParen | Token::String(_) | Token::Char(_) => {
                 Err(LexError::UnexpectedToken(next_node))
             }
-            // Skip comment token
-            Token::Comment(_) => Err(LexError::IgnoredWithoutWarning),
+            // Comments are skipped, other than their annotations
+            Token::Comment(_) => Err(LexError::IsComment(next_node)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passes::DiagnosticLocation;
+    use crate::reader::FileReaderError;
+    use std::cell::RefCell;
+
+    /// A reader of files in memory that counts how many times each file is
+    /// lexed.
+    ///
+    /// The parser asks for the tab width of a file once for every lexer it
+    /// makes, so this is counted.
+    #[derive(Clone, Default)]
+    struct CountingReader {
+        files: HashMap<String, (Uuid, String)>,
+        lexed: RefCell<HashMap<Uuid, usize>>,
+    }
+
+    impl CountingReader {
+        fn set(&mut self, path: &str, text: &str) {
+            let uuid = self.files.get(path).map_or_else(Uuid::new_v4, |f| f.0);
+            self.files.insert(path.to_owned(), (uuid, text.to_owned()));
+        }
+
+        fn lexed(&self, path: &str) -> usize {
+            let uuid = self.files[path].0;
+            self.lexed.borrow().get(&uuid).copied().unwrap_or_default()
+        }
+    }
+
+    impl FileReader for CountingReader {
+        fn import_file(
+            &mut self,
+            path: &str,
+            _parent_file: Option<Uuid>,
+        ) -> Result<(Uuid, String), FileReaderError> {
+            self.files
+                .get(path)
+                .cloned()
+                .ok_or(FileReaderError::InternalFileNotFound)
+        }
+
+        fn get_text(&self, uuid: Uuid) -> Option<String> {
+            self.files
+                .values()
+                .find(|f| f.0 == uuid)
+                .map(|f| f.1.clone())
+        }
+
+        fn get_filename(&self, uuid: Uuid) -> Option<String> {
+            self.files
+                .iter()
+                .find(|f| f.1 .0 == uuid)
+                .map(|f| f.0.clone())
+        }
+
+        fn tab_width(&self, uuid: Uuid) -> usize {
+            *self.lexed.borrow_mut().entry(uuid).or_default() += 1;
+            1
+        }
+    }
+
+    const MAIN: &str = ".include \"lib.s\"\nmain:\n    jal fn_a\n    li a7, 10\n    ecall\n";
+    const LIB: &str = ".equ VALUE, 1\nfn_a:\n    li a0, VALUE\n    jall\n    ret\n";
+
+    fn parse(parser: &mut RVParser<CountingReader>) -> (Vec<String>, usize) {
+        let (nodes, errors) = parser.parse_from_file("main.s", false);
+        let nodes = nodes.iter().map(ToString::to_string).collect();
+        (nodes, errors.len())
+    }
+
+    #[test]
+    fn unchanged_files_are_not_parsed_again() {
+        let mut reader = CountingReader::default();
+        reader.set("main.s", MAIN);
+        reader.set("lib.s", LIB);
+        let mut parser = RVParser::new(reader);
+        parser.set_incremental(true);
+
+        let first = parse(&mut parser);
+        assert_eq!(first.1, 1);
+        assert_eq!(parser.reader.lexed("main.s"), 1);
+        assert_eq!(parser.reader.lexed("lib.s"), 1);
+
+        let second = parse(&mut parser);
+        assert_eq!(second, first);
+        assert_eq!(parser.reader.lexed("main.s"), 1);
+        assert_eq!(parser.reader.lexed("lib.s"), 1);
+    }
+
+    #[test]
+    fn only_changed_files_are_parsed_again() {
+        let mut reader = CountingReader::default();
+        reader.set("main.s", MAIN);
+        reader.set("lib.s", LIB);
+        let mut parser = RVParser::new(reader);
+        parser.set_incremental(true);
+        let (first, _) = parse(&mut parser);
+
+        // The included file has not changed, so it is reused
+        parser.reader.set("main.s", &format!("{MAIN}    nop\n"));
+        let (nodes, errors) = parse(&mut parser);
+        assert_eq!(parser.reader.lexed("main.s"), 2);
+        assert_eq!(parser.reader.lexed("lib.s"), 1);
+        assert_eq!(nodes.len(), first.len() + 1);
+        assert_eq!(nodes[..first.len()], first[..]);
+        assert_eq!(errors, 1);
+    }
+
+    #[test]
+    fn files_that_include_a_changed_file_are_parsed_again() {
+        let mut reader = CountingReader::default();
+        reader.set("main.s", MAIN);
+        reader.set("lib.s", LIB);
+        let mut parser = RVParser::new(reader);
+        parser.set_incremental(true);
+        parse(&mut parser);
+
+        parser.reader.set("lib.s", &LIB.replace("    jall\n", ""));
+        let (_, errors) = parse(&mut parser);
+        assert_eq!(errors, 0);
+        assert_eq!(parser.reader.lexed("main.s"), 2);
+        assert_eq!(parser.reader.lexed("lib.s"), 2);
+        assert_eq!(
+            parser.cached_includes(parser.reader.files["main.s"].0),
+            Some(vec![parser.reader.files["lib.s"].0])
+        );
+    }
+
+    #[test]
+    fn files_are_parsed_again_when_not_incremental() {
+        let mut reader = CountingReader::default();
+        reader.set("main.s", MAIN);
+        reader.set("lib.s", LIB);
+        let mut parser = RVParser::new(reader);
+        parse(&mut parser);
+        parse(&mut parser);
+        assert_eq!(parser.reader.lexed("main.s"), 2);
+        assert_eq!(parser.reader.lexed("lib.s"), 2);
+    }
+
+    #[test]
+    fn self_include_is_reported() {
+        let mut reader = CountingReader::default();
+        reader.set(
+            "main.s",
+            ".include \"main.s\"\nmain:\n    li a7, 10\n    ecall\n",
+        );
+        let mut parser = RVParser::new(reader);
+        let (nodes, errors) = parser.parse_from_file("main.s", false);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], ParseError::CyclicDependency(_)));
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(parser.reader.lexed("main.s"), 1);
+    }
+
+    #[test]
+    fn include_cycle_is_reported() {
+        let mut reader = CountingReader::default();
+        reader.set("main.s", MAIN);
+        reader.set("lib.s", &format!(".include \"main.s\"\n{LIB}"));
+        let mut parser = RVParser::new(reader);
+        parser.set_incremental(true);
+        let (_, errors) = parser.parse_from_file("main.s", false);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], ParseError::CyclicDependency(_)));
+        assert_eq!(parser.cached_includes(parser.reader.files["lib.s"].0), None);
+    }
+
+    #[test]
+    fn diamond_include_reads_shared_file_once() {
+        let mut reader = CountingReader::default();
+        reader.set(
+            "main.s",
+            ".include \"a.s\"\n.include \"b.s\"\nmain:\n    li a7, 10\n    ecall\n",
+        );
+        reader.set(
+            "a.s",
+            ".include \"shared.s\"\nfn_a:\n    jal shared\n    ret\n",
+        );
+        reader.set(
+            "b.s",
+            ".include \"shared.s\"\nfn_b:\n    jal shared\n    ret\n",
+        );
+        reader.set("shared.s", "shared:\n    ret\n");
+        let mut parser = RVParser::new(reader);
+        let (nodes, errors) = parser.parse_from_file("main.s", false);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(parser.reader.lexed("shared.s"), 1);
+        let shared = parser.reader.files["shared.s"].0;
+        assert_eq!(nodes.iter().filter(|n| n.file() == shared).count(), 2);
+    }
+}
@@ -46,6 +46,39 @@ pub enum Register {
     X29,
     X30,
     X31,
+    // Floating-point registers, used by the F and D extensions
+    F0,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    F25,
+    F26,
+    F27,
+    F28,
+    F29,
+    F30,
+    F31,
 }
 
 impl TryFrom<Info> for Register {
@@ -62,40 +95,30 @@ impl TryFrom<Info> for Register {
 impl FromStr for Register {
     type Err = ();
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Register::from_numeric_name(s)
+            .or_else(|_| Register::from_abi_name(s))
+            .map_err(|_| ())
+    }
+}
+
+/// The names that registers are displayed with, see [`Register::display`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegisterNames {
+    /// ABI names, like `a0` and `sp`.
+    #[default]
+    Abi,
+    /// Numeric names, like `x10` and `x2`.
+    Numeric,
+}
+
+impl FromStr for RegisterNames {
+    type Err = ();
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "x0" | "zero" => Ok(Register::X0),
-            "x1" | "ra" => Ok(Register::X1),
-            "x2" | "sp" => Ok(Register::X2),
-            "x3" | "gp" => Ok(Register::X3),
-            "x4" | "tp" => Ok(Register::X4),
-            "x5" | "t0" => Ok(Register::X5),
-            "x6" | "t1" => Ok(Register::X6),
-            "x7" | "t2" => Ok(Register::X7),
-            "x8" | "s0" | "fp" => Ok(Register::X8),
-            "x9" | "s1" => Ok(Register::X9),
-            "x10" | "a0" => Ok(Register::X10),
-            "x11" | "a1" => Ok(Register::X11),
-            "x12" | "a2" => Ok(Register::X12),
-            "x13" | "a3" => Ok(Register::X13),
-            "x14" | "a4" => Ok(Register::X14),
-            "x15" | "a5" => Ok(Register::X15),
-            "x16" | "a6" => Ok(Register::X16),
-            "x17" | "a7" => Ok(Register::X17),
-            "x18" | "s2" => Ok(Register::X18),
-            "x19" | "s3" => Ok(Register::X19),
-            "x20" | "s4" => Ok(Register::X20),
-            "x21" | "s5" => Ok(Register::X21),
-            "x22" | "s6" => Ok(Register::X22),
-            "x23" | "s7" => Ok(Register::X23),
-            "x24" | "s8" => Ok(Register::X24),
-            "x25" | "s9" => Ok(Register::X25),
-            "x26" | "s10" => Ok(Register::X26),
-            "x27" | "s11" => Ok(Register::X27),
-            "x28" | "t3" => Ok(Register::X28),
-            "x29" | "t4" => Ok(Register::X29),
-            "x30" | "t5" => Ok(Register::X30),
-            "x31" | "t6" => Ok(Register::X31),
+            "abi" => Ok(RegisterNames::Abi),
+            "numeric" => Ok(RegisterNames::Numeric),
             _ => Err(()),
         }
     }
@@ -111,6 +134,191 @@ impl Display for ParseRegisterError {
 }
 
 impl Register {
+    /// Returns a register from its ABI name, like `a0` or `ft0`.
+    ///
+    /// Both `s0` and `fp` are names of `x8`.
+    pub fn from_abi_name(name: &str) -> Result<Register, ParseRegisterError> {
+        Ok(match name {
+            "zero" => Register::X0,
+            "ra" => Register::X1,
+            "sp" => Register::X2,
+            "gp" => Register::X3,
+            "tp" => Register::X4,
+            "t0" => Register::X5,
+            "t1" => Register::X6,
+            "t2" => Register::X7,
+            "s0" | "fp" => Register::X8,
+            "s1" => Register::X9,
+            "a0" => Register::X10,
+            "a1" => Register::X11,
+            "a2" => Register::X12,
+            "a3" => Register::X13,
+            "a4" => Register::X14,
+            "a5" => Register::X15,
+            "a6" => Register::X16,
+            "a7" => Register::X17,
+            "s2" => Register::X18,
+            "s3" => Register::X19,
+            "s4" => Register::X20,
+            "s5" => Register::X21,
+            "s6" => Register::X22,
+            "s7" => Register::X23,
+            "s8" => Register::X24,
+            "s9" => Register::X25,
+            "s10" => Register::X26,
+            "s11" => Register::X27,
+            "t3" => Register::X28,
+            "t4" => Register::X29,
+            "t5" => Register::X30,
+            "t6" => Register::X31,
+            "ft0" => Register::F0,
+            "ft1" => Register::F1,
+            "ft2" => Register::F2,
+            "ft3" => Register::F3,
+            "ft4" => Register::F4,
+            "ft5" => Register::F5,
+            "ft6" => Register::F6,
+            "ft7" => Register::F7,
+            "fs0" => Register::F8,
+            "fs1" => Register::F9,
+            "fa0" => Register::F10,
+            "fa1" => Register::F11,
+            "fa2" => Register::F12,
+            "fa3" => Register::F13,
+            "fa4" => Register::F14,
+            "fa5" => Register::F15,
+            "fa6" => Register::F16,
+            "fa7" => Register::F17,
+            "fs2" => Register::F18,
+            "fs3" => Register::F19,
+            "fs4" => Register::F20,
+            "fs5" => Register::F21,
+            "fs6" => Register::F22,
+            "fs7" => Register::F23,
+            "fs8" => Register::F24,
+            "fs9" => Register::F25,
+            "fs10" => Register::F26,
+            "fs11" => Register::F27,
+            "ft8" => Register::F28,
+            "ft9" => Register::F29,
+            "ft10" => Register::F30,
+            "ft11" => Register::F31,
+            _ => return Err(ParseRegisterError),
+        })
+    }
+
+    /// Returns a register from its numeric name, like `x10` or `f10`.
+    pub fn from_numeric_name(name: &str) -> Result<Register, ParseRegisterError> {
+        let (is_float, digits) = if let Some(digits) = name.strip_prefix('x') {
+            (false, digits)
+        } else if let Some(digits) = name.strip_prefix('f') {
+            (true, digits)
+        } else {
+            return Err(ParseRegisterError);
+        };
+        // Only the shortest form of a number is a name, so `x01` is not
+        let num = u8::from_str(digits).map_err(|_| ParseRegisterError)?;
+        if num.to_string() != digits {
+            return Err(ParseRegisterError);
+        }
+        if is_float {
+            Register::from_float_num(num)
+        } else {
+            Register::from_num(num)
+        }
+    }
+
+    /// Returns the ABI name of the register, like `a0` or `ft0`.
+    ///
+    /// `x8` is named `s0` rather than `fp`.
+    #[must_use]
+    pub fn abi_name(self) -> &'static str {
+        match self {
+            Register::X0 => "zero",
+            Register::X1 => "ra",
+            Register::X2 => "sp",
+            Register::X3 => "gp",
+            Register::X4 => "tp",
+            Register::X5 => "t0",
+            Register::X6 => "t1",
+            Register::X7 => "t2",
+            Register::X8 => "s0",
+            Register::X9 => "s1",
+            Register::X10 => "a0",
+            Register::X11 => "a1",
+            Register::X12 => "a2",
+            Register::X13 => "a3",
+            Register::X14 => "a4",
+            Register::X15 => "a5",
+            Register::X16 => "a6",
+            Register::X17 => "a7",
+            Register::X18 => "s2",
+            Register::X19 => "s3",
+            Register::X20 => "s4",
+            Register::X21 => "s5",
+            Register::X22 => "s6",
+            Register::X23 => "s7",
+            Register::X24 => "s8",
+            Register::X25 => "s9",
+            Register::X26 => "s10",
+            Register::X27 => "s11",
+            Register::X28 => "t3",
+            Register::X29 => "t4",
+            Register::X30 => "t5",
+            Register::X31 => "t6",
+            Register::F0 => "ft0",
+            Register::F1 => "ft1",
+            Register::F2 => "ft2",
+            Register::F3 => "ft3",
+            Register::F4 => "ft4",
+            Register::F5 => "ft5",
+            Register::F6 => "ft6",
+            Register::F7 => "ft7",
+            Register::F8 => "fs0",
+            Register::F9 => "fs1",
+            Register::F10 => "fa0",
+            Register::F11 => "fa1",
+            Register::F12 => "fa2",
+            Register::F13 => "fa3",
+            Register::F14 => "fa4",
+            Register::F15 => "fa5",
+            Register::F16 => "fa6",
+            Register::F17 => "fa7",
+            Register::F18 => "fs2",
+            Register::F19 => "fs3",
+            Register::F20 => "fs4",
+            Register::F21 => "fs5",
+            Register::F22 => "fs6",
+            Register::F23 => "fs7",
+            Register::F24 => "fs8",
+            Register::F25 => "fs9",
+            Register::F26 => "fs10",
+            Register::F27 => "fs11",
+            Register::F28 => "ft8",
+            Register::F29 => "ft9",
+            Register::F30 => "ft10",
+            Register::F31 => "ft11",
+        }
+    }
+
+    /// Returns an adapter that displays the register with the given names.
+    ///
+    /// The [`Display`] of a register always uses ABI names.
+    #[must_use]
+    pub fn display(self, names: RegisterNames) -> RegisterDisplay {
+        RegisterDisplay {
+            register: self,
+            names,
+        }
+    }
+
+    /// Returns the numeric name of the register, like `x10` or `f10`.
+    #[must_use]
+    pub fn numeric_name(self) -> String {
+        let prefix = if self.is_float() { 'f' } else { 'x' };
+        format!("{prefix}{}", self.to_num())
+    }
+
     #[must_use]
     pub fn all_representations(&self) -> HashSet<String> {
         match self {
@@ -146,6 +354,38 @@ impl Register {
             Register::X29 => vec!["x29", "t4"],
             Register::X30 => vec!["x30", "t5"],
             Register::X31 => vec!["x31", "t6"],
+            Register::F0 => vec!["f0", "ft0"],
+            Register::F1 => vec!["f1", "ft1"],
+            Register::F2 => vec!["f2", "ft2"],
+            Register::F3 => vec!["f3", "ft3"],
+            Register::F4 => vec!["f4", "ft4"],
+            Register::F5 => vec!["f5", "ft5"],
+            Register::F6 => vec!["f6", "ft6"],
+            Register::F7 => vec!["f7", "ft7"],
+            Register::F8 => vec!["f8", "fs0"],
+            Register::F9 => vec!["f9", "fs1"],
+            Register::F10 => vec!["f10", "fa0"],
+            Register::F11 => vec!["f11", "fa1"],
+            Register::F12 => vec!["f12", "fa2"],
+            Register::F13 => vec!["f13", "fa3"],
+            Register::F14 => vec!["f14", "fa4"],
+            Register::F15 => vec!["f15", "fa5"],
+            Register::F16 => vec!["f16", "fa6"],
+            Register::F17 => vec!["f17", "fa7"],
+            Register::F18 => vec!["f18", "fs2"],
+            Register::F19 => vec!["f19", "fs3"],
+            Register::F20 => vec!["f20", "fs4"],
+            Register::F21 => vec!["f21", "fs5"],
+            Register::F22 => vec!["f22", "fs6"],
+            Register::F23 => vec!["f23", "fs7"],
+            Register::F24 => vec!["f24", "fs8"],
+            Register::F25 => vec!["f25", "fs9"],
+            Register::F26 => vec!["f26", "fs10"],
+            Register::F27 => vec!["f27", "fs11"],
+            Register::F28 => vec!["f28", "ft8"],
+            Register::F29 => vec!["f29", "ft9"],
+            Register::F30 => vec!["f30", "ft10"],
+            Register::F31 => vec!["f31", "ft11"],
         }
         .iter()
         .copied()
@@ -192,44 +432,93 @@ impl Register {
         })
     }
 
+    /// Returns a floating-point register from a number
+    pub fn from_float_num(num: u8) -> Result<Register, ParseRegisterError> {
+        Ok(match num {
+            0 => Register::F0,
+            1 => Register::F1,
+            2 => Register::F2,
+            3 => Register::F3,
+            4 => Register::F4,
+            5 => Register::F5,
+            6 => Register::F6,
+            7 => Register::F7,
+            8 => Register::F8,
+            9 => Register::F9,
+            10 => Register::F10,
+            11 => Register::F11,
+            12 => Register::F12,
+            13 => Register::F13,
+            14 => Register::F14,
+            15 => Register::F15,
+            16 => Register::F16,
+            17 => Register::F17,
+            18 => Register::F18,
+            19 => Register::F19,
+            20 => Register::F20,
+            21 => Register::F21,
+            22 => Register::F22,
+            23 => Register::F23,
+            24 => Register::F24,
+            25 => Register::F25,
+            26 => Register::F26,
+            27 => Register::F27,
+            28 => Register::F28,
+            29 => Register::F29,
+            30 => Register::F30,
+            31 => Register::F31,
+            _ => return Err(ParseRegisterError),
+        })
+    }
+
+    /// Returns the number of the register within its register file.
+    ///
+    /// Integer and floating-point registers are both numbered from 0 to
+    /// 31, so `x10` and `f10` have the same number.
     #[must_use]
     pub fn to_num(self) -> u8 {
         match self {
-            Register::X0 => 0,
-            Register::X1 => 1,
-            Register::X2 => 2,
-            Register::X3 => 3,
-            Register::X4 => 4,
-            Register::X5 => 5,
-            Register::X6 => 6,
-            Register::X7 => 7,
-            Register::X8 => 8,
-            Register::X9 => 9,
-            Register::X10 => 10,
-            Register::X11 => 11,
-            Register::X12 => 12,
-            Register::X13 => 13,
-            Register::X14 => 14,
-            Register::X15 => 15,
-            Register::X16 => 16,
-            Register::X17 => 17,
-            Register::X18 => 18,
-            Register::X19 => 19,
-            Register::X20 => 20,
-            Register::X21 => 21,
-            Register::X22 => 22,
-            Register::X23 => 23,
-            Register::X24 => 24,
-            Register::X25 => 25,
-            Register::X26 => 26,
-            Register::X27 => 27,
-            Register::X28 => 28,
-            Register::X29 => 29,
-            Register::X30 => 30,
-            Register::X31 => 31,
+            Register::X0 | Register::F0 => 0,
+            Register::X1 | Register::F1 => 1,
+            Register::X2 | Register::F2 => 2,
+            Register::X3 | Register::F3 => 3,
+            Register::X4 | Register::F4 => 4,
+            Register::X5 | Register::F5 => 5,
+            Register::X6 | Register::F6 => 6,
+            Register::X7 | Register::F7 => 7,
+            Register::X8 | Register::F8 => 8,
+            Register::X9 | Register::F9 => 9,
+            Register::X10 | Register::F10 => 10,
+            Register::X11 | Register::F11 => 11,
+            Register::X12 | Register::F12 => 12,
+            Register::X13 | Register::F13 => 13,
+            Register::X14 | Register::F14 => 14,
+            Register::X15 | Register::F15 => 15,
+            Register::X16 | Register::F16 => 16,
+            Register::X17 | Register::F17 => 17,
+            Register::X18 | Register::F18 => 18,
+            Register::X19 | Register::F19 => 19,
+            Register::X20 | Register::F20 => 20,
+            Register::X21 | Register::F21 => 21,
+            Register::X22 | Register::F22 => 22,
+            Register::X23 | Register::F23 => 23,
+            Register::X24 | Register::F24 => 24,
+            Register::X25 | Register::F25 => 25,
+            Register::X26 | Register::F26 => 26,
+            Register::X27 | Register::F27 => 27,
+            Register::X28 | Register::F28 => 28,
+            Register::X29 | Register::F29 => 29,
+            Register::X30 | Register::F30 => 30,
+            Register::X31 | Register::F31 => 31,
         }
     }
 
+    /// Check if this is a floating-point register, from `f0` to `f31`.
+    #[must_use]
+    pub fn is_float(self) -> bool {
+        self >= Register::F0
+    }
+
     #[must_use]
     pub fn is_sp(self) -> bool {
         self == Register::X2
@@ -275,6 +564,38 @@ impl Register {
             Register::X29,
             Register::X30,
             Register::X31,
+            Register::F0,
+            Register::F1,
+            Register::F2,
+            Register::F3,
+            Register::F4,
+            Register::F5,
+            Register::F6,
+            Register::F7,
+            Register::F8,
+            Register::F9,
+            Register::F10,
+            Register::F11,
+            Register::F12,
+            Register::F13,
+            Register::F14,
+            Register::F15,
+            Register::F16,
+            Register::F17,
+            Register::F18,
+            Register::F19,
+            Register::F20,
+            Register::F21,
+            Register::F22,
+            Register::F23,
+            Register::F24,
+            Register::F25,
+            Register::F26,
+            Register::F27,
+            Register::F28,
+            Register::F29,
+            Register::F30,
+            Register::F31,
         ]
         .iter()
         .copied()
@@ -284,49 +605,105 @@ impl Register {
 
 impl Hash for Register {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.to_num().hash(state);
+        (*self as u8).hash(state);
     }
 }
+/// Registers are displayed with their ABI names, like `a0`.
 impl Display for Register {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Register::{
-            X0, X1, X10, X11, X12, X13, X14, X15, X16, X17, X18, X19, X2, X20, X21, X22, X23, X24,
-            X25, X26, X27, X28, X29, X3, X30, X31, X4, X5, X6, X7, X8, X9,
-        };
-        let res = match self {
-            X0 => "zero",
-            X1 => "ra",
-            X2 => "sp",
-            X3 => "gp",
-            X4 => "tp",
-            X5 => "t0",
-            X6 => "t1",
-            X7 => "t2",
-            X8 => "s0",
-            X9 => "s1",
-            X10 => "a0",
-            X11 => "a1",
-            X12 => "a2",
-            X13 => "a3",
-            X14 => "a4",
-            X15 => "a5",
-            X16 => "a6",
-            X17 => "a7",
-            X18 => "s2",
-            X19 => "s3",
-            X20 => "s4",
-            X21 => "s5",
-            X22 => "s6",
-            X23 => "s7",
-            X24 => "s8",
-            X25 => "s9",
-            X26 => "s10",
-            X27 => "s11",
-            X28 => "t3",
-            X29 => "t4",
-            X30 => "t5",
-            X31 => "t6",
-        };
-        f.write_str(res)
+        f.write_str(self.abi_name())
+    }
+}
+
+/// A register displayed with a chosen set of names, see
+/// [`Register::display`].
+#[derive(Debug, Clone, Copy)]
+pub struct RegisterDisplay {
+    register: Register,
+    names: RegisterNames,
+}
+
+impl Display for RegisterDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.names {
+            RegisterNames::Abi => f.write_str(self.register.abi_name()),
+            RegisterNames::Numeric => f.write_str(&self.register.numeric_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_float_registers() {
+        assert_eq!(Register::from_str("f0"), Ok(Register::F0));
+        assert_eq!(Register::from_str("ft0"), Ok(Register::F0));
+        assert_eq!(Register::from_str("ft7"), Ok(Register::F7));
+        assert_eq!(Register::from_str("fs0"), Ok(Register::F8));
+        assert_eq!(Register::from_str("fs1"), Ok(Register::F9));
+        assert_eq!(Register::from_str("fa0"), Ok(Register::F10));
+        assert_eq!(Register::from_str("fa7"), Ok(Register::F17));
+        assert_eq!(Register::from_str("fs2"), Ok(Register::F18));
+        assert_eq!(Register::from_str("fs11"), Ok(Register::F27));
+        assert_eq!(Register::from_str("ft8"), Ok(Register::F28));
+        assert_eq!(Register::from_str("f31"), Ok(Register::F31));
+        assert_eq!(Register::from_str("ft11"), Ok(Register::F31));
+        assert_eq!(Register::from_str("f32"), Err(()));
+        assert_eq!(Register::from_str("fa8"), Err(()));
+
+        assert!(Register::F10.is_float());
+        assert!(!Register::X10.is_float());
+        assert_eq!(Register::F10.to_num(), Register::X10.to_num());
+        assert_eq!(Register::F10.to_string(), "fa0");
+        assert_eq!(Register::from_float_num(10).unwrap(), Register::F10);
+    }
+
+    #[test]
+    fn registers_round_trip_through_names() {
+        for reg in &Register::all() {
+            assert_eq!(Register::from_abi_name(reg.abi_name()).unwrap(), reg);
+            assert_eq!(Register::from_numeric_name(&reg.numeric_name()).unwrap(), reg);
+            assert_eq!(Register::from_str(reg.abi_name()), Ok(reg));
+            assert_eq!(Register::from_str(&reg.numeric_name()), Ok(reg));
+        }
+        assert_eq!(Register::all().into_iter().count(), 64);
+    }
+
+    #[test]
+    fn frame_pointer_is_s0() {
+        assert_eq!(Register::from_abi_name("fp").unwrap(), Register::X8);
+        assert_eq!(Register::from_abi_name("s0").unwrap(), Register::X8);
+        assert_eq!(Register::X8.abi_name(), "s0");
+        assert_eq!(Register::X8.numeric_name(), "x8");
+    }
+
+    #[test]
+    fn display_with_names() {
+        assert_eq!(Register::X10.to_string(), "a0");
+        assert_eq!(Register::X10.display(RegisterNames::Abi).to_string(), "a0");
+        assert_eq!(
+            Register::X10.display(RegisterNames::Numeric).to_string(),
+            "x10"
+        );
+        assert_eq!(
+            Register::F8.display(RegisterNames::Numeric).to_string(),
+            "f8"
+        );
+        for reg in &Register::all() {
+            let numeric = reg.display(RegisterNames::Numeric).to_string();
+            assert_eq!(Register::from_str(&numeric), Ok(reg));
+        }
+    }
+
+    #[test]
+    fn names_are_only_parsed_in_their_scheme() {
+        assert!(Register::from_abi_name("x10").is_err());
+        assert!(Register::from_numeric_name("a0").is_err());
+        assert!(Register::from_numeric_name("x01").is_err());
+        assert!(Register::from_numeric_name("x+1").is_err());
+        assert!(Register::from_numeric_name("x32").is_err());
+        assert!(Register::from_str("x01").is_err());
     }
 }
@@ -11,7 +11,8 @@ impl RegSets {
     #[must_use]
     pub fn temporary() -> RegisterSet {
         use Register::{X28, X29, X30, X31, X5, X6, X7};
-        [X5, X6, X7, X28, X29, X30, X31].into_iter().collect()
+        [X5, X6, X7, X28, X29, X30, X31].into_iter().collect::<RegisterSet>()
+            | RegSets::float_temporary()
     }
 
     #[must_use]
@@ -19,7 +20,8 @@ impl RegSets {
         use Register::{X10, X11, X12, X13, X14, X15, X16, X17};
         [X10, X11, X12, X13, X14, X15, X16, X17]
             .into_iter()
-            .collect()
+            .collect::<RegisterSet>()
+            | RegSets::float_argument()
     }
 
     #[must_use]
@@ -31,6 +33,34 @@ impl RegSets {
     pub fn saved() -> RegisterSet {
         use Register::{X18, X19, X20, X21, X22, X23, X24, X25, X26, X27, X8, X9};
         [X8, X9, X18, X19, X20, X21, X22, X23, X24, X25, X26, X27]
+            .into_iter()
+            .collect::<RegisterSet>()
+            | RegSets::float_saved()
+    }
+
+    /// The floating-point temporaries, `ft0` to `ft11`.
+    #[must_use]
+    pub fn float_temporary() -> RegisterSet {
+        use Register::{F0, F1, F2, F28, F29, F3, F30, F31, F4, F5, F6, F7};
+        [F0, F1, F2, F3, F4, F5, F6, F7, F28, F29, F30, F31]
+            .into_iter()
+            .collect()
+    }
+
+    /// The floating-point arguments and return values, `fa0` to `fa7`.
+    #[must_use]
+    pub fn float_argument() -> RegisterSet {
+        use Register::{F10, F11, F12, F13, F14, F15, F16, F17};
+        [F10, F11, F12, F13, F14, F15, F16, F17]
+            .into_iter()
+            .collect()
+    }
+
+    /// The floating-point saved registers, `fs0` to `fs11`.
+    #[must_use]
+    pub fn float_saved() -> RegisterSet {
+        use Register::{F18, F19, F20, F21, F22, F23, F24, F25, F26, F27, F8, F9};
+        [F8, F9, F18, F19, F20, F21, F22, F23, F24, F25, F26, F27]
             .into_iter()
             .collect()
     }
@@ -56,3 +86,29 @@ impl RegSets {
         [Register::ecall_type()].into_iter().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_register_classes() {
+        let temporary = ["ft0", "ft7", "ft8", "ft11"];
+        let argument = ["fa0", "fa7"];
+        let saved = ["fs0", "fs1", "fs2", "fs11"];
+        for name in temporary.iter().chain(&argument) {
+            let reg = name.parse::<Register>().unwrap();
+            assert!(RegSets::caller_saved().contains(&reg), "{name}");
+            assert!(!RegSets::callee_saved().contains(&reg), "{name}");
+        }
+        for name in saved {
+            let reg = name.parse::<Register>().unwrap();
+            assert!(RegSets::callee_saved().contains(&reg), "{name}");
+            assert!(!RegSets::caller_saved().contains(&reg), "{name}");
+        }
+        assert!(RegSets::temporary().contains(&Register::F0));
+        assert!(RegSets::argument().contains(&Register::F10));
+        assert!(RegSets::saved().contains(&Register::F8));
+        assert!(!RegSets::program_args().contains(&Register::F10));
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Info, LabelString, ParserNode, Position, Range, Token, With};
+
+/// The kind of a relocation operand, like the `%hi` of `%hi(symbol)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelocationKind {
+    /// The upper 20 bits of the address of a symbol.
+    Hi,
+    /// The lower 12 bits of the address of a symbol.
+    Lo,
+    /// The upper 20 bits of the offset from the instruction to a symbol.
+    PcrelHi,
+    /// The lower 12 bits of the offset found by the `%pcrel_hi` at a label.
+    PcrelLo,
+}
+
+impl RelocationKind {
+    /// Check if this relocation is the upper bits of an address.
+    #[must_use]
+    pub fn is_upper(self) -> bool {
+        matches!(self, RelocationKind::Hi | RelocationKind::PcrelHi)
+    }
+}
+
+impl FromStr for RelocationKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hi" => Ok(RelocationKind::Hi),
+            "lo" => Ok(RelocationKind::Lo),
+            "pcrel_hi" => Ok(RelocationKind::PcrelHi),
+            "pcrel_lo" => Ok(RelocationKind::PcrelLo),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for RelocationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelocationKind::Hi => write!(f, "%hi"),
+            RelocationKind::Lo => write!(f, "%lo"),
+            RelocationKind::PcrelHi => write!(f, "%pcrel_hi"),
+            RelocationKind::PcrelLo => write!(f, "%pcrel_lo"),
+        }
+    }
+}
+
+/// A relocation operand, like `%hi(symbol)` or `%lo(symbol)`.
+///
+/// The assembler replaces a relocation with part of the address of a symbol
+/// once addresses are known. Addresses are not known here, so the immediate
+/// of an instruction with a relocation is zero, and the relocation is kept
+/// to know which symbol the address is of.
+///
+/// A `%pcrel_lo` names the label of the `auipc` with the matching
+/// `%pcrel_hi`, not the symbol. The symbol of the `%pcrel_hi` is linked to
+/// it once all of the labels are known.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relocation {
+    pub kind: RelocationKind,
+    /// The symbol in the parentheses.
+    pub symbol: With<LabelString>,
+    /// The symbol of the matching `%pcrel_hi`, for a `%pcrel_lo`.
+    pub linked: Option<LabelString>,
+}
+
+impl Relocation {
+    /// Get the symbol that this relocation is part of the address of.
+    ///
+    /// Returns `None` for a `%pcrel_lo` that is not linked to a `%pcrel_hi`.
+    #[must_use]
+    pub fn address_of(&self) -> Option<&LabelString> {
+        match self.kind {
+            RelocationKind::PcrelLo => self.linked.as_ref(),
+            _ => Some(&self.symbol.data),
+        }
+    }
+}
+
+impl Display for Relocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.kind, self.symbol)
+    }
+}
+
+impl TryFrom<Info> for Relocation {
+    type Error = ();
+
+    fn try_from(value: Info) -> Result<Self, Self::Error> {
+        let Token::Symbol(s) = &value.token else {
+            return Err(());
+        };
+        let (kind, symbol) = s
+            .strip_prefix('%')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once('('))
+            .ok_or(())?;
+
+        // The symbol starts after the `%` and the `(`, and ends before the `)`
+        let offset = kind.len() + 2;
+        let start = Position {
+            line: value.pos.start.line,
+            column: value.pos.start.column + offset,
+            raw_index: value.pos.start.raw_index + offset,
+        };
+        let end = Position {
+            line: value.pos.end.line,
+            column: value.pos.end.column.saturating_sub(1),
+            raw_index: value.pos.end.raw_index.saturating_sub(1),
+        };
+        let symbol = Info {
+            token: Token::Symbol(symbol.to_owned()),
+            pos: Range { start, end },
+            file: value.file,
+        };
+        Ok(Relocation {
+            kind: RelocationKind::from_str(kind)?,
+            symbol: With::try_from(symbol)?,
+            linked: None,
+        })
+    }
+}
+
+/// Link each `%pcrel_lo` to the symbol of the `%pcrel_hi` at its label.
+///
+/// A `%pcrel_lo` that names a label without a `%pcrel_hi` is not linked,
+/// so the address it is part of is not known.
+pub(crate) fn link_pcrel_relocations(nodes: &mut [ParserNode]) {
+    // The symbol of the `%pcrel_hi` at each label
+    let mut symbols = HashMap::new();
+    let mut labels = Vec::new();
+    for node in nodes.iter() {
+        if let ParserNode::Label(label) = node {
+            labels.push(label.name.data.clone());
+        } else if node.is_instruction() {
+            if let Some(reloc) = node
+                .relocation()
+                .filter(|reloc| reloc.kind == RelocationKind::PcrelHi)
+            {
+                for label in labels.drain(..) {
+                    symbols.insert(label, reloc.symbol.data.clone());
+                }
+            }
+            labels.clear();
+        }
+    }
+
+    for node in nodes.iter_mut() {
+        if let Some(reloc) = node.relocation_mut() {
+            if reloc.kind == RelocationKind::PcrelLo {
+                reloc.linked = symbols.get(&reloc.symbol.data).cloned();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::analysis::AvailableValue;
+    use crate::cfg::{Cfg, CfgNode};
+    use crate::parser::{RVStringParser, Register};
+    use crate::passes::Manager;
+
+    fn gen_cfg(input: &str) -> Cfg {
+        let (nodes, error) = RVStringParser::parse_from_text(input);
+        assert_eq!(error.len(), 0);
+        Manager::gen_full_cfg(nodes).unwrap()
+    }
+
+    fn find(cfg: &Cfg, text: &str) -> Rc<CfgNode> {
+        cfg.nodes()
+            .iter()
+            .find(|node| node.node().token().text == text)
+            .map(Rc::clone)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_hi_lo_pair() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text("lui a0, %hi(msg)\naddi a0, a0, %lo(msg)\n");
+        assert_eq!(errors.len(), 0);
+
+        let relocs = nodes
+            .iter()
+            .filter_map(ParserNode::relocation)
+            .collect::<Vec<_>>();
+        assert_eq!(relocs.len(), 2);
+        assert_eq!(relocs[0].kind, RelocationKind::Hi);
+        assert_eq!(relocs[1].kind, RelocationKind::Lo);
+        assert_eq!(relocs[1].to_string(), "%lo(msg)");
+
+        // The symbol points at the name inside the parentheses
+        assert_eq!(relocs[0].symbol.data, "msg");
+        assert_eq!(relocs[0].symbol.pos.start.column, 12);
+        assert_eq!(relocs[0].symbol.pos.end.column, 14);
+    }
+
+    #[test]
+    fn hi_lo_pair_is_address_of_symbol() {
+        let cfg = gen_cfg(
+            "\
+            main:                        \n\
+                lui   a0, %hi(msg)       \n\
+                addi  a0, a0, %lo(msg)   \n\
+                lui   a1, %hi(msg)       \n\
+                lw    a1, %lo(msg)(a1)   \n\
+                li    a7, 10             \n\
+                ecall                    \n\
+            .data                        \n\
+            msg: .word 1                 \n",
+        );
+        let label = LabelString("msg".to_owned());
+
+        let lui = find(&cfg, "lui a0 %hi(msg)");
+        assert_eq!(
+            lui.reg_values_out().get(&Register::X10),
+            Some(&AvailableValue::UpperAddress(label.clone()))
+        );
+        let addi = find(&cfg, "addi a0 a0 %lo(msg)");
+        assert_eq!(
+            addi.reg_values_out().get(&Register::X10),
+            Some(&AvailableValue::Address(label.clone()))
+        );
+        let load = find(&cfg, "lw a1 %lo(msg) ( a1 )");
+        assert_eq!(
+            load.reg_values_out().get(&Register::X11),
+            Some(&AvailableValue::Memory(label, 0))
+        );
+    }
+
+    #[test]
+    fn pcrel_lo_is_linked_to_its_pcrel_hi() {
+        let cfg = gen_cfg(
+            "\
+            main:                               \n\
+            1:  auipc a0, %pcrel_hi(msg)        \n\
+                addi  a0, a0, %pcrel_lo(1b)     \n\
+                li    a7, 10                    \n\
+                ecall                           \n\
+            .data                               \n\
+            msg: .word 1                        \n",
+        );
+        let addi = find(&cfg, "addi a0 a0 %pcrel_lo(1b)");
+        let reloc = addi.node().relocation().cloned().unwrap();
+        assert_eq!(reloc.kind, RelocationKind::PcrelLo);
+        assert_eq!(reloc.address_of(), Some(&LabelString("msg".to_owned())));
+        assert_eq!(
+            addi.reg_values_out().get(&Register::X10),
+            Some(&AvailableValue::Address(LabelString("msg".to_owned())))
+        );
+    }
+
+    #[test]
+    fn jump_to_hi_lo_address_is_resolved() {
+        let cfg = gen_cfg(
+            "\
+            main:                        \n\
+                lui   t0, %hi(target)    \n\
+                addi  t0, t0, %lo(target)\n\
+                jr    t0                 \n\
+            target:                      \n\
+                li    a7, 10             \n\
+                ecall                    \n",
+        );
+        let jump = find(&cfg, "jr t0");
+        assert_eq!(
+            cfg.indirect_jump_targets(&jump),
+            Some(vec![LabelString("target".to_owned())])
+        );
+    }
+
+    #[test]
+    fn mismatched_lo_is_not_an_address() {
+        let cfg = gen_cfg(
+            "\
+            main:                        \n\
+                lui   a0, %hi(msg)       \n\
+                addi  a0, a0, %lo(other) \n\
+                li    a7, 10             \n\
+                ecall                    \n\
+            .data                        \n\
+            msg: .word 1                 \n\
+            other: .word 2               \n",
+        );
+        let addi = find(&cfg, "addi a0 a0 %lo(other)");
+        assert_eq!(addi.reg_values_out().get(&Register::X10), None);
+    }
+}
@@ -31,6 +31,9 @@ impl RVStringParser {
 mod test {
 
     use super::*;
+    use crate::cfg::{RegisterSet, Segment};
+    use crate::parser::{AlignMode, DirectiveType, Imm, Inst, Register, Token};
+    use crate::passes::{DiagnosticLocation, Manager, SeverityLevel};
 
     #[test]
     fn can_parse_from_text() {
@@ -42,6 +45,109 @@ mod test {
         assert_eq!(nodes[1].to_string(), "add ra <- a0, a1");
     }
 
+    fn parse_single(text: &str) -> ParserNode {
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes.len(), 2);
+        nodes[1].clone()
+    }
+
+    #[test]
+    fn can_parse_negw() {
+        let node = parse_single("negw a0, a1\n");
+        assert!(matches!(node, ParserNode::Arith(_)));
+        assert_eq!(node.to_string(), "subw a0 <- zero, a1");
+        assert_eq!(node.token().text, "negw a0 a1");
+        assert_eq!(node.gen_reg(), RegisterSet::from_iter([Register::X11]));
+        assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X10]));
+    }
+
+    #[test]
+    fn can_parse_sext_w() {
+        let node = parse_single("sext.w a0, a1\n");
+        assert!(matches!(node, ParserNode::IArith(_)));
+        assert_eq!(node.to_string(), "addiw a0 <- a1, 0");
+        assert_eq!(node.gen_reg(), RegisterSet::from_iter([Register::X11]));
+        assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X10]));
+    }
+
+    #[test]
+    fn can_parse_rv64_loads() {
+        for (text, inst) in [("ld a0, 8(sp)\n", Inst::Ld), ("lwu a0, 8(sp)\n", Inst::Lwu)] {
+            let node = parse_single(text);
+            assert!(matches!(node, ParserNode::Load(_)));
+            assert_eq!(node.inst(), inst);
+            assert_eq!(node.gen_reg(), RegisterSet::from_iter([Register::X2]));
+            assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X10]));
+        }
+    }
+
+    #[test]
+    fn can_parse_sd() {
+        let node = parse_single("sd a0, 8(sp)\n");
+        assert!(matches!(node, ParserNode::Store(_)));
+        assert_eq!(node.inst(), Inst::Sd);
+        assert_eq!(
+            node.gen_reg(),
+            RegisterSet::from_iter([Register::X2, Register::X10])
+        );
+        assert_eq!(node.kill_reg(), RegisterSet::new());
+    }
+
+    #[test]
+    fn can_parse_rv64_arith() {
+        for (text, inst) in [
+            ("addw a0, a1, a2\n", Inst::Addw),
+            ("subw a0, a1, a2\n", Inst::Subw),
+            ("sllw a0, a1, a2\n", Inst::Sllw),
+            ("srlw a0, a1, a2\n", Inst::Srlw),
+            ("sraw a0, a1, a2\n", Inst::Sraw),
+        ] {
+            let node = parse_single(text);
+            assert!(matches!(node, ParserNode::Arith(_)));
+            assert_eq!(node.inst(), inst);
+            assert_eq!(
+                node.gen_reg(),
+                RegisterSet::from_iter([Register::X11, Register::X12])
+            );
+            assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X10]));
+        }
+        for (text, inst) in [
+            ("addiw a0, a1, 1\n", Inst::Addiw),
+            ("slliw a0, a1, 1\n", Inst::Slliw),
+            ("srliw a0, a1, 1\n", Inst::Srliw),
+            ("sraiw a0, a1, 1\n", Inst::Sraiw),
+        ] {
+            let node = parse_single(text);
+            assert!(matches!(node, ParserNode::IArith(_)));
+            assert_eq!(node.inst(), inst);
+            assert_eq!(node.gen_reg(), RegisterSet::from_iter([Register::X11]));
+            assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X10]));
+        }
+    }
+
+    #[test]
+    fn can_parse_tail() {
+        let node = parse_single("tail fn_a\n");
+        assert!(matches!(node, ParserNode::JumpLink(_)));
+        assert_eq!(node.to_string(), "jal [fn_a] | zero <- PC");
+        assert!(node.is_tail_call());
+        assert!(node.is_unconditional_jump());
+        assert!(node.calls_to().is_none());
+
+        let node = parse_single("j fn_a\n");
+        assert!(!node.is_tail_call());
+    }
+
+    #[test]
+    fn can_parse_zext_b() {
+        let node = parse_single("zext.b t0, t1\n");
+        assert!(matches!(node, ParserNode::IArith(_)));
+        assert_eq!(node.to_string(), "andi t0 <- t1, 255");
+        assert_eq!(node.gen_reg(), RegisterSet::from_iter([Register::X6]));
+        assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X5]));
+    }
+
     #[test]
     fn can_emit_parse_errors() {
         let (nodes, errors) =
@@ -54,6 +160,505 @@ mod test {
         matches!(&errors[0], ParseError::UnexpectedToken(_));
     }
 
+    #[test]
+    fn missing_operand_does_not_consume_next_line() {
+        let (nodes, errors) = RVStringParser::parse_from_text("add t0, t1\nadd t2, t3, t4\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(nodes[1].to_string(), "add t2 <- t3, t4");
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 3, 2)));
+        assert_eq!(errors[0].to_string(), "Expected 3 operands, found 2");
+
+        // The error points to where the operand was expected, right after
+        // the last operand. This is where `, t4` starts on the second line.
+        let range = errors[0].range();
+        assert_eq!(range.start.line, 0);
+        assert_eq!(range.start.column, nodes[1].token().pos.end.column - 3);
+    }
+
+    #[test]
+    fn missing_all_operands() {
+        let (nodes, errors) = RVStringParser::parse_from_text("jal\nret\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 1, 0)));
+        assert_eq!(errors[0].to_string(), "Expected 1 operand, found 0");
+    }
+
+    #[test]
+    fn missing_operand_before_comment() {
+        let (nodes, errors) = RVStringParser::parse_from_text("li a0 # comment\nret\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 2, 1)));
+    }
+
+    #[test]
+    fn missing_operand_at_end_of_file() {
+        let (nodes, errors) = RVStringParser::parse_from_text("addi t0, t1");
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 3, 2)));
+    }
+
+    #[test]
+    fn missing_offset_operand() {
+        let (nodes, errors) = RVStringParser::parse_from_text("lw t0\nlw t1, 0(sp)\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 2, 1)));
+    }
+
+    #[test]
+    fn too_many_operands() {
+        let (nodes, errors) = RVStringParser::parse_from_text("add t0, t1, t2, t3\nret\n");
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(nodes[1].to_string(), "add t0 <- t1, t2");
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 3, 4)));
+
+        // The error points to the extra operand
+        let range = errors[0].range();
+        assert!(range.start.column > nodes[1].token().pos.end.column);
+    }
+
+    #[test]
+    fn too_many_operands_with_offset() {
+        let (nodes, errors) = RVStringParser::parse_from_text("sw t0, 0(sp), t1, t2\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 3, 4)));
+
+        let (_, errors) = RVStringParser::parse_from_text("ecall a0\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::WrongOperandCount(_, 0, 1)));
+    }
+
+    #[test]
+    fn every_bad_operand_is_reported() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text("add x0, notareg, alsobad\nadd t0, t1, t2\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[1].to_string(), "add t0 <- t1, t2");
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], ParseError::Expected(_, got) if got.pos.start.column == 8));
+        assert!(matches!(&errors[1], ParseError::Expected(_, got) if got.pos.start.column == 17));
+    }
+
+    #[test]
+    fn bad_operand_and_missing_operand() {
+        let (nodes, errors) = RVStringParser::parse_from_text("addi t0, 5\nret\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], ParseError::Expected(..)));
+        assert!(matches!(&errors[1], ParseError::WrongOperandCount(_, 3, 2)));
+    }
+
+    #[test]
+    fn unrecoverable_operand_stops_instruction() {
+        // A parenthesis is not a misspelled operand, so the rest of the
+        // instruction is not checked.
+        let (nodes, errors) = RVStringParser::parse_from_text("add t0, (t1), bad\nret\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::Expected(..)));
+    }
+
+    #[test]
+    fn optional_operands_are_accepted() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text("jalr t0\njal fn_a\njal ra, fn_a\nlw t0, (sp)\n");
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(errors.len(), 0);
+    }
+
+    #[test]
+    fn missing_directive_operand() {
+        let (nodes, errors) = RVStringParser::parse_from_text(".align\nret\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::Expected(..)));
+    }
+
+    #[test]
+    fn globl_directive_with_many_labels() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text(".globl main, handler\n.global fn_a\n");
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes.len(), 3);
+        let ParserNode::Directive(dir) = &nodes[1] else {
+            panic!("expected a directive");
+        };
+        let DirectiveType::Global(labels) = &dir.dir else {
+            panic!("expected a global directive");
+        };
+        let names = labels.iter().map(|l| l.data.0.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["main", "handler"]);
+    }
+
+    #[test]
+    fn extern_directive_with_many_labels() {
+        let (nodes, errors) = RVStringParser::parse_from_text(".extern printf, exit\n");
+        assert_eq!(errors.len(), 0);
+        let ParserNode::Directive(dir) = &nodes[1] else {
+            panic!("expected a directive");
+        };
+        let DirectiveType::Extern(labels) = &dir.dir else {
+            panic!("expected an extern directive");
+        };
+        let names = labels.iter().map(|l| l.data.0.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["printf", "exit"]);
+    }
+
+    fn data_image(text: &str) -> Vec<u8> {
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        let ParserNode::Directive(dir) = &nodes[1] else {
+            panic!("expected a directive");
+        };
+        dir.dir.data_image().unwrap()
+    }
+
+    #[test]
+    fn float_directive_encoding() {
+        assert_eq!(data_image(".float 3.14\n"), vec![0xc3, 0xf5, 0x48, 0x40]);
+        assert_eq!(
+            data_image(".float 1, -2.5\n"),
+            vec![0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x20, 0xc0]
+        );
+    }
+
+    #[test]
+    fn double_directive_encoding() {
+        assert_eq!(
+            data_image(".double 1e10\n"),
+            vec![0x00, 0x00, 0x00, 0x20, 0x5f, 0xa0, 0x02, 0x42]
+        );
+    }
+
+    #[test]
+    fn integer_directive_encoding() {
+        assert_eq!(
+            data_image(".word 1, -1\n.half 0x1234\n"),
+            vec![0x01, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(data_image(".half 0x1234\n"), vec![0x34, 0x12]);
+    }
+
+    #[test]
+    fn invalid_float_literal() {
+        let (nodes, errors) = RVStringParser::parse_from_text(".float 3.1.4\nret\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::InvalidFloat(info)
+            if info.token == Token::Symbol("3.1.4".to_string())));
+    }
+
+    fn alignment(text: &str, mode: AlignMode) -> (Option<u32>, Vec<ParseError>) {
+        let mut parser = RVParser::new(EmptyFileReader::new(text));
+        parser.set_align_mode(mode);
+        let (nodes, errors) = parser.parse_from_file(EmptyFileReader::get_file_path(), false);
+        let ParserNode::Directive(dir) = &nodes[1] else {
+            panic!("expected a directive");
+        };
+        let DirectiveType::Align { bytes, .. } = dir.dir else {
+            panic!("expected an align directive");
+        };
+        (bytes, errors)
+    }
+
+    #[test]
+    fn align_to_power_of_two() {
+        let (bytes, errors) = alignment(".align 3\n", AlignMode::Power);
+        assert_eq!(bytes, Some(8));
+        assert_eq!(errors.len(), 0);
+    }
+
+    #[test]
+    fn align_to_bytes() {
+        let (bytes, errors) = alignment(".align 8\n", AlignMode::Bytes);
+        assert_eq!(bytes, Some(8));
+        assert_eq!(errors.len(), 0);
+    }
+
+    #[test]
+    fn invalid_alignment() {
+        let (bytes, errors) = alignment(".align 20\n", AlignMode::Power);
+        assert_eq!(bytes, None);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidAlignment(_, AlignMode::Power)
+        ));
+
+        let (bytes, errors) = alignment(".align 3\n", AlignMode::Bytes);
+        assert_eq!(bytes, None);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidAlignment(_, AlignMode::Bytes)
+        ));
+    }
+
+    #[test]
+    fn balign_is_always_bytes() {
+        let (bytes, errors) = alignment(".balign 8\n", AlignMode::Power);
+        assert_eq!(bytes, Some(8));
+        assert_eq!(errors.len(), 0);
+
+        let (bytes, errors) = alignment(".balign 3\n", AlignMode::Power);
+        assert_eq!(bytes, None);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidAlignment(_, AlignMode::Bytes)
+        ));
+    }
+
+    #[test]
+    fn constant_is_replaced_by_its_value() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text(".equ SIZE, 16\naddi sp, sp, -SIZE\n");
+        let (literal, _) = RVStringParser::parse_from_text("addi sp, sp, -16\n");
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes.len(), 3);
+        assert!(matches!(
+            &nodes[1],
+            ParserNode::Directive(dir) if matches!(&dir.dir, DirectiveType::Constant { .. })
+        ));
+        assert_eq!(nodes[2].to_string(), literal[1].to_string());
+        assert_eq!(nodes[2].to_string(), "addi sp <- sp, -16");
+    }
+
+    #[test]
+    fn constant_can_be_used_before_definition() {
+        let text = "lw a0, OFFSET(sp)\n.set OFFSET, SIZE\n.set SIZE, 8\n";
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes[1].to_string(), "lw a0 <- 8(sp)");
+    }
+
+    #[test]
+    fn redefined_constant() {
+        let (_, errors) = RVStringParser::parse_from_text(".equ SIZE, 4\n.equ SIZE, 8\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::RedefinedConstant(_)));
+        assert_eq!(errors[0].to_string(), "Constant SIZE is already defined");
+    }
+
+    #[test]
+    fn branch_to_label_with_constants() {
+        let text = ".eqv COUNT, 3\nli t0, COUNT\nbeq t0, zero, done\ndone:\n";
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes[2].to_string(), "addi t0 <- zero, 3");
+        let ParserNode::Branch(branch) = &nodes[3] else {
+            panic!("expected a branch");
+        };
+        assert_eq!(branch.name.data, "done");
+    }
+
+    #[test]
+    fn pseudo_branch_keeps_mnemonic_and_order() {
+        let (nodes, errors) = RVStringParser::parse_from_text("bgt a0, a1, done\ndone:\n");
+        assert_eq!(errors.len(), 0);
+        let ParserNode::Branch(branch) = &nodes[1] else {
+            panic!("expected a branch");
+        };
+        assert_eq!(branch.rs1.data, Register::X11);
+        assert_eq!(branch.rs2.data, Register::X10);
+        assert_eq!(branch.mnemonic(), "bgt");
+        assert_eq!(branch.source_operands(), vec![Register::X10, Register::X11]);
+        assert_eq!(nodes[1].to_string(), "bgt a0--a1, [done]");
+    }
+
+    #[test]
+    fn branch_against_zero_compares_with_zero() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text("bgtz a0, done\nblez a0, done\ndone:\n");
+        assert_eq!(errors.len(), 0);
+        for node in &nodes[1..3] {
+            let ParserNode::Branch(branch) = node else {
+                panic!("expected a branch");
+            };
+            assert_eq!(branch.rs1.data, Register::X0);
+            assert_eq!(branch.rs2.data, Register::X10);
+            assert_eq!(branch.source_operands(), vec![Register::X10]);
+        }
+        assert_eq!(nodes[1].to_string(), "bgtz a0, [done]");
+    }
+
+    #[test]
+    fn rept_repeats_block() {
+        let (nodes, errors) = RVStringParser::parse_from_text(".rept 3\nnop\n.endr\nret\n");
+        assert_eq!(errors.len(), 0);
+        let nodes = nodes.iter().skip(1).collect::<Vec<_>>();
+        assert_eq!(nodes.len(), 4);
+        for node in &nodes[..3] {
+            assert!(node.is_nop());
+            // Every copy points at the line it was written on
+            assert_eq!(node.range().start.line, 1);
+        }
+        assert!(nodes[3].is_return());
+        assert_ne!(nodes[0].id(), nodes[1].id());
+    }
+
+    #[test]
+    fn nested_rept_blocks() {
+        let text = ".eqv COUNT, 3\n.rept 2\nli t0, 1\n.rept COUNT\nnop\n.endr\n.endr\n";
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        let nodes = nodes
+            .iter()
+            .filter(|node| node.is_instruction())
+            .map(|node| if node.is_nop() { "nop" } else { "li" })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nodes,
+            vec!["li", "nop", "nop", "nop", "li", "nop", "nop", "nop"]
+        );
+    }
+
+    #[test]
+    fn empty_rept_block() {
+        let (nodes, errors) = RVStringParser::parse_from_text(".rept 0\nnop\n.endr\n");
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes.len(), 1);
+    }
+
+    #[test]
+    fn unterminated_rept_block() {
+        let (nodes, errors) = RVStringParser::parse_from_text("nop\n.rept 2\nnop\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::UnterminatedRepeat(_)));
+        assert_eq!(errors[0].range().start.line, 1);
+    }
+
+    #[test]
+    fn invalid_rept_count() {
+        for count in ["-1", "100000"] {
+            let text = format!(".rept {count}\nnop\n.endr\n");
+            let (nodes, errors) = RVStringParser::parse_from_text(&text);
+            assert_eq!(nodes.len(), 1);
+            assert_eq!(errors.len(), 1);
+            assert!(matches!(&errors[0], ParseError::InvalidRepeatCount(_)));
+        }
+
+        // A large block cannot be repeated as many times as a small one
+        let text = format!(".rept 10000\n{}.endr\n", "nop\n".repeat(60));
+        let (nodes, errors) = RVStringParser::parse_from_text(&text);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::InvalidRepeatCount(_)));
+    }
+
+    #[test]
+    fn endr_without_rept() {
+        let (_, errors) = RVStringParser::parse_from_text("nop\n.endr\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::UnexpectedToken(_)));
+    }
+
+    fn float_nodes(text: &str) -> Vec<ParserNode> {
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        nodes.into_iter().skip(1).collect()
+    }
+
+    #[test]
+    fn float_arithmetic() {
+        let nodes = float_nodes("fadd.s fa0, fa1, fa2\nfmadd.d ft0, ft1, ft2, ft3\n");
+        assert_eq!(nodes[0].to_string(), "fadd.s fa0 <- fa1, fa2");
+        assert_eq!(nodes[0].stores_to().unwrap().data, Register::F10);
+        let reads = nodes[0].reads_from().into_iter().map(|r| r.data).collect::<RegisterSet>();
+        assert_eq!(reads, [Register::F11, Register::F12].into_iter().collect());
+        assert_eq!(nodes[1].to_string(), "fmadd.d ft0 <- ft1, ft2, ft3");
+    }
+
+    #[test]
+    fn float_conversions_and_comparisons() {
+        let nodes = float_nodes(
+            "fcvt.w.s a0, fa0, rtz\nfcvt.s.w fa0, a0\nflt.s a1, fa0, fa1\nfmv.x.w t0, ft0\n",
+        );
+        assert_eq!(nodes[0].to_string(), "fcvt.w.s a0 <- fa0");
+        assert_eq!(nodes[1].to_string(), "fcvt.s.w fa0 <- a0");
+        assert_eq!(nodes[2].to_string(), "flt.s a1 <- fa0, fa1");
+        assert_eq!(nodes[3].to_string(), "fmv.x.w t0 <- ft0");
+    }
+
+    #[test]
+    fn float_loads_and_stores() {
+        let nodes = float_nodes("flw ft0, 4(a0)\nfsd fs0, 8(sp)\nflw ft1, value, t0\n");
+        assert_eq!(nodes[0].to_string(), "flw ft0 <- 4(a0)");
+        assert_eq!(nodes[1].to_string(), "fsd fs0 -> 8(sp)");
+        assert_eq!(nodes[2].to_string(), "la t0 <- [value]");
+        assert_eq!(nodes[3].to_string(), "flw ft1 <- 0(t0)");
+    }
+
+    #[test]
+    fn float_pseudo_instructions() {
+        let nodes = float_nodes("fmv.s fa0, fs0\nfneg.d ft0, ft1\n");
+        assert_eq!(nodes[0].to_string(), "fsgnj.s fa0 <- fs0, fs0");
+        assert_eq!(nodes[1].to_string(), "fsgnjn.d ft0 <- ft1, ft1");
+    }
+
+    #[test]
+    fn float_operand_classes() {
+        let (_, errors) = RVStringParser::parse_from_text("fadd.s a0, fa1, fa2\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::Expected(..)));
+        let (_, errors) = RVStringParser::parse_from_text("fcvt.w.s fa0, fa0\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn float_register_is_not_integer_operand() {
+        let (_, errors) = RVStringParser::parse_from_text("add fa0, a0, a1\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::Expected(..)));
+    }
+
+    fn section_segment(text: &str) -> Option<Segment> {
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes.len(), 2);
+        let ParserNode::Directive(directive) = &nodes[1] else {
+            panic!("expected a directive");
+        };
+        directive.dir.segment()
+    }
+
+    #[test]
+    fn named_sections() {
+        assert_eq!(section_segment(".section .text\n"), Some(Segment::Text));
+        assert_eq!(section_segment(".section .data\n"), Some(Segment::Data));
+        assert_eq!(section_segment(".section .rodata\n"), Some(Segment::Data));
+        assert_eq!(section_segment(".section .bss\n"), Some(Segment::Data));
+        assert_eq!(section_segment(".section .custom\n"), Some(Segment::Data));
+    }
+
+    #[test]
+    fn suffixed_section_names() {
+        assert_eq!(section_segment(".section .text.startup\n"), Some(Segment::Text));
+        assert_eq!(section_segment(".section .rodata.str1.1\n"), Some(Segment::Data));
+    }
+
+    #[test]
+    fn section_flags_are_skipped() {
+        let text = ".section .text.startup, \"ax\"\naddi a0, a0, 1\n";
+        let (nodes, errors) = RVStringParser::parse_from_text(text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[2].to_string(), "addi a0 <- a0, 1");
+    }
+
+    #[test]
+    fn section_without_name() {
+        let (_, errors) = RVStringParser::parse_from_text(".section\n");
+        assert_eq!(errors.len(), 1);
+    }
+
     #[test]
     fn can_emit_error_on_include_directive() {
         let (nodes, errors) = RVStringParser::parse_from_text(".include \"file.s\"");
@@ -72,4 +677,184 @@ mod test {
         matches!(&nodes[0], ParserNode::Directive(_));
         matches!(&errors[0], ParseError::FileNotFound(_));
     }
+
+    #[test]
+    fn large_li_is_split_into_lui_and_addi() {
+        let (nodes, errors) =
+            RVStringParser::parse_from_text("li a0, 0x12345\nli a1, -2048\nli a2, 0x7ffff800\n");
+        assert_eq!(errors.len(), 0);
+        let nodes = nodes.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            nodes[1..],
+            [
+                "lui a0 <- 18",
+                "addi a0 <- a0, 837",
+                "addi a1 <- zero, -2048",
+                "lui a2 <- 524288",
+                "addi a2 <- a2, -2048",
+            ]
+        );
+    }
+
+    #[test]
+    fn grouped_and_char_immediates() {
+        let (nodes, errors) = RVStringParser::parse_from_text(
+            "li a0, 'A'\naddi a1, a1, '\\n'\nli a2, 1_000\nli a3, 0x1234_5678\n",
+        );
+        assert_eq!(errors.len(), 0);
+        let nodes = nodes.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            nodes[1..],
+            [
+                "addi a0 <- zero, 65",
+                "addi a1 <- a1, 10",
+                "addi a2 <- zero, 1000",
+                "lui a3 <- 74565",
+                "addi a3 <- a3, 1656",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_char_immediates() {
+        let (_, errors) = RVStringParser::parse_from_text("li a0, 'AB'\nli a1, '\n");
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, ParseError::InvalidString(..))));
+    }
+
+    #[test]
+    fn large_li_is_folded_into_constant() {
+        for (text, value) in [
+            ("li a0, 0x12345", 0x12345),
+            ("li a0, 0x12000", 0x12000),
+            ("li a0, -0x801", -0x801),
+            ("li a0, 0x7fffffff", 0x7fff_ffff),
+        ] {
+            let (nodes, errors) = RVStringParser::parse_from_text(&format!("{text}\nret\n"));
+            assert_eq!(errors.len(), 0);
+            let cfg = Manager::gen_full_cfg(nodes).unwrap();
+            let ret = cfg.iter().find(|node| node.node().is_return()).unwrap();
+            assert_eq!(
+                ret.reg_values_in().resolved_constant(Register::X10),
+                Some(value)
+            );
+        }
+    }
+
+    #[test]
+    fn lui_keeps_immediate_as_written() {
+        let node = parse_single("lui a0, 0xfffff\n");
+        let ParserNode::UpperArith(upper) = &node else {
+            panic!("expected an upper immediate instruction");
+        };
+        assert_eq!(node.inst(), Inst::Lui);
+        assert_eq!(node.to_string(), "lui a0 <- 1048575");
+        assert_eq!(upper.value(), -4096);
+        assert_eq!(node.gen_reg(), RegisterSet::new());
+        assert_eq!(node.kill_reg(), RegisterSet::from_iter([Register::X10]));
+    }
+
+    fn read_and_written(node: &ParserNode) -> (Vec<Register>, Vec<Register>) {
+        (
+            node.read_registers().map(|reg| reg.data).collect(),
+            node.write_registers().map(|reg| reg.data).collect(),
+        )
+    }
+
+    #[test]
+    fn store_operands() {
+        let node = parse_single("sw t0, 8(sp)\n");
+        assert_eq!(
+            read_and_written(&node),
+            (vec![Register::X2, Register::X5], vec![])
+        );
+        assert_eq!(
+            node.immediates().map(|imm| imm.data).collect::<Vec<_>>(),
+            [Imm(8)]
+        );
+    }
+
+    #[test]
+    fn pseudo_branch_operands() {
+        let node = parse_single("beqz a0, end\n");
+        // The branch compares with the zero register, which is not written
+        assert_eq!(
+            read_and_written(&node),
+            (vec![Register::X10, Register::X0], vec![])
+        );
+        assert_eq!(node.immediates().count(), 0);
+        assert_eq!(node.gen_reg(), RegisterSet::from_iter([Register::X10]));
+
+        let node = parse_single("j end\n");
+        assert_eq!(read_and_written(&node), (vec![], vec![Register::X0]));
+        assert_eq!(node.kill_reg(), RegisterSet::new());
+    }
+
+    #[test]
+    fn csr_operands() {
+        let node = parse_single("csrr t0, fcsr\n");
+        assert_eq!(
+            read_and_written(&node),
+            (vec![Register::X0], vec![Register::X5])
+        );
+        assert_eq!(node.immediates().count(), 0);
+
+        let node = parse_single("csrwi fcsr, 3\n");
+        assert_eq!(read_and_written(&node), (vec![], vec![Register::X0]));
+        assert_eq!(
+            node.immediates().map(|imm| imm.data).collect::<Vec<_>>(),
+            [Imm(3)]
+        );
+    }
+
+    #[test]
+    fn csr_names_and_operand_order() {
+        let node = parse_single("csrr t0, mstatus\n");
+        assert!(matches!(&node, ParserNode::Csr(x) if x.csr.data.0 == 0x300));
+        assert_eq!(node.to_string(), "csrrs t0 <- mstatus <- zero");
+
+        // The register and the CSR of `csrw` can be written in either order
+        let rars = parse_single("csrw t0, mtvec\n");
+        let gnu = parse_single("csrw mtvec, t0\n");
+        assert_eq!(rars.to_string(), gnu.to_string());
+        assert_eq!(
+            read_and_written(&gnu),
+            (vec![Register::X5], vec![Register::X0])
+        );
+    }
+
+    #[test]
+    fn unknown_csr_name() {
+        let (nodes, errors) = RVStringParser::parse_from_text("csrr t0, mstatu\nnop\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::UnknownCsr(info) if info.pos.start.column == 9));
+        assert_eq!(SeverityLevel::from(&errors[0]), SeverityLevel::Warning);
+        // The instruction is skipped
+        assert_eq!(nodes.len(), 2);
+    }
+
+    #[test]
+    fn lui_and_addi_are_folded_into_constant() {
+        let value_of_a0 = |text: &str| {
+            let (nodes, errors) = RVStringParser::parse_from_text(text);
+            assert_eq!(errors.len(), 0);
+            let cfg = Manager::gen_full_cfg(nodes).unwrap();
+            let ret = cfg.iter().find(|node| node.node().is_return()).unwrap();
+            ret.reg_values_in().resolved_constant(Register::X10)
+        };
+
+        assert_eq!(value_of_a0("lui a0, 0xfffff\nret\n"), Some(-4096));
+        assert_eq!(
+            value_of_a0("lui a0, 0xfffff\naddi a0, a0, 0x7ff\nret\n"),
+            Some(-2049)
+        );
+        assert_eq!(
+            value_of_a0("lui a0, 0x80000\naddi a0, a0, -1\nret\n"),
+            Some(0x7fff_ffff)
+        );
+        // The address of the instruction is not known
+        assert_eq!(value_of_a0("auipc a0, 1\nret\n"), None);
+    }
 }
@@ -113,6 +113,39 @@ impl<T> With<T> {
             pos: self.pos.clone(),
         }
     }
+
+    /// Get the range of the token that the data came from.
+    #[must_use]
+    pub fn range(&self) -> Range {
+        self.pos.clone()
+    }
+
+    /// Get the file of the token that the data came from.
+    #[must_use]
+    pub fn file(&self) -> Uuid {
+        self.file
+    }
+
+    /// Convert the data, keeping the token, range, and file it came from.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> With<U> {
+        With {
+            token: self.token,
+            pos: self.pos,
+            file: self.file,
+            data: f(self.data),
+        }
+    }
+
+    /// Borrow the data, keeping the token, range, and file it came from.
+    #[must_use]
+    pub fn as_ref(&self) -> With<&T> {
+        With {
+            token: self.token.clone(),
+            pos: self.pos.clone(),
+            file: self.file,
+            data: &self.data,
+        }
+    }
 }
 
 impl<T> PartialOrd for With<T>
@@ -245,11 +278,11 @@ impl Display for VecTokenDisplayWrapper<'_> {
 }
 
 pub trait ToDisplayForTokenVec {
-    fn to_display(&self) -> VecTokenDisplayWrapper;
+    fn to_display(&self) -> VecTokenDisplayWrapper<'_>;
 }
 
 impl ToDisplayForTokenVec for Vec<Info> {
-    fn to_display(&self) -> VecTokenDisplayWrapper {
+    fn to_display(&self) -> VecTokenDisplayWrapper<'_> {
         VecTokenDisplayWrapper(self)
     }
 }
@@ -267,10 +300,10 @@ impl std::fmt::Display for Range {
 
 impl<T> DiagnosticLocation for With<T> {
     fn range(&self) -> Range {
-        self.pos.clone()
+        With::range(self)
     }
     fn file(&self) -> Uuid {
-        self.file
+        With::file(self)
     }
 }
 
@@ -334,3 +367,67 @@ where
         self.data == *other
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{LabelString, ParserNode, RVStringParser, Register};
+
+    fn info() -> Info {
+        Info {
+            token: Token::Symbol("a0".to_owned()),
+            pos: Range {
+                start: Position {
+                    line: 2,
+                    column: 4,
+                    raw_index: 20,
+                },
+                end: Position {
+                    line: 2,
+                    column: 6,
+                    raw_index: 22,
+                },
+            },
+            file: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn map_keeps_range_and_file() {
+        let info = info();
+        let reg = With::new(Register::X10, info.clone());
+        let name = reg.map(|reg| LabelString(reg.to_string()));
+        assert_eq!(name.data, LabelString("a0".to_owned()));
+        assert_eq!(name.range(), info.pos);
+        assert_eq!(name.file(), info.file);
+        assert_eq!(name.token, info.token);
+    }
+
+    #[test]
+    fn as_ref_keeps_range_and_file() {
+        let info = info();
+        let reg = With::new(Register::X10, info.clone());
+        let borrowed = reg.as_ref();
+        assert_eq!(*borrowed.data, Register::X10);
+        assert_eq!(borrowed.range(), info.pos);
+        assert_eq!(borrowed.file(), info.file);
+    }
+
+    #[test]
+    fn equality_ignores_location() {
+        // Wrapped data is equal wherever it came from
+        let (nodes, errors) = RVStringParser::parse_from_text("add a0, a0, a1\nadd a0, a0, a1\n");
+        assert_eq!(errors.len(), 0);
+        let regs = nodes
+            .iter()
+            .filter_map(ParserNode::stores_to)
+            .collect::<Vec<_>>();
+        assert_eq!(regs.len(), 2);
+        assert_ne!(regs[0].range(), regs[1].range());
+        assert_eq!(regs[0], regs[1]);
+        assert_eq!(
+            regs[0].clone().map(|reg| reg == Register::X10),
+            regs[1].clone().map(|_| true)
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// The width of the integer registers of the target.
+///
+/// Programs for RV32 and RV64 targets are written with the same
+/// instructions, but registers hold 32-bit or 64-bit values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Xlen {
+    /// 32-bit registers, as in RV32I.
+    #[default]
+    #[serde(alias = "rv32")]
+    Rv32,
+    /// 64-bit registers, as in RV64I.
+    #[serde(alias = "rv64")]
+    Rv64,
+}
+
+impl Xlen {
+    /// Get the number of bits in a register.
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        match self {
+            Xlen::Rv32 => 32,
+            Xlen::Rv64 => 64,
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::{collections::HashSet, fmt::Display};
 
 use crate::parser::{LabelString, ParserNode, With};
 
-use super::{DiagnosticLocation, DiagnosticMessage, SeverityLevel};
+use super::{DiagnosticLocation, DiagnosticMessage, RuleInfo, SeverityLevel};
 
 #[derive(Debug, Clone)]
 // TODO CfgErrors that do not require the whole thing to be re-run
@@ -13,8 +13,6 @@ use super::{DiagnosticLocation, DiagnosticMessage, SeverityLevel};
 /// the point of error. As much effort should be done to avoid these errors
 /// and to use `LintErrors`, as those are recoverable.
 pub enum CfgError {
-    /// This error occurs when a label is used but not defined.
-    LabelsNotDefined(HashSet<With<LabelString>>),
     /// This error occurs when a label is defined more than once.
     DuplicateLabel(With<LabelString>),
     /// This error occurs when a return statement is used but can be reached by
@@ -50,9 +48,6 @@ where
 impl Display for CfgError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            CfgError::LabelsNotDefined(labels) => {
-                write!(f, "Labels not defined: {}", labels.as_str_list())
-            }
             CfgError::DuplicateLabel(label) => {
                 write!(f, "Duplicate label: {label}")
             }
@@ -68,11 +63,20 @@ impl Display for CfgError {
     }
 }
 
+impl CfgError {
+    /// The rule of every error in generating the CFG.
+    pub const RULE: RuleInfo = RuleInfo::new(
+        "cfg-error",
+        "Control flow error",
+        SeverityLevel::Error,
+        "The control flow of the code could not be found, so it is not analyzed.",
+    );
+}
+
 impl From<&CfgError> for SeverityLevel {
     fn from(value: &CfgError) -> Self {
         match value {
-            CfgError::LabelsNotDefined(_)
-            | CfgError::DuplicateLabel(_)
+            CfgError::DuplicateLabel(_)
             | CfgError::MultipleLabelsForReturn(_, _)
             | CfgError::NoLabelForReturn(_)
             | CfgError::UnexpectedError
@@ -88,7 +92,6 @@ impl DiagnosticLocation for CfgError {
                 | CfgError::NoLabelForReturn(node) => {
                 node.file()
             }
-            CfgError::LabelsNotDefined(labels) => labels.iter().next().unwrap().file(),
             CfgError::DuplicateLabel(label) => label.file(),
             CfgError::UnexpectedError | CfgError::AssertionError => uuid::Uuid::nil(),
         }
@@ -100,7 +103,6 @@ impl DiagnosticLocation for CfgError {
                 | CfgError::NoLabelForReturn(node) => {
                 node.range()
             }
-            CfgError::LabelsNotDefined(labels) => labels.iter().next().unwrap().range(),
             CfgError::DuplicateLabel(label) => label.range(),
             CfgError::UnexpectedError | CfgError::AssertionError => crate::parser::Range::default(),
         }
@@ -108,6 +110,9 @@ impl DiagnosticLocation for CfgError {
 }
 
 impl DiagnosticMessage for CfgError {
+    fn code(&self) -> String {
+        Self::RULE.id.to_string()
+    }
     fn related(&self) -> Option<Vec<super::RelatedDiagnosticItem>> {
         None
     }
@@ -126,10 +131,6 @@ impl DiagnosticMessage for CfgError {
             CfgError::DuplicateLabel(label) => format!(
                 "The label {label} is defined more than once. Labels must be unique."
             ),
-            CfgError::LabelsNotDefined(labels) => format!(
-                "The labels {} are used but not defined. Labels must be defined within your file.",
-                labels.as_str_list()
-            ),
             CfgError::MultipleLabelsForReturn(_, labels) => format!(
                 "The return statement can be reached by multiple function labels: {}.\n\n\
                 Every return statement should only be reachable by one label. This also ensures\
@@ -10,11 +10,28 @@ pub trait DiagnosticLocation {
 }
 
 pub trait DiagnosticMessage {
+    /// The name of the lint or kind of error, like `dead-assignment`.
+    fn code(&self) -> String;
     fn title(&self) -> String;
     fn description(&self) -> String;
     fn long_description(&self) -> String;
     fn level(&self) -> SeverityLevel;
     fn related(&self) -> Option<Vec<RelatedDiagnosticItem>>;
+    /// Edits to the source that fix the issue, if any are known.
+    fn fixes(&self) -> Vec<TextEdit> {
+        Vec::new()
+    }
+}
+
+/// A change to the source that replaces a range of a file with new text.
+///
+/// An empty range inserts the text, and empty text removes the range. Each
+/// edit has its own file, as a fix can change more than one file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub file: Uuid,
+    pub range: Range,
+    pub replacement: String,
 }
 
 #[derive(Clone)]
@@ -28,16 +45,18 @@ pub struct RelatedDiagnosticItem {
 pub struct DiagnosticItem {
     pub file: Uuid,
     pub range: Range,
+    pub code: String,
     pub title: String,
     pub description: String,
     pub long_description: String,
     pub level: SeverityLevel,
     pub related: Option<Vec<RelatedDiagnosticItem>>,
+    pub fixes: Vec<TextEdit>,
 }
 
 impl PartialEq for DiagnosticItem {
     fn eq(&self, other: &Self) -> bool {
-        self.range == other.range && self.file == other.file
+        self.cmp(other) == std::cmp::Ordering::Equal
     }
 }
 impl Eq for DiagnosticItem {}
@@ -48,13 +67,17 @@ impl PartialOrd for DiagnosticItem {
     }
 }
 
+/// Diagnostics are ordered by their file, range and rule, then by their
+/// text, so that diagnostics at the same location are always sorted the same
+/// way.
 impl Ord for DiagnosticItem {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self.file == other.file {
-            self.range.cmp(&other.range)
-        } else {
-            self.file.cmp(&other.file)
-        }
+        self.file
+            .cmp(&other.file)
+            .then_with(|| self.range.cmp(&other.range))
+            .then_with(|| self.code.cmp(&other.code))
+            .then_with(|| self.title.cmp(&other.title))
+            .then_with(|| self.description.cmp(&other.description))
     }
 }
 
@@ -66,18 +89,225 @@ where
         let level = val.level();
         let range = val.range();
         let file = val.file();
+        let code = val.code();
         let title = val.title();
         let description = val.description();
         let long_description = val.long_description();
         let related = val.related();
+        let fixes = val.fixes();
         DiagnosticItem {
             file,
             range,
+            code,
             title,
             description,
             long_description,
             level,
             related,
+            fixes,
+        }
+    }
+}
+
+impl RelatedDiagnosticItem {
+    fn same_as(&self, other: &Self) -> bool {
+        self.file == other.file
+            && self.range == other.range
+            && self.description == other.description
+    }
+}
+
+impl DiagnosticItem {
+    /// Check if another diagnostic reports the same issue, with the same
+    /// rule and title in the same file and range.
+    fn same_issue(&self, other: &Self) -> bool {
+        self.file == other.file
+            && self.range == other.range
+            && self.code == other.code
+            && self.title == other.title
+    }
+
+    /// Merge another diagnostic of the same issue into this one.
+    ///
+    /// The related information of both is kept. If only the other
+    /// diagnostic has fixes, they are kept.
+    fn merge(&mut self, mut other: DiagnosticItem) {
+        if self.fixes.is_empty() {
+            self.fixes = std::mem::take(&mut other.fixes);
+        }
+
+        let mut related = self.related.take().unwrap_or_default();
+        for item in other.related.unwrap_or_default() {
+            if !related.iter().any(|r| r.same_as(&item)) {
+                related.push(item);
+            }
+        }
+        self.related = if related.is_empty() {
+            None
+        } else {
+            Some(related)
+        };
+    }
+}
+
+/// Combine diagnostics that report the same issue.
+///
+/// A lint can report the same issue more than once, like for each caller of
+/// a function. Diagnostics with the same rule and title in the same file and
+/// range are merged into one, with the related information of each.
+/// Diagnostics of different rules are always kept, as not every printer
+/// shows related information. The result is sorted by location.
+#[must_use]
+pub fn dedup_diagnostics(mut diagnostics: Vec<DiagnosticItem>) -> Vec<DiagnosticItem> {
+    diagnostics.sort();
+
+    let mut merged: Vec<DiagnosticItem> = Vec::with_capacity(diagnostics.len());
+    for item in diagnostics {
+        match merged.last_mut() {
+            Some(last) if last.same_issue(&item) => last.merge(item),
+            _ => merged.push(item),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Position;
+
+    fn item(line: usize, title: &str, level: SeverityLevel) -> DiagnosticItem {
+        let pos = Position {
+            line,
+            column: 0,
+            raw_index: 0,
+        };
+        DiagnosticItem {
+            file: Uuid::nil(),
+            range: Range {
+                start: pos,
+                end: pos,
+            },
+            code: String::new(),
+            title: title.to_string(),
+            description: String::new(),
+            long_description: String::new(),
+            level,
+            related: None,
+            fixes: Vec::new(),
         }
     }
+
+    #[test]
+    fn identical_diagnostics_are_collapsed() {
+        let diagnostics = dedup_diagnostics(vec![
+            item(1, "Unused value", SeverityLevel::Warning),
+            item(1, "Unused value", SeverityLevel::Warning),
+            item(2, "Unused value", SeverityLevel::Warning),
+        ]);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].related.is_none());
+    }
+
+    #[test]
+    fn different_diagnostics_are_kept() {
+        let with_code = |code: &str, title: &str, level| {
+            let mut item = item(1, title, level);
+            item.code = code.to_string();
+            item
+        };
+        let diagnostics = dedup_diagnostics(vec![
+            with_code("suspicious-value", "Suspicious value", SeverityLevel::Hint),
+            with_code("invalid-value", "Invalid value", SeverityLevel::Error),
+            with_code("suspicious-value", "Suspicious value", SeverityLevel::Hint),
+            with_code("suspicious-value", "Other value", SeverityLevel::Hint),
+        ]);
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics.iter().all(|d| d.related.is_none()));
+    }
+
+    #[test]
+    fn related_information_is_combined() {
+        let with_related = |description: &str| {
+            let mut item = item(1, "Unused value", SeverityLevel::Warning);
+            item.related = Some(vec![RelatedDiagnosticItem {
+                file: Uuid::nil(),
+                range: item.range.clone(),
+                description: description.to_string(),
+            }]);
+            item
+        };
+        let diagnostics = dedup_diagnostics(vec![
+            with_related("Call to fn_a"),
+            with_related("Call to fn_b"),
+            with_related("Call to fn_a"),
+        ]);
+        assert_eq!(diagnostics.len(), 1);
+        let related = diagnostics[0].related.as_ref().unwrap();
+        assert_eq!(related.len(), 2);
+    }
+
+    /// Get the line, rule and title of each diagnostic.
+    fn order(diagnostics: &[DiagnosticItem]) -> Vec<(usize, &str, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.range.start.line, d.code.as_str(), d.title.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn same_location_is_sorted_by_rule() {
+        let with_code = |code: &str, title: &str| {
+            let mut item = item(1, title, SeverityLevel::Warning);
+            item.code = code.to_string();
+            item
+        };
+        let mut diagnostics = vec![
+            with_code("unused-value", "Unused value"),
+            with_code("dead-assignment", "Unused value"),
+            item(0, "Invalid value", SeverityLevel::Error),
+            with_code("dead-assignment", "Dead assignment"),
+        ];
+        let mut reversed = diagnostics.clone();
+        reversed.reverse();
+        diagnostics.sort();
+        reversed.sort();
+
+        assert_eq!(order(&diagnostics), order(&reversed));
+        assert_eq!(
+            order(&diagnostics),
+            vec![
+                (0, "", "Invalid value"),
+                (1, "dead-assignment", "Dead assignment"),
+                (1, "dead-assignment", "Unused value"),
+                (1, "unused-value", "Unused value"),
+            ]
+        );
+    }
+
+    #[test]
+    fn fixes_are_kept_when_merged() {
+        let mut fixed = item(1, "Suspicious value", SeverityLevel::Hint);
+        fixed.fixes.push(TextEdit {
+            file: Uuid::nil(),
+            range: fixed.range.clone(),
+            replacement: "nop\n".to_string(),
+        });
+        let diagnostics = dedup_diagnostics(vec![
+            item(1, "Suspicious value", SeverityLevel::Hint),
+            fixed,
+        ]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].fixes.len(), 1);
+        assert_eq!(diagnostics[0].fixes[0].replacement, "nop\n");
+    }
+
+    #[test]
+    fn diagnostics_in_other_files_are_kept() {
+        let mut other = item(1, "Unused value", SeverityLevel::Warning);
+        other.file = Uuid::from_u128(1);
+        let diagnostics =
+            dedup_diagnostics(vec![item(1, "Unused value", SeverityLevel::Warning), other]);
+        assert_eq!(diagnostics.len(), 2);
+    }
 }
@@ -1,19 +1,30 @@
+use std::ops::RangeInclusive;
 use std::rc::Rc;
+use std::str::FromStr;
 
 use uuid::Uuid;
 
 use crate::cfg::Function;
 
+use crate::parser::AnnotationKind;
+use crate::parser::ArithType;
+use crate::parser::CSRImm;
+use crate::parser::Imm;
+use crate::parser::Inst;
 use crate::parser::LabelString;
+use crate::parser::LoadType;
 use crate::parser::ParserNode;
 use crate::parser::Range;
 use crate::parser::Register;
+use crate::parser::Type;
 use crate::parser::With;
 
 use itertools::Itertools;
+use serde::Deserialize;
 
 use super::DiagnosticLocation;
 use super::DiagnosticMessage;
+use super::RuleInfo;
 
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
@@ -22,10 +33,12 @@ pub enum LintError {
     // if a branch is always going to execute (i.e. if true) using constants and zero register
     LostRegisterValue(With<Register>),
 
-    /// A register 0 is used after a call to function 1 at call site 2
-    InvalidUseAfterCall(With<Register>, Rc<Function>, With<LabelString>),
+    /// A register 0 is used after a call to function 1 at call site 2,
+    /// which is inside a loop if 3 is true
+    InvalidUseAfterCall(With<Register>, Rc<Function>, With<LabelString>, bool),
     InvalidUseBeforeAssignment(With<Register>),
-    OverwriteCalleeSavedRegister(With<Register>),
+    /// A callee-saved register 0 is overwritten and not restored by function 1
+    OverwriteCalleeSavedRegister(With<Register>, Rc<Function>),
     FirstInstructionIsFunction(ParserNode, Rc<Function>), // if the first instruction has a function, it is incorrect
     /// A function is entered through a non-conventional way
     ///
@@ -36,14 +49,14 @@ pub enum LintError {
     /// (First line in function, line where function is entered through, function)
     InvalidJumpToFunction(ParserNode, ParserNode, Rc<Function>),
     DeadAssignment(With<Register>),
-    SaveToZero(With<Register>),
+    SaveToZero(With<Register>, Option<Register>), // the register that was likely meant
     InvalidSegment(ParserNode),
     UnknownEcall(ParserNode),
     UnknownStack(ParserNode),        // stack value is not definitely known
     InvalidStackPointer(ParserNode), // stack value is being overwritten
     InvalidStackPosition(ParserNode, i32), // stack value is wrong way (positive)
     InvalidStackOffsetUsage(ParserNode, i32), // read/write using invalid stack offser
-    UnreachableCode(ParserNode),     // -- code that is unreachable
+    UnreachableCode(ParserNode, ParserNode, usize, Option<LabelString>), // (first, last, count, label)
                                      // SetBadRegister(Range, Register), -- used when setting registers that should not be set
                                      // FallOffEnd(Range), program may fall off the end of code
                                      // InvalidControlFlowRead(Range), -- reading from a register that is not assigned to
@@ -53,10 +66,183 @@ pub enum LintError {
                                      // AnyJumpToData -- if any jump is to a data label, then it is a warning (label strings should have data/text prefix)
 
     /// An instruction is a member of more than one function.
-    NodeInManyFunctions(ParserNode, Vec<Rc<Function>>)
+    NodeInManyFunctions(ParserNode, Vec<Rc<Function>>),
+
+    /// A register is copied into a register that already holds the same value.
+    ///
+    /// This is usually a broken attempt to swap two registers without a
+    /// temporary register.
+    ///
+    /// (Ineffective copy, earlier copy that made the registers equal)
+    IneffectiveSwap(ParserNode, ParserNode),
+
+    /// Both paths of a conditional branch continue at the same instruction.
+    RedundantBranch(ParserNode),
+
+    /// A function has more instructions than the configured maximum.
+    ///
+    /// (Function entry, function, number of instructions, maximum)
+    FunctionTooLarge(ParserNode, Rc<Function>, usize, usize),
+
+    /// A register holding the result of a comparison (0 or 1) is used as an
+    /// address or shift amount.
+    BooleanMisuse(With<Register>),
+
+    /// A callee-saved register is saved and restored by a function that
+    /// never changes it.
+    ///
+    /// (Save, restores, register)
+    UnnecessaryCalleeSave(ParserNode, Vec<ParserNode>, Register),
+
+    /// Arithmetic on known constants overflows a register.
+    ///
+    /// (Destination register, exact result, wrapped result, bits of the
+    /// arithmetic)
+    ConstantOverflow(With<Register>, i128, i64, u32),
+
+    /// The return address register is overwritten with a value that is not
+    /// a return address before the function returns.
+    ///
+    /// (Write to the register, returns that the write reaches)
+    ReturnAddressOverwritten(With<Register>, Vec<ParserNode>),
+
+    /// A sign or zero extension of a value that is already extended.
+    ///
+    /// (Extension, shift that starts the extension)
+    RedundantExtension(ParserNode, Option<ParserNode>),
+
+    /// A memory access whose width does not match the data declared at the
+    /// label it accesses.
+    ///
+    /// (Memory access, label of the data, width of the declared values)
+    MismatchedDataWidth(ParserNode, With<LabelString>, usize),
+
+    /// A branch that is the only way out of a loop compares registers that
+    /// are never written inside the loop.
+    ///
+    /// (Branch, header of the loop, registers compared)
+    UnchangedLoopCondition(ParserNode, ParserNode, Vec<Register>),
+
+    /// A register is subtracted from or exclusive ored with itself, which
+    /// always results in zero.
+    SelfCancellingArith(ParserNode),
+
+    /// An argument register is set up for a call to function 1 at call site
+    /// 2, but the function never reads it.
+    UnusedCallArgument(With<Register>, Rc<Function>, With<LabelString>),
+
+    /// A stack slot is read after the stack pointer was moved above it.
+    ///
+    /// (Load, bytes between the slot and the stack pointer, stores to the
+    /// slot)
+    FreedStackRead(ParserNode, i32, Vec<ParserNode>),
+
+    /// Function 1, starting at entry node 0, reads temporary registers that
+    /// it never set. The first reads are 2.
+    TemporaryReadOnEntry(ParserNode, Rc<Function>, Vec<With<Register>>),
+
+    /// An instruction that only exists on RV64 is used on an RV32 target.
+    Rv64OnlyInstruction(ParserNode),
+
+    /// A stack pointer adjustment leaves the stack pointer unaligned at a
+    /// call.
+    ///
+    /// (Adjustment, offset of the stack pointer from the start of the
+    /// function, calls)
+    UnalignedStackPointer(ParserNode, i32, Vec<ParserNode>),
+
+    /// A stack slot is read before anything is stored to it.
+    ///
+    /// (Load, offset of the slot from the stack pointer at the start of the
+    /// function, whether no path stores to the slot)
+    UninitializedStackRead(ParserNode, i32, bool),
+
+    /// A jump through a register other than `ra` goes to an unknown place,
+    /// so the CFG has no edge for it.
+    UnknownJumpTarget(ParserNode),
+
+    /// An argument register of a system call is not set on every path to
+    /// the `ecall`.
+    ///
+    /// (Ecall, argument register, name of the system call)
+    UninitializedEcallArgument(ParserNode, Register, &'static str),
+
+    /// A load reads a value that an earlier load already put in a
+    /// register, and neither the address nor the memory changed since.
+    ///
+    /// (Load, earlier load, register holding the value)
+    RedundantLoad(ParserNode, ParserNode, Register),
+
+    /// Load 0 reads the same memory on every iteration of the loop that
+    /// starts at node 1.
+    LoopInvariantLoad(ParserNode, ParserNode),
+
+    /// An immediate does not fit in the bits that its instruction encodes
+    /// it in.
+    ///
+    /// (Immediate, instruction, values that the immediate can hold)
+    ImmediateOutOfRange(With<Imm>, Inst, RangeInclusive<i32>),
+
+    /// Function 1 reads argument register 0 before setting it, but its
+    /// arguments, found from the argument registers it reads in order, are
+    /// only 2.
+    ArgumentBeyondArity(With<Register>, Rc<Function>, Vec<Register>),
+
+    /// A branch compares a register with itself, so it is always or never
+    /// taken.
+    ///
+    /// (Branch, whether the branch is always taken)
+    ConstantBranch(ParserNode, bool),
+
+    /// The values that a branch compares are known on every path to it, so
+    /// it always goes the same way.
+    ///
+    /// (Branch, whether the branch is always taken)
+    KnownBranchDirection(ParserNode, bool),
+
+    /// A path through a function returns without undoing its stack pointer
+    /// adjustments.
+    ///
+    /// (Return, offset of the stack pointer from the start of the function,
+    /// adjustments on the path)
+    UnbalancedStack(ParserNode, i32, Vec<ParserNode>),
+
+    /// The annotations of function 1 do not match how it uses register 0.
+    ///
+    /// For `Arg`, the register is read as an argument but not declared. For
+    /// `Returns`, the register is declared as returned but not set.
+    AnnotationMismatch(With<Register>, Rc<Function>, AnnotationKind),
+
+    /// A label is used, but it is not defined in any file, and it is not
+    /// declared with `.globl` or `.extern`.
+    UndefinedLabel(With<LabelString>),
+
+    /// A CSR address is larger than 12 bits.
+    CsrOutOfRange(With<CSRImm>),
+
+    /// A CSR that can only be read is written.
+    ReadOnlyCsrWrite(With<CSRImm>),
+
+    /// A diagnostic from a lint pass that is not built in.
+    Custom(CustomDiagnostic),
 }
 
-#[derive(Clone)]
+/// A diagnostic reported by a lint pass that is not built in, like one added
+/// with [`ManagerConfiguration::custom_lints`](super::ManagerConfiguration::custom_lints).
+#[derive(Debug, Clone)]
+pub struct CustomDiagnostic {
+    /// The name of the lint, like `no-nop`, which is used to change its
+    /// level.
+    pub name: &'static str,
+    /// The node that the diagnostic is reported at.
+    pub node: ParserNode,
+    pub level: SeverityLevel,
+    pub title: String,
+    pub description: String,
+}
+
+/// The severity of a diagnostic, from most to least severe.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SeverityLevel {
     Error,
     Warning,
@@ -67,45 +253,548 @@ pub enum SeverityLevel {
 impl From<&LintError> for SeverityLevel {
     fn from(val: &LintError) -> Self {
         match val {
-            LintError::DeadAssignment(_)
-            | LintError::SaveToZero(_)
-            | LintError::InvalidSegment(_)
-            | LintError::InvalidJumpToFunction(..)
-            | LintError::FirstInstructionIsFunction(..)
-            | LintError::LostRegisterValue(_)
-            | LintError::NodeInManyFunctions(..)
-            | LintError::UnreachableCode(_) => SeverityLevel::Warning,
-            LintError::UnknownEcall(_)
-            | LintError::InvalidUseAfterCall(..)
-            | LintError::InvalidUseBeforeAssignment(_)
-            | LintError::UnknownStack(_)
-            | LintError::InvalidStackPointer(_)
-            | LintError::InvalidStackPosition(_, _)
-            | LintError::InvalidStackOffsetUsage(_, _)
-            | LintError::OverwriteCalleeSavedRegister(_) => SeverityLevel::Error,
+            // Reads that are sure to be of uninitialized memory are errors
+            LintError::UninitializedStackRead(.., true) => SeverityLevel::Error,
+            LintError::Custom(custom) => custom.level.clone(),
+            _ => val
+                .rule()
+                .map_or(SeverityLevel::Warning, |rule| rule.level.clone()),
+        }
+    }
+}
+
+impl LintError {
+    /// The names of every lint, as returned by [`LintError::name`], taken
+    /// from [`LintError::RULES`].
+    // An index out of range fails to compile, as this is a constant
+    #[allow(clippy::indexing_slicing)]
+    pub const NAMES: [&'static str; Self::RULES.len()] = {
+        let mut names = [""; Self::RULES.len()];
+        let mut i = 0;
+        while i < names.len() {
+            names[i] = Self::RULES[i].id;
+            i += 1;
+        }
+        names
+    };
+
+    /// The rule of every lint. Each lint has a constant with its rule, like
+    /// [`LintError::DEAD_ASSIGNMENT`], which is where the name, the title
+    /// and the level of the lint are kept.
+    pub const RULES: &'static [RuleInfo] = &[
+        Self::DEAD_ASSIGNMENT,
+        Self::SAVE_TO_ZERO,
+        Self::INVALID_USE_AFTER_CALL,
+        Self::INVALID_USE_BEFORE_ASSIGNMENT,
+        Self::OVERWRITE_CALLEE_SAVED_REGISTER,
+        Self::LOST_REGISTER_VALUE,
+        Self::INVALID_SEGMENT,
+        Self::INVALID_JUMP_TO_FUNCTION,
+        Self::FIRST_INSTRUCTION_IS_FUNCTION,
+        Self::UNREACHABLE_CODE,
+        Self::UNKNOWN_JUMP_TARGET,
+        Self::UNKNOWN_ECALL,
+        Self::UNKNOWN_STACK,
+        Self::INVALID_STACK_POINTER,
+        Self::INVALID_STACK_POSITION,
+        Self::INVALID_STACK_OFFSET_USAGE,
+        Self::NODE_IN_MANY_FUNCTIONS,
+        Self::INEFFECTIVE_SWAP,
+        Self::REDUNDANT_BRANCH,
+        Self::FUNCTION_TOO_LARGE,
+        Self::BOOLEAN_MISUSE,
+        Self::UNNECESSARY_CALLEE_SAVE,
+        Self::CONSTANT_OVERFLOW,
+        Self::RETURN_ADDRESS_OVERWRITTEN,
+        Self::REDUNDANT_EXTENSION,
+        Self::MISMATCHED_DATA_WIDTH,
+        Self::UNCHANGED_LOOP_CONDITION,
+        Self::SELF_CANCELLING_ARITH,
+        Self::UNUSED_CALL_ARGUMENT,
+        Self::FREED_STACK_READ,
+        Self::TEMPORARY_READ_ON_ENTRY,
+        Self::RV64_ONLY_INSTRUCTION,
+        Self::UNALIGNED_STACK_POINTER,
+        Self::UNINITIALIZED_STACK_READ,
+        Self::UNINITIALIZED_ECALL_ARGUMENT,
+        Self::REDUNDANT_LOAD,
+        Self::IMMEDIATE_OUT_OF_RANGE,
+        Self::ARGUMENT_BEYOND_ARITY,
+        Self::CONSTANT_BRANCH,
+        Self::KNOWN_BRANCH_DIRECTION,
+        Self::UNBALANCED_STACK,
+        Self::ANNOTATION_MISMATCH,
+        Self::UNDEFINED_LABEL,
+        Self::CSR_OUT_OF_RANGE,
+        Self::READ_ONLY_CSR_WRITE,
+        Self::LOOP_INVARIANT_LOAD,
+    ];
+
+    pub const DEAD_ASSIGNMENT: RuleInfo = RuleInfo::new(
+        "dead-assignment",
+        "Unused value",
+        SeverityLevel::Warning,
+        "A value is written to a register, but it is never read.",
+    );
+
+    pub const SAVE_TO_ZERO: RuleInfo = RuleInfo::new(
+        "save-to-zero",
+        "Saving to zero register",
+        SeverityLevel::Warning,
+        "The result of an instruction is written to x0, so it is lost.",
+    );
+
+    pub const INVALID_USE_AFTER_CALL: RuleInfo = RuleInfo::new(
+        "invalid-use-after-call",
+        "Invalid use after call",
+        SeverityLevel::Error,
+        "A register that a call does not keep or return is read after the call.",
+    );
+
+    pub const INVALID_USE_BEFORE_ASSIGNMENT: RuleInfo = RuleInfo::new(
+        "invalid-use-before-assignment",
+        "Invalid use before assignment",
+        SeverityLevel::Error,
+        "A register is read before it is set.",
+    );
+
+    pub const OVERWRITE_CALLEE_SAVED_REGISTER: RuleInfo = RuleInfo::new(
+        "overwrite-callee-saved-register",
+        "Overwriting callee-saved register",
+        SeverityLevel::Error,
+        "A function changes a callee-saved register without restoring it.",
+    );
+
+    pub const LOST_REGISTER_VALUE: RuleInfo = RuleInfo::new(
+        "lost-register-value",
+        "Lost register value",
+        SeverityLevel::Warning,
+        "A function changes a callee-saved register, and its original value is lost.",
+    );
+
+    pub const INVALID_SEGMENT: RuleInfo = RuleInfo::new(
+        "invalid-segment",
+        "Node is in the incorrect segment",
+        SeverityLevel::Warning,
+        "An instruction is in the data segment, or data is in the text segment.",
+    );
+
+    pub const INVALID_JUMP_TO_FUNCTION: RuleInfo = RuleInfo::new(
+        "invalid-jump-to-function",
+        "Invalid jump to function",
+        SeverityLevel::Warning,
+        "Code jumps into a function without calling it.",
+    );
+
+    pub const FIRST_INSTRUCTION_IS_FUNCTION: RuleInfo = RuleInfo::new(
+        "first-instruction-is-function",
+        "First instruction is in function",
+        SeverityLevel::Warning,
+        "The program starts in the middle of a function.",
+    );
+
+    pub const UNREACHABLE_CODE: RuleInfo = RuleInfo::new(
+        "unreachable-code",
+        "Unreachable code",
+        SeverityLevel::Warning,
+        "A run of code that no path from an entry reaches.",
+    );
+
+    pub const UNKNOWN_JUMP_TARGET: RuleInfo = RuleInfo::new(
+        "unknown-jump-target",
+        "Jump target is not known",
+        SeverityLevel::Information,
+        "A jump through a register goes to a place that is not known.",
+    );
+
+    pub const UNKNOWN_ECALL: RuleInfo = RuleInfo::new(
+        "unknown-ecall",
+        "Unknown ecall",
+        SeverityLevel::Error,
+        "The system call of an ecall is not known.",
+    );
+
+    pub const UNKNOWN_STACK: RuleInfo = RuleInfo::new(
+        "unknown-stack",
+        "Unknown stack value",
+        SeverityLevel::Error,
+        "The value of the stack pointer is not known.",
+    );
+
+    pub const INVALID_STACK_POINTER: RuleInfo = RuleInfo::new(
+        "invalid-stack-pointer",
+        "Invalid stack pointer",
+        SeverityLevel::Error,
+        "The stack pointer is set to a value that is not an offset of itself.",
+    );
+
+    pub const INVALID_STACK_POSITION: RuleInfo = RuleInfo::new(
+        "invalid-stack-position",
+        "Invalid stack position",
+        SeverityLevel::Error,
+        "The stack pointer is moved above where it started.",
+    );
+
+    pub const INVALID_STACK_OFFSET_USAGE: RuleInfo = RuleInfo::new(
+        "invalid-stack-offset-usage",
+        "Invalid stack offset usage",
+        SeverityLevel::Error,
+        "Memory is accessed above where the stack pointer started.",
+    );
+
+    pub const NODE_IN_MANY_FUNCTIONS: RuleInfo = RuleInfo::new(
+        "node-in-many-functions",
+        "Part of multiple functions",
+        SeverityLevel::Warning,
+        "An instruction is part of more than one function.",
+    );
+
+    pub const INEFFECTIVE_SWAP: RuleInfo = RuleInfo::new(
+        "ineffective-swap",
+        "Ineffective register swap",
+        SeverityLevel::Warning,
+        "Two registers are copied into each other, so both hold the same value.",
+    );
+
+    pub const REDUNDANT_BRANCH: RuleInfo = RuleInfo::new(
+        "redundant-branch",
+        "Branch condition has no effect",
+        SeverityLevel::Hint,
+        "Both paths of a branch continue at the same instruction.",
+    );
+
+    pub const FUNCTION_TOO_LARGE: RuleInfo = RuleInfo::new(
+        "function-too-large",
+        "Function is too large",
+        SeverityLevel::Hint,
+        "A function has more instructions than the configured maximum.",
+    );
+
+    pub const BOOLEAN_MISUSE: RuleInfo = RuleInfo::new(
+        "boolean-misuse",
+        "Suspicious use of comparison result",
+        SeverityLevel::Hint,
+        "The result of a comparison is used as an address, jump target or shift amount.",
+    );
+
+    pub const UNNECESSARY_CALLEE_SAVE: RuleInfo = RuleInfo::new(
+        "unnecessary-callee-save",
+        "Register is saved but never changed",
+        SeverityLevel::Hint,
+        "A callee-saved register is saved and restored by a function that never changes it.",
+    );
+
+    pub const CONSTANT_OVERFLOW: RuleInfo = RuleInfo::new(
+        "constant-overflow",
+        "Arithmetic overflow",
+        SeverityLevel::Warning,
+        "Arithmetic on known values overflows a register.",
+    );
+
+    pub const RETURN_ADDRESS_OVERWRITTEN: RuleInfo = RuleInfo::new(
+        "return-address-overwritten",
+        "Return address is overwritten before returning",
+        SeverityLevel::Error,
+        "A function changes ra and returns without restoring it.",
+    );
+
+    pub const REDUNDANT_EXTENSION: RuleInfo = RuleInfo::new(
+        "redundant-extension",
+        "Extension has no effect",
+        SeverityLevel::Hint,
+        "A sign or zero extension does not change its value.",
+    );
+
+    pub const MISMATCHED_DATA_WIDTH: RuleInfo = RuleInfo::new(
+        "mismatched-data-width",
+        "Access width does not match data",
+        SeverityLevel::Warning,
+        "Memory is accessed with a width that does not match its declaration.",
+    );
+
+    pub const UNCHANGED_LOOP_CONDITION: RuleInfo = RuleInfo::new(
+        "unchanged-loop-condition",
+        "Loop condition never changes",
+        SeverityLevel::Warning,
+        "The registers that a loop branches on never change inside the loop.",
+    );
+
+    pub const SELF_CANCELLING_ARITH: RuleInfo = RuleInfo::new(
+        "self-cancelling-arith",
+        "Result is always zero",
+        SeverityLevel::Hint,
+        "A register is subtracted or exclusive ored with itself.",
+    );
+
+    pub const UNUSED_CALL_ARGUMENT: RuleInfo = RuleInfo::new(
+        "unused-call-argument",
+        "Function does not read argument",
+        SeverityLevel::Hint,
+        "An argument is set up for a call, but the function never reads it.",
+    );
+
+    pub const FREED_STACK_READ: RuleInfo = RuleInfo::new(
+        "freed-stack-read",
+        "Read from freed stack memory",
+        SeverityLevel::Error,
+        "Stack memory is read after the stack pointer is moved above it.",
+    );
+
+    pub const TEMPORARY_READ_ON_ENTRY: RuleInfo = RuleInfo::new(
+        "temporary-read-on-entry",
+        "Function reads a temporary register it never set",
+        SeverityLevel::Warning,
+        "A function reads a temporary register before setting it.",
+    );
+
+    pub const RV64_ONLY_INSTRUCTION: RuleInfo = RuleInfo::new(
+        "rv64-only-instruction",
+        "Instruction is only available on RV64",
+        SeverityLevel::Error,
+        "An instruction that only exists on RV64 is used on an RV32 target.",
+    );
+
+    pub const UNALIGNED_STACK_POINTER: RuleInfo = RuleInfo::new(
+        "unaligned-stack-pointer",
+        "Stack pointer is not 16-byte aligned at a call",
+        SeverityLevel::Warning,
+        "The stack pointer is not a multiple of 16 when a function is called.",
+    );
+
+    pub const UNINITIALIZED_STACK_READ: RuleInfo = RuleInfo::new(
+        "uninitialized-stack-read",
+        "Read from possibly uninitialized stack memory",
+        SeverityLevel::Warning,
+        "A stack slot is read before a value is stored to it.",
+    );
+
+    pub const UNINITIALIZED_ECALL_ARGUMENT: RuleInfo = RuleInfo::new(
+        "uninitialized-ecall-argument",
+        "Argument of system call is not set",
+        SeverityLevel::Warning,
+        "An argument register of a system call is not set before the ecall.",
+    );
+
+    pub const REDUNDANT_LOAD: RuleInfo = RuleInfo::new(
+        "redundant-load",
+        "Load is redundant",
+        SeverityLevel::Hint,
+        "A load reads a value that is already in a register.",
+    );
+
+    pub const IMMEDIATE_OUT_OF_RANGE: RuleInfo = RuleInfo::new(
+        "immediate-out-of-range",
+        "Immediate is out of range",
+        SeverityLevel::Error,
+        "An immediate does not fit in the instruction it is written in.",
+    );
+
+    pub const ARGUMENT_BEYOND_ARITY: RuleInfo = RuleInfo::new(
+        "argument-beyond-arity",
+        "Function reads an argument it does not take",
+        SeverityLevel::Warning,
+        "A function reads an argument register past the arguments it takes.",
+    );
+
+    pub const CONSTANT_BRANCH: RuleInfo = RuleInfo::new(
+        "constant-branch",
+        "Branch compares a register with itself",
+        SeverityLevel::Warning,
+        "A branch compares a register with itself, so it is always or never taken.",
+    );
+
+    pub const KNOWN_BRANCH_DIRECTION: RuleInfo = RuleInfo::new(
+        "known-branch-direction",
+        "Branch direction is known",
+        SeverityLevel::Information,
+        "The values that a branch compares are known, so it always goes the same way.",
+    );
+
+    pub const UNBALANCED_STACK: RuleInfo = RuleInfo::new(
+        "unbalanced-stack",
+        "Stack pointer is not restored before returning",
+        SeverityLevel::Error,
+        "A path through a function returns without undoing its stack pointer adjustments.",
+    );
+
+    pub const ANNOTATION_MISMATCH: RuleInfo = RuleInfo::new(
+        "annotation-mismatch",
+        "Function does not match its annotations",
+        SeverityLevel::Warning,
+        "A function reads an argument that it does not declare, or does not set a declared return value.",
+    );
+
+    pub const UNDEFINED_LABEL: RuleInfo = RuleInfo::new(
+        "undefined-label",
+        "Label is not defined",
+        SeverityLevel::Error,
+        "A label is used, but it is not defined in any file of the program.",
+    );
+
+    pub const CSR_OUT_OF_RANGE: RuleInfo = RuleInfo::new(
+        "csr-out-of-range",
+        "CSR address is out of range",
+        SeverityLevel::Warning,
+        "A CSR address does not fit in 12 bits.",
+    );
+
+    pub const READ_ONLY_CSR_WRITE: RuleInfo = RuleInfo::new(
+        "read-only-csr-write",
+        "Read-only CSR is written",
+        SeverityLevel::Warning,
+        "An instruction writes to a CSR that can only be read.",
+    );
+
+    pub const LOOP_INVARIANT_LOAD: RuleInfo = RuleInfo::new(
+        "loop-invariant-load",
+        "Load does not change in the loop",
+        SeverityLevel::Hint,
+        "A load inside a loop reads the same memory on every iteration.",
+    );
+
+    /// Get the name of the lint that reported this error.
+    ///
+    /// Names are stable, so they can be used in configuration files to
+    /// change the level of a lint or to turn it off.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self.rule_or_custom() {
+            Ok(rule) => rule.id,
+            Err(custom) => custom.name,
+        }
+    }
+
+    /// Get the rule of the lint that reported this error.
+    ///
+    /// Custom lints do not have a rule.
+    #[must_use]
+    pub fn rule(&self) -> Option<&'static RuleInfo> {
+        self.rule_or_custom().ok()
+    }
+
+    /// Get the rule of the lint, or the diagnostic of a custom lint.
+    fn rule_or_custom(&self) -> Result<&'static RuleInfo, &CustomDiagnostic> {
+        let rule = match self {
+            LintError::DeadAssignment(..) => &Self::DEAD_ASSIGNMENT,
+            LintError::SaveToZero(..) => &Self::SAVE_TO_ZERO,
+            LintError::InvalidUseAfterCall(..) => &Self::INVALID_USE_AFTER_CALL,
+            LintError::InvalidUseBeforeAssignment(..) => &Self::INVALID_USE_BEFORE_ASSIGNMENT,
+            LintError::OverwriteCalleeSavedRegister(..) => &Self::OVERWRITE_CALLEE_SAVED_REGISTER,
+            LintError::LostRegisterValue(..) => &Self::LOST_REGISTER_VALUE,
+            LintError::InvalidSegment(..) => &Self::INVALID_SEGMENT,
+            LintError::InvalidJumpToFunction(..) => &Self::INVALID_JUMP_TO_FUNCTION,
+            LintError::FirstInstructionIsFunction(..) => &Self::FIRST_INSTRUCTION_IS_FUNCTION,
+            LintError::UnreachableCode(..) => &Self::UNREACHABLE_CODE,
+            LintError::UnknownJumpTarget(..) => &Self::UNKNOWN_JUMP_TARGET,
+            LintError::UnknownEcall(..) => &Self::UNKNOWN_ECALL,
+            LintError::UnknownStack(..) => &Self::UNKNOWN_STACK,
+            LintError::InvalidStackPointer(..) => &Self::INVALID_STACK_POINTER,
+            LintError::InvalidStackPosition(..) => &Self::INVALID_STACK_POSITION,
+            LintError::InvalidStackOffsetUsage(..) => &Self::INVALID_STACK_OFFSET_USAGE,
+            LintError::NodeInManyFunctions(..) => &Self::NODE_IN_MANY_FUNCTIONS,
+            LintError::IneffectiveSwap(..) => &Self::INEFFECTIVE_SWAP,
+            LintError::RedundantBranch(..) => &Self::REDUNDANT_BRANCH,
+            LintError::FunctionTooLarge(..) => &Self::FUNCTION_TOO_LARGE,
+            LintError::BooleanMisuse(..) => &Self::BOOLEAN_MISUSE,
+            LintError::UnnecessaryCalleeSave(..) => &Self::UNNECESSARY_CALLEE_SAVE,
+            LintError::ConstantOverflow(..) => &Self::CONSTANT_OVERFLOW,
+            LintError::ReturnAddressOverwritten(..) => &Self::RETURN_ADDRESS_OVERWRITTEN,
+            LintError::RedundantExtension(..) => &Self::REDUNDANT_EXTENSION,
+            LintError::MismatchedDataWidth(..) => &Self::MISMATCHED_DATA_WIDTH,
+            LintError::UnchangedLoopCondition(..) => &Self::UNCHANGED_LOOP_CONDITION,
+            LintError::SelfCancellingArith(..) => &Self::SELF_CANCELLING_ARITH,
+            LintError::UnusedCallArgument(..) => &Self::UNUSED_CALL_ARGUMENT,
+            LintError::FreedStackRead(..) => &Self::FREED_STACK_READ,
+            LintError::TemporaryReadOnEntry(..) => &Self::TEMPORARY_READ_ON_ENTRY,
+            LintError::Rv64OnlyInstruction(..) => &Self::RV64_ONLY_INSTRUCTION,
+            LintError::UnalignedStackPointer(..) => &Self::UNALIGNED_STACK_POINTER,
+            LintError::UninitializedStackRead(..) => &Self::UNINITIALIZED_STACK_READ,
+            LintError::UninitializedEcallArgument(..) => &Self::UNINITIALIZED_ECALL_ARGUMENT,
+            LintError::RedundantLoad(..) => &Self::REDUNDANT_LOAD,
+            LintError::ImmediateOutOfRange(..) => &Self::IMMEDIATE_OUT_OF_RANGE,
+            LintError::ArgumentBeyondArity(..) => &Self::ARGUMENT_BEYOND_ARITY,
+            LintError::ConstantBranch(..) => &Self::CONSTANT_BRANCH,
+            LintError::KnownBranchDirection(..) => &Self::KNOWN_BRANCH_DIRECTION,
+            LintError::UnbalancedStack(..) => &Self::UNBALANCED_STACK,
+            LintError::AnnotationMismatch(..) => &Self::ANNOTATION_MISMATCH,
+            LintError::UndefinedLabel(_) => &Self::UNDEFINED_LABEL,
+            LintError::CsrOutOfRange(_) => &Self::CSR_OUT_OF_RANGE,
+            LintError::ReadOnlyCsrWrite(_) => &Self::READ_ONLY_CSR_WRITE,
+            LintError::LoopInvariantLoad(..) => &Self::LOOP_INVARIANT_LOAD,
+            LintError::Custom(custom) => return Err(custom),
+        };
+        Ok(rule)
+    }
+
+    /// Name a branch by the mnemonic it was written with, like `` `bgt` ``.
+    fn branch_name(node: &ParserNode) -> String {
+        match node {
+            ParserNode::Branch(branch) => format!("`{}`", branch.mnemonic()),
+            _ => "branch".to_string(),
+        }
+    }
+}
+
+/// The level that a lint is reported at, set by
+/// [`ManagerConfiguration::lint_levels`](super::ManagerConfiguration::lint_levels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// The lint is not run, and nothing is reported.
+    Off,
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl LintLevel {
+    /// Get the severity that the lint is reported with, if it is not off.
+    #[must_use]
+    pub fn severity(self) -> Option<SeverityLevel> {
+        match self {
+            LintLevel::Off => None,
+            LintLevel::Error => Some(SeverityLevel::Error),
+            LintLevel::Warning => Some(SeverityLevel::Warning),
+            LintLevel::Information => Some(SeverityLevel::Information),
+            LintLevel::Hint => Some(SeverityLevel::Hint),
+        }
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(LintLevel::Off),
+            "error" => Ok(LintLevel::Error),
+            "warning" => Ok(LintLevel::Warning),
+            "information" => Ok(LintLevel::Information),
+            "hint" => Ok(LintLevel::Hint),
+            _ => Err(()),
         }
     }
 }
 
 // implement display for passerror
 impl std::fmt::Display for LintError {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            LintError::DeadAssignment(_) => write!(f, "Unused value"),
-            LintError::SaveToZero(_) => write!(f, "Saving to zero register"),
-            LintError::InvalidUseAfterCall(_, func, _) => {
+            LintError::InvalidUseAfterCall(_, func, _, false) => {
                 write!(f, "Invalid use after call to function {}", func.name())
             }
-            LintError::InvalidSegment(_) => write!(f, "Node is in the incorrect segment"),
-            LintError::InvalidJumpToFunction(..) => write!(f, "Invalid jump to function"),
+            LintError::InvalidUseAfterCall(_, func, _, true) => {
+                write!(
+                    f,
+                    "Invalid use after call to function {} in a loop",
+                    func.name()
+                )
+            }
             LintError::FirstInstructionIsFunction(_, func) => {
                 write!(f, "First instruction is in function {}", func.name())
             }
-            LintError::UnknownEcall(_) => write!(f, "Unknown ecall"),
-            LintError::UnreachableCode(_) => write!(f, "Unreachable code"),
-            LintError::InvalidUseBeforeAssignment(_) => write!(f, "Invalid use before assignment"),
-            LintError::UnknownStack(_) => write!(f, "Unknown stack value"),
-            LintError::InvalidStackPointer(_) => write!(f, "Invalid stack pointer"),
             LintError::InvalidStackPosition(_, i) => write!(
                 f,
                 "Invalid stack position: original sp {} {}",
@@ -118,9 +807,6 @@ impl std::fmt::Display for LintError {
                 },
                 i.abs()
             ),
-            LintError::OverwriteCalleeSavedRegister(_) => {
-                write!(f, "Overwriting callee-saved register")
-            }
             LintError::LostRegisterValue(r) => {
                 write!(f, "Lost register value: {}", r.data)
             }
@@ -145,11 +831,87 @@ impl std::fmt::Display for LintError {
                        .join(" | ")
                 )
             }
+            LintError::FunctionTooLarge(_, func, count, _) => {
+                write!(f, "Function {} has {count} instructions", func.name())
+            }
+            LintError::UnnecessaryCalleeSave(_, _, reg) => {
+                write!(f, "Register {reg} is saved but never changed")
+            }
+            LintError::MismatchedDataWidth(_, label, declared) => {
+                write!(f, "Access width does not match {declared}-byte values of {label}")
+            }
+            LintError::UninitializedStackRead(.., true) => {
+                write!(f, "Read from uninitialized stack memory")
+            }
+            LintError::UnusedCallArgument(reg, func, _) => {
+                write!(f, "Function {} does not read argument {}", func.name(), reg.data)
+            }
+            LintError::Rv64OnlyInstruction(node) => {
+                write!(
+                    f,
+                    "Instruction {} is only available on RV64",
+                    node.mnemonic()
+                )
+            }
+            LintError::TemporaryReadOnEntry(_, func, _) => {
+                write!(f, "Function {} reads a temporary register it never set", func.name())
+            }
+            LintError::ConstantOverflow(_, _, wrapped, _) => {
+                write!(f, "Arithmetic overflow, result wraps to {wrapped}")
+            }
+            LintError::UninitializedEcallArgument(_, reg, name) => {
+                write!(f, "Argument {reg} of {name} is not set")
+            }
+            LintError::ImmediateOutOfRange(imm, inst, _) => {
+                write!(f, "Immediate {} is out of range for {inst}", imm.data.0)
+            }
+            LintError::ArgumentBeyondArity(reg, func, _) => {
+                write!(
+                    f,
+                    "Function {} reads argument {} it does not take",
+                    func.name(),
+                    reg.data
+                )
+            }
+            LintError::ConstantBranch(_, true) => write!(f, "Branch is always taken"),
+            LintError::ConstantBranch(_, false) => write!(f, "Branch is never taken"),
+            LintError::KnownBranchDirection(_, true) => {
+                write!(f, "Branch condition is always true")
+            }
+            LintError::KnownBranchDirection(_, false) => {
+                write!(f, "Branch condition is always false")
+            }
+            LintError::UnbalancedStack(_, offset, _) if *offset < 0 => {
+                write!(f, "Stack is not fully restored before returning")
+            }
+            LintError::UnbalancedStack(..) => {
+                write!(f, "Stack is restored too far before returning")
+            }
+            LintError::AnnotationMismatch(reg, func, AnnotationKind::Arg) => write!(
+                f,
+                "Function {} reads {}, which is not a declared argument",
+                func.name(),
+                reg.data
+            ),
+            LintError::AnnotationMismatch(reg, func, AnnotationKind::Returns) => write!(
+                f,
+                "Function {} does not set its declared return value {}",
+                func.name(),
+                reg.data
+            ),
+            LintError::UndefinedLabel(label) => write!(f, "Label {label} is not defined"),
+            LintError::CsrOutOfRange(csr) => write!(f, "CSR address {} is out of range", csr.data),
+            LintError::ReadOnlyCsrWrite(csr) => write!(f, "CSR {} is read-only", csr.data),
+            LintError::Custom(custom) => write!(f, "{}", custom.title),
+            _ => write!(f, "{}", self.rule().map_or(self.name(), |rule| rule.title)),
         }
     }
 }
 
 impl DiagnosticMessage for LintError {
+    fn code(&self) -> String {
+        self.name().to_string()
+    }
     fn level(&self) -> SeverityLevel {
         self.into()
     }
@@ -159,12 +921,344 @@ impl DiagnosticMessage for LintError {
     fn description(&self) -> String {
         self.long_description()
     }
+    #[allow(clippy::too_many_lines)]
     fn long_description(&self) -> String {
-        self.to_string()
+        match self {
+            LintError::IneffectiveSwap(node, _) => {
+                let (Some(rd), Some(rs)) = (node.stores_to(), node.copies_from()) else {
+                    return self.to_string();
+                };
+                format!(
+                    "{rd} already holds the same value as {rs}, so this copy has no effect. \
+                     Copying one register into another and then copying it back does not \
+                     swap them, as both registers end up with the same value. Use a \
+                     temporary register to hold one of the values, or swap with three \
+                     xor instructions."
+                )
+            }
+            LintError::SaveToZero(_, intended) => {
+                let hint = match intended {
+                    Some(reg) => format!(
+                        "{reg} is read later without being set, so the result was likely \
+                         meant for {reg}."
+                    ),
+                    None => "Check that the destination register is correct.".to_string(),
+                };
+                format!(
+                    "The result of this instruction is written to the zero register, which \
+                     always holds 0, so the result is lost. {hint}"
+                )
+            }
+            LintError::RedundantBranch(node) => format!(
+                "Both the taken and the fall-through path of this {} continue at the same \
+                 instruction, so the condition is never used. Check that the branch target \
+                 is correct.",
+                Self::branch_name(node)
+            ),
+            LintError::FunctionTooLarge(_, func, count, max) => format!(
+                "Function {} has {count} instructions, which is more than the maximum of \
+                 {max}. Consider splitting it into smaller functions.",
+                func.name()
+            ),
+            LintError::BooleanMisuse(reg) => format!(
+                "{} holds the result of a comparison, which is either 0 or 1. It is unusual \
+                 to use this value as a memory address, jump target or shift amount. Check \
+                 that the correct register is used.",
+                reg.data
+            ),
+            LintError::UnnecessaryCalleeSave(_, _, reg) => format!(
+                "{reg} is saved to the stack, but the function never changes it, so it \
+                 already holds its original value when the function returns. The save \
+                 and the matching restore can be removed."
+            ),
+            LintError::ConstantOverflow(reg, exact, wrapped, bits) => format!(
+                "The result of this instruction is {exact}, which does not fit in {bits} \
+                 bits. {} will hold {wrapped} instead. Check that the values used are \
+                 correct.",
+                reg.data
+            ),
+            LintError::ReturnAddressOverwritten(..) => "The return address register (ra) \
+                holds the address that the function returns to. It is overwritten here and \
+                not restored before the function returns, so the function will return to \
+                the wrong place. Use a different register, or save ra and restore it before \
+                returning."
+                .to_string(),
+            LintError::MismatchedDataWidth(node, label, declared) => format!(
+                "{label} is declared with values that are {declared} bytes wide, but this \
+                 instruction accesses {} bytes. This reads or writes only part of a value, \
+                 or more than one value. Check that the instruction matches the data, like \
+                 lw for .word and lb for .byte.",
+                match node {
+                    ParserNode::Load(expr) => expr.inst.data.width(),
+                    ParserNode::Store(expr) => expr.inst.data.width(),
+                    _ => 0,
+                }
+            ),
+            LintError::UnchangedLoopCondition(node, _, regs) => format!(
+                "This {} compares {}, which {} never changed inside the loop, so it \
+                 decides the same way on every iteration and the loop never ends. Is an \
+                 increment or decrement of the loop counter missing?",
+                Self::branch_name(node),
+                regs.iter().join(" and "),
+                if regs.len() == 1 { "is" } else { "are" }
+            ),
+            LintError::SelfCancellingArith(node) => {
+                let ParserNode::Arith(expr) = node else {
+                    return self.to_string();
+                };
+                let op = match expr.inst.data {
+                    ArithType::Xor => "exclusive ors",
+                    _ => "subtracts",
+                };
+                format!(
+                    "`{}` {op} {} with itself, so the result is always 0. If this is meant \
+                     to set {} to zero, use `li {}, 0` or `mv {}, {}` instead. Otherwise, \
+                     one of the registers is likely a typo.",
+                    node.token().text,
+                    expr.rs1.data,
+                    expr.rd.data,
+                    expr.rd.data,
+                    expr.rd.data,
+                    Register::X0
+                )
+            }
+            LintError::UnusedCallArgument(reg, func, _) => format!(
+                "{} is set up as an argument for the call to {}, but the function never \
+                 reads it, so the value is unused. Check that the function is called with \
+                 the right number of arguments.",
+                reg.data,
+                func.name()
+            ),
+            LintError::FreedStackRead(_, below, _) => format!(
+                "This reads a stack slot {below} bytes below the stack pointer. The slot \
+                 was freed when the stack pointer was moved above it, so it can be \
+                 overwritten at any time, like by the next call. Read the value before \
+                 moving the stack pointer back."
+            ),
+            LintError::InvalidUseAfterCall(reg, func, _, true) => format!(
+                "{} holds a value from before the call to {}, but the call may change \
+                 it, and the call is inside a loop, so the value is lost on every \
+                 iteration. Keep the value in a saved register, like s0, and save it \
+                 at the start of the function.",
+                reg.data,
+                func.name()
+            ),
+            LintError::TemporaryReadOnEntry(_, func, reads) => {
+                let regs = reads.iter().map(|read| read.data).unique().collect::<Vec<_>>();
+                format!(
+                    "Function {} reads {} before setting {}. Temporary registers are not \
+                     arguments, and callers do not keep their values, so the value depends \
+                     on whatever the caller happened to leave there. Pass the value in an \
+                     argument register instead.",
+                    func.name(),
+                    regs.iter().join(", "),
+                    if regs.len() == 1 { "it" } else { "them" }
+                )
+            }
+            LintError::Rv64OnlyInstruction(node) => format!(
+                "{} works on 64-bit registers, so it does not exist on RV32 targets. Set \
+                 `xlen: rv64` in the configuration if this program is for an RV64 target.",
+                node.mnemonic()
+            ),
+            LintError::UnalignedStackPointer(_, offset, _) => format!(
+                "After this instruction, the stack pointer is {} bytes away from where it \
+                 started, which is not a multiple of 16. The RISC-V calling convention \
+                 requires the stack pointer to be 16-byte aligned whenever a function is \
+                 called. Round the size of the stack frame up to a multiple of 16.",
+                offset.unsigned_abs()
+            ),
+            LintError::UninitializedStackRead(_, _, true) => "Nothing is stored to this \
+                stack slot before it is read, so the value read is whatever was left in \
+                memory. Store a value to the slot before reading it."
+                .to_string(),
+            LintError::UninitializedStackRead(_, _, false) => "A value is stored to this \
+                stack slot on some paths to this read, but not on all of them. On the other \
+                paths, the value read is whatever was left in memory. Store a value to the \
+                slot on every path before reading it."
+                .to_string(),
+            LintError::RedundantExtension(..) => "The value is already known to fit in the \
+                bits that are kept, so this extension does not change it and can be removed."
+                .to_string(),
+            LintError::UnknownJumpTarget(node) => format!(
+                "The address in {} could not be traced back to a label, so it is not known \
+                 where this jump goes. Code that is only reached through this jump may be \
+                 reported as unreachable, and values may not be checked along this path. \
+                 Load the target with `la`, or from a table of labels declared with `.word`.",
+                match node {
+                    ParserNode::JumpLinkR(expr) => expr.rs1.data.to_string(),
+                    _ => "the register".to_string(),
+                }
+            ),
+            LintError::UnreachableCode(_, _, count, name) => format!(
+                "{} never run, as no path from the start of the program, a function or a \
+                 trap handler reaches {}. Remove the code, or check for a missing jump or \
+                 call to it.",
+                match name {
+                    Some(name) => format!("The code at {name} is"),
+                    None if *count == 1 => "This instruction is".to_string(),
+                    None => format!("These {count} instructions are"),
+                },
+                if *count == 1 { "it" } else { "them" }
+            ),
+            LintError::UninitializedEcallArgument(_, reg, name) => format!(
+                "The {name} system call reads an argument from {reg}, but {reg} is not set \
+                 on every path to this ecall, so the call uses whatever value was left \
+                 there. Set {reg} before the ecall."
+            ),
+            LintError::RedundantLoad(ParserNode::Load(load), first, reg) => {
+                let line = first.range().start.line + 1;
+                if load.rd.data == *reg {
+                    format!(
+                        "{reg} already holds this value from the load on line {line}, and \
+                         neither the address nor the memory has changed since. This load can \
+                         be removed."
+                    )
+                } else {
+                    let mv = match load.inst.data {
+                        LoadType::Flw => "fmv.s",
+                        LoadType::Fld => "fmv.d",
+                        _ => "mv",
+                    };
+                    format!(
+                        "The load on line {line} already put this value in {reg}, and neither \
+                         the address nor the memory has changed since. Use `{mv} {}, {reg}` \
+                         to reuse it instead of loading it again.",
+                        load.rd.data
+                    )
+                }
+            }
+            LintError::ImmediateOutOfRange(imm, inst, range) => {
+                let hint = match inst {
+                    Inst::Slli | Inst::Srli | Inst::Srai
+                        if *range.end() == 31 && (32..=63).contains(&imm.data.0) =>
+                    {
+                        " Shifts by 32 or more are only available on RV64 targets."
+                    }
+                    Inst::Addi
+                    | Inst::Addiw
+                    | Inst::Andi
+                    | Inst::Ori
+                    | Inst::Xori
+                    | Inst::Slti
+                    | Inst::Sltiu => {
+                        " To use a larger value, load it into a register with `li` and \
+                         use the register form of the instruction."
+                    }
+                    _ if matches!(Type::from(inst), Type::Load(_) | Type::Store(_)) => {
+                        " To access an address that is further away, add the offset to \
+                         the base register first."
+                    }
+                    _ => "",
+                };
+                format!(
+                    "{inst} takes an immediate from {} to {}, so {} cannot be encoded in \
+                     it, and the instruction cannot be assembled.{hint}",
+                    range.start(),
+                    range.end(),
+                    imm.data.0
+                )
+            }
+            LintError::ArgumentBeyondArity(reg, func, arguments) => {
+                let takes = if arguments.is_empty() {
+                    "it does not take the arguments before it".to_string()
+                } else {
+                    format!("it only takes {}", arguments.iter().join(", "))
+                };
+                format!(
+                    "Function {} reads {} before setting it, but {takes}. \
+                     Arguments are passed in order, so {} is probably an argument that \
+                     callers do not pass, or a register that should be set first. If the \
+                     function takes a variable number of arguments, this can be ignored.",
+                    func.name(),
+                    reg.data,
+                    reg.data
+                )
+            }
+            LintError::ConstantBranch(node, true) => format!(
+                "This {} compares a register with itself, so it is always taken, and the \
+                 instruction after it is only reached from other paths. Use `j` for a jump \
+                 that is always taken, or check that the registers are correct.",
+                Self::branch_name(node)
+            ),
+            LintError::ConstantBranch(node, false) => format!(
+                "This {} compares a register with itself, so it is never taken, and its \
+                 target is only reached from other paths. Check that the registers are \
+                 correct.",
+                Self::branch_name(node)
+            ),
+            LintError::UnbalancedStack(_, offset, _) if *offset < 0 => format!(
+                "On a path to this return, the stack pointer is moved down {} bytes more \
+                 than it is moved back up, so the caller continues with the wrong stack \
+                 pointer. Undo every adjustment of the stack pointer on every path before \
+                 returning.",
+                offset.unsigned_abs()
+            ),
+            LintError::UnbalancedStack(_, offset, _) => format!(
+                "On a path to this return, the stack pointer is moved back up {offset} bytes \
+                 more than it is moved down, so the function frees stack memory of its \
+                 caller. Undo exactly the adjustments of the stack pointer on every path \
+                 before returning."
+            ),
+            LintError::AnnotationMismatch(reg, func, AnnotationKind::Arg) => {
+                let declared = func.declared_registers(AnnotationKind::Arg);
+                format!(
+                    "Function {} reads {} before setting it, but its annotations {}. Add an \
+                     `@arg {}` annotation if this is an argument, or set the register before \
+                     reading it.",
+                    func.name(),
+                    reg.data,
+                    if declared.is_empty() {
+                        "do not declare any arguments".to_string()
+                    } else {
+                        format!("only declare {}", declared.iter().join(", "))
+                    },
+                    reg.data
+                )
+            }
+            LintError::AnnotationMismatch(reg, func, AnnotationKind::Returns) => format!(
+                "The annotations of function {} declare that it returns a value in {}, but \
+                 the register is not set on every path to the return. Set the register \
+                 before returning, or remove the `@returns` annotation.",
+                func.name(),
+                reg.data
+            ),
+            LintError::KnownBranchDirection(node, taken) => format!(
+                "The values that this {} compares are the same on every path to it, so it \
+                 is {}. The code on the other path is not reached from here.",
+                Self::branch_name(node),
+                if *taken { "always taken" } else { "never taken" }
+            ),
+            LintError::UndefinedLabel(label) => format!(
+                "The label {label} is used here, but no file of the program defines it. Check \
+                 the name for a typo, or include the file that defines it. If it is defined \
+                 outside of the program, declare it with `.extern {label}`."
+            ),
+            LintError::LoopInvariantLoad(_, header) => format!(
+                "This load reads the same memory on every iteration of the loop that starts \
+                 on line {}. Its address does not change in the loop, and nothing in the loop \
+                 writes to the memory. The load can be moved before the loop.",
+                header.range().start.line + 1
+            ),
+            LintError::CsrOutOfRange(csr) => format!(
+                "CSR addresses are 12 bits, from 0x0 to {:#x}, but this address is {}. The \
+                 assembler cannot encode this instruction.",
+                CSRImm::MAX_ADDRESS,
+                csr.data
+            ),
+            LintError::ReadOnlyCsrWrite(csr) => format!(
+                "The CSR {} can only be read, so writing to it raises an illegal instruction \
+                 exception. Use `csrr` to read it instead.",
+                csr.data
+            ),
+            LintError::Custom(custom) => custom.description.clone(),
+            _ => self.to_string(),
+        }
     }
+    #[allow(clippy::too_many_lines)]
     fn related(&self) -> Option<Vec<super::RelatedDiagnosticItem>> {
         match self {
-            LintError::InvalidUseAfterCall(_, func, call_site) => {
+            LintError::InvalidUseAfterCall(_, func, call_site, _)
+            | LintError::UnusedCallArgument(_, func, call_site) => {
                 Some(vec![super::RelatedDiagnosticItem {
                     file: call_site.file(),
                     range: call_site.range(),
@@ -178,9 +1272,124 @@ impl DiagnosticMessage for LintError {
                     description: format!("Invalid jump to function {} occurs here", func.name()),
                 }])
             }
+            LintError::IneffectiveSwap(_, first) => Some(vec![super::RelatedDiagnosticItem {
+                file: first.file(),
+                range: first.range(),
+                description: "Registers are made equal here".to_string(),
+            }]),
+            LintError::MismatchedDataWidth(_, label, _) => Some(vec![super::RelatedDiagnosticItem {
+                file: label.file,
+                range: label.pos.clone(),
+                description: format!("{label} is declared here"),
+            }]),
+            LintError::UnchangedLoopCondition(_, header, _) => {
+                Some(vec![super::RelatedDiagnosticItem {
+                    file: header.file(),
+                    range: header.range(),
+                    description: "The loop starts here".to_string(),
+                }])
+            }
+            LintError::LoopInvariantLoad(_, header) => Some(vec![super::RelatedDiagnosticItem {
+                file: header.file(),
+                range: header.range(),
+                description: "The loop starts here".to_string(),
+            }]),
+            LintError::RedundantLoad(_, first, _) => Some(vec![super::RelatedDiagnosticItem {
+                file: first.file(),
+                range: first.range(),
+                description: "The value is first loaded here".to_string(),
+            }]),
+            LintError::RedundantExtension(_, Some(shift)) => {
+                Some(vec![super::RelatedDiagnosticItem {
+                    file: shift.file(),
+                    range: shift.range(),
+                    description: "The extension starts here".to_string(),
+                }])
+            }
+            LintError::ReturnAddressOverwritten(_, rets) => Some(
+                rets.iter()
+                    .map(|ret| super::RelatedDiagnosticItem {
+                        file: ret.file(),
+                        range: ret.range(),
+                        description: "The function returns here".to_string(),
+                    })
+                    .collect(),
+            ),
+            LintError::TemporaryReadOnEntry(_, _, reads) => Some(
+                reads
+                    .iter()
+                    .map(|read| super::RelatedDiagnosticItem {
+                        file: read.file,
+                        range: read.pos.clone(),
+                        description: format!("{} is read here", read.data),
+                    })
+                    .collect(),
+            ),
+            LintError::UnalignedStackPointer(_, _, calls) => Some(
+                calls
+                    .iter()
+                    .map(|call| super::RelatedDiagnosticItem {
+                        file: call.file(),
+                        range: call.range(),
+                        description: "The function is called here".to_string(),
+                    })
+                    .collect(),
+            ),
+            LintError::AnnotationMismatch(_, func, AnnotationKind::Arg) => Some(
+                func.annotations()
+                    .iter()
+                    .filter(|annotation| annotation.kind == AnnotationKind::Arg)
+                    .map(|annotation| super::RelatedDiagnosticItem {
+                        file: annotation.reg.file,
+                        range: annotation.reg.pos.clone(),
+                        description: format!("Argument {} is declared here", annotation.reg.data),
+                    })
+                    .collect(),
+            ),
+            LintError::UnbalancedStack(_, _, adjustments) => Some(
+                adjustments
+                    .iter()
+                    .map(|adjustment| super::RelatedDiagnosticItem {
+                        file: adjustment.file(),
+                        range: adjustment.range(),
+                        description: "The stack pointer is adjusted here on this path".to_string(),
+                    })
+                    .collect(),
+            ),
+            LintError::FreedStackRead(_, _, stores) => Some(
+                stores
+                    .iter()
+                    .map(|store| super::RelatedDiagnosticItem {
+                        file: store.file(),
+                        range: store.range(),
+                        description: "The slot is written here".to_string(),
+                    })
+                    .collect(),
+            ),
+            LintError::UnnecessaryCalleeSave(_, restores, reg) => Some(
+                restores
+                    .iter()
+                    .map(|restore| super::RelatedDiagnosticItem {
+                        file: restore.file(),
+                        range: restore.range(),
+                        description: format!("Register {reg} is restored here"),
+                    })
+                    .collect(),
+            ),
             _ => None,
         }
     }
+
+    fn fixes(&self) -> Vec<super::TextEdit> {
+        match self {
+            // Saving every register that must be saved fixes the function
+            LintError::OverwriteCalleeSavedRegister(_, func) => crate::fix::fix_stack(func)
+                .iter()
+                .filter_map(crate::fix::Manipulation::text_edit)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 // impl LintError {
@@ -210,43 +1419,106 @@ impl DiagnosticMessage for LintError {
 impl DiagnosticLocation for LintError {
     fn range(&self) -> Range {
         match self {
-            LintError::InvalidUseAfterCall(r, _, _)
-            | LintError::SaveToZero(r)
+            LintError::InvalidUseAfterCall(r, ..)
+            | LintError::SaveToZero(r, _)
             | LintError::InvalidUseBeforeAssignment(r)
             | LintError::LostRegisterValue(r)
-            | LintError::OverwriteCalleeSavedRegister(r)
+            | LintError::OverwriteCalleeSavedRegister(r, _)
+            | LintError::BooleanMisuse(r)
+            | LintError::ConstantOverflow(r, ..)
+            | LintError::ReturnAddressOverwritten(r, _)
+            | LintError::UnusedCallArgument(r, ..)
+            | LintError::AnnotationMismatch(r, ..)
+            | LintError::ArgumentBeyondArity(r, ..)
             | LintError::DeadAssignment(r) => r.pos.clone(),
+            LintError::ImmediateOutOfRange(imm, ..) => imm.pos.clone(),
+            LintError::UndefinedLabel(label) => label.pos.clone(),
+            LintError::CsrOutOfRange(csr) | LintError::ReadOnlyCsrWrite(csr) => csr.pos.clone(),
             LintError::InvalidJumpToFunction(r, _, _)
             | LintError::FirstInstructionIsFunction(r, _)
             | LintError::UnknownEcall(r)
-            | LintError::UnreachableCode(r)
             | LintError::InvalidSegment(r)
             | LintError::UnknownStack(r)
             | LintError::InvalidStackPointer(r)
             | LintError::InvalidStackOffsetUsage(r, _)
             | LintError::NodeInManyFunctions(r, _)
+            | LintError::IneffectiveSwap(r, _)
+            | LintError::RedundantBranch(r)
+            | LintError::ConstantBranch(r, _)
+            | LintError::KnownBranchDirection(r, _)
+            | LintError::UnbalancedStack(r, ..)
+            | LintError::FunctionTooLarge(r, ..)
+            | LintError::UnnecessaryCalleeSave(r, ..)
+            | LintError::RedundantExtension(r, _)
+            | LintError::MismatchedDataWidth(r, ..)
+            | LintError::UnchangedLoopCondition(r, ..)
+            | LintError::SelfCancellingArith(r)
+            | LintError::FreedStackRead(r, ..)
+            | LintError::TemporaryReadOnEntry(r, ..)
+            | LintError::Rv64OnlyInstruction(r)
+            | LintError::UnalignedStackPointer(r, ..)
+            | LintError::UninitializedStackRead(r, ..)
+            | LintError::UnknownJumpTarget(r)
+            | LintError::UninitializedEcallArgument(r, ..)
+            | LintError::RedundantLoad(r, ..)
+            | LintError::LoopInvariantLoad(r, _)
             | LintError::InvalidStackPosition(r, _) => r.range(),
+            LintError::Custom(custom) => custom.node.range(),
+            LintError::UnreachableCode(first, last, ..) => Range {
+                start: first.range().start,
+                end: last.range().end,
+            },
         }
     }
 
     fn file(&self) -> Uuid {
         match self {
-            LintError::InvalidUseAfterCall(r, _, _)
-            | LintError::SaveToZero(r)
+            LintError::InvalidUseAfterCall(r, ..)
+            | LintError::SaveToZero(r, _)
             | LintError::InvalidUseBeforeAssignment(r)
             | LintError::LostRegisterValue(r)
-            | LintError::OverwriteCalleeSavedRegister(r)
+            | LintError::OverwriteCalleeSavedRegister(r, _)
+            | LintError::BooleanMisuse(r)
+            | LintError::ConstantOverflow(r, ..)
+            | LintError::ReturnAddressOverwritten(r, _)
+            | LintError::UnusedCallArgument(r, ..)
+            | LintError::AnnotationMismatch(r, ..)
+            | LintError::ArgumentBeyondArity(r, ..)
             | LintError::DeadAssignment(r) => r.file,
+            LintError::ImmediateOutOfRange(imm, ..) => imm.file,
+            LintError::UndefinedLabel(label) => label.file,
+            LintError::CsrOutOfRange(csr) | LintError::ReadOnlyCsrWrite(csr) => csr.file,
             LintError::FirstInstructionIsFunction(r, _)
             | LintError::InvalidJumpToFunction(r, _, _)
             | LintError::UnknownEcall(r)
             | LintError::InvalidSegment(r)
-            | LintError::UnreachableCode(r)
             | LintError::UnknownStack(r)
             | LintError::InvalidStackPointer(r)
             | LintError::InvalidStackOffsetUsage(r, _)
             | LintError::NodeInManyFunctions(r, _)
+            | LintError::IneffectiveSwap(r, _)
+            | LintError::RedundantBranch(r)
+            | LintError::ConstantBranch(r, _)
+            | LintError::KnownBranchDirection(r, _)
+            | LintError::UnbalancedStack(r, ..)
+            | LintError::FunctionTooLarge(r, ..)
+            | LintError::UnnecessaryCalleeSave(r, ..)
+            | LintError::RedundantExtension(r, _)
+            | LintError::MismatchedDataWidth(r, ..)
+            | LintError::UnchangedLoopCondition(r, ..)
+            | LintError::SelfCancellingArith(r)
+            | LintError::FreedStackRead(r, ..)
+            | LintError::TemporaryReadOnEntry(r, ..)
+            | LintError::Rv64OnlyInstruction(r)
+            | LintError::UnalignedStackPointer(r, ..)
+            | LintError::UninitializedStackRead(r, ..)
+            | LintError::UnknownJumpTarget(r)
+            | LintError::UninitializedEcallArgument(r, ..)
+            | LintError::RedundantLoad(r, ..)
+            | LintError::LoopInvariantLoad(r, _)
             | LintError::InvalidStackPosition(r, _) => r.file(),
+            LintError::Custom(custom) => custom.node.file(),
+            LintError::UnreachableCode(first, ..) => first.file(),
         }
     }
 }
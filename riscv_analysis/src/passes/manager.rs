@@ -8,12 +8,24 @@ use crate::{
     lints::{
         CalleeSavedGarbageReadCheck, CalleeSavedRegisterCheck, ControlFlowCheck, DeadValueCheck,
         EcallCheck, GarbageInputValueCheck, InstructionInTextCheck, LostCalleeSavedRegisterCheck, SaveToZeroCheck,
-        StackCheckPass, OverlappingFunctionCheck,
+        StackCheckPass, OverlappingFunctionCheck, IneffectiveSwapCheck, RedundantBranchCheck,
+        FunctionSizeCheck, BooleanMisuseCheck, UnnecessaryCalleeSaveCheck, ConstantOverflowCheck,
+        ReturnAddressMisuseCheck, RedundantExtensionCheck, DataWidthCheck,
+        LoopCounterCheck, SelfCancellingArithCheck, UnusedCallArgumentCheck,
+        FreedStackReadCheck, TemporaryInputCheck, Rv64InstructionCheck,
+        StackAlignmentCheck, UninitializedStackReadCheck, EcallArgumentCheck, UnreachableCodeCheck,
+        RedundantLoadCheck, ImmediateRangeCheck, ArgumentArityCheck, StackBalanceCheck,
+        AnnotationCheck, UndefinedLabelCheck, CsrCheck,
+        LoopInvariantLoadCheck,
     },
-    parser::ParserNode,
+    parser::{AlignMode, ParseError, ParserNode, Xlen},
 };
 
-use super::{CfgError, GenerationPass, LintError, LintPass};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{CfgError, GenerationPass, LintError, LintLevel, LintPass, RuleInfo};
 
 #[derive(Default)]
 pub struct DebugInfo {
@@ -21,14 +33,138 @@ pub struct DebugInfo {
     pub yaml: bool,
 }
 
+/// Configuration for running the analysis.
+///
+/// This can be read from a configuration file. Fields that are left out
+/// keep their default value.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ManagerConfiguration {
+    /// Run the lints after the CFG is generated.
+    ///
+    /// If this is disabled, only parse errors and errors from building
+    /// the CFG are reported.
+    pub run_lints: bool,
+
+    /// The largest number of instructions a function may have.
+    ///
+    /// Functions with more instructions are reported. This is disabled
+    /// if not set.
+    pub max_function_instructions: Option<usize>,
+
+    /// Labels that the program can be entered through, other than the
+    /// start of the program.
+    ///
+    /// Labels exported with `.globl` are always entry points. Code that is
+    /// only reachable from an entry point is not reported as unreachable.
+    pub entry_points: Vec<String>,
+
+    /// Merge diagnostics of the same rule that are reported at the same
+    /// location.
+    ///
+    /// This can be turned off to see the output of every lint, which is
+    /// useful when debugging the lints themselves.
+    pub dedup_diagnostics: bool,
+
+    /// The register width of the target.
+    ///
+    /// This changes how constant values that overflow are analyzed.
+    pub xlen: Xlen,
+
+    /// How the arguments of `.align` directives are read.
+    pub align_mode: AlignMode,
+
+    /// Analyze the code as a library of functions instead of a program.
+    ///
+    /// A library has no program entry. Every label that is not jumped to
+    /// or fallen into is treated as the entry of a function, and each of
+    /// these functions is checked on its own.
+    pub library: bool,
+
+    /// The level of each lint, by the name of the lint.
+    ///
+    /// Lints that are not listed are reported at their usual level. A lint
+    /// that is turned off is not reported, and its pass is not run if all
+    /// of the lints of the pass are off. See [`LintError::NAMES`] for the
+    /// names of the lints.
+    pub lint_levels: HashMap<String, LintLevel>,
+
+    /// Lint passes to run after the built-in lints.
+    ///
+    /// These are run on the same CFG as the built-in lints, and their lints
+    /// can be changed with [`ManagerConfiguration::lint_levels`] like any
+    /// other lint. They cannot be set in a configuration file.
+    #[serde(skip)]
+    pub custom_lints: Vec<Rc<dyn LintPass>>,
+}
+
+impl Default for ManagerConfiguration {
+    fn default() -> Self {
+        Self {
+            run_lints: true,
+            max_function_instructions: None,
+            entry_points: Vec::new(),
+            dedup_diagnostics: true,
+            xlen: Xlen::default(),
+            align_mode: AlignMode::default(),
+            library: false,
+            lint_levels: HashMap::new(),
+            custom_lints: Vec::new(),
+        }
+    }
+}
+
+impl ManagerConfiguration {
+    /// Get the level that a lint was set to, if it was changed.
+    #[must_use]
+    pub fn lint_level(&self, name: &str) -> Option<LintLevel> {
+        self.lint_levels.get(name).copied()
+    }
+
+    /// Check if a lint is reported, as it is not turned off.
+    #[must_use]
+    pub fn is_lint_enabled(&self, name: &str) -> bool {
+        self.lint_level(name) != Some(LintLevel::Off)
+    }
+
+    /// Check if a pass reports any lint that is not turned off.
+    fn is_pass_enabled(&self, pass: &dyn LintPass) -> bool {
+        let lints = pass.lints();
+        lints.is_empty() || lints.iter().any(|name| self.is_lint_enabled(name))
+    }
+}
+
 pub struct Manager;
 impl Manager {
     pub fn gen_full_cfg(cfg: Vec<ParserNode>) -> Result<Cfg, Box<CfgError>> {
-        let mut cfg = Cfg::new(cfg)?;
+        Self::gen_full_cfg_with_config(cfg, &ManagerConfiguration::default())
+    }
+    pub fn gen_full_cfg_with_config(
+        cfg: Vec<ParserNode>,
+        config: &ManagerConfiguration,
+    ) -> Result<Cfg, Box<CfgError>> {
+        let mut cfg = if config.library {
+            Cfg::new_library(cfg)?
+        } else {
+            Cfg::new(cfg)?
+        };
+        cfg.set_xlen(config.xlen);
+        for label in &config.entry_points {
+            cfg.add_entry_point(label.clone());
+        }
 
         NodeDirectionPass::run(&mut cfg)?;
+        // Jump tables are found with the available values, and must be
+        // connected before the code they jump to is treated as dead
+        AvailableValuePass::run(&mut cfg)?;
+        cfg.connect_indirect_jumps();
         EliminateDeadCodeDirectionsPass::run(&mut cfg)?;
         AvailableValuePass::run(&mut cfg)?;
+        // Branches with known conditions are found with the available
+        // values, and the paths they never take lead to dead code
+        cfg.prune_known_branches();
+        EliminateDeadCodeDirectionsPass::run(&mut cfg)?;
         EcallTerminationPass::run(&mut cfg)?;
         FunctionMarkupPass::run(&mut cfg)?;
 
@@ -38,23 +174,82 @@ impl Manager {
         LivenessPass::run(&mut cfg)?;
         Ok(cfg)
     }
-    pub fn run_diagnostics(cfg: &Cfg, errors: &mut Vec<LintError>) {
-        SaveToZeroCheck::run(cfg, errors);
-        DeadValueCheck::run(cfg, errors);
-        InstructionInTextCheck::run(cfg, errors);
-        EcallCheck::run(cfg, errors);
-        ControlFlowCheck::run(cfg, errors);
-        GarbageInputValueCheck::run(cfg, errors);
-        StackCheckPass::run(cfg, errors);
-        CalleeSavedRegisterCheck::run(cfg, errors);
-        CalleeSavedGarbageReadCheck::run(cfg, errors);
-        LostCalleeSavedRegisterCheck::run(cfg, errors);
-        OverlappingFunctionCheck::run(cfg, errors);
+    /// Get the built-in lint passes that are run with a configuration.
+    pub(crate) fn lint_passes(config: &ManagerConfiguration) -> Vec<Box<dyn LintPass>> {
+        let mut passes: Vec<Box<dyn LintPass>> = vec![
+            Box::new(SaveToZeroCheck),
+            Box::new(DeadValueCheck),
+            Box::new(InstructionInTextCheck),
+            Box::new(EcallCheck),
+            Box::new(ControlFlowCheck),
+            Box::new(UnreachableCodeCheck),
+            Box::new(GarbageInputValueCheck),
+            Box::new(StackCheckPass),
+            Box::new(CalleeSavedRegisterCheck),
+            Box::new(CalleeSavedGarbageReadCheck),
+            Box::new(LostCalleeSavedRegisterCheck),
+            Box::new(OverlappingFunctionCheck),
+            Box::new(IneffectiveSwapCheck),
+            Box::new(RedundantBranchCheck),
+            Box::new(UnnecessaryCalleeSaveCheck),
+            Box::new(ConstantOverflowCheck),
+            Box::new(ReturnAddressMisuseCheck),
+            Box::new(RedundantExtensionCheck),
+            Box::new(DataWidthCheck),
+            Box::new(LoopCounterCheck),
+            Box::new(SelfCancellingArithCheck),
+            Box::new(UnusedCallArgumentCheck),
+            Box::new(FreedStackReadCheck),
+            Box::new(TemporaryInputCheck),
+            Box::new(Rv64InstructionCheck),
+            Box::new(StackAlignmentCheck),
+            Box::new(StackBalanceCheck),
+            Box::new(UninitializedStackReadCheck),
+            Box::new(EcallArgumentCheck),
+            Box::new(RedundantLoadCheck),
+            Box::new(ImmediateRangeCheck),
+            Box::new(ArgumentArityCheck),
+            Box::new(AnnotationCheck),
+            Box::new(UndefinedLabelCheck),
+            Box::new(CsrCheck),
+            Box::new(LoopInvariantLoadCheck),
+            Box::new(BooleanMisuseCheck),
+        ];
+        if let Some(max_instructions) = config.max_function_instructions {
+            passes.push(Box::new(FunctionSizeCheck { max_instructions }));
+        }
+        passes
+    }
+
+    pub fn run_diagnostics(cfg: &Cfg, config: &ManagerConfiguration, errors: &mut Vec<LintError>) {
+        let passes = Self::lint_passes(config);
+        let custom = config.custom_lints.iter().map(AsRef::as_ref);
+        for pass in passes.iter().map(AsRef::as_ref).chain(custom) {
+            // A pass is not run if every lint it reports is turned off
+            if config.is_pass_enabled(pass) {
+                pass.run(cfg, errors);
+            }
+        }
+        // Passes that report many lints are run if any of them is on
+        errors.retain(|error| config.is_lint_enabled(error.name()));
     }
+
+    /// Get the rule of every diagnostic that the analysis can report.
+    ///
+    /// This has a rule for every lint, followed by the rules of errors that
+    /// stop the analysis.
+    #[must_use]
+    pub fn rule_catalog() -> Vec<RuleInfo> {
+        let mut rules = LintError::RULES.to_vec();
+        rules.push(ParseError::RULE);
+        rules.push(CfgError::RULE);
+        rules
+    }
+
     pub fn run(cfg: Vec<ParserNode>) -> Result<Vec<LintError>, Box<CfgError>> {
         let mut errors = Vec::new();
         let cfg = Self::gen_full_cfg(cfg)?;
-        Self::run_diagnostics(&cfg, &mut errors);
+        Self::run_diagnostics(&cfg, &ManagerConfiguration::default(), &mut errors);
         Ok(errors)
     }
 }
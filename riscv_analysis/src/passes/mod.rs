@@ -12,3 +12,6 @@ pub use manager::*;
 
 mod diagnostics;
 pub use diagnostics::*;
+
+mod rule;
+pub use rule::*;
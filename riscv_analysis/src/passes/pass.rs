@@ -10,8 +10,22 @@ pub trait AssertionPass {
     fn run(cfg: &Cfg) -> Result<(), Box<CfgError>>;
 }
 
+/// A pass that reports lints on a CFG.
+///
+/// The built-in lints implement this trait, and other lints can be run with
+/// them by adding them to
+/// [`ManagerConfiguration::custom_lints`](super::ManagerConfiguration::custom_lints).
+/// Lints are run on the full CFG, after every analysis is done.
 pub trait LintPass {
-    fn run(cfg: &Cfg, errors: &mut Vec<LintError>);
+    /// The names of the lints that the pass reports.
+    ///
+    /// The pass is skipped if every one of these lints is turned off. Passes
+    /// that do not list their lints are always run. See [`LintError::name`].
+    fn lints(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>);
 
     /// Run a single pass along a set of `ParserNode`s and return the errors.
     ///
@@ -25,7 +39,7 @@ pub trait LintPass {
     ///
     /// struct MyPass;
     /// impl LintPass for MyPass {
-    ///    fn run(cfg: &Cfg, errors: &mut Vec<LintError>) {
+    ///    fn run(&self, cfg: &Cfg, errors: &mut Vec<LintError>) {
     ///       for node in cfg {
     ///         errors.push(LintError::InvalidStackPointer(node.node()));
     ///      }
@@ -33,20 +47,20 @@ pub trait LintPass {
     /// }
     ///
     /// let nodes = &[iarith!(Addi X1 X0 0)];
-    /// let errors = MyPass::run_single_pass_along_nodes(nodes);
+    /// let errors = MyPass.run_single_pass_along_nodes(nodes);
     /// assert_eq!(errors.len(), 1);
     /// assert!(matches!(errors[0], LintError::InvalidStackPointer(_)));
     /// ```
     #[must_use]
-    fn run_single_pass_along_nodes(nodes: &[ParserNode]) -> Vec<LintError> {
+    fn run_single_pass_along_nodes(&self, nodes: &[ParserNode]) -> Vec<LintError> {
         let cfg = Cfg::new(nodes.into()).unwrap();
-        Self::run_single_pass_along_cfg(&cfg)
+        self.run_single_pass_along_cfg(&cfg)
     }
 
     #[must_use]
-    fn run_single_pass_along_cfg(cfg: &Cfg) -> Vec<LintError> {
+    fn run_single_pass_along_cfg(&self, cfg: &Cfg) -> Vec<LintError> {
         let mut errors = Vec::new();
-        Self::run(cfg, &mut errors);
+        self.run(cfg, &mut errors);
         errors
     }
 }
@@ -0,0 +1,104 @@
+use super::SeverityLevel;
+
+/// Where the documentation of every rule is, with a section for each rule.
+const DOCUMENTATION: &str =
+    "https://github.com/rajanmaghera/riscv-analysis/blob/main/docs/lints.md";
+
+/// Information about a kind of diagnostic, for documentation and for tools
+/// that show diagnostics.
+///
+/// Every lint has a rule with the same ID as its name. Errors that stop the
+/// analysis, like parse errors, have a rule each kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleInfo {
+    /// The stable ID of the rule, like `dead-assignment`.
+    pub id: &'static str,
+    /// A title for diagnostics of the rule. Diagnostics may use a more
+    /// specific title.
+    pub title: &'static str,
+    /// The level that diagnostics of the rule are reported at, unless it
+    /// is changed in the configuration. Some rules report a more severe
+    /// level when they are sure of the problem.
+    pub level: SeverityLevel,
+    /// A short description of what the rule finds.
+    pub description: &'static str,
+}
+
+impl RuleInfo {
+    #[must_use]
+    pub const fn new(
+        id: &'static str,
+        title: &'static str,
+        level: SeverityLevel,
+        description: &'static str,
+    ) -> Self {
+        RuleInfo {
+            id,
+            title,
+            level,
+            description,
+        }
+    }
+
+    /// The address of the documentation of the rule.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("{DOCUMENTATION}#{}", self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Info, Register, With};
+    use crate::passes::{
+        DiagnosticMessage, LintError, Manager, ManagerConfiguration, SeverityLevel,
+    };
+
+    #[test]
+    fn every_lint_has_a_rule() {
+        assert_eq!(LintError::NAMES.len(), LintError::RULES.len());
+        assert_eq!(LintError::NAMES[0], "dead-assignment");
+
+        let catalog = Manager::rule_catalog();
+        for (i, rule) in catalog.iter().enumerate() {
+            assert!(catalog.iter().skip(i + 1).all(|r| r.id != rule.id));
+        }
+
+        // Every pass, including the ones that are only run when configured
+        let config = ManagerConfiguration {
+            max_function_instructions: Some(1),
+            ..Default::default()
+        };
+        for pass in Manager::lint_passes(&config) {
+            for name in pass.lints() {
+                assert!(LintError::NAMES.contains(name), "{name} has no rule");
+            }
+        }
+    }
+
+    #[test]
+    fn title_and_level_come_from_rule() {
+        let lint = LintError::DeadAssignment(With::new(Register::X5, Info::default()));
+        let rule = lint.rule().unwrap();
+        assert_eq!(rule.id, lint.name());
+        assert_eq!(lint.title(), rule.title);
+        assert_eq!(lint.level(), rule.level);
+        assert_eq!(rule.level, SeverityLevel::Warning);
+    }
+
+    #[test]
+    fn every_rule_is_documented() {
+        let docs = include_str!("../../../docs/lints.md");
+        for rule in Manager::rule_catalog() {
+            assert!(
+                docs.lines().any(|line| line == format!("## {}", rule.id)),
+                "{} is not documented",
+                rule.id
+            );
+        }
+        assert_eq!(
+            LintError::RULES[0].url(),
+            "https://github.com/rajanmaghera/riscv-analysis/blob/main/docs/lints.md#dead-assignment"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use super::{FileReader, FileReaderError};
+
+/// A file reader that reads files from a map of paths to their contents.
+///
+/// This file reader is useful when the contents of every file are already
+/// in memory, like in a sandbox or a test, so that nothing is read from
+/// the file system.
+///
+/// Paths are separated by `/`. An `.include` path is relative to the
+/// directory of the file that includes it, unless it starts with `/`.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryFileReader {
+    sources: HashMap<String, String>,
+    files: HashMap<Uuid, String>,
+}
+
+impl InMemoryFileReader {
+    /// Create a new file reader from a map of paths to their contents.
+    #[must_use]
+    pub fn new(sources: HashMap<String, String>) -> Self {
+        Self {
+            sources,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Join a path to the directory of a parent path, removing `.` and `..`
+    /// components.
+    ///
+    /// ```
+    /// use riscv_analysis::reader::InMemoryFileReader;
+    /// assert_eq!(InMemoryFileReader::join("src/main.s", "lib.s"), "src/lib.s");
+    /// assert_eq!(InMemoryFileReader::join("src/main.s", "../lib.s"), "lib.s");
+    /// assert_eq!(InMemoryFileReader::join("src/main.s", "/lib.s"), "/lib.s");
+    /// ```
+    #[must_use]
+    pub fn join(parent: &str, path: &str) -> String {
+        let full = match parent.rsplit_once('/') {
+            Some((dir, _)) if !path.starts_with('/') => format!("{dir}/{path}"),
+            _ => path.to_owned(),
+        };
+        let mut parts: Vec<&str> = Vec::new();
+        for (i, part) in full.split('/').enumerate() {
+            match part {
+                // An empty first part is the root of an absolute path
+                "" if i == 0 => parts.push(part),
+                "" | "." => {}
+                ".." if parts
+                    .last()
+                    .is_some_and(|last| !last.is_empty() && *last != "..") =>
+                {
+                    parts.pop();
+                }
+                _ => parts.push(part),
+            }
+        }
+        parts.join("/")
+    }
+}
+
+impl FileReader for InMemoryFileReader {
+    fn import_file(
+        &mut self,
+        path: &str,
+        parent_file: Option<Uuid>,
+    ) -> Result<(Uuid, String), FileReaderError> {
+        let path = match parent_file {
+            Some(uuid) => {
+                let parent = self
+                    .files
+                    .get(&uuid)
+                    .ok_or(FileReaderError::InternalFileNotFound)?;
+                Self::join(parent, path)
+            }
+            None => Self::join("", path),
+        };
+        let text = self
+            .sources
+            .get(&path)
+            .ok_or(FileReaderError::InvalidPath)?
+            .clone();
+
+        // a file that was already read keeps its UUID, so that the parser
+        // can find cycles and files that are included more than once
+        if let Some((uuid, _)) = self.files.iter().find(|(_, p)| **p == path) {
+            return Ok((*uuid, text));
+        }
+
+        let uuid = Uuid::new_v4();
+        self.files.insert(uuid, path);
+        Ok((uuid, text))
+    }
+
+    fn get_text(&self, uuid: Uuid) -> Option<String> {
+        self.sources.get(self.files.get(&uuid)?).cloned()
+    }
+
+    fn get_filename(&self, uuid: Uuid) -> Option<String> {
+        self.files.get(&uuid).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{ParseError, ParserNode, RVParser};
+
+    fn reader(files: &[(&str, &str)]) -> InMemoryFileReader {
+        InMemoryFileReader::new(
+            files
+                .iter()
+                .map(|(path, text)| ((*path).to_string(), (*text).to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parses_program_with_include() {
+        let reader = reader(&[
+            (
+                "src/main.s",
+                ".include \"lib/util.s\"\nmain:\n    jal fn_a\n    li a7, 10\n    ecall\n",
+            ),
+            ("src/lib/util.s", "fn_a:\n    li a0, 1\n    ret\n"),
+        ]);
+        let mut parser = RVParser::new(reader);
+        let (nodes, errors) = parser.parse_from_file("src/main.s", false);
+        assert_eq!(errors.len(), 0);
+
+        let files = nodes
+            .iter()
+            .filter(|node| node.is_instruction())
+            .map(|node| parser.reader.get_filename(node.token().file).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![
+                "src/lib/util.s",
+                "src/lib/util.s",
+                "src/main.s",
+                "src/main.s",
+                "src/main.s"
+            ]
+        );
+        assert!(nodes.iter().any(|node| matches!(
+            node, ParserNode::JumpLink(call) if call.name.data.0 == "fn_a"
+        )));
+    }
+
+    #[test]
+    fn include_is_relative_to_including_file() {
+        let mut reader = reader(&[
+            ("src/main.s", ""),
+            ("src/lib/util.s", ""),
+            ("shared.s", "nop\n"),
+        ]);
+        let (main, _) = reader.import_file("src/main.s", None).unwrap();
+        let (util, _) = reader.import_file("lib/util.s", Some(main)).unwrap();
+        let (shared, text) = reader.import_file("../../shared.s", Some(util)).unwrap();
+        assert_eq!(text, "nop\n");
+        assert_eq!(reader.get_filename(shared).unwrap(), "shared.s");
+        assert_eq!(reader.get_text(shared).unwrap(), "nop\n");
+
+        // The same file keeps its UUID
+        let (again, _) = reader.import_file("./src/../shared.s", None).unwrap();
+        assert_eq!(again, shared);
+    }
+
+    #[test]
+    fn missing_include_is_not_found() {
+        let reader = reader(&[("main.s", ".include \"missing.s\"\nnop\n")]);
+        let mut parser = RVParser::new(reader);
+        let (_, errors) = parser.parse_from_file("main.s", false);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0], ParseError::FileNotFound(path) if path.data == "missing.s"
+        ));
+    }
+
+    #[test]
+    fn cyclic_include_is_reported() {
+        let reader = reader(&[
+            ("main.s", ".include \"lib.s\"\nnop\n"),
+            ("lib.s", ".include \"main.s\"\nnop\n"),
+        ]);
+        let mut parser = RVParser::new(reader);
+        let (_, errors) = parser.parse_from_file("main.s", false);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], ParseError::CyclicDependency(_)));
+    }
+
+    #[test]
+    fn unknown_files_are_errors() {
+        let mut reader = reader(&[("main.s", "")]);
+        assert!(matches!(
+            reader.import_file("other.s", None),
+            Err(FileReaderError::InvalidPath)
+        ));
+        assert!(matches!(
+            reader.import_file("main.s", Some(Uuid::new_v4())),
+            Err(FileReaderError::InternalFileNotFound)
+        ));
+        assert_eq!(reader.get_text(Uuid::new_v4()), None);
+        assert_eq!(reader.get_filename(Uuid::new_v4()), None);
+    }
+}
@@ -1,5 +1,8 @@
 use uuid::Uuid;
 
+mod memory;
+pub use memory::*;
+
 #[derive(Debug)]
 pub enum FileReaderError {
     IOErr(String),
@@ -22,4 +25,11 @@ pub trait FileReader: Sized {
     fn get_text(&self, uuid: uuid::Uuid) -> Option<String>;
 
     fn get_filename(&self, uuid: uuid::Uuid) -> Option<String>;
+
+    /// The number of columns a tab takes up in a file.
+    ///
+    /// By default, a tab is a single column wide.
+    fn tab_width(&self, _uuid: uuid::Uuid) -> usize {
+        1
+    }
 }
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use riscv_analysis::passes::DiagnosticItem;
+use riscv_analysis::reader::FileReader;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum BaselineError {
+    /// The baseline file could not be read or written.
+    Io(PathBuf, std::io::Error),
+    /// The baseline file is not valid.
+    Json(PathBuf, serde_json::Error),
+}
+
+impl Display for BaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaselineError::Io(path, err) => {
+                write!(
+                    f,
+                    "Unable to access baseline file {}: {err}",
+                    path.display()
+                )
+            }
+            BaselineError::Json(path, err) => {
+                write!(f, "Invalid baseline file {}: {err}", path.display())
+            }
+        }
+    }
+}
+
+/// A diagnostic that was already reported.
+///
+/// Diagnostics are matched by their file, their title, and the text of the
+/// line they are on, but not by their line number. A diagnostic is still
+/// matched if code is added or removed above it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub title: String,
+    pub source: String,
+}
+
+impl BaselineEntry {
+    fn new<T: FileReader>(item: &DiagnosticItem, reader: &T) -> Self {
+        let file = reader
+            .get_filename(item.file)
+            .map(|path| relative_path(&path))
+            .unwrap_or_default();
+        let source = reader
+            .get_text(item.file)
+            .and_then(|text| {
+                text.lines()
+                    .nth(item.range.start.line)
+                    .map(|line| line.trim().to_string())
+            })
+            .unwrap_or_default();
+        BaselineEntry {
+            file,
+            title: item.title.clone(),
+            source,
+        }
+    }
+}
+
+/// A set of diagnostics that were already reported, so that only new
+/// diagnostics are shown.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Baseline {
+    pub diagnostics: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Create a baseline of the current diagnostics.
+    pub fn new<T: FileReader>(diagnostics: &[DiagnosticItem], reader: &T) -> Self {
+        Baseline {
+            diagnostics: diagnostics
+                .iter()
+                .map(|item| BaselineEntry::new(item, reader))
+                .collect(),
+        }
+    }
+
+    /// Read a baseline from a file.
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| BaselineError::Io(path.to_path_buf(), err))?;
+        serde_json::from_str(&text).map_err(|err| BaselineError::Json(path.to_path_buf(), err))
+    }
+
+    /// Write the baseline to a file.
+    pub fn save(&self, path: &Path) -> Result<(), BaselineError> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|err| BaselineError::Json(path.to_path_buf(), err))?;
+        std::fs::write(path, text + "\n").map_err(|err| BaselineError::Io(path.to_path_buf(), err))
+    }
+
+    /// Remove the diagnostics that are in the baseline.
+    ///
+    /// Each entry of the baseline removes at most one diagnostic, so if a
+    /// line gets a second diagnostic with the same title, it is still
+    /// reported.
+    pub fn new_diagnostics<T: FileReader>(
+        &self,
+        diagnostics: Vec<DiagnosticItem>,
+        reader: &T,
+    ) -> Vec<DiagnosticItem> {
+        let mut known: HashMap<&BaselineEntry, usize> = HashMap::new();
+        for entry in &self.diagnostics {
+            *known.entry(entry).or_default() += 1;
+        }
+
+        diagnostics
+            .into_iter()
+            .filter(|item| {
+                let entry = BaselineEntry::new(item, reader);
+                match known.get_mut(&entry) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Get a path relative to the working directory, if it is inside of it.
+///
+/// This keeps baseline files the same on every machine that checks out the
+/// code.
+fn relative_path(path: &str) -> String {
+    let path = PathBuf::from(path);
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use riscv_analysis::parser::{Position, Range};
+    use riscv_analysis::passes::SeverityLevel;
+    use riscv_analysis::reader::FileReaderError;
+    use uuid::Uuid;
+
+    struct TextReader(String);
+
+    impl FileReader for TextReader {
+        fn import_file(
+            &mut self,
+            _path: &str,
+            _parent_file: Option<Uuid>,
+        ) -> Result<(Uuid, String), FileReaderError> {
+            Err(FileReaderError::Unexpected)
+        }
+        fn get_text(&self, _uuid: Uuid) -> Option<String> {
+            Some(self.0.clone())
+        }
+        fn get_filename(&self, _uuid: Uuid) -> Option<String> {
+            Some("code.s".to_string())
+        }
+    }
+
+    fn item(line: usize, title: &str) -> DiagnosticItem {
+        let pos = Position {
+            line,
+            column: 4,
+            raw_index: 0,
+        };
+        DiagnosticItem {
+            file: Uuid::nil(),
+            range: Range {
+                start: pos,
+                end: pos,
+            },
+            code: String::new(),
+            title: title.to_string(),
+            description: String::new(),
+            long_description: String::new(),
+            level: SeverityLevel::Warning,
+            related: None,
+            fixes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn known_diagnostics_are_removed() {
+        let reader = TextReader("main:\n    li t0, 1\n    li t1, 2\n".to_string());
+        let baseline = Baseline::new(&[item(1, "Unused value")], &reader);
+        assert_eq!(baseline.diagnostics[0].source, "li t0, 1");
+
+        let new = baseline.new_diagnostics(
+            vec![item(1, "Unused value"), item(2, "Unused value")],
+            &reader,
+        );
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].range.start.line, 2);
+    }
+
+    #[test]
+    fn diagnostics_are_matched_after_lines_move() {
+        let old = TextReader("main:\n    li t0, 1\n".to_string());
+        let baseline = Baseline::new(&[item(1, "Unused value")], &old);
+
+        let moved = TextReader("main:\n    nop\n    nop\n    li t0, 1\n".to_string());
+        let new = baseline.new_diagnostics(vec![item(3, "Unused value")], &moved);
+        assert!(new.is_empty());
+
+        // A different diagnostic on the same line is new
+        let new = baseline.new_diagnostics(vec![item(3, "Unknown ecall")], &moved);
+        assert_eq!(new.len(), 1);
+    }
+
+    #[test]
+    fn each_entry_matches_once() {
+        let reader = TextReader("main:\n    li t0, 1\n".to_string());
+        let baseline = Baseline::new(&[item(1, "Unused value")], &reader);
+
+        let new = baseline.new_diagnostics(
+            vec![item(1, "Unused value"), item(1, "Unused value")],
+            &reader,
+        );
+        assert_eq!(new.len(), 1);
+    }
+
+    #[test]
+    fn baseline_round_trips_through_json() {
+        let reader = TextReader("main:\n    li t0, 1\n".to_string());
+        let baseline = Baseline::new(&[item(1, "Unused value")], &reader);
+
+        let text = serde_json::to_string(&baseline).unwrap();
+        let loaded: Baseline = serde_json::from_str(&text).unwrap();
+        assert_eq!(loaded, baseline);
+    }
+}
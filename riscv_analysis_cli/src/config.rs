@@ -0,0 +1,118 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use riscv_analysis::passes::ManagerConfiguration;
+
+/// Names of the configuration file that is used if no file is given.
+///
+/// The first file that exists in the working directory is used.
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["riscv-analysis.yaml", "riscv-analysis.yml"];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    Io(PathBuf, std::io::Error),
+    /// The configuration file is not valid.
+    Parse(PathBuf, serde_yaml::Error),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => {
+                write!(f, "Unable to read config file {}: {err}", path.display())
+            }
+            ConfigError::Parse(path, err) => {
+                write!(f, "Invalid config file {}: {err}", path.display())
+            }
+        }
+    }
+}
+
+/// Load the analysis configuration.
+///
+/// If a path is given, that file must exist. Otherwise, the working
+/// directory is searched for one of [`CONFIG_FILE_NAMES`]. If there is no
+/// configuration file, the default configuration is used.
+pub fn load_config(path: Option<&Path>) -> Result<ManagerConfiguration, ConfigError> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match CONFIG_FILE_NAMES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+        {
+            Some(path) => path,
+            None => return Ok(ManagerConfiguration::default()),
+        },
+    };
+
+    let text = std::fs::read_to_string(&path).map_err(|err| ConfigError::Io(path.clone(), err))?;
+    parse_config(&text).map_err(|err| ConfigError::Parse(path, err))
+}
+
+/// Parse the text of a configuration file.
+fn parse_config(text: &str) -> Result<ManagerConfiguration, serde_yaml::Error> {
+    // An empty file is an empty document, not an empty map
+    if text.trim().is_empty() {
+        return Ok(ManagerConfiguration::default());
+    }
+    serde_yaml::from_str(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use riscv_analysis::parser::Xlen;
+    use riscv_analysis::passes::LintLevel;
+
+    #[test]
+    fn parse_full_config() {
+        let text = "\
+            run_lints: true\n\
+            max_function_instructions: 50\n\
+            entry_points: [handler, other]\n\
+            dedup_diagnostics: false\n\
+            xlen: rv64\n\
+            library: true\n\
+            lint_levels: {dead-assignment: off, unreachable-code: error, boolean-misuse: off}\n";
+
+        let config = parse_config(text).unwrap();
+        assert_eq!(config.max_function_instructions, Some(50));
+        assert!(!config.is_lint_enabled("boolean-misuse"));
+        assert_eq!(config.entry_points, vec!["handler", "other"]);
+        assert!(!config.dedup_diagnostics);
+        assert_eq!(config.xlen, Xlen::Rv64);
+        assert!(config.library);
+        assert_eq!(config.lint_level("dead-assignment"), Some(LintLevel::Off));
+        assert_eq!(
+            config.lint_level("unreachable-code"),
+            Some(LintLevel::Error)
+        );
+        assert_eq!(config.lint_level("save-to-zero"), None);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let config = parse_config("max_function_instructions: 10\n").unwrap();
+        assert_eq!(config.max_function_instructions, Some(10));
+        assert!(config.run_lints);
+        assert!(config.is_lint_enabled("boolean-misuse"));
+        assert!(config.dedup_diagnostics);
+        assert_eq!(config.xlen, Xlen::Rv32);
+
+        let config = parse_config("").unwrap();
+        assert_eq!(config.max_function_instructions, None);
+    }
+
+    #[test]
+    fn unknown_field_is_error() {
+        assert!(parse_config("max_function_size: 10\n").is_err());
+    }
+
+    #[test]
+    fn missing_config_file_is_error() {
+        let path = Path::new("does-not-exist.yaml");
+        assert!(matches!(load_config(Some(path)), Err(ConfigError::Io(..))));
+    }
+}
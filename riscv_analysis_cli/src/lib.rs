@@ -14,6 +14,22 @@ pub mod wrapper {
         pub description: String,
         pub level: String,
         pub range: RangeTestCase,
+        /// The name of the lint or kind of error, like `dead-assignment`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub rule_id: Option<String>,
+        /// Edits that fix the issue, which tools can apply on their own.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub fixes: Vec<TextEditTestCase>,
+    }
+
+    /// A replacement of a range of a file with new text.
+    ///
+    /// Each edit has its own file, as a fix can change more than one file.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct TextEditTestCase {
+        pub file: Option<String>,
+        pub range: RangeTestCase,
+        pub replacement: String,
     }
 
     #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -1,5 +1,11 @@
+mod baseline;
+mod config;
 mod printer;
+mod stats;
+use baseline::Baseline;
+use config::load_config;
 use printer::*;
+use stats::Stats;
 
 use std::fmt::Display;
 use std::io::Write;
@@ -8,13 +14,17 @@ use std::{collections::HashMap, str::FromStr};
 // use bat::line_range::{LineRange, LineRanges};
 // use bat::{Input, PrettyPrinter};
 use colored::Colorize;
-use riscv_analysis::fix::Manipulation;
-use riscv_analysis::parser::RVParser;
-use riscv_analysis::passes::DiagnosticItem;
+use riscv_analysis::analyze;
+use riscv_analysis::fix::{fix_stack, remove_stack_saves, Manipulation};
+use riscv_analysis::parser::{emit_assembly, RVParser, RegisterNames};
 use std::path::PathBuf;
+use std::rc::Rc;
 use uuid::Uuid;
 
-use riscv_analysis::passes::{DiagnosticLocation, Manager};
+use riscv_analysis::passes::{
+    DiagnosticItem, DiagnosticLocation, LintError, LintLevel, Manager, ManagerConfiguration,
+    SeverityLevel,
+};
 
 use clap::{Args, Parser, Subcommand};
 use riscv_analysis::reader::{FileReader, FileReaderError};
@@ -31,6 +41,15 @@ enum Commands {
     /// Lint a file
     #[clap(name = "lint")]
     Lint(Lint),
+    /// Save and restore the registers of a function
+    #[clap(name = "fix")]
+    Fix(Fix),
+    /// Count the instructions and registers of a file
+    #[clap(name = "stats")]
+    Stats(StatsArgs),
+    /// Print a file as assembly for an assembler, keeping its relocations
+    #[clap(name = "emit")]
+    Emit(Emit),
     /// Debug options for testing
     #[clap(name = "debug_parse")]
     DebugParse(DebugParse),
@@ -46,12 +65,148 @@ struct Lint {
     /// Output debug as yaml
     #[clap(long)]
     yaml: bool,
+    /// Output the CFG as JSON
+    #[clap(long)]
+    cfg_json: bool,
     /// Output lints as JSON
     #[clap(long)]
     json: bool,
+    /// Output lints as SARIF 2.1.0, for code scanning in CI
+    #[clap(long)]
+    sarif: bool,
     /// Remove output
     #[clap(long)]
     no_output: bool,
+    /// Only report warnings and errors
+    #[clap(short, long)]
+    quiet: bool,
+    /// Exit with a failure if there are warnings, not only if there are
+    /// errors
+    #[clap(long)]
+    deny_warnings: bool,
+    /// Report functions with more than this many instructions
+    #[clap(long)]
+    max_function_size: Option<usize>,
+    /// Treat this label as another entry point of the program
+    ///
+    /// Labels exported with `.globl` are always entry points.
+    #[clap(long = "entry")]
+    entry_points: Vec<String>,
+    /// Report every diagnostic, even if the same rule reports another at the
+    /// same location
+    #[clap(long)]
+    no_dedup: bool,
+    /// Only print this many diagnostics, and count the rest
+    #[clap(long, value_name = "N")]
+    max_errors: Option<usize>,
+    /// Read the analysis configuration from this file
+    ///
+    /// By default, `riscv-analysis.yaml` in the working directory is used if
+    /// it exists. Options given on the command line override the file.
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Analyze a file of functions that has no program entry
+    ///
+    /// Every label that is not jumped to or fallen into is checked as the
+    /// entry of a function.
+    #[clap(long)]
+    library: bool,
+    /// Only report diagnostics that are not in this baseline file
+    ///
+    /// Diagnostics are matched by their file, title, and the text of their
+    /// line, so they still match if the code around them moves.
+    #[clap(long)]
+    baseline: Option<PathBuf>,
+    /// Save the current diagnostics to this baseline file
+    #[clap(long)]
+    write_baseline: Option<PathBuf>,
+    /// Print the CFG as a Graphviz DOT graph instead of the diagnostics
+    ///
+    /// If the CFG cannot be built, the diagnostics are printed instead.
+    #[clap(long)]
+    dot: bool,
+    /// Continue each call of the `--dot` graph into the function it calls,
+    /// and each return after every call to its function
+    #[clap(long, requires = "dot")]
+    interprocedural: bool,
+    /// Print the graph of which functions call which as a Graphviz DOT graph
+    /// instead of the diagnostics
+    ///
+    /// If the CFG cannot be built, the diagnostics are printed instead.
+    #[clap(long)]
+    call_graph: bool,
+    /// Set the level of a lint, like `dead-assignment=error`
+    ///
+    /// The level is one of `error`, `warning`, `information`, `hint`, or
+    /// `off` to not report the lint. This can be given many times.
+    #[clap(long = "lint-level", value_name = "NAME=LEVEL", value_parser = parse_lint_level)]
+    lint_levels: Vec<(String, LintLevel)>,
+    /// Print the registers of the `--dot` graph with ABI names, like `a0`,
+    /// or numeric names, like `x10`
+    ///
+    /// This only changes the graph. Diagnostics always use ABI names.
+    #[clap(
+        long,
+        value_name = "abi|numeric",
+        default_value = "abi",
+        requires = "dot",
+        value_parser = parse_register_names
+    )]
+    dot_reg_names: RegisterNames,
+}
+
+/// Parse the value of `--lint-level`, like `dead-assignment=error`.
+fn parse_lint_level(arg: &str) -> Result<(String, LintLevel), String> {
+    let (name, level) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=LEVEL, found `{arg}`"))?;
+    if !LintError::NAMES.contains(&name) {
+        return Err(format!(
+            "unknown lint `{name}`, expected one of: {}",
+            LintError::NAMES.join(", ")
+        ));
+    }
+    let level = LintLevel::from_str(level).map_err(|()| {
+        format!("unknown level `{level}`, expected one of: error, warning, information, hint, off")
+    })?;
+    Ok((name.to_string(), level))
+}
+
+/// Parse the value of `--dot-reg-names`, either `abi` or `numeric`.
+fn parse_register_names(arg: &str) -> Result<RegisterNames, String> {
+    RegisterNames::from_str(arg)
+        .map_err(|()| format!("unknown register names `{arg}`, expected abi or numeric"))
+}
+
+impl Lint {
+    /// Override the values of a configuration with the options that were
+    /// given on the command line.
+    fn apply_to(&self, config: &mut ManagerConfiguration) {
+        if let Some(max) = self.max_function_size {
+            config.max_function_instructions = Some(max);
+        }
+        if !self.entry_points.is_empty() {
+            config.entry_points.clone_from(&self.entry_points);
+        }
+        if self.no_dedup {
+            config.dedup_diagnostics = false;
+        }
+        if self.library {
+            config.library = true;
+        }
+        for (name, level) in &self.lint_levels {
+            config.lint_levels.insert(name.clone(), *level);
+        }
+    }
+
+    /// Check if a diagnostic of this level makes the exit code a failure.
+    fn fails_on(&self, level: &SeverityLevel) -> bool {
+        match level {
+            SeverityLevel::Error => true,
+            SeverityLevel::Warning => self.deny_warnings,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Args)]
@@ -65,6 +220,45 @@ struct Fix {
     ///
     /// Name of a function to fix.
     func_name: String,
+    /// Apply every change without asking
+    #[clap(short = 'y', long, visible_alias = "yes")]
+    fix_all: bool,
+    /// Print the changes as a unified diff instead of writing them
+    #[clap(long)]
+    dry_run: bool,
+    /// Remove the saves and restores that the function already has
+    ///
+    /// Without this, the new saves and restores are only inserted, and the
+    /// old ones must be removed by hand.
+    #[clap(long)]
+    remove_old: bool,
+}
+
+impl Fix {
+    fn mode(&self) -> FixMode {
+        if self.dry_run {
+            FixMode::DryRun
+        } else if self.fix_all {
+            FixMode::Apply
+        } else {
+            FixMode::Ask
+        }
+    }
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// Input file
+    input: PathBuf,
+    /// Output the counts as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct Emit {
+    /// Input file
+    input: PathBuf,
 }
 
 #[derive(Args)]
@@ -80,124 +274,156 @@ struct IOFileReader {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 enum ManipulationError {
     InternalError,
+    WriteError(String),
 }
 
 impl Display for ManipulationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ManipulationError::InternalError => write!(f, "Internal error"),
+            ManipulationError::WriteError(err) => write!(f, "Unable to write file: {err}"),
         }
     }
 }
 
+/// How fixes are applied to the files they change.
+#[derive(Clone, Copy, PartialEq)]
+enum FixMode {
+    /// Ask before writing the changes
+    Ask,
+    /// Write the changes without asking
+    Apply,
+    /// Print the changes as a unified diff without writing them
+    DryRun,
+}
+
 impl IOFileReader {
     fn new() -> Self {
         IOFileReader {
             files: HashMap::new(),
         }
     }
-    #[allow(dead_code)]
-    fn apply_fixes(&self, fixes: Vec<Manipulation>) -> Result<(), ManipulationError> {
-        struct ChangedRanges {
-            filename: String,
-            file: uuid::Uuid,
-            begin_window: usize,
-            end_window: usize,
-            begin_change: usize,
-            end_change: usize,
-        }
-
-        // map of file uuid to (path, source, offet pos, offset lines)
-        let mut changed_files: HashMap<uuid::Uuid, (String, String, i64, i64)> = HashMap::new();
-        let mut changed_ranges = Vec::new();
+    /// Get the new path and contents of every file changed by a list of fixes.
+    ///
+    /// Fixes are applied from the top of each file down, and each one is
+    /// moved by the lines added and removed by the fixes before it.
+    fn fixed_files(
+        &self,
+        mut fixes: Vec<Manipulation>,
+    ) -> Result<HashMap<uuid::Uuid, (String, String)>, ManipulationError> {
+        // The sort is stable, so an insertion before a line that is removed
+        // stays before the removal.
+        fixes.sort_by_key(Manipulation::line);
+
+        // map of file uuid to (path, source, offset lines)
+        let mut changed_files: HashMap<uuid::Uuid, (String, String, isize)> = HashMap::new();
 
         for fix in fixes {
             // check if we already have changed this file
             // otherwise, get the file details
-            let (path, source, mut offset, mut offset_lines) =
-                if let Some(x) = changed_files.get(&fix.file()) {
-                    (x.0.clone(), x.1.clone(), x.2, x.3)
-                } else {
-                    let file_details = self
+            let file = fix.file();
+            let (path, mut source, offset_lines) = match changed_files.remove(&file) {
+                Some(details) => details,
+                None => {
+                    let (path, source) = self
                         .files
-                        .get(&fix.file())
-                        .ok_or(ManipulationError::InternalError)?;
-                    let res = file_details.clone();
-                    // changed_files.insert(fix.file(), (res.0.clone(), res.1.clone(), 0));
-                    (res.0, res.1, 0, 0)
-                };
-
-            let row = fix.line();
-            let pos = fix.raw_pos() - 1;
+                        .get(&file)
+                        .ok_or(ManipulationError::InternalError)?
+                        .clone();
+                    (path, source, 0)
+                }
+            };
 
-            let file = fix.file();
-            // insert fix text into source
-            match fix {
-                Manipulation::Insert(_, _, s, lines) => {
-                    let mut new_source = source.clone();
-                    // we know that the insert only inserts, so we can be safe returning the offset
-                    // as usize
-                    new_source.insert_str(pos + offset as usize, &s);
-                    changed_ranges.push(ChangedRanges {
-                        filename: path.clone(),
-                        file,
-                        begin_window: row - 2 + offset_lines as usize,
-                        end_window: row + lines + offset_lines as usize + 1,
-                        begin_change: row + offset_lines as usize,
-                        end_change: row + lines + offset_lines as usize - 1,
-                    });
-                    offset += s.len() as i64;
-                    offset_lines += lines as i64;
-                    changed_files.insert(file, (path.clone(), new_source, offset, offset_lines));
+            let row = fix
+                .line()
+                .checked_add_signed(offset_lines)
+                .ok_or(ManipulationError::InternalError)?;
+            let pos =
+                byte_index(&source, row, fix.column()).ok_or(ManipulationError::InternalError)?;
+
+            let offset_lines = match fix {
+                Manipulation::Insert(_, _, text, lines) => {
+                    source.insert_str(pos, &text);
+                    offset_lines + lines as isize
                 }
-            }
+                Manipulation::RemoveLine(..) => {
+                    let end = source[pos..]
+                        .find('\n')
+                        .map_or(source.len(), |newline| pos + newline + 1);
+                    source.replace_range(pos..end, "");
+                    offset_lines - 1
+                }
+            };
+            changed_files.insert(file, (path, source, offset_lines));
         }
 
-        // display changed ranges
-        // for changed_range in changed_ranges {
-        //     let input =
-        //         Input::from_reader(changed_files.get(&changed_range.file).unwrap().1.as_bytes())
-        //             .name(changed_range.filename.clone());
-        //     PrettyPrinter::new()
-        //         .input(input)
-        //         .line_numbers(true)
-        //         .header(true)
-        //         .grid(true)
-        //         .line_ranges(LineRanges::from(vec![LineRange::new(
-        //             changed_range.begin_window + 1,
-        //             changed_range.end_window + 1,
-        //         )]))
-        //         .highlight_range(changed_range.begin_change + 1, changed_range.end_change + 1)
-        //         .print()
-        //         .unwrap();
-        // }
+        Ok(changed_files
+            .into_iter()
+            .map(|(file, (path, source, _))| (file, (path, source)))
+            .collect())
+    }
 
-        // ask user to apply changes
-        let mut apply_changes = false;
-        loop {
-            let mut input = String::new();
-            print!("Apply changes? [y/n] ");
-            // flush stdout
-            std::io::stdout().flush().unwrap();
-            std::io::stdin().read_line(&mut input).unwrap();
-            if input.trim() == "y" {
-                apply_changes = true;
-                break;
-            } else if input.trim() == "n" {
-                break;
+    fn apply_fixes(
+        &self,
+        fixes: Vec<Manipulation>,
+        mode: FixMode,
+    ) -> Result<(), ManipulationError> {
+        let removes_lines = fixes
+            .iter()
+            .any(|fix| matches!(fix, Manipulation::RemoveLine(..)));
+        let mut changed_files = self.fixed_files(fixes)?.into_values().collect::<Vec<_>>();
+        changed_files.sort();
+
+        if mode == FixMode::DryRun {
+            for (path, source) in &changed_files {
+                let original = self
+                    .files
+                    .values()
+                    .find(|(file_path, _)| file_path == path)
+                    .map(|(_, text)| text.as_str())
+                    .unwrap_or_default();
+                let lines = |text: &str| {
+                    text.lines()
+                        .map(|line| format!("{line}\n"))
+                        .collect::<Vec<_>>()
+                };
+                let diff =
+                    difflib::unified_diff(&lines(original), &lines(source), path, path, "", "", 3);
+                print!("{}", diff.concat());
             }
+            return Ok(());
         }
 
-        if apply_changes {
-            for (path, source, _, _) in changed_files.values() {
-                let mut file = std::fs::File::create(path).unwrap();
-                file.write_all(source.as_bytes()).unwrap();
+        // ask user to apply changes
+        if mode == FixMode::Ask {
+            let mut apply_changes = false;
+            loop {
+                let mut input = String::new();
+                print!("Apply changes? [y/n] ");
+                // flush stdout
+                std::io::stdout().flush().unwrap();
+                std::io::stdin().read_line(&mut input).unwrap();
+                if input.trim() == "y" {
+                    apply_changes = true;
+                    break;
+                } else if input.trim() == "n" {
+                    break;
+                }
+            }
+            if !apply_changes {
+                return Ok(());
             }
+        }
+
+        for (path, source) in &changed_files {
+            std::fs::write(path, source)
+                .map_err(|err| ManipulationError::WriteError(err.to_string()))?;
+        }
 
-            println!("{}", "Changes applied.".green());
+        println!("{}", "Changes applied.".green());
+        if !removes_lines {
             println!(
                 "{}Please remove all other instances of stack manipulation in your code. This fix did not remove any lines of code.",
                 "WARNING: ".red().bold()
@@ -207,6 +433,36 @@ impl IOFileReader {
     }
 }
 
+/// Sort diagnostics by the path of their file, then by their location and
+/// rule.
+///
+/// Files are identified by a UUID that is new on every run, so sorting by
+/// the path keeps the output the same from run to run.
+fn sort_diagnostics(diagnostics: &mut [DiagnosticItem], reader: &IOFileReader) {
+    diagnostics.sort_by(|a, b| {
+        reader
+            .get_filename(a.file)
+            .cmp(&reader.get_filename(b.file))
+            .then_with(|| a.cmp(b))
+    });
+}
+
+/// Find the byte index of a line and column in a source.
+///
+/// Columns count characters, so that lines with multi-byte characters are
+/// handled.
+fn byte_index(source: &str, line: usize, column: usize) -> Option<usize> {
+    let start = match line.checked_sub(1) {
+        Some(prev) => source.match_indices('\n').nth(prev)?.0 + 1,
+        None => 0,
+    };
+    source[start..]
+        .char_indices()
+        .map(|(i, _)| start + i)
+        .chain(std::iter::once(source.len()))
+        .nth(column)
+}
+
 impl FileReader for IOFileReader {
     fn get_text(&self, uuid: uuid::Uuid) -> Option<String> {
         self.files.get(&uuid).map(|(_, text)| text.clone())
@@ -229,8 +485,11 @@ impl FileReader for IOFileReader {
                     .ok()
                     .ok_or(FileReaderError::InvalidPath)?;
                 let parent = parent.parent().ok_or(FileReaderError::InvalidPath)?;
-                parent
-                    .join(path)
+                let full_path = parent.join(path);
+                // Missing files are reported when they are read
+                full_path
+                    .canonicalize()
+                    .unwrap_or(full_path)
                     .to_str()
                     .ok_or(FileReaderError::InvalidPath)?
                     .to_owned()
@@ -247,6 +506,12 @@ impl FileReader for IOFileReader {
                 .to_owned()
         };
 
+        // a file that was already read keeps its UUID, so that the parser
+        // can find cycles and files that are included more than once
+        if let Some((uuid, (_, text))) = self.files.iter().find(|(_, (p, _))| *p == path) {
+            return Ok((*uuid, text.clone()));
+        }
+
         // open file and read it
         let file = match std::fs::read_to_string(path.clone()) {
             Ok(file) => file,
@@ -255,13 +520,7 @@ impl FileReader for IOFileReader {
 
         // store full path to file
         let uuid = uuid::Uuid::new_v4();
-        if self
-            .files
-            .insert(uuid, (path.clone(), file.clone()))
-            .is_some()
-        {
-            return Err(FileReaderError::FileAlreadyRead(path));
-        }
+        self.files.insert(uuid, (path, file.clone()));
 
         Ok((uuid, file))
     }
@@ -272,53 +531,205 @@ fn main() {
     match args.command {
         Commands::Lint(lint) => {
             let reader = IOFileReader::new();
-            let mut parser = RVParser::new(reader);
-
-            let mut diags = Vec::new();
-            let parsed = parser.parse_from_file(
-                lint.input
-                    .to_str()
-                    .expect("unable to convert path to string"),
-                false,
-            );
-            parsed
-                .1
-                .iter()
-                .for_each(|x| diags.push(DiagnosticItem::from(x.clone())));
-
-            match Manager::gen_full_cfg(parsed.0) {
-                Ok(full_cfg) => {
-                    // if debug, print out the cfg
-                    if lint.yaml {
-                        let wrapped = riscv_analysis::cfg::CfgWrapper::from(&full_cfg);
-                        println!("{}", serde_yaml::to_string(&wrapped).unwrap());
-                    } else if lint.debug {
-                        println!("{}", full_cfg);
+            let input = lint
+                .input
+                .to_str()
+                .expect("unable to convert path to string");
+            let mut config = match load_config(lint.config.as_deref()) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("{} {}", "error:".red().bold(), err);
+                    std::process::exit(1);
+                }
+            };
+            lint.apply_to(&mut config);
+            let mut result = analyze((reader, input), &config);
+
+            // if debug, print out the cfg
+            if let Some(full_cfg) = &result.cfg {
+                if lint.yaml {
+                    let wrapped = riscv_analysis::cfg::CfgWrapper::from(full_cfg);
+                    println!("{}", serde_yaml::to_string(&wrapped).unwrap());
+                } else if lint.cfg_json {
+                    let wrapped = riscv_analysis::cfg::CfgWrapper::from(full_cfg);
+                    println!("{}", serde_json::to_string_pretty(&wrapped).unwrap());
+                } else if lint.debug {
+                    println!("{}", full_cfg);
+                }
+                if lint.dot {
+                    if lint.interprocedural {
+                        print!(
+                            "{}",
+                            full_cfg.to_interprocedural_dot(false, lint.dot_reg_names)
+                        );
+                    } else {
+                        print!("{}", full_cfg.to_dot(false, lint.dot_reg_names));
                     }
-                    let mut errs = Vec::new();
-                    Manager::run_diagnostics(&full_cfg, &mut errs);
-                    errs.iter()
-                        .for_each(|x| diags.push(DiagnosticItem::from(x.clone())));
+                    return;
                 }
-                Err(err) => {
-                    diags.push(DiagnosticItem::from(*err));
+                if lint.call_graph {
+                    print!("{}", full_cfg.function_call_graph().to_dot());
+                    return;
                 }
+            }
+
+            if let Some(path) = &lint.write_baseline {
+                let baseline = Baseline::new(&result.diagnostics, &result.reader);
+                if let Err(err) = baseline.save(path) {
+                    eprintln!("{} {}", "error:".red().bold(), err);
+                    std::process::exit(1);
+                }
+                println!(
+                    "Saved {} diagnostics to {}",
+                    baseline.diagnostics.len(),
+                    path.display()
+                );
+                return;
+            }
+
+            if let Some(path) = &lint.baseline {
+                let baseline = match Baseline::load(path) {
+                    Ok(baseline) => baseline,
+                    Err(err) => {
+                        eprintln!("{} {}", "error:".red().bold(), err);
+                        std::process::exit(1);
+                    }
+                };
+                let diags = std::mem::take(&mut result.diagnostics);
+                result.diagnostics = baseline.new_diagnostics(diags, &result.reader);
+            }
+
+            if lint.quiet {
+                result.diagnostics.retain(|diag| {
+                    matches!(diag.level, SeverityLevel::Error | SeverityLevel::Warning)
+                });
+            }
+
+            // The exit code counts every diagnostic, even ones that are not
+            // printed
+            let failed = result
+                .diagnostics
+                .iter()
+                .any(|diag| lint.fails_on(&diag.level));
+
+            sort_diagnostics(&mut result.diagnostics, &result.reader);
+            let hidden = match lint.max_errors {
+                Some(max) if result.diagnostics.len() > max => {
+                    let hidden = result.diagnostics.len() - max;
+                    result.diagnostics.truncate(max);
+                    hidden
+                }
+                _ => 0,
             };
 
             if !lint.no_output {
-                diags.sort();
+                let diags = std::mem::take(&mut result.diagnostics);
 
                 // Output as JSON
                 if lint.json {
                     let mut printer = JSONPrint::new(diags);
-                    printer.display_errors(&parser);
+                    printer.display_errors(&result.reader);
+                }
+                // Output as SARIF
+                else if lint.sarif {
+                    let mut printer = SarifPrint::new(diags);
+                    printer.display_errors(&result.reader);
                 }
                 // Pretty print output
                 else {
                     let mut printer = PrettyPrint::new(diags);
-                    printer.display_errors(&parser);
+                    printer.display_errors(&result.reader);
+                }
+
+                // The footer is not part of JSON or SARIF output, so that
+                // it can still be parsed
+                if hidden > 0 {
+                    let footer = format!("…and {hidden} more");
+                    if lint.json || lint.sarif {
+                        eprintln!("{footer}");
+                    } else {
+                        println!("{footer}");
+                    }
                 }
             }
+
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Fix(fix) => {
+            let input = fix
+                .input
+                .to_str()
+                .expect("unable to convert path to string");
+            let result = analyze(
+                (IOFileReader::new(), input),
+                &ManagerConfiguration::default(),
+            );
+            let func = result.cfg.as_ref().and_then(|cfg| {
+                cfg.functions()
+                    .iter()
+                    .find(|(label, _)| label.data == fix.func_name.as_str())
+                    .map(|(_, func)| Rc::clone(func))
+            });
+            let Some(func) = func else {
+                eprintln!(
+                    "{} no function named {}",
+                    "error:".red().bold(),
+                    fix.func_name
+                );
+                std::process::exit(1);
+            };
+
+            let mut fixes = fix_stack(&func);
+            if fix.remove_old {
+                fixes.extend(remove_stack_saves(&func));
+            }
+            if let Err(err) = result.reader.apply_fixes(fixes, fix.mode()) {
+                eprintln!("{} {}", "error:".red().bold(), err);
+                std::process::exit(1);
+            }
+        }
+        Commands::Stats(stats) => {
+            let mut parser = RVParser::new(IOFileReader::new());
+            let (nodes, _) = parser.parse_from_file(
+                stats
+                    .input
+                    .to_str()
+                    .expect("unable to convert path to string"),
+                false,
+            );
+            let cfg = match Manager::gen_full_cfg(nodes) {
+                Ok(cfg) => cfg,
+                Err(err) => {
+                    eprintln!("{} {}", "error:".red().bold(), err);
+                    std::process::exit(1);
+                }
+            };
+            let counts = Stats::new(&cfg);
+            if stats.json {
+                counts.print_json();
+            } else {
+                counts.print();
+            }
+        }
+        Commands::Emit(emit) => {
+            // Included files are kept as `.include` directives
+            let mut parser = RVParser::new(IOFileReader::new());
+            let (nodes, errors) = parser.parse_from_file(
+                emit.input
+                    .to_str()
+                    .expect("unable to convert path to string"),
+                true,
+            );
+            // Lines that do not parse would be left out of the output
+            if !errors.is_empty() {
+                for err in errors {
+                    eprintln!("{} {} ({})", "error:".red().bold(), err, err.range());
+                }
+                std::process::exit(1);
+            }
+            print!("{}", emit_assembly(&nodes));
         }
         Commands::DebugParse(debu) => {
             // Debug mode that prints out parsing errors only
@@ -388,4 +799,26 @@ mod tests {
     }
     file_test_case!(loop_check);
     file_test_case!(treg);
+
+    #[test]
+    fn cfg_json_round_trip() {
+        let filename = concat!(file_name!("loop_check"), "/code.s");
+        let json = || {
+            let mut parser = RVParser::new(IOFileReader::new());
+            let parsed = parser.parse_from_file(filename, false);
+            let cfg = Manager::gen_full_cfg(parsed.0).unwrap();
+            serde_json::to_string_pretty(&CfgWrapper::from(&cfg)).unwrap()
+        };
+
+        let first = json();
+        assert_eq!(first, json());
+
+        let res: CfgWrapper = serde_json::from_str(&first).unwrap();
+        assert_eq!(res.schema_version, CfgWrapper::SCHEMA_VERSION);
+        assert!(res.nodes.iter().any(|node| !node.func_entry.is_empty()));
+        let compare = concat!(file_name!("loop_check"), "/raw.yaml");
+        let compare: CfgWrapper =
+            serde_yaml::from_str(&std::fs::read_to_string(compare).unwrap()).unwrap();
+        assert_eq!(res, compare);
+    }
 }
@@ -1,17 +1,19 @@
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 use colored::Colorize;
+use serde::Serialize;
 
-use riscv_analysis::parser::RVParser;
-use riscv_analysis::passes::{DiagnosticItem, SeverityLevel};
+use riscv_analysis::parser::Range;
+use riscv_analysis::passes::{DiagnosticItem, Manager, RuleInfo, SeverityLevel};
 use riscv_analysis::reader::FileReader;
 use uuid::Uuid;
 
-use riscv_analysis_cli::wrapper::{DiagnosticTestCase, TestCase};
+use riscv_analysis_cli::wrapper::{DiagnosticTestCase, TestCase, TextEditTestCase};
 
 pub trait ErrorDisplay {
-    fn display_errors<T: FileReader + Clone>(&mut self, parser: &RVParser<T>);
+    fn display_errors<T: FileReader>(&mut self, reader: &T);
 }
 
 /// Pretty printer for errors.
@@ -29,10 +31,10 @@ impl PrettyPrint {
     }
 
     /// Return the contents of a file, caching the results.
-    fn get_file<T: FileReader + Clone>(&mut self, parser: &RVParser<T>, file: &Uuid) -> Option<&Vec<String>> {
+    fn get_file<T: FileReader>(&mut self, reader: &T, file: &Uuid) -> Option<&Vec<String>> {
         // Load the file if we haven't already
         if !self.files.contains_key(file) {
-            let path = parser.reader.get_filename(*file)?;
+            let path = reader.get_filename(*file)?;
             let contents = fs::read_to_string(path).ok()?;
             let lines: Vec<String> = contents
                 .split('\n')
@@ -99,10 +101,10 @@ impl PrettyPrint {
     }
 
     /// Fromat a diagnostic item.
-    fn format_item<T: FileReader + Clone>(&mut self, parser: &RVParser<T>, item: &DiagnosticItem) -> String {
+    fn format_item<T: FileReader>(&mut self, reader: &T, item: &DiagnosticItem) -> String {
         let level = self.level(&item.level);
         let title = &item.title;
-        let path = parser.reader
+        let path = reader
                          .get_filename(item.file)
                          .unwrap_or("<unknown file>".to_string());
 
@@ -112,7 +114,7 @@ impl PrettyPrint {
         );
 
         // Print the relevant source region
-        if let Some(text) = self.get_file(parser, &item.file) {
+        if let Some(text) = self.get_file(reader, &item.file) {
             let line = item.range.start.line;
             if let Some(region) = Self::get_line(text, line) {
                 let start = item.range.start.column;
@@ -127,9 +129,9 @@ impl PrettyPrint {
 }
 
 impl ErrorDisplay for PrettyPrint {
-    fn display_errors<T: FileReader + Clone>(&mut self, parser: &RVParser<T>) {
+    fn display_errors<T: FileReader>(&mut self, reader: &T) {
         for err in self.diagnostics.clone() {
-            let out = self.format_item(parser, &err);
+            let out = self.format_item(reader, &err);
             print!("{}", out);
         }
     }
@@ -148,14 +150,18 @@ impl JSONPrint {
         }
     }
 
+    /// Get the full path of a file, as it is printed.
+    fn path<T: FileReader>(reader: &T, file: Uuid) -> Option<String> {
+        reader
+            .get_filename(file)
+            .map(|f| fs::canonicalize(f).unwrap_or_default())
+            .map(|p| p.to_str().unwrap_or_default().to_string())
+    }
+
     /// Convert a single diagnostic item to JSON
-    fn wrap_item<T: FileReader + Clone> (&self, parser: &RVParser<T>, item: &DiagnosticItem) -> DiagnosticTestCase {
+    fn wrap_item<T: FileReader> (&self, reader: &T, item: &DiagnosticItem) -> DiagnosticTestCase {
         // Get the fields
-        let path = parser
-            .reader
-            .get_filename(item.file)
-            .map(|f| fs::canonicalize(f).unwrap_or_default())
-            .map(|p| p.to_str().unwrap_or_default().to_string());
+        let path = Self::path(reader, item.file);
         let level = match item.level {
             SeverityLevel::Error => "Error",
             SeverityLevel::Warning => "Warning",
@@ -169,17 +175,27 @@ impl JSONPrint {
             description: item.description.clone(),
             level: level.to_string(),
             range: item.range.clone().into(),
+            rule_id: Some(item.code.clone()).filter(|code| !code.is_empty()),
+            fixes: item
+                .fixes
+                .iter()
+                .map(|fix| TextEditTestCase {
+                    file: Self::path(reader, fix.file),
+                    range: fix.range.clone().into(),
+                    replacement: fix.replacement.clone(),
+                })
+                .collect(),
         }
     }
 }
 
 impl ErrorDisplay for JSONPrint {
-    fn display_errors<T: FileReader + Clone>(&mut self, parser: &RVParser<T>) {
+    fn display_errors<T: FileReader>(&mut self, reader: &T) {
         // Convert the diagnostic items to JSON
         let sub: Vec<_> = self
             .diagnostics
             .iter()
-            .map(|d| self.wrap_item(parser, d))
+            .map(|d| self.wrap_item(reader, d))
             .collect();
 
         // Print the results
@@ -188,3 +204,275 @@ impl ErrorDisplay for JSONPrint {
         println!("{}", text);
     }
 }
+
+/// Print lints as SARIF 2.1.0, for code scanning in CI.
+///
+/// Every lint that is reported becomes a rule, keyed by its name. Files in
+/// the working directory are given relative to `%SRCROOT%`, so that the
+/// results can be matched to a checkout.
+pub struct SarifPrint {
+    diagnostics: Vec<DiagnosticItem>,
+}
+
+#[derive(Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun {
+    tool: SarifTool,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    original_uri_base_ids: HashMap<&'static str, SarifArtifactLocation>,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: String,
+    short_description: SarifMessage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: SarifPhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<SarifMessage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+const SRCROOT: &str = "%SRCROOT%";
+
+impl SarifPrint {
+    /// Create a new SARIF printer.
+    pub fn new(errors: Vec<DiagnosticItem>) -> Self {
+        Self {
+            diagnostics: errors,
+        }
+    }
+
+    /// Return the name of a severity level in SARIF.
+    ///
+    /// SARIF has no level for hints, so they are notes like information.
+    fn level(level: &SeverityLevel) -> &'static str {
+        match level {
+            SeverityLevel::Error => "error",
+            SeverityLevel::Warning => "warning",
+            SeverityLevel::Information | SeverityLevel::Hint => "note",
+        }
+    }
+
+    /// Convert a path to a URI, percent-encoding the characters that are not
+    /// allowed in one.
+    fn encode_uri(path: &str) -> String {
+        let mut uri = String::new();
+        for byte in path.replace('\\', "/").bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+                uri.push(char::from(byte));
+            } else {
+                uri.push_str(&format!("%{byte:02X}"));
+            }
+        }
+        uri
+    }
+
+    /// Find the location of a file, relative to the working directory if
+    /// it is inside of it.
+    fn artifact<T: FileReader>(
+        reader: &T,
+        file: Uuid,
+        root: Option<&Path>,
+    ) -> SarifArtifactLocation {
+        let path = reader
+            .get_filename(file)
+            .map(|f| fs::canonicalize(&f).unwrap_or_else(|_| PathBuf::from(f)))
+            .unwrap_or_default();
+        if let Some(relative) = root.and_then(|root| path.strip_prefix(root).ok()) {
+            return SarifArtifactLocation {
+                uri: Self::encode_uri(&relative.to_string_lossy()),
+                uri_base_id: Some(SRCROOT),
+            };
+        }
+        let path = Self::encode_uri(&path.to_string_lossy());
+        let path = path.strip_prefix('/').unwrap_or(&path);
+        SarifArtifactLocation {
+            uri: format!("file:///{path}"),
+            uri_base_id: None,
+        }
+    }
+
+    /// Convert a range to a region.
+    ///
+    /// Lines and columns start at 1 in SARIF, and the end column is just
+    /// after the range, while ranges end on their last character.
+    fn region(range: &Range) -> SarifRegion {
+        let start_column = range.start.column + 1;
+        let mut end_column = range.end.column + 2;
+        if range.start.line == range.end.line {
+            end_column = end_column.max(start_column + 1);
+        }
+        SarifRegion {
+            start_line: range.start.line + 1,
+            start_column,
+            end_line: range.end.line + 1,
+            end_column,
+        }
+    }
+
+    /// Convert the diagnostics to a SARIF log.
+    fn log<T: FileReader>(&self, reader: &T) -> SarifLog {
+        let root = std::env::current_dir().and_then(fs::canonicalize).ok();
+        let location = |file: Uuid, range: &Range| SarifPhysicalLocation {
+            artifact_location: Self::artifact(reader, file, root.as_deref()),
+            region: Self::region(range),
+        };
+
+        let catalog = Manager::rule_catalog();
+        let mut rules: Vec<SarifRule> = Vec::new();
+        let mut results = Vec::new();
+        for item in &self.diagnostics {
+            let rule_index = match rules.iter().position(|rule| rule.id == item.code) {
+                Some(index) => index,
+                None => {
+                    let rule = catalog.iter().find(|rule| rule.id == item.code);
+                    rules.push(SarifRule {
+                        id: item.code.clone(),
+                        short_description: SarifMessage {
+                            text: rule.map_or_else(|| item.title.clone(), |r| r.title.to_string()),
+                        },
+                        help_uri: rule.map(RuleInfo::url),
+                    });
+                    rules.len() - 1
+                }
+            };
+            let related_locations = item
+                .related
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(id, related)| SarifLocation {
+                    id: Some(id),
+                    physical_location: location(related.file, &related.range),
+                    message: Some(SarifMessage {
+                        text: related.description.clone(),
+                    }),
+                })
+                .collect();
+            results.push(SarifResult {
+                rule_id: item.code.clone(),
+                rule_index,
+                level: Self::level(&item.level),
+                message: SarifMessage {
+                    text: item.description.clone(),
+                },
+                locations: vec![SarifLocation {
+                    id: None,
+                    physical_location: location(item.file, &item.range),
+                    message: None,
+                }],
+                related_locations,
+            });
+        }
+
+        let mut original_uri_base_ids = HashMap::new();
+        if let Some(root) = &root {
+            let root = Self::encode_uri(&root.to_string_lossy());
+            let root = root.trim_start_matches('/').trim_end_matches('/');
+            original_uri_base_ids.insert(
+                SRCROOT,
+                SarifArtifactLocation {
+                    uri: format!("file:///{root}/"),
+                    uri_base_id: None,
+                },
+            );
+        }
+
+        SarifLog {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "rva",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: env!("CARGO_PKG_REPOSITORY"),
+                        rules,
+                    },
+                },
+                original_uri_base_ids,
+                results,
+            }],
+        }
+    }
+}
+
+impl ErrorDisplay for SarifPrint {
+    fn display_errors<T: FileReader>(&mut self, reader: &T) {
+        let text = serde_json::to_string_pretty(&self.log(reader)).unwrap();
+        println!("{}", text);
+    }
+}
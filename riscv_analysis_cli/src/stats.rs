@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use riscv_analysis::cfg::Cfg;
+use riscv_analysis::parser::{Inst, RawToken, Register, Type};
+use serde::Serialize;
+
+/// Counts of the instructions, registers, and control flow of a program.
+///
+/// Instructions are counted as they are written in the source, so a
+/// pseudo-instruction like `li` counts once by its own mnemonic, even if it
+/// expands to more than one instruction.
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub instructions: usize,
+    pub functions: usize,
+    pub basic_blocks: usize,
+    /// Instructions by their type, like `Arith` or `Pseudo`.
+    pub by_type: BTreeMap<String, usize>,
+    /// Instructions by their mnemonic, like `addi` or `li`.
+    pub by_mnemonic: BTreeMap<String, usize>,
+    /// The number of instructions that read each register, other than
+    /// `x0`.
+    pub reads: BTreeMap<String, usize>,
+    /// The number of instructions that write each register, other than
+    /// `x0`.
+    pub writes: BTreeMap<String, usize>,
+}
+
+/// An instruction as it is written in the source, with the registers that
+/// all of its expansions read and write.
+struct SourceInstruction {
+    token: RawToken,
+    reads: HashSet<Register>,
+    writes: HashSet<Register>,
+}
+
+impl SourceInstruction {
+    /// Get the mnemonic that the instruction is written with.
+    fn mnemonic(&self) -> String {
+        self.token
+            .text
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+}
+
+/// Get the name of the type of an instruction.
+fn type_name(inst: &Type) -> &'static str {
+    match inst {
+        Type::Arith(_) => "Arith",
+        Type::IArith(_) => "IArith",
+        Type::UpperArith(_) => "UpperArith",
+        Type::Basic(_) => "Basic",
+        Type::JumpLink(_) => "JumpLink",
+        Type::JumpLinkR(_) => "JumpLinkR",
+        Type::Load(_) => "Load",
+        Type::Store(_) => "Store",
+        Type::Csr(_) => "Csr",
+        Type::CsrI(_) => "CsrI",
+        Type::Ignore(_) => "Ignore",
+        Type::Branch(_) => "Branch",
+        Type::Pseudo(_) => "Pseudo",
+        Type::FloatArith(_) => "FloatArith",
+    }
+}
+
+impl Stats {
+    /// Count the instructions and registers of a CFG.
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        let mut stats = Stats {
+            functions: cfg.functions().len(),
+            basic_blocks: cfg
+                .basic_blocks()
+                .iter()
+                .filter(|block| block.iter().any(|node| node.node().is_instruction()))
+                .count(),
+            ..Default::default()
+        };
+
+        for inst in Self::source_instructions(cfg) {
+            stats.instructions += 1;
+            let mnemonic = inst.mnemonic();
+            let kind = Inst::from_str(&mnemonic)
+                .map_or("Unknown", |parsed| type_name(&Type::from(&parsed)));
+            *stats.by_type.entry(kind.to_string()).or_default() += 1;
+            *stats.by_mnemonic.entry(mnemonic).or_default() += 1;
+            for reg in &inst.reads {
+                *stats.reads.entry(reg.to_string()).or_default() += 1;
+            }
+            for reg in &inst.writes {
+                *stats.writes.entry(reg.to_string()).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// Group the instruction nodes of a CFG by the source instruction that
+    /// they were expanded from.
+    ///
+    /// A register that is written by one expansion and read by a later one
+    /// is only counted as a write, as the source instruction does not read
+    /// it. Reads of `x0` are left out, as many pseudo-instructions expand to
+    /// an instruction that reads it.
+    fn source_instructions(cfg: &Cfg) -> Vec<SourceInstruction> {
+        let mut insts: Vec<SourceInstruction> = Vec::new();
+        for node in cfg.iter_source() {
+            let node = node.node();
+            if !node.is_instruction() {
+                continue;
+            }
+            let token = node.token();
+            let inst = match insts.last_mut() {
+                Some(last) if last.token == token => last,
+                _ => {
+                    insts.push(SourceInstruction {
+                        token,
+                        reads: HashSet::new(),
+                        writes: HashSet::new(),
+                    });
+                    insts.last_mut().expect("an instruction was just added")
+                }
+            };
+            let reads = node
+                .reads_from()
+                .into_iter()
+                .map(|reg| reg.data)
+                .filter(|reg| *reg != Register::X0 && !inst.writes.contains(reg))
+                .collect::<Vec<_>>();
+            inst.reads.extend(reads);
+            if let Some(reg) = node.stores_to().filter(|reg| reg.data != Register::X0) {
+                inst.writes.insert(reg.data);
+            }
+        }
+        insts
+    }
+
+    /// Print the counts as text, with the most common first.
+    pub fn print(&self) {
+        println!("Instructions: {}", self.instructions);
+        println!("Functions: {}", self.functions);
+        println!("Basic blocks: {}", self.basic_blocks);
+        Self::print_histogram("Instructions by type", &self.by_type);
+        Self::print_histogram("Instructions by mnemonic", &self.by_mnemonic);
+        Self::print_histogram("Registers read", &self.reads);
+        Self::print_histogram("Registers written", &self.writes);
+    }
+
+    fn print_histogram(title: &str, counts: &BTreeMap<String, usize>) {
+        println!("\n{title}:");
+        let mut counts = counts.iter().collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let width = counts.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, count) in counts {
+            println!("  {name:<width$}  {count}");
+        }
+    }
+
+    /// Print the counts as JSON.
+    pub fn print_json(&self) {
+        println!("{}", serde_json::to_string_pretty(self).unwrap());
+    }
+}
@@ -0,0 +1,27 @@
+main:
+    li      a0, 5
+    jal     is_even
+    li      a7, 10
+    ecall
+
+is_even:
+    addi    sp, sp, -4
+    sw      ra, 0(sp)
+    beqz    a0, even_done
+    addi    a0, a0, -1
+    jal     is_odd
+even_done:
+    lw      ra, 0(sp)
+    addi    sp, sp, 4
+    ret
+
+is_odd:
+    addi    sp, sp, -4
+    sw      ra, 0(sp)
+    beqz    a0, odd_done
+    addi    a0, a0, -1
+    jal     is_even
+odd_done:
+    lw      ra, 0(sp)
+    addi    sp, sp, 4
+    ret
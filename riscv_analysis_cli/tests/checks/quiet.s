@@ -0,0 +1,5 @@
+main:
+    sub     t0, t0, t0
+    li      t1, 4
+    li      a7, 10
+    ecall
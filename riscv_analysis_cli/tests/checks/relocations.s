@@ -0,0 +1,20 @@
+	.text
+	.globl	main
+	.extern	puts
+main:
+	addi	sp,sp,-16
+	sw	ra,12(sp)
+	lui	a0,%hi(msg)
+	addi	a0,a0,%lo(msg)
+	call	puts
+pcrel_hi0:
+	auipc	a1,%pcrel_hi(counter)
+	lw	a2,%pcrel_lo(pcrel_hi0)(a1)
+	lw	ra,12(sp)
+	addi	sp,sp,16
+	jr	ra
+	.data
+counter:
+	.word	3
+msg:
+	.string	"Hello"
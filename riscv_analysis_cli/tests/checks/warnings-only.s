@@ -0,0 +1,4 @@
+main:
+    li      t0, 4
+    li      a7, 10
+    ecall
@@ -1,3 +1,4 @@
+use riscv_analysis::parser::RVStringParser;
 use riscv_analysis_cli::wrapper::{DiagnosticTestCase, TestCase};
 
 use std::fs;
@@ -22,11 +23,11 @@ fn diagnostic_eq(actual: &DiagnosticTestCase, expected: &DiagnosticTestCase) ->
     let expected_path = file_to_path(expected.file.clone());
 
     // All other fields must be equal
-    return actual.title == expected.title
+    actual.title == expected.title
         && actual_path == expected_path
         && actual.description == expected.description
         && actual.level == expected.level
-        && actual.range == expected.range;
+        && actual.range == expected.range
 }
 
 fn output_eq(actual: TestCase, expected: TestCase) -> bool {
@@ -88,3 +89,479 @@ fn no_invalid_assign_for_ret() {
     let out = PathBuf::from("./checks/no-invalid-assign-for-ret.json");
     run_test(asm, out);
 }
+
+#[test]
+fn baseline_hides_known_diagnostics() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sample/unused-value.s");
+    let baseline = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("unused-value-baseline.json");
+
+    rva_bin()
+        .arg("lint")
+        .arg("--write-baseline")
+        .arg(&baseline)
+        .arg(&asm)
+        .assert()
+        .success();
+
+    let out = rva_bin()
+        .arg("lint")
+        .arg("--json")
+        .arg("--baseline")
+        .arg(&baseline)
+        .arg(&asm)
+        .output()
+        .unwrap();
+    let actual: TestCase = serde_json::from_str(&String::from_utf8(out.stdout).unwrap()).unwrap();
+    assert!(actual.diagnostics.is_empty());
+}
+
+#[test]
+fn quiet_hides_hints() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/quiet.s");
+    let levels = |quiet: bool| {
+        let mut bin = rva_bin();
+        let cmd = bin.arg("lint").arg("--json");
+        if quiet {
+            cmd.arg("--quiet");
+        }
+        let out = cmd.arg(&asm).output().unwrap();
+        let actual: TestCase =
+            serde_json::from_str(&String::from_utf8(out.stdout).unwrap()).unwrap();
+        actual
+            .diagnostics
+            .into_iter()
+            .map(|d| d.level)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(levels(false).iter().any(|level| level == "Hint"));
+    let quiet = levels(true);
+    assert!(!quiet.is_empty());
+    assert!(quiet.iter().all(|level| level == "Error" || level == "Warning"));
+}
+
+#[test]
+fn exit_code_reflects_diagnostics() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/warnings-only.s");
+    let lint = |args: &[&str]| rva_bin().arg("lint").args(args).arg(&asm).assert();
+
+    lint(&[]).success();
+    lint(&["--no-output"]).success();
+    lint(&["--deny-warnings"]).failure().code(1);
+    lint(&["--deny-warnings", "--no-output"]).failure().code(1);
+
+    // Only the levels after `--lint-level` are counted
+    lint(&["--lint-level", "dead-assignment=error"]).failure().code(1);
+    lint(&["--deny-warnings", "--lint-level", "dead-assignment=hint"]).success();
+}
+
+#[test]
+fn max_errors_truncates_sorted_output() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/quiet.s");
+    let lint = |args: &[&str]| {
+        let out = rva_bin()
+            .arg("lint")
+            .arg("--json")
+            .args(args)
+            .arg(&asm)
+            .output()
+            .unwrap();
+        let actual: TestCase =
+            serde_json::from_str(&String::from_utf8(out.stdout).unwrap()).unwrap();
+        let titles = actual
+            .diagnostics
+            .into_iter()
+            .map(|d| (d.range.start.line, d.range.start.column, d.title))
+            .collect::<Vec<_>>();
+        (titles, String::from_utf8(out.stderr).unwrap())
+    };
+
+    let (all, _) = lint(&[]);
+    assert!(all.len() > 2);
+    let mut sorted = all.clone();
+    sorted.sort_by_key(|(line, column, _)| (*line, *column));
+    assert_eq!(all, sorted);
+
+    let (first, footer) = lint(&["--max-errors", "2"]);
+    assert_eq!(first, all[..2]);
+    assert_eq!(footer.trim(), format!("…and {} more", all.len() - 2));
+}
+
+#[test]
+fn lint_level_changes_and_silences_lints() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sample/unused-value.s");
+    let levels = |lint_level: &str| {
+        let out = rva_bin()
+            .arg("lint")
+            .arg("--json")
+            .arg("--lint-level")
+            .arg(lint_level)
+            .arg(&asm)
+            .output()
+            .unwrap();
+        let actual: TestCase =
+            serde_json::from_str(&String::from_utf8(out.stdout).unwrap()).unwrap();
+        actual
+            .diagnostics
+            .into_iter()
+            .map(|d| d.level)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(levels("dead-assignment=warning"), vec!["Warning"]);
+    assert_eq!(levels("dead-assignment=error"), vec!["Error"]);
+    assert!(levels("dead-assignment=off").is_empty());
+}
+
+#[test]
+fn unknown_lint_level_is_rejected() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sample/unused-value.s");
+    rva_bin()
+        .arg("lint")
+        .arg("--lint-level")
+        .arg("not-a-lint=error")
+        .arg(&asm)
+        .assert()
+        .failure();
+    rva_bin()
+        .arg("lint")
+        .arg("--lint-level")
+        .arg("dead-assignment=loud")
+        .arg(&asm)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn dot_prints_cfg() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/quiet.s");
+    let out = rva_bin().arg("lint").arg("--dot").arg(&asm).output().unwrap();
+    let dot = String::from_utf8(out.stdout).unwrap();
+    assert!(dot.starts_with("digraph cfg {"));
+    assert!(dot.contains("sub t0 <- t0, t0"));
+    assert!(dot.trim_end().ends_with('}'));
+}
+
+#[test]
+fn interprocedural_dot_follows_calls() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/mutual-recursion.s");
+    let out = rva_bin()
+        .arg("lint")
+        .arg("--dot")
+        .arg("--interprocedural")
+        .arg(&asm)
+        .output()
+        .unwrap();
+    let dot = String::from_utf8(out.stdout).unwrap();
+    assert!(dot.starts_with("digraph cfg {"));
+    assert!(dot.contains("[style=dashed];"));
+    assert!(dot.trim_end().ends_with('}'));
+}
+
+#[test]
+fn interprocedural_requires_dot() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/mutual-recursion.s");
+    let out = rva_bin()
+        .arg("lint")
+        .arg("--interprocedural")
+        .arg(&asm)
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+}
+
+#[test]
+fn emit_keeps_relocations() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/relocations.s");
+    let out = rva_bin().arg("emit").arg(&asm).output().unwrap();
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(text.contains(".globl main\n"));
+    assert!(text.contains(".extern puts\n"));
+    assert!(text.contains("    lui a0, %hi(msg)\n"));
+    assert!(text.contains("    addi a0, a0, %lo(msg)\n"));
+    assert!(text.contains("    lw a2, %pcrel_lo(pcrel_hi0)(a1)\n"));
+}
+
+#[test]
+fn call_graph_prints_calls() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/mutual-recursion.s");
+    let out = rva_bin()
+        .arg("lint")
+        .arg("--call-graph")
+        .arg(&asm)
+        .output()
+        .unwrap();
+    let dot = String::from_utf8(out.stdout).unwrap();
+    assert!(dot.starts_with("digraph calls {"));
+    assert!(dot.contains("f0 [label=\"is_even\", style=filled, fillcolor=orange];"));
+    assert!(dot.contains("f0 -> f1;"));
+    assert!(dot.contains("f1 -> f0;"));
+    assert!(dot.trim_end().ends_with('}'));
+}
+
+#[test]
+fn dot_reg_names_prints_numeric_names() {
+    let asm = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/checks/quiet.s");
+    let out = rva_bin()
+        .arg("lint")
+        .arg("--dot")
+        .arg("--dot-reg-names")
+        .arg("numeric")
+        .arg(&asm)
+        .output()
+        .unwrap();
+    let dot = String::from_utf8(out.stdout).unwrap();
+    assert!(dot.contains("sub x5 <- x5, x5"));
+    assert!(!dot.contains("t0"));
+
+    rva_bin()
+        .arg("lint")
+        .arg("--dot")
+        .arg("--dot-reg-names")
+        .arg("names")
+        .arg(&asm)
+        .assert()
+        .failure();
+
+    // The names are only used in the graph
+    rva_bin()
+        .arg("lint")
+        .arg("--dot-reg-names")
+        .arg("numeric")
+        .arg(&asm)
+        .assert()
+        .failure();
+}
+
+const SAVED_FUNCTION: &str = "\
+main:
+    jal fn_a
+    li a7, 10
+    ecall
+fn_a:
+    addi sp, sp, -4
+    sw s0, 0(sp)
+    li s0, 1
+    li s1, 2
+    add a0, s0, s1
+    lw s0, 0(sp)
+    addi sp, sp, 4
+    ret
+";
+
+#[test]
+fn fix_all_applies_without_asking() {
+    let asm = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("fix-all.s");
+    fs::write(&asm, SAVED_FUNCTION).unwrap();
+
+    rva_bin()
+        .arg("fix")
+        .arg("--fix-all")
+        .arg("--remove-old")
+        .arg(&asm)
+        .arg("fn_a")
+        .assert()
+        .success();
+
+    let fixed = fs::read_to_string(&asm).unwrap();
+    let (nodes, errors) = RVStringParser::parse_from_text(&fixed);
+    assert!(errors.is_empty());
+    assert!(!nodes.is_empty());
+
+    // The old save and restore of s0 are replaced by the new ones
+    assert_eq!(fixed.matches("sw s0, 0(sp)").count(), 1);
+    assert_eq!(fixed.matches("lw s0, 0(sp)").count(), 1);
+    assert!(fixed.contains("sw s1, 4(sp)"));
+    let save = fixed.find("# save to stack").unwrap();
+    let restore = fixed.find("# restore from stack").unwrap();
+    assert!(fixed.find("fn_a:").unwrap() < save);
+    assert!(restore < fixed.find("    ret").unwrap());
+    assert!(fixed.contains("    addi sp, sp, -4\n    li s0, 1\n"));
+}
+
+#[test]
+fn dry_run_prints_diff() {
+    let asm = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dry-run.s");
+    fs::write(&asm, SAVED_FUNCTION).unwrap();
+
+    let out = rva_bin()
+        .arg("fix")
+        .arg("--dry-run")
+        .arg("--remove-old")
+        .arg(&asm)
+        .arg("fn_a")
+        .output()
+        .unwrap();
+    let diff = String::from_utf8(out.stdout).unwrap();
+    assert!(diff.starts_with("--- "));
+    assert!(diff.contains("\n-    sw s0, 0(sp)\n"));
+    assert!(diff.contains("\n+sw s1, 4(sp)\n"));
+    assert_eq!(fs::read_to_string(&asm).unwrap(), SAVED_FUNCTION);
+}
+
+#[test]
+fn self_include_is_reported() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("self-include");
+    fs::create_dir_all(&dir).unwrap();
+    let asm = dir.join("main.s");
+    fs::write(
+        &asm,
+        ".include \"./main.s\"\nmain:\n    li a7, 10\n    ecall\n",
+    )
+    .unwrap();
+
+    let out = rva_bin().arg("lint").arg("--json").arg(&asm).output().unwrap();
+    let actual: TestCase = serde_json::from_str(&String::from_utf8(out.stdout).unwrap()).unwrap();
+    let cycles = actual
+        .diagnostics
+        .iter()
+        .filter(|d| d.title == "Cyclic dependency")
+        .count();
+    assert_eq!(cycles, 1);
+}
+
+#[test]
+fn sarif_reports_rules_and_locations() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("sarif");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("main.s"),
+        "main:\n    li t1, 2\n    jal fn_a\n    li a7, 10\n    ecall\n.include \"lib.s\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("lib.s"), "fn_a:\n    li t0, 1\n    ret\n").unwrap();
+
+    let out = rva_bin()
+        .current_dir(&dir)
+        .arg("lint")
+        .arg("--sarif")
+        .arg("main.s")
+        .output()
+        .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "rva");
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["id"], "dead-assignment");
+    assert!(rules[0]["helpUri"]
+        .as_str()
+        .unwrap()
+        .ends_with("docs/lints.md#dead-assignment"));
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    let location = |result: &serde_json::Value| {
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        (
+            location["artifactLocation"]["uri"].as_str().unwrap().to_string(),
+            location["region"]["startLine"].as_u64().unwrap(),
+            location["region"]["startColumn"].as_u64().unwrap(),
+            location["region"]["endColumn"].as_u64().unwrap(),
+        )
+    };
+    for result in results {
+        assert_eq!(result["ruleId"], "dead-assignment");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "warning");
+        assert!(result["message"]["text"].is_string());
+    }
+    // Diagnostics of different files are in no particular order
+    let mut locations = results.iter().map(location).collect::<Vec<_>>();
+    locations.sort();
+    assert_eq!(
+        locations,
+        vec![
+            ("lib.s".to_string(), 2, 8, 10),
+            ("main.s".to_string(), 2, 8, 10)
+        ]
+    );
+}
+
+#[test]
+fn json_includes_rule_and_fixes() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("json-fixes");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("main.s"),
+        "main:\n    jal fn_a\n    li a7, 10\n    ecall\n.include \"lib.s\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("lib.s"),
+        "fn_a:\n    li s0, 1\n    mv a0, s0\n    ret\n",
+    )
+    .unwrap();
+
+    let out = rva_bin()
+        .current_dir(&dir)
+        .arg("lint")
+        .arg("--json")
+        .arg("main.s")
+        .output()
+        .unwrap();
+    let actual: TestCase = serde_json::from_slice(&out.stdout).unwrap();
+    let diagnostic = actual
+        .diagnostics
+        .iter()
+        .find(|d| d.rule_id.as_deref() == Some("overwrite-callee-saved-register"))
+        .unwrap();
+    assert_eq!(diagnostic.fixes.len(), 2);
+
+    // The edits are in the file of the function, not the file that was linted
+    let lib = fs::canonicalize(dir.join("lib.s")).unwrap();
+    for fix in &diagnostic.fixes {
+        assert_eq!(file_to_path(fix.file.clone()), lib);
+        assert_eq!(fix.range.start, fix.range.end);
+    }
+    let save = &diagnostic.fixes[0];
+    assert_eq!(save.range.start.line, 1);
+    assert!(save.replacement.contains("# save to stack"));
+    assert!(save.replacement.contains("sw s0, 0(sp)"));
+    let restore = &diagnostic.fixes[1];
+    assert_eq!(restore.range.start.line, 3);
+    assert!(restore.replacement.contains("lw s0, 0(sp)"));
+    assert!(actual.diagnostics.iter().all(|d| d.rule_id.is_some()));
+}
+
+#[test]
+fn stats_counts_source_instructions() {
+    let asm = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("stats.s");
+    fs::write(
+        &asm,
+        "main:\n    li a0, 5\n    li t0, 100000\n    jal square\n    mv a1, a0\n    \
+         li a7, 10\n    ecall\nsquare:\n    mul a0, a0, a0\n    ret\n",
+    )
+    .unwrap();
+
+    let out = rva_bin()
+        .arg("stats")
+        .arg("--json")
+        .arg(&asm)
+        .output()
+        .unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(stats["instructions"], 8);
+    assert_eq!(stats["functions"], 1);
+    assert_eq!(stats["basic_blocks"], 2);
+
+    // The `li` of a large immediate expands to two instructions, but is
+    // counted once by the mnemonic it is written with
+    assert_eq!(stats["by_mnemonic"]["li"], 3);
+    assert!(stats["by_mnemonic"].get("lui").is_none());
+    assert!(stats["by_mnemonic"].get("addi").is_none());
+    assert_eq!(stats["by_type"]["Pseudo"], 5);
+    assert_eq!(stats["by_type"]["Arith"], 1);
+    assert_eq!(stats["by_type"]["JumpLink"], 1);
+    assert_eq!(stats["by_type"]["Basic"], 1);
+
+    assert_eq!(stats["reads"]["a0"], 2);
+    assert_eq!(stats["reads"]["ra"], 1);
+    assert!(stats["reads"].get("t0").is_none());
+    assert!(stats["reads"].get("zero").is_none());
+    assert_eq!(stats["writes"]["a0"], 2);
+    assert_eq!(stats["writes"]["t0"], 1);
+}
@@ -0,0 +1,51 @@
+//! Compare getting the diagnostics of a document in a new session with
+//! getting them again after an edit, and when nothing changed.
+//!
+//! Run with `cargo bench -p riscv_analysis_lsp`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use riscv_analysis::parser::RVDocument;
+use riscv_analysis_lsp::{DiagnosticSession, LSPRVDiagnostic};
+
+const ITERATIONS: u32 = 200;
+
+fn time(name: &str, mut f: impl FnMut(u32) -> Vec<LSPRVDiagnostic>) {
+    // Warm up before timing
+    black_box(f(0));
+
+    let mut total = Duration::ZERO;
+    for i in 1..=ITERATIONS {
+        let start = Instant::now();
+        black_box(f(i));
+        total += start.elapsed();
+    }
+    println!("{name:<24} {:>10.2?} per call", total / ITERATIONS);
+}
+
+fn main() {
+    let text = include_str!("../../riscv_analysis_cli/resources/test/loop_check/code.s");
+    let docs = |text: String| {
+        vec![RVDocument {
+            uri: "file:///code.s".to_string(),
+            text,
+            tab_width: 1,
+        }]
+    };
+
+    time("new session", |_| {
+        DiagnosticSession::new().diagnostics(docs(text.to_string()))
+    });
+
+    // Each edit adds a comment so that the document is analyzed again
+    let mut session = DiagnosticSession::new();
+    time("edited document", |i| {
+        session.diagnostics(docs(format!("{text}# edit {i}\n")))
+    });
+
+    let mut session = DiagnosticSession::new();
+    time("unchanged document", |_| {
+        session.diagnostics(docs(text.to_string()))
+    });
+}
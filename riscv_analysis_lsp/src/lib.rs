@@ -1,8 +1,18 @@
 mod lsp;
-use lsp::{LSPDiag, LSPFileReader, LSPRVDiagnostic, LSPRVSingleDiagnostic, RVCompletionItem};
-use lsp_types::Diagnostic;
+pub use lsp::LSPRVDiagnostic;
+use lsp::{
+    definition_at, document_symbols, hover_at, references_at, semantic_tokens,
+    semantic_tokens_legend, stack_fix_actions, LSPDiag, LSPFileReader, LSPRVSingleDiagnostic,
+    RVCompletionItem,
+};
+use lsp_types::{
+    CodeAction, Diagnostic, DocumentSymbol, Hover, Location, Position, Range, SemanticTokens,
+};
+use riscv_analysis::cfg::Cfg;
 use riscv_analysis::parser::{CanGetURIString, DirectiveType, ParserNode, RVDocument, RVParser};
+use riscv_analysis::passes::ManagerConfiguration;
 use riscv_analysis::reader::FileReader;
+use riscv_analysis::{analyze, analyze_with_parser};
 use serde_wasm_bindgen::to_value;
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
@@ -60,61 +70,833 @@ where
     }
 }
 
+/// The diagnostics of a document that is not included by another, with
+/// every file that it includes.
+struct AnalyzedDocument {
+    /// The document and the files it includes, if they are known.
+    files: Option<HashSet<Uuid>>,
+    diagnostics: Vec<LSPRVSingleDiagnostic>,
+}
+
+/// Diagnostics of the open documents that are kept between edits.
+///
+/// Documents keep their UUIDs while they are open, and the parser is
+/// incremental, so a file is only parsed again if it or a file it includes
+/// changed. A document that is not included by another is only analyzed
+/// again if one of its files changed.
+///
+/// The editor creates one session and keeps it while the server runs.
+#[wasm_bindgen]
+pub struct DiagnosticSession {
+    parser: RVParser<LSPFileReader>,
+    /// The URIs of the files that each document includes.
+    imports: HashMap<Uuid, HashSet<String>>,
+    /// The diagnostics of each document that is not included by another.
+    results: HashMap<Uuid, AnalyzedDocument>,
+}
+
+impl Default for DiagnosticSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl DiagnosticSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        let mut parser = RVParser::new(LSPFileReader::new(Vec::new()));
+        parser.set_incremental(true);
+        DiagnosticSession {
+            parser,
+            imports: HashMap::new(),
+            results: HashMap::new(),
+        }
+    }
+
+    /// Get the diagnostics of every open document after an edit, see
+    /// [`DiagnosticSession::diagnostics`].
+    pub fn get_diagnostics(&mut self, docs: JsValue) -> JsValue {
+        let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+        serde_wasm_bindgen::to_value(&self.diagnostics(docs)).unwrap()
+    }
+}
+
+impl DiagnosticSession {
+    /// Get the diagnostics of every open document after an edit.
+    pub fn diagnostics(&mut self, docs: Vec<RVDocument>) -> Vec<LSPRVDiagnostic> {
+        let changed = self.parser.reader.update(docs);
+
+        // Only the documents that changed are scanned for imports again
+        self.imports.retain(|uuid, _| !changed.contains(uuid));
+        let docs = self
+            .parser
+            .reader
+            .file_uris
+            .iter()
+            .map(|(uuid, doc)| (*uuid, doc.uri.clone()))
+            .collect::<Vec<_>>();
+        for (uuid, uri) in &docs {
+            if !self.imports.contains_key(uuid) {
+                let imports = self.parser.get_imports(uri);
+                self.imports.insert(*uuid, imports);
+            }
+        }
+        let imported = self
+            .imports
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        // Documents that are included by another are analyzed with it, and
+        // the others are analyzed again only if one of their files changed.
+        let roots = docs
+            .iter()
+            .filter(|(_, uri)| !imported.contains(uri))
+            .collect::<Vec<_>>();
+        self.results.retain(|uuid, result| {
+            roots.iter().any(|(root, _)| root == uuid)
+                && result
+                    .files
+                    .as_ref()
+                    .is_some_and(|files| files.is_disjoint(&changed))
+        });
+        for (uuid, uri) in roots {
+            if self.results.contains_key(uuid) {
+                continue;
+            }
+            let result =
+                analyze_with_parser(&mut self.parser, uri, &ManagerConfiguration::default());
+            let diagnostics = result
+                .diagnostics
+                .iter()
+                .map(|f| f.to_lsp_diag(&result.reader))
+                .collect();
+            let files = self
+                .parser
+                .cached_includes(*uuid)
+                .map(|files| files.into_iter().chain([*uuid]).collect());
+            self.results
+                .insert(*uuid, AnalyzedDocument { files, diagnostics });
+        }
+
+        // insert empty vec for each file
+        let mut diag_map = docs
+            .into_iter()
+            .map(|(_, uri)| (uri, Vec::new()))
+            .collect::<HashMap<_, _>>();
+        for err in self.results.values().flat_map(|r| &r.diagnostics) {
+            let diags = diag_map.entry(err.uri.clone()).or_insert(Vec::new());
+            diags.push(err.diagnostic.clone());
+        }
+
+        diag_map
+            .into_iter()
+            .map(|(uri, diagnostics)| LSPRVDiagnostic { uri, diagnostics })
+            .collect()
+    }
+}
+
+/// Get the URIs of the documents that are imported by another document.
+fn imported_uris(docs: &[RVDocument]) -> HashSet<String> {
+    docs.iter()
+        .flat_map(|doc| RVParser::new(LSPFileReader::new(docs.to_vec())).get_imports(&doc.uri))
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn riscv_get_code_actions(docs: JsValue, uri: String, range: JsValue) -> JsValue {
+    let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+    let range: Range = serde_wasm_bindgen::from_value(range).unwrap();
+    serde_wasm_bindgen::to_value(&get_code_actions(docs, &uri, range)).unwrap()
+}
+
+/// Get the code actions for a range in a document.
+///
+/// If the range overlaps a function that does not restore a register that
+/// it must save, this offers to insert the saves and restores around the
+/// function.
+pub fn get_code_actions(docs: Vec<RVDocument>, uri: &str, range: Range) -> Vec<CodeAction> {
+    let config = ManagerConfiguration {
+        run_lints: false,
+        ..Default::default()
+    };
+    let result = analyze((LSPFileReader::new(docs), uri), &config);
+    match result.cfg {
+        Some(cfg) => stack_fix_actions(&cfg, &result.reader, uri, range),
+        None => Vec::new(),
+    }
+}
+
 #[wasm_bindgen]
-pub fn riscv_get_diagnostics(docs: JsValue) -> JsValue {
-    // convert docs to Vec<LSPRVDocument>
+pub fn riscv_get_hover(docs: JsValue, uri: String, position: JsValue) -> JsValue {
     let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+    let position: Position = serde_wasm_bindgen::from_value(position).unwrap();
+    serde_wasm_bindgen::to_value(&get_hover(docs, &uri, position)).unwrap()
+}
 
-    // parse and lex all files, without imports and collect that info
+/// Get the hover for a position in a document.
+///
+/// Instructions, registers and labels have a hover. Anything else, or a
+/// document that cannot be analyzed, has none.
+pub fn get_hover(docs: Vec<RVDocument>, uri: &str, position: Position) -> Option<Hover> {
+    let config = ManagerConfiguration {
+        run_lints: false,
+        ..Default::default()
+    };
+    let result = analyze((LSPFileReader::new(docs), uri), &config);
+    hover_at(result.cfg.as_ref()?, &result.reader, uri, position)
+}
 
-    let imported = docs
-        .clone()
-        .into_iter()
-        .map(|doc| RVParser::new(LSPFileReader::new(docs.clone())).get_imports(&doc.uri))
-        .reduce(|mut x, y| {
-            x.extend(y);
-            x
+#[wasm_bindgen]
+pub fn riscv_get_definition(docs: JsValue, uri: String, position: JsValue) -> JsValue {
+    let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+    let position: Position = serde_wasm_bindgen::from_value(position).unwrap();
+    serde_wasm_bindgen::to_value(&get_definition(docs, &uri, position)).unwrap()
+}
+
+/// Get the location where the label at a position in a document is
+/// defined.
+///
+/// Every document that is not imported by another is analyzed with the
+/// files it includes, so a label used in an included file is found too. If
+/// the label is defined in more than one place, the first is used.
+pub fn get_definition(docs: Vec<RVDocument>, uri: &str, position: Position) -> Option<Location> {
+    find_in_roots(&docs, |cfg, reader| {
+        definition_at(cfg, reader, uri, position)
+    })
+}
+
+#[wasm_bindgen]
+pub fn riscv_get_references(
+    docs: JsValue,
+    uri: String,
+    position: JsValue,
+    include_declaration: bool,
+) -> JsValue {
+    let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+    let position: Position = serde_wasm_bindgen::from_value(position).unwrap();
+    serde_wasm_bindgen::to_value(&get_references(docs, &uri, position, include_declaration))
+        .unwrap()
+}
+
+/// Get the locations that reference the label or register at a position in
+/// a document.
+///
+/// Labels are referenced across every document that is included with it.
+/// Registers are only referenced in the function that the position is in.
+pub fn get_references(
+    docs: Vec<RVDocument>,
+    uri: &str,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<Location> {
+    find_in_roots(&docs, |cfg, reader| {
+        let references = references_at(cfg, reader, uri, position, include_declaration);
+        (!references.is_empty()).then_some(references)
+    })
+    .unwrap_or_default()
+}
+
+#[wasm_bindgen]
+pub fn riscv_get_document_symbols(docs: JsValue, uri: String) -> JsValue {
+    let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+    serde_wasm_bindgen::to_value(&get_document_symbols(docs, &uri)).unwrap()
+}
+
+/// Get the outline of a document, with functions as containers of their
+/// labels and data.
+pub fn get_document_symbols(docs: Vec<RVDocument>, uri: &str) -> Vec<DocumentSymbol> {
+    find_in_roots(&docs, |cfg, reader| {
+        let symbols = document_symbols(cfg, reader, uri);
+        (!symbols.is_empty()).then_some(symbols)
+    })
+    .unwrap_or_default()
+}
+
+#[wasm_bindgen]
+pub fn riscv_get_semantic_tokens_legend() -> JsValue {
+    serde_wasm_bindgen::to_value(&semantic_tokens_legend()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn riscv_get_semantic_tokens(docs: JsValue, uri: String) -> JsValue {
+    let docs: Vec<RVDocument> = serde_wasm_bindgen::from_value(docs).unwrap();
+    serde_wasm_bindgen::to_value(&get_semantic_tokens(docs, &uri)).unwrap()
+}
+
+/// Get the semantic tokens of a document, with the types of
+/// [`riscv_get_semantic_tokens_legend`].
+pub fn get_semantic_tokens(docs: Vec<RVDocument>, uri: &str) -> SemanticTokens {
+    semantic_tokens(&LSPFileReader::new(docs), uri)
+}
+
+/// Analyze, without lints, every document that is not imported by another,
+/// and get the first result that `f` finds in its CFG.
+///
+/// Each document is analyzed with the files it includes, so a position in
+/// an included file is found from the document that includes it.
+fn find_in_roots<T>(
+    docs: &[RVDocument],
+    f: impl Fn(&Cfg, &LSPFileReader) -> Option<T>,
+) -> Option<T> {
+    let config = ManagerConfiguration {
+        run_lints: false,
+        ..Default::default()
+    };
+    let imported = imported_uris(docs);
+    docs.iter()
+        .filter(|doc| !imported.contains(&doc.uri))
+        .find_map(|root| {
+            let result = analyze(
+                (LSPFileReader::new(docs.to_vec()), root.uri.as_str()),
+                &config,
+            );
+            f(result.cfg.as_ref()?, &result.reader)
         })
-        .unwrap_or_default();
-
-    // filter out files that are imported by anything
-    let to_parse = docs
-        .clone()
-        .into_iter()
-        .filter(|x| !imported.contains(&x.uri));
-
-    let errs = to_parse
-        .flat_map(|f| {
-            let mut parser = RVParser::new(LSPFileReader::new(docs.clone()));
-            let items = parser.run(&f.uri);
-            items
-                .into_iter()
-                .map(|f| f.to_lsp_diag(&parser))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::SymbolKind;
+
+    fn doc(uri: &str, text: &str) -> RVDocument {
+        RVDocument {
+            uri: uri.to_string(),
+            text: text.to_string(),
+            tab_width: 1,
+        }
+    }
+
+    /// An empty range at a position, like a cursor.
+    fn cursor(line: u32, character: u32) -> Range {
+        let position = Position::new(line, character);
+        Range::new(position, position)
+    }
+
+    #[test]
+    fn stack_fix_action_for_missing_saves() {
+        let text = "main:\n    jal fn_a\n    li a7, 10\n    ecall\nfn_a:\n    li s0, 1\n    mv a0, s0\n    ret\n";
+        let docs = vec![doc("file:///main.s", text)];
+
+        // On the label or in the body of the function
+        for range in [cursor(4, 2), cursor(5, 4)] {
+            let actions = get_code_actions(docs.clone(), "file:///main.s", range);
+            assert_eq!(actions.len(), 1);
+            assert_eq!(actions[0].kind, Some(lsp_types::CodeActionKind::QUICKFIX));
+            let changes = actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap();
+            let edits = &changes[&url::Url::parse("file:///main.s").unwrap()];
+            assert_eq!(edits.len(), 2);
+            assert!(edits[0].new_text.contains("sw s0, 0(sp)"));
+            assert_eq!(edits[0].range, cursor(5, 0));
+            assert!(edits[1].new_text.contains("lw s0, 0(sp)"));
+            assert_eq!(edits[1].range, cursor(7, 0));
+        }
+
+        // A range that starts in main and ends in the function overlaps it
+        let range = Range::new(Position::new(1, 0), Position::new(4, 0));
+        let actions = get_code_actions(docs.clone(), "file:///main.s", range);
+        assert_eq!(actions.len(), 1);
+
+        // Not in a function that saves registers
+        assert!(get_code_actions(docs.clone(), "file:///main.s", cursor(0, 2)).is_empty());
+        assert!(get_code_actions(docs, "file:///other.s", cursor(4, 2)).is_empty());
+    }
+
+    #[test]
+    fn one_stack_fix_action_for_function_with_two_labels() {
+        let text = "main:\n    jal fn_a\n    jal fn_b\n    li a7, 10\n    ecall\nfn_a:\nfn_b:\n    li s0, 1\n    mv a0, s0\n    ret\n";
+        let docs = vec![doc("file:///main.s", text)];
+
+        let range = Range::new(Position::new(5, 0), Position::new(9, 0));
+        assert_eq!(get_code_actions(docs, "file:///main.s", range).len(), 1);
+    }
+
+    #[test]
+    fn no_stack_fix_action_when_registers_are_restored() {
+        let text = "main:\n    jal fn_a\n    li a7, 10\n    ecall\nfn_a:\n    addi sp, sp, -4\n    sw s0, 0(sp)\n    li s0, 1\n    mv a0, s0\n    lw s0, 0(sp)\n    addi sp, sp, 4\n    ret\n";
+        let docs = vec![doc("file:///main.s", text)];
+
+        let range = Range::new(Position::new(0, 0), Position::new(11, 0));
+        assert!(get_code_actions(docs, "file:///main.s", range).is_empty());
+    }
+
+    /// Get the diagnostics of documents opened in a new session.
+    fn get_diagnostics(docs: Vec<RVDocument>) -> Vec<LSPRVDiagnostic> {
+        DiagnosticSession::new().diagnostics(docs)
+    }
+
+    #[test]
+    fn single_document_is_reported() {
+        let docs = vec![doc(
+            "file:///main.s",
+            include_str!("../../riscv_analysis_cli/resources/test/treg/code.s"),
+        )];
+
+        let diagnostics = get_diagnostics(docs);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].uri, "file:///main.s");
+        assert!(!diagnostics[0].diagnostics.is_empty());
+    }
+
+    #[test]
+    fn documents_with_imports_are_all_reported() {
+        let docs = vec![
+            doc(
+                "file:///main.s",
+                ".include \"lib.s\"\nmain:\n    jal fn_a\n    li a7, 10\n    ecall\n",
+            ),
+            doc("file:///lib.s", "fn_a:\n    ret\n"),
+        ];
+
+        let mut uris = get_diagnostics(docs)
+            .into_iter()
+            .map(|d| d.uri)
+            .collect::<Vec<_>>();
+        uris.sort();
+        assert_eq!(uris, vec!["file:///lib.s", "file:///main.s"]);
+    }
+
+    #[test]
+    fn jump_table_in_imported_document() {
+        let docs = vec![
+            doc(
+                "file:///main.s",
+                ".include \"table.s\"\nmain:\n    slli t1, a0, 2\n    la t0, table\n    \
+                 add t0, t0, t1\n    lw t0, 0(t0)\n    jr t0\ncase_a:\n    li a0, 1\n    \
+                 j end\ncase_b:\n    li a0, 2\nend:\n    li a7, 10\n    ecall\n",
+            ),
+            doc(
+                "file:///table.s",
+                ".data\ntable:\n    .word case_a, case_b\n.text\n",
+            ),
+        ];
+
+        let codes = get_diagnostics(docs)
+            .into_iter()
+            .flat_map(|d| d.diagnostics)
+            .filter_map(|d| match d.code {
+                Some(lsp_types::NumberOrString::String(code)) => Some(code),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!codes.is_empty());
+        assert!(!codes.iter().any(|c| c == "unreachable-code"));
+        assert!(!codes.iter().any(|c| c == "unknown-jump-target"));
+    }
+
+    fn messages(diagnostics: &[LSPRVDiagnostic], uri: &str) -> Vec<String> {
+        let diagnostics = diagnostics.iter().find(|d| d.uri == uri).unwrap();
+        let mut messages = diagnostics
+            .diagnostics
+            .iter()
+            .map(|d| d.message.clone())
+            .collect::<Vec<_>>();
+        messages.sort();
+        messages
+    }
+
+    #[test]
+    fn session_updates_documents_that_include_an_edited_file() {
+        let main = "main:\n    jal fn_a\n    li a7, 10\n    ecall\n.include \"lib.s\"\n";
+        let other = "main:\n    li t0, 1\n    li a7, 10\n    ecall\n";
+        let docs = |lib: &str| {
+            vec![
+                doc("file:///main.s", main),
+                doc("file:///lib.s", lib),
+                doc("file:///other.s", other),
+            ]
+        };
+        let mut session = DiagnosticSession::new();
+
+        let first = session.diagnostics(docs("fn_a:\n    ret\n"));
+        assert_eq!(first.len(), 3);
+        assert!(messages(&first, "file:///lib.s").is_empty());
+        assert!(!messages(&first, "file:///other.s").is_empty());
+        let again = session.diagnostics(docs("fn_a:\n    ret\n"));
+        assert_eq!(
+            messages(&again, "file:///other.s"),
+            messages(&first, "file:///other.s")
+        );
+
+        // The result of a document whose files did not change is reused as
+        // is, which is seen by changing the stored result.
+        let other_uuid = *session
+            .parser
+            .reader
+            .file_uris
+            .iter()
+            .find(|(_, d)| d.uri == "file:///other.s")
+            .unwrap()
+            .0;
+        session
+            .results
+            .get_mut(&other_uuid)
+            .unwrap()
+            .diagnostics
+            .clear();
+
+        // The edit of the included file is found by analyzing the document
+        // that includes it
+        let edited = session.diagnostics(docs("fn_a:\n    li s0, 1\n    ret\n"));
+        assert!(!messages(&edited, "file:///lib.s").is_empty());
+        assert!(messages(&edited, "file:///other.s").is_empty());
+        assert_eq!(
+            messages(&edited, "file:///main.s"),
+            messages(&first, "file:///main.s")
+        );
+    }
+
+    #[test]
+    fn session_matches_new_session() {
+        let docs = |lib: &str| {
+            vec![
+                doc(
+                    "file:///main.s",
+                    ".include \"lib.s\"\nmain:\n    jal fn_a\n    li a7, 10\n    ecall\n",
+                ),
+                doc("file:///lib.s", lib),
+            ]
+        };
+
+        let mut session = DiagnosticSession::new();
+        session.diagnostics(docs("fn_a:\n    ret\n"));
+        let incremental = session.diagnostics(docs("fn_a:\n    li s0, 1\n    ret\n"));
+        let full = get_diagnostics(docs("fn_a:\n    li s0, 1\n    ret\n"));
+        for uri in ["file:///main.s", "file:///lib.s"] {
+            assert_eq!(messages(&incremental, uri), messages(&full, uri));
+        }
+        assert!(!messages(&full, "file:///lib.s").is_empty());
+    }
+
+    #[test]
+    fn tab_width_sets_diagnostic_columns() {
+        let text = "main:\n\tli t0, 1\n\tli t1, 2\n\tecall\n\tli a7, 10\n\tecall\n";
+        let columns = |tab_width| {
+            let docs = vec![RVDocument {
+                tab_width,
+                ..doc("file:///main.s", text)
+            }];
+            get_diagnostics(docs)[0]
+                .diagnostics
+                .iter()
+                .map(|d| (d.range.start.line, d.range.start.character))
                 .collect::<Vec<_>>()
-        })
-        .collect::<Vec<LSPRVSingleDiagnostic>>();
-
-    // insert empty vec for each file
-    let mut diag_map = docs
-        .iter()
-        .map(|x| (x.uri.clone(), Vec::new()))
-        .collect::<HashMap<_, _>>();
-
-    // collect all diagnostics by fil
-    for err in errs {
-        let uri = err.uri;
-        let diag = err.diagnostic;
-        let diags = diag_map.entry(uri).or_insert(Vec::new());
-        diags.push(diag);
-    }
-
-    let errs = diag_map
-        .iter()
-        .map(|(uri, diagnostics)| LSPRVDiagnostic {
-            uri: uri.clone(),
-            diagnostics: diagnostics.clone(),
-        })
-        .collect::<Vec<_>>();
+        };
+
+        assert_eq!(columns(1), vec![(1, 4), (2, 4), (3, 1)]);
+        assert_eq!(columns(4), vec![(1, 7), (2, 7), (3, 4)]);
+    }
+
+    fn hover_text(text: &str, position: Position) -> Option<String> {
+        let hover = get_hover(
+            vec![doc("file:///main.s", text)],
+            "file:///main.s",
+            position,
+        )?;
+        match hover.contents {
+            lsp_types::HoverContents::Markup(markup) => Some(markup.value),
+            _ => None,
+        }
+    }
+
+    const HOVER_TEXT: &str = "main:\n    addi a0, zero, 1  # one\n    jal fn_a\n    li a7, 10\n    ecall\nfn_a:\n    ret\n";
+
+    #[test]
+    fn hover_on_instruction() {
+        let hover = hover_text(HOVER_TEXT, Position::new(1, 5)).unwrap();
+        assert!(hover.contains("addi rd, rs1, imm"));
+        assert!(hover.contains("rd = rs1 + imm"));
+
+        let hover = hover_text(HOVER_TEXT, Position::new(3, 4)).unwrap();
+        assert!(hover.contains("Pseudo-instruction: rd = imm"));
+
+        let hover = hover_text(HOVER_TEXT, Position::new(6, 5)).unwrap();
+        assert!(hover.contains("```riscv\nret\n```"));
+    }
 
-    serde_wasm_bindgen::to_value(&errs).unwrap()
+    #[test]
+    fn hover_on_register() {
+        let hover = hover_text(HOVER_TEXT, Position::new(1, 9)).unwrap();
+        assert!(hover.contains("Register `a0` (a0, x10)"));
+        assert!(hover.contains("Argument and return value"));
+
+        let hover = hover_text(HOVER_TEXT, Position::new(1, 14)).unwrap();
+        assert!(hover.contains("Hard-wired zero"));
+    }
+
+    #[test]
+    fn hover_on_label() {
+        let hover = hover_text(HOVER_TEXT, Position::new(2, 9)).unwrap();
+        assert!(hover.contains("Function `fn_a`"));
+        assert!(hover.contains("- line 3 of file:///main.s"));
+
+        let hover = hover_text(HOVER_TEXT, Position::new(0, 1)).unwrap();
+        assert_eq!(hover, "Label `main`");
+    }
+
+    #[test]
+    fn no_hover_on_whitespace_or_comment() {
+        assert_eq!(hover_text(HOVER_TEXT, Position::new(1, 1)), None);
+        assert_eq!(hover_text(HOVER_TEXT, Position::new(1, 25)), None);
+    }
+
+    #[test]
+    fn definition_of_branch_target() {
+        let text = "main:\n    li t0, 3\nloop:\n    addi t0, t0, -1\n    bnez t0, loop\n    \
+                    li a7, 10\n    ecall\n";
+        let docs = vec![doc("file:///main.s", text)];
+        let location =
+            get_definition(docs.clone(), "file:///main.s", Position::new(4, 14)).unwrap();
+        assert_eq!(location.uri.as_str(), "file:///main.s");
+        assert_eq!(location.range.start, Position::new(2, 0));
+
+        // The definition is its own definition
+        let location = get_definition(docs.clone(), "file:///main.s", Position::new(2, 2)).unwrap();
+        assert_eq!(location.range.start, Position::new(2, 0));
+
+        assert_eq!(
+            get_definition(docs, "file:///main.s", Position::new(4, 9)),
+            None
+        );
+    }
+
+    #[test]
+    fn definition_in_included_document() {
+        let docs = vec![
+            doc(
+                "file:///main.s",
+                ".include \"lib.s\"\nmain:\n    call fn_a\n    li a7, 10\n    ecall\n",
+            ),
+            doc("file:///lib.s", "nop\nfn_a:\n    j fn_b\nfn_b:\n    ret\n"),
+        ];
+        let location =
+            get_definition(docs.clone(), "file:///main.s", Position::new(2, 10)).unwrap();
+        assert_eq!(location.uri.as_str(), "file:///lib.s");
+        assert_eq!(location.range.start, Position::new(1, 0));
+
+        // Labels used in the included document are found too
+        let location = get_definition(docs.clone(), "file:///lib.s", Position::new(2, 7)).unwrap();
+        assert_eq!(location.uri.as_str(), "file:///lib.s");
+        assert_eq!(location.range.start, Position::new(3, 0));
+    }
+
+    #[test]
+    fn no_definition_of_undefined_label() {
+        let text = "main:\n    j nowhere\n    li a7, 10\n    ecall\n";
+        let docs = vec![doc("file:///main.s", text)];
+        assert_eq!(
+            get_definition(docs, "file:///main.s", Position::new(1, 8)),
+            None
+        );
+    }
+
+    fn reference_starts(locations: &[Location]) -> Vec<(&str, u32, u32)> {
+        locations
+            .iter()
+            .map(|l| (l.uri.as_str(), l.range.start.line, l.range.start.character))
+            .collect()
+    }
+
+    #[test]
+    fn references_of_label_across_documents() {
+        let docs = vec![
+            doc(
+                "file:///main.s",
+                "main:\n    call fn_a\n    la t0, fn_a\n    li a7, 10\n    ecall\n\
+                 .include \"lib.s\"\n",
+            ),
+            doc(
+                "file:///lib.s",
+                "fn_a:\n    beqz a0, fn_b\nfn_b:\n    ret\n.data\ntable: .word fn_a\n",
+            ),
+        ];
+
+        let references =
+            get_references(docs.clone(), "file:///main.s", Position::new(1, 10), false);
+        assert_eq!(
+            reference_starts(&references),
+            vec![
+                ("file:///lib.s", 5, 13),
+                ("file:///main.s", 1, 9),
+                ("file:///main.s", 2, 11)
+            ]
+        );
+
+        // The definition is included first, from the included document
+        let references = get_references(docs, "file:///lib.s", Position::new(0, 1), true);
+        assert_eq!(
+            reference_starts(&references),
+            vec![
+                ("file:///lib.s", 0, 0),
+                ("file:///lib.s", 5, 13),
+                ("file:///main.s", 1, 9),
+                ("file:///main.s", 2, 11)
+            ]
+        );
+    }
+
+    #[test]
+    fn references_of_register_are_in_function() {
+        let text = "main:\n    li a0, 1\n    call fn_a\n    mv a1, a0\n    li a7, 10\n    \
+                    ecall\nfn_a:\n    addi a0, a0, 1\n    ret\n";
+        let docs = vec![doc("file:///main.s", text)];
+
+        let references =
+            get_references(docs.clone(), "file:///main.s", Position::new(7, 14), false);
+        assert_eq!(
+            reference_starts(&references),
+            vec![("file:///main.s", 7, 9), ("file:///main.s", 7, 13)]
+        );
+
+        let references = get_references(docs, "file:///main.s", Position::new(1, 8), true);
+        assert_eq!(
+            reference_starts(&references),
+            vec![("file:///main.s", 1, 7), ("file:///main.s", 3, 11)]
+        );
+    }
+
+    #[test]
+    fn document_symbols_of_two_functions() {
+        let text = "main:\n    li a0, 1\n    jal fn_a\n    jal fn_b\n    li a7, 10\n    ecall\n\
+                    fn_a:\n    li t0, 3\nloop:\n    addi t0, t0, -1\n    bnez t0, loop\n    ret\n\
+                    fn_b:\n    la a0, msg\n    ret\n.data\nmsg: .string \"hi\"\n";
+        let symbols = get_document_symbols(vec![doc("file:///main.s", text)], "file:///main.s");
+
+        let outline = symbols
+            .iter()
+            .map(|s| {
+                let children = s
+                    .children
+                    .iter()
+                    .flatten()
+                    .map(|c| (c.name.as_str(), c.kind, c.range.start.line))
+                    .collect::<Vec<_>>();
+                (
+                    s.name.as_str(),
+                    s.kind,
+                    s.range.start.line,
+                    s.range.end.line,
+                    children,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outline,
+            vec![
+                ("main", SymbolKind::CONSTANT, 0, 0, vec![]),
+                (
+                    "fn_a",
+                    SymbolKind::FUNCTION,
+                    6,
+                    11,
+                    vec![("loop", SymbolKind::CONSTANT, 8)]
+                ),
+                ("fn_b", SymbolKind::FUNCTION, 12, 14, vec![]),
+                ("msg", SymbolKind::VARIABLE, 16, 16, vec![]),
+            ]
+        );
+
+        // The name of a function is selected, and the function spans its
+        // body
+        let fn_a = &symbols[1];
+        assert_eq!(fn_a.selection_range.start, Position::new(6, 0));
+        assert_eq!(fn_a.selection_range.end.line, 6);
+        assert!(fn_a.range.end.character > 0);
+    }
+
+    #[test]
+    fn semantic_tokens_of_snippet() {
+        let text = "loop:\n    li t0, 5\n    addi t0, t0, -1\n    bnez t0, loop  # again\n\
+                    .data\nmsg: .string \"hi\"\n";
+        let tokens = get_semantic_tokens(vec![doc("file:///main.s", text)], "file:///main.s");
+
+        // Decode the positions, which are relative to the token before
+        let legend = semantic_tokens_legend();
+        let mut line = 0;
+        let mut start = 0;
+        let decoded = tokens
+            .data
+            .iter()
+            .map(|token| {
+                if token.delta_line > 0 {
+                    start = 0;
+                }
+                line += token.delta_line;
+                start += token.delta_start;
+                let kind = legend.token_types[token.token_type as usize].as_str();
+                let modifiers = token.token_modifiers_bitset;
+                (line, start, token.length, kind, modifiers)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decoded,
+            vec![
+                (0, 0, 5, "label", 1),
+                (1, 4, 2, "macro", 0),
+                (1, 7, 2, "variable", 0),
+                (1, 11, 1, "number", 0),
+                (2, 4, 4, "keyword", 0),
+                (2, 9, 2, "variable", 0),
+                (2, 13, 2, "variable", 0),
+                (2, 17, 2, "number", 0),
+                (3, 4, 4, "macro", 0),
+                (3, 9, 2, "variable", 0),
+                (3, 13, 4, "label", 0),
+                (3, 19, 7, "comment", 0),
+                (4, 0, 5, "decorator", 0),
+                (5, 0, 4, "label", 1),
+                (5, 5, 7, "decorator", 0),
+                (5, 13, 4, "string", 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_count_utf16_code_units() {
+        // The emoji is two UTF-16 code units, and the accent is a character
+        // of its own that combines with the `e` before it
+        let text =
+            "main:  # café 😀\n    li a7, 10\n    ecall\n.data\nmsg: .string \"e\u{301}😀\", 5\n";
+        let diagnostics = get_diagnostics(vec![doc("file:///main.s", text)]);
+        let range = diagnostics[0]
+            .diagnostics
+            .iter()
+            .find(|d| d.range.start.line == 4)
+            .unwrap()
+            .range;
+        assert_eq!(range.start, Position::new(4, 21));
+        let line = text
+            .lines()
+            .nth(4)
+            .unwrap()
+            .encode_utf16()
+            .collect::<Vec<_>>();
+        assert_eq!(String::from_utf16(&line[21..22]).unwrap(), "5");
+
+        // Comments after the range do not move it
+        let text = "main:\n    li t0, 1  # 😀😀\n    li a7, 10\n    ecall\n";
+        let diagnostics = get_diagnostics(vec![doc("file:///main.s", text)]);
+        assert_eq!(
+            diagnostics[0].diagnostics[0].range.start,
+            Position::new(1, 7)
+        );
+    }
+
+    #[test]
+    fn diagnostics_link_to_their_rule() {
+        let text = "main:\n\tli t0, 1\n\tli a7, 10\n\tecall\n";
+        let diagnostics = get_diagnostics(vec![doc("file:///main.s", text)]);
+        let diagnostic = diagnostics[0]
+            .diagnostics
+            .iter()
+            .find(|d| d.code == Some(lsp_types::NumberOrString::String("dead-assignment".into())))
+            .unwrap();
+        assert_eq!(
+            diagnostic.code_description.as_ref().unwrap().href.fragment(),
+            Some("dead-assignment")
+        );
+    }
 }
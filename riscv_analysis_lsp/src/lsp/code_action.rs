@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use lsp_types::{CodeAction, CodeActionKind, Range, TextEdit, WorkspaceEdit};
+use riscv_analysis::cfg::{Cfg, Function};
+use riscv_analysis::fix::{fix_stack, Manipulation};
+use riscv_analysis::passes::DiagnosticLocation;
+use riscv_analysis::reader::FileReader;
+use url::Url;
+
+use super::LSPFileReader;
+
+/// Check if two LSP ranges overlap.
+///
+/// The ends are included, so an empty range at the end of another range,
+/// like a cursor right after a label, overlaps it.
+fn overlaps(a: &Range, b: &Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// Check if a range of a document overlaps the label or the instructions of
+/// a function.
+fn function_overlaps(func: &Function, reader: &LSPFileReader, uri: &str, range: &Range) -> bool {
+    let label_ranges = func
+        .labels()
+        .into_iter()
+        .map(|label| (label.file, label.pos))
+        .collect::<Vec<_>>();
+    let node_ranges = func
+        .nodes()
+        .iter()
+        .map(|node| (node.node().file(), node.node().range()))
+        .collect::<Vec<_>>();
+    label_ranges
+        .into_iter()
+        .chain(node_ranges)
+        .any(|(file, pos)| {
+            reader.get_filename(file).as_deref() == Some(uri)
+                && overlaps(&reader.to_lsp_range(file, &pos), range)
+        })
+}
+
+/// Get the code actions that save and restore the registers of the functions
+/// that a range of a document overlaps.
+///
+/// An action is only offered if the function does not restore a register
+/// that it must save, which is reported by the
+/// `overwrite-callee-saved-register` lint. The prologue is inserted at the
+/// start of the function and the epilogue before each of its returns. Each
+/// edit is in the file of the code it is inserted before, which may not be
+/// the document of the range.
+pub fn stack_fix_actions(
+    cfg: &Cfg,
+    reader: &LSPFileReader,
+    uri: &str,
+    range: Range,
+) -> Vec<CodeAction> {
+    cfg.unique_functions()
+        .into_iter()
+        .filter(|func| function_overlaps(func, reader, uri, &range))
+        .filter(|func| {
+            !(func.usage_summary().callee_saved_not_restored & func.to_save()).is_empty()
+        })
+        .filter_map(|func| {
+            let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+            for edit in fix_stack(&func).iter().filter_map(Manipulation::text_edit) {
+                let url = Url::parse(&reader.get_filename(edit.file)?).ok()?;
+                changes.entry(url).or_default().push(TextEdit {
+                    range: reader.to_lsp_range(edit.file, &edit.range),
+                    new_text: edit.replacement,
+                });
+            }
+            Some(CodeAction {
+                title: format!("Save and restore registers of {}", func.name()),
+                kind: Some(CodeActionKind::QUICKFIX),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
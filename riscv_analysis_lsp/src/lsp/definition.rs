@@ -0,0 +1,47 @@
+use lsp_types::{Location, Position};
+use riscv_analysis::cfg::Cfg;
+use riscv_analysis::parser::{LabelString, Token, With};
+use uuid::Uuid;
+
+use super::LSPFileReader;
+
+/// Get the location where the label at a position in a document is
+/// defined.
+///
+/// The label can be a use, like the target of a branch, or the definition
+/// itself. Labels of code and of data are found, in any file that was
+/// parsed with the CFG. If the document was not parsed with the CFG, or the
+/// label is not defined, there is no definition.
+pub fn definition_at(
+    cfg: &Cfg,
+    reader: &LSPFileReader,
+    uri: &str,
+    position: Position,
+) -> Option<Location> {
+    let info = reader.token_at(uri, position)?;
+    if !is_parsed(cfg, info.file) {
+        return None;
+    }
+    let name = match info.token {
+        Token::Label(name) | Token::Symbol(name) => name,
+        _ => return None,
+    };
+
+    let label = label_definition(cfg, &name)?;
+    reader.to_lsp_location(label.file, &label.pos)
+}
+
+/// Check if a file was parsed with the CFG.
+pub(super) fn is_parsed(cfg: &Cfg, file: Uuid) -> bool {
+    cfg.nodes()
+        .iter()
+        .any(|node| node.node().token().file == file)
+}
+
+/// Get the label where a label of code or of data is defined.
+pub(super) fn label_definition(cfg: &Cfg, name: &str) -> Option<With<LabelString>> {
+    match cfg.label_node_map.get(name) {
+        Some(node) => node.labels().into_iter().find(|label| label.data.0 == name),
+        None => Some(cfg.data_symbol(&LabelString(name.into()))?.label().clone()),
+    }
+}
@@ -0,0 +1,364 @@
+use std::rc::Rc;
+use std::str::FromStr;
+
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use riscv_analysis::cfg::Cfg;
+use riscv_analysis::parser::{FloatArithType, Inst, PseudoType, RegSets, Register, Token, Type};
+use riscv_analysis::reader::FileReader;
+
+use super::LSPFileReader;
+
+/// Get the hover of the token at a position in a document.
+///
+/// Instructions show their operands and what they do, registers show their
+/// ABI name and role in the calling convention, and labels show whether they
+/// are a function and where that function is called. Anything else, like
+/// whitespace or a comment, has no hover.
+pub fn hover_at(cfg: &Cfg, reader: &LSPFileReader, uri: &str, position: Position) -> Option<Hover> {
+    let info = reader.token_at(uri, position)?;
+
+    let value = match &info.token {
+        Token::Label(name) => label_hover(cfg, reader, name)?,
+        Token::Symbol(name) => {
+            if let Ok(reg) = Register::from_str(name) {
+                register_hover(reg)
+            } else if let Ok(inst) = Inst::from_str(&name.to_lowercase()) {
+                instruction_hover(inst)
+            } else {
+                label_hover(cfg, reader, name)?
+            }
+        }
+        _ => return None,
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(reader.to_lsp_range(info.file, &info.pos)),
+    })
+}
+
+/// Describe an instruction by its operands and what it does.
+fn instruction_hover(inst: Inst) -> String {
+    let signature = match operand_format(&inst) {
+        "" => inst.to_string(),
+        operands => format!("{inst} {operands}"),
+    };
+    let kind = if matches!(Type::from(&inst), Type::Pseudo(_)) {
+        "Pseudo-instruction"
+    } else {
+        "Instruction"
+    };
+    format!("```riscv\n{signature}\n```\n{kind}: {}", semantics(&inst))
+}
+
+/// Describe a register by its names and its role in the calling convention.
+fn register_hover(reg: Register) -> String {
+    let role = match reg {
+        Register::X0 => "Hard-wired zero. Writes to it are ignored.",
+        Register::X1 => "Return address. A function that makes calls must save it.",
+        Register::X2 => "Stack pointer. Functions must restore it before returning.",
+        Register::X3 => "Global pointer. Functions do not change it.",
+        Register::X4 => "Thread pointer. Functions do not change it.",
+        Register::X8 => "Saved register and frame pointer. Functions must restore it.",
+        Register::X10 | Register::X11 | Register::F10 | Register::F11 => {
+            "Argument and return value. Calls do not keep it."
+        }
+        _ if RegSets::saved().contains(&reg) => "Saved register. Functions must restore it.",
+        _ if RegSets::argument().contains(&reg) => "Argument. Calls do not keep it.",
+        _ => "Temporary. Calls do not keep it.",
+    };
+    let mut names = reg.all_representations().into_iter().collect::<Vec<_>>();
+    names.sort();
+    format!("Register `{reg}` ({})\n\n{role}", names.join(", "))
+}
+
+/// Describe a label by whether it is a function and where it is called.
+fn label_hover(cfg: &Cfg, reader: &LSPFileReader, name: &str) -> Option<String> {
+    if !cfg.label_node_map.contains_key(name) {
+        return None;
+    }
+    let Some(func) = cfg
+        .functions()
+        .into_iter()
+        .find(|(label, _)| label.data.0 == name)
+        .map(|(_, func)| func)
+    else {
+        return Some(format!("Label `{name}`"));
+    };
+
+    let mut calls = cfg
+        .iter()
+        .filter_map(|node| node.calls_to(cfg))
+        .filter(|(called, _)| Rc::ptr_eq(called, &func))
+        .map(|(_, call_site)| (call_site.file, call_site.pos.start.line + 1))
+        .collect::<Vec<_>>();
+    calls.sort();
+    calls.dedup();
+    let calls = if calls.is_empty() {
+        "It is not called.".to_string()
+    } else {
+        let sites = calls
+            .into_iter()
+            .map(|(file, line)| match reader.get_filename(file) {
+                Some(filename) => format!("- line {line} of {filename}"),
+                None => format!("- line {line}"),
+            })
+            .collect::<Vec<_>>();
+        format!("It is called from:\n{}", sites.join("\n"))
+    };
+    Some(format!("Function `{name}`\n\n{calls}"))
+}
+
+/// Get the operands of an instruction as they are written.
+fn operand_format(inst: &Inst) -> &'static str {
+    match Type::from(inst) {
+        Type::Arith(_) => "rd, rs1, rs2",
+        Type::IArith(_) => "rd, rs1, imm",
+        Type::UpperArith(_) => "rd, imm",
+        Type::Basic(_) | Type::Ignore(_) => "",
+        Type::JumpLink(_) => "rd, label",
+        Type::JumpLinkR(_) => "rd, rs1, imm",
+        Type::Load(_) => "rd, imm(rs1)",
+        Type::Store(_) => "rs2, imm(rs1)",
+        Type::Csr(_) => "rd, csr, rs1",
+        Type::CsrI(_) => "rd, csr, imm",
+        Type::Branch(_) => "rs1, rs2, label",
+        Type::FloatArith(inst) => match inst.source_count() {
+            3 => "rd, rs1, rs2, rs3",
+            2 => "rd, rs1, rs2",
+            _ => "rd, rs1",
+        },
+        Type::Pseudo(inst) => match inst {
+            PseudoType::Nop | PseudoType::Ret => "",
+            PseudoType::J | PseudoType::B | PseudoType::Call | PseudoType::Tail => "label",
+            PseudoType::Jr => "rs1",
+            PseudoType::Bgt | PseudoType::Ble | PseudoType::Bgtu | PseudoType::Bleu => {
+                "rs1, rs2, label"
+            }
+            PseudoType::Beqz
+            | PseudoType::Bnez
+            | PseudoType::Bltz
+            | PseudoType::Bgez
+            | PseudoType::Bgtz
+            | PseudoType::Blez => "rs1, label",
+            PseudoType::La => "rd, label",
+            PseudoType::Li => "rd, imm",
+            PseudoType::Csrr => "rd, csr",
+            PseudoType::Csrc | PseudoType::Csrs | PseudoType::Csrw => "rs1, csr",
+            PseudoType::Csrci | PseudoType::Csrsi | PseudoType::Csrwi => "csr, imm",
+            PseudoType::Mv
+            | PseudoType::Neg
+            | PseudoType::Negw
+            | PseudoType::SextW
+            | PseudoType::ZextB
+            | PseudoType::Not
+            | PseudoType::Seqz
+            | PseudoType::Snez
+            | PseudoType::Sgtz
+            | PseudoType::Sltz
+            | PseudoType::Sgez
+            | PseudoType::FmvS
+            | PseudoType::FnegS
+            | PseudoType::FabsS
+            | PseudoType::FmvD
+            | PseudoType::FnegD
+            | PseudoType::FabsD => "rd, rs1",
+        },
+    }
+}
+
+/// Get a one-line description of what an instruction does.
+#[allow(clippy::too_many_lines)]
+fn semantics(inst: &Inst) -> String {
+    if let Type::FloatArith(inst) = Type::from(inst) {
+        return float_semantics(inst);
+    }
+    let text = match inst {
+        Inst::Ret => "Return to the caller, by jumping to `ra`.",
+        Inst::Ebreak => "Stop in the debugger.",
+        Inst::Ecall => "Ask the environment for the system call in `a7`.",
+        Inst::Uret => "Return from a user trap handler to `uepc`.",
+        Inst::Nop => "Do nothing.",
+        Inst::Add => "rd = rs1 + rs2",
+        Inst::Addw => "rd = rs1 + rs2, on the lower 32 bits, sign-extended",
+        Inst::Sub => "rd = rs1 - rs2",
+        Inst::Subw => "rd = rs1 - rs2, on the lower 32 bits, sign-extended",
+        Inst::And => "rd = rs1 & rs2",
+        Inst::Or => "rd = rs1 | rs2",
+        Inst::Xor => "rd = rs1 ^ rs2",
+        Inst::Sll => "rd = rs1 << rs2",
+        Inst::Sllw => "rd = rs1 << rs2, on the lower 32 bits, sign-extended",
+        Inst::Srl => "rd = rs1 >> rs2, filling with zeros",
+        Inst::Srlw => "rd = rs1 >> rs2, on the lower 32 bits, filling with zeros",
+        Inst::Sra => "rd = rs1 >> rs2, filling with the sign bit",
+        Inst::Sraw => "rd = rs1 >> rs2, on the lower 32 bits, filling with the sign bit",
+        Inst::Slt => "rd = 1 if rs1 < rs2 as signed values, otherwise 0",
+        Inst::Sltu => "rd = 1 if rs1 < rs2 as unsigned values, otherwise 0",
+        Inst::Mul => "rd = rs1 * rs2, the lower bits of the product",
+        Inst::Mulh => "rd = the upper bits of rs1 * rs2, as signed values",
+        Inst::Mulhsu => "rd = the upper bits of rs1 * rs2, with rs1 signed and rs2 unsigned",
+        Inst::Mulhu => "rd = the upper bits of rs1 * rs2, as unsigned values",
+        Inst::Div => "rd = rs1 / rs2, as signed values",
+        Inst::Divu => "rd = rs1 / rs2, as unsigned values",
+        Inst::Divw => "rd = rs1 / rs2, on the lower 32 bits as signed values",
+        Inst::Rem => "rd = rs1 % rs2, as signed values",
+        Inst::Remu => "rd = rs1 % rs2, as unsigned values",
+        Inst::Remw => "rd = rs1 % rs2, on the lower 32 bits as signed values",
+        Inst::Remuw => "rd = rs1 % rs2, on the lower 32 bits as unsigned values",
+        Inst::Beq => "Branch to label if rs1 == rs2.",
+        Inst::Bne => "Branch to label if rs1 != rs2.",
+        Inst::Blt => "Branch to label if rs1 < rs2, as signed values.",
+        Inst::Bge => "Branch to label if rs1 >= rs2, as signed values.",
+        Inst::Bltu => "Branch to label if rs1 < rs2, as unsigned values.",
+        Inst::Bgeu => "Branch to label if rs1 >= rs2, as unsigned values.",
+        Inst::Bgt => "Branch to label if rs1 > rs2, as signed values.",
+        Inst::Ble => "Branch to label if rs1 <= rs2, as signed values.",
+        Inst::Bgtu => "Branch to label if rs1 > rs2, as unsigned values.",
+        Inst::Bleu => "Branch to label if rs1 <= rs2, as unsigned values.",
+        Inst::Beqz => "Branch to label if rs1 == 0.",
+        Inst::Bnez => "Branch to label if rs1 != 0.",
+        Inst::Bltz => "Branch to label if rs1 < 0.",
+        Inst::Bgez => "Branch to label if rs1 >= 0.",
+        Inst::Bgtz => "Branch to label if rs1 > 0.",
+        Inst::Blez => "Branch to label if rs1 <= 0.",
+        Inst::Addi => "rd = rs1 + imm",
+        Inst::Addiw => "rd = rs1 + imm, on the lower 32 bits, sign-extended",
+        Inst::Andi => "rd = rs1 & imm",
+        Inst::Ori => "rd = rs1 | imm",
+        Inst::Xori => "rd = rs1 ^ imm",
+        Inst::Slli => "rd = rs1 << imm",
+        Inst::Slliw => "rd = rs1 << imm, on the lower 32 bits, sign-extended",
+        Inst::Srli => "rd = rs1 >> imm, filling with zeros",
+        Inst::Srliw => "rd = rs1 >> imm, on the lower 32 bits, filling with zeros",
+        Inst::Srai => "rd = rs1 >> imm, filling with the sign bit",
+        Inst::Sraiw => "rd = rs1 >> imm, on the lower 32 bits, filling with the sign bit",
+        Inst::Slti => "rd = 1 if rs1 < imm as signed values, otherwise 0",
+        Inst::Sltiu => "rd = 1 if rs1 < imm as unsigned values, otherwise 0",
+        Inst::Lui => "rd = imm << 12",
+        Inst::Auipc => "rd = pc + (imm << 12)",
+        Inst::Lb => "Load a sign-extended byte: rd = memory[rs1 + imm]",
+        Inst::Lbu => "Load a zero-extended byte: rd = memory[rs1 + imm]",
+        Inst::Lh => "Load a sign-extended half word: rd = memory[rs1 + imm]",
+        Inst::Lhu => "Load a zero-extended half word: rd = memory[rs1 + imm]",
+        Inst::Lw => "Load a sign-extended word: rd = memory[rs1 + imm]",
+        Inst::Lwu => "Load a zero-extended word: rd = memory[rs1 + imm]",
+        Inst::Ld => "Load a double word: rd = memory[rs1 + imm]",
+        Inst::Flw => "Load a single-precision float: rd = memory[rs1 + imm]",
+        Inst::Fld => "Load a double-precision float: rd = memory[rs1 + imm]",
+        Inst::Sb => "Store the lower byte: memory[rs1 + imm] = rs2",
+        Inst::Sh => "Store the lower half word: memory[rs1 + imm] = rs2",
+        Inst::Sw => "Store the lower word: memory[rs1 + imm] = rs2",
+        Inst::Sd => "Store a double word: memory[rs1 + imm] = rs2",
+        Inst::Fsw => "Store a single-precision float: memory[rs1 + imm] = rs2",
+        Inst::Fsd => "Store a double-precision float: memory[rs1 + imm] = rs2",
+        Inst::Csrrw => "rd = csr, then csr = rs1",
+        Inst::Csrrs => "rd = csr, then set the bits of rs1 in csr",
+        Inst::Csrrc => "rd = csr, then clear the bits of rs1 in csr",
+        Inst::Csrrwi => "rd = csr, then csr = imm",
+        Inst::Csrrsi => "rd = csr, then set the bits of imm in csr",
+        Inst::Csrrci => "rd = csr, then clear the bits of imm in csr",
+        Inst::Csrr => "rd = csr",
+        Inst::Csrw => "csr = rs1",
+        Inst::Csrs => "Set the bits of rs1 in csr.",
+        Inst::Csrc => "Clear the bits of rs1 in csr.",
+        Inst::Csrwi => "csr = imm",
+        Inst::Csrsi => "Set the bits of imm in csr.",
+        Inst::Csrci => "Clear the bits of imm in csr.",
+        Inst::Fence => "Order memory accesses of other harts and devices.",
+        Inst::Fencei => "Order writes to instruction memory with instruction fetches.",
+        Inst::Jal => "rd = pc + 4, then jump to label",
+        Inst::Jalr => "rd = pc + 4, then jump to rs1 + imm",
+        Inst::J | Inst::B => "Jump to label.",
+        Inst::Jr => "Jump to the address in rs1.",
+        Inst::Call => "Call the function at label, setting `ra` to return here.",
+        Inst::Tail => "Jump to the function at label, which returns to the caller of this one.",
+        Inst::La => "rd = the address of label",
+        Inst::Li => "rd = imm",
+        Inst::Mv => "rd = rs1",
+        Inst::Neg => "rd = -rs1",
+        Inst::Negw => "rd = -rs1, on the lower 32 bits, sign-extended",
+        Inst::SextW => "rd = the lower 32 bits of rs1, sign-extended",
+        Inst::ZextB => "rd = the lower byte of rs1, zero-extended",
+        Inst::Not => "rd = ~rs1",
+        Inst::Seqz => "rd = 1 if rs1 == 0, otherwise 0",
+        Inst::Snez => "rd = 1 if rs1 != 0, otherwise 0",
+        Inst::Sltz => "rd = 1 if rs1 < 0, otherwise 0",
+        Inst::Sgez => "rd = 1 if rs1 >= 0, otherwise 0",
+        Inst::Sgtz => "rd = 1 if rs1 > 0, otherwise 0",
+        Inst::FmvS | Inst::FmvD => "rd = rs1",
+        Inst::FnegS | Inst::FnegD => "rd = -rs1",
+        Inst::FabsS | Inst::FabsD => "rd = |rs1|",
+        // The other floating-point instructions are described above
+        _ => "",
+    };
+    text.to_string()
+}
+
+/// Get a one-line description of what a floating-point instruction does.
+fn float_semantics(inst: FloatArithType) -> String {
+    let name = inst.to_string();
+    let precision = if name.ends_with(".s") {
+        "single-precision"
+    } else {
+        "double-precision"
+    };
+    let text = match inst {
+        FloatArithType::FaddS | FloatArithType::FaddD => "rd = rs1 + rs2",
+        FloatArithType::FsubS | FloatArithType::FsubD => "rd = rs1 - rs2",
+        FloatArithType::FmulS | FloatArithType::FmulD => "rd = rs1 * rs2",
+        FloatArithType::FdivS | FloatArithType::FdivD => "rd = rs1 / rs2",
+        FloatArithType::FsqrtS | FloatArithType::FsqrtD => "rd = sqrt(rs1)",
+        FloatArithType::FminS | FloatArithType::FminD => "rd = min(rs1, rs2)",
+        FloatArithType::FmaxS | FloatArithType::FmaxD => "rd = max(rs1, rs2)",
+        FloatArithType::FsgnjS | FloatArithType::FsgnjD => "rd = rs1 with the sign of rs2",
+        FloatArithType::FsgnjnS | FloatArithType::FsgnjnD => {
+            "rd = rs1 with the opposite sign of rs2"
+        }
+        FloatArithType::FsgnjxS | FloatArithType::FsgnjxD => {
+            "rd = rs1 with the sign of rs1 xor the sign of rs2"
+        }
+        FloatArithType::FmaddS | FloatArithType::FmaddD => "rd = rs1 * rs2 + rs3",
+        FloatArithType::FmsubS | FloatArithType::FmsubD => "rd = rs1 * rs2 - rs3",
+        FloatArithType::FnmaddS | FloatArithType::FnmaddD => "rd = -(rs1 * rs2) - rs3",
+        FloatArithType::FnmsubS | FloatArithType::FnmsubD => "rd = -(rs1 * rs2) + rs3",
+        FloatArithType::FeqS | FloatArithType::FeqD => "rd = 1 if rs1 == rs2, otherwise 0",
+        FloatArithType::FltS | FloatArithType::FltD => "rd = 1 if rs1 < rs2, otherwise 0",
+        FloatArithType::FleS | FloatArithType::FleD => "rd = 1 if rs1 <= rs2, otherwise 0",
+        FloatArithType::FclassS | FloatArithType::FclassD => {
+            "rd = a mask of the class of rs1, like zero or NaN"
+        }
+        FloatArithType::FmvXW | FloatArithType::FmvXD => {
+            "rd = the bits of the float in rs1, unchanged"
+        }
+        FloatArithType::FmvWX | FloatArithType::FmvDX => {
+            "rd = the bits of the integer in rs1, unchanged"
+        }
+        _ => return conversion_semantics(&name),
+    };
+    match inst {
+        FloatArithType::FmvXW
+        | FloatArithType::FmvWX
+        | FloatArithType::FmvXD
+        | FloatArithType::FmvDX => text.to_string(),
+        _ => format!("{text}, as {precision} floats"),
+    }
+}
+
+/// Get a one-line description of a conversion, like `fcvt.w.s`, from its
+/// name, which is written as the type it converts to and then from.
+fn conversion_semantics(name: &str) -> String {
+    let kind = |suffix| match suffix {
+        "w" => "a signed word",
+        "wu" => "an unsigned word",
+        "l" => "a signed double word",
+        "lu" => "an unsigned double word",
+        "s" => "a single-precision float",
+        _ => "a double-precision float",
+    };
+    let mut parts = name.split('.').skip(1);
+    let to = parts.next().unwrap_or_default();
+    let from = parts.next().unwrap_or_default();
+    format!("rd = rs1, converted from {} to {}", kind(from), kind(to))
+}
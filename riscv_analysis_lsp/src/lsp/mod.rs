@@ -1,37 +1,68 @@
 // Type conversions for LSP
 
 use lsp_types::{
-    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range,
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+    NumberOrString, Position, Range,
+};
+use riscv_analysis::parser::{
+    CanGetURIString, Info, Lexer, Position as MyPosition, RVDocument, Range as MyRange,
 };
-use riscv_analysis::parser::{CanGetURIString, RVDocument, RVParser, Range as MyRange};
 use riscv_analysis::passes::DiagnosticItem;
+use riscv_analysis::passes::Manager;
 use riscv_analysis::passes::SeverityLevel;
 use riscv_analysis::reader::{FileReader, FileReaderError};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 mod completion;
 pub use completion::*;
+
+mod code_action;
+pub use code_action::*;
+
+mod hover;
+pub use hover::*;
+
+mod definition;
+pub use definition::*;
+
+mod references;
+pub use references::*;
+
+mod symbols;
+pub use symbols::*;
+
+mod semantic_tokens;
+pub use semantic_tokens::*;
+
 use serde::{Deserialize, Serialize};
 use url::Url;
 use uuid::Uuid;
 
-trait RangeInto {
-    fn to_range(&self) -> Range;
-}
-
-impl RangeInto for MyRange {
-    fn to_range(&self) -> Range {
-        lsp_types::Range {
-            start: Position {
-                line: self.start.line.try_into().unwrap_or(0),
-                character: self.start.column.try_into().unwrap_or(0),
-            },
-            end: Position {
-                line: self.end.line.try_into().unwrap_or(0),
-                character: self.end.column.try_into().unwrap_or(0),
-            },
+/// Convert a column of a line to the number of UTF-16 code units before it.
+///
+/// Columns count characters, with a tab moving to the next multiple of the
+/// tab width. LSP counts UTF-16 code units, so a character outside of the
+/// Basic Multilingual Plane, like an emoji, takes two. Tabs keep their
+/// width, so that the columns still match the tab width of the document.
+fn utf16_column(line: &str, column: usize, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+    let mut col = 0;
+    let mut units = 0;
+    for ch in line.chars() {
+        if col >= column {
+            break;
+        }
+        if ch == '\t' {
+            let next = (col / tab_width + 1) * tab_width;
+            units += next - col;
+            col = next;
+        } else {
+            units += ch.len_utf16();
+            col += 1;
         }
     }
+    // Columns past the end of the line, like the newline, are one unit each
+    units + column.saturating_sub(col)
 }
 
 trait WarningInto {
@@ -50,21 +81,24 @@ impl WarningInto for SeverityLevel {
 }
 
 pub trait LSPDiag {
-    fn to_lsp_diag(&self, parser: &RVParser<LSPFileReader>) -> LSPRVSingleDiagnostic;
+    fn to_lsp_diag(&self, reader: &LSPFileReader) -> LSPRVSingleDiagnostic;
 }
 
 impl LSPDiag for DiagnosticItem {
-    fn to_lsp_diag(&self, parser: &RVParser<LSPFileReader>) -> LSPRVSingleDiagnostic {
+    fn to_lsp_diag(&self, reader: &LSPFileReader) -> LSPRVSingleDiagnostic {
         LSPRVSingleDiagnostic {
-            uri: parser
-                .reader
+            uri: reader
                 .get_filename(self.file)
                 .unwrap_or_default(), // Empty string by default
             diagnostic: Diagnostic {
-                range: self.range.to_range(),
+                range: reader.to_lsp_range(self.file, &self.range),
                 severity: Some(self.level.clone().to_severity()),
-                code: None,
-                code_description: None,
+                code: Some(NumberOrString::String(self.code.clone())),
+                code_description: Manager::rule_catalog()
+                    .into_iter()
+                    .find(|rule| rule.id == self.code)
+                    .and_then(|rule| Url::parse(&rule.url()).ok())
+                    .map(|href| CodeDescription { href }),
                 source: None,
                 message: self.long_description.clone(),
                 related_information: self.related.clone().map(|f| {
@@ -72,11 +106,11 @@ impl LSPDiag for DiagnosticItem {
                         .map(|f1| DiagnosticRelatedInformation {
                             location: Location {
                                 uri: Url::parse(
-                                    &parser.reader.get_filename(f1.file)
+                                    &reader.get_filename(f1.file)
                                                   .unwrap_or_default(), // Empty string by default
                                 )
                                 .unwrap(),
-                                range: f1.range.to_range(),
+                                range: reader.to_lsp_range(f1.file, &f1.range),
                             },
                             message: f1.description,
                         })
@@ -100,6 +134,7 @@ pub struct LSPRVDiagnostic {
     pub diagnostics: Vec<Diagnostic>,
 }
 
+#[derive(Clone)]
 pub struct LSPRVSingleDiagnostic {
     pub uri: String,
     pub diagnostic: Diagnostic,
@@ -118,6 +153,9 @@ impl FileReader for LSPFileReader {
     fn get_filename(&self, uuid: uuid::Uuid) -> Option<String> {
         self.file_uris.get(&uuid).map(|x| x.uri.clone())
     }
+    fn tab_width(&self, uuid: uuid::Uuid) -> usize {
+        self.file_uris.get(&uuid).map_or(1, |x| x.tab_width)
+    }
 
     fn import_file(
         &mut self,
@@ -155,6 +193,56 @@ impl FileReader for LSPFileReader {
 }
 
 impl LSPFileReader {
+    /// Convert a position in a document to an LSP position, counting UTF-16
+    /// code units.
+    ///
+    /// If the document is not known, the column is used as is.
+    pub fn to_lsp_position(&self, file: Uuid, pos: &MyPosition) -> Position {
+        let character = match self.file_uris.get(&file) {
+            Some(doc) => {
+                let line = doc.text.split('\n').nth(pos.line).unwrap_or_default();
+                utf16_column(line, pos.column, doc.tab_width)
+            }
+            None => pos.column,
+        };
+        Position {
+            line: pos.line.try_into().unwrap_or(0),
+            character: character.try_into().unwrap_or(0),
+        }
+    }
+
+    /// Convert a range in a document to an LSP range, counting UTF-16 code
+    /// units.
+    pub fn to_lsp_range(&self, file: Uuid, range: &MyRange) -> Range {
+        Range {
+            start: self.to_lsp_position(file, &range.start),
+            end: self.to_lsp_position(file, &range.end),
+        }
+    }
+
+    /// Convert a range in a document to an LSP location.
+    ///
+    /// There is no location if the document does not have a valid URI.
+    pub fn to_lsp_location(&self, file: Uuid, range: &MyRange) -> Option<Location> {
+        Some(Location {
+            uri: Url::parse(&self.get_filename(file)?).ok()?,
+            range: self.to_lsp_range(file, range),
+        })
+    }
+
+    /// Get the token at an LSP position in a document.
+    ///
+    /// Whitespace is not part of any token, so there is no token there.
+    pub fn token_at(&self, uri: &str, position: Position) -> Option<Info> {
+        let (file, doc) = self.file_uris.iter().find(|(_, doc)| doc.uri == uri)?;
+        Lexer::new_with_tab_width(doc.text.clone(), *file, doc.tab_width)
+            .filter_map(Result::ok)
+            .find(|info| {
+                let range = self.to_lsp_range(info.file, &info.pos);
+                range.start <= position && position <= range.end
+            })
+    }
+
     pub fn new(docs: Vec<RVDocument>) -> Self {
         let mut map = HashMap::new();
 
@@ -165,4 +253,43 @@ impl LSPFileReader {
 
         LSPFileReader { file_uris: map }
     }
+
+    /// Replace the documents of the reader, keeping the UUID of every
+    /// document that is still open.
+    ///
+    /// A document whose tab width changed is given a new UUID, as the
+    /// positions in it are different. Returns the UUIDs of the documents
+    /// that were opened, changed or closed.
+    pub fn update(&mut self, docs: Vec<RVDocument>) -> HashSet<Uuid> {
+        let mut old = self
+            .file_uris
+            .drain()
+            .map(|(uuid, doc)| (doc.uri.clone(), (uuid, doc)))
+            .collect::<HashMap<_, _>>();
+        let mut changed = HashSet::new();
+        for doc in docs {
+            let uuid = match old.remove(&doc.uri) {
+                Some((uuid, old_doc)) if old_doc.tab_width == doc.tab_width => {
+                    if old_doc.text != doc.text {
+                        changed.insert(uuid);
+                    }
+                    uuid
+                }
+                Some((uuid, _)) => {
+                    changed.insert(uuid);
+                    let new_uuid = Uuid::new_v4();
+                    changed.insert(new_uuid);
+                    new_uuid
+                }
+                None => {
+                    let uuid = Uuid::new_v4();
+                    changed.insert(uuid);
+                    uuid
+                }
+            };
+            self.file_uris.insert(uuid, doc);
+        }
+        changed.extend(old.into_values().map(|(uuid, _)| uuid));
+        changed
+    }
 }
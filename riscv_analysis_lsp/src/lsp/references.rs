@@ -0,0 +1,146 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use lsp_types::{Location, Position};
+use riscv_analysis::cfg::{Cfg, CfgNode};
+use riscv_analysis::parser::Range as MyRange;
+use riscv_analysis::parser::{Info, Lexer, ParserNode, Register, Token};
+use uuid::Uuid;
+
+use super::definition::{is_parsed, label_definition};
+use super::LSPFileReader;
+
+/// Get the locations that reference the label or register at a position in
+/// a document.
+///
+/// A label is referenced by the branches, jumps, calls and loads of its
+/// address that name it, and by data that stores its address, like a jump
+/// table, in any file that was parsed with the CFG. The
+/// definition of the label is only included if `include_declaration` is
+/// set.
+///
+/// A register is referenced by the instructions that read or write it, in
+/// the functions that the instruction at the position is part of. Outside
+/// of any function, the references are the ones that are not part of a
+/// function either. Registers have no declaration.
+pub fn references_at(
+    cfg: &Cfg,
+    reader: &LSPFileReader,
+    uri: &str,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let Some(info) = reader.token_at(uri, position) else {
+        return Vec::new();
+    };
+    if !is_parsed(cfg, info.file) {
+        return Vec::new();
+    }
+
+    let ranges = match &info.token {
+        Token::Symbol(name) => match Register::from_str(name) {
+            Ok(reg) => register_references(cfg, reader, &info, reg),
+            Err(()) => label_references(cfg, name, include_declaration),
+        },
+        Token::Label(name) => label_references(cfg, name, include_declaration),
+        _ => Vec::new(),
+    };
+
+    let mut locations = ranges
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|(file, range)| reader.to_lsp_location(file, &range))
+        .collect::<Vec<_>>();
+    locations.sort_by_key(|location| (location.uri.to_string(), location.range.start));
+    locations
+}
+
+/// Get the ranges that name a label, in source order.
+fn label_references(cfg: &Cfg, name: &str, include_declaration: bool) -> Vec<(Uuid, MyRange)> {
+    let mut ranges = Vec::new();
+    if include_declaration {
+        ranges.extend(label_definition(cfg, name).map(|label| (label.file, label.pos)));
+    }
+    for node in cfg.iter_source() {
+        let node = node.node();
+        let uses = [node.calls_to(), node.jumps_to(), node.reads_address_of()];
+        ranges.extend(
+            uses.into_iter()
+                .flatten()
+                .filter(|label| label.data.0 == name)
+                .map(|label| (label.file, label.pos)),
+        );
+    }
+    for symbol in cfg.data_symbols() {
+        ranges.extend(
+            symbol
+                .addresses()
+                .into_iter()
+                .filter(|label| label.data.0 == name)
+                .map(|label| (label.file, label.pos.clone())),
+        );
+    }
+    ranges
+}
+
+/// Get the ranges that read or write a register, in the functions of the
+/// instruction that the token is part of.
+fn register_references(
+    cfg: &Cfg,
+    reader: &LSPFileReader,
+    info: &Info,
+    reg: Register,
+) -> Vec<(Uuid, MyRange)> {
+    let Some(node) = cfg.iter_source().find(|node| {
+        let token = node.node().token();
+        token.file == info.file
+            && token.pos.start <= info.pos.start
+            && info.pos.end <= token.pos.end
+    }) else {
+        return Vec::new();
+    };
+
+    let scope: Vec<Rc<CfgNode>> = if node.is_part_of_some_function() {
+        let functions = node.functions().iter().cloned().collect::<Vec<_>>();
+        cfg.iter_source()
+            .filter(|other| {
+                other
+                    .functions()
+                    .iter()
+                    .any(|f| functions.iter().any(|g| Rc::ptr_eq(f, g)))
+            })
+            .collect()
+    } else {
+        cfg.iter_source()
+            .filter(|other| !other.is_part_of_some_function())
+            .collect()
+    };
+
+    // Instructions that are expanded or implied, like the return address
+    // of a call, have registers that are not written in the source, so only
+    // the registers that are written as a token are kept
+    let written = written_registers(reader, reg);
+    scope
+        .iter()
+        .map(|node| node.node())
+        .filter(ParserNode::is_instruction)
+        .flat_map(|node| node.reads_from().into_iter().chain(node.stores_to()))
+        .filter(|used| used.data == reg)
+        .map(|used| (used.file, used.pos))
+        .filter(|range| written.contains(range))
+        .collect()
+}
+
+/// Get the ranges of every token that names a register, in every document.
+fn written_registers(reader: &LSPFileReader, reg: Register) -> BTreeSet<(Uuid, MyRange)> {
+    reader
+        .file_uris
+        .iter()
+        .flat_map(|(file, doc)| Lexer::new_with_tab_width(doc.text.clone(), *file, doc.tab_width))
+        .filter_map(Result::ok)
+        .filter(|info| matches!(&info.token, Token::Symbol(name) if Register::from_str(name) == Ok(reg)))
+        .map(|info| (info.file, info.pos))
+        .collect()
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+};
+use riscv_analysis::parser::{
+    CSRImm, FloatImm, Imm, Inst, Lexer, Position as MyPosition, Register, Token, Type,
+};
+
+use super::LSPFileReader;
+
+/// The kind of a token, which is its index in the legend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Instruction,
+    PseudoInstruction,
+    Register,
+    Immediate,
+    Label,
+    Directive,
+    String,
+    Comment,
+}
+
+/// The modifier of a label that is defined, rather than used.
+const DECLARATION: u32 = 1;
+
+/// Get the legend of the token types and modifiers in
+/// [`semantic_tokens`].
+///
+/// Pseudo-instructions are macros, so that they are highlighted apart from
+/// the instructions that they expand to.
+#[must_use]
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::KEYWORD,
+            SemanticTokenType::MACRO,
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::NUMBER,
+            SemanticTokenType::new("label"),
+            SemanticTokenType::DECORATOR,
+            SemanticTokenType::STRING,
+            SemanticTokenType::COMMENT,
+        ],
+        token_modifiers: vec![SemanticTokenModifier::DECLARATION],
+    }
+}
+
+/// Get the semantic tokens of a document.
+///
+/// Tokens are classified from the lexer alone, so tokens that the parser
+/// does not keep, like the body of a macro, are classified too. The first
+/// symbol of a line is an instruction, and the symbols after it are its
+/// operands.
+pub fn semantic_tokens(reader: &LSPFileReader, uri: &str) -> SemanticTokens {
+    let Some((&file, doc)) = reader.file_uris.iter().find(|(_, doc)| doc.uri == uri) else {
+        return SemanticTokens::default();
+    };
+
+    let mut data = Vec::new();
+    let mut at_line_start = true;
+    let mut last = lsp_types::Position::default();
+    for info in Lexer::new_with_tab_width(doc.text.clone(), file, doc.tab_width).flatten() {
+        let kind = match &info.token {
+            Token::Newline => {
+                at_line_start = true;
+                None
+            }
+            Token::Label(_) => Some(TokenKind::Label),
+            Token::Directive(_) => Some(TokenKind::Directive),
+            Token::String(_) | Token::Char(_) => Some(TokenKind::String),
+            Token::Comment(_) => Some(TokenKind::Comment),
+            Token::Symbol(name) if at_line_start => instruction_kind(name),
+            Token::Symbol(name) => Some(operand_kind(name)),
+            Token::LParen | Token::RParen => None,
+        };
+        if !matches!(
+            info.token,
+            Token::Newline | Token::Label(_) | Token::Comment(_)
+        ) {
+            at_line_start = false;
+        }
+
+        // Tokens do not span lines, apart from strings with errors
+        let Some(kind) = kind.filter(|_| info.pos.start.line == info.pos.end.line) else {
+            continue;
+        };
+        let start = reader.to_lsp_position(file, &info.pos.start);
+        let after = MyPosition {
+            column: info.pos.end.column + 1,
+            ..info.pos.end
+        };
+        let end = reader.to_lsp_position(file, &after);
+
+        data.push(SemanticToken {
+            delta_line: start.line - last.line,
+            delta_start: if start.line == last.line {
+                start.character - last.character
+            } else {
+                start.character
+            },
+            length: end.character - start.character,
+            token_type: kind as u32,
+            token_modifiers_bitset: if matches!(info.token, Token::Label(_)) {
+                DECLARATION
+            } else {
+                0
+            },
+        });
+        last = start;
+    }
+
+    SemanticTokens {
+        result_id: None,
+        data,
+    }
+}
+
+/// Get the kind of the mnemonic of an instruction, or nothing if it is not
+/// an instruction, like the name of a macro.
+fn instruction_kind(name: &str) -> Option<TokenKind> {
+    let inst = Inst::from_str(&name.to_lowercase()).ok()?;
+    match Type::from(&inst) {
+        Type::Pseudo(_) => Some(TokenKind::PseudoInstruction),
+        _ => Some(TokenKind::Instruction),
+    }
+}
+
+/// Get the kind of an operand of an instruction or directive.
+///
+/// Control and status registers are registers, and any other name is a
+/// label.
+fn operand_kind(name: &str) -> TokenKind {
+    if Register::from_str(name).is_ok() {
+        TokenKind::Register
+    } else if Imm::from_str(name).is_ok() || FloatImm::from_str(name).is_ok() {
+        TokenKind::Immediate
+    } else if CSRImm::from_str(name).is_ok() {
+        TokenKind::Register
+    } else {
+        TokenKind::Label
+    }
+}
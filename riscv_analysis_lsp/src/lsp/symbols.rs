@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use lsp_types::{DocumentSymbol, SymbolKind};
+use riscv_analysis::cfg::Cfg;
+use riscv_analysis::parser::{LabelString, Range as MyRange, With};
+use uuid::Uuid;
+
+use super::LSPFileReader;
+
+/// Get the outline of a document.
+///
+/// Functions are containers of the labels and data that are declared
+/// between their first label and their last instruction. Any other label
+/// or data is a symbol of its own. A function ends before the next one
+/// starts, so functions that overlap, like one that falls through into
+/// another, are still outlined one after the other.
+pub fn document_symbols(cfg: &Cfg, reader: &LSPFileReader, uri: &str) -> Vec<DocumentSymbol> {
+    let Some((&file, _)) = reader.file_uris.iter().find(|(_, doc)| doc.uri == uri) else {
+        return Vec::new();
+    };
+    let in_file = |label: &With<LabelString>| label.file == file;
+
+    let mut functions = cfg
+        .unique_functions()
+        .into_iter()
+        .filter_map(|func| {
+            let mut labels = func
+                .labels()
+                .into_iter()
+                .filter(in_file)
+                .collect::<Vec<_>>();
+            labels.sort_by(|a, b| a.pos.cmp(&b.pos));
+            let first = labels.first()?.pos.clone();
+            let names = labels.iter().map(|l| l.data.0.clone()).collect::<Vec<_>>();
+            Some((first, names, Rc::clone(&func)))
+        })
+        .collect::<Vec<_>>();
+    functions.sort_by(|a, b| a.0.cmp(&b.0));
+    let function_labels = functions
+        .iter()
+        .flat_map(|(_, names, _)| names.iter().cloned())
+        .collect::<HashSet<_>>();
+
+    let mut containers = Vec::new();
+    for (i, (start, names, func)) in functions.iter().enumerate() {
+        let next = functions.get(i + 1).map(|(next, _, _)| next);
+        let end = func
+            .nodes()
+            .iter()
+            .map(|node| node.node().token())
+            .filter(|token| {
+                token.file == file && next.is_none_or(|next| token.pos.end < next.start)
+            })
+            .map(|token| token.pos.end)
+            .max()
+            .map_or_else(|| start.end, |end| end.max(start.end));
+        let range = MyRange {
+            start: start.start,
+            end,
+        };
+        containers.push((
+            range,
+            symbol(reader, file, names.join(", "), SymbolKind::FUNCTION, start),
+        ));
+    }
+
+    // Labels of code that are not functions, and labels of data
+    let mut labels = cfg
+        .nodes()
+        .iter()
+        .flat_map(|node| node.labels())
+        .filter(in_file)
+        .filter(|label| !function_labels.contains(&label.data.0))
+        .filter(|label| cfg.data_symbol(&label.data).is_none())
+        .map(|label| (label, SymbolKind::CONSTANT))
+        .collect::<Vec<_>>();
+    labels.extend(
+        cfg.data_symbols()
+            .map(|data| data.label().clone())
+            .filter(in_file)
+            .map(|label| (label, SymbolKind::VARIABLE)),
+    );
+    labels.sort_by(|a, b| a.0.pos.cmp(&b.0.pos));
+    labels.dedup_by(|a, b| a.0 == b.0);
+
+    let mut symbols = Vec::new();
+    for (label, kind) in labels {
+        let child = symbol(reader, file, label.data.0, kind, &label.pos);
+        let container = containers
+            .iter_mut()
+            .find(|(range, _)| range.start <= label.pos.start && label.pos.end <= range.end);
+        match container {
+            Some((_, parent)) => parent.children.get_or_insert_with(Vec::new).push(child),
+            None => symbols.push(child),
+        }
+    }
+    symbols.extend(containers.into_iter().map(|(range, mut container)| {
+        container.range = reader.to_lsp_range(file, &range);
+        container
+    }));
+    symbols.sort_by_key(|symbol| symbol.range.start);
+    symbols
+}
+
+/// Create a symbol that spans only its name.
+#[allow(deprecated)]
+fn symbol(
+    reader: &LSPFileReader,
+    file: Uuid,
+    name: String,
+    kind: SymbolKind,
+    pos: &MyRange,
+) -> DocumentSymbol {
+    let range = reader.to_lsp_range(file, pos);
+    DocumentSymbol {
+        name,
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range: range,
+        children: None,
+    }
+}